@@ -0,0 +1,327 @@
+//! Déconfliction de l'espace aérien.
+//!
+//! Des plafonds de tir ([`AirspaceRestriction`]) protègent la circulation
+//! aérienne, sur toute la zone ou dans un couloir ([`Corridor`]). La
+//! trajectoire de chaque anneau de la solution est estimée à partir de la durée
+//! de trajet (trajectoire dans le vide : flèche `g·T²/8` au-dessus de la ligne
+//! pièce-but) ; un anneau dont la trajectoire dépasse un plafond est signalé
+//! dans [`AirspaceConflict`] pour coordination avec l'aviation.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::replay::GRAVITY_MPS2;
+use crate::{AmmoKind, BallisticTables, FiringSolution, MortarPosition, TargetPosition};
+
+/// Nombre de points de trajectoire contrôlés par anneau.
+pub const AIRSPACE_SAMPLES: u32 = 32;
+
+/// Couloir aérien rectiligne.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Corridor {
+    /// Point d'entrée `[x, y]` de l'axe du couloir (mètres)
+    pub from: [f64; 2],
+    /// Point de sortie `[x, y]` de l'axe du couloir (mètres)
+    pub to: [f64; 2],
+    /// Largeur totale du couloir (mètres)
+    pub width_m: f64,
+}
+
+impl Corridor {
+    /// Indique si le point `(x, y)` est dans le couloir.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::airspace::Corridor;
+    /// let corridor = Corridor { from: [0.0, 0.0], to: [1000.0, 0.0], width_m: 200.0 };
+    /// assert!(corridor.contains(500.0, 90.0));
+    /// assert!(!corridor.contains(500.0, 110.0));
+    /// assert!(!corridor.contains(1200.0, 0.0));
+    /// ```
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let (dx, dy) = (self.to[0] - self.from[0], self.to[1] - self.from[1]);
+        let len2 = dx * dx + dy * dy;
+        let t = if len2 > 0.0 {
+            (((x - self.from[0]) * dx + (y - self.from[1]) * dy) / len2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let (px, py) = (self.from[0] + t * dx, self.from[1] + t * dy);
+        (x - px).hypot(y - py) <= self.width_m / 2.0
+    }
+}
+
+/// Plafond de tir, sur toute la zone ou dans un couloir.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AirspaceRestriction {
+    /// Altitude maximale des trajectoires (mètres)
+    pub ceiling_m: f64,
+    /// Couloir concerné ; absent pour un plafond sur toute la zone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corridor: Option<Corridor>,
+}
+
+impl AirspaceRestriction {
+    /// Indique si le plafond s'applique au point `(x, y)`.
+    pub fn applies_at(&self, x: f64, y: f64) -> bool {
+        self.corridor.is_none_or(|c| c.contains(x, y))
+    }
+}
+
+/// Restrictions d'espace aérien de l'opération, par nom.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Airspace(pub BTreeMap<String, AirspaceRestriction>);
+
+impl Airspace {
+    /// Indique si aucune restriction n'est définie.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Dépassement d'un plafond par la trajectoire d'un anneau.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AirspaceViolation {
+    /// Nom de la restriction
+    pub restriction: String,
+    /// Anneau (ex: "2R")
+    pub ring: String,
+    /// Plafond de la restriction (mètres)
+    pub ceiling_m: f64,
+    /// Altitude la plus haute de la trajectoire sous la restriction (mètres)
+    pub altitude_m: f64,
+}
+
+/// Conflits d'espace aérien d'une solution.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AirspaceConflict {
+    /// Dépassements, par anneau puis restriction
+    pub violations: Vec<AirspaceViolation>,
+    /// Anneaux non contrôlés faute de durée de trajet
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unchecked_rings: Vec<String>,
+}
+
+/// Flèche (mètres) d'une trajectoire de durée `time_of_flight_s` au-dessus de
+/// la ligne pièce-but, dans le vide.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::airspace::max_ordinate_m;
+/// assert!((max_ordinate_m(20.0) - 490.5).abs() < 1e-9);
+/// ```
+pub fn max_ordinate_m(time_of_flight_s: f64) -> f64 {
+    GRAVITY_MPS2 * time_of_flight_s * time_of_flight_s / 8.0
+}
+
+/// Durée de trajet (s) dans le vide d'un tir à `elev_mil` atteignant `range_m`,
+/// pour estimer la trajectoire d'une table sans durées de trajet.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::airspace::vacuum_time_of_flight_s;
+/// // 800 mils = 45° : T² = 2·R/g
+/// assert!((vacuum_time_of_flight_s(981.0, 800.0) - 200f64.sqrt()).abs() < 1e-9);
+/// ```
+pub fn vacuum_time_of_flight_s(range_m: f64, elev_mil: f64) -> f64 {
+    let elevation = crate::mils::mil_to_rad(elev_mil);
+    (2.0 * range_m * elevation.tan() / GRAVITY_MPS2)
+        .max(0.0)
+        .sqrt()
+}
+
+/// Hauteur maximale (mètres) de la trajectoire au-dessus de la pièce, pour un
+/// but situé `rise_m` mètres plus haut (négatif s'il est plus bas).
+///
+/// La trajectoire dans le vide culmine à `g·T²/8` au-dessus de la ligne
+/// pièce-but sur terrain plat ; le dénivelé déplace le sommet vers le plus
+/// haut des deux points.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::airspace::{apex_height_m, max_ordinate_m};
+/// assert!((apex_height_m(20.0, 0.0) - max_ordinate_m(20.0)).abs() < 1e-9);
+/// assert!(apex_height_m(20.0, 100.0) > apex_height_m(20.0, 0.0) + 50.0);
+/// assert!(apex_height_m(20.0, -100.0) < apex_height_m(20.0, 0.0));
+/// ```
+pub fn apex_height_m(time_of_flight_s: f64, rise_m: f64) -> f64 {
+    let arc = GRAVITY_MPS2 * time_of_flight_s * time_of_flight_s / 2.0;
+    if arc <= 0.0 {
+        return rise_m.max(0.0);
+    }
+    let tau = (0.5 + rise_m / (2.0 * arc)).clamp(0.0, 1.0);
+    rise_m * tau + arc * tau * (1.0 - tau)
+}
+
+/// Altitude la plus haute de la trajectoire là où `restriction` s'applique.
+fn highest_point(
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    time_of_flight_s: f64,
+    restriction: &AirspaceRestriction,
+) -> Option<f64> {
+    (0..=AIRSPACE_SAMPLES)
+        .filter_map(|k| {
+            let tau = f64::from(k) / f64::from(AIRSPACE_SAMPLES);
+            let x = mortar.x + (target.x - mortar.x) * tau;
+            let y = mortar.y + (target.y - mortar.y) * tau;
+            restriction.applies_at(x, y).then(|| {
+                let ground = mortar.elevation + (target.elevation - mortar.elevation) * tau;
+                let t = tau * time_of_flight_s;
+                ground + 0.5 * GRAVITY_MPS2 * t * (time_of_flight_s - t)
+            })
+        })
+        .max_by(|a, b| a.total_cmp(b))
+}
+
+/// Contrôle les anneaux de la solution sélectionnée contre les restrictions
+/// et ajoute un avertissement par dépassement.
+///
+/// Sans restriction ni solution sélectionnée, la solution est inchangée.
+pub fn attach_airspace_check(
+    solution: &mut FiringSolution,
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    ballistics: &BallisticTables,
+    airspace: &Airspace,
+) {
+    if airspace.is_empty() {
+        return;
+    }
+    let (ammo, elevations) = match &solution.selected_solution {
+        Some(sel) => match AmmoKind::from_name(&sel.ammo_type) {
+            Some(ammo) => (ammo, sel.elevations),
+            None => return,
+        },
+        None => return,
+    };
+
+    let mut conflict = AirspaceConflict::default();
+    for (ring, _) in elevations.iter().filter(|(_, e)| e.is_some()) {
+        let tof = ballistics
+            .get(&(solution.weapon, ammo, ring))
+            .and_then(|t| t.time_of_flight_at(solution.distance_m));
+        let tof = match tof {
+            Some(tof) => tof,
+            None => {
+                conflict.unchecked_rings.push(format!("{}R", ring));
+                continue;
+            }
+        };
+        for (name, restriction) in &airspace.0 {
+            match highest_point(mortar, target, tof, restriction) {
+                Some(altitude_m) if altitude_m > restriction.ceiling_m => {
+                    conflict.violations.push(AirspaceViolation {
+                        restriction: name.clone(),
+                        ring: format!("{}R", ring),
+                        ceiling_m: restriction.ceiling_m,
+                        altitude_m,
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for v in &conflict.violations {
+        solution.warnings.push(format!(
+            "Airspace conflict: {} trajectory reaches {:.0} m, above the {} ceiling ({:.0} m)",
+            v.ring, v.altitude_m, v.restriction, v.ceiling_m
+        ));
+    }
+    if !conflict.violations.is_empty() || !conflict.unchecked_rings.is_empty() {
+        solution.airspace_conflict = Some(conflict);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calculate_solution_with_dispersion, BallisticPoint, BallisticTable, DispersionTable,
+        TargetType, WeaponSystem,
+    };
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn rings_above_a_ceiling_are_reported() {
+        let table = |tof: f64| BallisticTable {
+            points: vec![
+                BallisticPoint {
+                    range_m: 0.0,
+                    elev_mil: 1400.0,
+                    time_flight_s: Some(tof),
+                    delta_elev_per_100m_mil: None,
+                },
+                BallisticPoint {
+                    range_m: 2000.0,
+                    elev_mil: 800.0,
+                    time_flight_s: Some(tof),
+                    delta_elev_per_100m_mil: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let ballistics = BTreeMap::from([
+            ((WeaponSystem::M224, AmmoKind::He, 1), table(10.0)),
+            ((WeaponSystem::M224, AmmoKind::He, 2), table(20.0)),
+        ]);
+        let mortar = MortarPosition::new("M1".into(), 100.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            100.0,
+            1000.0,
+            0.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let mut solution = calculate_solution_with_dispersion(
+            &mortar,
+            &target,
+            &ballistics,
+            &DispersionTable::new(),
+        );
+
+        // Couloir traversant la ligne de tir à mi-parcours, plafond à 300 m
+        let airspace = Airspace(BTreeMap::from([(
+            "HELICO".to_string(),
+            AirspaceRestriction {
+                ceiling_m: 300.0,
+                corridor: Some(Corridor {
+                    from: [500.0, -1000.0],
+                    to: [500.0, 1000.0],
+                    width_m: 200.0,
+                }),
+            },
+        )]));
+        attach_airspace_check(&mut solution, &mortar, &target, &ballistics, &airspace);
+
+        // 1R : flèche 122,6 m (altitude 222,6 m) ; 2R : flèche 490,5 m
+        let conflict = solution.airspace_conflict.as_ref().unwrap();
+        assert_eq!(conflict.violations.len(), 1);
+        assert_eq!(conflict.violations[0].ring, "2R");
+        assert!((conflict.violations[0].altitude_m - 590.5).abs() < 1e-9);
+        assert!(solution.warnings[0].contains("HELICO"));
+
+        // Sans restriction, la solution est inchangée
+        let mut solution = calculate_solution_with_dispersion(
+            &mortar,
+            &target,
+            &ballistics,
+            &DispersionTable::new(),
+        );
+        attach_airspace_check(
+            &mut solution,
+            &mortar,
+            &target,
+            &ballistics,
+            &Airspace::default(),
+        );
+        assert!(solution.airspace_conflict.is_none());
+    }
+}
@@ -0,0 +1,548 @@
+//! Registre des munitions.
+//!
+//! Les quatre munitions livrées (PRACTICE, HE, SMOKE, FLARE) sont complétées
+//! par celles déclarées dans le manifeste `ammo.json` du répertoire de
+//! données : désignation, répertoire et préfixe des tables, anneaux
+//! disponibles, calibres et cibles suggérées. Une munition modifiée ou d'une
+//! autre armée s'ajoute ainsi sans modifier le code.
+//!
+//! ```json
+//! {
+//!     "version": 1,
+//!     "ammo": [
+//!         {
+//!             "name": "M720_HE",
+//!             "designation": "M720",
+//!             "rings": [0, 1, 2, 3, 4],
+//!             "aliases": ["HE_LONG"],
+//!             "suggested_targets": ["INF", "VEH"]
+//!         }
+//!     ]
+//! }
+//! ```
+//!
+//! Une munition déclarée est un [`AmmoKind::Custom`] portant son nom
+//! canonique. Sa description (tables, calibres...) est celle du registre du
+//! répertoire de données chargé : une munition retirée du manifeste n'est
+//! plus reconnue au rechargement suivant.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::archive::{is_data_archive, DataArchive};
+use crate::{weapon_path, AmmoKind, Caliber, Ring, TargetType, WeaponSystem, RING_COUNT};
+
+/// Nom du manifeste des munitions dans le répertoire de données.
+pub const AMMO_MANIFEST: &str = "ammo.json";
+
+/// Version du manifeste `ammo.json` supportée.
+pub const AMMO_MANIFEST_VERSION: u32 = 1;
+
+/// Longueur maximale du nom canonique d'une munition déclarée (octets).
+pub const MAX_AMMO_NAME_LEN: usize = 32;
+
+/// Nom canonique d'une munition déclarée.
+///
+/// Le nom est conservé dans la valeur même (au plus [`MAX_AMMO_NAME_LEN`]
+/// caractères `A-Z`, `0-9` ou `_`), si bien que [`AmmoKind`] reste `Copy`
+/// et se compare sans consulter de registre.
+///
+/// ```
+/// use mortar::ammo::AmmoName;
+///
+/// assert_eq!(AmmoName::new("M720_HE").unwrap().as_str(), "M720_HE");
+/// assert!(AmmoName::new("m720").is_none());
+/// assert!(AmmoName::new("").is_none());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AmmoName {
+    len: u8,
+    bytes: [u8; MAX_AMMO_NAME_LEN],
+}
+
+impl AmmoName {
+    /// Nom déjà normalisé (voir [`normalize`]) ; `None` s'il est vide, trop
+    /// long ou contient un autre caractère que `A-Z`, `0-9` et `_`.
+    pub fn new(name: &str) -> Option<Self> {
+        let valid = |b: u8| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_';
+        if name.is_empty() || name.len() > MAX_AMMO_NAME_LEN || !name.bytes().all(valid) {
+            return None;
+        }
+        let mut bytes = [0; MAX_AMMO_NAME_LEN];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        Some(AmmoName {
+            len: name.len() as u8,
+            bytes,
+        })
+    }
+
+    /// Nom canonique.
+    pub fn as_str(&self) -> &str {
+        // Seuls des octets ASCII sont acceptés par `new`
+        std::str::from_utf8(&self.bytes[..usize::from(self.len)]).unwrap_or_default()
+    }
+}
+
+impl PartialOrd for AmmoName {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AmmoName {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl std::fmt::Debug for AmmoName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+/// Description d'une munition du registre.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AmmoInfo {
+    /// Munition
+    pub kind: AmmoKind,
+    /// Nom canonique (`HE`, `M720_HE`...)
+    pub name: String,
+    /// Désignation de l'obus
+    pub designation: String,
+    /// Noms alternatifs acceptés
+    pub aliases: Vec<String>,
+    /// Répertoire des tables, relatif au répertoire de données
+    pub directory: String,
+    /// Préfixe des fichiers de tables (`<préfixe>_<n>R.csv`)
+    pub file_prefix: String,
+    /// Anneaux dont une table est attendue
+    pub rings: Vec<Ring>,
+    /// Calibres pouvant tirer la munition
+    pub calibers: Vec<Caliber>,
+    /// Types de cibles pour lesquels la munition est indiquée
+    pub suggested_targets: Vec<TargetType>,
+}
+
+impl AmmoInfo {
+    /// Chemin de la table d'un anneau, relatif au répertoire de données.
+    pub fn file_name(&self, ring: Ring) -> String {
+        format!("{}/{}_{}R.csv", self.directory, self.file_prefix, ring)
+    }
+
+    /// Indique si `key` (déjà normalisée) désigne cette munition.
+    fn matches(&self, key: &str) -> bool {
+        self.name == key || self.designation == key || self.aliases.iter().any(|a| a == key)
+    }
+}
+
+/// Munitions livrées : munition, répertoire, préfixe des fichiers et anneaux.
+const BUILTIN_AMMO: &[(AmmoKind, &str, &str, std::ops::RangeInclusive<Ring>)] = &[
+    (AmmoKind::Practice, "PRACTICE", "M879_PRACTICE", 0..=4),
+    (AmmoKind::He, "HE", "M821_HE", 0..=4),
+    // SMOKE et FLARE : pas de 0R
+    (AmmoKind::Smoke, "SMOKE", "M819_SMOKE", 1..=4),
+    (AmmoKind::Flare, "FLARE", "M853A1_FLARE", 1..=4),
+];
+
+/// Munitions livrées, dans l'ordre de chargement des tables.
+pub fn builtin_ammo() -> Vec<AmmoInfo> {
+    BUILTIN_AMMO
+        .iter()
+        .map(|(kind, directory, file_prefix, rings)| AmmoInfo {
+            kind: *kind,
+            name: kind.as_str().to_string(),
+            designation: kind.designation().to_string(),
+            aliases: kind.aliases().iter().map(|a| a.to_string()).collect(),
+            directory: directory.to_string(),
+            file_prefix: file_prefix.to_string(),
+            rings: rings.clone().collect(),
+            calibers: vec![Caliber::Mm60],
+            suggested_targets: TargetType::all()
+                .iter()
+                .copied()
+                .filter(|t| t.suggested_ammo() == *kind)
+                .collect(),
+        })
+        .collect()
+}
+
+/// Forme normalisée d'un nom de munition : majuscules, espaces et tirets
+/// remplacés par `_`.
+pub fn normalize(s: &str) -> String {
+    s.trim().to_uppercase().replace([' ', '-'], "_")
+}
+
+/// Entrée du manifeste `ammo.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AmmoSpec {
+    /// Nom canonique
+    pub name: String,
+    /// Désignation de l'obus (défaut : le nom)
+    #[serde(default)]
+    pub designation: Option<String>,
+    /// Répertoire des tables (défaut : le nom)
+    #[serde(default)]
+    pub directory: Option<String>,
+    /// Préfixe des fichiers de tables (défaut : le nom)
+    #[serde(default)]
+    pub file_prefix: Option<String>,
+    /// Anneaux dont une table est attendue
+    pub rings: Vec<Ring>,
+    /// Calibres (défaut : 60mm)
+    #[serde(default)]
+    pub calibers: Vec<String>,
+    /// Noms alternatifs
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Types de cibles suggérés (INF, VEH, SOU...)
+    #[serde(default)]
+    pub suggested_targets: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AmmoManifest {
+    #[serde(default = "default_manifest_version")]
+    version: u32,
+    #[serde(default)]
+    ammo: Vec<AmmoSpec>,
+}
+
+fn default_manifest_version() -> u32 {
+    AMMO_MANIFEST_VERSION
+}
+
+/// Munitions d'un répertoire de données : les munitions livrées puis celles
+/// de son manifeste `ammo.json`.
+///
+/// Le registre est chargé avec les tables et conservé avec elles (voir
+/// [`crate::Metrics::ammo`]) ; les fonctions qui ont besoin de la description
+/// d'une munition déclarée le reçoivent en argument.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AmmoRegistry {
+    /// Munitions, dans l'ordre de chargement des tables
+    pub ammo: Vec<AmmoInfo>,
+}
+
+impl Default for AmmoRegistry {
+    fn default() -> Self {
+        AmmoRegistry {
+            ammo: builtin_ammo(),
+        }
+    }
+}
+
+impl AmmoRegistry {
+    /// Charge le manifeste `ammo.json` d'un répertoire ou d'un paquet `.zip`.
+    ///
+    /// Sans manifeste, le registre ne contient que les munitions livrées.
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si le manifeste est illisible ou invalide (voir
+    /// [`AmmoRegistry::parse`]).
+    pub fn load<P: AsRef<Path>>(base: P) -> Result<Self> {
+        let base = base.as_ref();
+        match read_manifest(base)? {
+            Some(bytes) => Self::parse(bytes.as_slice())
+                .with_context(|| format!("invalid {} in {}", AMMO_MANIFEST, base.display())),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Charge le registre d'un répertoire de données complété par des
+    /// répertoires de surcharge : une munition déclarée par une surcharge
+    /// remplace celle de même nom des couches inférieures.
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si un manifeste est illisible ou invalide.
+    pub fn load_layered<P: AsRef<Path>>(base: P, overlays: &[PathBuf]) -> Result<Self> {
+        let mut registry = Self::load(base)?;
+        for overlay in overlays {
+            for info in Self::load(overlay)?.ammo {
+                match registry.ammo.iter_mut().find(|a| a.kind == info.kind) {
+                    Some(existing) => *existing = info,
+                    None => registry.ammo.push(info),
+                }
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Analyse un manifeste `ammo.json`.
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si la version est supérieure à
+    /// [`AMMO_MANIFEST_VERSION`], si un nom ou un alias est vide, déjà pris
+    /// par une munition livrée ou une autre entrée, si un nom dépasse
+    /// [`MAX_AMMO_NAME_LEN`] caractères, si un anneau est hors de
+    /// `0..RING_COUNT` ou si un calibre ou un type de cible est inconnu.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::ammo::AmmoRegistry;
+    /// use mortar::AmmoKind;
+    ///
+    /// let json = r#"{ "ammo": [{ "name": "M720_HE", "designation": "M720", "rings": [1, 2] }] }"#;
+    /// let registry = AmmoRegistry::parse(json.as_bytes()).unwrap();
+    /// let m720 = registry.parse_kind("m720").unwrap();
+    /// assert_eq!(m720.as_str(), "M720_HE");
+    /// assert_eq!(registry.get(m720).unwrap().file_name(2), "M720_HE/M720_HE_2R.csv");
+    /// // Inconnue hors de ce registre
+    /// assert_eq!(AmmoKind::parse_str("m720"), None);
+    ///
+    /// let clash = r#"{ "ammo": [{ "name": "HE", "rings": [1] }] }"#;
+    /// assert!(AmmoRegistry::parse(clash.as_bytes()).is_err());
+    /// ```
+    pub fn parse<R: std::io::Read>(reader: R) -> Result<Self> {
+        let manifest: AmmoManifest = serde_json::from_reader(reader)?;
+        if manifest.version > AMMO_MANIFEST_VERSION {
+            bail!(
+                "unsupported version {} (expected at most {})",
+                manifest.version,
+                AMMO_MANIFEST_VERSION
+            );
+        }
+
+        let mut registry = Self::default();
+        let mut taken: Vec<String> = Vec::new();
+        for spec in &manifest.ammo {
+            let name = normalize(&spec.name);
+            let designation = spec.designation.as_deref().map_or(name.clone(), normalize);
+            let aliases: Vec<String> = spec.aliases.iter().map(|a| normalize(a)).collect();
+            for key in std::iter::once(&name).chain(&aliases) {
+                if key.is_empty() {
+                    bail!("{}: empty name or alias", spec.name);
+                }
+                if AmmoKind::parse_str(key).is_some() {
+                    bail!("{}: '{}' is a built-in ammo name", spec.name, key);
+                }
+                if taken.contains(key) {
+                    bail!("{}: '{}' is declared twice", spec.name, key);
+                }
+                taken.push(key.clone());
+            }
+            let kind = match AmmoName::new(&name) {
+                Some(n) => AmmoKind::Custom(n),
+                None => bail!(
+                    "{}: name must be at most {} letters, digits or '_'",
+                    spec.name,
+                    MAX_AMMO_NAME_LEN
+                ),
+            };
+            if spec.rings.is_empty() {
+                bail!("{}: no ring", spec.name);
+            }
+            if let Some(r) = spec.rings.iter().find(|&&r| usize::from(r) >= RING_COUNT) {
+                bail!(
+                    "{}: ring {}R out of range (0-{}R)",
+                    spec.name,
+                    r,
+                    RING_COUNT - 1
+                );
+            }
+            let mut calibers = spec
+                .calibers
+                .iter()
+                .map(|c| {
+                    Caliber::parse_str(c)
+                        .ok_or_else(|| anyhow!("{}: unknown caliber '{}'", spec.name, c))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if calibers.is_empty() {
+                calibers.push(Caliber::Mm60);
+            }
+            let suggested_targets = spec
+                .suggested_targets
+                .iter()
+                .map(|t| {
+                    TargetType::parse_str(t)
+                        .ok_or_else(|| anyhow!("{}: unknown target type '{}'", spec.name, t))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mut rings = spec.rings.clone();
+            rings.sort_unstable();
+            rings.dedup();
+            registry.ammo.push(AmmoInfo {
+                kind,
+                directory: spec.directory.clone().unwrap_or_else(|| name.clone()),
+                file_prefix: spec.file_prefix.clone().unwrap_or_else(|| name.clone()),
+                name,
+                designation,
+                aliases,
+                rings,
+                calibers,
+                suggested_targets,
+            });
+        }
+        Ok(registry)
+    }
+
+    /// Description d'une munition du registre.
+    pub fn get(&self, kind: AmmoKind) -> Option<&AmmoInfo> {
+        self.ammo.iter().find(|a| a.kind == kind)
+    }
+
+    /// Munitions du registre, livrées puis déclarées.
+    pub fn kinds(&self) -> impl Iterator<Item = AmmoKind> + '_ {
+        self.ammo.iter().map(|a| a.kind)
+    }
+
+    /// Munition du registre correspondant à une saisie : noms acceptés par
+    /// [`AmmoKind::parse_str`], puis nom, désignation ou alias d'une munition
+    /// déclarée (insensible à la casse, espaces et tirets traités comme `_`).
+    pub fn parse_kind(&self, s: &str) -> Option<AmmoKind> {
+        if let Some(kind) = AmmoKind::parse_str(s) {
+            return Some(kind);
+        }
+        let key = normalize(s);
+        self.ammo.iter().find(|a| a.matches(&key)).map(|a| a.kind)
+    }
+
+    /// Calibres pouvant tirer une munition ; aucun si elle est inconnue du
+    /// registre.
+    pub fn calibers(&self, kind: AmmoKind) -> &[Caliber] {
+        self.get(kind).map_or(&[], |a| a.calibers.as_slice())
+    }
+
+    /// Chemin de la table d'un système d'arme, d'une munition et d'un anneau
+    /// sous son nom fixe, relatif au répertoire de données.
+    ///
+    /// Une munition inconnue du registre est rangée sous son nom (voir
+    /// [`crate::ballistic_file_name`]).
+    pub fn file_name(&self, weapon: WeaponSystem, kind: AmmoKind, ring: Ring) -> String {
+        match self.get(kind) {
+            Some(info) => weapon_path(weapon, &info.file_name(ring)),
+            None => crate::ballistic_file_name(weapon, kind, ring),
+        }
+    }
+
+    /// Manifeste `ammo.json` décrivant les munitions déclarées parmi `kinds`,
+    /// ou `None` s'il n'y en a aucune (les munitions livrées sont ignorées).
+    pub fn manifest_json(&self, kinds: impl IntoIterator<Item = AmmoKind>) -> Option<Vec<u8>> {
+        let mut ammo: Vec<AmmoSpec> = Vec::new();
+        for kind in kinds {
+            let info = match (kind, self.get(kind)) {
+                (AmmoKind::Custom(_), Some(info)) => info,
+                _ => continue,
+            };
+            if ammo.iter().any(|a| a.name == info.name) {
+                continue;
+            }
+            ammo.push(AmmoSpec {
+                name: info.name.clone(),
+                designation: Some(info.designation.clone()),
+                directory: Some(info.directory.clone()),
+                file_prefix: Some(info.file_prefix.clone()),
+                rings: info.rings.clone(),
+                calibers: info
+                    .calibers
+                    .iter()
+                    .map(|c| c.as_str().to_string())
+                    .collect(),
+                aliases: info.aliases.clone(),
+                suggested_targets: info
+                    .suggested_targets
+                    .iter()
+                    .map(|t| t.as_str().to_string())
+                    .collect(),
+            });
+        }
+        if ammo.is_empty() {
+            return None;
+        }
+        let manifest = AmmoManifest {
+            version: AMMO_MANIFEST_VERSION,
+            ammo,
+        };
+        serde_json::to_vec_pretty(&manifest).ok()
+    }
+}
+
+/// Contenu du manifeste d'un répertoire ou d'un paquet, s'il existe.
+fn read_manifest(base: &Path) -> Result<Option<Vec<u8>>> {
+    if is_data_archive(base) {
+        return Ok(DataArchive::open(base)?
+            .file(AMMO_MANIFEST)
+            .map(<[u8]>::to_vec));
+    }
+    match std::fs::read(base.join(AMMO_MANIFEST)) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => {
+            Err(e).with_context(|| format!("cannot open {}", base.join(AMMO_MANIFEST).display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declared_ammo_is_described_by_its_registry() {
+        let json = r#"{
+            "version": 1,
+            "ammo": [{
+                "name": "x900 he",
+                "designation": "X900",
+                "directory": "X900",
+                "rings": [3, 1, 1],
+                "calibers": ["81mm"],
+                "suggested_targets": ["VEH"]
+            }]
+        }"#;
+        let registry = AmmoRegistry::parse(json.as_bytes()).unwrap();
+        let info = registry.ammo.last().unwrap().clone();
+        assert_eq!(registry.ammo.len(), builtin_ammo().len() + 1);
+        assert_eq!(info.name, "X900_HE");
+        assert_eq!(info.rings, vec![1, 3]);
+        assert_eq!(info.file_name(1), "X900/X900_HE_1R.csv");
+        assert_eq!(registry.calibers(info.kind), &[Caliber::Mm81]);
+        assert_eq!(info.suggested_targets, vec![TargetType::Vehicule]);
+        assert_eq!(registry.parse_kind("x900"), Some(info.kind));
+        assert_eq!(registry.parse_kind("he"), Some(AmmoKind::He));
+        assert!(registry.kinds().any(|k| k == info.kind));
+
+        // Registre d'un autre répertoire : la munition n'y est plus connue
+        let other = AmmoRegistry::default();
+        assert_eq!(other.parse_kind("x900"), None);
+        assert!(other.calibers(info.kind).is_empty());
+
+        // Sérialisée par son nom, relue sans registre
+        assert_eq!(serde_json::to_string(&info.kind).unwrap(), "\"X900_HE\"");
+        let parsed: AmmoKind = serde_json::from_str("\"x900_he\"").unwrap();
+        assert_eq!(parsed, info.kind);
+
+        // Manifeste des paquets hors ligne : relu à l'identique
+        assert_eq!(registry.manifest_json([AmmoKind::He]), None);
+        let json = registry
+            .manifest_json([AmmoKind::He, info.kind, info.kind])
+            .unwrap();
+        let reread = AmmoRegistry::parse(json.as_slice()).unwrap();
+        assert_eq!(reread.ammo.last(), Some(&info));
+    }
+
+    #[test]
+    fn invalid_manifests_are_rejected() {
+        for json in [
+            r#"{ "version": 2 }"#,
+            r#"{ "ammo": [{ "name": "TEST_BAD_1", "rings": [] }] }"#,
+            r#"{ "ammo": [{ "name": "TEST_BAD_2", "rings": [5] }] }"#,
+            r#"{ "ammo": [{ "name": "TEST_BAD_3", "rings": [1], "aliases": ["M821"] }] }"#,
+            r#"{ "ammo": [{ "name": "TEST_BAD_4", "rings": [1], "calibers": ["50mm"] }] }"#,
+            r#"{ "ammo": [{ "name": "TEST_BAD_5", "rings": [1] }, { "name": "TEST_BAD_5", "rings": [1] }] }"#,
+            r#"{ "ammo": [{ "name": "TEST_BAD_6", "rings": [1], "color": "red" }] }"#,
+            r#"{ "ammo": [{ "name": "TEST_BAD_7_WITH_A_NAME_TOO_LONG_TO_FIT", "rings": [1] }] }"#,
+            r#"{ "ammo": [{ "name": "TEST/BAD", "rings": [1] }] }"#,
+        ] {
+            assert!(AmmoRegistry::parse(json.as_bytes()).is_err(), "{json}");
+        }
+    }
+}
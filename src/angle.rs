@@ -0,0 +1,215 @@
+//! Angles saisis avec leur unité.
+//!
+//! Les gisements et les angles arrivent sous des formes variées : degrés
+//! (`67.5deg`, `67.5°`, `67.5`), millièmes OTAN (`1200mil`) ou millièmes d'un
+//! autre système, précisé entre parenthèses par le nombre de millièmes du tour
+//! (`1200mils(6000)` pour le système à 6000 millièmes). [`Angle`] les ramène
+//! tous à une même valeur et les convertit d'une convention à l'autre ; le CLI
+//! et l'API le partagent pour lire les angles de la même façon.
+
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::MILS_PER_CIRCLE;
+
+/// Millièmes dans un tour, système OTAN.
+pub const NATO_MILS_PER_CIRCLE: f64 = MILS_PER_CIRCLE;
+
+/// Millièmes dans un tour, système du Pacte de Varsovie.
+pub const WARSAW_PACT_MILS_PER_CIRCLE: f64 = 6000.0;
+
+/// Angle, conservé en degrés.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::Angle;
+///
+/// let a: Angle = "1200mils(6000)".parse().unwrap();
+/// assert_eq!(a.degrees(), 72.0);
+/// assert!((a.mils() - 1280.0).abs() < 1e-9);
+/// assert_eq!("67.5deg".parse::<Angle>().unwrap().degrees(), 67.5);
+/// assert_eq!("1600mil".parse::<Angle>().unwrap(), Angle::from_degrees(90.0));
+/// assert!("45grad".parse::<Angle>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Angle {
+    degrees: f64,
+}
+
+impl Angle {
+    /// Angle en degrés.
+    pub fn from_degrees(degrees: f64) -> Self {
+        Angle { degrees }
+    }
+
+    /// Angle en millièmes OTAN (6400 au tour).
+    pub fn from_mils(mils: f64) -> Self {
+        Self::from_mils_in(mils, NATO_MILS_PER_CIRCLE)
+    }
+
+    /// Angle en millièmes d'un système de `mils_per_circle` millièmes au tour.
+    pub fn from_mils_in(mils: f64, mils_per_circle: f64) -> Self {
+        Angle {
+            degrees: mils * 360.0 / mils_per_circle,
+        }
+    }
+
+    /// Angle en radians.
+    pub fn from_radians(radians: f64) -> Self {
+        Angle {
+            degrees: radians.to_degrees(),
+        }
+    }
+
+    /// Valeur en degrés.
+    pub fn degrees(self) -> f64 {
+        self.degrees
+    }
+
+    /// Valeur en millièmes OTAN.
+    pub fn mils(self) -> f64 {
+        self.mils_in(NATO_MILS_PER_CIRCLE)
+    }
+
+    /// Valeur en millièmes d'un système de `mils_per_circle` millièmes au tour.
+    pub fn mils_in(self, mils_per_circle: f64) -> f64 {
+        self.degrees * mils_per_circle / 360.0
+    }
+
+    /// Valeur en radians.
+    pub fn radians(self) -> f64 {
+        self.degrees.to_radians()
+    }
+
+    /// Même direction ramenée à `[0, 360[` degrés.
+    pub fn normalized(self) -> Self {
+        Angle {
+            degrees: self.degrees.rem_euclid(360.0),
+        }
+    }
+}
+
+impl FromStr for Angle {
+    type Err = String;
+
+    /// Lit un angle avec son unité (insensible à la casse).
+    ///
+    /// Suffixes acceptés : `mil` ou `mils` pour les millièmes OTAN, suivis
+    /// éventuellement du nombre de millièmes du tour entre parenthèses
+    /// (`mils(6000)`) ; `deg`, `d` ou `°` pour les degrés. Sans suffixe, la
+    /// valeur est en degrés.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid angle '{}' (expected e.g. 45deg, 800mil or 750mils(6000))",
+                s.trim()
+            )
+        };
+        let text = s.trim().to_lowercase();
+        let (text, circle) = match text.strip_suffix(')') {
+            Some(rest) => {
+                let (rest, circle) = rest.rsplit_once('(').ok_or_else(invalid)?;
+                let circle: f64 = circle.trim().parse().map_err(|_| invalid())?;
+                if !(circle.is_finite() && circle > 0.0) {
+                    return Err(invalid());
+                }
+                (rest.trim_end().to_string(), Some(circle))
+            }
+            None => (text, None),
+        };
+        let (value, mils) = match text
+            .strip_suffix("mils")
+            .or_else(|| text.strip_suffix("mil"))
+        {
+            Some(v) => (v, true),
+            None if circle.is_some() => return Err(invalid()),
+            None => (
+                text.strip_suffix("deg")
+                    .or_else(|| text.strip_suffix('°'))
+                    .or_else(|| text.strip_suffix('d'))
+                    .unwrap_or(&text),
+                false,
+            ),
+        };
+        let value: f64 = value.trim().parse().map_err(|_| invalid())?;
+        if !value.is_finite() {
+            return Err(invalid());
+        }
+        Ok(if mils {
+            Angle::from_mils_in(value, circle.unwrap_or(NATO_MILS_PER_CIRCLE))
+        } else {
+            Angle::from_degrees(value)
+        })
+    }
+}
+
+impl fmt::Display for Angle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} deg", self.degrees)
+    }
+}
+
+impl Serialize for Angle {
+    /// Sérialisé en degrés.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.degrees)
+    }
+}
+
+impl<'de> Deserialize<'de> for Angle {
+    /// Accepte un nombre (degrés) ou un texte avec unité (`"1600mil"`).
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawAngle {
+            Degrees(f64),
+            Text(String),
+        }
+
+        match RawAngle::deserialize(deserializer)? {
+            RawAngle::Degrees(deg) => Ok(Angle::from_degrees(deg)),
+            RawAngle::Text(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn angles_convert_between_mil_systems() {
+        let a: Angle = " 750 MILS ( 6000 ) ".parse().unwrap();
+        assert_eq!(a.degrees(), 45.0);
+        assert_eq!(a.mils(), 800.0);
+        assert_eq!(a.mils_in(WARSAW_PACT_MILS_PER_CIRCLE), 750.0);
+        assert!((a.radians() - std::f64::consts::FRAC_PI_4).abs() < 1e-12);
+        assert_eq!(Angle::from_radians(a.radians()).degrees(), 45.0);
+
+        assert_eq!(
+            "-90".parse::<Angle>().unwrap().normalized().degrees(),
+            270.0
+        );
+        assert_eq!("45°".parse::<Angle>().unwrap().degrees(), 45.0);
+        assert_eq!("45d".parse::<Angle>().unwrap().degrees(), 45.0);
+        for bad in [
+            "",
+            "mil",
+            "45deg(6000)",
+            "800mil(0)",
+            "800mil(x)",
+            "800mil6000)",
+            "inf",
+        ] {
+            assert!(bad.parse::<Angle>().is_err(), "{bad}");
+        }
+
+        let json: Vec<Angle> =
+            serde_json::from_str(r#"[90, "1600mil", "1500mils(6000)"]"#).unwrap();
+        assert!(json.iter().all(|a| a.degrees() == 90.0));
+        assert_eq!(serde_json::to_string(&json[1]).unwrap(), "90.0");
+        assert!(serde_json::from_str::<Angle>(r#""north""#).is_err());
+    }
+}
@@ -0,0 +1,338 @@
+//! Lecture des paquets de données `.zip`.
+//!
+//! Un paquet regroupe les répertoires de tables balistiques
+//! (`PRACTICE/`, `HE/`, `SMOKE/`, `FLARE/`) et `metrics.json`, éventuellement
+//! sous un répertoire racine commun. Il est distribué et remplacé d'un bloc,
+//! comme un artefact versionné.
+//!
+//! Seules les entrées stockées ou compressées en *deflate* sont prises en
+//! charge (format produit par les outils `zip` usuels) ; les archives
+//! chiffrées ou au format ZIP64 sont refusées.
+
+use anyhow::{bail, Context, Result};
+use flate2::read::DeflateDecoder;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Signature de la fin du répertoire central
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+/// Signature d'une entrée du répertoire central
+const CENTRAL_DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+/// Signature d'un en-tête local
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+/// Taille minimale d'une entrée du répertoire central
+const CENTRAL_DIRECTORY_ENTRY_LEN: usize = 46;
+/// Taux de compression maximal du format *deflate*
+const MAX_DEFLATE_RATIO: usize = 1032;
+
+/// Indique si un chemin désigne un paquet de données (extension `.zip`).
+///
+/// ```
+/// use mortar::archive::is_data_archive;
+/// assert!(is_data_archive("mortar-data-v3.ZIP"));
+/// assert!(!is_data_archive("data"));
+/// ```
+pub fn is_data_archive<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+}
+
+/// Contenu décompressé d'un paquet de données, par chemin relatif.
+#[derive(Clone, Debug, Default)]
+pub struct DataArchive {
+    /// Fichiers par chemin relatif à la racine du paquet (`HE/M821_HE_0R.csv`)
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl DataArchive {
+    /// Ouvre et décompresse un paquet `.zip`.
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si le fichier est illisible ou n'est pas une
+    /// archive prise en charge.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes =
+            std::fs::read(path).with_context(|| format!("cannot open {}", path.display()))?;
+        Self::from_zip(&bytes).with_context(|| format!("invalid data pack {}", path.display()))
+    }
+
+    /// Décompresse une archive `.zip` en mémoire.
+    ///
+    /// La racine du paquet est le répertoire contenant `metrics.json` : un
+    /// paquet créé depuis le répertoire `data/` lui-même est donc accepté.
+    pub fn from_zip(bytes: &[u8]) -> Result<Self> {
+        let mut files = BTreeMap::new();
+        for (name, data) in read_zip(bytes)? {
+            if !name.ends_with('/') {
+                files.insert(name, data);
+            }
+        }
+
+        let root = files
+            .keys()
+            .filter_map(|name| name.strip_suffix("metrics.json"))
+            .filter(|prefix| prefix.is_empty() || prefix.ends_with('/'))
+            .min_by_key(|prefix| prefix.len())
+            .map(str::to_string)
+            .unwrap_or_default();
+        let files = files
+            .into_iter()
+            .filter_map(|(name, data)| Some((name.strip_prefix(&root)?.to_string(), data)))
+            .collect();
+        Ok(DataArchive { files })
+    }
+
+    /// Contenu d'un fichier du paquet, par chemin relatif à sa racine.
+    pub fn file(&self, name: &str) -> Option<&[u8]> {
+        self.files.get(name).map(Vec::as_slice)
+    }
+
+    /// Chemins relatifs des fichiers du paquet.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+}
+
+/// Lit les entrées d'une archive `.zip` à partir de son répertoire central.
+fn read_zip(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    // La fin du répertoire central est suivie d'un commentaire d'au plus 64 Kio
+    let end = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(22 + u16::MAX as usize)
+        .find(|&i| u32_at(bytes, i) == Some(END_OF_CENTRAL_DIRECTORY))
+        .context("not a zip archive")?;
+    let count = u16_at(bytes, end + 10).context("truncated archive")? as usize;
+    let mut pos = u32_at(bytes, end + 16).context("truncated archive")? as usize;
+    if count == u16::MAX as usize || pos == u32::MAX as usize {
+        bail!("ZIP64 archives are not supported");
+    }
+
+    // Les tailles des en-têtes ne sont pas fiables : les réservations sont
+    // bornées par ce que les octets restants peuvent effectivement contenir
+    let mut entries = Vec::with_capacity(
+        count.min(bytes.len().saturating_sub(pos) / CENTRAL_DIRECTORY_ENTRY_LEN),
+    );
+    for _ in 0..count {
+        if u32_at(bytes, pos) != Some(CENTRAL_DIRECTORY_ENTRY) {
+            bail!("corrupted central directory");
+        }
+        let field = |offset: usize| u16_at(bytes, pos + offset).context("truncated archive");
+        let flags = field(8)?;
+        let method = field(10)?;
+        let crc = u32_at(bytes, pos + 16).context("truncated archive")?;
+        let compressed = u32_at(bytes, pos + 20).context("truncated archive")? as usize;
+        let size = u32_at(bytes, pos + 24).context("truncated archive")? as usize;
+        let name_len = field(28)? as usize;
+        let extra_len = field(30)? as usize;
+        let comment_len = field(32)? as usize;
+        let local = u32_at(bytes, pos + 42).context("truncated archive")? as usize;
+        let name = bytes
+            .get(pos + 46..pos + 46 + name_len)
+            .context("truncated archive")?;
+        let name = String::from_utf8_lossy(name).replace('\\', "/");
+        pos += 46 + name_len + extra_len + comment_len;
+
+        if flags & 1 != 0 {
+            bail!("{name}: encrypted entries are not supported");
+        }
+        if u32_at(bytes, local) != Some(LOCAL_HEADER) {
+            bail!("{name}: corrupted local header");
+        }
+        let start = local
+            + 30
+            + u16_at(bytes, local + 26).context("truncated archive")? as usize
+            + u16_at(bytes, local + 28).context("truncated archive")? as usize;
+        let raw = bytes
+            .get(start..start + compressed)
+            .with_context(|| format!("{name}: truncated entry"))?;
+
+        let data = match method {
+            0 => raw.to_vec(),
+            8 => {
+                let mut data =
+                    Vec::with_capacity(size.min(raw.len().saturating_mul(MAX_DEFLATE_RATIO)));
+                // Un octet de plus que la taille annoncée suffit à la contredire
+                DeflateDecoder::new(raw)
+                    .take(size as u64 + 1)
+                    .read_to_end(&mut data)
+                    .with_context(|| format!("{name}: invalid compressed data"))?;
+                data
+            }
+            _ => bail!("{name}: unsupported compression method {method}"),
+        };
+        let mut check = flate2::Crc::new();
+        check.update(&data);
+        if data.len() != size || check.sum() != crc {
+            bail!("{name}: checksum mismatch");
+        }
+        entries.push((name, data));
+    }
+    Ok(entries)
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Fichier à écrire dans une archive (voir [`write_zip`]).
+#[derive(Clone, Copy, Debug)]
+pub struct ZipEntry<'a> {
+    /// Chemin dans l'archive (séparateur `/`)
+    pub name: &'a str,
+    /// Contenu
+    pub data: &'a [u8],
+    /// Permissions Unix d'un exécutable à l'extraction
+    pub executable: bool,
+}
+
+impl<'a> ZipEntry<'a> {
+    /// Fichier ordinaire.
+    pub fn file(name: &'a str, data: &'a [u8]) -> Self {
+        ZipEntry {
+            name,
+            data,
+            executable: false,
+        }
+    }
+}
+
+/// Écrit une archive `.zip` dont chaque entrée est compressée en *deflate*.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si l'archive nécessiterait le format ZIP64 (plus de
+/// 65 534 entrées ou plus de 4 Gio).
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::archive::{write_zip, DataArchive, ZipEntry};
+///
+/// let bytes = write_zip(&[ZipEntry::file("metrics.json", b"{}")]).unwrap();
+/// let archive = DataArchive::from_zip(&bytes).unwrap();
+/// assert_eq!(archive.file("metrics.json"), Some(&b"{}"[..]));
+/// ```
+pub fn write_zip(entries: &[ZipEntry]) -> Result<Vec<u8>> {
+    if entries.len() >= u16::MAX as usize {
+        bail!("too many entries for a zip archive");
+    }
+    let offset_of = |len: usize| u32::try_from(len).ok().filter(|&n| n != u32::MAX);
+    let (mut out, mut central) = (Vec::new(), Vec::new());
+    for entry in entries {
+        let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), Default::default());
+        std::io::Write::write_all(&mut encoder, entry.data)?;
+        let body = encoder.finish()?;
+        let mut crc = flate2::Crc::new();
+        crc.update(entry.data);
+        let (local, size, compressed) = match (
+            offset_of(out.len()),
+            offset_of(entry.data.len()),
+            offset_of(body.len()),
+        ) {
+            (Some(local), Some(size), Some(compressed)) => (local, size, compressed),
+            _ => bail!("{}: too large for a zip archive", entry.name),
+        };
+
+        let mut common = Vec::new();
+        common.extend(20u16.to_le_bytes()); // version requise
+        common.extend(0u16.to_le_bytes()); // drapeaux
+        common.extend(8u16.to_le_bytes()); // deflate
+        common.extend(0u16.to_le_bytes()); // heure
+        common.extend(0x21u16.to_le_bytes()); // 1er janvier 1980
+        common.extend(crc.sum().to_le_bytes());
+        common.extend(compressed.to_le_bytes());
+        common.extend(size.to_le_bytes());
+        common.extend((entry.name.len() as u16).to_le_bytes());
+        common.extend(0u16.to_le_bytes()); // extra
+
+        let mode: u32 = if entry.executable { 0o100755 } else { 0o100644 };
+        central.extend(CENTRAL_DIRECTORY_ENTRY.to_le_bytes());
+        central.extend((3u16 << 8 | 20).to_le_bytes()); // créée sous Unix
+        central.extend(&common);
+        central.extend([0; 6]); // commentaire, disque, attributs internes
+        central.extend((mode << 16).to_le_bytes());
+        central.extend(local.to_le_bytes());
+        central.extend(entry.name.as_bytes());
+
+        out.extend(LOCAL_HEADER.to_le_bytes());
+        out.extend(&common);
+        out.extend(entry.name.as_bytes());
+        out.extend(body);
+    }
+    let offset = match offset_of(out.len()) {
+        Some(offset) => offset,
+        None => bail!("too large for a zip archive"),
+    };
+    out.extend(&central);
+    out.extend(END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+    out.extend([0; 4]);
+    out.extend((entries.len() as u16).to_le_bytes());
+    out.extend((entries.len() as u16).to_le_bytes());
+    out.extend((central.len() as u32).to_le_bytes());
+    out.extend(offset.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_files_are_relative_to_the_metrics_directory() {
+        let csv = b"range_m,elev_mil\n100,1400\n200,1300\n";
+        let bytes = write_zip(&[
+            ZipEntry::file("data/", b""),
+            ZipEntry::file("data/metrics.json", b"{}"),
+            ZipEntry::file("data/HE/M821_HE_0R.csv", csv),
+        ])
+        .unwrap();
+        let archive = DataArchive::from_zip(&bytes).unwrap();
+        assert_eq!(archive.file("metrics.json"), Some(&b"{}"[..]));
+        assert_eq!(archive.file("HE/M821_HE_0R.csv"), Some(&csv[..]));
+        assert_eq!(archive.names().count(), 2);
+    }
+
+    #[test]
+    fn corrupted_archives_are_rejected() {
+        let mut bytes = write_zip(&[ZipEntry::file("metrics.json", b"{\"version\": 1}")]).unwrap();
+        assert!(DataArchive::from_zip(&bytes).is_ok());
+        // Octet du contenu compressé modifié : contenu invalide ou somme de
+        // contrôle différente
+        bytes[30 + "metrics.json".len() + 2] ^= 0xff;
+        assert!(DataArchive::from_zip(&bytes).is_err());
+        assert!(DataArchive::from_zip(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn declared_sizes_are_not_trusted() {
+        let zeros = vec![0u8; 1 << 20];
+        let valid = write_zip(&[ZipEntry::file("metrics.json", &zeros)]).unwrap();
+        let end = valid.len() - 22;
+        let central = u32_at(&valid, end + 16).unwrap() as usize;
+        let patched = |at: usize, value: &[u8]| {
+            let mut bytes = valid.clone();
+            bytes[at..at + value.len()].copy_from_slice(value);
+            DataArchive::from_zip(&bytes)
+        };
+        assert!(patched(central, &CENTRAL_DIRECTORY_ENTRY.to_le_bytes()).is_ok());
+
+        // Taille décompressée annoncée de 4 Gio, puis de 10 octets pour 1 Mio
+        // de zéros : refusées sans réserver ni décompresser au-delà
+        let err = patched(central + 24, &(u32::MAX - 1).to_le_bytes()).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+        let err = patched(central + 24, &10u32.to_le_bytes()).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+        // 65 534 entrées annoncées pour une seule présente
+        let err = patched(end + 10, &(u16::MAX - 1).to_le_bytes()).unwrap_err();
+        assert!(err.to_string().contains("corrupted central directory"));
+    }
+}
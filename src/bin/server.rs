@@ -1,13 +1,139 @@
 use std::io::{self, BufRead, IsTerminal};
+use std::path::PathBuf;
+use std::time::Duration;
 
-use mortar::server::build_app_with_state;
-use mortar::server_cli::{handle_cli_command, print_prompt};
+use clap::Parser;
+use mortar::bundle::read_bundle_state;
+use mortar::server::{build_app_with_options, spawn_autosave, spawn_mirror, AppOptions};
+use mortar::server_cli::{handle_cli_command, print_prompt, print_table_summary};
+use mortar::snapshot::{read_snapshot, MergeStrategy};
+use mortar::terrain::{ElevationThresholds, TerrainGeoref, TerrainModel};
+use mortar::{
+    load_ballistics_layered, Declination, ElevationFallback, Interpolation, SignConvention,
+};
 use tokio::net::TcpListener;
 
+#[derive(Parser, Debug)]
+struct Args {
+    /// Data directory, or a .zip data pack with the same layout (default: data)
+    #[arg(long)]
+    data: Option<PathBuf>,
+    /// Partial data directory layered over the data directory (repeatable, later ones take priority):
+    /// its tables and metrics.json replace those of the layers below
+    #[arg(long = "data-overlay", conflicts_with = "bundle")]
+    data_overlays: Vec<PathBuf>,
+    /// Offline bundle (see the `bundle` command): loads its tables and metrics and restores its state
+    #[arg(long, conflicts_with = "data")]
+    bundle: Option<PathBuf>,
+    /// Autosave file; restored on startup if it exists
+    #[arg(long)]
+    autosave: Option<PathBuf>,
+    /// Autosave interval in seconds
+    #[arg(long, default_value_t = 60)]
+    autosave_interval: u64,
+    /// Drop inconsistent rows (duplicate ranges, non-decreasing elevations) from ballistic tables
+    #[arg(long)]
+    repair_tables: bool,
+    /// Find ballistic tables by scanning the ammo directories (<AMMO>/**/*_<n>R*.csv) instead of fixed file names
+    #[arg(long)]
+    scan_tables: bool,
+    /// Spectator mode: reject all modifications; the autosave file is mirrored instead of written
+    #[arg(long)]
+    read_only: bool,
+    /// Positive directions of correction deviations, e.g. S+/E+ (default) or N+/E+
+    #[arg(long, default_value = "S+/E+", value_parser = parse_sign_convention)]
+    sign_convention: SignConvention,
+    /// Positions entered without elevation copy it from the nearest known position
+    #[arg(long)]
+    elevation_from_nearest: bool,
+    /// Elevation (m) of positions entered without one when no other source applies
+    #[arg(long, default_value_t = 0.0)]
+    elevation_datum: f64,
+    /// Heightmap (8 or 16-bit grayscale PNG) giving the elevation of positions entered without one
+    #[arg(long, requires = "heightmap_georef")]
+    heightmap: Option<PathBuf>,
+    /// Heightmap georeference: X,Y of the north-west pixel, pixel size (m), meters per gray level
+    /// (default 1) and elevation of level 0 (default 0), e.g. 1000,5000,10,0.5,-20
+    #[arg(long, value_parser = parse_georef, requires = "heightmap", allow_hyphen_values = true)]
+    heightmap_georef: Option<TerrainGeoref>,
+    /// Magnetic declination in degrees (east positive), printed on mission packs and volley tables
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    declination: f64,
+    /// Grid convergence in degrees (east positive)
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    grid_convergence: f64,
+    /// Elevation difference (m) between mortar and target beyond which solutions are flagged for checking
+    #[arg(long, default_value_t = 100.0)]
+    elevation_caution: f64,
+    /// Elevation difference (m) beyond which the flat-ground model is flagged as unreliable
+    #[arg(long, default_value_t = 300.0)]
+    elevation_critical: f64,
+    /// Propellant temperature in °C applied to every solution (default: standard temperature of the tables)
+    #[arg(long, allow_negative_numbers = true)]
+    charge_temperature: Option<f64>,
+    /// Directory of the web interface (default: src/web); unknown paths serve its index.html
+    #[arg(long)]
+    web_dir: Option<PathBuf>,
+    /// Browser cache lifetime of web assets other than HTML, in seconds (0: always revalidate)
+    #[arg(long, default_value_t = 300)]
+    asset_max_age: u64,
+    /// Time budget of heavy calculations (simulations) in milliseconds; longer requests return a partial result (0: unlimited)
+    #[arg(long, default_value_t = 2000)]
+    calc_budget_ms: u64,
+    /// Interpolation of elevations between ballistic table rows: linear or pchip (monotone cubic)
+    #[arg(long, default_value = "linear", value_parser = parse_interpolation)]
+    interpolation: Interpolation,
+    /// Resample ballistic tables every meter at load time so elevations are direct lookups (more memory)
+    #[arg(long)]
+    dense_tables: bool,
+    /// Print a data quality summary of the ballistic tables and exit (status 1 if a table is inconsistent)
+    #[arg(long)]
+    verify: bool,
+    /// Start the CLI in dry-run mode: mutating commands report what they would change without applying it
+    #[arg(long)]
+    dry_run: bool,
+    /// Refuse to fire (spot rounds) at targets that have not been cleared
+    #[arg(long)]
+    require_clearance: bool,
+    /// Session seed of simulated impacts, so an exercise replays identically (default: clock)
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+fn parse_sign_convention(s: &str) -> Result<SignConvention, String> {
+    SignConvention::try_from(s.to_string())
+}
+
+fn parse_georef(s: &str) -> Result<TerrainGeoref, String> {
+    TerrainGeoref::parse_str(s)
+        .ok_or_else(|| format!("expected X,Y,CELL[,SCALE[,BASE]], got {}", s))
+}
+
+fn parse_interpolation(s: &str) -> Result<Interpolation, String> {
+    Interpolation::parse_str(s).ok_or_else(|| format!("expected linear or pchip, got {}", s))
+}
+
 #[tokio::main]
 async fn main() {
+    let args = Args::parse();
+    let elevation_thresholds = ElevationThresholds {
+        caution_m: args.elevation_caution,
+        critical_m: args.elevation_critical,
+    };
+    if !elevation_thresholds.is_valid() {
+        eprintln!("Error: elevation thresholds must be positive with --elevation-caution <= --elevation-critical");
+        std::process::exit(2);
+    }
+
     // Determine data path
-    let data_path = if std::path::Path::new("data").exists() {
+    let data_dir = args
+        .data
+        .as_ref()
+        .or(args.bundle.as_ref())
+        .map(|p| p.to_string_lossy().to_string());
+    let data_path = if let Some(path) = &data_dir {
+        path.as_str()
+    } else if std::path::Path::new("data").exists() {
         "data"
     } else if std::path::Path::new("/workspace/rust/mortar/data").exists() {
         "/workspace/rust/mortar/data"
@@ -15,8 +141,41 @@ async fn main() {
         "data"
     };
 
+    if args.verify {
+        let consistent = match load_ballistics_layered(
+            data_path,
+            &args.data_overlays,
+            false,
+            args.scan_tables,
+        ) {
+            Ok((tables, report)) => {
+                for file in report.failed() {
+                    println!(
+                        "{} {}R: not loaded ({}): {}",
+                        file.ammo_type,
+                        file.ring,
+                        file.path,
+                        file.error.as_deref().unwrap_or("unknown error")
+                    );
+                }
+                print_table_summary(&tables) && report.failed().next().is_none()
+            }
+            Err(e) => {
+                eprintln!("Error: failed to load ballistics: {e}");
+                false
+            }
+        };
+        std::process::exit(if consistent { 0 } else { 1 });
+    }
+
     // Determine web assets path
-    let web_path = if std::path::Path::new("src/web").exists() {
+    let web_dir = args
+        .web_dir
+        .as_ref()
+        .map(|p| p.to_string_lossy().to_string());
+    let web_path = if let Some(dir) = &web_dir {
+        dir.as_str()
+    } else if std::path::Path::new("src/web").exists() {
         "src/web"
     } else if std::path::Path::new("/workspace/rust/mortar/src/web").exists() {
         "/workspace/rust/mortar/src/web"
@@ -24,13 +183,118 @@ async fn main() {
         "src/web"
     };
 
+    let terrain = match (&args.heightmap, args.heightmap_georef) {
+        (Some(path), Some(georef)) => {
+            let loaded = std::fs::File::open(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|f| TerrainModel::from_png(io::BufReader::new(f), georef));
+            match loaded {
+                Ok(terrain) => Some(terrain),
+                Err(e) => {
+                    eprintln!("Error: failed to load heightmap {}: {e:#}", path.display());
+                    std::process::exit(2);
+                }
+            }
+        }
+        _ => None,
+    };
+
     // Build router + shared state from library
-    let (app, state) = build_app_with_state(data_path, web_path);
+    let options = AppOptions {
+        repair_tables: args.repair_tables,
+        scan_tables: args.scan_tables,
+        data_overlays: args.data_overlays.clone(),
+        read_only: args.read_only,
+        sign_convention: args.sign_convention,
+        elevation_fallback: ElevationFallback {
+            copy_nearest: args.elevation_from_nearest,
+            datum_m: args.elevation_datum,
+        },
+        terrain,
+        declination: Declination {
+            magnetic_deg: args.declination,
+            grid_convergence_deg: args.grid_convergence,
+        },
+        elevation_thresholds,
+        charge_temperature_c: args.charge_temperature,
+        asset_max_age_s: args.asset_max_age,
+        calc_budget_ms: args.calc_budget_ms,
+        interpolation: args.interpolation,
+        dense_tables: args.dense_tables,
+        dry_run: args.dry_run,
+        require_clearance: args.require_clearance,
+        seed: args.seed,
+    };
+    let (app, state) = build_app_with_options(data_path, web_path, &options);
+
+    if let Some(path) = &args.bundle {
+        match read_bundle_state(path) {
+            Ok(snapshot) => {
+                let report = state
+                    .restore(&snapshot, MergeStrategy::ReplaceAll, false)
+                    .await;
+                println!(
+                    "Restored {} mortars and {} targets from bundle {}",
+                    report.mortars.added.len(),
+                    report.targets.added.len(),
+                    path.display()
+                );
+            }
+            Err(e) => eprintln!("Warning: failed to restore bundle state: {e:#}"),
+        }
+    }
+
+    if let (Some(path), true) = (&args.autosave, args.read_only) {
+        spawn_mirror(
+            state.clone(),
+            path.clone(),
+            Duration::from_secs(args.autosave_interval.max(1)),
+        );
+        println!(
+            "Mirroring {} every {}s",
+            path.display(),
+            args.autosave_interval.max(1)
+        );
+    } else if let Some(path) = &args.autosave {
+        if path.exists() {
+            match read_snapshot(path) {
+                Ok(snapshot) => {
+                    let report = state
+                        .restore(&snapshot, MergeStrategy::ReplaceAll, false)
+                        .await;
+                    println!(
+                        "Restored {} mortars and {} targets from {}",
+                        report.mortars.added.len(),
+                        report.targets.added.len(),
+                        path.display()
+                    );
+                }
+                Err(e) => eprintln!("Warning: failed to restore autosave: {e:#}"),
+            }
+        }
+        spawn_autosave(
+            state.clone(),
+            path.clone(),
+            Duration::from_secs(args.autosave_interval.max(1)),
+        );
+        println!(
+            "Autosave to {} every {}s",
+            path.display(),
+            args.autosave_interval.max(1)
+        );
+    }
+
+    if args.read_only {
+        println!("Read-only mode: modifications are disabled");
+    }
 
     let addr = "0.0.0.0:3000";
     println!("Server starting on http://{addr}");
     println!("Web assets from: {web_path}");
     println!("Ballistics from: {data_path}");
+    for overlay in &args.data_overlays {
+        println!("  overridden by: {}", overlay.display());
+    }
     println!();
 
     let interactive = io::stdin().is_terminal();
@@ -0,0 +1,170 @@
+//! Paquets hors ligne : tables, métriques et état dans une seule archive.
+//!
+//! Un paquet est un paquet de données `.zip` (voir [`crate::archive`]) : les
+//! tables balistiques en service sous leurs noms fixes, `metrics.json`,
+//! `ammo.json` si des munitions déclarées sont en service et,
+//! en plus, l'état de l'opération (`state.json`) et éventuellement
+//! l'exécutable du serveur (`bin/`). Il se transfère d'un bloc vers un poste
+//! déconnecté, où le serveur le charge directement (`--bundle`).
+
+use anyhow::{bail, Context, Result};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::ammo::{AmmoRegistry, AMMO_MANIFEST};
+use crate::archive::{write_zip, DataArchive, ZipEntry};
+use crate::snapshot::StateSnapshot;
+use crate::{BallisticTable, BallisticTables};
+
+/// Nom de l'état de l'opération dans un paquet.
+pub const STATE_FILE: &str = "state.json";
+
+/// Répertoire de l'exécutable dans un paquet.
+pub const BINARY_DIR: &str = "bin";
+
+/// Écrit une table balistique au format CSV des fichiers de données.
+///
+/// ```
+/// use mortar::bundle::table_csv;
+/// use mortar::{read_ballistic_points, BallisticPoint, BallisticTable};
+///
+/// let table = BallisticTable {
+///     points: vec![BallisticPoint {
+///         range_m: 100.0,
+///         elev_mil: 1400.5,
+///         time_flight_s: Some(13.2),
+///         delta_elev_per_100m_mil: None,
+///     }],
+///     ..Default::default()
+/// };
+/// let csv = table_csv(&table);
+/// assert_eq!(csv, "range_m,elev_mil,time_flight_s,delta_elev_per_100m_mil\n100,1400.5,13.2,\n");
+/// assert_eq!(read_ballistic_points(csv.as_bytes()).unwrap(), table.points);
+/// ```
+pub fn table_csv(table: &BallisticTable) -> String {
+    let optional = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+    let mut csv = String::from("range_m,elev_mil,time_flight_s,delta_elev_per_100m_mil\n");
+    for p in &table.points {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{}",
+            p.range_m,
+            p.elev_mil,
+            optional(p.time_flight_s),
+            optional(p.delta_elev_per_100m_mil)
+        );
+    }
+    csv
+}
+
+/// Construit un paquet hors ligne.
+///
+/// Les tables sont rangées sous les noms de `registry`, qui décrit aussi les
+/// munitions déclarées dans le manifeste `ammo.json` du paquet. `binary` est
+/// le nom et le contenu de l'exécutable à joindre, rangé sous [`BINARY_DIR`].
+///
+/// # Erreurs
+///
+/// Retourne une erreur si l'état ne peut pas être sérialisé ou si le paquet
+/// dépasse les limites du format `.zip`.
+pub fn build_bundle(
+    tables: &BallisticTables,
+    registry: &AmmoRegistry,
+    metrics_json: &[u8],
+    snapshot: &StateSnapshot,
+    binary: Option<(&str, &[u8])>,
+) -> Result<Vec<u8>> {
+    let files: Vec<(String, String)> = tables
+        .iter()
+        .map(|((weapon, ammo, ring), table)| {
+            (registry.file_name(*weapon, *ammo, *ring), table_csv(table))
+        })
+        .collect();
+    let state = serde_json::to_vec_pretty(snapshot)?;
+    let binary_name = binary.map(|(name, _)| format!("{}/{}", BINARY_DIR, name));
+    let ammo = registry.manifest_json(tables.keys().map(|(_, ammo, _)| *ammo));
+
+    let mut entries = vec![
+        ZipEntry::file("metrics.json", metrics_json),
+        ZipEntry::file(STATE_FILE, &state),
+    ];
+    if let Some(ammo) = &ammo {
+        entries.push(ZipEntry::file(AMMO_MANIFEST, ammo));
+    }
+    entries.extend(
+        files
+            .iter()
+            .map(|(name, csv)| ZipEntry::file(name, csv.as_bytes())),
+    );
+    if let (Some(name), Some((_, data))) = (&binary_name, binary) {
+        entries.push(ZipEntry {
+            name,
+            data,
+            executable: true,
+        });
+    }
+    write_zip(&entries)
+}
+
+/// Lit l'état de l'opération d'un paquet hors ligne.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le paquet est illisible ou ne contient pas d'état
+/// valide.
+pub fn read_bundle_state<P: AsRef<Path>>(path: P) -> Result<StateSnapshot> {
+    let path = path.as_ref();
+    let pack = DataArchive::open(path)?;
+    let state = match pack.file(STATE_FILE) {
+        Some(state) => state,
+        None => bail!("{} not found in {}", STATE_FILE, path.display()),
+    };
+    serde_json::from_slice(state)
+        .with_context(|| format!("invalid {} in {}", STATE_FILE, path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        load_ballistics_checked, load_metrics_from, AmmoKind, MortarPosition, WeaponSystem,
+    };
+
+    #[test]
+    fn bundles_load_as_data_packs_with_their_state() {
+        let data = Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let (tables, _) = load_ballistics_checked(&data, false).unwrap();
+        let metrics = std::fs::read(data.join("metrics.json")).unwrap();
+        let snapshot = StateSnapshot {
+            mortars: vec![MortarPosition::new("M1".into(), 100.0, 0.0, 0.0)],
+            ..Default::default()
+        };
+
+        let bytes = build_bundle(
+            &tables,
+            &AmmoRegistry::load(&data).unwrap(),
+            &metrics,
+            &snapshot,
+            Some(("server", b"\x7fELF")),
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("mortar_bundle_{}.zip", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        let (loaded, report) = load_ballistics_checked(&path, false).unwrap();
+        let state = read_bundle_state(&path).unwrap();
+        let dispersions = load_metrics_from(&path).unwrap().dispersions;
+        let binary = DataArchive::open(&path)
+            .unwrap()
+            .file("bin/server")
+            .map(<[u8]>::to_vec);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(report.failed().count(), 0);
+        assert_eq!(loaded.len(), tables.len());
+        let key = (WeaponSystem::M224, AmmoKind::He, 2);
+        assert_eq!(loaded[&key].points, tables[&key].points);
+        assert_eq!(state.mortars[0].name, "M1");
+        assert_eq!(dispersions, load_metrics_from(&data).unwrap().dispersions);
+        assert_eq!(binary.as_deref(), Some(&b"\x7fELF"[..]));
+    }
+}
@@ -0,0 +1,239 @@
+//! Couverture d'une pièce : champ d'élévation échantillonné sur une grille.
+//!
+//! Pour un mortier, une munition et un anneau, chaque cellule d'une grille
+//! régulière reçoit l'élévation lue dans la table balistique à la distance
+//! de la pièce, ou rien si elle est hors portée. L'interface web en tire une
+//! carte de chaleur calculée sur les tables réelles.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{AmmoKind, BallisticTable, MortarPosition, Position, Ring, Sector};
+
+/// Pas de grille par défaut, en mètres.
+pub const DEFAULT_CELL_M: f64 = 50.0;
+
+/// Nombre maximal de cellules d'une grille de couverture.
+pub const MAX_COVERAGE_CELLS: usize = 40_000;
+
+/// Emprise d'une grille, en coordonnées de carte (mètres).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GridExtent {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl GridExtent {
+    /// Carré centré sur `(x, y)`, de demi-côté `radius_m`.
+    pub fn around(x: f64, y: f64, radius_m: f64) -> Self {
+        GridExtent {
+            min_x: x - radius_m,
+            min_y: y - radius_m,
+            max_x: x + radius_m,
+            max_y: y + radius_m,
+        }
+    }
+}
+
+/// Champ d'élévation d'une pièce sur une grille.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CoverageGrid {
+    /// Nom du mortier
+    pub mortar: String,
+    pub ammo_type: AmmoKind,
+    pub ring: Ring,
+    /// Emprise de la grille
+    pub extent: GridExtent,
+    /// Côté d'une cellule (mètres)
+    pub cell_m: f64,
+    /// Nombre de colonnes (d'Ouest en Est)
+    pub cols: usize,
+    /// Nombre de lignes (du Nord au Sud)
+    pub rows: usize,
+    /// Portées minimale et maximale de la table (mètres)
+    pub min_range_m: f64,
+    pub max_range_m: f64,
+    /// Secteur de tir de la pièce, hors duquel les cellules sont exclues
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sector: Option<Sector>,
+    /// Cellules à portée
+    pub reachable_cells: usize,
+    /// Élévations arrondies au mil au centre de chaque cellule, ligne par
+    /// ligne du Nord au Sud (comme une image) ; `None` hors portée
+    pub elevations_mil: Vec<Vec<Option<i32>>>,
+}
+
+/// Échantillonne l'élévation de `table` sur une grille autour de `mortar`.
+///
+/// Sans `extent`, la grille couvre le carré centré sur la pièce dont le
+/// demi-côté est la portée maximale de la table. Les élévations sont lues à
+/// la distance horizontale du centre de chaque cellule, sans correction de
+/// dénivelé. Les cellules hors du secteur de tir de la pièce sont exclues.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si la table est vide, si `cell_m` n'est pas un nombre
+/// strictement positif, si l'emprise est vide ou si la grille dépasse
+/// [`MAX_COVERAGE_CELLS`] cellules.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::coverage::coverage_grid;
+/// use mortar::{AmmoKind, BallisticPoint, BallisticTable, MortarPosition};
+///
+/// let point = |range_m, elev_mil| BallisticPoint {
+///     range_m,
+///     elev_mil,
+///     time_flight_s: None,
+///     delta_elev_per_100m_mil: None,
+/// };
+/// let table = BallisticTable {
+///     points: vec![point(100.0, 1400.0), point(300.0, 1200.0)],
+///     ..Default::default()
+/// };
+/// let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+/// let grid = coverage_grid(&mortar, &table, AmmoKind::He, 1, None, 100.0).unwrap();
+/// assert_eq!((grid.cols, grid.rows), (6, 6));
+/// // Cellule (50, 250) : 255 m de la pièce
+/// assert_eq!(grid.elevations_mil[0][3], Some(1245));
+/// // Cellule (50, 50) : trop près
+/// assert_eq!(grid.elevations_mil[2][3], None);
+/// ```
+pub fn coverage_grid(
+    mortar: &MortarPosition,
+    table: &BallisticTable,
+    ammo_type: AmmoKind,
+    ring: Ring,
+    extent: Option<GridExtent>,
+    cell_m: f64,
+) -> Result<CoverageGrid> {
+    // Portées au sol, compte tenu du rendement des charges de la pièce
+    let (min_range_m, max_range_m) = match table.range_bounds() {
+        Some((min, max)) => (mortar.ground_range(min), mortar.ground_range(max)),
+        None => bail!("{} {}R table is empty", ammo_type, ring),
+    };
+    if !cell_m.is_finite() || cell_m <= 0.0 {
+        bail!("invalid cell size {} m", cell_m);
+    }
+    let extent = extent.unwrap_or_else(|| GridExtent::around(mortar.x, mortar.y, max_range_m));
+    let (width, height) = (extent.max_x - extent.min_x, extent.max_y - extent.min_y);
+    if !(width > 0.0 && height > 0.0 && width.is_finite() && height.is_finite()) {
+        bail!("empty grid extent");
+    }
+    let (cols, rows) = (
+        (width / cell_m).ceil() as usize,
+        (height / cell_m).ceil() as usize,
+    );
+    if cols.saturating_mul(rows) > MAX_COVERAGE_CELLS {
+        bail!(
+            "{} x {} cells exceed the limit of {} (use a larger cell size)",
+            cols,
+            rows,
+            MAX_COVERAGE_CELLS
+        );
+    }
+
+    let origin = mortar.as_position();
+    let mut reachable_cells = 0;
+    let elevations_mil = (0..rows)
+        .map(|r| {
+            let y = extent.max_y - (r as f64 + 0.5) * cell_m;
+            (0..cols)
+                .map(|c| {
+                    let x = extent.min_x + (c as f64 + 0.5) * cell_m;
+                    if !mortar.in_sector(x, y) {
+                        return None;
+                    }
+                    let cell = Position::new(String::new(), origin.elevation, x, y);
+                    let elev = table.elev_at(mortar.table_range(origin.distance_to(&cell)))?;
+                    reachable_cells += 1;
+                    Some(elev.round() as i32)
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(CoverageGrid {
+        mortar: mortar.name.clone(),
+        ammo_type,
+        ring,
+        extent,
+        cell_m,
+        cols,
+        rows,
+        min_range_m,
+        max_range_m,
+        sector: mortar.sector,
+        reachable_cells,
+        elevations_mil,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BallisticPoint;
+
+    fn table() -> BallisticTable {
+        let point = |range_m, elev_mil| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: None,
+            delta_elev_per_100m_mil: None,
+        };
+        BallisticTable {
+            points: vec![point(100.0, 1400.0), point(500.0, 1000.0)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn grid_rows_run_from_north_to_south() {
+        let mortar = MortarPosition::new("M1".into(), 0.0, 1000.0, 1000.0);
+        let extent = GridExtent {
+            min_x: 1000.0,
+            min_y: 1000.0,
+            max_x: 1100.0,
+            max_y: 1400.0,
+        };
+        let grid = coverage_grid(&mortar, &table(), AmmoKind::He, 2, Some(extent), 100.0).unwrap();
+        assert_eq!((grid.cols, grid.rows), (1, 4));
+        let column: Vec<Option<i32>> = grid.elevations_mil.iter().map(|row| row[0]).collect();
+        // Centres à 354, 255, 158 et 71 m de la pièce
+        assert_eq!(column, vec![Some(1146), Some(1245), Some(1342), None]);
+        assert_eq!(grid.reachable_cells, 3);
+    }
+
+    #[test]
+    fn cells_outside_the_sector_of_fire_are_excluded() {
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let full = coverage_grid(&mortar, &table(), AmmoKind::He, 2, None, 50.0).unwrap();
+        // Quart Nord-Est, limité à 400 m
+        mortar.sector = Some(Sector {
+            left_deg: 0.0,
+            right_deg: 90.0,
+            max_range_m: Some(400.0),
+        });
+        let grid = coverage_grid(&mortar, &table(), AmmoKind::He, 2, None, 50.0).unwrap();
+        assert_eq!(grid.sector, mortar.sector);
+        assert!(grid.reachable_cells * 4 < full.reachable_cells);
+        // Cellule (225, 225) : Nord-Est, à 318 m ; cellule (-225, 225) : Nord-Ouest
+        assert!(grid.elevations_mil[3][14].is_some());
+        assert_eq!(grid.elevations_mil[3][5], None);
+        assert!(full.elevations_mil[3][5].is_some());
+    }
+
+    #[test]
+    fn oversized_or_empty_grids_are_rejected() {
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        assert!(coverage_grid(&mortar, &table(), AmmoKind::He, 2, None, 1.0).is_err());
+        assert!(coverage_grid(&mortar, &table(), AmmoKind::He, 2, None, 0.0).is_err());
+        let empty = GridExtent::around(0.0, 0.0, 0.0);
+        assert!(coverage_grid(&mortar, &table(), AmmoKind::He, 2, Some(empty), 50.0).is_err());
+        let grid = coverage_grid(&mortar, &table(), AmmoKind::He, 2, None, 50.0).unwrap();
+        assert_eq!((grid.cols, grid.rows), (20, 20));
+    }
+}
@@ -0,0 +1,213 @@
+//! Simulation des commandes CLI (mode `--dry-run`).
+//!
+//! En mode simulation, une commande modifiant l'état est exécutée sur une
+//! copie jetable de l'état ; les deux états sont ensuite comparés au travers
+//! de vues ([`StateView`]) pour décrire ce que la commande aurait changé
+//! ([`changes`]), sans toucher à l'opération en cours.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Vue comparable d'un état : entités par nom et réglages, en JSON.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateView {
+    /// Mortiers, par nom
+    pub mortars: BTreeMap<String, Value>,
+    /// Cibles, par nom
+    pub targets: BTreeMap<String, Value>,
+    /// Réglages de l'opération, par nom
+    pub settings: BTreeMap<&'static str, Value>,
+    /// Nombre de solutions enregistrées, par cible
+    pub history: BTreeMap<String, usize>,
+    /// Jetons des liens de partage
+    pub shares: Vec<String>,
+}
+
+/// Modification qu'aurait apportée une commande.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// Entité créée
+    Created { kind: &'static str, name: String },
+    /// Entité supprimée
+    Removed { kind: &'static str, name: String },
+    /// Champ d'une entité modifié
+    Updated {
+        kind: &'static str,
+        name: String,
+        field: String,
+        from: Value,
+        to: Value,
+    },
+    /// Réglage de l'opération modifié
+    Setting {
+        name: &'static str,
+        from: Value,
+        to: Value,
+    },
+    /// Solutions ajoutées à l'historique d'une cible
+    Recorded { target: String, count: usize },
+    /// Liens de partage créés (positif) ou révoqués (négatif)
+    Shares { delta: i64 },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Created { kind, name } => write!(f, "create {} '{}'", kind, name),
+            Change::Removed { kind, name } => write!(f, "remove {} '{}'", kind, name),
+            Change::Updated {
+                kind,
+                name,
+                field,
+                from,
+                to,
+            } => write!(f, "{} '{}': {} {} -> {}", kind, name, field, from, to),
+            Change::Setting { name, from, to } => write!(f, "{}: {} -> {}", name, from, to),
+            Change::Recorded { target, count } => {
+                write!(f, "record {} solution(s) for '{}'", count, target)
+            }
+            Change::Shares { delta } if *delta >= 0 => write!(f, "create {} share link(s)", delta),
+            Change::Shares { delta } => write!(f, "revoke {} share link(s)", -delta),
+        }
+    }
+}
+
+/// Différences entre deux vues d'un état, entités puis réglages.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::dry_run::{changes, Change, StateView};
+/// use serde_json::json;
+///
+/// let before = StateView::default();
+/// let mut after = StateView::default();
+/// after.mortars.insert("M1".into(), json!({ "name": "M1", "x": 0.0 }));
+/// assert_eq!(
+///     changes(&before, &after),
+///     vec![Change::Created { kind: "mortar", name: "M1".into() }]
+/// );
+/// ```
+pub fn changes(before: &StateView, after: &StateView) -> Vec<Change> {
+    let mut out = Vec::new();
+    entity_changes(&mut out, "mortar", &before.mortars, &after.mortars);
+    entity_changes(&mut out, "target", &before.targets, &after.targets);
+
+    for (name, to) in &after.settings {
+        let from = before.settings.get(name).cloned().unwrap_or(Value::Null);
+        if &from != to {
+            out.push(Change::Setting {
+                name,
+                from,
+                to: to.clone(),
+            });
+        }
+    }
+    for (target, count) in &after.history {
+        let previous = before.history.get(target).copied().unwrap_or(0);
+        if *count > previous {
+            out.push(Change::Recorded {
+                target: target.clone(),
+                count: count - previous,
+            });
+        }
+    }
+    let delta = after.shares.len() as i64 - before.shares.len() as i64;
+    if delta != 0 {
+        out.push(Change::Shares { delta });
+    }
+    out
+}
+
+/// Entités créées, supprimées et champs modifiés d'un type d'entité.
+fn entity_changes(
+    out: &mut Vec<Change>,
+    kind: &'static str,
+    before: &BTreeMap<String, Value>,
+    after: &BTreeMap<String, Value>,
+) {
+    for name in before.keys().filter(|n| !after.contains_key(*n)) {
+        out.push(Change::Removed {
+            kind,
+            name: name.clone(),
+        });
+    }
+    for (name, to) in after {
+        let from = match before.get(name) {
+            Some(from) => from,
+            None => {
+                out.push(Change::Created {
+                    kind,
+                    name: name.clone(),
+                });
+                continue;
+            }
+        };
+        let empty = serde_json::Map::new();
+        let (from, to) = (
+            from.as_object().unwrap_or(&empty),
+            to.as_object().unwrap_or(&empty),
+        );
+        let mut fields: Vec<&String> = from.keys().chain(to.keys()).collect();
+        fields.sort();
+        fields.dedup();
+        for field in fields {
+            let (a, b) = (
+                from.get(field).unwrap_or(&Value::Null),
+                to.get(field).unwrap_or(&Value::Null),
+            );
+            if a != b {
+                out.push(Change::Updated {
+                    kind,
+                    name: name.clone(),
+                    field: field.clone(),
+                    from: a.clone(),
+                    to: b.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn changes_list_updated_fields_and_settings() {
+        let mut before = StateView::default();
+        before
+            .targets
+            .insert("T1".into(), json!({ "name": "T1", "x": 500.0, "y": 300.0 }));
+        before.targets.insert("T2".into(), json!({ "name": "T2" }));
+        before.settings.insert("sign_convention", json!("S+/E+"));
+        before.history.insert("T1".into(), 2);
+
+        let mut after = before.clone();
+        after
+            .targets
+            .insert("T1".into(), json!({ "name": "T1", "x": 480.0, "y": 300.0 }));
+        after.targets.remove("T2");
+        after.settings.insert("sign_convention", json!("S-/E-"));
+        after.history.insert("T1".into(), 3);
+        after.shares.push("abc".into());
+
+        let described: Vec<String> = changes(&before, &after)
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                "remove target 'T2'",
+                "target 'T1': x 500.0 -> 480.0",
+                "sign_convention: \"S+/E+\" -> \"S-/E-\"",
+                "record 1 solution(s) for 'T1'",
+                "create 1 share link(s)",
+            ]
+        );
+        assert!(changes(&before, &before).is_empty());
+    }
+}
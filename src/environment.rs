@@ -0,0 +1,972 @@
+//! Conditions d'environnement (vent, température des charges).
+//!
+//! Le vent est saisi directement (direction d'où il vient, vitesse). Il sert à
+//! évaluer l'efficacité d'un écran fumigène et à proposer un point de visée
+//! décalé au vent de la cible.
+//!
+//! Le vent de l'opération, éventuellement complété de couches en altitude,
+//! corrige aussi les solutions : le vent balistique rencontré par le projectile
+//! décale l'impact pendant sa durée de trajet, la portée et le gisement sont
+//! corrigés en sens inverse avant la lecture des tables.
+//!
+//! La température et la pression de l'air donnent la densité de l'air au
+//! niveau de la pièce : un air moins dense que l'atmosphère standard des
+//! tables freine moins le projectile, qui porte plus loin.
+//!
+//! La température des charges corrige la portée : une charge chaude porte plus
+//! loin, une charge froide plus court. Les facteurs par munition et anneau
+//! proviennent de la section `charge_temperature` de `metrics.json` ; la
+//! portée est corrigée avant la lecture des tables.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::airspace::max_ordinate_m;
+use crate::{
+    mils, AmmoKind, BallisticTables, FiringSolution, MortarPosition, Position, Ring, RingTable,
+    TargetPosition, RING_COUNT,
+};
+
+/// Température de référence des tables de tir (°C).
+pub const STANDARD_CHARGE_TEMPERATURE_C: f64 = 21.0;
+
+/// Températures des charges acceptées (°C).
+pub const CHARGE_TEMPERATURE_RANGE_C: std::ops::RangeInclusive<f64> = -60.0..=70.0;
+
+/// Vent traversier (m/s) au-delà duquel un écran fumigène est déconseillé.
+pub const SMOKE_MAX_CROSSWIND_MPS: f64 = 8.0;
+
+/// Vent (m/s) en deçà duquel la fumée stagne autour du point d'impact.
+pub const SMOKE_MIN_WIND_MPS: f64 = 1.0;
+
+/// Temps de dérive (s) utilisé pour décaler le point de visée au vent.
+pub const SMOKE_DRIFT_TIME_S: f64 = 15.0;
+
+/// Décalage maximal (m) du point de visée d'un écran.
+pub const SMOKE_MAX_OFFSET_M: f64 = 150.0;
+
+/// Vitesses de vent acceptées (m/s).
+pub const WIND_SPEED_RANGE_MPS: std::ops::RangeInclusive<f64> = 0.0..=60.0;
+
+/// Part de la vitesse du vent communiquée au projectile : l'impact est décalé
+/// de `composante × durée de trajet × WIND_DRIFT_FACTOR`.
+pub const WIND_DRIFT_FACTOR: f64 = 0.5;
+
+/// Vent au sol.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Wind {
+    /// Direction d'où vient le vent, en degrés depuis le Nord (accepte aussi
+    /// `"4800mil"` en entrée)
+    #[serde(deserialize_with = "crate::deserialize_angle_deg")]
+    pub direction_deg: f64,
+    /// Vitesse en m/s
+    pub speed_mps: f64,
+}
+
+impl Wind {
+    /// Décompose le vent selon un axe de tir.
+    ///
+    /// # Retourne
+    ///
+    /// `(face, travers)` en m/s : `face` est positif pour un vent de face
+    /// (venant de la direction de tir), `travers` est positif pour un vent
+    /// soufflant de gauche à droite.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::environment::Wind;
+    /// // Tir plein Nord, vent d'Ouest : vent traversier de gauche à droite
+    /// let wind = Wind { direction_deg: 270.0, speed_mps: 5.0 };
+    /// let (head, cross) = wind.components(0.0);
+    /// assert!(head.abs() < 1e-9);
+    /// assert!((cross - 5.0).abs() < 1e-9);
+    /// ```
+    pub fn components(&self, bearing_deg: f64) -> (f64, f64) {
+        let rel = (self.direction_deg - bearing_deg).to_radians();
+        (self.speed_mps * rel.cos(), -self.speed_mps * rel.sin())
+    }
+}
+
+/// Vent d'une couche en altitude.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindLayer {
+    /// Altitude de la base de la couche au-dessus de la pièce (m)
+    pub height_m: f64,
+    /// Direction d'où vient le vent, en degrés depuis le Nord
+    #[serde(deserialize_with = "crate::deserialize_angle_deg")]
+    pub direction_deg: f64,
+    /// Vitesse en m/s
+    pub speed_mps: f64,
+}
+
+/// Vent de l'opération : vent au sol et couches en altitude éventuelles.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindProfile {
+    /// Vent au sol
+    #[serde(flatten)]
+    pub surface: Wind,
+    /// Couches en altitude ; chacune s'applique de sa base à la suivante, une
+    /// couche à 0 m remplaçant le vent au sol pour la correction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<WindLayer>,
+}
+
+impl From<Wind> for WindProfile {
+    fn from(surface: Wind) -> Self {
+        WindProfile {
+            surface,
+            layers: Vec::new(),
+        }
+    }
+}
+
+impl WindProfile {
+    /// Vérifie les vitesses, directions et altitudes du profil.
+    pub fn is_valid(&self) -> bool {
+        let valid = |direction_deg: f64, speed_mps: f64| {
+            direction_deg.is_finite() && WIND_SPEED_RANGE_MPS.contains(&speed_mps)
+        };
+        valid(self.surface.direction_deg, self.surface.speed_mps)
+            && self.layers.iter().all(|l| {
+                l.height_m.is_finite() && l.height_m >= 0.0 && valid(l.direction_deg, l.speed_mps)
+            })
+    }
+
+    /// Vent balistique d'une trajectoire culminant à `max_ordinate_m`.
+    ///
+    /// Moyenne vectorielle des vents des tranches traversées, pondérée par
+    /// leur épaisseur sous la flèche : le vent au sol vaut jusqu'à la première
+    /// couche, chaque couche jusqu'à la suivante.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::environment::{Wind, WindLayer, WindProfile};
+    /// let profile = WindProfile {
+    ///     surface: Wind { direction_deg: 0.0, speed_mps: 2.0 },
+    ///     layers: vec![WindLayer { height_m: 100.0, direction_deg: 0.0, speed_mps: 6.0 }],
+    /// };
+    /// // Flèche de 200 m : moitié au sol, moitié dans la couche
+    /// assert!((profile.ballistic_wind(200.0).speed_mps - 4.0).abs() < 1e-9);
+    /// // Flèche sous la couche : vent au sol seul
+    /// assert!((profile.ballistic_wind(50.0).speed_mps - 2.0).abs() < 1e-9);
+    /// ```
+    pub fn ballistic_wind(&self, max_ordinate_m: f64) -> Wind {
+        let mut slices: Vec<(f64, Wind)> = std::iter::once((0.0, self.surface))
+            .chain(self.layers.iter().map(|l| {
+                (
+                    l.height_m,
+                    Wind {
+                        direction_deg: l.direction_deg,
+                        speed_mps: l.speed_mps,
+                    },
+                )
+            }))
+            .collect();
+        slices.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let (mut east, mut north, mut total) = (0.0, 0.0, 0.0);
+        for (i, (base, wind)) in slices.iter().enumerate() {
+            let top = slices
+                .get(i + 1)
+                .map_or(max_ordinate_m, |(next, _)| next.min(max_ordinate_m));
+            let thickness = top - base;
+            if thickness <= 0.0 {
+                continue;
+            }
+            let from = wind.direction_deg.to_radians();
+            east += wind.speed_mps * from.sin() * thickness;
+            north += wind.speed_mps * from.cos() * thickness;
+            total += thickness;
+        }
+        if total <= 0.0 {
+            return self.surface;
+        }
+        let (east, north) = (east / total, north / total);
+        Wind {
+            direction_deg: east.atan2(north).to_degrees().rem_euclid(360.0),
+            speed_mps: east.hypot(north),
+        }
+    }
+}
+
+/// Conseil d'emploi lié au vent pour une solution.
+#[derive(Clone, Debug, Serialize)]
+pub struct WindAdvice {
+    /// Vent pris en compte
+    pub wind: Wind,
+    /// Composante de face (m/s, positive de face)
+    pub headwind_mps: f64,
+    /// Composante traversière (m/s, positive de gauche à droite)
+    pub crosswind_mps: f64,
+    /// Pour un écran fumigène : `false` si le vent le rend inefficace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smoke_effective: Option<bool>,
+    /// Pour un écran fumigène : point de visée décalé au vent de la cible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upwind_aim: Option<Position>,
+}
+
+/// Évalue l'effet du vent sur l'emploi de la munition de la cible.
+///
+/// Pour la fumée, l'écran est déconseillé par fort vent traversier ou vent
+/// nul, et le point de visée est décalé au vent afin que la fumée dérive sur
+/// la ligne à masquer.
+///
+/// # Arguments
+///
+/// * `target` - Cible ; les conseils fumigènes s'appliquent si sa munition ou
+///   la munition recommandée pour son type est la fumée
+/// * `bearing_deg` - Azimut de tir en degrés
+/// * `wind` - Vent au sol
+pub fn wind_advice(target: &TargetPosition, bearing_deg: f64, wind: &Wind) -> WindAdvice {
+    advice_for(
+        target,
+        target.target_type.suggested_ammo(),
+        bearing_deg,
+        wind,
+    )
+}
+
+/// Comme [`wind_advice`], avec la munition recommandée par la solution.
+fn advice_for(
+    target: &TargetPosition,
+    recommended: AmmoKind,
+    bearing_deg: f64,
+    wind: &Wind,
+) -> WindAdvice {
+    let (headwind_mps, crosswind_mps) = wind.components(bearing_deg);
+    let smoke = target.ammo_type == AmmoKind::Smoke || recommended == AmmoKind::Smoke;
+
+    let upwind_aim = smoke.then(|| {
+        let offset = (wind.speed_mps * SMOKE_DRIFT_TIME_S).min(SMOKE_MAX_OFFSET_M);
+        let from = wind.direction_deg.to_radians();
+        Position::new(
+            format!("{}_VENT", target.name),
+            target.elevation,
+            target.x + offset * from.sin(),
+            target.y + offset * from.cos(),
+        )
+    });
+
+    WindAdvice {
+        wind: *wind,
+        headwind_mps,
+        crosswind_mps,
+        smoke_effective: smoke.then(|| {
+            crosswind_mps.abs() <= SMOKE_MAX_CROSSWIND_MPS && wind.speed_mps >= SMOKE_MIN_WIND_MPS
+        }),
+        upwind_aim,
+    }
+}
+
+/// Renseigne le conseil lié au vent d'une solution et ajoute les avertissements.
+///
+/// La munition recommandée est celle de la solution (voir
+/// [`AmmoPolicy::apply`](crate::AmmoPolicy::apply)).
+pub fn attach_wind_advice(solution: &mut FiringSolution, target: &TargetPosition, wind: &Wind) {
+    let recommended = AmmoKind::from_name(&solution.recommended_ammo)
+        .unwrap_or_else(|| target.target_type.suggested_ammo());
+    let advice = advice_for(target, recommended, solution.azimuth_deg, wind);
+
+    match advice.smoke_effective {
+        Some(false) if advice.crosswind_mps.abs() > SMOKE_MAX_CROSSWIND_MPS => {
+            solution.warnings.push(format!(
+                "Crosswind {:.1} m/s exceeds {:.0} m/s: smoke screen not advised",
+                advice.crosswind_mps.abs(),
+                SMOKE_MAX_CROSSWIND_MPS
+            ))
+        }
+        Some(false) => solution.warnings.push(format!(
+            "Wind below {:.0} m/s: smoke will not drift along the screen line",
+            SMOKE_MIN_WIND_MPS
+        )),
+        _ => {}
+    }
+    if let Some(aim) = &advice.upwind_aim {
+        solution.warnings.push(format!(
+            "Smoke: aim upwind at X={:.0} Y={:.0} so the screen drifts onto the target",
+            aim.x, aim.y
+        ));
+    }
+
+    solution.wind_advice = Some(advice);
+}
+
+/// Correction du vent pour la munition sélectionnée.
+#[derive(Clone, Debug, Serialize)]
+pub struct WindCorrection {
+    /// Vent de l'opération pris en compte
+    pub wind: WindProfile,
+    /// Composante de face du vent balistique par anneau (m/s, positive de face)
+    pub headwind_mps: RingTable,
+    /// Composante traversière du vent balistique par anneau (m/s, positive de
+    /// gauche à droite)
+    pub crosswind_mps: RingTable,
+    /// Correction de portée ajoutée avant la lecture des tables, par anneau
+    /// (m, positive par vent de face)
+    pub range_correction_m: RingTable,
+    /// Correction de gisement par anneau (mils, positive vers la droite)
+    pub deflection_mil: RingTable,
+    /// Gisement grille corrigé du vent par anneau (mils)
+    pub azimuth_mil: RingTable,
+    /// Élévations corrigées par anneau (mils)
+    pub elevations: RingTable,
+}
+
+/// Applique la correction du vent à une solution.
+///
+/// Pour chaque anneau utilisable dont la table donne la durée de trajet, le
+/// vent balistique est calculé sous la flèche de la trajectoire ; le décalage
+/// d'impact qu'il produit est compensé en visant d'autant plus loin (vent de
+/// face) et à contre-vent en gisement. Les élévations de `selected_solution`
+/// sont relues à la portée corrigée ; le gisement de la solution
+/// (`azimuth_deg`) n'est pas modifié, les gisements corrigés sont donnés
+/// dans `wind`. Un anneau sans durée de trajet garde l'élévation de la table.
+///
+/// À appliquer avant [`attach_atmosphere`] et
+/// [`attach_charge_temperature`], qui partent de la portée corrigée du vent.
+pub fn attach_wind_correction(
+    solution: &mut FiringSolution,
+    mortar: &MortarPosition,
+    ballistics: &BallisticTables,
+    profile: &WindProfile,
+) {
+    let ammo = match solution
+        .selected_solution
+        .as_ref()
+        .and_then(|s| AmmoKind::from_name(&s.ammo_type))
+    {
+        Some(ammo) => ammo,
+        None => return,
+    };
+
+    let mut correction = WindCorrection {
+        wind: profile.clone(),
+        headwind_mps: RingTable::default(),
+        crosswind_mps: RingTable::default(),
+        range_correction_m: RingTable::default(),
+        deflection_mil: RingTable::default(),
+        azimuth_mil: RingTable::default(),
+        elevations: RingTable::default(),
+    };
+    for ring in 0..RING_COUNT as Ring {
+        let table = match ballistics.get(&(mortar.weapon, ammo, ring)) {
+            Some(table) if mortar.ring_available(ring) => table,
+            _ => continue,
+        };
+        let range_m = solution.aim_range_m(ring);
+        let tof = match table.time_of_flight_at(mortar.table_range(range_m)) {
+            Some(tof) => tof,
+            None => continue,
+        };
+        let wind = profile.ballistic_wind(max_ordinate_m(tof));
+        let (headwind, crosswind) = wind.components(solution.azimuth_deg);
+        let range_correction = headwind * tof * WIND_DRIFT_FACTOR;
+        let deflection = -mils::deg_to_mil(
+            (crosswind * tof * WIND_DRIFT_FACTOR)
+                .atan2(range_m)
+                .to_degrees(),
+        );
+
+        correction.headwind_mps.set(ring, Some(headwind));
+        correction.crosswind_mps.set(ring, Some(crosswind));
+        correction
+            .range_correction_m
+            .set(ring, Some(range_correction));
+        correction.deflection_mil.set(ring, Some(deflection));
+        correction.azimuth_mil.set(
+            ring,
+            Some(mils::normalize(
+                mils::deg_to_mil(solution.azimuth_deg) + deflection,
+            )),
+        );
+        correction.elevations.set(
+            ring,
+            table.elev_at(mortar.table_range(range_m + range_correction)),
+        );
+    }
+
+    let corrected = correction
+        .elevations
+        .iter()
+        .filter(|(_, e)| e.is_some())
+        .count();
+    if let Some(sel) = solution.selected_solution.as_mut() {
+        for (ring, elevation) in correction.elevations.iter() {
+            if elevation.is_some() {
+                sel.elevations.set(ring, elevation);
+            }
+        }
+    }
+    if corrected > 0 {
+        solution.warnings.push(format!(
+            "Wind {:.1} m/s from {:.0}° applied: elevations read at corrected ranges, use the corrected azimuths",
+            profile.surface.speed_mps, profile.surface.direction_deg
+        ));
+    } else {
+        solution
+            .warnings
+            .push("Wind not corrected: the tables give no time of flight".to_string());
+    }
+
+    solution.wind = Some(correction);
+}
+
+/// Température de l'air au niveau de la mer de l'atmosphère standard (°C).
+pub const STANDARD_AIR_TEMPERATURE_C: f64 = 15.0;
+
+/// Pression au niveau de la mer de l'atmosphère standard (hPa).
+pub const STANDARD_PRESSURE_HPA: f64 = 1013.25;
+
+/// Températures de l'air acceptées (°C).
+pub const AIR_TEMPERATURE_RANGE_C: std::ops::RangeInclusive<f64> = -60.0..=60.0;
+
+/// Pressions au niveau de la pièce acceptées (hPa).
+pub const PRESSURE_RANGE_HPA: std::ops::RangeInclusive<f64> = 500.0..=1100.0;
+
+/// Variation relative de portée par variation relative de la densité de
+/// l'air, en sens inverse : 10 % de densité en moins portent 3 % plus loin.
+pub const DENSITY_RANGE_FACTOR: f64 = 0.3;
+
+/// Conditions de l'air au niveau de la pièce.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Atmosphere {
+    /// Température de l'air (°C)
+    pub air_temperature_c: f64,
+    /// Pression au niveau de la pièce (hPa) ; absente : pression standard à
+    /// l'altitude de la pièce
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure_hpa: Option<f64>,
+}
+
+impl Atmosphere {
+    /// Vérifie la température et la pression.
+    pub fn is_valid(&self) -> bool {
+        AIR_TEMPERATURE_RANGE_C.contains(&self.air_temperature_c)
+            && self
+                .pressure_hpa
+                .is_none_or(|p| PRESSURE_RANGE_HPA.contains(&p))
+    }
+
+    /// Pression retenue à l'altitude `elevation_m` (hPa).
+    pub fn pressure_at(&self, elevation_m: f64) -> f64 {
+        self.pressure_hpa
+            .unwrap_or_else(|| standard_pressure_hpa(elevation_m))
+    }
+
+    /// Densité de l'air rapportée à la densité standard au niveau de la mer.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::environment::Atmosphere;
+    /// let standard = Atmosphere { air_temperature_c: 15.0, pressure_hpa: Some(1013.25) };
+    /// assert!((standard.density_ratio(0.0) - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn density_ratio(&self, elevation_m: f64) -> f64 {
+        let kelvin = |c: f64| c + 273.15;
+        self.pressure_at(elevation_m) / STANDARD_PRESSURE_HPA * kelvin(STANDARD_AIR_TEMPERATURE_C)
+            / kelvin(self.air_temperature_c)
+    }
+}
+
+/// Pression de l'atmosphère standard à une altitude (hPa).
+pub fn standard_pressure_hpa(elevation_m: f64) -> f64 {
+    STANDARD_PRESSURE_HPA * (1.0 - 2.255_77e-5 * elevation_m).powf(5.255_88)
+}
+
+/// Altitude-densité (m) : altitude de l'atmosphère standard de même densité.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::environment::density_altitude_m;
+/// assert!(density_altitude_m(1.0).abs() < 1e-9);
+/// assert!((density_altitude_m(0.9075) - 1000.0).abs() < 5.0);
+/// ```
+pub fn density_altitude_m(density_ratio: f64) -> f64 {
+    44_330.77 * (1.0 - density_ratio.powf(0.234_969))
+}
+
+/// Correction des conditions de l'air pour la munition sélectionnée.
+#[derive(Clone, Debug, Serialize)]
+pub struct AtmosphereCorrection {
+    /// Température de l'air (°C)
+    pub air_temperature_c: f64,
+    /// Pression retenue au niveau de la pièce (hPa)
+    pub pressure_hpa: f64,
+    /// Densité de l'air rapportée à la densité standard
+    pub density_ratio: f64,
+    /// Altitude-densité (m)
+    pub density_altitude_m: f64,
+    /// Variation relative de portée due à la densité (positive : le coup porte plus loin)
+    pub range_factor: f64,
+    /// Correction de portée ajoutée avant la lecture des tables, par anneau
+    /// (m, négative lorsque l'air peu dense allonge le tir)
+    pub range_correction_m: RingTable,
+    /// Élévations corrigées par anneau (mils)
+    pub elevations: RingTable,
+}
+
+/// Applique la correction des conditions de l'air à une solution.
+///
+/// Les tables supposent l'atmosphère standard au niveau de la mer ; la
+/// portée réelle vaut `portée_table × (1 + k × (1 − densité))`, avec
+/// `k` = [`DENSITY_RANGE_FACTOR`]. La portée de chaque anneau utilisable est
+/// corrigée avant la lecture de la table et les élévations de
+/// `selected_solution` sont remplacées par les élévations corrigées.
+///
+/// À appliquer après [`attach_wind_correction`] et avant
+/// [`attach_charge_temperature`].
+pub fn attach_atmosphere(
+    solution: &mut FiringSolution,
+    mortar: &MortarPosition,
+    ballistics: &BallisticTables,
+    atmosphere: &Atmosphere,
+) {
+    let ammo = match solution
+        .selected_solution
+        .as_ref()
+        .and_then(|s| AmmoKind::from_name(&s.ammo_type))
+    {
+        Some(ammo) => ammo,
+        None => return,
+    };
+
+    let density_ratio = atmosphere.density_ratio(mortar.elevation);
+    let range_factor = DENSITY_RANGE_FACTOR * (1.0 - density_ratio);
+    let mut range_correction_m = RingTable::default();
+    let mut elevations = RingTable::default();
+    for ring in 0..RING_COUNT as Ring {
+        let table = match ballistics.get(&(mortar.weapon, ammo, ring)) {
+            Some(table) if mortar.ring_available(ring) => table,
+            _ => continue,
+        };
+        let range_m = solution.aim_range_m(ring);
+        let correction = range_m / (1.0 + range_factor) - range_m;
+        range_correction_m.set(ring, Some(correction));
+        elevations.set(
+            ring,
+            table.elev_at(mortar.table_range(range_m + correction)),
+        );
+    }
+
+    if let Some(sel) = solution.selected_solution.as_mut() {
+        for (ring, elevation) in elevations.iter() {
+            if elevation.is_some() {
+                sel.elevations.set(ring, elevation);
+            }
+        }
+    }
+    let density_altitude_m = density_altitude_m(density_ratio);
+    solution.warnings.push(format!(
+        "Air {:.0} °C, density altitude {:.0} m applied: range {:+.1} %",
+        atmosphere.air_temperature_c,
+        density_altitude_m,
+        range_factor * 100.0
+    ));
+
+    solution.atmosphere = Some(AtmosphereCorrection {
+        air_temperature_c: atmosphere.air_temperature_c,
+        pressure_hpa: atmosphere.pressure_at(mortar.elevation),
+        density_ratio,
+        density_altitude_m,
+        range_factor,
+        range_correction_m,
+        elevations,
+    });
+}
+
+/// Facteurs de correction de portée selon la température des charges.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChargeTemperatureTable {
+    /// Température de référence des tables (°C)
+    pub standard_c: f64,
+    /// Variation relative de portée par °C au-dessus de la référence,
+    /// par (munition, anneau)
+    pub factors: BTreeMap<(AmmoKind, Ring), f64>,
+    /// Variation des munitions et anneaux absents de `factors`
+    pub default_factor: Option<f64>,
+}
+
+impl Default for ChargeTemperatureTable {
+    fn default() -> Self {
+        ChargeTemperatureTable {
+            standard_c: STANDARD_CHARGE_TEMPERATURE_C,
+            factors: BTreeMap::new(),
+            default_factor: None,
+        }
+    }
+}
+
+impl ChargeTemperatureTable {
+    /// Retourne la variation relative de portée par °C d'une munition à un
+    /// anneau, ou `None` si aucun facteur n'est défini.
+    pub fn factor(&self, ammo: AmmoKind, ring: Ring) -> Option<f64> {
+        self.factors
+            .get(&(ammo, ring))
+            .copied()
+            .or(self.default_factor)
+    }
+
+    /// Retourne la portée de table à viser pour atteindre `range_m` avec des
+    /// charges à `temperature_c`, ou `None` si aucun facteur n'est défini.
+    ///
+    /// Avec un facteur `k`, la portée réelle vaut
+    /// `portée_table × (1 + k × (T − T_ref))` ; la portée visée est donc
+    /// `range_m / (1 + k × (T − T_ref))`.
+    pub fn aim_range(
+        &self,
+        ammo: AmmoKind,
+        ring: Ring,
+        range_m: f64,
+        temperature_c: f64,
+    ) -> Option<f64> {
+        let k = self.factor(ammo, ring)?;
+        let scale = 1.0 + k * (temperature_c - self.standard_c);
+        (scale > 0.0).then(|| range_m / scale)
+    }
+}
+
+/// Correction de température des charges pour la munition sélectionnée.
+#[derive(Clone, Debug, Serialize)]
+pub struct ChargeTemperatureCorrection {
+    /// Température des charges (°C)
+    pub temperature_c: f64,
+    /// Température de référence des tables (°C)
+    pub standard_c: f64,
+    /// Correction de portée à appliquer par anneau (m, négative pour des charges chaudes)
+    pub range_correction_m: RingTable,
+    /// Élévations corrigées par anneau (mils)
+    pub elevations: RingTable,
+}
+
+/// Applique la correction de température des charges à une solution.
+///
+/// La portée de chaque anneau utilisable est corrigée avant la lecture de la
+/// table : les élévations de `selected_solution` sont remplacées par les
+/// élévations corrigées des anneaux dotés d'un facteur, et la correction est
+/// rappelée dans `charge_temperature`. Un anneau sans facteur garde
+/// l'élévation de la table.
+pub fn attach_charge_temperature(
+    solution: &mut FiringSolution,
+    mortar: &MortarPosition,
+    ballistics: &BallisticTables,
+    table: &ChargeTemperatureTable,
+    temperature_c: f64,
+) {
+    let ammo = match solution
+        .selected_solution
+        .as_ref()
+        .and_then(|s| AmmoKind::from_name(&s.ammo_type))
+    {
+        Some(ammo) => ammo,
+        None => return,
+    };
+
+    let mut range_correction_m = RingTable::default();
+    let mut elevations = RingTable::default();
+    for ring in 0..RING_COUNT as Ring {
+        // Portée de table corrigée du réglage, du vent, de l'air et du
+        // rendement des charges de la pièce
+        let base_m = mortar.table_range(solution.aim_range_m(ring));
+        let aim = mortar
+            .ring_available(ring)
+            .then(|| table.aim_range(ammo, ring, base_m, temperature_c))
+            .flatten();
+        range_correction_m.set(ring, aim.map(|r| r - base_m));
+        elevations.set(
+            ring,
+            aim.and_then(|r| ballistics.get(&(mortar.weapon, ammo, ring))?.elev_at(r)),
+        );
+    }
+
+    let corrected = elevations.iter().filter(|(_, e)| e.is_some()).count();
+    if let Some(sel) = solution.selected_solution.as_mut() {
+        for (ring, elevation) in elevations.iter() {
+            if elevation.is_some() {
+                sel.elevations.set(ring, elevation);
+            }
+        }
+    }
+    if corrected > 0 {
+        solution.warnings.push(format!(
+            "Charge temperature {:.0} °C applied ({:+.0} °C from standard): elevations read at corrected ranges",
+            temperature_c,
+            temperature_c - table.standard_c
+        ));
+    }
+
+    solution.charge_temperature = Some(ChargeTemperatureCorrection {
+        temperature_c,
+        standard_c: table.standard_c,
+        range_correction_m,
+        elevations,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TargetType;
+
+    fn smoke_target() -> TargetPosition {
+        TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Soutien,
+            AmmoKind::Smoke,
+        )
+    }
+
+    #[test]
+    fn smoke_is_discouraged_in_strong_crosswind_and_aimed_upwind() {
+        let target = smoke_target();
+
+        // Vent d'Est de 10 m/s, tir plein Nord : vent traversier fort
+        let advice = wind_advice(
+            &target,
+            0.0,
+            &Wind {
+                direction_deg: 90.0,
+                speed_mps: 10.0,
+            },
+        );
+        assert!((advice.crosswind_mps + 10.0).abs() < 1e-9);
+        assert_eq!(advice.smoke_effective, Some(false));
+        let aim = advice.upwind_aim.unwrap();
+        assert!((aim.x - SMOKE_MAX_OFFSET_M).abs() < 1e-9);
+        assert!((aim.y - 1000.0).abs() < 1e-9);
+
+        // Vent de face modéré : écran efficace, visée décalée vers le Nord
+        let advice = wind_advice(
+            &target,
+            0.0,
+            &Wind {
+                direction_deg: 0.0,
+                speed_mps: 4.0,
+            },
+        );
+        assert_eq!(advice.smoke_effective, Some(true));
+        assert!((advice.headwind_mps - 4.0).abs() < 1e-9);
+        assert!((advice.upwind_aim.unwrap().y - 1060.0).abs() < 1e-9);
+
+        let he = TargetPosition::new(
+            "T2".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let advice = wind_advice(
+            &he,
+            0.0,
+            &Wind {
+                direction_deg: 90.0,
+                speed_mps: 10.0,
+            },
+        );
+        assert_eq!(advice.smoke_effective, None);
+        assert!(advice.upwind_aim.is_none());
+    }
+
+    #[test]
+    fn wind_is_compensated_before_the_table_lookup() {
+        use crate::{
+            calculate_solution_with_dispersion, BallisticPoint, BallisticTable, DispersionTable,
+            WeaponSystem,
+        };
+        let point = |range_m, elev_mil| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: Some(20.0),
+            delta_elev_per_100m_mil: None,
+        };
+        let table = BallisticTable {
+            points: vec![point(0.0, 1600.0), point(2000.0, 800.0)],
+            ..Default::default()
+        };
+        let ballistics = BallisticTables::from([((WeaponSystem::M224, AmmoKind::He, 2), table)]);
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let solve = |wind: Wind| {
+            let mut solution = calculate_solution_with_dispersion(
+                &mortar,
+                &target,
+                &ballistics,
+                &DispersionTable::new(),
+            );
+            attach_wind_correction(&mut solution, &mortar, &ballistics, &wind.into());
+            solution
+        };
+
+        // Vent de face de 4 m/s pendant 20 s : viser 40 m plus loin
+        let solution = solve(Wind {
+            direction_deg: 0.0,
+            speed_mps: 4.0,
+        });
+        let correction = solution.wind.as_ref().unwrap();
+        assert!((correction.range_correction_m.get(2).unwrap() - 40.0).abs() < 1e-9);
+        assert!(correction.deflection_mil.get(2).unwrap().abs() < 1e-9);
+        let elevation = solution.selected_solution.unwrap().elevations.get(2);
+        assert!((elevation.unwrap() - 1184.0).abs() < 1e-9);
+
+        // Vent d'Ouest : l'impact dérive à droite, viser à gauche
+        let solution = solve(Wind {
+            direction_deg: 270.0,
+            speed_mps: 5.0,
+        });
+        let correction = solution.wind.unwrap();
+        assert!(correction.range_correction_m.get(2).unwrap().abs() < 1e-9);
+        let expected = -mils::rad_to_mil(50.0f64.atan2(1000.0));
+        assert!((correction.deflection_mil.get(2).unwrap() - expected).abs() < 1e-9);
+        assert!((correction.azimuth_mil.get(2).unwrap() - mils::normalize(expected)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn thin_air_is_aimed_short() {
+        use crate::{
+            calculate_solution_with_dispersion, BallisticPoint, BallisticTable, DispersionTable,
+            WeaponSystem,
+        };
+        let point = |range_m, elev_mil| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: None,
+            delta_elev_per_100m_mil: None,
+        };
+        let table = BallisticTable {
+            points: vec![point(0.0, 1600.0), point(2000.0, 800.0)],
+            ..Default::default()
+        };
+        let ballistics = BallisticTables::from([((WeaponSystem::M224, AmmoKind::He, 2), table)]);
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let mut solution = calculate_solution_with_dispersion(
+            &mortar,
+            &target,
+            &ballistics,
+            &DispersionTable::new(),
+        );
+
+        // Air chaud et basse pression : densité 0,83, le coup porte 5 % plus loin
+        let atmosphere = Atmosphere {
+            air_temperature_c: 35.0,
+            pressure_hpa: Some(900.0),
+        };
+        attach_atmosphere(&mut solution, &mortar, &ballistics, &atmosphere);
+        let correction = solution.atmosphere.as_ref().unwrap();
+        let density = 900.0 / 1013.25 * 288.15 / 308.15;
+        assert!((correction.density_ratio - density).abs() < 1e-12);
+        assert!(correction.density_altitude_m > 1500.0);
+        let aim = 1000.0 / (1.0 + DENSITY_RANGE_FACTOR * (1.0 - density));
+        assert!((correction.range_correction_m.get(2).unwrap() - (aim - 1000.0)).abs() < 1e-9);
+        assert!((solution.aim_range_m(2) - aim).abs() < 1e-9);
+        let elevation = solution.selected_solution.unwrap().elevations.get(2);
+        assert!((elevation.unwrap() - (1600.0 - 0.4 * aim)).abs() < 1e-9);
+
+        // Sans pression, pression standard à l'altitude de la pièce
+        let standard = Atmosphere {
+            air_temperature_c: 15.0 - 0.0065 * 1000.0,
+            pressure_hpa: None,
+        };
+        assert!((standard.density_ratio(1000.0) - 0.9075).abs() < 1e-3);
+    }
+
+    #[test]
+    fn hot_charges_are_aimed_short() {
+        let mut table = ChargeTemperatureTable::default();
+        table.factors.insert((AmmoKind::He, 2), 0.001);
+
+        // +20 °C : la charge porte 2 % plus loin
+        let aim = table.aim_range(AmmoKind::He, 2, 1020.0, 41.0).unwrap();
+        assert!((aim - 1000.0).abs() < 1e-9);
+        assert!(table.aim_range(AmmoKind::He, 1, 1000.0, 41.0).is_none());
+
+        let cold = table.aim_range(AmmoKind::He, 2, 1000.0, 1.0).unwrap();
+        assert!(cold > 1000.0);
+
+        // Facteur par défaut pour les anneaux non listés
+        table.default_factor = Some(0.002);
+        assert_eq!(table.factor(AmmoKind::He, 2), Some(0.001));
+        assert_eq!(table.factor(AmmoKind::Smoke, 3), Some(0.002));
+    }
+
+    #[test]
+    fn charge_temperature_replaces_the_selected_elevations() {
+        use crate::{
+            calculate_solution_with_dispersion, BallisticPoint, BallisticTable, DispersionTable,
+            TargetType, WeaponSystem,
+        };
+        let point = |range_m, elev_mil| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: None,
+            delta_elev_per_100m_mil: None,
+        };
+        let table = BallisticTable {
+            points: vec![point(0.0, 1600.0), point(2000.0, 800.0)],
+            ..Default::default()
+        };
+        let ballistics = BallisticTables::from([((WeaponSystem::M224, AmmoKind::He, 2), table)]);
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1020.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let mut solution = calculate_solution_with_dispersion(
+            &mortar,
+            &target,
+            &ballistics,
+            &DispersionTable::new(),
+        );
+        let table_elevation = solution
+            .selected_solution
+            .as_ref()
+            .unwrap()
+            .elevations
+            .get(2);
+
+        let temperatures = ChargeTemperatureTable {
+            default_factor: Some(0.001),
+            ..Default::default()
+        };
+        attach_charge_temperature(&mut solution, &mortar, &ballistics, &temperatures, 41.0);
+
+        // Visée à 1000 m : élévation de la table à 1000 m
+        let elevation = solution
+            .selected_solution
+            .as_ref()
+            .unwrap()
+            .elevations
+            .get(2);
+        assert!((elevation.unwrap() - 1200.0).abs() < 1e-9);
+        assert!(elevation > table_elevation);
+        let correction = solution.charge_temperature.unwrap();
+        assert!((correction.range_correction_m.get(2).unwrap() + 20.0).abs() < 1e-9);
+        assert!(solution.warnings[0].contains("+20 °C"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Bus d'événements de l'état.
+//!
+//! Chaque modification de l'état (ajout, suppression, mise à jour, correction,
+//! calcul) est publiée sous forme de [`StateEvent`]. Les intégrations
+//! (WebSocket, SSE, MQTT, applications embarquant la bibliothèque) s'abonnent
+//! au même flux via [`EventBus::subscribe`].
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::reference::ReferencePoint;
+use crate::safety::FriendlyPosition;
+use crate::timeline::ScheduledMission;
+use crate::{CorrectionRecord, MortarPosition, TargetPosition};
+
+/// Nombre d'événements conservés pour un abonné en retard.
+pub const EVENT_BUS_CAPACITY: usize = 256;
+
+/// Événement émis lors d'une modification de l'état.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StateEvent {
+    /// Mortier ajouté
+    MortarAdded { mortar: MortarPosition },
+    /// Mortier modifié
+    MortarUpdated { mortar: MortarPosition },
+    /// Mortier supprimé
+    MortarRemoved { name: String },
+    /// Cible ajoutée
+    TargetAdded { target: TargetPosition },
+    /// Cible modifiée (type, munition, renseignement...)
+    TargetUpdated { target: TargetPosition },
+    /// Cible supprimée
+    TargetRemoved { name: String },
+    /// Position amie ajoutée
+    FriendlyAdded { friendly: FriendlyPosition },
+    /// Position amie déplacée
+    FriendlyUpdated { friendly: FriendlyPosition },
+    /// Position amie supprimée
+    FriendlyRemoved { name: String },
+    /// Point de référence ajouté
+    ReferencePointAdded { name: String, point: ReferencePoint },
+    /// Point de référence redéfini
+    ReferencePointUpdated { name: String, point: ReferencePoint },
+    /// Point de référence supprimé
+    ReferencePointRemoved { name: String },
+    /// Mission programmée ou reprogrammée
+    MissionScheduled {
+        name: String,
+        mission: ScheduledMission,
+    },
+    /// Mission retirée du programme (supprimée ou close)
+    MissionRemoved { name: String },
+    /// Correction appliquée à une cible
+    TargetCorrected {
+        /// Cible d'origine
+        original: String,
+        /// Cible corrigée (identique à `original` pour une correction sur place)
+        corrected: String,
+        /// Décalage appliqué
+        correction: CorrectionRecord,
+    },
+    /// Solution calculée ou tirée
+    SolutionCalculated {
+        mortar: String,
+        target: String,
+        fired: bool,
+    },
+    /// État restauré depuis un instantané
+    StateRestored { mortars: usize, targets: usize },
+}
+
+/// Bus de diffusion des événements de l'état.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::events::{EventBus, StateEvent};
+///
+/// let bus = EventBus::new();
+/// let mut rx = bus.subscribe();
+/// bus.emit(StateEvent::MortarRemoved { name: "M1".into() });
+/// assert!(matches!(rx.try_recv(), Ok(StateEvent::MortarRemoved { .. })));
+/// ```
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<StateEvent>,
+}
+
+impl EventBus {
+    /// Crée un bus avec la capacité par défaut.
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        EventBus { sender }
+    }
+
+    /// S'abonne au flux d'événements.
+    ///
+    /// Seuls les événements émis après l'abonnement sont reçus.
+    pub fn subscribe(&self) -> broadcast::Receiver<StateEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publie un événement ; sans abonné, l'événement est ignoré.
+    pub fn emit(&self, event: StateEvent) {
+        let _ = self.sender.send(event);
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::new()
+    }
+}
@@ -0,0 +1,1121 @@
+//! Procédures de tir : coup de réglage (spotting round) et zone battue.
+//!
+//! Enchaîne un coup de réglage (réel ou simulé), l'observation de l'impact,
+//! la correction de la cible et la recommandation de passer au tir d'efficacité.
+//! Permet aussi de simuler la gerbe d'impacts attendue avant d'engager des coups,
+//! d'estimer la probabilité d'atteinte d'un anneau et de préparer le tableau
+//! de tir d'une salve de tir d'efficacité.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::safety::DangerClose;
+use crate::simulation::{
+    sample_impact, scatter_within, Budget, Impact, SimRng, TrainingOffset, BUDGET_CHECK_INTERVAL,
+    CEP_PER_SIGMA,
+};
+use crate::{
+    calculate_solution_with_dispersion, firing_data_correction, AmmoKind, BallisticTable,
+    BallisticTables, CorrectionRecord, Declination, Dispersion, DispersionTable,
+    FiringDataCorrection, FiringSolution, IncrementTable, MortarPosition, Ring, TargetPosition,
+};
+
+/// Tolérance par défaut (mètres) pour passer au tir d'efficacité
+/// lorsque la dispersion de l'anneau est inconnue.
+pub const DEFAULT_FFE_TOLERANCE_M: f64 = 50.0;
+
+/// Nombre maximal d'impacts simulés par requête de zone battue.
+pub const MAX_SCATTER_IMPACTS: usize = 2000;
+
+/// Nombre maximal de tirages d'une estimation de probabilité d'atteinte.
+pub const MAX_SIMULATED_IMPACTS: usize = 20_000;
+
+/// Nombre maximal d'impacts rendus avec une estimation de probabilité d'atteinte.
+pub const MAX_RETURNED_IMPACTS: usize = MAX_SCATTER_IMPACTS;
+
+/// Probabilité d'au moins une atteinte visée par le nombre de coups conseillé.
+pub const HIT_CONFIDENCE: f64 = 0.9;
+
+/// Intervalle par défaut (s) entre deux coups d'un même tube dans une salve.
+pub const DEFAULT_ROUND_INTERVAL_S: f64 = 5.0;
+
+/// Nombre maximal de coups (tubes × coups par tube) d'une salve.
+pub const MAX_VOLLEY_ROUNDS: usize = 500;
+
+/// Déviation observée d'un impact par rapport à la cible.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpotObservation {
+    /// Nord (négatif) / Sud (positif), en mètres
+    pub vertical_m: f64,
+    /// Ouest (négatif) / Est (positif), en mètres
+    pub horizontal_m: f64,
+}
+
+/// Résultat d'un coup de réglage.
+#[derive(Clone, Debug, Serialize)]
+pub struct SpotReport {
+    /// Munition tirée
+    pub ammo_type: String,
+    /// Anneau utilisé
+    pub ring: Ring,
+    /// `true` si l'impact a été simulé
+    pub simulated: bool,
+    /// Déviation observée (ou simulée)
+    pub deviation: SpotObservation,
+    /// Point d'impact (observé ou simulé)
+    pub impact: Impact,
+    /// Durée de trajet en secondes, si la table la fournit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_of_flight_s: Option<f64>,
+    /// Distance entre l'impact et la cible en mètres
+    pub miss_distance_m: f64,
+    /// Écart toléré pour passer au tir d'efficacité
+    pub tolerance_m: f64,
+    /// Correction équivalente en données de tir
+    pub gun_correction: FiringDataCorrection,
+    /// Correction appliquée à la cible
+    pub correction: CorrectionRecord,
+    /// Solution recalculée sur la cible corrigée
+    pub solution: FiringSolution,
+    /// `true` si l'impact est dans la tolérance
+    pub fire_for_effect: bool,
+    /// Recommandation lisible
+    pub recommendation: String,
+}
+
+/// Exécute un coup de réglage et corrige la cible sur place.
+///
+/// Si `observation` est `None`, l'impact est simulé selon la dispersion de
+/// l'anneau. La cible est déplacée à l'opposé de l'impact sur la carte (Y
+/// vers le Nord), la correction est ajoutée à son historique et la solution
+/// est recalculée. Le tir d'efficacité est recommandé lorsque
+/// l'impact tombe dans la dispersion ajustée de l'anneau.
+///
+/// Avec un `offset` d'entraînement, l'impact simulé est décalé du point visé
+/// et la déviation est mesurée depuis la position initiale de la cible (avant
+/// ses corrections) : les corrections successives compensent l'erreur.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si aucun anneau ne couvre la portée ou si l'anneau
+/// demandé n'a pas de table balistique.
+#[allow(clippy::too_many_arguments)]
+pub fn spot_round(
+    mortar: &MortarPosition,
+    target: &mut TargetPosition,
+    ballistics: &BallisticTables,
+    dispersions: &DispersionTable,
+    ring: Option<Ring>,
+    observation: Option<SpotObservation>,
+    offset: Option<TrainingOffset>,
+    rng: &mut SimRng,
+) -> Result<SpotReport> {
+    let ammo = target.ammo_type;
+    let (ring, table) = select_ring(mortar, target, ballistics, ring)?;
+    let tolerance_m = ring_dispersion(mortar, target, dispersions, ring);
+
+    let (deviation, impact, simulated) = match observation {
+        Some(obs) => {
+            let impact = Impact {
+                x: target.x + obs.horizontal_m,
+                y: target.y - obs.vertical_m,
+            };
+            (obs, impact, false)
+        }
+        None => {
+            // Avec une erreur d'entraînement, la déviation est mesurée depuis
+            // la position initiale de la cible
+            let (aim_x, aim_y, true_x, true_y) = match offset {
+                Some(o) => {
+                    let (x0, y0) = target
+                        .corrections
+                        .first()
+                        .map_or((target.x, target.y), |c| (c.from_x, c.from_y));
+                    (target.x + o.east_m, target.y + o.north_m, x0, y0)
+                }
+                None => (target.x, target.y, target.x, target.y),
+            };
+            let impact = sample_impact(rng, aim_x, aim_y, tolerance_m);
+            let obs = SpotObservation {
+                vertical_m: -(impact.y - true_y),
+                horizontal_m: impact.x - true_x,
+            };
+            (obs, impact, true)
+        }
+    };
+
+    let distance_m = mortar.as_position().distance_to(&target.as_position());
+    let time_of_flight_s = table.time_of_flight_at(mortar.table_range(distance_m));
+    let miss_distance_m = deviation.vertical_m.hypot(deviation.horizontal_m);
+    let gun_correction = firing_data_correction(
+        mortar,
+        target,
+        table,
+        deviation.vertical_m,
+        deviation.horizontal_m,
+    );
+    // Y vers le Nord : le point visé est déplacé à l'opposé de l'impact,
+    // vers le Sud pour un coup tombé au Nord (déviation verticale négative)
+    let correction = CorrectionRecord {
+        vertical_m: deviation.vertical_m,
+        horizontal_m: deviation.horizontal_m,
+        from_x: target.x,
+        from_y: target.y,
+        to_x: target.x - deviation.horizontal_m,
+        to_y: target.y + deviation.vertical_m,
+    };
+    target.x = correction.to_x;
+    target.y = correction.to_y;
+    target.corrections.push(correction.clone());
+    let solution = calculate_solution_with_dispersion(mortar, target, ballistics, dispersions);
+
+    let fire_for_effect = miss_distance_m <= tolerance_m;
+    let recommendation = if fire_for_effect {
+        format!(
+            "FIRE FOR EFFECT: impact {:.0} m from target, within {:.0} m",
+            miss_distance_m, tolerance_m
+        )
+    } else {
+        format!(
+            "ADJUST FIRE: impact {:.0} m from target (tolerance {:.0} m), fire another spotting round",
+            miss_distance_m, tolerance_m
+        )
+    };
+
+    Ok(SpotReport {
+        ammo_type: ammo.as_str().to_string(),
+        ring,
+        simulated,
+        deviation,
+        impact,
+        time_of_flight_s,
+        miss_distance_m,
+        tolerance_m,
+        gun_correction,
+        correction,
+        solution,
+        fire_for_effect,
+        recommendation,
+    })
+}
+
+/// Gerbe d'impacts simulée autour d'une cible.
+#[derive(Clone, Debug, Serialize)]
+pub struct ScatterReport {
+    /// Munition simulée
+    pub ammo_type: String,
+    /// Anneau utilisé
+    pub ring: Ring,
+    /// Point visé X en mètres
+    pub aim_x: f64,
+    /// Point visé Y en mètres
+    pub aim_y: f64,
+    /// Dispersion (CEP) utilisée en mètres
+    pub cep_m: f64,
+    /// Points d'impact simulés
+    pub impacts: Vec<Impact>,
+    /// Nombre d'impacts demandés
+    pub requested: usize,
+    /// `true` si le budget de calcul a été épuisé avant la fin (résultat partiel)
+    pub truncated: bool,
+}
+
+/// Simule `count` impacts sur une cible pour visualiser la zone battue.
+///
+/// L'anneau est choisi comme pour [`spot_round`]; la dispersion est celle de
+/// l'anneau ajustée au dénivelé (ou [`DEFAULT_FFE_TOLERANCE_M`] si inconnue).
+/// Un `offset` d'entraînement décale la gerbe du point visé. Le tirage
+/// s'arrête si le `budget` est épuisé : le rapport est alors partiel.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si aucun anneau ne couvre la portée, si l'anneau
+/// demandé n'a pas de table balistique ou si `count` dépasse
+/// [`MAX_SCATTER_IMPACTS`].
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_scatter(
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    ballistics: &BallisticTables,
+    dispersions: &DispersionTable,
+    ring: Option<Ring>,
+    count: usize,
+    offset: Option<TrainingOffset>,
+    rng: &mut SimRng,
+    budget: &Budget,
+) -> Result<ScatterReport> {
+    if count > MAX_SCATTER_IMPACTS {
+        bail!("count must not exceed {}", MAX_SCATTER_IMPACTS);
+    }
+    let (ring, _) = select_ring(mortar, target, ballistics, ring)?;
+    let cep_m = ring_dispersion(mortar, target, dispersions, ring);
+    let impacts = scatter_within(
+        rng,
+        target.x + offset.map_or(0.0, |o| o.east_m),
+        target.y + offset.map_or(0.0, |o| o.north_m),
+        cep_m,
+        count,
+        budget,
+    );
+
+    Ok(ScatterReport {
+        ammo_type: target.ammo_type.as_str().to_string(),
+        ring,
+        aim_x: target.x,
+        aim_y: target.y,
+        cep_m,
+        truncated: impacts.len() < count,
+        requested: count,
+        impacts,
+    })
+}
+
+/// Rayons contenant une part des impacts simulés, autour du point visé.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct PercentileRadii {
+    /// Rayon contenant 50 % des impacts (m)
+    pub p50_m: f64,
+    /// Rayon contenant 90 % des impacts (m)
+    pub p90_m: f64,
+    /// Rayon contenant 99 % des impacts (m)
+    pub p99_m: f64,
+}
+
+/// Estimation par tirages de la probabilité d'atteinte d'une cible.
+#[derive(Clone, Debug, Serialize)]
+pub struct HitProbability {
+    /// Munition de la solution
+    pub ammo_type: String,
+    /// Anneau simulé
+    pub ring: Ring,
+    /// Rayon de la cible (m)
+    pub target_radius_m: f64,
+    /// Écarts probables en portée et en direction utilisés (m)
+    pub dispersion: Dispersion,
+    /// Part des impacts tombés dans le rayon de la cible
+    pub hit_probability: f64,
+    /// Coups nécessaires pour atteindre au moins une fois la cible avec une
+    /// probabilité de [`HIT_CONFIDENCE`] (absent si aucun impact n'atteint)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rounds_for_confidence: Option<u32>,
+    /// Rayons contenant 50, 90 et 99 % des impacts
+    pub radii: PercentileRadii,
+    /// Premiers impacts tirés (au plus [`MAX_RETURNED_IMPACTS`]), en mètres
+    /// vers l'Est (`x`) et le Nord (`y`) depuis le point visé
+    pub impacts: Vec<Impact>,
+    /// Nombre de tirages demandés
+    pub requested: usize,
+    /// Nombre de tirages effectués
+    pub samples: usize,
+    /// `true` si le budget de calcul a été épuisé avant la fin (estimation partielle)
+    pub truncated: bool,
+    /// Amis menacés par la solution simulée
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub danger_close: Option<DangerClose>,
+}
+
+/// Estime par tirages la probabilité qu'un coup tombe à moins de
+/// `target_radius_m` du point visé, pour un anneau de la solution.
+///
+/// Les impacts suivent l'ellipse de dispersion de la solution : écart en
+/// portée le long de la ligne pièce-but, écart en direction perpendiculaire.
+/// Comme pour la gerbe simulée ([`simulate_scatter`]), chaque écart est lu
+/// comme le CEP d'une loi normale de même écart-type ; une dispersion
+/// circulaire donne donc la même gerbe. Le tirage s'arrête si le `budget` est
+/// épuisé : l'estimation porte alors sur les impacts déjà tirés.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le rayon n'est pas strictement positif, si `count`
+/// est nul ou dépasse [`MAX_SIMULATED_IMPACTS`], ou si la solution ne donne
+/// pas de dispersion pour l'anneau.
+pub fn simulate_impacts(
+    solution: &FiringSolution,
+    ring: Ring,
+    target_radius_m: f64,
+    count: usize,
+    rng: &mut SimRng,
+    budget: &Budget,
+) -> Result<HitProbability> {
+    if !(target_radius_m.is_finite() && target_radius_m > 0.0) {
+        bail!("target radius must be positive");
+    }
+    if count == 0 || count > MAX_SIMULATED_IMPACTS {
+        bail!("count must be between 1 and {}", MAX_SIMULATED_IMPACTS);
+    }
+    let Some(selected) = &solution.selected_solution else {
+        bail!("Solution has no selected ammo");
+    };
+    let dispersion = match &selected.dispersion_ellipse {
+        Some(ellipse) => ellipse
+            .range_pe_m
+            .get(ring)
+            .zip(ellipse.deflection_pe_m.get(ring))
+            .map(|(range_pe_m, deflection_pe_m)| Dispersion {
+                range_pe_m,
+                deflection_pe_m,
+            }),
+        None => selected.dispersions.get(ring).map(Dispersion::circular),
+    };
+    let Some(dispersion) = dispersion else {
+        bail!(
+            "No dispersion data for {} at ring {}R",
+            selected.ammo_type,
+            ring
+        );
+    };
+
+    let (sin, cos) = solution.azimuth_deg.to_radians().sin_cos();
+    let sigma_range = dispersion.range_pe_m.max(0.0) / CEP_PER_SIGMA;
+    let sigma_deflection = dispersion.deflection_pe_m.max(0.0) / CEP_PER_SIGMA;
+    let mut impacts = Vec::new();
+    let mut misses_m = Vec::with_capacity(count);
+    while misses_m.len() < count {
+        let chunk = (count - misses_m.len()).min(BUDGET_CHECK_INTERVAL);
+        for _ in 0..chunk {
+            let along = rng.gaussian() * sigma_range;
+            let across = rng.gaussian() * sigma_deflection;
+            let impact = Impact {
+                x: along * sin + across * cos,
+                y: along * cos - across * sin,
+            };
+            misses_m.push(impact.x.hypot(impact.y));
+            if impacts.len() < MAX_RETURNED_IMPACTS {
+                impacts.push(impact);
+            }
+        }
+        if budget.is_exhausted() {
+            break;
+        }
+    }
+
+    let samples = misses_m.len();
+    let hits = misses_m.iter().filter(|&&d| d <= target_radius_m).count();
+    let hit_probability = hits as f64 / samples as f64;
+    let rounds_for_confidence = if hit_probability >= 1.0 {
+        Some(1)
+    } else if hit_probability > 0.0 {
+        let rounds = ((1.0 - HIT_CONFIDENCE).ln() / (1.0 - hit_probability).ln()).ceil();
+        Some(rounds.min(f64::from(u32::MAX)) as u32)
+    } else {
+        None
+    };
+    misses_m.sort_by(f64::total_cmp);
+    let percentile =
+        |p: f64| misses_m[((p * samples as f64).ceil() as usize).clamp(1, samples) - 1];
+
+    Ok(HitProbability {
+        ammo_type: selected.ammo_type.clone(),
+        ring,
+        target_radius_m,
+        dispersion,
+        hit_probability,
+        rounds_for_confidence,
+        radii: PercentileRadii {
+            p50_m: percentile(0.5),
+            p90_m: percentile(0.9),
+            p99_m: percentile(0.99),
+        },
+        impacts,
+        requested: count,
+        samples,
+        truncated: samples < count,
+        danger_close: solution.danger_close.clone(),
+    })
+}
+
+/// Ligne du tableau de tir d'une salve.
+#[derive(Clone, Debug, Serialize)]
+pub struct VolleyRound {
+    /// Numéro du tube (à partir de 1)
+    pub tube: u32,
+    /// Numéro du coup pour ce tube (à partir de 1)
+    pub round: u32,
+    /// Anneau
+    pub ring: Ring,
+    /// Gisement en mils
+    pub deflection_mil: f64,
+    /// Élévation en mils
+    pub elevation_mil: f64,
+    /// Heure de départ du coup (s depuis le commandement « feu »)
+    pub fire_time_s: f64,
+    /// Heure d'impact (s depuis le commandement « feu »), si la durée de
+    /// trajet est connue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impact_time_s: Option<f64>,
+}
+
+/// Consommation de munitions pour une munition et un anneau : obus et
+/// charges additionnelles (incréments), comptés séparément.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Expenditure {
+    /// Munition
+    pub ammo_type: String,
+    /// Anneau
+    pub ring: Ring,
+    /// Nombre d'obus
+    pub bombs: u32,
+    /// Incréments par obus, si `metrics.json` les renseigne
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub increments_per_bomb: Option<u32>,
+    /// Incréments consommés au total, si connus
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub increments: Option<u32>,
+}
+
+impl Expenditure {
+    /// Consommation de `bombs` coups tirés à l'anneau `ring`.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::fire::Expenditure;
+    /// use mortar::{AmmoKind, IncrementTable};
+    ///
+    /// let mut increments = IncrementTable::new();
+    /// increments.insert((AmmoKind::He, 3), 3);
+    /// let e = Expenditure::new(AmmoKind::He, 3, 12, &increments);
+    /// assert_eq!(e.increments, Some(36));
+    /// assert_eq!(Expenditure::new(AmmoKind::He, 2, 12, &increments).increments, None);
+    /// ```
+    pub fn new(ammo: AmmoKind, ring: Ring, bombs: u32, increments: &IncrementTable) -> Self {
+        let increments_per_bomb = increments.get(&(ammo, ring)).copied();
+        Expenditure {
+            ammo_type: ammo.as_str().to_string(),
+            ring,
+            bombs,
+            increments_per_bomb,
+            increments: increments_per_bomb.map(|n| n.saturating_mul(bombs)),
+        }
+    }
+
+    /// Résumé imprimable (ex: `12 obus HE 3R, 36 increments (3 par obus)`).
+    pub fn describe(&self) -> String {
+        let increments = match (self.increments, self.increments_per_bomb) {
+            (Some(total), Some(per_bomb)) => {
+                format!("{} increments ({} par obus)", total, per_bomb)
+            }
+            _ => "increments inconnus".to_string(),
+        };
+        format!(
+            "{} obus {} {}R, {}",
+            self.bombs, self.ammo_type, self.ring, increments
+        )
+    }
+}
+
+/// Tableau de tir complet d'une salve de tir d'efficacité.
+#[derive(Clone, Debug, Serialize)]
+pub struct VolleyPlan {
+    /// Mortier (section) tirant la salve
+    pub mortar: String,
+    /// Cible
+    pub target: String,
+    /// Munition
+    pub ammo_type: String,
+    /// Nombre de tubes
+    pub tubes: u32,
+    /// Coups par tube
+    pub rounds_per_tube: u32,
+    /// Intervalle entre deux coups d'un même tube (s)
+    pub round_interval_s: f64,
+    /// Distance horizontale (m)
+    pub distance_m: f64,
+    /// Gisement grille en mils
+    pub azimuth_mil: f64,
+    /// Gisement magnétique en mils
+    pub magnetic_azimuth_mil: f64,
+    /// Déclinaison utilisée pour le gisement magnétique
+    pub declination: Declination,
+    /// Durée de trajet (s), si la table la fournit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_of_flight_s: Option<f64>,
+    /// Coups, par ordre de départ puis de tube
+    pub rounds: Vec<VolleyRound>,
+    /// Munitions consommées par la salve
+    pub expenditure: Expenditure,
+    /// Rayon d'effet de la munition (m), si la table des effets le fournit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effect_radius_m: Option<f64>,
+    /// Coups nécessaires pour couvrir la zone visée (voir [`rounds_required`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rounds_required: Option<u32>,
+    /// Amis menacés par la cible, renseigné par le serveur (voir
+    /// [`crate::safety::attach_danger_close`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub danger_close: Option<DangerClose>,
+}
+
+/// Nombre de coups couvrant une zone circulaire de rayon `zone_radius_m`
+/// avec des effets de rayon `effect_radius_m` : rapport des surfaces,
+/// arrondi au coup supérieur, au moins un coup.
+///
+/// # Retourne
+///
+/// `None` si le rayon d'effet n'est pas strictement positif.
+pub fn rounds_required(zone_radius_m: f64, effect_radius_m: f64) -> Option<u32> {
+    if effect_radius_m.is_nan() || effect_radius_m <= 0.0 || !zone_radius_m.is_finite() {
+        return None;
+    }
+    let ratio = (zone_radius_m.max(0.0) / effect_radius_m).powi(2);
+    Some((ratio.ceil() as u32).max(1))
+}
+
+/// Prépare le tableau de tir d'une salve de tir d'efficacité.
+///
+/// Les `tubes` de la section sont en batterie sur la position du mortier et
+/// tirent ensemble : le coup `n` de chaque tube part à
+/// `(n − 1) × round_interval_s` secondes. L'anneau est choisi comme pour
+/// [`spot_round`]. Élévation et gisement sont ceux de `solution`, la
+/// solution complétée de la pièce sur la cible : réglage, dérive, vent, air
+/// et température des charges compris. Le danger rapproché est repris de la
+/// solution. La consommation (obus et incréments) est reportée d'après
+/// `increments`. Lorsque la solution porte un rayon d'effet, le nombre de
+/// coups nécessaires pour couvrir la dispersion de l'anneau, élargie de
+/// l'incertitude de la cible, est indiqué ([`rounds_required`]).
+///
+/// # Erreurs
+///
+/// Retourne une erreur si `tubes` ou `rounds_per_tube` est nul, si la salve
+/// dépasse [`MAX_VOLLEY_ROUNDS`] coups, si l'intervalle est négatif, si
+/// aucun anneau ne couvre la portée ou si la solution ne donne pas
+/// d'élévation à l'anneau choisi.
+#[allow(clippy::too_many_arguments)]
+pub fn volley_plan(
+    solution: &FiringSolution,
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    ballistics: &BallisticTables,
+    increments: &IncrementTable,
+    ring: Option<Ring>,
+    tubes: u32,
+    rounds_per_tube: u32,
+    round_interval_s: f64,
+    declination: Declination,
+) -> Result<VolleyPlan> {
+    if tubes == 0 || rounds_per_tube == 0 {
+        bail!("tubes and rounds_per_tube must be at least 1");
+    }
+    if tubes as usize * rounds_per_tube as usize > MAX_VOLLEY_ROUNDS {
+        bail!("volley must not exceed {} rounds", MAX_VOLLEY_ROUNDS);
+    }
+    if !round_interval_s.is_finite() || round_interval_s < 0.0 {
+        bail!("round_interval_s must be a positive number");
+    }
+
+    let (ring, table) = select_ring(mortar, target, ballistics, ring)?;
+    let distance_m = solution.distance_m;
+    let selected = solution.selected_solution.as_ref();
+    let elevation_mil = match selected.and_then(|s| s.elevations.get(ring)) {
+        Some(e) => e,
+        None => bail!(
+            "{}R does not cover {:.0} m for {}",
+            ring,
+            distance_m,
+            target.ammo_type
+        ),
+    };
+    let deflection_mil = solution.aim_azimuth_mil(ring);
+    let time_of_flight_s = table.time_of_flight_at(mortar.table_range(solution.aim_range_m(ring)));
+    let effect_radius_m = selected.and_then(|s| s.effect_radius_m);
+    let zone_radius_m = selected
+        .and_then(|s| s.dispersions.get(ring))
+        .unwrap_or(0.0)
+        + target.uncertainty_m.unwrap_or(0.0);
+    let rounds_required = effect_radius_m.and_then(|e| rounds_required(zone_radius_m, e));
+
+    let mut rounds = Vec::with_capacity(tubes as usize * rounds_per_tube as usize);
+    for round in 1..=rounds_per_tube {
+        let fire_time_s = (round - 1) as f64 * round_interval_s;
+        for tube in 1..=tubes {
+            rounds.push(VolleyRound {
+                tube,
+                round,
+                ring,
+                deflection_mil,
+                elevation_mil,
+                fire_time_s,
+                impact_time_s: time_of_flight_s.map(|tof| fire_time_s + tof),
+            });
+        }
+    }
+
+    Ok(VolleyPlan {
+        mortar: mortar.name.clone(),
+        target: target.name.clone(),
+        ammo_type: target.ammo_type.as_str().to_string(),
+        tubes,
+        rounds_per_tube,
+        round_interval_s,
+        distance_m,
+        azimuth_mil: deflection_mil,
+        magnetic_azimuth_mil: declination.magnetic_azimuth_mil(deflection_mil),
+        declination,
+        time_of_flight_s,
+        expenditure: Expenditure::new(target.ammo_type, ring, tubes * rounds_per_tube, increments),
+        rounds,
+        effect_radius_m,
+        rounds_required,
+        danger_close: solution.danger_close.clone(),
+    })
+}
+
+/// Rend le tableau de tir d'une salve en texte imprimable.
+pub fn render_volley_text(plan: &VolleyPlan) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "SALVE {} -> {} ({}, {} tubes x {} coups, {:.0} m)",
+        plan.mortar, plan.target, plan.ammo_type, plan.tubes, plan.rounds_per_tube, plan.distance_m
+    );
+    let _ = writeln!(
+        out,
+        "Gisement: {:.1} mils grille / {:.1} mils magnetique",
+        plan.azimuth_mil, plan.magnetic_azimuth_mil
+    );
+    let _ = writeln!(out, "{}", plan.declination.describe());
+    if let Some(tof) = plan.time_of_flight_s {
+        let _ = writeln!(out, "Duree de trajet: {:.1} s", tof);
+    }
+    let _ = writeln!(out, "Consommation: {}", plan.expenditure.describe());
+    if let (Some(effect), Some(required)) = (plan.effect_radius_m, plan.rounds_required) {
+        let planned = plan.tubes * plan.rounds_per_tube;
+        let _ = writeln!(
+            out,
+            "Rayon d'effet: {:.0} m, {} coups requis ({} prevus{})",
+            effect,
+            required,
+            planned,
+            if planned < required {
+                ", INSUFFISANT"
+            } else {
+                ""
+            }
+        );
+    }
+    if let Some(danger) = &plan.danger_close {
+        for f in &danger.friendlies {
+            let _ = writeln!(
+                out,
+                "DANGER RAPPROCHE: ami '{}' a {:.0} m ({})",
+                f.name,
+                f.distance_m,
+                f.rings.join(", ")
+            );
+        }
+    }
+    let _ = writeln!(
+        out,
+        "{:>4} {:>4} {:>6} {:>10} {:>10} {:>8} {:>8}",
+        "TUBE", "COUP", "ANNEAU", "GIS (mil)", "ELEV (mil)", "DEPART", "IMPACT"
+    );
+    for r in &plan.rounds {
+        let impact = r
+            .impact_time_s
+            .map_or("N/A".to_string(), |t| format!("{:.1}s", t));
+        let _ = writeln!(
+            out,
+            "{:>4} {:>4} {:>6} {:>10.1} {:>10.1} {:>8} {:>8}",
+            r.tube,
+            r.round,
+            format!("{}R", r.ring),
+            r.deflection_mil,
+            r.elevation_mil,
+            format!("{:.1}s", r.fire_time_s),
+            impact
+        );
+    }
+    out
+}
+
+/// Choisit l'anneau demandé, sinon l'anneau préféré de la pièce
+/// (voir [`MortarPosition::preferred_ring`]).
+fn select_ring<'a>(
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    ballistics: &'a BallisticTables,
+    ring: Option<Ring>,
+) -> Result<(Ring, &'a BallisticTable)> {
+    let ammo = target.ammo_type;
+    let distance = mortar.as_position().distance_to(&target.as_position());
+
+    let ring = match ring.or_else(|| mortar.preferred_ring(ballistics, ammo, distance)) {
+        Some(r) if !mortar.ring_available(r) => {
+            bail!("Ring {}R is excluded on mortar '{}'", r, mortar.name)
+        }
+        Some(r) => r,
+        None => bail!("No usable {} ring covers {:.0} m", ammo, distance),
+    };
+    match ballistics.get(&(mortar.weapon, ammo, ring)) {
+        Some(t) => Ok((ring, t)),
+        None => bail!("No ballistic table for {} {}R", ammo, ring),
+    }
+}
+
+/// Dispersion ajustée de l'anneau, ou la tolérance par défaut si inconnue.
+fn ring_dispersion(
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    dispersions: &DispersionTable,
+    ring: Ring,
+) -> f64 {
+    dispersions
+        .get(&(target.ammo_type, ring))
+        .map(|base| {
+            let range_m = mortar.as_position().distance_to(&target.as_position());
+            base.adjusted(range_m, mortar.elevation, target.elevation)
+                .scaled(mortar.dispersion_factor(ring))
+                .radius_m()
+        })
+        .unwrap_or(DEFAULT_FFE_TOLERANCE_M)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BallisticPoint, TargetType, WeaponSystem};
+
+    fn fixtures() -> (BallisticTables, DispersionTable) {
+        let mut ballistics = BallisticTables::new();
+        ballistics.insert(
+            (WeaponSystem::M224, AmmoKind::He, 2),
+            BallisticTable {
+                points: vec![
+                    BallisticPoint {
+                        range_m: 0.0,
+                        elev_mil: 1400.0,
+                        time_flight_s: None,
+                        delta_elev_per_100m_mil: None,
+                    },
+                    BallisticPoint {
+                        range_m: 1000.0,
+                        elev_mil: 900.0,
+                        time_flight_s: None,
+                        delta_elev_per_100m_mil: None,
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        let mut dispersions = DispersionTable::new();
+        dispersions.insert((AmmoKind::He, 2), Dispersion::circular(39.0).into());
+        (ballistics, dispersions)
+    }
+
+    #[test]
+    fn observed_spot_corrects_target_and_recommends() {
+        let (ballistics, dispersions) = fixtures();
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let mut target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let mut rng = SimRng::new(1);
+
+        let far = SpotObservation {
+            vertical_m: 0.0,
+            horizontal_m: 100.0,
+        };
+        let report = spot_round(
+            &mortar,
+            &mut target,
+            &ballistics,
+            &dispersions,
+            None,
+            Some(far),
+            None,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(report.ring, 2);
+        assert!(!report.simulated);
+        assert!(!report.fire_for_effect);
+        assert_eq!(target.x, -100.0);
+
+        let close = SpotObservation {
+            vertical_m: -10.0,
+            horizontal_m: 5.0,
+        };
+        let report = spot_round(
+            &mortar,
+            &mut target,
+            &ballistics,
+            &dispersions,
+            None,
+            Some(close),
+            None,
+            &mut rng,
+        )
+        .unwrap();
+        assert!(report.fire_for_effect);
+        assert_eq!(target.corrections.len(), 2);
+        // Coup tombé 10 m au Nord : le point visé recule vers le Sud
+        assert_eq!((target.x, target.y), (-105.0, 490.0));
+        assert_eq!(report.correction.vertical_m, -10.0);
+    }
+
+    #[test]
+    fn scatter_is_centred_on_target_and_capped() {
+        let (ballistics, dispersions) = fixtures();
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+
+        let report = simulate_scatter(
+            &mortar,
+            &target,
+            &ballistics,
+            &dispersions,
+            None,
+            500,
+            None,
+            &mut SimRng::new(3),
+            &Budget::unlimited(),
+        )
+        .unwrap();
+        assert_eq!(report.ring, 2);
+        assert_eq!(report.cep_m, 39.0);
+        assert_eq!(report.impacts.len(), 500);
+        assert!(!report.truncated);
+        let mean_y = report.impacts.iter().map(|i| i.y).sum::<f64>() / 500.0;
+        assert!((mean_y - 500.0).abs() < 5.0);
+
+        assert!(simulate_scatter(
+            &mortar,
+            &target,
+            &ballistics,
+            &dispersions,
+            None,
+            MAX_SCATTER_IMPACTS + 1,
+            None,
+            &mut SimRng::new(3),
+            &Budget::unlimited(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn hit_probability_follows_the_dispersion_ellipse() {
+        let (ballistics, mut dispersions) = fixtures();
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            500.0,
+            0.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+
+        // Dispersion circulaire : la moitié des coups tombe dans le CEP
+        let solution =
+            calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+        let mut rng = SimRng::new(7);
+        let report =
+            simulate_impacts(&solution, 2, 39.0, 10_000, &mut rng, &Budget::unlimited()).unwrap();
+        assert!((report.hit_probability - 0.5).abs() < 0.02);
+        assert!((report.radii.p50_m - 39.0).abs() < 1.5);
+        assert!(report.radii.p50_m < report.radii.p90_m && report.radii.p90_m < report.radii.p99_m);
+        assert_eq!(report.rounds_for_confidence, Some(4));
+        assert_eq!(report.impacts.len(), MAX_RETURNED_IMPACTS);
+        assert_eq!(report.samples, 10_000);
+
+        // Ellipse allongée en portée : la gerbe s'étire vers l'Est (tir plein Est)
+        dispersions.insert(
+            (AmmoKind::He, 2),
+            Dispersion {
+                range_pe_m: 40.0,
+                deflection_pe_m: 10.0,
+            }
+            .into(),
+        );
+        let solution =
+            calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+        let report =
+            simulate_impacts(&solution, 2, 20.0, 5_000, &mut rng, &Budget::unlimited()).unwrap();
+        let spread = |f: fn(&Impact) -> f64| {
+            report.impacts.iter().map(|i| f(i).powi(2)).sum::<f64>() / report.impacts.len() as f64
+        };
+        assert!(spread(|i| i.x) > 10.0 * spread(|i| i.y));
+
+        assert!(simulate_impacts(&solution, 2, 0.0, 100, &mut rng, &Budget::unlimited()).is_err());
+        assert!(simulate_impacts(&solution, 3, 20.0, 100, &mut rng, &Budget::unlimited()).is_err());
+        let budget = Budget::unlimited();
+        budget.cancel();
+        let partial = simulate_impacts(&solution, 2, 20.0, 1_000, &mut rng, &budget).unwrap();
+        assert!(partial.truncated);
+        assert_eq!(partial.samples, BUDGET_CHECK_INTERVAL);
+    }
+
+    #[test]
+    fn training_offset_is_discovered_and_corrected() {
+        let (ballistics, mut dispersions) = fixtures();
+        // Dispersion négligeable : seul le décalage d'entraînement compte
+        dispersions.insert((AmmoKind::He, 2), Dispersion::circular(0.0).into());
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let mut target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let offset = TrainingOffset {
+            north_m: 80.0,
+            east_m: -40.0,
+        };
+        let mut rng = SimRng::new(5);
+
+        let report = spot_round(
+            &mortar,
+            &mut target,
+            &ballistics,
+            &dispersions,
+            None,
+            None,
+            Some(offset),
+            &mut rng,
+        )
+        .unwrap();
+        assert!((report.deviation.vertical_m + 80.0).abs() < 1e-9);
+        assert!((report.deviation.horizontal_m + 40.0).abs() < 1e-9);
+        assert!(!report.fire_for_effect);
+
+        // La pièce vise désormais à côté de la cible : le coup suivant porte
+        let report = spot_round(
+            &mortar,
+            &mut target,
+            &ballistics,
+            &dispersions,
+            None,
+            None,
+            Some(offset),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(report.miss_distance_m < 1e-9);
+        assert!((target.x - 40.0).abs() < 1e-9);
+        assert!((target.y - 420.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn volley_lists_every_round_of_every_tube() {
+        let (mut ballistics, _) = fixtures();
+        for p in &mut ballistics
+            .get_mut(&(WeaponSystem::M224, AmmoKind::He, 2))
+            .unwrap()
+            .points
+        {
+            p.time_flight_s = Some(20.0);
+        }
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            500.0,
+            0.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+
+        let declination = Declination {
+            magnetic_deg: -4.5,
+            grid_convergence_deg: 0.0,
+        };
+        let increments = IncrementTable::from([((AmmoKind::He, 2), 2)]);
+        let solution = crate::calculate_solution(&mortar, &target, &ballistics);
+        let plan = volley_plan(
+            &solution,
+            &mortar,
+            &target,
+            &ballistics,
+            &increments,
+            None,
+            3,
+            2,
+            4.0,
+            declination,
+        )
+        .unwrap();
+        assert_eq!(plan.rounds.len(), 6);
+        let last = plan.rounds.last().unwrap();
+        assert_eq!((last.tube, last.round, last.ring), (3, 2, 2));
+        assert!((last.deflection_mil - 1600.0).abs() < 1e-9);
+        assert!((last.elevation_mil - 1150.0).abs() < 1e-9);
+        assert_eq!(last.fire_time_s, 4.0);
+        assert_eq!(last.impact_time_s, Some(24.0));
+        // Déclinaison 4,5° Ouest : gisement magnétique augmenté de 80 mils
+        assert!((plan.magnetic_azimuth_mil - 1680.0).abs() < 1e-9);
+        // 6 obus, 2 incréments chacun
+        assert_eq!(plan.expenditure.bombs, 6);
+        assert_eq!(plan.expenditure.increments, Some(12));
+        assert_eq!(render_volley_text(&plan).lines().count(), 6 + 6);
+        // Sans table des effets, pas de dimensionnement
+        assert_eq!(plan.rounds_required, None);
+
+        let (s, d, inc) = (&solution, Declination::default(), &increments);
+        assert!(volley_plan(s, &mortar, &target, &ballistics, inc, None, 0, 2, 4.0, d).is_err());
+        assert!(volley_plan(s, &mortar, &target, &ballistics, inc, None, 100, 6, 4.0, d).is_err());
+    }
+
+    #[test]
+    fn rounds_required_covers_the_zone_with_the_effect_radius() {
+        assert_eq!(rounds_required(0.0, 35.0), Some(1));
+        assert_eq!(rounds_required(35.0, 35.0), Some(1));
+        assert_eq!(rounds_required(70.0, 35.0), Some(4));
+        assert_eq!(rounds_required(80.0, 35.0), Some(6));
+        assert_eq!(rounds_required(80.0, 0.0), None);
+
+        // Dispersion 39 m + incertitude 31 m = 70 m, rayon létal 35 m
+        let (ballistics, dispersions) = fixtures();
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let mut target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            500.0,
+            0.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        target.uncertainty_m = Some(31.0);
+        let mut solution =
+            calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+        let effects = crate::EffectTable::from([(
+            AmmoKind::He,
+            crate::EffectRadii {
+                casualty_m: Some(35.0),
+                ..Default::default()
+            },
+        )]);
+        crate::attach_effect_radius(&mut solution, &effects);
+        let plan = volley_plan(
+            &solution,
+            &mortar,
+            &target,
+            &ballistics,
+            &IncrementTable::new(),
+            None,
+            1,
+            3,
+            4.0,
+            Declination::default(),
+        )
+        .unwrap();
+        assert_eq!(plan.effect_radius_m, Some(35.0));
+        assert_eq!(plan.rounds_required, Some(4));
+        assert!(render_volley_text(&plan)
+            .contains("Rayon d'effet: 35 m, 4 coups requis (3 prevus, INSUFFISANT)"));
+    }
+}
@@ -15,16 +15,22 @@
 //! ## Exemple d'utilisation
 //!
 //! ```rust,ignore
-//! use mortar::{MortarPosition, TargetPosition, AmmoKind, TargetType};
-//! use mortar::{load_ballistics, load_dispersion, calculate_solution_with_dispersion};
+//! use mortar::prelude::*;
 //!
 //! // Charger les tables balistiques et de dispersion
 //! let ballistics = load_ballistics().unwrap();
 //! let dispersions = load_dispersion().unwrap();
 //!
 //! // Définir les positions
-//! let mortar = MortarPosition::new("M1".to_string(), 100.0, 0.0, 0.0);
-//! let target = TargetPosition::new("T1".to_string(), 50.0, 500.0, 300.0, TargetType::Infanterie, AmmoKind::He);
+//! let mortar = MortarPosition::builder().name("M1").at(0.0, 0.0).elevation(100.0).build().unwrap();
+//! let target = TargetPosition::builder()
+//!     .name("T1")
+//!     .at(500.0, 300.0)
+//!     .elevation(50.0)
+//!     .target_type(TargetType::Infanterie)
+//!     .ammo(AmmoKind::He)
+//!     .build()
+//!     .unwrap();
 //!
 //! // Calculer la solution de tir
 //! let solution = calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
@@ -33,12 +39,16 @@
 //! println!("Azimut: {:.1} deg", solution.azimuth_deg);
 //! ```
 
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
-use std::io::BufReader;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ammo::AmmoRegistry;
 
 // ============================================================================
 // Types de munitions
@@ -55,7 +65,8 @@ use std::path::Path;
 /// - `He` - Munition explosive M821 High Explosive (anneaux 0R-4R)
 /// - `Smoke` - Munition fumigène M819 (anneaux 1R-4R, pas de 0R)
 /// - `Flare` - Munition éclairante M853A1 (anneaux 1R-4R, pas de 0R)
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+/// - `Custom` - Munition déclarée dans `ammo.json`, par son nom (voir [`ammo`])
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum AmmoKind {
     /// Munition d'entraînement M879
     Practice,
@@ -65,6 +76,8 @@ pub enum AmmoKind {
     Smoke,
     /// Munition éclairante M853A1
     Flare,
+    /// Munition déclarée dans `ammo.json`, décrite par le registre chargé
+    Custom(ammo::AmmoName),
 }
 
 impl AmmoKind {
@@ -76,16 +89,20 @@ impl AmmoKind {
     /// use mortar::AmmoKind;
     /// assert_eq!(AmmoKind::He.as_str(), "HE");
     /// ```
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             AmmoKind::Practice => "PRACTICE",
             AmmoKind::He => "HE",
             AmmoKind::Smoke => "SMOKE",
             AmmoKind::Flare => "FLARE",
+            AmmoKind::Custom(name) => name.as_str(),
         }
     }
 
-    /// Retourne un slice contenant tous les types de munitions disponibles.
+    /// Retourne un slice contenant les munitions livrées.
+    ///
+    /// Les munitions déclarées dans `ammo.json` sont celles du registre
+    /// chargé (voir [`ammo::AmmoRegistry::kinds`]).
     ///
     /// # Exemple
     ///
@@ -102,13 +119,48 @@ impl AmmoKind {
         ]
     }
 
+    /// Retourne la désignation de l'obus (ex: "M821").
+    ///
+    /// Une munition déclarée a sa désignation dans le registre
+    /// ([`ammo::AmmoInfo::designation`]) ; son nom est retourné ici.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::AmmoKind;
+    /// assert_eq!(AmmoKind::Flare.designation(), "M853A1");
+    /// ```
+    pub fn designation(&self) -> &str {
+        match self {
+            AmmoKind::Practice => "M879",
+            AmmoKind::He => "M821",
+            AmmoKind::Smoke => "M819",
+            AmmoKind::Flare => "M853A1",
+            AmmoKind::Custom(name) => name.as_str(),
+        }
+    }
+
+    /// Retourne les noms alternatifs acceptés pour une munition livrée
+    /// (ceux d'une munition déclarée sont dans le registre).
+    pub fn aliases(&self) -> Vec<&'static str> {
+        AMMO_ALIASES
+            .iter()
+            .filter(|(_, kind)| kind == self)
+            .map(|(alias, _)| *alias)
+            .collect()
+    }
+
     /// Parse une chaîne de caractères en type de munition.
     ///
-    /// La conversion est insensible à la casse.
+    /// La conversion est insensible à la casse et accepte les noms alternatifs
+    /// de [`AMMO_ALIASES`] (désignations, abréviations, noms français).
+    /// Les espaces et tirets sont traités comme des `_`. Les munitions
+    /// déclarées dans `ammo.json` se lisent avec
+    /// [`ammo::AmmoRegistry::parse_kind`].
     ///
     /// # Arguments
     ///
-    /// * `s` - Chaîne à parser ("PRACTICE", "HE", "SMOKE", "FLARE")
+    /// * `s` - Chaîne à parser ("PRACTICE", "HE", "M821", "EXPLOSIVE", ...)
     ///
     /// # Retourne
     ///
@@ -119,19 +171,283 @@ impl AmmoKind {
     /// ```
     /// use mortar::AmmoKind;
     /// assert_eq!(AmmoKind::parse_str("he"), Some(AmmoKind::He));
+    /// assert_eq!(AmmoKind::parse_str("M821"), Some(AmmoKind::He));
+    /// assert_eq!(AmmoKind::parse_str("high explosive"), Some(AmmoKind::He));
     /// assert_eq!(AmmoKind::parse_str("invalid"), None);
     /// ```
     pub fn parse_str(s: &str) -> Option<AmmoKind> {
-        match s.to_uppercase().as_str() {
-            "PRACTICE" => Some(AmmoKind::Practice),
-            "HE" => Some(AmmoKind::He),
-            "SMOKE" => Some(AmmoKind::Smoke),
-            "FLARE" => Some(AmmoKind::Flare),
+        let key = ammo::normalize(s);
+        AMMO_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == key)
+            .map(|(_, kind)| *kind)
+    }
+
+    /// Munition d'un nom canonique (voir [`AmmoKind::as_str`]) : munition
+    /// livrée, sinon munition déclarée de ce nom, connue ou non du registre.
+    ///
+    /// # Retourne
+    ///
+    /// `None` si le nom ne peut pas être celui d'une munition déclarée (voir
+    /// [`ammo::AmmoName`]).
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::AmmoKind;
+    /// assert_eq!(AmmoKind::from_name("HE"), Some(AmmoKind::He));
+    /// assert_eq!(AmmoKind::from_name("m720 he").unwrap().as_str(), "M720_HE");
+    /// assert_eq!(AmmoKind::from_name(""), None);
+    /// ```
+    pub fn from_name(s: &str) -> Option<AmmoKind> {
+        AmmoKind::parse_str(s)
+            .or_else(|| ammo::AmmoName::new(&ammo::normalize(s)).map(AmmoKind::Custom))
+    }
+}
+
+impl Serialize for AmmoKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = match self {
+            // Noms des variantes, inchangés pour les fichiers déjà sauvegardés
+            AmmoKind::Practice => "Practice",
+            AmmoKind::He => "He",
+            AmmoKind::Smoke => "Smoke",
+            AmmoKind::Flare => "Flare",
+            AmmoKind::Custom(_) => self.as_str(),
+        };
+        serializer.serialize_str(name)
+    }
+}
+
+impl<'de> Deserialize<'de> for AmmoKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Une munition déclarée est relue par son nom, même hors registre
+        let s = String::deserialize(deserializer)?;
+        AmmoKind::from_name(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown ammo type '{}'", s)))
+    }
+}
+
+/// Calibre d'une pièce de mortier.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Caliber {
+    /// Mortier léger 60mm
+    #[default]
+    #[serde(rename = "60mm")]
+    Mm60,
+    /// Mortier moyen 81mm
+    #[serde(rename = "81mm")]
+    Mm81,
+    /// Mortier lourd 120mm
+    #[serde(rename = "120mm")]
+    Mm120,
+}
+
+impl Caliber {
+    /// Retourne la représentation textuelle du calibre (ex: "81mm").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Caliber::Mm60 => "60mm",
+            Caliber::Mm81 => "81mm",
+            Caliber::Mm120 => "120mm",
+        }
+    }
+
+    /// Parse un calibre ("81", "81mm", insensible à la casse).
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::Caliber;
+    /// assert_eq!(Caliber::parse_str("81"), Some(Caliber::Mm81));
+    /// assert_eq!(Caliber::parse_str("120MM"), Some(Caliber::Mm120));
+    /// assert_eq!(Caliber::parse_str("82"), None);
+    /// ```
+    pub fn parse_str(s: &str) -> Option<Caliber> {
+        let s = s.trim().to_ascii_lowercase();
+        match s.strip_suffix("mm").unwrap_or(&s).trim() {
+            "60" => Some(Caliber::Mm60),
+            "81" => Some(Caliber::Mm81),
+            "120" => Some(Caliber::Mm120),
             _ => None,
         }
     }
 }
 
+impl std::fmt::Display for Caliber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Système d'arme d'une pièce de mortier.
+///
+/// Chaque système a son propre espace de tables balistiques : les tables du
+/// M224 sont à la racine du répertoire de données, celles des autres systèmes
+/// dans un sous-répertoire à leur nom (`M252/HE/M821_HE_2R.csv`...). Une même
+/// munition peut ainsi avoir des tables différentes selon la pièce qui la tire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WeaponSystem {
+    /// Mortier léger 60mm M224
+    #[default]
+    M224,
+    /// Mortier moyen 81mm M252
+    M252,
+    /// Mortier lourd 120mm M120
+    M120,
+}
+
+impl WeaponSystem {
+    /// Retourne la désignation du système (ex: "M252").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WeaponSystem::M224 => "M224",
+            WeaponSystem::M252 => "M252",
+            WeaponSystem::M120 => "M120",
+        }
+    }
+
+    /// Retourne tous les systèmes d'arme.
+    pub fn all() -> &'static [WeaponSystem] {
+        &[WeaponSystem::M224, WeaponSystem::M252, WeaponSystem::M120]
+    }
+
+    /// Retourne le calibre du système.
+    pub fn caliber(&self) -> Caliber {
+        match self {
+            WeaponSystem::M224 => Caliber::Mm60,
+            WeaponSystem::M252 => Caliber::Mm81,
+            WeaponSystem::M120 => Caliber::Mm120,
+        }
+    }
+
+    /// Retourne le système d'un calibre.
+    pub fn from_caliber(caliber: Caliber) -> WeaponSystem {
+        match caliber {
+            Caliber::Mm60 => WeaponSystem::M224,
+            Caliber::Mm81 => WeaponSystem::M252,
+            Caliber::Mm120 => WeaponSystem::M120,
+        }
+    }
+
+    /// Retourne le nombre d'anneaux (charges) du système, de `0R` à
+    /// `(n - 1)R`.
+    pub fn ring_count(&self) -> usize {
+        match self {
+            // Charges 0 à 4 pour les trois systèmes
+            WeaponSystem::M224 | WeaponSystem::M252 | WeaponSystem::M120 => RING_COUNT,
+        }
+    }
+
+    /// Retourne le répertoire des tables du système, relatif au répertoire
+    /// de données (`None` pour le M224, dont les tables sont à la racine).
+    pub fn table_namespace(&self) -> Option<&'static str> {
+        match self {
+            WeaponSystem::M224 => None,
+            _ => Some(self.as_str()),
+        }
+    }
+
+    /// Parse un système par sa désignation ou son calibre, insensible à la
+    /// casse.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::WeaponSystem;
+    /// assert_eq!(WeaponSystem::parse_str("m252"), Some(WeaponSystem::M252));
+    /// assert_eq!(WeaponSystem::parse_str("120mm"), Some(WeaponSystem::M120));
+    /// assert_eq!(WeaponSystem::parse_str("M29"), None);
+    /// ```
+    pub fn parse_str(s: &str) -> Option<WeaponSystem> {
+        let key = s.trim().to_ascii_uppercase();
+        WeaponSystem::all()
+            .iter()
+            .copied()
+            .find(|w| w.as_str() == key)
+            .or_else(|| Caliber::parse_str(s).map(WeaponSystem::from_caliber))
+    }
+}
+
+impl std::fmt::Display for WeaponSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for WeaponSystem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for WeaponSystem {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Les sauvegardes antérieures donnent le calibre ("81mm")
+        let s = String::deserialize(deserializer)?;
+        WeaponSystem::parse_str(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown weapon system '{}'", s)))
+    }
+}
+
+/// Munition ne pouvant pas être tirée par le calibre d'une pièce.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AmmoIncompatibility {
+    /// Nom du mortier
+    pub mortar: String,
+    /// Calibre du mortier
+    pub caliber: Caliber,
+    /// Munition demandée
+    pub ammo: AmmoKind,
+    /// Calibres pouvant tirer la munition
+    pub calibers: Vec<Caliber>,
+}
+
+impl std::fmt::Display for AmmoIncompatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let calibers: Vec<&str> = self.calibers.iter().map(|c| c.as_str()).collect();
+        write!(
+            f,
+            "{} ammo ({}) cannot be fired from mortar '{}' ({})",
+            self.ammo,
+            calibers.join("/"),
+            self.mortar,
+            self.caliber
+        )
+    }
+}
+
+impl std::error::Error for AmmoIncompatibility {}
+
+/// Table des noms acceptés pour chaque type de munition.
+///
+/// Le nom canonique (`as_str`) et la désignation figurent en tête pour chaque munition.
+pub const AMMO_ALIASES: &[(&str, AmmoKind)] = &[
+    ("PRACTICE", AmmoKind::Practice),
+    ("M879", AmmoKind::Practice),
+    ("TP", AmmoKind::Practice),
+    ("TRAINING", AmmoKind::Practice),
+    ("EXERCICE", AmmoKind::Practice),
+    ("HE", AmmoKind::He),
+    ("M821", AmmoKind::He),
+    ("EXPLOSIVE", AmmoKind::He),
+    ("HIGH_EXPLOSIVE", AmmoKind::He),
+    ("EXPLOSIF", AmmoKind::He),
+    ("SMOKE", AmmoKind::Smoke),
+    ("M819", AmmoKind::Smoke),
+    ("SMK", AmmoKind::Smoke),
+    ("RP", AmmoKind::Smoke),
+    ("FUMIGENE", AmmoKind::Smoke),
+    ("FLARE", AmmoKind::Flare),
+    ("M853A1", AmmoKind::Flare),
+    ("M853", AmmoKind::Flare),
+    ("ILLUM", AmmoKind::Flare),
+    ("ILLUMINATION", AmmoKind::Flare),
+    ("ECLAIRANT", AmmoKind::Flare),
+];
+
 impl std::fmt::Display for AmmoKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
@@ -151,7 +467,7 @@ impl std::fmt::Display for AmmoKind {
 /// - `Infanterie` - Personnel à découvert ou en position (recommandation: HE)
 /// - `Vehicule` - Véhicules légers non blindés (recommandation: HE)
 /// - `Soutien` - Position de soutien, marquage, écran (recommandation: SMOKE)
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 pub enum TargetType {
     /// Cible d'infanterie - Personnel ennemi
     #[default]
@@ -227,6 +543,25 @@ impl TargetType {
             TargetType::Soutien => AmmoKind::Smoke,
         }
     }
+
+    /// Retourne la demi-vie du renseignement sur ce type de cible, en
+    /// secondes : durée au bout de laquelle la position observée n'a plus
+    /// qu'une chance sur deux d'être encore exacte.
+    ///
+    /// Les véhicules se déplacent vite, l'infanterie un peu moins ; une
+    /// position de soutien reste en place plusieurs heures.
+    pub fn knowledge_half_life_s(&self) -> u64 {
+        match self {
+            TargetType::Infanterie => 900,
+            TargetType::Vehicule => 300,
+            TargetType::Soutien => 7200,
+        }
+    }
+
+    /// Indique si ce type de cible se déplace (infanterie, véhicules).
+    pub fn is_mobile(&self) -> bool {
+        !matches!(self, TargetType::Soutien)
+    }
 }
 
 impl std::fmt::Display for TargetType {
@@ -235,6 +570,246 @@ impl std::fmt::Display for TargetType {
     }
 }
 
+/// Munition par défaut par type de cible, propre à une opération.
+///
+/// Une entrée remplace la recommandation de [`TargetType::suggested_ammo`] ;
+/// par exemple une unité qui marque les véhicules pour l'appui aérien
+/// associe la fumée aux véhicules.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::{AmmoKind, AmmoPolicy, TargetType};
+///
+/// let mut policy = AmmoPolicy::default();
+/// policy.0.insert(TargetType::Vehicule, AmmoKind::Smoke);
+/// assert_eq!(policy.recommended(TargetType::Vehicule), AmmoKind::Smoke);
+/// assert_eq!(policy.recommended(TargetType::Infanterie), AmmoKind::He);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AmmoPolicy(pub BTreeMap<TargetType, AmmoKind>);
+
+impl AmmoPolicy {
+    /// Retourne la munition fixée par la politique pour ce type, s'il y en a une.
+    pub fn get(&self, target_type: TargetType) -> Option<AmmoKind> {
+        self.0.get(&target_type).copied()
+    }
+
+    /// Retourne la munition recommandée pour ce type de cible.
+    pub fn recommended(&self, target_type: TargetType) -> AmmoKind {
+        self.get(target_type)
+            .unwrap_or_else(|| target_type.suggested_ammo())
+    }
+
+    /// Indique si aucune munition n'est fixée.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Remplace la munition recommandée d'une solution selon la politique.
+    pub fn apply(&self, solution: &mut FiringSolution, target_type: TargetType) {
+        solution.recommended_ammo = self.recommended(target_type).as_str().to_string();
+    }
+}
+
+/// Correction enregistrée, en convention standard (Sud et Est positifs).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CorrectionPreset {
+    /// Nord (négatif) / Sud (positif), en mètres
+    pub vertical_m: f64,
+    /// Ouest (négatif) / Est (positif), en mètres
+    pub horizontal_m: f64,
+}
+
+/// Corrections enregistrées de l'opération, par nom.
+///
+/// Elles servent aux erreurs systématiques récurrentes (décalage de carte,
+/// position de mortier mal levée...) : la correction est appliquée par son
+/// nom au lieu d'être ressaisie.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::{CorrectionPreset, CorrectionPresets};
+///
+/// let mut presets = CorrectionPresets::default();
+/// presets.0.insert(
+///     "carte_NO40".into(),
+///     CorrectionPreset { vertical_m: -28.0, horizontal_m: -28.0 },
+/// );
+/// assert_eq!(presets.get("carte_NO40").unwrap().vertical_m, -28.0);
+/// assert!(presets.get("inconnu").is_none());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CorrectionPresets(pub BTreeMap<String, CorrectionPreset>);
+
+impl CorrectionPresets {
+    /// Retourne la correction enregistrée sous ce nom.
+    pub fn get(&self, name: &str) -> Option<CorrectionPreset> {
+        self.0.get(name).copied()
+    }
+
+    /// Indique si aucune correction n'est enregistrée.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Plus grand numéro de cible (quatre chiffres).
+pub const TARGET_NUMBER_MAX: u32 = 9999;
+
+/// Numérotation automatique des cibles créées sans nom, propre à une opération.
+///
+/// Les noms suivent le format standard des numéros de cible : deux lettres
+/// suivies de quatre chiffres (`AB1001`, `AB1002`...). Le compteur repart à 1
+/// après 9999 et saute les noms déjà pris.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::TargetNumbering;
+///
+/// let mut numbering = TargetNumbering::default();
+/// assert_eq!(numbering.next_name(|name| name == "AB1001"), "AB1002");
+/// assert_eq!(numbering.next_name(|_| false), "AB1003");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetNumbering {
+    /// Préfixe de deux lettres majuscules
+    pub prefix: String,
+    /// Prochain numéro attribué (1 à 9999)
+    pub next: u32,
+}
+
+impl Default for TargetNumbering {
+    fn default() -> Self {
+        TargetNumbering {
+            prefix: "AB".to_string(),
+            next: 1001,
+        }
+    }
+}
+
+impl TargetNumbering {
+    /// Crée une numérotation ; `None` si le préfixe n'est pas formé de deux
+    /// lettres ou si le numéro sort de 1..=9999. Le préfixe est mis en majuscules.
+    pub fn new(prefix: &str, next: u32) -> Option<Self> {
+        let valid = prefix.len() == 2 && prefix.chars().all(|c| c.is_ascii_alphabetic());
+        if !valid || next == 0 || next > TARGET_NUMBER_MAX {
+            return None;
+        }
+        Some(TargetNumbering {
+            prefix: prefix.to_ascii_uppercase(),
+            next,
+        })
+    }
+
+    /// Nom correspondant à un numéro.
+    pub fn format(&self, number: u32) -> String {
+        format!("{}{:04}", self.prefix, number)
+    }
+
+    /// Attribue le prochain nom libre et avance le compteur.
+    ///
+    /// `taken` indique si un nom est déjà utilisé. Si tous les numéros sont
+    /// pris, le nom du numéro courant est retourné.
+    pub fn next_name(&mut self, taken: impl Fn(&str) -> bool) -> String {
+        let start = self.next;
+        loop {
+            let name = self.format(self.next);
+            self.next = if self.next >= TARGET_NUMBER_MAX {
+                1
+            } else {
+                self.next + 1
+            };
+            if !taken(&name) || self.next == start {
+                return name;
+            }
+        }
+    }
+
+    /// Indique si la numérotation est celle par défaut.
+    pub fn is_default(&self) -> bool {
+        *self == TargetNumbering::default()
+    }
+}
+
+// ============================================================================
+// Renseignement
+// ============================================================================
+
+/// Fiabilité d'un renseignement sur une cible.
+///
+/// Les variantes sont ordonnées de la plus fiable à la moins fiable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Reliability {
+    /// Confirmé par plusieurs sources ou observation directe
+    Confirmed,
+    /// Probable
+    Probable,
+    /// Possible
+    Possible,
+    /// Douteux
+    Doubtful,
+}
+
+impl Reliability {
+    /// Retourne la représentation textuelle de la fiabilité.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Reliability::Confirmed => "CONFIRMED",
+            Reliability::Probable => "PROBABLE",
+            Reliability::Possible => "POSSIBLE",
+            Reliability::Doubtful => "DOUBTFUL",
+        }
+    }
+
+    /// Retourne un slice contenant tous les niveaux de fiabilité.
+    pub fn all() -> &'static [Reliability] {
+        &[
+            Reliability::Confirmed,
+            Reliability::Probable,
+            Reliability::Possible,
+            Reliability::Doubtful,
+        ]
+    }
+
+    /// Parse une chaîne de caractères en niveau de fiabilité (insensible à la casse).
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::Reliability;
+    /// assert_eq!(Reliability::parse_str("probable"), Some(Reliability::Probable));
+    /// assert_eq!(Reliability::parse_str("C"), Some(Reliability::Confirmed));
+    /// ```
+    pub fn parse_str(s: &str) -> Option<Reliability> {
+        match s.to_uppercase().as_str() {
+            "CONFIRMED" | "C" => Some(Reliability::Confirmed),
+            "PROBABLE" | "PR" => Some(Reliability::Probable),
+            "POSSIBLE" | "PO" => Some(Reliability::Possible),
+            "DOUBTFUL" | "D" => Some(Reliability::Doubtful),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Reliability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Retourne l'heure courante en secondes depuis l'époque Unix.
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 // ============================================================================
 // Structures géométriques
 // ============================================================================
@@ -348,769 +923,6025 @@ impl Position {
     }
 }
 
-/// Position d'un mortier.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct MortarPosition {
-    /// Identifiant du mortier (ex: "M1", "Alpha")
-    pub name: String,
-    /// Altitude en mètres
-    pub elevation: f64,
-    /// Coordonnée X en mètres
-    pub x: f64,
-    /// Coordonnée Y en mètres
-    pub y: f64,
+/// Origine de l'altitude enregistrée pour une position.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElevationSource {
+    /// Altitude saisie
+    #[default]
+    Provided,
+    /// Altitude lue sur la carte d'altitude chargée
+    Terrain,
+    /// Altitude copiée de la position connue la plus proche
+    NearestPosition,
+    /// Altitude de référence par défaut
+    Datum,
 }
 
-impl MortarPosition {
-    /// Crée une nouvelle position de mortier.
-    pub fn new(name: String, elevation: f64, x: f64, y: f64) -> Self {
-        MortarPosition {
-            name,
-            elevation,
-            x,
-            y,
-        }
+impl ElevationSource {
+    /// Indique si l'altitude a été saisie.
+    pub fn is_provided(&self) -> bool {
+        *self == ElevationSource::Provided
     }
 
-    /// Convertit en position générique.
-    pub fn as_position(&self) -> Position {
-        Position::new(self.name.clone(), self.elevation, self.x, self.y)
+    /// Retourne le nom sérialisé de l'origine.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ElevationSource::Provided => "provided",
+            ElevationSource::Terrain => "terrain",
+            ElevationSource::NearestPosition => "nearest_position",
+            ElevationSource::Datum => "datum",
+        }
     }
 }
 
-/// Position d'une cible avec son type tactique et le type de munition à employer.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct TargetPosition {
-    /// Identifiant de la cible (ex: "T1", "Objectif Alpha")
-    pub name: String,
-    /// Altitude en mètres
-    pub elevation: f64,
-    /// Coordonnée X en mètres
-    pub x: f64,
-    /// Coordonnée Y en mètres
-    pub y: f64,
-    /// Type tactique de la cible
-    pub target_type: TargetType,
-    /// Type de munition à utiliser contre cette cible
-    pub ammo_type: AmmoKind,
+/// Disponibilité d'une pièce et de son équipe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MortarStatus {
+    /// En batterie, prête à tirer
+    #[default]
+    Ready,
+    /// En déplacement
+    Moving,
+    /// Hors de combat (panne, équipe indisponible)
+    OutOfAction,
+    /// En réapprovisionnement
+    Resupply,
 }
 
-impl TargetPosition {
-    /// Crée une nouvelle position de cible.
+impl MortarStatus {
+    /// Indique si la pièce peut recevoir des missions.
+    pub fn is_ready(&self) -> bool {
+        *self == MortarStatus::Ready
+    }
+
+    /// Retourne le nom sérialisé de l'état.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MortarStatus::Ready => "READY",
+            MortarStatus::Moving => "MOVING",
+            MortarStatus::OutOfAction => "OUT_OF_ACTION",
+            MortarStatus::Resupply => "RESUPPLY",
+        }
+    }
+
+    /// Parse un état (insensible à la casse, `OOA` pour hors de combat).
     ///
-    /// # Arguments
+    /// # Exemple
     ///
-    /// * `name` - Identifiant de la cible
-    /// * `elevation` - Altitude en mètres
-    /// * `x` - Coordonnée X en mètres
-    /// * `y` - Coordonnée Y en mètres
-    /// * `target_type` - Type tactique de la cible
-    /// * `ammo_type` - Type de munition à employer
-    pub fn new(name: String, elevation: f64, x: f64, y: f64, target_type: TargetType, ammo_type: AmmoKind) -> Self {
-        TargetPosition {
-            name,
-            elevation,
-            x,
-            y,
-            target_type,
-            ammo_type,
+    /// ```
+    /// use mortar::MortarStatus;
+    /// assert_eq!(MortarStatus::parse_str("moving"), Some(MortarStatus::Moving));
+    /// assert_eq!(MortarStatus::parse_str("OOA"), Some(MortarStatus::OutOfAction));
+    /// assert_eq!(MortarStatus::parse_str("idle"), None);
+    /// ```
+    pub fn parse_str(s: &str) -> Option<Self> {
+        match s.trim().to_uppercase().replace('-', "_").as_str() {
+            "READY" => Some(MortarStatus::Ready),
+            "MOVING" => Some(MortarStatus::Moving),
+            "OUT_OF_ACTION" | "OOA" => Some(MortarStatus::OutOfAction),
+            "RESUPPLY" => Some(MortarStatus::Resupply),
+            _ => None,
         }
     }
+}
 
-    /// Convertit en position générique.
-    pub fn as_position(&self) -> Position {
-        Position::new(self.name.clone(), self.elevation, self.x, self.y)
+impl std::fmt::Display for MortarStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
-// ============================================================================
-// Tables balistiques
-// ============================================================================
-
-/// Point de données balistiques associant une portée à une élévation.
+/// Chaîne de repli pour l'altitude d'une position saisie sans altitude.
 ///
-/// Représente un point de la table de tir pour une munition et un anneau donnés.
-#[derive(Clone, Debug)]
-pub struct BallisticPoint {
-    /// Portée en mètres
-    pub range_m: f64,
-    /// Élévation en millièmes (mils)
-    pub elev_mil: f64,
-}
-
-/// Table balistique contenant les points de données pour une munition/anneau.
+/// Faute de carte d'altitude ([`terrain::TerrainModel`]) couvrant la
+/// position, l'altitude est copiée de la position connue la
+/// plus proche si `copy_nearest` est actif, sinon (ou s'il n'y en a aucune)
+/// l'altitude de référence `datum_m` est retenue.
 ///
-/// Permet l'interpolation linéaire pour obtenir l'élévation à n'importe
-/// quelle portée dans les limites de la table.
-#[derive(Clone, Debug)]
-pub struct BallisticTable {
-    /// Points de données triés par portée croissante
-    pub points: Vec<BallisticPoint>,
+/// # Exemple
+///
+/// ```
+/// use mortar::{ElevationFallback, ElevationSource, Position};
+///
+/// let known = vec![
+///     Position::new("M1".into(), 120.0, 0.0, 0.0),
+///     Position::new("T1".into(), 80.0, 1000.0, 0.0),
+/// ];
+/// let fallback = ElevationFallback { copy_nearest: true, datum_m: 0.0 };
+/// assert_eq!(fallback.resolve(900.0, 0.0, &known), (80.0, ElevationSource::NearestPosition));
+///
+/// let datum = ElevationFallback { copy_nearest: false, datum_m: 50.0 };
+/// assert_eq!(datum.resolve(900.0, 0.0, &known), (50.0, ElevationSource::Datum));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ElevationFallback {
+    /// Copier l'altitude de la position connue la plus proche
+    pub copy_nearest: bool,
+    /// Altitude de référence en mètres
+    pub datum_m: f64,
 }
 
-impl BallisticTable {
-    /// Charge une table balistique depuis un fichier CSV.
-    ///
-    /// Le fichier doit contenir au minimum les colonnes `range_m` et `elev_mil`.
+impl ElevationFallback {
+    /// Retourne l'altitude à retenir en `(x, y)` et son origine.
     ///
     /// # Arguments
     ///
-    /// * `path` - Chemin vers le fichier CSV
+    /// * `x`, `y` - Coordonnées de la position sans altitude
+    /// * `known` - Positions dont l'altitude a été saisie
+    pub fn resolve(&self, x: f64, y: f64, known: &[Position]) -> (f64, ElevationSource) {
+        let nearest = known.iter().filter(|_| self.copy_nearest).min_by(|a, b| {
+            let da = (a.x - x).hypot(a.y - y);
+            let db = (b.x - x).hypot(b.y - y);
+            da.partial_cmp(&db).unwrap_or(Ordering::Equal)
+        });
+        match nearest {
+            Some(p) => (p.elevation, ElevationSource::NearestPosition),
+            None => (self.datum_m, ElevationSource::Datum),
+        }
+    }
+}
+
+/// Position d'un mortier.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MortarPosition {
+    /// Identifiant du mortier (ex: "M1", "Alpha")
+    pub name: String,
+    /// Altitude en mètres
+    pub elevation: f64,
+    /// Coordonnée X en mètres
+    pub x: f64,
+    /// Coordonnée Y en mètres
+    pub y: f64,
+    /// Anneaux indisponibles pour cette pièce (ex: plus de charges 4)
+    #[serde(default)]
+    pub excluded_rings: BTreeSet<Ring>,
+    /// Anneau employé par défaut lorsqu'aucun n'est précisé
+    #[serde(default)]
+    pub default_ring: Option<Ring>,
+    /// Origine de l'altitude
+    #[serde(default, skip_serializing_if = "ElevationSource::is_provided")]
+    pub elevation_source: ElevationSource,
+    /// Système d'arme de la pièce (`caliber` dans les sauvegardes antérieures)
+    #[serde(default, alias = "caliber")]
+    pub weapon: WeaponSystem,
+    /// Positions précédentes, de la plus ancienne à la plus récente
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub displacements: Vec<Displacement>,
+    /// Rendement des charges : fraction de la portée des tables réellement
+    /// atteinte (ex: 0.97 pour des charges humides ou anciennes tirant 3 %
+    /// court), `None` pour des charges nominales
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charge_efficiency: Option<f64>,
+    /// Écart de vitesse initiale du tube en % (ex: -2.0 pour un tube usé
+    /// perdant 2 % de vitesse), `None` pour un tube neuf
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mv_correction_pct: Option<f64>,
+    /// Correction tirée du dernier réglage sur point connu, appliquée à
+    /// toutes les solutions de la pièce
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration: Option<registration::Registration>,
+    /// Disponibilité de la pièce ; seules les pièces prêtes reçoivent des missions
+    #[serde(default)]
+    pub status: MortarStatus,
+    /// Date du dernier changement d'état (secondes Unix)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_since: Option<u64>,
+    /// Secteur de tir assigné ; la pièce ne reçoit pas de cible en dehors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sector: Option<Sector>,
+    /// Multiplicateurs de dispersion par anneau (usure du tube, état du
+    /// bipied), appliqués aux dispersions des tables ; un anneau absent garde
+    /// la dispersion des tables
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dispersion_factors: BTreeMap<Ring, f64>,
+}
+
+/// Secteur de tir assigné à une pièce : limites gauche et droite en azimut
+/// et portée maximale.
+///
+/// Le secteur s'étend de la limite gauche à la limite droite dans le sens
+/// horaire, en passant éventuellement par le Nord.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::Sector;
+///
+/// let sector = Sector { left_deg: 300.0, right_deg: 60.0, max_range_m: Some(2000.0) };
+/// assert!(sector.contains(0.0, 1500.0));
+/// assert!(sector.contains(45.0, 2000.0));
+/// assert!(!sector.contains(90.0, 1500.0));
+/// assert!(!sector.contains(10.0, 2500.0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Sector {
+    /// Limite gauche (azimut en degrés, ou `"5300mil"`)
+    #[serde(deserialize_with = "deserialize_angle_deg")]
+    pub left_deg: f64,
+    /// Limite droite (azimut en degrés, ou `"1100mil"`)
+    #[serde(deserialize_with = "deserialize_angle_deg")]
+    pub right_deg: f64,
+    /// Portée maximale assignée (mètres), sans limite si absente
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_range_m: Option<f64>,
+}
+
+impl Sector {
+    /// Vérifie que les limites sont des azimuts distincts dans [0, 360[ et
+    /// que la portée maximale est positive.
+    pub fn is_valid(&self) -> bool {
+        let azimuth = |deg: f64| (0.0..360.0).contains(&deg);
+        azimuth(self.left_deg)
+            && azimuth(self.right_deg)
+            && self.left_deg != self.right_deg
+            && self.max_range_m.is_none_or(|m| m.is_finite() && m > 0.0)
+    }
+
+    /// Ouverture du secteur en degrés.
+    pub fn width_deg(&self) -> f64 {
+        (self.right_deg - self.left_deg).rem_euclid(360.0)
+    }
+
+    /// Indique si un point à cet azimut et cette distance est dans le secteur.
+    pub fn contains(&self, azimuth_deg: f64, distance_m: f64) -> bool {
+        (azimuth_deg - self.left_deg).rem_euclid(360.0) <= self.width_deg()
+            && self.max_range_m.is_none_or(|m| distance_m <= m)
+    }
+}
+
+impl std::fmt::Display for Sector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0}-{:.0} deg", self.left_deg, self.right_deg)?;
+        if let Some(m) = self.max_range_m {
+            write!(f, ", max {:.0} m", m)?;
+        }
+        Ok(())
+    }
+}
+
+/// Bornes acceptées du rendement des charges d'une pièce.
+pub const CHARGE_EFFICIENCY_RANGE: std::ops::RangeInclusive<f64> = 0.5..=1.2;
+
+/// Bornes acceptées du multiplicateur de dispersion d'une pièce : jamais plus
+/// précise que les tables.
+pub const DISPERSION_FACTOR_RANGE: std::ops::RangeInclusive<f64> = 1.0..=5.0;
+
+/// Bornes acceptées de l'écart de vitesse initiale d'un tube (%).
+pub const MV_CORRECTION_RANGE_PCT: std::ops::RangeInclusive<f64> = -20.0..=10.0;
+
+/// Position quittée par un mortier lors d'un déplacement.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Displacement {
+    /// Coordonnée X quittée en mètres
+    pub x: f64,
+    /// Coordonnée Y quittée en mètres
+    pub y: f64,
+    /// Altitude quittée en mètres
+    pub elevation: f64,
+    /// Date du déplacement (secondes Unix)
+    pub moved_at: u64,
+}
+
+/// Données de remise en direction d'une cible après un déplacement.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RelayTarget {
+    /// Nom de la cible
+    pub target: String,
+    /// Gisement depuis la nouvelle position (mils)
+    pub azimuth_mil: f64,
+    /// Distance depuis la nouvelle position (mètres)
+    pub distance_m: f64,
+    /// Gisement depuis la position quittée (mils)
+    pub previous_azimuth_mil: f64,
+    /// Distance depuis la position quittée (mètres)
+    pub previous_distance_m: f64,
+    /// Variation de gisement, dans ]-3200, 3200] mils (positif : vers la droite)
+    pub azimuth_shift_mil: f64,
+}
+
+impl MortarPosition {
+    /// Crée une nouvelle position de mortier.
+    pub fn new(name: String, elevation: f64, x: f64, y: f64) -> Self {
+        MortarPosition {
+            name,
+            elevation,
+            x,
+            y,
+            excluded_rings: BTreeSet::new(),
+            default_ring: None,
+            elevation_source: ElevationSource::Provided,
+            weapon: WeaponSystem::default(),
+            displacements: Vec::new(),
+            charge_efficiency: None,
+            mv_correction_pct: None,
+            registration: None,
+            status: MortarStatus::Ready,
+            status_since: None,
+            sector: None,
+            dispersion_factors: BTreeMap::new(),
+        }
+    }
+
+    /// Multiplicateur de dispersion d'un anneau (1 sans multiplicateur déclaré).
+    pub fn dispersion_factor(&self, ring: Ring) -> f64 {
+        self.dispersion_factors.get(&ring).copied().unwrap_or(1.0)
+    }
+
+    /// Indique si le point `(x, y)` est dans le secteur de tir de la pièce
+    /// (toujours vrai sans secteur).
+    pub fn in_sector(&self, x: f64, y: f64) -> bool {
+        self.sector.is_none_or(|sector| {
+            let origin = self.as_position();
+            let point = Position::new(String::new(), 0.0, x, y);
+            sector.contains(origin.azimuth_to(&point), origin.distance_to(&point))
+        })
+    }
+
+    /// Vérifie que la cible est dans le secteur de tir de la pièce.
     ///
     /// # Erreurs
     ///
-    /// Retourne une erreur si le fichier ne peut pas être lu ou parsé.
-    ///
-    /// # Format CSV attendu
-    ///
-    /// ```csv
-    /// range_m,elev_mil,time_flight_s,delta_elev_per_100m_mil,time_flight_per_100m_s
-    /// 50,1540,13.2,61,
-    /// 100,1479,13.2,63,0.2
-    /// ```
-    pub fn from_csv<P: AsRef<Path>>(path: P) -> Result<Self> {
-        #[derive(Deserialize)]
-        struct Row {
-            range_m: f64,
-            elev_mil: f64,
+    /// Retourne une erreur si la cible est hors du secteur.
+    pub fn check_sector(&self, target: &TargetPosition) -> Result<()> {
+        match self.sector {
+            Some(sector) if !self.in_sector(target.x, target.y) => bail!(
+                "Target '{}' is outside the sector of fire of mortar '{}' ({})",
+                target.name,
+                self.name,
+                sector
+            ),
+            _ => Ok(()),
         }
+    }
 
-        let f = File::open(&path)?;
-        let mut rdr = csv::Reader::from_reader(f);
-
-        let mut pts: Vec<BallisticPoint> = Vec::new();
-        for rec in rdr.deserialize::<Row>() {
-            let r = rec?;
-            if r.range_m.is_finite() && r.elev_mil.is_finite() {
-                pts.push(BallisticPoint {
-                    range_m: r.range_m,
-                    elev_mil: r.elev_mil,
-                });
-            }
+    /// Change l'état de la pièce et date le changement.
+    pub fn set_status(&mut self, status: MortarStatus, now: u64) {
+        if self.status != status {
+            self.status = status;
+            self.status_since = Some(now);
         }
-
-        pts.sort_by(|a, b| a.range_m.partial_cmp(&b.range_m).unwrap());
-        Ok(Self { points: pts })
     }
 
-    /// Retourne les bornes de portée de la table (min, max).
+    /// Vérifie que la pièce peut recevoir une mission.
     ///
-    /// # Retourne
+    /// # Erreurs
     ///
-    /// `Some((min, max))` si la table contient des points, `None` sinon.
-    pub fn range_bounds(&self) -> Option<(f64, f64)> {
-        let first = self.points.first()?.range_m;
-        let last = self.points.last()?.range_m;
-        Some((first, last))
+    /// Retourne une erreur si la pièce n'est pas prête ([`MortarStatus::Ready`]).
+    pub fn check_ready(&self) -> Result<()> {
+        if self.status.is_ready() {
+            Ok(())
+        } else {
+            bail!("Mortar '{}' is not ready ({})", self.name, self.status)
+        }
     }
 
-    /// Calcule l'élévation pour une portée donnée par interpolation linéaire.
+    /// Fraction de la portée des tables atteinte du fait de l'écart de
+    /// vitesse initiale du tube : la portée varie comme le carré de la vitesse.
     ///
-    /// # Arguments
+    /// # Exemple
     ///
-    /// * `range_m` - Portée en mètres
+    /// ```
+    /// use mortar::MortarPosition;
     ///
-    /// # Retourne
+    /// let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+    /// assert_eq!(mortar.mv_range_factor(), 1.0);
+    /// mortar.mv_correction_pct = Some(-10.0);
+    /// assert!((mortar.mv_range_factor() - 0.81).abs() < 1e-9);
+    /// ```
+    pub fn mv_range_factor(&self) -> f64 {
+        let v = 1.0 + self.mv_correction_pct.unwrap_or(0.0) / 100.0;
+        v * v
+    }
+
+    /// Portée de table à viser pour atteindre `range_m` compte tenu du
+    /// rendement des charges et de l'usure du tube.
     ///
-    /// `Some(elev_mil)` si la portée est dans les limites de la table, `None` sinon.
+    /// # Exemple
     ///
-    /// # Algorithme
+    /// ```
+    /// use mortar::MortarPosition;
     ///
-    /// Utilise une interpolation linéaire entre les deux points encadrant
-    /// la portée demandée.
-    pub fn elev_at(&self, range_m: f64) -> Option<f64> {
-        if self.points.len() < 2 {
-            return None;
-        }
-        let (minr, maxr) = self.range_bounds()?;
-        if range_m < minr || range_m > maxr {
-            return None;
-        }
+    /// let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+    /// assert_eq!(mortar.table_range(970.0), 970.0);
+    /// // Charges tirant 3 % court : viser 1000 m de table pour 970 m au sol
+    /// mortar.charge_efficiency = Some(0.97);
+    /// assert!((mortar.table_range(970.0) - 1000.0).abs() < 1e-9);
+    /// assert!((mortar.ground_range(1000.0) - 970.0).abs() < 1e-9);
+    /// ```
+    pub fn table_range(&self, range_m: f64) -> f64 {
+        range_m / (self.charge_efficiency.unwrap_or(1.0) * self.mv_range_factor())
+    }
 
-        if let Ok(i) = self
-            .points
-            .binary_search_by(|p| p.range_m.partial_cmp(&range_m).unwrap())
-        {
-            return Some(self.points[i].elev_mil);
-        }
+    /// Portée au sol atteinte avec une portée de table, inverse de
+    /// [`table_range`](Self::table_range).
+    pub fn ground_range(&self, table_range_m: f64) -> f64 {
+        table_range_m * self.charge_efficiency.unwrap_or(1.0) * self.mv_range_factor()
+    }
 
-        let idx = match self
-            .points
-            .binary_search_by(|p| p.range_m.partial_cmp(&range_m).unwrap())
-        {
-            Ok(i) => i,
-            Err(ins) => ins.saturating_sub(1),
+    /// Déplace la pièce en conservant la position quittée dans
+    /// [`displacements`](Self::displacements). Le réglage de la pièce, propre
+    /// à sa position, est effacé.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::MortarPosition;
+    /// let mut mortar = MortarPosition::new("M1".into(), 100.0, 0.0, 0.0);
+    /// mortar.move_to(120.0, 50.0, 20.0, 1_760_000_000);
+    /// assert_eq!((mortar.x, mortar.y, mortar.elevation), (50.0, 20.0, 120.0));
+    /// assert_eq!(mortar.displacements[0].x, 0.0);
+    /// ```
+    pub fn move_to(&mut self, elevation: f64, x: f64, y: f64, moved_at: u64) {
+        self.displacements.push(Displacement {
+            x: self.x,
+            y: self.y,
+            elevation: self.elevation,
+            moved_at,
+        });
+        self.x = x;
+        self.y = y;
+        self.elevation = elevation;
+        self.registration = None;
+    }
+
+    /// Données de remise en direction vers `targets` depuis la position
+    /// actuelle, comparées à la dernière position quittée.
+    ///
+    /// # Retourne
+    ///
+    /// Une entrée par cible, vide si la pièce ne s'est jamais déplacée.
+    pub fn relay_data(&self, targets: &[TargetPosition]) -> Vec<RelayTarget> {
+        let previous = match self.displacements.last() {
+            Some(d) => Position::new(self.name.clone(), d.elevation, d.x, d.y),
+            None => return Vec::new(),
         };
-        if idx + 1 >= self.points.len() {
-            return Some(self.points.last()?.elev_mil);
-        }
+        let current = self.as_position();
+        targets
+            .iter()
+            .map(|t| {
+                let target = t.as_position();
+                let azimuth_mil = mils::deg_to_mil(current.azimuth_to(&target));
+                let previous_azimuth_mil = mils::deg_to_mil(previous.azimuth_to(&target));
+                let shift = mils::signed(azimuth_mil - previous_azimuth_mil);
+                RelayTarget {
+                    target: t.name.clone(),
+                    azimuth_mil,
+                    distance_m: current.distance_to(&target),
+                    previous_azimuth_mil,
+                    previous_distance_m: previous.distance_to(&target),
+                    azimuth_shift_mil: shift,
+                }
+            })
+            .collect()
+    }
 
-        let p0 = &self.points[idx];
-        let p1 = &self.points[idx + 1];
-        let t = (range_m - p0.range_m) / (p1.range_m - p0.range_m);
-        Some(p0.elev_mil + t * (p1.elev_mil - p0.elev_mil))
+    /// Vérifie que la pièce peut tirer la munition, d'après les calibres du
+    /// registre ; une munition inconnue du registre n'est tirée par aucune.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::ammo::AmmoRegistry;
+    /// use mortar::{AmmoKind, MortarPosition, WeaponSystem};
+    /// let registry = AmmoRegistry::default();
+    /// let mut mortar = MortarPosition::new("M2".into(), 0.0, 0.0, 0.0);
+    /// assert!(mortar.check_ammo(AmmoKind::He, &registry).is_ok());
+    /// mortar.weapon = WeaponSystem::M252;
+    /// assert_eq!(
+    ///     mortar.check_ammo(AmmoKind::He, &registry).unwrap_err().to_string(),
+    ///     "HE ammo (60mm) cannot be fired from mortar 'M2' (81mm)"
+    /// );
+    /// ```
+    pub fn check_ammo(
+        &self,
+        ammo: AmmoKind,
+        registry: &AmmoRegistry,
+    ) -> std::result::Result<(), AmmoIncompatibility> {
+        let calibers = registry.calibers(ammo);
+        if calibers.contains(&self.caliber()) {
+            Ok(())
+        } else {
+            Err(AmmoIncompatibility {
+                mortar: self.name.clone(),
+                caliber: self.caliber(),
+                ammo,
+                calibers: calibers.to_vec(),
+            })
+        }
     }
-}
 
-/// Type alias pour le numéro d'anneau de précision (0-4).
-pub type Ring = u8;
+    /// Retourne le calibre de la pièce.
+    pub fn caliber(&self) -> Caliber {
+        self.weapon.caliber()
+    }
 
-// ============================================================================
-// Données de dispersion
-// ============================================================================
+    /// Indique si l'anneau est utilisable par cette pièce : il existe sur
+    /// son système d'arme et n'est pas exclu.
+    pub fn ring_available(&self, ring: Ring) -> bool {
+        usize::from(ring) < self.weapon.ring_count() && !self.excluded_rings.contains(&ring)
+    }
 
-/// Structure interne pour la désérialisation du fichier metrics.json.
-#[derive(Clone, Debug, Deserialize)]
-pub struct MetricsFile {
-    /// Map des dispersions par type de munition et anneau
-    pub dispersion: BTreeMap<String, BTreeMap<String, f64>>,
-}
+    /// Retourne les anneaux utilisables par cette pièce qui couvrent la portée,
+    /// par ordre croissant (voir [`available_rings`]).
+    ///
+    /// `range_m` est la portée au sol : elle est convertie en portée de table
+    /// selon le rendement des charges (voir [`table_range`](Self::table_range)).
+    pub fn usable_rings(
+        &self,
+        ballistics: &BallisticTables,
+        ammo: AmmoKind,
+        range_m: f64,
+    ) -> Vec<Ring> {
+        available_rings(ballistics, self.weapon, ammo, self.table_range(range_m))
+            .into_iter()
+            .filter(|r| self.ring_available(*r))
+            .collect()
+    }
 
-/// Table de dispersion associant chaque couple (munition, anneau) à un rayon de dispersion.
-///
-/// Les valeurs sont en mètres et représentent le rayon de dispersion probable
-/// (CEP - Circular Error Probable).
-pub type DispersionTable = BTreeMap<(AmmoKind, Ring), f64>;
+    /// Retourne l'anneau à employer pour la portée : l'anneau par défaut de la
+    /// pièce s'il est disponible et couvre la portée, sinon le plus petit
+    /// anneau utilisable.
+    pub fn preferred_ring(
+        &self,
+        ballistics: &BallisticTables,
+        ammo: AmmoKind,
+        range_m: f64,
+    ) -> Option<Ring> {
+        let usable = self.usable_rings(ballistics, ammo, range_m);
+        self.default_ring
+            .filter(|r| usable.contains(r))
+            .or_else(|| usable.first().copied())
+    }
 
-/// Charge les données de dispersion depuis le répertoire par défaut (`data/`).
-///
-/// # Erreurs
-///
-/// Retourne une erreur si le fichier `data/metrics.json` ne peut pas être lu.
-pub fn load_dispersion() -> Result<DispersionTable> {
-    load_dispersion_from("data")
+    /// Convertit en position générique.
+    pub fn as_position(&self) -> Position {
+        Position::new(self.name.clone(), self.elevation, self.x, self.y)
+    }
 }
 
-/// Charge les données de dispersion depuis un répertoire spécifié.
+/// Constructeur de [`MortarPosition`] avec validation.
 ///
-/// # Arguments
+/// # Exemple
 ///
-/// * `base` - Chemin du répertoire contenant `metrics.json`
+/// ```
+/// use mortar::{MortarPosition, WeaponSystem};
 ///
-/// # Format du fichier metrics.json
+/// let mortar = MortarPosition::builder()
+///     .name("M1")
+///     .at(0.0, 0.0)
+///     .elevation(100.0)
+///     .weapon(WeaponSystem::M224)
+///     .default_ring(2)
+///     .build()
+///     .unwrap();
+/// assert_eq!(mortar.elevation, 100.0);
+/// assert_eq!(mortar.default_ring, Some(2));
 ///
-/// ```json
-/// {
-///     "dispersion": {
-///         "HE": { "0R": 10, "1R": 23, "2R": 39, "3R": 54, "4R": 69 },
-///         "PRACTICE": { "0R": 10, "1R": 24, "2R": 39, "3R": 54, "4R": 68 }
-///     }
-/// }
+/// let worn = MortarPosition::builder().name("M2").at(0.0, 0.0).charge_efficiency(0.2);
+/// assert!(worn.build().is_err());
 /// ```
-pub fn load_dispersion_from<P: AsRef<Path>>(base: P) -> Result<DispersionTable> {
-    let path = base.as_ref().join("metrics.json");
-    let file = File::open(&path)?;
-    let reader = BufReader::new(file);
-    let metrics: MetricsFile = serde_json::from_reader(reader)?;
+#[derive(Clone, Debug, Default)]
+pub struct MortarBuilder {
+    name: String,
+    position: Option<(f64, f64)>,
+    elevation: f64,
+    weapon: WeaponSystem,
+    excluded_rings: BTreeSet<Ring>,
+    default_ring: Option<Ring>,
+    charge_efficiency: Option<f64>,
+    mv_correction_pct: Option<f64>,
+    dispersion_factors: BTreeMap<Ring, f64>,
+    status: MortarStatus,
+    sector: Option<Sector>,
+}
 
-    let mut table = DispersionTable::new();
+impl MortarPosition {
+    /// Commence la construction d'une pièce (voir [`MortarBuilder`]).
+    pub fn builder() -> MortarBuilder {
+        MortarBuilder::default()
+    }
+}
 
-    for (ammo_str, rings) in &metrics.dispersion {
-        let ammo = match AmmoKind::parse_str(ammo_str) {
-            Some(a) => a,
-            None => continue,
-        };
+impl MortarBuilder {
+    /// Identifiant du mortier (obligatoire).
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
 
-        for (ring_str, &value) in rings {
-            let ring: Ring = ring_str.trim_end_matches('R').parse().unwrap_or(0);
-            table.insert((ammo, ring), value);
+    /// Coordonnées X/Y en mètres (obligatoires).
+    pub fn at(mut self, x: f64, y: f64) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// Altitude en mètres (0 par défaut).
+    pub fn elevation(mut self, elevation: f64) -> Self {
+        self.elevation = elevation;
+        self
+    }
+
+    /// Système d'arme de la pièce.
+    pub fn weapon(mut self, weapon: WeaponSystem) -> Self {
+        self.weapon = weapon;
+        self
+    }
+
+    /// Exclut un anneau indisponible pour la pièce.
+    pub fn exclude_ring(mut self, ring: Ring) -> Self {
+        self.excluded_rings.insert(ring);
+        self
+    }
+
+    /// Anneau employé par défaut.
+    pub fn default_ring(mut self, ring: Ring) -> Self {
+        self.default_ring = Some(ring);
+        self
+    }
+
+    /// Rendement des charges (voir [`CHARGE_EFFICIENCY_RANGE`]).
+    pub fn charge_efficiency(mut self, factor: f64) -> Self {
+        self.charge_efficiency = Some(factor);
+        self
+    }
+
+    /// Écart de vitesse initiale du tube en % (voir [`MV_CORRECTION_RANGE_PCT`]).
+    pub fn mv_correction_pct(mut self, pct: f64) -> Self {
+        self.mv_correction_pct = Some(pct);
+        self
+    }
+
+    /// Multiplicateur de dispersion d'un anneau (voir [`DISPERSION_FACTOR_RANGE`]).
+    pub fn dispersion_factor(mut self, ring: Ring, factor: f64) -> Self {
+        self.dispersion_factors.insert(ring, factor);
+        self
+    }
+
+    /// Disponibilité initiale de la pièce (prête par défaut).
+    pub fn status(mut self, status: MortarStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Secteur de tir assigné.
+    pub fn sector(mut self, sector: Sector) -> Self {
+        self.sector = Some(sector);
+        self
+    }
+
+    /// Construit la pièce.
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si le nom est vide, si les coordonnées manquent ou
+    /// ne sont pas finies, si l'anneau par défaut est indisponible, si le
+    /// secteur est invalide, ou si le rendement des charges, l'écart de
+    /// vitesse initiale ou un multiplicateur de dispersion sort de ses bornes.
+    pub fn build(self) -> Result<MortarPosition> {
+        let (x, y) = check_builder_position(&self.name, self.position, self.elevation)?;
+        if let Some(f) = self
+            .charge_efficiency
+            .filter(|f| !CHARGE_EFFICIENCY_RANGE.contains(f))
+        {
+            bail!(
+                "Charge efficiency {} out of range ({}-{})",
+                f,
+                CHARGE_EFFICIENCY_RANGE.start(),
+                CHARGE_EFFICIENCY_RANGE.end()
+            );
+        }
+        if let Some(pct) = self
+            .mv_correction_pct
+            .filter(|pct| !MV_CORRECTION_RANGE_PCT.contains(pct))
+        {
+            bail!(
+                "Muzzle velocity correction {}% out of range ({}-{}%)",
+                pct,
+                MV_CORRECTION_RANGE_PCT.start(),
+                MV_CORRECTION_RANGE_PCT.end()
+            );
+        }
+        if let Some((ring, f)) = self
+            .dispersion_factors
+            .iter()
+            .find(|(_, f)| !DISPERSION_FACTOR_RANGE.contains(*f))
+        {
+            bail!(
+                "Dispersion factor {} for ring {}R out of range ({}-{})",
+                f,
+                ring,
+                DISPERSION_FACTOR_RANGE.start(),
+                DISPERSION_FACTOR_RANGE.end()
+            );
         }
+        if let Some(sector) = self.sector.filter(|s| !s.is_valid()) {
+            bail!("Invalid sector of fire: {}", sector);
+        }
+        let mut mortar = MortarPosition::new(self.name, self.elevation, x, y);
+        mortar.weapon = self.weapon;
+        mortar.sector = self.sector;
+        mortar.excluded_rings = self.excluded_rings;
+        if let Some(ring) = self.default_ring {
+            if !mortar.ring_available(ring) {
+                bail!(
+                    "Default ring {}R is not available on mortar '{}'",
+                    ring,
+                    mortar.name
+                );
+            }
+        }
+        mortar.default_ring = self.default_ring;
+        mortar.charge_efficiency = self.charge_efficiency;
+        mortar.mv_correction_pct = self.mv_correction_pct;
+        mortar.dispersion_factors = self.dispersion_factors;
+        mortar.status = self.status;
+        Ok(mortar)
     }
+}
 
-    Ok(table)
+/// Autorisation de tir d'une cible, délivrée à l'issue du contrôle de sécurité.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Clearance {
+    /// Auteur de l'autorisation (indicatif, nom...)
+    pub cleared_by: String,
+    /// Date de l'autorisation (secondes Unix)
+    pub cleared_at: u64,
 }
 
-/// Calcule la dispersion ajustée en fonction du dénivelé mortier-cible.
-///
-/// La dispersion est modifiée selon la règle suivante :
-/// - Mortier plus haut que la cible : **+5% par mètre** de dénivelé
-/// - Mortier plus bas que la cible : **-1% par mètre** de dénivelé
-///
-/// # Arguments
-///
-/// * `base_dispersion` - Dispersion de base en mètres (depuis la table)
-/// * `mortar_elevation` - Altitude du mortier en mètres
-/// * `target_elevation` - Altitude de la cible en mètres
-///
-/// # Retourne
-///
-/// Dispersion ajustée en mètres.
-///
-/// # Formule
+/// Position d'une cible avec son type tactique et le type de munition à employer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TargetPosition {
+    /// Identifiant de la cible (ex: "T1", "Objectif Alpha")
+    pub name: String,
+    /// Altitude en mètres
+    pub elevation: f64,
+    /// Coordonnée X en mètres
+    pub x: f64,
+    /// Coordonnée Y en mètres
+    pub y: f64,
+    /// Type tactique de la cible
+    pub target_type: TargetType,
+    /// Type de munition à utiliser contre cette cible
+    pub ammo_type: AmmoKind,
+    /// Historique des corrections appliquées sur place
+    #[serde(default)]
+    pub corrections: Vec<CorrectionRecord>,
+    /// Date de dernière observation (secondes Unix)
+    #[serde(default)]
+    pub last_observed: Option<u64>,
+    /// Source du renseignement (observateur, drone, patrouille...)
+    #[serde(default)]
+    pub reporting_source: Option<String>,
+    /// Fiabilité du renseignement
+    #[serde(default)]
+    pub reliability: Option<Reliability>,
+    /// Origine de l'altitude
+    #[serde(default, skip_serializing_if = "ElevationSource::is_provided")]
+    pub elevation_source: ElevationSource,
+    /// Autorisation de tir ; absente tant que la cible n'est pas autorisée
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clearance: Option<Clearance>,
+    /// Rayon d'incertitude de la localisation (mètres), pour une position
+    /// estimée (zone de départ ennemie)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uncertainty_m: Option<f64>,
+}
+
+impl TargetPosition {
+    /// Crée une nouvelle position de cible.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Identifiant de la cible
+    /// * `elevation` - Altitude en mètres
+    /// * `x` - Coordonnée X en mètres
+    /// * `y` - Coordonnée Y en mètres
+    /// * `target_type` - Type tactique de la cible
+    /// * `ammo_type` - Type de munition à employer
+    pub fn new(
+        name: String,
+        elevation: f64,
+        x: f64,
+        y: f64,
+        target_type: TargetType,
+        ammo_type: AmmoKind,
+    ) -> Self {
+        TargetPosition {
+            name,
+            elevation,
+            x,
+            y,
+            target_type,
+            ammo_type,
+            corrections: Vec::new(),
+            last_observed: None,
+            reporting_source: None,
+            reliability: None,
+            elevation_source: ElevationSource::Provided,
+            clearance: None,
+            uncertainty_m: None,
+        }
+    }
+
+    /// Convertit en position générique.
+    pub fn as_position(&self) -> Position {
+        Position::new(self.name.clone(), self.elevation, self.x, self.y)
+    }
+
+    /// Indique si la cible a reçu son autorisation de tir.
+    pub fn is_cleared(&self) -> bool {
+        self.clearance.is_some()
+    }
+
+    /// Retourne l'âge du renseignement en secondes, si la date d'observation est connue.
+    pub fn observation_age(&self, now: u64) -> Option<u64> {
+        self.last_observed.map(|t| now.saturating_sub(t))
+    }
+
+    /// Indique si la cible satisfait les critères de renseignement.
+    ///
+    /// Une cible sans fiabilité (resp. sans date d'observation) est exclue dès
+    /// qu'un filtre de fiabilité (resp. d'âge) est demandé.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_reliability` - Fiabilité minimale acceptée
+    /// * `max_age_s` - Âge maximal du renseignement en secondes
+    /// * `now` - Heure de référence (secondes Unix)
+    pub fn matches_intel(
+        &self,
+        min_reliability: Option<Reliability>,
+        max_age_s: Option<u64>,
+        now: u64,
+    ) -> bool {
+        let reliable = match min_reliability {
+            Some(min) => self.reliability.is_some_and(|r| r <= min),
+            None => true,
+        };
+        let fresh = match max_age_s {
+            Some(max) => self.observation_age(now).is_some_and(|age| age <= max),
+            None => true,
+        };
+        reliable && fresh
+    }
+
+    /// Retourne la péremption du renseignement, si la date d'observation est
+    /// connue.
+    ///
+    /// Le score croît de 0 (observation à l'instant) vers 1 selon la demi-vie
+    /// du type de cible ([`TargetType::knowledge_half_life_s`]) : il vaut 0.5
+    /// au bout d'une demi-vie, 0.75 au bout de deux.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::{AmmoKind, TargetPosition, TargetType};
+    ///
+    /// let mut t = TargetPosition::new("T1".into(), 0.0, 0.0, 0.0, TargetType::Vehicule, AmmoKind::He);
+    /// assert!(t.staleness(1000).is_none());
+    /// t.last_observed = Some(1000);
+    /// // Deux demi-vies (2 × 5 min) pour un véhicule : renseignement périmé
+    /// let staleness = t.staleness(1600).unwrap();
+    /// assert!((staleness.score - 0.75).abs() < 1e-9);
+    /// assert!(staleness.stale);
+    /// ```
+    pub fn staleness(&self, now: u64) -> Option<Staleness> {
+        let age_s = self.observation_age(now)?;
+        let half_life_s = self.target_type.knowledge_half_life_s();
+        let score = 1.0 - 0.5f64.powf(age_s as f64 / half_life_s as f64);
+        Some(Staleness {
+            age_s,
+            half_life_s,
+            score,
+            stale: self.target_type.is_mobile() && score >= STALE_SCORE,
+        })
+    }
+}
+
+/// Constructeur de [`TargetPosition`] avec validation.
 ///
-/// ```text
-/// delta = mortar_elevation - target_elevation
-/// si delta >= 0 : dispersion = base * (1 + delta * 0.05)
-/// si delta < 0  : dispersion = base * (1 + delta * 0.01)
-/// ```
+/// La munition est par défaut celle suggérée pour le type de cible.
 ///
 /// # Exemple
 ///
 /// ```
-/// use mortar::calculate_dispersion;
+/// use mortar::{AmmoKind, TargetPosition, TargetType};
 ///
-/// // Mortier 5m plus haut que la cible, dispersion base 39m
-/// let disp = calculate_dispersion(39.0, 105.0, 100.0);
-/// assert!((disp - 48.75).abs() < 0.01); // 39 * 1.25 = 48.75
+/// let target = TargetPosition::builder()
+///     .name("T1")
+///     .at(500.0, 300.0)
+///     .elevation(50.0)
+///     .target_type(TargetType::Soutien)
+///     .build()
+///     .unwrap();
+/// assert_eq!(target.ammo_type, AmmoKind::Smoke);
 ///
-/// // Mortier 10m plus bas que la cible
-/// let disp = calculate_dispersion(39.0, 90.0, 100.0);
-/// assert!((disp - 35.1).abs() < 0.01); // 39 * 0.90 = 35.1
+/// assert!(TargetPosition::builder().name("T2").build().is_err());
 /// ```
-pub fn calculate_dispersion(
-    base_dispersion: f64,
-    mortar_elevation: f64,
-    target_elevation: f64,
-) -> f64 {
-    let delta = mortar_elevation - target_elevation;
-    let factor = if delta >= 0.0 {
-        1.0 + delta * 0.05 // +5% per meter when mortar is higher
-    } else {
-        1.0 + delta * 0.01 // -1% per meter when mortar is lower (delta is negative)
-    };
-    base_dispersion * factor
+#[derive(Clone, Debug, Default)]
+pub struct TargetBuilder {
+    name: String,
+    position: Option<(f64, f64)>,
+    elevation: f64,
+    target_type: TargetType,
+    ammo_type: Option<AmmoKind>,
+    reporting_source: Option<String>,
+    reliability: Option<Reliability>,
+    last_observed: Option<u64>,
 }
 
-// ============================================================================
-// Chargement des tables balistiques
-// ============================================================================
-
-/// Charge toutes les tables balistiques depuis le répertoire par défaut (`data/`).
-///
-/// # Erreurs
-///
-/// Retourne une erreur si les fichiers CSV ne peuvent pas être lus.
-pub fn load_ballistics() -> Result<BTreeMap<(AmmoKind, Ring), BallisticTable>> {
-    load_ballistics_from("data")
+impl TargetPosition {
+    /// Commence la construction d'une cible (voir [`TargetBuilder`]).
+    pub fn builder() -> TargetBuilder {
+        TargetBuilder::default()
+    }
 }
 
-/// Charge toutes les tables balistiques depuis un répertoire spécifié.
-///
-/// # Structure attendue
-///
-/// ```text
-/// base/
-/// ├── PRACTICE/
-/// │   ├── M879_PRACTICE_0R.csv
-/// │   ├── M879_PRACTICE_1R.csv
-/// │   └── ...
-/// ├── HE/
-/// │   ├── M821_HE_0R.csv
-/// │   └── ...
-/// ├── SMOKE/
-/// │   ├── M819_SMOKE_1R.csv  (pas de 0R pour SMOKE)
-/// │   └── ...
-/// └── FLARE/
-///     ├── M853A1_FLARE_1R.csv  (pas de 0R pour FLARE)
-///     └── ...
-/// ```
-///
-/// # Arguments
-///
-/// * `base` - Chemin du répertoire de données
-pub fn load_ballistics_from<P: AsRef<Path>>(
-    base: P,
-) -> Result<BTreeMap<(AmmoKind, Ring), BallisticTable>> {
-    let base = base.as_ref();
-    let mut m: BTreeMap<(AmmoKind, Ring), BallisticTable> = BTreeMap::new();
+impl TargetBuilder {
+    /// Identifiant de la cible (obligatoire).
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
 
-    // PRACTICE (0..4)
-    for r in 0..=4u8 {
-        let p = base.join(format!("PRACTICE/M879_PRACTICE_{}R.csv", r));
-        if let Ok(t) = BallisticTable::from_csv(&p) {
-            m.insert((AmmoKind::Practice, r), t);
-        }
+    /// Coordonnées X/Y en mètres (obligatoires).
+    pub fn at(mut self, x: f64, y: f64) -> Self {
+        self.position = Some((x, y));
+        self
     }
 
-    // HE (0..4)
-    for r in 0..=4u8 {
-        let p = base.join(format!("HE/M821_HE_{}R.csv", r));
-        if let Ok(t) = BallisticTable::from_csv(&p) {
-            m.insert((AmmoKind::He, r), t);
-        }
+    /// Altitude en mètres (0 par défaut).
+    pub fn elevation(mut self, elevation: f64) -> Self {
+        self.elevation = elevation;
+        self
     }
 
-    // SMOKE (1..4) - pas de 0R
-    for r in 1..=4u8 {
-        let p = base.join(format!("SMOKE/M819_SMOKE_{}R.csv", r));
-        if let Ok(t) = BallisticTable::from_csv(&p) {
-            m.insert((AmmoKind::Smoke, r), t);
-        }
+    /// Type tactique de la cible (infanterie par défaut).
+    pub fn target_type(mut self, target_type: TargetType) -> Self {
+        self.target_type = target_type;
+        self
     }
 
-    // FLARE (1..4) - pas de 0R
-    for r in 1..=4u8 {
-        let p = base.join(format!("FLARE/M853A1_FLARE_{}R.csv", r));
-        if let Ok(t) = BallisticTable::from_csv(&p) {
-            m.insert((AmmoKind::Flare, r), t);
-        }
+    /// Munition à employer.
+    pub fn ammo(mut self, ammo: AmmoKind) -> Self {
+        self.ammo_type = Some(ammo);
+        self
+    }
+
+    /// Source du renseignement.
+    pub fn reporting_source(mut self, source: impl Into<String>) -> Self {
+        self.reporting_source = Some(source.into());
+        self
+    }
+
+    /// Fiabilité du renseignement.
+    pub fn reliability(mut self, reliability: Reliability) -> Self {
+        self.reliability = Some(reliability);
+        self
+    }
+
+    /// Date d'observation (secondes Unix).
+    pub fn observed_at(mut self, at: u64) -> Self {
+        self.last_observed = Some(at);
+        self
+    }
+
+    /// Construit la cible.
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si le nom est vide, si les coordonnées manquent ou
+    /// si une coordonnée n'est pas finie.
+    pub fn build(self) -> Result<TargetPosition> {
+        let (x, y) = check_builder_position(&self.name, self.position, self.elevation)?;
+        let ammo_type = self
+            .ammo_type
+            .unwrap_or_else(|| self.target_type.suggested_ammo());
+        let mut target =
+            TargetPosition::new(self.name, self.elevation, x, y, self.target_type, ammo_type);
+        target.reporting_source = self.reporting_source;
+        target.reliability = self.reliability;
+        target.last_observed = self.last_observed;
+        Ok(target)
+    }
+}
+
+/// Contrôles communs aux constructeurs de positions : nom non vide,
+/// coordonnées présentes et finies.
+fn check_builder_position(
+    name: &str,
+    position: Option<(f64, f64)>,
+    elevation: f64,
+) -> Result<(f64, f64)> {
+    if name.is_empty() {
+        bail!("Name cannot be empty");
+    }
+    let Some((x, y)) = position else {
+        bail!("Position of '{}' is missing", name);
+    };
+    if ![x, y, elevation].iter().all(|v| v.is_finite()) {
+        bail!("Position of '{}' must be finite", name);
     }
+    Ok((x, y))
+}
+
+/// Score de péremption à partir duquel le renseignement sur une cible mobile
+/// est jugé périmé (deux demi-vies).
+pub const STALE_SCORE: f64 = 0.75;
+
+/// Péremption du renseignement sur une cible.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Staleness {
+    /// Âge de la dernière observation (s)
+    pub age_s: u64,
+    /// Demi-vie du renseignement pour le type de cible (s)
+    pub half_life_s: u64,
+    /// Score de péremption, de 0 (frais) à 1 (position inconnue)
+    pub score: f64,
+    /// `true` si la cible est mobile et son score atteint [`STALE_SCORE`]
+    pub stale: bool,
+}
 
-    Ok(m)
+/// Renseigne [`FiringSolution::staleness`] et avertit avant de tirer sur une
+/// cible mobile dont le renseignement est périmé.
+pub fn attach_staleness(solution: &mut FiringSolution, target: &TargetPosition, now: u64) {
+    let staleness = match target.staleness(now) {
+        Some(s) => s,
+        None => return,
+    };
+    if staleness.stale {
+        solution.warnings.push(format!(
+            "Stale intel on mobile target '{}': observed {} min ago (staleness {:.0}%), confirm position before firing",
+            target.name,
+            staleness.age_s / 60,
+            staleness.score * 100.0
+        ));
+    }
+    solution.staleness = Some(staleness);
 }
 
 // ============================================================================
-// Solution de tir
+// Tables balistiques
 // ============================================================================
 
-/// Solution de tir complète entre un mortier et une cible.
+/// Point de données balistiques associant une portée à une élévation.
 ///
-/// Contient toutes les informations nécessaires pour effectuer un tir :
-/// - Paramètres géométriques (distance, azimut, dénivelé)
-/// - Élévations pour chaque type de munition et anneau
-/// - Dispersions ajustées pour le dénivelé
-/// - Solution sélectionnée basée sur la munition du mortier
-#[derive(Clone, Debug, Serialize)]
-pub struct FiringSolution {
-    /// Distance horizontale en mètres
-    pub distance_m: f64,
-    /// Azimut en degrés (0-360, depuis le Nord)
-    pub azimuth_deg: f64,
-    /// Différence d'élévation absolue en mètres
-    pub elevation_diff_m: f64,
-    /// Différence d'élévation signée (mortier - cible, positif = mortier plus haut)
-    pub signed_elevation_diff_m: f64,
-    /// Type de munition chargée dans le mortier
-    pub mortar_ammo: String,
-    /// Type tactique de la cible
-    pub target_type: String,
-    /// Type de munition recommandé pour cette cible
-    pub recommended_ammo: String,
-    /// Élévations par type de munition et anneau
-    /// Structure: `{ "HE": { "0R": 1200.5, "1R": 1180.2, ... }, ... }`
-    pub solutions: BTreeMap<String, BTreeMap<String, Option<f64>>>,
-    /// Dispersions ajustées par type de munition et anneau (en mètres)
-    pub dispersions: BTreeMap<String, BTreeMap<String, Option<f64>>>,
-    /// Solution sélectionnée basée sur la munition du mortier
-    pub selected_solution: Option<SelectedSolution>,
+/// Représente un point de la table de tir pour une munition et un anneau donnés.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BallisticPoint {
+    /// Portée en mètres
+    pub range_m: f64,
+    /// Élévation en millièmes (mils)
+    pub elev_mil: f64,
+    /// Durée de trajet en secondes, si la table la fournit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_flight_s: Option<f64>,
+    /// Variation d'élévation pour 100 m de portée (en mils), si la table la
+    /// fournit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_elev_per_100m_mil: Option<f64>,
 }
 
-/// Solution de tir sélectionnée pour un type de munition spécifique.
-#[derive(Clone, Debug, Serialize)]
-pub struct SelectedSolution {
-    /// Type de munition
-    pub ammo_type: String,
-    /// Élévations par anneau (en mils)
-    pub elevations: BTreeMap<String, Option<f64>>,
-    /// Dispersions ajustées par anneau (en mètres)
-    pub dispersions: BTreeMap<String, Option<f64>>,
+/// Mode d'interpolation des élévations entre deux lignes d'une table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// Interpolation linéaire entre les deux lignes encadrantes
+    #[default]
+    Linear,
+    /// Interpolation cubique monotone (PCHIP, voir [`pchip`]) sur la table
+    /// brute, sans lissage préalable du CSV
+    Pchip,
 }
 
-/// Calcule la solution de tir sans données de dispersion.
-///
-/// Équivalent à `calculate_solution_with_dispersion` avec une table de dispersion vide.
-///
-/// # Arguments
-///
-/// * `mortar` - Position du mortier
-/// * `target` - Position de la cible
-/// * `ballistics` - Tables balistiques chargées
-pub fn calculate_solution(
-    mortar: &MortarPosition,
-    target: &TargetPosition,
-    ballistics: &BTreeMap<(AmmoKind, Ring), BallisticTable>,
-) -> FiringSolution {
-    calculate_solution_with_dispersion(mortar, target, ballistics, &DispersionTable::new())
+impl Interpolation {
+    /// Retourne le nom du mode (`linear` ou `pchip`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Interpolation::Linear => "linear",
+            Interpolation::Pchip => "pchip",
+        }
+    }
+
+    /// Parse un nom de mode, insensible à la casse.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::Interpolation;
+    /// assert_eq!(Interpolation::parse_str("PCHIP"), Some(Interpolation::Pchip));
+    /// assert_eq!(Interpolation::parse_str("spline"), None);
+    /// ```
+    pub fn parse_str(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "linear" => Some(Interpolation::Linear),
+            "pchip" => Some(Interpolation::Pchip),
+            _ => None,
+        }
+    }
 }
 
-/// Calcule la solution de tir complète avec dispersions ajustées.
+/// Table balistique contenant les points de données pour une munition/anneau.
 ///
-/// # Arguments
+/// Permet l'interpolation (linéaire ou PCHIP) pour obtenir l'élévation à
+/// n'importe quelle portée dans les limites de la table.
+#[derive(Clone, Debug, Default)]
+pub struct BallisticTable {
+    /// Points de données triés par portée croissante
+    pub points: Vec<BallisticPoint>,
+    /// Mode d'interpolation des élévations
+    pub interpolation: Interpolation,
+    /// Élévations précalculées au mètre, voir [`BallisticTable::densify`]
+    pub dense: Option<DenseElevations>,
+}
+
+/// Élévations d'une table rééchantillonnées tous les [`DENSE_STEP_M`] mètres.
+#[derive(Clone, Debug, Default)]
+pub struct DenseElevations {
+    /// Portée du premier échantillon (en mètres)
+    pub start_m: f64,
+    /// Élévation (en mils) à `start_m + i * DENSE_STEP_M`
+    pub elev_mil: Vec<f64>,
+}
+
+/// Pas de rééchantillonnage des tables denses (en mètres).
+pub const DENSE_STEP_M: f64 = 1.0;
+
+/// Pas par défaut des courbes de tables rééchantillonnées, voir
+/// [`BallisticTable::curve`] (en mètres).
+pub const DEFAULT_CURVE_STEP_M: f64 = 10.0;
+
+impl BallisticTable {
+    /// Charge une table balistique depuis un fichier CSV.
+    ///
+    /// Le fichier doit contenir au minimum les colonnes `range_m` et `elev_mil`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Chemin vers le fichier CSV
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si le fichier ne peut pas être lu ou parsé.
+    ///
+    /// # Format CSV attendu
+    ///
+    /// ```csv
+    /// range_m,elev_mil,time_flight_s,delta_elev_per_100m_mil,time_flight_per_100m_s
+    /// 50,1540,13.2,61,
+    /// 100,1479,13.2,63,0.2
+    /// ```
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut pts = read_csv_points(path)?;
+        pts.sort_by(|a, b| a.range_m.partial_cmp(&b.range_m).unwrap());
+        Ok(Self {
+            points: pts,
+            ..Default::default()
+        })
+    }
+
+    /// Contrôle la qualité d'une table triée par portée.
+    ///
+    /// L'élévation doit décroître strictement lorsque la portée augmente ;
+    /// une portée en double ou une élévation qui remonte signale en général
+    /// une erreur de saisie qui fausserait l'interpolation
+    /// ([`TableReport::issues`]). Le rapport indique aussi la couverture en
+    /// portée de la table et les écarts anormalement grands entre deux lignes
+    /// ([`TableReport::gaps`]), signe d'une ligne manquante.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::{BallisticPoint, BallisticTable, TableIssue};
+    ///
+    /// let table = BallisticTable {
+    ///     points: vec![
+    ///         BallisticPoint { range_m: 100.0, elev_mil: 1400.0, time_flight_s: None, delta_elev_per_100m_mil: None },
+    ///         BallisticPoint { range_m: 200.0, elev_mil: 1450.0, time_flight_s: None, delta_elev_per_100m_mil: None }, // faute de frappe
+    ///         BallisticPoint { range_m: 300.0, elev_mil: 1200.0, time_flight_s: None, delta_elev_per_100m_mil: None },
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    /// let report = table.validate();
+    /// assert!(matches!(report.issues[0], TableIssue::NonMonotonic { .. }));
+    /// assert_eq!((report.min_range_m, report.max_range_m), (Some(100.0), Some(300.0)));
+    /// ```
+    pub fn validate(&self) -> TableReport {
+        let mut steps: Vec<f64> = self
+            .points
+            .windows(2)
+            .map(|w| w[1].range_m - w[0].range_m)
+            .filter(|step| *step > 0.0)
+            .collect();
+        steps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let median_step_m = steps.get(steps.len() / 2).copied();
+        let gaps = match median_step_m {
+            Some(median) => self
+                .points
+                .windows(2)
+                .filter(|w| w[1].range_m - w[0].range_m > SUSPICIOUS_GAP_FACTOR * median)
+                .map(|w| RangeGap {
+                    from_m: w[0].range_m,
+                    to_m: w[1].range_m,
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        TableReport {
+            rows: self.points.len(),
+            min_range_m: self.points.first().map(|p| p.range_m),
+            max_range_m: self.points.last().map(|p| p.range_m),
+            median_step_m,
+            issues: self.issues(),
+            gaps,
+        }
+    }
+
+    /// Lignes incohérentes d'une table triée par portée.
+    fn issues(&self) -> Vec<TableIssue> {
+        self.points
+            .windows(2)
+            .filter_map(|w| {
+                if w[1].range_m == w[0].range_m {
+                    Some(TableIssue::DuplicateRange {
+                        range_m: w[1].range_m,
+                    })
+                } else if w[1].elev_mil >= w[0].elev_mil {
+                    Some(TableIssue::NonMonotonic {
+                        range_m: w[1].range_m,
+                        elev_mil: w[1].elev_mil,
+                        previous_elev_mil: w[0].elev_mil,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Supprime le plus petit nombre de lignes rendant la table cohérente.
+    ///
+    /// La table doit être triée par portée. Les lignes conservées forment la
+    /// plus longue suite à portée strictement croissante et élévation
+    /// strictement décroissante.
+    ///
+    /// # Retourne
+    ///
+    /// Les lignes supprimées, dans l'ordre de la table.
+    pub fn repair(&mut self) -> Vec<BallisticPoint> {
+        let n = self.points.len();
+        // Plus longue suite valide se terminant en i, et prédécesseur
+        let mut len = vec![1usize; n];
+        let mut prev = vec![None; n];
+        for i in 0..n {
+            for j in 0..i {
+                let ok = self.points[j].range_m < self.points[i].range_m
+                    && self.points[j].elev_mil > self.points[i].elev_mil;
+                if ok && len[j] + 1 > len[i] {
+                    len[i] = len[j] + 1;
+                    prev[i] = Some(j);
+                }
+            }
+        }
+
+        let mut keep = vec![false; n];
+        let mut cur = (0..n).max_by_key(|&i| (len[i], std::cmp::Reverse(i)));
+        while let Some(i) = cur {
+            keep[i] = true;
+            cur = prev[i];
+        }
+
+        let (kept, dropped): (Vec<_>, Vec<_>) = std::mem::take(&mut self.points)
+            .into_iter()
+            .zip(keep)
+            .partition(|(_, k)| *k);
+        self.points = kept.into_iter().map(|(p, _)| p).collect();
+        dropped.into_iter().map(|(p, _)| p).collect()
+    }
+
+    /// Retourne les bornes de portée de la table (min, max).
+    ///
+    /// # Retourne
+    ///
+    /// `Some((min, max))` si la table contient des points, `None` sinon.
+    pub fn range_bounds(&self) -> Option<(f64, f64)> {
+        let first = self.points.first()?.range_m;
+        let last = self.points.last()?.range_m;
+        Some((first, last))
+    }
+
+    /// Calcule l'élévation pour une portée donnée par interpolation linéaire.
+    ///
+    /// # Arguments
+    ///
+    /// * `range_m` - Portée en mètres
+    ///
+    /// # Retourne
+    ///
+    /// `Some(elev_mil)` si la portée est dans les limites de la table, `None` sinon.
+    ///
+    /// # Algorithme
+    ///
+    /// Selon [`BallisticTable::interpolation`], interpolation linéaire entre
+    /// les deux points encadrant la portée demandée, ou cubique monotone
+    /// (PCHIP) sur l'ensemble de la table. Une table à portées non
+    /// strictement croissantes est toujours interpolée linéairement.
+    ///
+    /// Une table densifiée ([`BallisticTable::densify`]) est lue par accès
+    /// direct entre deux échantillons, sans recherche dans la table.
+    pub fn elev_at(&self, range_m: f64) -> Option<f64> {
+        if self.points.len() < 2 {
+            return None;
+        }
+        let (minr, maxr) = self.range_bounds()?;
+        if range_m < minr || range_m > maxr {
+            return None;
+        }
+        if let Some(elev) = self.dense_elev_at(range_m) {
+            return Some(elev);
+        }
+        if let Some(elev) = self.pchip(|x, y, d| pchip::pchip_eval(x, y, d, range_m)) {
+            return Some(elev);
+        }
+
+        if let Ok(i) = self
+            .points
+            .binary_search_by(|p| p.range_m.partial_cmp(&range_m).unwrap())
+        {
+            return Some(self.points[i].elev_mil);
+        }
+
+        let idx = match self
+            .points
+            .binary_search_by(|p| p.range_m.partial_cmp(&range_m).unwrap())
+        {
+            Ok(i) => i,
+            Err(ins) => ins.saturating_sub(1),
+        };
+        if idx + 1 >= self.points.len() {
+            return Some(self.points.last()?.elev_mil);
+        }
+
+        let p0 = &self.points[idx];
+        let p1 = &self.points[idx + 1];
+        let t = (range_m - p0.range_m) / (p1.range_m - p0.range_m);
+        Some(p0.elev_mil + t * (p1.elev_mil - p0.elev_mil))
+    }
+
+    /// Rééchantillonne la table tous les [`DENSE_STEP_M`] mètres pour que
+    /// [`BallisticTable::elev_at`] devienne un accès direct.
+    ///
+    /// Les échantillons suivent le mode d'interpolation courant : à appeler
+    /// après avoir fixé [`BallisticTable::interpolation`] ou modifié les
+    /// points (ex: [`BallisticTable::repair`]).
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::{BallisticPoint, BallisticTable};
+    /// let mut table = BallisticTable {
+    ///     points: vec![
+    ///         BallisticPoint { range_m: 100.0, elev_mil: 1400.0, time_flight_s: None, delta_elev_per_100m_mil: None },
+    ///         BallisticPoint { range_m: 300.0, elev_mil: 1200.0, time_flight_s: None, delta_elev_per_100m_mil: None },
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    /// table.densify();
+    /// assert_eq!(table.dense.as_ref().unwrap().elev_mil.len(), 201);
+    /// assert_eq!(table.elev_at(150.5), Some(1349.5));
+    /// ```
+    pub fn densify(&mut self) {
+        self.dense = None;
+        let (minr, maxr) = match self.range_bounds() {
+            Some(bounds) if self.points.len() >= 2 && bounds.1 > bounds.0 => bounds,
+            _ => return,
+        };
+        let samples = ((maxr - minr) / DENSE_STEP_M).floor() as usize + 1;
+        let elev_mil = (0..samples)
+            .map(|i| self.elev_at(minr + i as f64 * DENSE_STEP_M))
+            .collect::<Option<Vec<f64>>>();
+        self.dense = elev_mil.map(|elev_mil| DenseElevations {
+            start_m: minr,
+            elev_mil,
+        });
+    }
+
+    /// Élévation interpolée entre les deux échantillons denses encadrant la
+    /// portée, si la table est densifiée et la portée couverte.
+    fn dense_elev_at(&self, range_m: f64) -> Option<f64> {
+        let dense = self.dense.as_ref()?;
+        let pos = (range_m - dense.start_m) / DENSE_STEP_M;
+        let i = pos.floor() as usize;
+        let e0 = *dense.elev_mil.get(i)?;
+        if pos == i as f64 {
+            return Some(e0);
+        }
+        let e1 = *dense.elev_mil.get(i + 1)?;
+        Some(e0 + (pos - i as f64) * (e1 - e0))
+    }
+
+    /// Évalue `f` sur la courbe PCHIP de la table, en mode
+    /// [`Interpolation::Pchip`] et si les portées sont strictement croissantes.
+    fn pchip(&self, f: impl FnOnce(&[f64], &[f64], &[f64]) -> Result<f64>) -> Option<f64> {
+        if self.interpolation != Interpolation::Pchip {
+            return None;
+        }
+        let x: Vec<f64> = self.points.iter().map(|p| p.range_m).collect();
+        let y: Vec<f64> = self.points.iter().map(|p| p.elev_mil).collect();
+        let d = pchip::pchip_slopes(&x, &y).ok()?;
+        f(&x, &y, &d).ok()
+    }
+
+    /// Retourne la pente locale de la table (variation d'élévation par mètre de portée).
+    ///
+    /// La pente est celle du segment encadrant la portée demandée (ou la
+    /// dérivée de la courbe PCHIP). Elle est généralement négative : plus
+    /// l'élévation baisse, plus le tir porte loin.
+    ///
+    /// # Retourne
+    ///
+    /// `Some(mil_par_metre)` si la portée est dans les limites de la table, `None` sinon.
+    pub fn slope_at(&self, range_m: f64) -> Option<f64> {
+        if self.points.len() < 2 {
+            return None;
+        }
+        let (minr, maxr) = self.range_bounds()?;
+        if range_m < minr || range_m > maxr {
+            return None;
+        }
+        if let Some(slope) = self.pchip(|x, y, d| pchip::pchip_derivative(x, y, d, range_m)) {
+            return Some(slope);
+        }
+
+        let idx = self
+            .points
+            .partition_point(|p| p.range_m <= range_m)
+            .clamp(1, self.points.len() - 1);
+        let p0 = &self.points[idx - 1];
+        let p1 = &self.points[idx];
+        Some((p1.elev_mil - p0.elev_mil) / (p1.range_m - p0.range_m))
+    }
+
+    /// Retourne la variation de portée (en mètres) produite par 1 mil
+    /// d'élévation à la portée donnée.
+    ///
+    /// # Retourne
+    ///
+    /// `None` si la portée est hors table ou si la table est plate à cette portée.
+    pub fn range_per_mil(&self, range_m: f64) -> Option<f64> {
+        self.slope_at(range_m)
+            .filter(|slope| *slope != 0.0)
+            .map(|slope| 1.0 / slope.abs())
+    }
+
+    /// Rééchantillonne la table tous les `step_m` mètres avec son mode
+    /// d'interpolation, pour tracer sa courbe.
+    ///
+    /// Les portées vont de la première à la dernière ligne de la table, qui
+    /// termine toujours la courbe. Les durées de trajet sont interpolées
+    /// linéairement lorsque la table les fournit.
+    ///
+    /// # Retourne
+    ///
+    /// Une courbe vide si la table a moins de deux lignes ou si `step_m`
+    /// n'est pas strictement positif.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::{BallisticPoint, BallisticTable};
+    ///
+    /// let point = |range_m, elev_mil| BallisticPoint {
+    ///     range_m,
+    ///     elev_mil,
+    ///     time_flight_s: Some(20.0),
+    ///     delta_elev_per_100m_mil: None,
+    /// };
+    /// let table = BallisticTable {
+    ///     points: vec![point(100.0, 1400.0), point(325.0, 1175.0)],
+    ///     ..Default::default()
+    /// };
+    /// let curve = table.curve(100.0);
+    /// let ranges: Vec<f64> = curve.iter().map(|p| p.range_m).collect();
+    /// assert_eq!(ranges, [100.0, 200.0, 300.0, 325.0]);
+    /// assert_eq!(curve[1].elev_mil, 1300.0);
+    /// assert_eq!(curve[1].time_flight_s, Some(20.0));
+    /// ```
+    pub fn curve(&self, step_m: f64) -> Vec<BallisticPoint> {
+        let (minr, maxr) = match self.range_bounds() {
+            Some(bounds) if self.points.len() >= 2 && step_m > 0.0 => bounds,
+            _ => return Vec::new(),
+        };
+        let sample = |range_m: f64| {
+            Some(BallisticPoint {
+                range_m,
+                elev_mil: self.elev_at(range_m)?,
+                time_flight_s: self.time_of_flight_at(range_m),
+                delta_elev_per_100m_mil: None,
+            })
+        };
+        let mut curve: Vec<BallisticPoint> = (0..)
+            .map(|i| minr + f64::from(i) * step_m)
+            .take_while(|r| *r < maxr)
+            .filter_map(sample)
+            .collect();
+        curve.extend(sample(maxr));
+        curve
+    }
+
+    /// Écrit la table au format CSV lu par [`read_ballistic_points`].
+    ///
+    /// Sans pas, les lignes sont celles utilisées par le calcul (triées, et
+    /// réparées si la table l'a été au chargement). Avec un pas, la table est
+    /// rééchantillonnée comme par [`curve`](Self::curve) selon son
+    /// interpolation, la variation d'élévation par 100 m étant celle lue par
+    /// [`elev_per_100m_at`](Self::elev_per_100m_at) : le fichier reproduit
+    /// alors ce que le calcul voit entre les lignes.
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si l'écriture échoue.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::{read_ballistic_points, BallisticTable};
+    ///
+    /// let csv = "range_m,elev_mil,time_flight_s\n100,1400,13.5\n300,1200,\n";
+    /// let table = BallisticTable {
+    ///     points: read_ballistic_points(csv.as_bytes()).unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// let mut out = Vec::new();
+    /// table.write_csv(&mut out, None).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(out.clone()).unwrap(),
+    ///     "range_m,elev_mil,time_flight_s,delta_elev_per_100m_mil\n100.0,1400.0,13.5,\n300.0,1200.0,,\n"
+    /// );
+    /// assert_eq!(read_ballistic_points(out.as_slice()).unwrap(), table.points);
+    ///
+    /// let mut resampled = Vec::new();
+    /// table.write_csv(&mut resampled, Some(50.0)).unwrap();
+    /// let points = read_ballistic_points(resampled.as_slice()).unwrap();
+    /// assert_eq!(points.len(), 5);
+    /// assert_eq!((points[1].range_m, points[1].elev_mil), (150.0, 1350.0));
+    /// assert_eq!(points[1].delta_elev_per_100m_mil, Some(100.0));
+    /// ```
+    pub fn write_csv<W: std::io::Write>(&self, writer: W, step_m: Option<f64>) -> Result<()> {
+        #[derive(Serialize)]
+        struct Row {
+            range_m: f64,
+            elev_mil: f64,
+            time_flight_s: Option<f64>,
+            delta_elev_per_100m_mil: Option<f64>,
+        }
+
+        let rows: Vec<BallisticPoint> = match step_m {
+            Some(step) => self
+                .curve(step)
+                .into_iter()
+                .map(|p| BallisticPoint {
+                    delta_elev_per_100m_mil: self.elev_per_100m_at(p.range_m),
+                    ..p
+                })
+                .collect(),
+            None => self.points.clone(),
+        };
+        let mut wtr = csv::Writer::from_writer(writer);
+        for p in rows {
+            wtr.serialize(Row {
+                range_m: p.range_m,
+                elev_mil: p.elev_mil,
+                time_flight_s: p.time_flight_s,
+                delta_elev_per_100m_mil: p.delta_elev_per_100m_mil,
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Retourne la durée de trajet (en secondes) interpolée à la portée donnée.
+    ///
+    /// # Retourne
+    ///
+    /// `None` si la portée est hors table ou si l'un des points encadrants
+    /// n'a pas de durée de trajet.
+    pub fn time_of_flight_at(&self, range_m: f64) -> Option<f64> {
+        if self.points.len() < 2 {
+            return None;
+        }
+        let (minr, maxr) = self.range_bounds()?;
+        if range_m < minr || range_m > maxr {
+            return None;
+        }
+
+        let idx = self
+            .points
+            .partition_point(|p| p.range_m <= range_m)
+            .clamp(1, self.points.len() - 1);
+        let p0 = &self.points[idx - 1];
+        let p1 = &self.points[idx];
+        let (t0, t1) = (p0.time_flight_s?, p1.time_flight_s?);
+        let t = (range_m - p0.range_m) / (p1.range_m - p0.range_m);
+        Some(t0 + t * (t1 - t0))
+    }
+
+    /// Retourne l'élévation (en mils) à retirer pour allonger le tir de 100 m
+    /// à la portée donnée, telle que l'indique la colonne
+    /// `delta_elev_per_100m_mil` de la table.
+    ///
+    /// La colonne est interpolée linéairement. Si l'un des points encadrants
+    /// ne la fournit pas, la valeur est déduite de la pente de la table
+    /// ([`BallisticTable::slope_at`]).
+    ///
+    /// # Retourne
+    ///
+    /// `None` si la portée est hors table.
+    pub fn elev_per_100m_at(&self, range_m: f64) -> Option<f64> {
+        let slope = self.slope_at(range_m)?;
+        let idx = self
+            .points
+            .partition_point(|p| p.range_m <= range_m)
+            .clamp(1, self.points.len() - 1);
+        let p0 = &self.points[idx - 1];
+        let p1 = &self.points[idx];
+        match (p0.delta_elev_per_100m_mil, p1.delta_elev_per_100m_mil) {
+            (Some(d0), Some(d1)) => {
+                let t = (range_m - p0.range_m) / (p1.range_m - p0.range_m);
+                Some(d0 + t * (d1 - d0))
+            }
+            _ => Some(-slope * 100.0),
+        }
+    }
+}
+
+/// Retourne le décalage latéral (en mètres) produit par 1 mil de gisement à
+/// la distance donnée.
 ///
-/// * `mortar` - Position du mortier avec type de munition
-/// * `target` - Position de la cible avec type tactique
-/// * `ballistics` - Tables balistiques chargées
-/// * `dispersion_table` - Table des dispersions de base
+/// # Exemple
 ///
-/// # Retourne
+/// ```
+/// use mortar::lateral_m_per_mil;
+/// // Environ 1 m par mil à 1000 m
+/// assert!((lateral_m_per_mil(1000.0) - 0.98).abs() < 0.01);
+/// ```
+pub fn lateral_m_per_mil(distance_m: f64) -> f64 {
+    distance_m * mils::mil_to_rad(1.0)
+}
+
+/// Lit les points valides d'un CSV balistique dans l'ordre du fichier.
+fn read_csv_points<P: AsRef<Path>>(path: P) -> Result<Vec<BallisticPoint>> {
+    read_ballistic_points(File::open(&path)?)
+}
+
+/// Lit les lignes d'une table balistique au format CSV (voir
+/// [`BallisticTable::from_csv`]), dans l'ordre du fichier.
 ///
-/// Une [`FiringSolution`] contenant toutes les informations de tir.
+/// Les lignes dont la portée ou l'élévation n'est pas un nombre fini sont
+/// ignorées.
 ///
-/// # Exemple
+/// # Erreurs
 ///
-/// ```rust,ignore
-/// let solution = calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+/// Retourne une erreur si le CSV ne peut pas être lu ou parsé.
 ///
-/// println!("Distance: {} m", solution.distance_m);
-/// println!("Azimut: {} deg", solution.azimuth_deg);
+/// # Exemple
 ///
-/// if let Some(sel) = &solution.selected_solution {
-///     println!("Élévation 2R: {:?} mils", sel.elevations.get("2R"));
-///     println!("Dispersion 2R: {:?} m", sel.dispersions.get("2R"));
-/// }
 /// ```
-pub fn calculate_solution_with_dispersion(
+/// use mortar::read_ballistic_points;
+/// let csv = "range_m,elev_mil,time_flight_s\n100,1400,13.0\n200,1300,\n";
+/// let points = read_ballistic_points(csv.as_bytes()).unwrap();
+/// assert_eq!(points.len(), 2);
+/// assert_eq!(points[1].time_flight_s, None);
+/// ```
+pub fn read_ballistic_points<R: std::io::Read>(reader: R) -> Result<Vec<BallisticPoint>> {
+    #[derive(Deserialize)]
+    struct Row {
+        range_m: f64,
+        elev_mil: f64,
+        #[serde(default)]
+        time_flight_s: Option<f64>,
+        #[serde(default)]
+        delta_elev_per_100m_mil: Option<f64>,
+    }
+
+    let mut rdr = csv::Reader::from_reader(reader);
+
+    let mut pts: Vec<BallisticPoint> = Vec::new();
+    for rec in rdr.deserialize::<Row>() {
+        let r = rec?;
+        if r.range_m.is_finite() && r.elev_mil.is_finite() {
+            pts.push(BallisticPoint {
+                range_m: r.range_m,
+                elev_mil: r.elev_mil,
+                time_flight_s: r.time_flight_s.filter(|t| t.is_finite()),
+                delta_elev_per_100m_mil: r.delta_elev_per_100m_mil.filter(|d| d.is_finite()),
+            });
+        }
+    }
+    Ok(pts)
+}
+
+/// Incohérence détectée dans une table balistique.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TableIssue {
+    /// Les lignes du fichier ne sont pas triées par portée
+    Unsorted,
+    /// Plusieurs lignes pour la même portée
+    DuplicateRange { range_m: f64 },
+    /// L'élévation ne décroît pas avec la portée
+    NonMonotonic {
+        range_m: f64,
+        elev_mil: f64,
+        previous_elev_mil: f64,
+    },
+}
+
+impl std::fmt::Display for TableIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableIssue::Unsorted => write!(f, "rows are not sorted by range"),
+            TableIssue::DuplicateRange { range_m } => {
+                write!(f, "duplicate range {} m", range_m)
+            }
+            TableIssue::NonMonotonic {
+                range_m,
+                elev_mil,
+                previous_elev_mil,
+            } => write!(
+                f,
+                "elevation {} mil at {} m does not decrease (previous {} mil)",
+                elev_mil, range_m, previous_elev_mil
+            ),
+        }
+    }
+}
+
+/// Facteur, relatif au pas médian d'une table, au-delà duquel l'écart entre
+/// deux portées consécutives est signalé.
+pub const SUSPICIOUS_GAP_FACTOR: f64 = 3.0;
+
+/// Écart anormalement grand entre deux lignes consécutives d'une table.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RangeGap {
+    /// Portée de la ligne précédant l'écart (en mètres)
+    pub from_m: f64,
+    /// Portée de la ligne suivant l'écart (en mètres)
+    pub to_m: f64,
+}
+
+/// Rapport de qualité d'une table balistique (voir [`BallisticTable::validate`]).
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TableReport {
+    /// Nombre de lignes
+    pub rows: usize,
+    /// Portée minimale couverte (en mètres)
+    pub min_range_m: Option<f64>,
+    /// Portée maximale couverte (en mètres)
+    pub max_range_m: Option<f64>,
+    /// Pas médian entre deux portées consécutives (en mètres)
+    pub median_step_m: Option<f64>,
+    /// Incohérences (portées en double, élévations non décroissantes)
+    pub issues: Vec<TableIssue>,
+    /// Écarts de plus de [`SUSPICIOUS_GAP_FACTOR`] fois le pas médian
+    pub gaps: Vec<RangeGap>,
+}
+
+impl TableReport {
+    /// Indique si la table ne présente ni incohérence ni écart suspect.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty() && self.gaps.is_empty()
+    }
+}
+
+/// Résultat du contrôle d'une table au chargement.
+#[derive(Clone, Debug, Serialize)]
+pub struct TableCheck {
+    /// Système d'arme
+    pub weapon: WeaponSystem,
+    /// Munition
+    pub ammo_type: AmmoKind,
+    /// Anneau
+    pub ring: Ring,
+    /// Incohérences détectées (avant réparation)
+    pub issues: Vec<TableIssue>,
+    /// Lignes supprimées par la réparation automatique
+    pub dropped: Vec<BallisticPoint>,
+}
+
+/// Chargement d'un fichier balistique attendu.
+#[derive(Clone, Debug, Serialize)]
+pub struct FileLoad {
+    /// Système d'arme
+    pub weapon: WeaponSystem,
+    /// Munition
+    pub ammo_type: AmmoKind,
+    /// Anneau
+    pub ring: Ring,
+    /// Chemin du fichier
+    pub path: String,
+    /// Le fichier a été chargé
+    pub loaded: bool,
+    /// Erreur de lecture (fichier absent ou CSV invalide)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Bilan du chargement des tables balistiques.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LoadReport {
+    /// Fichiers attendus, chargés ou non
+    pub files: Vec<FileLoad>,
+    /// Tables présentant une incohérence
+    pub checks: Vec<TableCheck>,
+}
+
+impl LoadReport {
+    /// Fichiers attendus qui n'ont pas pu être chargés.
+    pub fn failed(&self) -> impl Iterator<Item = &FileLoad> {
+        self.files.iter().filter(|f| !f.loaded)
+    }
+}
+
+/// Type alias pour le numéro d'anneau de précision (0-4).
+pub type Ring = u8;
+
+/// Parse un numéro d'anneau ("2" ou "2R", insensible à la casse).
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::parse_ring;
+/// assert_eq!(parse_ring("4R"), Some(4));
+/// assert_eq!(parse_ring("2"), Some(2));
+/// assert_eq!(parse_ring("X"), None);
+/// ```
+pub fn parse_ring(s: &str) -> Option<Ring> {
+    let s = s.trim();
+    s.strip_suffix(['R', 'r']).unwrap_or(s).parse().ok()
+}
+
+/// Nombre d'anneaux de précision (0R à 4R).
+pub const RING_COUNT: usize = 5;
+
+/// Libellés des anneaux, indexés par numéro d'anneau.
+pub const RING_LABELS: [&str; RING_COUNT] = ["0R", "1R", "2R", "3R", "4R"];
+
+/// Valeur optionnelle par anneau (élévation, dispersion...).
+///
+/// Sérialisée comme un objet indexé par libellé d'anneau :
+/// `{ "0R": 1200.5, "1R": null, ... }`.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::RingTable;
+///
+/// let mut table = RingTable::default();
+/// table.set(2, Some(1150.0));
+/// assert_eq!(table.get(2), Some(1150.0));
+/// assert_eq!(table.get(7), None);
+/// assert_eq!(
+///     serde_json::to_string(&table).unwrap(),
+///     r#"{"0R":null,"1R":null,"2R":1150.0,"3R":null,"4R":null}"#
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RingTable([Option<f64>; RING_COUNT]);
+
+impl RingTable {
+    /// Construit la table en évaluant `f` pour chaque anneau.
+    pub fn from_fn(mut f: impl FnMut(Ring) -> Option<f64>) -> Self {
+        RingTable(std::array::from_fn(|i| f(i as Ring)))
+    }
+
+    /// Valeur d'un anneau (`None` si absente ou anneau hors limites).
+    pub fn get(&self, ring: Ring) -> Option<f64> {
+        self.0.get(usize::from(ring)).copied().flatten()
+    }
+
+    /// Fixe la valeur d'un anneau ; un anneau hors limites est ignoré.
+    pub fn set(&mut self, ring: Ring, value: Option<f64>) {
+        if let Some(slot) = self.0.get_mut(usize::from(ring)) {
+            *slot = value;
+        }
+    }
+
+    /// Parcourt les anneaux dans l'ordre, avec leur valeur.
+    pub fn iter(&self) -> impl Iterator<Item = (Ring, Option<f64>)> + '_ {
+        self.0.iter().enumerate().map(|(i, v)| (i as Ring, *v))
+    }
+
+    /// Première valeur connue, par anneau croissant.
+    pub fn first(&self) -> Option<f64> {
+        self.0.iter().find_map(|v| *v)
+    }
+
+    /// Indique si aucun anneau n'a de valeur.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(Option::is_none)
+    }
+}
+
+impl Serialize for RingTable {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(RING_COUNT))?;
+        for (label, value) in RING_LABELS.iter().zip(&self.0) {
+            map.serialize_entry(label, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Tables par anneau pour chaque type de munition.
+pub type AmmoRingTables = BTreeMap<AmmoKind, RingTable>;
+
+/// Sérialise des tables par munition avec les noms de munition en clé
+/// (`{ "HE": { "0R": ... }, ... }`).
+fn serialize_ammo_ring_tables<S: Serializer>(
+    tables: &AmmoRingTables,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(tables.len()))?;
+    for (ammo, table) in tables {
+        map.serialize_entry(ammo.as_str(), table)?;
+    }
+    map.end()
+}
+
+/// Nombre de millièmes (mils OTAN) dans un tour complet.
+pub const MILS_PER_CIRCLE: f64 = 6400.0;
+
+/// Convertit un angle en mils vers des degrés (voir [`mils`] pour les autres
+/// conversions).
+pub fn mils_to_degrees(mils: f64) -> f64 {
+    mils::mil_to_deg(mils)
+}
+
+/// Parse un angle saisi avec son unité et le retourne en degrés.
+///
+/// Suffixes acceptés (insensibles à la casse) : `mil` ou `mils` pour les
+/// millièmes, `deg`, `d` ou `°` pour les degrés. Sans suffixe, la valeur est
+/// en degrés. Voir [`Angle`] pour les autres systèmes de millièmes.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::parse_angle_deg;
+/// assert_eq!(parse_angle_deg("1600mil"), Some(90.0));
+/// assert_eq!(parse_angle_deg("3200 mils"), Some(180.0));
+/// assert_eq!(parse_angle_deg("1500mils(6000)"), Some(90.0));
+/// assert_eq!(parse_angle_deg("45deg"), Some(45.0));
+/// assert_eq!(parse_angle_deg("45°"), Some(45.0));
+/// assert_eq!(parse_angle_deg("45"), Some(45.0));
+/// assert_eq!(parse_angle_deg("45grad"), None);
+/// ```
+pub fn parse_angle_deg(s: &str) -> Option<f64> {
+    s.parse::<Angle>().ok().map(Angle::degrees)
+}
+
+/// Désérialise un angle donné soit en degrés (nombre), soit sous forme de
+/// texte avec unité (`"1600mil"`, `"90deg"`), voir [`Angle`].
+pub fn deserialize_angle_deg<'de, D>(deserializer: D) -> std::result::Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Angle::deserialize(deserializer).map(Angle::degrees)
+}
+
+/// Déclinaison magnétique et convergence des méridiens de la zone d'opération.
+///
+/// Les gisements calculés sont des gisements « grille » (axe Y vers le Nord
+/// du quadrillage). L'angle grille-magnétique (déclinaison moins convergence,
+/// Est positif) permet d'en déduire le gisement magnétique lu à la boussole.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::Declination;
+///
+/// // Déclinaison 2° Est, convergence 0,5° Est : angle G-M de 1,5° Est
+/// let d = Declination { magnetic_deg: 2.0, grid_convergence_deg: 0.5 };
+/// assert!((d.grid_magnetic_angle_mil() - 26.67).abs() < 0.01);
+/// assert!((d.magnetic_azimuth_mil(1600.0) - 1573.33).abs() < 0.01);
+/// assert!((d.magnetic_azimuth_mil(0.0) - 6373.33).abs() < 0.01);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Declination {
+    /// Déclinaison magnétique en degrés (Est positif)
+    #[serde(default)]
+    pub magnetic_deg: f64,
+    /// Convergence des méridiens en degrés (Est positif)
+    #[serde(default)]
+    pub grid_convergence_deg: f64,
+}
+
+impl Declination {
+    /// Angle grille-magnétique en mils (Est positif).
+    pub fn grid_magnetic_angle_mil(&self) -> f64 {
+        mils::deg_to_mil(self.magnetic_deg - self.grid_convergence_deg)
+    }
+
+    /// Convertit un gisement grille en gisement magnétique (mils, 0 à 6400).
+    pub fn magnetic_azimuth_mil(&self, grid_azimuth_mil: f64) -> f64 {
+        mils::normalize(grid_azimuth_mil - self.grid_magnetic_angle_mil())
+    }
+
+    /// Mention imprimée sur les documents, par exemple
+    /// `Declinaison +2.0 deg, convergence +0.5 deg (G-M +27 mils)`.
+    pub fn describe(&self) -> String {
+        format!(
+            "Declinaison {:+.1} deg, convergence {:+.1} deg (G-M {:+.0} mils)",
+            self.magnetic_deg,
+            self.grid_convergence_deg,
+            self.grid_magnetic_angle_mil()
+        )
+    }
+}
+
+/// Retourne les anneaux dont la table couvre la portée demandée, par ordre croissant.
+///
+/// # Arguments
+///
+/// * `ballistics` - Tables balistiques chargées
+/// * `weapon` - Système d'arme
+/// * `ammo` - Type de munition
+/// * `range_m` - Portée en mètres
+pub fn available_rings(
+    ballistics: &BallisticTables,
+    weapon: WeaponSystem,
+    ammo: AmmoKind,
+    range_m: f64,
+) -> Vec<Ring> {
+    ballistics
+        .range((weapon, ammo, Ring::MIN)..=(weapon, ammo, Ring::MAX))
+        .filter(|(_, t)| t.elev_at(range_m).is_some())
+        .map(|((_, _, r), _)| *r)
+        .collect()
+}
+
+// ============================================================================
+// Données de dispersion
+// ============================================================================
+
+/// Version du schéma `metrics.json` supportée par ce chargeur.
+pub const METRICS_VERSION: u32 = 1;
+
+fn default_metrics_version() -> u32 {
+    1
+}
+
+/// Structure interne pour la désérialisation du fichier metrics.json.
+///
+/// Les clés inconnues sont refusées afin qu'une faute de frappe dans le
+/// fichier ne soit pas ignorée silencieusement.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetricsFile {
+    /// Version du schéma (1 si absente)
+    #[serde(default = "default_metrics_version")]
+    pub version: u32,
+    /// Map des dispersions par type de munition et anneau
+    pub dispersion: BTreeMap<String, BTreeMap<String, DispersionEntry>>,
+    /// Rayons d'effet par type de munition
+    #[serde(default)]
+    pub effect_radius: BTreeMap<String, EffectRadii>,
+    /// Distances minimales de sécurité par type de munition (mètres)
+    #[serde(default)]
+    pub min_safe_distance: BTreeMap<String, f64>,
+    /// Coefficients de correction de la dispersion selon le dénivelé
+    #[serde(default)]
+    pub dispersion_coefficients: DispersionCoefficients,
+    /// Correction de portée selon la température des charges
+    #[serde(default)]
+    pub charge_temperature: ChargeTemperatureFile,
+    /// Charges additionnelles consommées par coup, par munition et anneau
+    #[serde(default)]
+    pub increments: BTreeMap<String, BTreeMap<String, u32>>,
+    /// Courbes de dérive `[portée m, dérive mils]`, par munition et anneau
+    #[serde(default)]
+    pub drift: BTreeMap<String, BTreeMap<String, Vec<[f64; 2]>>>,
+}
+
+fn default_standard_charge_temperature() -> f64 {
+    environment::STANDARD_CHARGE_TEMPERATURE_C
+}
+
+/// Section `charge_temperature` de `metrics.json`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChargeTemperatureFile {
+    /// Température de référence des tables de tir (°C)
+    #[serde(default = "default_standard_charge_temperature")]
+    pub standard_c: f64,
+    /// Variation relative de portée par °C, par munition et anneau
+    #[serde(default)]
+    pub range_factor_per_c: BTreeMap<String, BTreeMap<String, f64>>,
+    /// Variation relative de portée par °C des munitions et anneaux absents
+    /// de `range_factor_per_c` (aucune correction si omise)
+    #[serde(default)]
+    pub default_range_factor_per_c: Option<f64>,
+}
+
+impl Default for ChargeTemperatureFile {
+    fn default() -> Self {
+        ChargeTemperatureFile {
+            standard_c: default_standard_charge_temperature(),
+            range_factor_per_c: BTreeMap::new(),
+            default_range_factor_per_c: None,
+        }
+    }
+}
+
+/// Dispersion de base d'un couple (munition, anneau) : écarts probables en
+/// portée et en direction, en mètres.
+///
+/// Les impacts se répartissent selon une ellipse dont le grand axe suit
+/// généralement la ligne pièce-but.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dispersion {
+    /// Écart probable en portée, le long de la ligne pièce-but
+    pub range_pe_m: f64,
+    /// Écart probable en direction, perpendiculairement à la ligne pièce-but
+    pub deflection_pe_m: f64,
+}
+
+impl Dispersion {
+    /// Dispersion circulaire : même écart probable en portée et en direction.
+    pub fn circular(radius_m: f64) -> Self {
+        Dispersion {
+            range_pe_m: radius_m,
+            deflection_pe_m: radius_m,
+        }
+    }
+
+    /// Rayon du cercle englobant l'ellipse (plus grand des deux écarts).
+    pub fn radius_m(&self) -> f64 {
+        self.range_pe_m.max(self.deflection_pe_m)
+    }
+
+    /// Dispersion multipliée par `factor` (tube usé, bipied fatigué).
+    pub fn scaled(&self, factor: f64) -> Self {
+        Dispersion {
+            range_pe_m: self.range_pe_m * factor,
+            deflection_pe_m: self.deflection_pe_m * factor,
+        }
+    }
+
+    /// Dispersion ajustée au dénivelé mortier-cible (voir [`calculate_dispersion`]).
+    pub fn adjusted(&self, mortar_elevation: f64, target_elevation: f64) -> Self {
+        Dispersion {
+            range_pe_m: calculate_dispersion(self.range_pe_m, mortar_elevation, target_elevation),
+            deflection_pe_m: calculate_dispersion(
+                self.deflection_pe_m,
+                mortar_elevation,
+                target_elevation,
+            ),
+        }
+    }
+}
+
+/// Dispersion d'un anneau dans `metrics.json` : un rayon (dispersion
+/// circulaire) ou les deux écarts probables.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum DispersionEntry {
+    /// Rayon de dispersion probable (CEP)
+    Radius(f64),
+    /// Écarts probables en portée et en direction
+    Ellipse(Dispersion),
+}
+
+impl From<DispersionEntry> for Dispersion {
+    fn from(entry: DispersionEntry) -> Self {
+        match entry {
+            DispersionEntry::Radius(r) => Dispersion::circular(r),
+            DispersionEntry::Ellipse(d) => d,
+        }
+    }
+}
+
+/// Courbe de dispersion d'une munition à un anneau en fonction de la portée.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DispersionCurve {
+    /// Points `(portée m, dispersion)` triés par portée strictement croissante
+    pub points: Vec<(f64, Dispersion)>,
+}
+
+impl DispersionCurve {
+    /// Dispersion interpolée linéairement à la portée donnée, bornée aux
+    /// extrémités de la courbe.
+    ///
+    /// # Retourne
+    ///
+    /// `None` si la courbe est vide.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::{Dispersion, DispersionCurve};
+    ///
+    /// let curve = DispersionCurve {
+    ///     points: vec![(500.0, Dispersion::circular(20.0)), (1500.0, Dispersion::circular(40.0))],
+    /// };
+    /// assert_eq!(curve.dispersion_at(1000.0).unwrap().range_pe_m, 30.0);
+    /// assert_eq!(curve.dispersion_at(2000.0).unwrap().range_pe_m, 40.0);
+    /// ```
+    pub fn dispersion_at(&self, range_m: f64) -> Option<Dispersion> {
+        let (first, last) = (self.points.first()?, self.points.last()?);
+        if range_m <= first.0 {
+            return Some(first.1);
+        }
+        if range_m >= last.0 {
+            return Some(last.1);
+        }
+        let idx = self.points.partition_point(|p| p.0 <= range_m);
+        let ((r0, d0), (r1, d1)) = (self.points[idx - 1], self.points[idx]);
+        let t = (range_m - r0) / (r1 - r0);
+        Some(Dispersion {
+            range_pe_m: d0.range_pe_m + t * (d1.range_pe_m - d0.range_pe_m),
+            deflection_pe_m: d0.deflection_pe_m + t * (d1.deflection_pe_m - d0.deflection_pe_m),
+        })
+    }
+}
+
+/// Dispersion de base d'un anneau : valeur fixe de `metrics.json` et, si
+/// elle est fournie, courbe en fonction de la portée.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RingDispersion {
+    /// Dispersion indépendante de la portée
+    pub flat: Dispersion,
+    /// Courbe `dispersion/<MUNITION>_<n>R.csv`, prioritaire sur la valeur fixe
+    pub curve: Option<DispersionCurve>,
+}
+
+impl From<Dispersion> for RingDispersion {
+    fn from(flat: Dispersion) -> Self {
+        RingDispersion { flat, curve: None }
+    }
+}
+
+impl RingDispersion {
+    /// Dispersion de base à une portée : lue sur la courbe si elle existe,
+    /// sinon la valeur fixe.
+    pub fn at_range(&self, range_m: f64) -> Dispersion {
+        self.curve
+            .as_ref()
+            .and_then(|c| c.dispersion_at(range_m))
+            .unwrap_or(self.flat)
+    }
+
+    /// Dispersion à une portée, ajustée au dénivelé mortier-cible.
+    pub fn adjusted(
+        &self,
+        range_m: f64,
+        mortar_elevation: f64,
+        target_elevation: f64,
+    ) -> Dispersion {
+        self.at_range(range_m)
+            .adjusted(mortar_elevation, target_elevation)
+    }
+}
+
+/// Table de dispersion associant chaque couple (munition, anneau) à sa
+/// dispersion de base.
+pub type DispersionTable = BTreeMap<(AmmoKind, Ring), RingDispersion>;
+
+/// Chemin de la courbe de dispersion d'une munition à un anneau, relatif au
+/// répertoire de données.
+///
+/// ```
+/// use mortar::{dispersion_file_name, AmmoKind};
+/// assert_eq!(dispersion_file_name(AmmoKind::He, 2), "dispersion/HE_2R.csv");
+/// ```
+pub fn dispersion_file_name(ammo: AmmoKind, ring: Ring) -> String {
+    format!("dispersion/{}_{}R.csv", ammo.as_str(), ring)
+}
+
+/// Lit une courbe de dispersion au format CSV.
+///
+/// Les colonnes sont `range_m` puis soit `dispersion_m` (dispersion
+/// circulaire), soit `range_pe_m` et `deflection_pe_m`.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le CSV est illisible, vide, si une ligne n'a pas
+/// de dispersion, si une valeur est négative ou non finie, ou si deux lignes
+/// ont la même portée.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::read_dispersion_curve;
+///
+/// let csv = "range_m,range_pe_m,deflection_pe_m\n1000,30,12\n200,10,5\n";
+/// let curve = read_dispersion_curve(csv.as_bytes()).unwrap();
+/// assert_eq!(curve.points[0].0, 200.0);
+/// assert!(read_dispersion_curve("range_m,dispersion_m\n100,-1\n".as_bytes()).is_err());
+/// ```
+pub fn read_dispersion_curve<R: Read>(reader: R) -> Result<DispersionCurve> {
+    #[derive(Deserialize)]
+    struct Row {
+        range_m: f64,
+        #[serde(default)]
+        dispersion_m: Option<f64>,
+        #[serde(default)]
+        range_pe_m: Option<f64>,
+        #[serde(default)]
+        deflection_pe_m: Option<f64>,
+    }
+
+    let mut rdr = csv::Reader::from_reader(reader);
+    let mut points = Vec::new();
+    for (i, rec) in rdr.deserialize::<Row>().enumerate() {
+        let row = rec?;
+        let line = i + 2;
+        let dispersion = match (row.range_pe_m, row.deflection_pe_m, row.dispersion_m) {
+            (Some(range_pe_m), Some(deflection_pe_m), _) => Dispersion {
+                range_pe_m,
+                deflection_pe_m,
+            },
+            (None, None, Some(radius)) => Dispersion::circular(radius),
+            _ => bail!("line {}: missing dispersion", line),
+        };
+        for value in [
+            row.range_m,
+            dispersion.range_pe_m,
+            dispersion.deflection_pe_m,
+        ] {
+            check_metric(value, || format!("line {}", line))?;
+        }
+        points.push((row.range_m, dispersion));
+    }
+    if points.is_empty() {
+        bail!("no dispersion rows");
+    }
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    if points.windows(2).any(|w| w[0].0 == w[1].0) {
+        bail!("duplicate range");
+    }
+    Ok(DispersionCurve { points })
+}
+
+/// Courbe de dérive d'une munition à un anneau : dérive latérale due à la
+/// rotation de l'obus en fonction de la portée.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DriftCurve {
+    /// Points `(portée m, dérive mils)` triés par portée strictement croissante ;
+    /// une dérive positive porte l'impact à droite de la ligne de tir
+    pub points: Vec<(f64, f64)>,
+}
+
+impl DriftCurve {
+    /// Dérive (mils) interpolée linéairement à la portée donnée.
+    ///
+    /// # Retourne
+    ///
+    /// `None` si la portée est hors de la courbe.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::DriftCurve;
+    ///
+    /// let curve = DriftCurve { points: vec![(100.0, 1.0), (1100.0, 6.0)] };
+    /// assert_eq!(curve.drift_at(600.0), Some(3.5));
+    /// assert_eq!(curve.drift_at(1200.0), None);
+    /// ```
+    pub fn drift_at(&self, range_m: f64) -> Option<f64> {
+        let (first, last) = (self.points.first()?, self.points.last()?);
+        if range_m < first.0 || range_m > last.0 {
+            return None;
+        }
+        if self.points.len() == 1 {
+            return Some(first.1);
+        }
+        let idx = self
+            .points
+            .partition_point(|p| p.0 <= range_m)
+            .clamp(1, self.points.len() - 1);
+        let ((r0, d0), (r1, d1)) = (self.points[idx - 1], self.points[idx]);
+        Some(d0 + (range_m - r0) / (r1 - r0) * (d1 - d0))
+    }
+}
+
+/// Courbes de dérive par (munition, anneau).
+pub type DriftTable = BTreeMap<(AmmoKind, Ring), DriftCurve>;
+
+/// Charges additionnelles (incréments) consommées par coup, par (munition, anneau).
+///
+/// Les incréments sont comptés à part des obus pour le suivi des munitions.
+pub type IncrementTable = BTreeMap<(AmmoKind, Ring), u32>;
+
+/// Coefficients d'ajustement de la dispersion au dénivelé, par mètre.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DispersionCoefficients {
+    /// Augmentation relative par mètre lorsque le mortier est plus haut
+    pub above_per_m: f64,
+    /// Diminution relative par mètre lorsque le mortier est plus bas
+    pub below_per_m: f64,
+}
+
+impl Default for DispersionCoefficients {
+    fn default() -> Self {
+        DispersionCoefficients {
+            above_per_m: 0.05,
+            below_per_m: 0.01,
+        }
+    }
+}
+
+impl DispersionCoefficients {
+    /// Ajuste une dispersion de base au dénivelé mortier-cible.
+    pub fn adjust(
+        &self,
+        base_dispersion: f64,
+        mortar_elevation: f64,
+        target_elevation: f64,
+    ) -> f64 {
+        let delta = mortar_elevation - target_elevation;
+        let factor = if delta >= 0.0 {
+            1.0 + delta * self.above_per_m
+        } else {
+            1.0 + delta * self.below_per_m // delta is negative
+        };
+        base_dispersion * factor
+    }
+}
+
+/// Contenu validé de `metrics.json`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metrics {
+    /// Version du schéma
+    pub version: u32,
+    /// Dispersions par (munition, anneau)
+    pub dispersions: DispersionTable,
+    /// Rayons d'effet par munition
+    pub effects: EffectTable,
+    /// Distances minimales de sécurité par munition (mètres)
+    pub min_safe_distances: BTreeMap<AmmoKind, f64>,
+    /// Coefficients d'ajustement de la dispersion
+    pub dispersion_coefficients: DispersionCoefficients,
+    /// Facteurs de correction de température des charges
+    pub charge_temperature: environment::ChargeTemperatureTable,
+    /// Charges additionnelles consommées par coup
+    pub increments: IncrementTable,
+    /// Courbes de dérive
+    pub drift: DriftTable,
+    /// Munitions du répertoire de données, auxquelles se rapportent les métriques
+    pub ammo: AmmoRegistry,
+}
+
+/// Charge et valide `metrics.json` depuis un répertoire spécifié, avec les
+/// courbes de dispersion en fonction de la portée (voir
+/// [`dispersion_file_name`] et [`read_dispersion_curve`]).
+///
+/// `base` peut aussi être un paquet de données `.zip` (voir
+/// [`load_ballistics_from_archive`]).
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le fichier est illisible ou invalide (voir [`parse_metrics`]).
+pub fn load_metrics_from<P: AsRef<Path>>(base: P) -> Result<Metrics> {
+    let base = base.as_ref();
+    load_metrics_with(base, AmmoRegistry::load(base)?)
+}
+
+/// Comme [`load_metrics_from`], les munitions déclarées étant celles de
+/// `registry` (par exemple chargé avec [`AmmoRegistry::load_layered`]).
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le fichier est illisible ou invalide (voir [`parse_metrics_with`]).
+pub fn load_metrics_with<P: AsRef<Path>>(base: P, registry: AmmoRegistry) -> Result<Metrics> {
+    let base = base.as_ref();
+    let bytes = read_metrics_file(base)?;
+    let mut metrics = parse_metrics_with(bytes.as_slice(), registry)
+        .with_context(|| format!("invalid metrics.json in {}", base.display()))?;
+    load_dispersion_curves(base, &metrics.ammo, &mut metrics.dispersions)?;
+    Ok(metrics)
+}
+
+/// Ajoute aux dispersions les courbes `dispersion/<MUNITION>_<n>R.csv`
+/// présentes dans le répertoire ou le paquet de données.
+///
+/// Un anneau sans valeur fixe dans `metrics.json` prend la dispersion de la
+/// plus grande portée de sa courbe.
+fn load_dispersion_curves(
+    base: &Path,
+    registry: &AmmoRegistry,
+    dispersions: &mut DispersionTable,
+) -> Result<()> {
+    let pack = if archive::is_data_archive(base) {
+        Some(archive::DataArchive::open(base)?)
+    } else {
+        None
+    };
+    for ammo in registry.kinds() {
+        for ring in 0..RING_COUNT as Ring {
+            let name = dispersion_file_name(ammo, ring);
+            let curve = match &pack {
+                Some(pack) => match pack.file(&name) {
+                    Some(csv) => read_dispersion_curve(csv),
+                    None => continue,
+                },
+                None if base.join(&name).is_file() => {
+                    read_dispersion_curve(File::open(base.join(&name))?)
+                }
+                None => continue,
+            }
+            .with_context(|| format!("invalid {} in {}", name, base.display()))?;
+            let farthest = curve.points[curve.points.len() - 1].1;
+            dispersions
+                .entry((ammo, ring))
+                .or_insert_with(|| farthest.into())
+                .curve = Some(curve);
+        }
+    }
+    Ok(())
+}
+
+/// Contenu brut de `metrics.json` d'un répertoire ou d'un paquet `.zip`.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le fichier ou le paquet est illisible.
+pub fn read_metrics_file<P: AsRef<Path>>(base: P) -> Result<Vec<u8>> {
+    let base = base.as_ref();
+    if archive::is_data_archive(base) {
+        let pack = archive::DataArchive::open(base)?;
+        return match pack.file("metrics.json") {
+            Some(file) => Ok(file.to_vec()),
+            None => bail!("metrics.json not found in {}", base.display()),
+        };
+    }
+    let path = base.join("metrics.json");
+    std::fs::read(&path).with_context(|| format!("cannot open {}", path.display()))
+}
+
+/// Analyse et valide le contenu d'un `metrics.json`.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si :
+/// - la version est supérieure à [`METRICS_VERSION`]
+/// - un nom de munition est inconnu
+/// - une clé d'anneau n'est pas de la forme `<n>R`
+/// - une valeur est négative ou non finie
+/// - une clé inconnue est présente
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::{parse_metrics, AmmoKind};
+///
+/// let json = r#"{ "version": 1, "dispersion": { "HE": {
+///     "2R": 39,
+///     "3R": { "range_pe_m": 54, "deflection_pe_m": 18 }
+/// } } }"#;
+/// let metrics = parse_metrics(json.as_bytes()).unwrap();
+/// assert_eq!(metrics.dispersions[&(AmmoKind::He, 2)].flat.deflection_pe_m, 39.0);
+/// assert_eq!(metrics.dispersions[&(AmmoKind::He, 3)].flat.range_pe_m, 54.0);
+///
+/// let bad = r#"{ "dispersion": { "HEAT": { "2R": 39 } } }"#;
+/// assert!(parse_metrics(bad.as_bytes()).is_err());
+/// ```
+pub fn parse_metrics<R: Read>(reader: R) -> Result<Metrics> {
+    parse_metrics_with(reader, AmmoRegistry::default())
+}
+
+/// Comme [`parse_metrics`], en acceptant aussi les munitions déclarées de
+/// `registry`, conservé dans [`Metrics::ammo`].
+///
+/// # Erreurs
+///
+/// Voir [`parse_metrics`].
+pub fn parse_metrics_with<R: Read>(reader: R, registry: AmmoRegistry) -> Result<Metrics> {
+    let file: MetricsFile = serde_json::from_reader(reader)?;
+
+    if file.version == 0 || file.version > METRICS_VERSION {
+        bail!(
+            "unsupported metrics version {} (supported: {})",
+            file.version,
+            METRICS_VERSION
+        );
+    }
+
+    let mut dispersions = DispersionTable::new();
+    for (ammo_str, rings) in &file.dispersion {
+        let ammo = parse_metrics_ammo(&registry, ammo_str, "dispersion")?;
+        for (ring_str, &entry) in rings {
+            let ring = parse_ring_key(ring_str)
+                .ok_or_else(|| anyhow!("dispersion.{}: invalid ring '{}'", ammo_str, ring_str))?;
+            let value = Dispersion::from(entry);
+            for pe in [value.range_pe_m, value.deflection_pe_m] {
+                check_metric(pe, || format!("dispersion.{}.{}", ammo_str, ring_str))?;
+            }
+            dispersions.insert((ammo, ring), value.into());
+        }
+    }
+
+    let mut effects = EffectTable::new();
+    for (ammo_str, radii) in &file.effect_radius {
+        let ammo = parse_metrics_ammo(&registry, ammo_str, "effect_radius")?;
+        for value in [radii.casualty_m, radii.screen_m, radii.illumination_m]
+            .into_iter()
+            .flatten()
+        {
+            check_metric(value, || format!("effect_radius.{}", ammo_str))?;
+        }
+        effects.insert(ammo, *radii);
+    }
+
+    let mut min_safe_distances = BTreeMap::new();
+    for (ammo_str, &value) in &file.min_safe_distance {
+        let ammo = parse_metrics_ammo(&registry, ammo_str, "min_safe_distance")?;
+        check_metric(value, || format!("min_safe_distance.{}", ammo_str))?;
+        min_safe_distances.insert(ammo, value);
+    }
+
+    let coefficients = file.dispersion_coefficients;
+    check_metric(coefficients.above_per_m, || {
+        "dispersion_coefficients.above_per_m".to_string()
+    })?;
+    check_metric(coefficients.below_per_m, || {
+        "dispersion_coefficients.below_per_m".to_string()
+    })?;
+
+    let standard_c = file.charge_temperature.standard_c;
+    if !standard_c.is_finite() {
+        bail!(
+            "charge_temperature.standard_c: invalid value {}",
+            standard_c
+        );
+    }
+    let default_factor = file.charge_temperature.default_range_factor_per_c;
+    if let Some(value) = default_factor {
+        check_metric(value, || {
+            "charge_temperature.default_range_factor_per_c".to_string()
+        })?;
+    }
+    let mut charge_temperature = environment::ChargeTemperatureTable {
+        standard_c,
+        factors: BTreeMap::new(),
+        default_factor,
+    };
+    for (ammo_str, rings) in &file.charge_temperature.range_factor_per_c {
+        let ammo = parse_metrics_ammo(&registry, ammo_str, "charge_temperature")?;
+        for (ring_str, &value) in rings {
+            let ring = parse_ring_key(ring_str).ok_or_else(|| {
+                anyhow!(
+                    "charge_temperature.range_factor_per_c.{}: invalid ring '{}'",
+                    ammo_str,
+                    ring_str
+                )
+            })?;
+            check_metric(value, || {
+                format!(
+                    "charge_temperature.range_factor_per_c.{}.{}",
+                    ammo_str, ring_str
+                )
+            })?;
+            charge_temperature.factors.insert((ammo, ring), value);
+        }
+    }
+
+    let mut increments = IncrementTable::new();
+    for (ammo_str, rings) in &file.increments {
+        let ammo = parse_metrics_ammo(&registry, ammo_str, "increments")?;
+        for (ring_str, &count) in rings {
+            let ring = parse_ring_key(ring_str)
+                .ok_or_else(|| anyhow!("increments.{}: invalid ring '{}'", ammo_str, ring_str))?;
+            increments.insert((ammo, ring), count);
+        }
+    }
+
+    let mut drift = DriftTable::new();
+    for (ammo_str, rings) in &file.drift {
+        let ammo = parse_metrics_ammo(&registry, ammo_str, "drift")?;
+        for (ring_str, points) in rings {
+            let field = || format!("drift.{}.{}", ammo_str, ring_str);
+            let ring = parse_ring_key(ring_str)
+                .ok_or_else(|| anyhow!("drift.{}: invalid ring '{}'", ammo_str, ring_str))?;
+            let mut points: Vec<(f64, f64)> = points.iter().map(|p| (p[0], p[1])).collect();
+            for &(range_m, drift_mil) in &points {
+                check_metric(range_m, field)?;
+                if !drift_mil.is_finite() {
+                    bail!("{}: invalid value {}", field(), drift_mil);
+                }
+            }
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            if points.windows(2).any(|w| w[0].0 == w[1].0) {
+                bail!("{}: duplicate range", field());
+            }
+            drift.insert((ammo, ring), DriftCurve { points });
+        }
+    }
+
+    Ok(Metrics {
+        version: file.version,
+        dispersions,
+        effects,
+        min_safe_distances,
+        dispersion_coefficients: coefficients,
+        charge_temperature,
+        increments,
+        drift,
+        ammo: registry,
+    })
+}
+
+fn parse_metrics_ammo(registry: &AmmoRegistry, name: &str, section: &str) -> Result<AmmoKind> {
+    registry.parse_kind(name).ok_or_else(|| {
+        anyhow!(
+            "{}: unknown ammo '{}' (expected one of {})",
+            section,
+            name,
+            registry
+                .ammo
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })
+}
+
+/// Analyse une clé d'anneau de la forme `"2R"`.
+fn parse_ring_key(key: &str) -> Option<Ring> {
+    key.strip_suffix('R')?.parse().ok()
+}
+
+fn check_metric(value: f64, field: impl FnOnce() -> String) -> Result<()> {
+    if !value.is_finite() || value < 0.0 {
+        bail!("{}: invalid value {}", field(), value);
+    }
+    Ok(())
+}
+
+/// Charge les données de dispersion depuis le répertoire par défaut (`data/`).
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le fichier `data/metrics.json` ne peut pas être lu.
+pub fn load_dispersion() -> Result<DispersionTable> {
+    load_dispersion_from("data")
+}
+
+/// Charge les données de dispersion depuis un répertoire spécifié.
+///
+/// # Arguments
+///
+/// * `base` - Chemin du répertoire contenant `metrics.json`
+///
+/// # Format du fichier metrics.json
+///
+/// ```json
+/// {
+///     "version": 1,
+///     "dispersion": {
+///         "HE": { "0R": 10, "1R": 23, "2R": 39, "3R": 54, "4R": 69 },
+///         "PRACTICE": { "0R": 10, "1R": 24, "2R": { "range_pe_m": 39, "deflection_pe_m": 15 } }
+///     }
+/// }
+/// ```
+///
+/// Un nombre donne une dispersion circulaire ; un objet donne les écarts
+/// probables en portée et en direction.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le fichier est illisible ou invalide (voir [`parse_metrics`]).
+pub fn load_dispersion_from<P: AsRef<Path>>(base: P) -> Result<DispersionTable> {
+    Ok(load_metrics_from(base)?.dispersions)
+}
+
+/// Rayons d'effet d'une munition, en mètres.
+///
+/// Seuls les rayons pertinents pour la munition sont renseignés
+/// (rayon létal pour HE, rayon d'écran pour SMOKE, rayon d'éclairement pour FLARE).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EffectRadii {
+    /// Rayon létal (casualty radius)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub casualty_m: Option<f64>,
+    /// Rayon de l'écran fumigène
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_m: Option<f64>,
+    /// Rayon d'éclairement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub illumination_m: Option<f64>,
+}
+
+impl EffectRadii {
+    /// Retourne le plus grand rayon d'effet renseigné.
+    pub fn effect_radius(&self) -> Option<f64> {
+        [self.casualty_m, self.screen_m, self.illumination_m]
+            .into_iter()
+            .flatten()
+            .reduce(f64::max)
+    }
+}
+
+/// Table des rayons d'effet par type de munition.
+pub type EffectTable = BTreeMap<AmmoKind, EffectRadii>;
+
+/// Charge les rayons d'effet depuis le répertoire par défaut (`data/`).
+pub fn load_effects() -> Result<EffectTable> {
+    load_effects_from("data")
+}
+
+/// Charge les rayons d'effet depuis `metrics.json` dans un répertoire spécifié.
+///
+/// La section `effect_radius` est optionnelle : une table vide est retournée
+/// si elle est absente.
+///
+/// ```json
+/// {
+///     "effect_radius": {
+///         "HE": { "casualty_m": 35 },
+///         "SMOKE": { "screen_m": 50 },
+///         "FLARE": { "illumination_m": 500 }
+///     }
+/// }
+/// ```
+pub fn load_effects_from<P: AsRef<Path>>(base: P) -> Result<EffectTable> {
+    Ok(load_metrics_from(base)?.effects)
+}
+
+/// Calcule la dispersion ajustée en fonction du dénivelé mortier-cible.
+///
+/// La dispersion est modifiée selon la règle suivante :
+/// - Mortier plus haut que la cible : **+5% par mètre** de dénivelé
+/// - Mortier plus bas que la cible : **-1% par mètre** de dénivelé
+///
+/// # Arguments
+///
+/// * `base_dispersion` - Dispersion de base en mètres (depuis la table)
+/// * `mortar_elevation` - Altitude du mortier en mètres
+/// * `target_elevation` - Altitude de la cible en mètres
+///
+/// # Retourne
+///
+/// Dispersion ajustée en mètres.
+///
+/// # Formule
+///
+/// ```text
+/// delta = mortar_elevation - target_elevation
+/// si delta >= 0 : dispersion = base * (1 + delta * 0.05)
+/// si delta < 0  : dispersion = base * (1 + delta * 0.01)
+/// ```
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::calculate_dispersion;
+///
+/// // Mortier 5m plus haut que la cible, dispersion base 39m
+/// let disp = calculate_dispersion(39.0, 105.0, 100.0);
+/// assert!((disp - 48.75).abs() < 0.01); // 39 * 1.25 = 48.75
+///
+/// // Mortier 10m plus bas que la cible
+/// let disp = calculate_dispersion(39.0, 90.0, 100.0);
+/// assert!((disp - 35.1).abs() < 0.01); // 39 * 0.90 = 35.1
+/// ```
+pub fn calculate_dispersion(
+    base_dispersion: f64,
+    mortar_elevation: f64,
+    target_elevation: f64,
+) -> f64 {
+    DispersionCoefficients::default().adjust(base_dispersion, mortar_elevation, target_elevation)
+}
+
+// ============================================================================
+// Chargement des tables balistiques
+// ============================================================================
+
+/// Charge toutes les tables balistiques depuis le répertoire par défaut (`data/`).
+///
+/// # Erreurs
+///
+/// Retourne une erreur si les fichiers CSV ne peuvent pas être lus.
+pub fn load_ballistics() -> Result<BallisticTables> {
+    load_ballistics_from("data")
+}
+
+/// Charge toutes les tables balistiques depuis un répertoire spécifié.
+///
+/// # Structure attendue
+///
+/// ```text
+/// base/
+/// ├── PRACTICE/
+/// │   ├── M879_PRACTICE_0R.csv
+/// │   ├── M879_PRACTICE_1R.csv
+/// │   └── ...
+/// ├── HE/
+/// │   ├── M821_HE_0R.csv
+/// │   └── ...
+/// ├── SMOKE/
+/// │   ├── M819_SMOKE_1R.csv  (pas de 0R pour SMOKE)
+/// │   └── ...
+/// └── FLARE/
+///     ├── M853A1_FLARE_1R.csv  (pas de 0R pour FLARE)
+///     └── ...
+/// ```
+///
+/// # Arguments
+///
+/// * `base` - Chemin du répertoire de données
+pub fn load_ballistics_from<P: AsRef<Path>>(base: P) -> Result<BallisticTables> {
+    load_ballistics_checked(base, false).map(|(tables, _)| tables)
+}
+
+/// Charge toutes les tables balistiques d'un paquet de données `.zip`.
+///
+/// Le paquet contient les répertoires `PRACTICE/`, `HE/`, `SMOKE/` et
+/// `FLARE/` ainsi que `metrics.json` (voir [`load_ballistics_from`]),
+/// éventuellement sous un répertoire racine commun. Les mises à jour des
+/// données sont ainsi distribuées et remplacées d'un bloc.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si l'archive est illisible ou n'est pas un `.zip`
+/// pris en charge (voir [`archive::DataArchive`]).
+pub fn load_ballistics_from_archive<P: AsRef<Path>>(path: P) -> Result<BallisticTables> {
+    let path = path.as_ref();
+    load_ballistics_with(path, &AmmoRegistry::load(path)?, false, false).map(|(tables, _)| tables)
+}
+
+/// Tables balistiques indexées par (système d'arme, munition, anneau).
+pub type BallisticTables = BTreeMap<TableKey, BallisticTable>;
+
+/// Clé d'une table balistique : système d'arme, munition et anneau.
+pub type TableKey = (WeaponSystem, AmmoKind, Ring);
+
+/// Chemin de la table d'un système d'arme, d'une munition et d'un anneau
+/// sous son nom fixe, relatif au répertoire de données.
+///
+/// Une munition déclarée est rangée sous `<NOM>/<NOM>_<n>R.csv` ; le
+/// répertoire et le préfixe de son manifeste sont donnés par
+/// [`AmmoRegistry::file_name`].
+///
+/// ```
+/// use mortar::{ballistic_file_name, AmmoKind, WeaponSystem};
+/// assert_eq!(
+///     ballistic_file_name(WeaponSystem::M224, AmmoKind::He, 2),
+///     "HE/M821_HE_2R.csv"
+/// );
+/// assert_eq!(
+///     ballistic_file_name(WeaponSystem::M252, AmmoKind::He, 2),
+///     "M252/HE/M821_HE_2R.csv"
+/// );
+/// ```
+pub fn ballistic_file_name(weapon: WeaponSystem, ammo: AmmoKind, ring: Ring) -> String {
+    let name = match ammo::builtin_ammo().into_iter().find(|a| a.kind == ammo) {
+        Some(info) => info.file_name(ring),
+        None => format!("{0}/{0}_{1}R.csv", ammo.as_str(), ring),
+    };
+    weapon_path(weapon, &name)
+}
+
+/// Écrit les tables chargées sous `dir`, avec les noms de fichiers fixes
+/// de `registry` (voir [`AmmoRegistry::file_name`]), au format relu par
+/// [`load_ballistics_from`].
+///
+/// Le répertoire obtenu archive ce que le calcul utilise réellement et se
+/// compare aux fichiers sources ; `step_m` rééchantillonne chaque table
+/// comme [`BallisticTable::write_csv`].
+///
+/// # Retourne
+///
+/// Les chemins écrits, relatifs à `dir`.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si un répertoire ou un fichier ne peut pas être créé.
+pub fn export_tables(
+    tables: &BallisticTables,
+    registry: &AmmoRegistry,
+    dir: &Path,
+    step_m: Option<f64>,
+) -> Result<Vec<String>> {
+    let mut written = Vec::with_capacity(tables.len());
+    for (&(weapon, ammo, ring), table) in tables {
+        let name = registry.file_name(weapon, ammo, ring);
+        let path = dir.join(&name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("cannot create {}", parent.display()))?;
+        }
+        let file = std::fs::File::create(&path)
+            .with_context(|| format!("cannot create {}", path.display()))?;
+        table.write_csv(std::io::BufWriter::new(file), step_m)?;
+        written.push(name);
+    }
+    Ok(written)
+}
+
+/// Préfixe un chemin relatif par le répertoire des tables du système.
+pub(crate) fn weapon_path(weapon: WeaponSystem, name: &str) -> String {
+    match weapon.table_namespace() {
+        Some(ns) => format!("{}/{}", ns, name),
+        None => name.to_string(),
+    }
+}
+
+/// Systèmes d'arme pouvant tirer une munition du registre.
+fn ammo_weapons(info: &ammo::AmmoInfo) -> impl Iterator<Item = WeaponSystem> + '_ {
+    WeaponSystem::all()
+        .iter()
+        .copied()
+        .filter(|w| info.calibers.contains(&w.caliber()))
+}
+
+/// Trie par portée les lignes lues d'une table et contrôle leur cohérence.
+///
+/// Avec `repair`, les lignes incohérentes sont supprimées (voir
+/// [`BallisticTable::repair`]).
+///
+/// # Retourne
+///
+/// La table, ses incohérences (dont [`TableIssue::Unsorted`] si les lignes
+/// n'étaient pas triées) et les lignes supprimées.
+pub fn check_table(
+    points: Vec<BallisticPoint>,
+    repair: bool,
+) -> (BallisticTable, Vec<TableIssue>, Vec<BallisticPoint>) {
+    let sorted = points.windows(2).all(|w| w[0].range_m <= w[1].range_m);
+    let mut table = BallisticTable {
+        points,
+        ..Default::default()
+    };
+    table
+        .points
+        .sort_by(|a, b| a.range_m.partial_cmp(&b.range_m).unwrap());
+
+    let mut issues = table.validate().issues;
+    if !sorted {
+        issues.insert(0, TableIssue::Unsorted);
+    }
+    let dropped = if repair && !issues.is_empty() {
+        table.repair()
+    } else {
+        Vec::new()
+    };
+    (table, issues, dropped)
+}
+
+/// Charge les tables balistiques et contrôle leur cohérence.
+///
+/// Chaque table est triée par portée puis validée (voir
+/// [`BallisticTable::validate`]). Avec `repair`, les lignes incohérentes
+/// sont supprimées (voir [`BallisticTable::repair`]) ; sinon les tables
+/// sont chargées telles quelles.
+///
+/// Un fichier absent ou illisible n'interrompt pas le chargement : il est
+/// signalé dans le [`LoadReport`].
+///
+/// `base` peut aussi être un paquet de données `.zip` (voir
+/// [`load_ballistics_from_archive`]).
+///
+/// Les fichiers sont lus et contrôlés en parallèle (un fil par cœur
+/// disponible) ; les résultats sont fusionnés dans l'ordre des fichiers, si
+/// bien que tables et bilan ne dépendent pas de l'ordonnancement.
+///
+/// # Retourne
+///
+/// Les tables et le bilan de chargement : un [`FileLoad`] par fichier attendu
+/// et un [`TableCheck`] pour chaque table présentant une incohérence.
+pub fn load_ballistics_checked<P: AsRef<Path>>(
+    base: P,
+    repair: bool,
+) -> Result<(BallisticTables, LoadReport)> {
+    let base = base.as_ref();
+    load_ballistics_with(base, &AmmoRegistry::load(base)?, repair, false)
+}
+
+/// Charge les tables balistiques trouvées par exploration des répertoires de
+/// munitions, quels que soient les noms de fichiers.
+///
+/// Chaque répertoire de munition (`PRACTICE/`, `HE/`, `SMOKE/`, `FLARE/`
+/// et ceux déclarés dans `ammo.json`) est parcouru récursivement : un fichier `.csv` dont le nom contient un segment
+/// `_<n>R` (`M821_HE_1R.csv`, `HE_1R_smoothed_1m.csv`...) est la table de
+/// l'anneau `n`. Les fichiers renommés ou lissés (voir `smooth_csv`) sont
+/// ainsi pris en compte sans modification du code.
+///
+/// Sont signalés comme non chargés dans le [`LoadReport`] : un anneau
+/// attendu sans fichier, un anneau trouvé dans plusieurs fichiers (aucun
+/// n'est retenu) et un anneau hors de `0..RING_COUNT`. Le reste est
+/// identique à [`load_ballistics_checked`], paquets `.zip` compris.
+pub fn scan_ballistics_checked<P: AsRef<Path>>(
+    base: P,
+    repair: bool,
+) -> Result<(BallisticTables, LoadReport)> {
+    let base = base.as_ref();
+    load_ballistics_with(base, &AmmoRegistry::load(base)?, repair, true)
+}
+
+/// Charge les tables balistiques d'un répertoire de données complété par
+/// des répertoires de surcharge partiels.
+///
+/// Les surcharges sont appliquées dans l'ordre, chacune l'emportant sur les
+/// précédentes : une unité peut ainsi remplacer la table d'un seul anneau
+/// sans recopier tout le jeu de données livré. Un fichier absent d'une
+/// surcharge est lu dans la couche inférieure ; un fichier présent mais
+/// illisible est signalé dans le [`LoadReport`] et la table inférieure est
+/// conservée. Les munitions déclarées sont celles de toutes les couches
+/// (voir [`AmmoRegistry::load_layered`]).
+///
+/// Sans surcharge, équivaut à [`load_ballistics_checked`] (ou
+/// [`scan_ballistics_checked`] avec `scan`).
+pub fn load_ballistics_layered<P: AsRef<Path>>(
+    base: P,
+    overlays: &[PathBuf],
+    repair: bool,
+    scan: bool,
+) -> Result<(BallisticTables, LoadReport)> {
+    let base = base.as_ref();
+    let registry = AmmoRegistry::load_layered(base, overlays)?;
+    let (mut tables, mut report) = load_ballistics_with(base, &registry, repair, scan)?;
+    for overlay in overlays {
+        let (layer, layer_report) = load_ballistics_with(overlay, &registry, repair, scan)?;
+        for file in layer_report.files {
+            let same = |f: &FileLoad| {
+                (f.weapon, f.ammo_type, f.ring) == (file.weapon, file.ammo_type, file.ring)
+            };
+            if file.loaded {
+                report.files.retain(|f| !same(f));
+                report.checks.retain(|c| {
+                    (c.weapon, c.ammo_type, c.ring) != (file.weapon, file.ammo_type, file.ring)
+                });
+                report.files.push(file);
+            } else if Path::new(&file.path).exists() {
+                report.files.push(file);
+            }
+        }
+        report.checks.extend(layer_report.checks);
+        tables.extend(layer);
+    }
+    Ok((tables, report))
+}
+
+/// Couche de données fournissant `name` : la dernière surcharge qui contient
+/// le fichier, sinon `base`.
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+/// use mortar::data_layer;
+///
+/// let overlays = [PathBuf::from("does-not-exist")];
+/// assert_eq!(data_layer(Path::new("data"), &overlays, "metrics.json"), Path::new("data"));
+/// ```
+pub fn data_layer<'a>(base: &'a Path, overlays: &'a [PathBuf], name: &str) -> &'a Path {
+    overlays
+        .iter()
+        .rev()
+        .find(|o| o.join(name).is_file())
+        .map_or(base, PathBuf::as_path)
+}
+
+/// Charge les tables d'un répertoire ou d'un paquet, par noms fixes ou par
+/// exploration (`scan`).
+fn load_ballistics_with(
+    base: &Path,
+    registry: &AmmoRegistry,
+    repair: bool,
+    scan: bool,
+) -> Result<(BallisticTables, LoadReport)> {
+    if archive::is_data_archive(base) {
+        let pack = archive::DataArchive::open(base)?;
+        let files = if scan {
+            scan_table_files(registry, pack.names())
+        } else {
+            fixed_table_files(registry)
+        };
+        return check_ballistic_files(base, repair, files, |name| match pack.file(name) {
+            Some(csv) => read_ballistic_points(csv),
+            None => bail!("not found in data pack"),
+        });
+    }
+    let files = if scan {
+        let mut names = Vec::new();
+        for info in &registry.ammo {
+            for weapon in ammo_weapons(info) {
+                list_files(base, &weapon_path(weapon, &info.directory), &mut names);
+            }
+        }
+        scan_table_files(registry, names.iter().map(String::as_str))
+    } else {
+        fixed_table_files(registry)
+    };
+    check_ballistic_files(base, repair, files, |name| read_csv_points(base.join(name)))
+}
+
+/// Table à charger : chemin relatif à la base (répertoire de la munition si
+/// la découverte a échoué) et erreur de découverte.
+struct TableFile {
+    weapon: WeaponSystem,
+    ammo: AmmoKind,
+    ring: Ring,
+    name: String,
+    error: Option<String>,
+}
+
+/// Tables des munitions du registre, sous leurs noms fixes, pour chaque
+/// système d'arme pouvant les tirer.
+fn fixed_table_files(registry: &AmmoRegistry) -> Vec<TableFile> {
+    let mut files = Vec::new();
+    for info in &registry.ammo {
+        for weapon in ammo_weapons(info) {
+            for &ring in &info.rings {
+                if usize::from(ring) < weapon.ring_count() {
+                    files.push(TableFile {
+                        weapon,
+                        ammo: info.kind,
+                        ring,
+                        name: weapon_path(weapon, &info.file_name(ring)),
+                        error: None,
+                    });
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Tables trouvées parmi des chemins relatifs (séparateur `/`), par
+/// munition, système d'arme puis anneau (voir [`scan_ballistics_checked`]).
+fn scan_table_files<'a>(
+    registry: &AmmoRegistry,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Vec<TableFile> {
+    let mut found: BTreeMap<TableKey, Vec<&str>> = BTreeMap::new();
+    let mut files = Vec::new();
+    for name in names {
+        let (weapon, ammo) = match registry.ammo.iter().find_map(|info| {
+            ammo_weapons(info)
+                .find(|&weapon| {
+                    name.strip_prefix(&weapon_path(weapon, &info.directory))
+                        .is_some_and(|rest| rest.starts_with('/'))
+                })
+                .map(|weapon| (weapon, info.kind))
+        }) {
+            Some(found) => found,
+            None => continue,
+        };
+        let stem = match name.rsplit('/').next().and_then(|f| {
+            let (stem, ext) = f.rsplit_once('.')?;
+            ext.eq_ignore_ascii_case("csv").then_some(stem)
+        }) {
+            Some(stem) => stem,
+            None => continue,
+        };
+        let ring = stem.split('_').skip(1).find_map(|token| {
+            let digits = token.strip_suffix(['R', 'r'])?;
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            Some(digits.parse::<usize>().unwrap_or(usize::MAX))
+        });
+        match ring {
+            Some(ring) if ring < weapon.ring_count() => found
+                .entry((weapon, ammo, ring as Ring))
+                .or_default()
+                .push(name),
+            Some(ring) => files.push(TableFile {
+                weapon,
+                ammo,
+                ring: ring.min(Ring::MAX as usize) as Ring,
+                name: name.to_string(),
+                error: Some(format!(
+                    "ring {}R out of range (0-{}R)",
+                    ring,
+                    weapon.ring_count() - 1
+                )),
+            }),
+            None => {}
+        }
+    }
+
+    for info in &registry.ammo {
+        for weapon in ammo_weapons(info) {
+            let (ammo, dir) = (info.kind, weapon_path(weapon, &info.directory));
+            for &ring in &info.rings {
+                if usize::from(ring) < weapon.ring_count() {
+                    found.entry((weapon, ammo, ring)).or_default();
+                }
+            }
+            for ((_, _, ring), names) in found.range((weapon, ammo, 0)..=(weapon, ammo, Ring::MAX))
+            {
+                files.push(match names.as_slice() {
+                    [name] => TableFile {
+                        weapon,
+                        ammo,
+                        ring: *ring,
+                        name: name.to_string(),
+                        error: None,
+                    },
+                    [] => TableFile {
+                        weapon,
+                        ammo,
+                        ring: *ring,
+                        name: dir.clone(),
+                        error: Some(format!("no {}R table found", ring)),
+                    },
+                    several => TableFile {
+                        weapon,
+                        ammo,
+                        ring: *ring,
+                        name: dir.clone(),
+                        error: Some(format!(
+                            "several {}R tables, keep only one: {}",
+                            ring,
+                            several.join(", ")
+                        )),
+                    },
+                });
+            }
+        }
+    }
+    files
+}
+
+/// Ajoute les fichiers de `base/dir`, récursivement et triés, en chemins
+/// relatifs à `base` (séparateur `/`).
+fn list_files(base: &Path, dir: &str, out: &mut Vec<String>) {
+    let mut entries: Vec<_> = match std::fs::read_dir(base.join(dir)) {
+        Ok(entries) => entries.flatten().collect(),
+        Err(_) => return,
+    };
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let name = format!("{}/{}", dir, entry.file_name().to_string_lossy());
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            list_files(base, &name, out);
+        } else {
+            out.push(name);
+        }
+    }
+}
+
+/// Lit et contrôle en parallèle les tables `files`.
+///
+/// `read` lit un fichier à partir de son chemin relatif à `base`.
+fn check_ballistic_files<F>(
+    base: &Path,
+    repair: bool,
+    files: Vec<TableFile>,
+    read: F,
+) -> Result<(BallisticTables, LoadReport)>
+where
+    F: Fn(&str) -> Result<Vec<BallisticPoint>> + Sync,
+{
+    let read = &read;
+
+    // Découpage en blocs contigus : la concaténation conserve l'ordre des fichiers
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .clamp(1, files.len().max(1));
+    let chunk = files.len().div_ceil(workers).max(1);
+    type Checked = (BallisticTable, Vec<TableIssue>, Vec<BallisticPoint>);
+    let loaded: Vec<Result<Checked>> = std::thread::scope(|s| {
+        let handles: Vec<_> = files
+            .chunks(chunk)
+            .map(|part| {
+                s.spawn(move || {
+                    part.iter()
+                        .map(|file| match &file.error {
+                            Some(e) => Err(anyhow!("{e}")),
+                            None => read(&file.name).map(|points| check_table(points, repair)),
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+
+    let mut m = BallisticTables::new();
+    let mut report = LoadReport::default();
+    for (file, checked) in files.into_iter().zip(loaded) {
+        let (weapon, ammo, r) = (file.weapon, file.ammo, file.ring);
+        let p = base.join(file.name);
+        let (table, issues, dropped) = match checked {
+            Ok(checked) => checked,
+            Err(e) => {
+                report.files.push(FileLoad {
+                    weapon,
+                    ammo_type: ammo,
+                    ring: r,
+                    path: p.display().to_string(),
+                    loaded: false,
+                    error: Some(format!("{e:#}")),
+                });
+                continue;
+            }
+        };
+        report.files.push(FileLoad {
+            weapon,
+            ammo_type: ammo,
+            ring: r,
+            path: p.display().to_string(),
+            loaded: true,
+            error: None,
+        });
+        if !issues.is_empty() {
+            report.checks.push(TableCheck {
+                weapon,
+                ammo_type: ammo,
+                ring: r,
+                issues,
+                dropped,
+            });
+        }
+        m.insert((weapon, ammo, r), table);
+    }
+
+    Ok((m, report))
+}
+
+// ============================================================================
+// Solution de tir
+// ============================================================================
+
+/// Solution de tir complète entre un mortier et une cible.
+///
+/// Contient toutes les informations nécessaires pour effectuer un tir :
+/// - Paramètres géométriques (distance, azimut, dénivelé)
+/// - Élévations pour chaque type de munition et anneau
+/// - Dispersions ajustées pour le dénivelé
+/// - Solution sélectionnée basée sur la munition du mortier
+#[derive(Clone, Debug, Serialize)]
+pub struct FiringSolution {
+    /// Distance horizontale en mètres
+    pub distance_m: f64,
+    /// Azimut en degrés (0-360, depuis le Nord)
+    pub azimuth_deg: f64,
+    /// Différence d'élévation absolue en mètres
+    pub elevation_diff_m: f64,
+    /// Différence d'élévation signée (mortier - cible, positif = mortier plus haut)
+    pub signed_elevation_diff_m: f64,
+    /// Système d'arme de la pièce, dont les tables ont été lues
+    pub weapon: WeaponSystem,
+    /// Type de munition chargée dans le mortier
+    pub mortar_ammo: String,
+    /// Type tactique de la cible
+    pub target_type: String,
+    /// Type de munition recommandé pour cette cible
+    pub recommended_ammo: String,
+    /// Élévations par type de munition et anneau
+    /// Structure: `{ "HE": { "0R": 1200.5, "1R": 1180.2, ... }, ... }`
+    #[serde(serialize_with = "serialize_ammo_ring_tables")]
+    pub solutions: AmmoRingTables,
+    /// Dispersions ajustées par type de munition et anneau (en mètres)
+    #[serde(serialize_with = "serialize_ammo_ring_tables")]
+    pub dispersions: AmmoRingTables,
+    /// Solution sélectionnée basée sur la munition du mortier
+    pub selected_solution: Option<SelectedSolution>,
+    /// Angle T lorsque la position d'un observateur est fournie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub angle_t: Option<observation::AngleT>,
+    /// Délais d'observation (départ, impact, son), lorsqu'un observateur est fourni
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observation_timing: Option<observation::ObservationTiming>,
+    /// Conseil d'emploi lié au vent, lorsque le vent est fourni
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind_advice: Option<environment::WindAdvice>,
+    /// Correction du vent, lorsque le vent de l'opération est défini
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wind: Option<environment::WindCorrection>,
+    /// Correction des conditions de l'air, lorsqu'elles sont définies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub atmosphere: Option<environment::AtmosphereCorrection>,
+    /// Correction de température des charges, lorsque la température est fournie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charge_temperature: Option<environment::ChargeTemperatureCorrection>,
+    /// Correction de dérive, lorsque `metrics.json` fournit la dérive de la munition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift: Option<DriftCorrection>,
+    /// Correction du rendement des charges, lorsque la pièce en déclare un
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charge_efficiency: Option<ChargeEfficiencyCorrection>,
+    /// Correction de vitesse initiale (usure du tube), lorsque la pièce en déclare une
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub muzzle_velocity: Option<MuzzleVelocityCorrection>,
+    /// Correction de réglage sur point connu, lorsque la pièce en a une
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registration: Option<registration::RegistrationCorrection>,
+    /// Péremption du renseignement sur la cible, si sa date d'observation est connue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staleness: Option<Staleness>,
+    /// Dépassements des plafonds d'espace aérien de l'opération, s'il y en a
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub airspace_conflict: Option<airspace::AirspaceConflict>,
+    /// Différence d'élévation au-delà des seuils configurés, s'il y a lieu
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elevation_warning: Option<terrain::ElevationWarning>,
+    /// Contrôle des crêtes du profil de terrain fourni, s'il y a lieu
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crest_clearance: Option<terrain::CrestClearance>,
+    /// Amis à l'intérieur du rayon de danger, s'il y en a
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub danger_close: Option<safety::DangerClose>,
+    /// `true` si des données manquent pour la munition sélectionnée
+    pub degraded: bool,
+    /// Données manquantes pour les anneaux utilisables de la munition sélectionnée
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_data: Vec<MissingData>,
+    /// Avertissements à porter à l'attention du calculateur
+    pub warnings: Vec<String>,
+}
+
+impl FiringSolution {
+    /// Portée à atteindre à un anneau (m) : distance de tir corrigée du
+    /// réglage, du vent et de l'air lorsqu'ils sont appliqués, avant rendement
+    /// des charges.
+    pub fn aim_range_m(&self, ring: Ring) -> f64 {
+        self.distance_m
+            + self
+                .registration
+                .as_ref()
+                .map_or(0.0, |r| r.range_correction_m)
+            + self
+                .wind
+                .as_ref()
+                .and_then(|w| w.range_correction_m.get(ring))
+                .unwrap_or(0.0)
+            + self
+                .atmosphere
+                .as_ref()
+                .and_then(|a| a.range_correction_m.get(ring))
+                .unwrap_or(0.0)
+    }
+
+    /// Gisement grille à pointer à un anneau (mils) : gisement de la cible
+    /// corrigé du réglage, de la dérive et du vent lorsqu'ils sont appliqués.
+    pub fn aim_azimuth_mil(&self, ring: Ring) -> f64 {
+        mils::normalize(
+            mils::deg_to_mil(self.azimuth_deg)
+                + self.registration.as_ref().map_or(0.0, |r| r.deflection_mil)
+                + self
+                    .drift
+                    .as_ref()
+                    .and_then(|d| d.correction_mil.get(ring))
+                    .unwrap_or(0.0)
+                + self
+                    .wind
+                    .as_ref()
+                    .and_then(|w| w.deflection_mil.get(ring))
+                    .unwrap_or(0.0),
+        )
+    }
+}
+
+/// Correction de portée due au rendement des charges d'une pièce.
+///
+/// Contrairement à la température des charges, elle est appliquée à toutes
+/// les élévations de la solution : la pièce tire toujours avec ces charges.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChargeEfficiencyCorrection {
+    /// Rendement des charges (fraction de la portée des tables)
+    pub factor: f64,
+    /// Portée lue dans les tables (m)
+    pub table_range_m: f64,
+    /// Correction de portée appliquée (m, positive pour des charges tirant court)
+    pub range_correction_m: f64,
+}
+
+/// Correction de portée due à l'écart de vitesse initiale d'un tube usé.
+///
+/// Elle s'ajoute au rendement des charges : `table_range_m` est la portée
+/// lue dans les tables après les deux corrections.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MuzzleVelocityCorrection {
+    /// Écart de vitesse initiale du tube (%)
+    pub mv_correction_pct: f64,
+    /// Fraction de la portée des tables atteinte du fait de cet écart
+    pub range_factor: f64,
+    /// Portée lue dans les tables (m)
+    pub table_range_m: f64,
+    /// Part de la correction due au tube (m, positive pour un tube tirant court)
+    pub range_correction_m: f64,
+}
+
+/// Type de donnée absente d'une solution dégradée.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingData {
+    /// Dispersion de base absente de `metrics.json`
+    Dispersion,
+    /// Durée de trajet absente de la table balistique
+    TimeOfFlight,
+}
+
+impl MissingData {
+    /// Retourne le nom sérialisé de la donnée manquante.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MissingData::Dispersion => "dispersion",
+            MissingData::TimeOfFlight => "time_of_flight",
+        }
+    }
+}
+
+/// Solution de tir sélectionnée pour un type de munition spécifique.
+#[derive(Clone, Debug, Serialize)]
+pub struct SelectedSolution {
+    /// Type de munition
+    pub ammo_type: String,
+    /// Élévations par anneau (en mils)
+    pub elevations: RingTable,
+    /// Dispersions ajustées par anneau (en mètres) : rayon du cercle
+    /// englobant l'ellipse de dispersion
+    pub dispersions: RingTable,
+    /// Ellipse de dispersion ajustée, si des dispersions sont connues
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dispersion_ellipse: Option<DispersionEllipse>,
+    /// Multiplicateurs de dispersion de la pièce déjà appliqués, par anneau
+    #[serde(skip_serializing_if = "RingTable::is_empty")]
+    pub dispersion_factors: RingTable,
+    /// Rayon d'effet de la munition (en mètres), si connu
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effect_radius_m: Option<f64>,
+    /// Anneaux exclus par la pièce (élévations non proposées)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_rings: Vec<String>,
+    /// Anneau par défaut de la pièce (ex: "2R"), s'il est défini et disponible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_ring: Option<String>,
+    /// Variation de portée pour 1 mil d'élévation, par anneau (en mètres)
+    pub range_m_per_mil: RingTable,
+    /// Élévation à retirer pour allonger le tir de 100 m, par anneau (en mils)
+    pub elev_mil_per_100m: RingTable,
+    /// Décalage latéral pour 1 mil de gisement à la distance de tir (en mètres)
+    pub lateral_m_per_mil: f64,
+    /// Flèche par anneau : hauteur maximale de la trajectoire au-dessus de la
+    /// pièce (en mètres), d'après la durée de trajet de la table ou, à défaut,
+    /// l'élévation (trajectoire dans le vide)
+    pub max_ordinate_m: RingTable,
+    /// Comparaison des anneaux lorsque plusieurs couvrent la distance de tir
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ring_comparison: Option<RingComparison>,
+}
+
+/// Ellipse de dispersion des impacts autour du point visé.
+#[derive(Clone, Debug, Serialize)]
+pub struct DispersionEllipse {
+    /// Orientation de l'axe en portée : azimut de la ligne pièce-but (degrés)
+    pub azimuth_deg: f64,
+    /// Écarts probables en portée par anneau (en mètres)
+    pub range_pe_m: RingTable,
+    /// Écarts probables en direction par anneau (en mètres)
+    pub deflection_pe_m: RingTable,
+}
+
+/// Marge (en mètres) en deçà de laquelle une portée est considérée en limite
+/// de table pour un anneau.
+pub const RING_EDGE_MARGIN_M: f64 = 50.0;
+
+/// Données de tir d'un anneau couvrant la distance de tir.
+#[derive(Clone, Debug, Serialize)]
+pub struct RingOption {
+    /// Anneau (ex: "2R")
+    pub ring: String,
+    /// Élévation en mils
+    pub elevation_mil: f64,
+    /// Durée de trajet en secondes, si la table la fournit
+    pub time_of_flight_s: Option<f64>,
+    /// Dispersion ajustée en mètres, si connue
+    pub dispersion_m: Option<f64>,
+    /// `true` si la distance est à moins de [`RING_EDGE_MARGIN_M`] d'une
+    /// borne de la table
+    pub near_table_limit: bool,
+}
+
+/// Comparaison des anneaux couvrant une même distance, avec l'anneau conseillé.
+#[derive(Clone, Debug, Serialize)]
+pub struct RingComparison {
+    /// Anneaux utilisables, par charge croissante
+    pub options: Vec<RingOption>,
+    /// Anneau conseillé
+    pub recommended_ring: String,
+    /// Motif du conseil
+    pub reason: String,
+}
+
+/// Compare les anneaux de la pièce couvrant la distance de tir.
+///
+/// `distance_m` est la portée lue dans les tables (voir
+/// [`MortarPosition::table_range`]).
+///
+/// Les anneaux en limite de table sont écartés si un autre anneau est
+/// disponible ; parmi les restants, l'anneau conseillé est celui de plus
+/// faible dispersion, puis de plus courte durée de trajet.
+///
+/// # Retourne
+///
+/// `None` si moins de deux anneaux couvrent la distance.
+pub fn compare_rings(
+    mortar: &MortarPosition,
+    ammo: AmmoKind,
+    distance_m: f64,
+    ballistics: &BallisticTables,
+    dispersions: &RingTable,
+) -> Option<RingComparison> {
+    let mut options = Vec::new();
+    for ring in 0..=4 {
+        if !mortar.ring_available(ring) {
+            continue;
+        }
+        let table = match ballistics.get(&(mortar.weapon, ammo, ring)) {
+            Some(table) => table,
+            None => continue,
+        };
+        let (elevation_mil, (min_r, max_r)) =
+            match (table.elev_at(distance_m), table.range_bounds()) {
+                (Some(elev), Some(bounds)) => (elev, bounds),
+                _ => continue,
+            };
+        options.push(RingOption {
+            dispersion_m: dispersions.get(ring),
+            ring: format!("{}R", ring),
+            elevation_mil,
+            time_of_flight_s: table.time_of_flight_at(distance_m),
+            near_table_limit: distance_m - min_r < RING_EDGE_MARGIN_M
+                || max_r - distance_m < RING_EDGE_MARGIN_M,
+        });
+    }
+    if options.len() < 2 {
+        return None;
+    }
+
+    let all_near_limit = options.iter().all(|o| o.near_table_limit);
+    let candidates: Vec<&RingOption> = options
+        .iter()
+        .filter(|o| all_near_limit || !o.near_table_limit)
+        .collect();
+    let score = |o: &RingOption| {
+        (
+            o.dispersion_m.unwrap_or(f64::INFINITY),
+            o.time_of_flight_s.unwrap_or(f64::INFINITY),
+        )
+    };
+    let best = candidates
+        .iter()
+        .copied()
+        .min_by(|a, b| score(a).partial_cmp(&score(b)).unwrap_or(Ordering::Equal))?;
+
+    let dispersion_tie = candidates
+        .iter()
+        .any(|o| o.ring != best.ring && o.dispersion_m == best.dispersion_m);
+    let mut reason = match (best.dispersion_m, best.time_of_flight_s) {
+        (Some(d), _) if !dispersion_tie => format!("lowest dispersion ({:.0} m)", d),
+        (_, Some(t)) => format!("shortest time of flight ({:.1} s)", t),
+        _ => "lowest charge".to_string(),
+    };
+    let edge: Vec<&str> = options
+        .iter()
+        .filter(|o| o.near_table_limit && !all_near_limit)
+        .map(|o| o.ring.as_str())
+        .collect();
+    if !edge.is_empty() {
+        reason.push_str(&format!("; {} near table limit", edge.join(", ")));
+    }
+
+    Some(RingComparison {
+        recommended_ring: best.ring.clone(),
+        reason,
+        options,
+    })
+}
+
+/// Renseigne le rayon d'effet de la munition sélectionnée d'une solution.
+pub fn attach_effect_radius(solution: &mut FiringSolution, effects: &EffectTable) {
+    if let Some(selected) = solution.selected_solution.as_mut() {
+        selected.effect_radius_m = AmmoKind::from_name(&selected.ammo_type)
+            .and_then(|ammo| effects.get(&ammo))
+            .and_then(EffectRadii::effect_radius);
+    }
+}
+
+/// Correction de gisement compensant la dérive, par anneau.
+#[derive(Clone, Debug, Serialize)]
+pub struct DriftCorrection {
+    /// Dérive de l'obus à la distance de tir (mils, positive à droite)
+    pub drift_mil: RingTable,
+    /// Correction de gisement à appliquer (mils, opposée à la dérive)
+    pub correction_mil: RingTable,
+    /// Gisement grille corrigé de la dérive (mils)
+    pub azimuth_mil: RingTable,
+}
+
+/// Calcule la correction de dérive de la munition sélectionnée d'une solution.
+///
+/// Le gisement de la solution (`azimuth_deg`) n'est pas modifié : la
+/// correction est reportée à part dans `drift`, pour les anneaux utilisables
+/// de la pièce dont la courbe couvre la distance de tir. Rien n'est ajouté si
+/// aucun anneau n'a de dérive connue.
+pub fn attach_drift(solution: &mut FiringSolution, mortar: &MortarPosition, drift: &DriftTable) {
+    let ammo = match solution
+        .selected_solution
+        .as_ref()
+        .and_then(|s| AmmoKind::from_name(&s.ammo_type))
+    {
+        Some(ammo) => ammo,
+        None => return,
+    };
+    let drift_mil = RingTable::from_fn(|ring| {
+        if !mortar.ring_available(ring) {
+            return None;
+        }
+        drift.get(&(ammo, ring))?.drift_at(solution.distance_m)
+    });
+    if drift_mil.first().is_none() {
+        return;
+    }
+
+    let azimuth_mil = mils::deg_to_mil(solution.azimuth_deg);
+    let correction_mil = RingTable::from_fn(|ring| drift_mil.get(ring).map(|d| -d));
+    solution.drift = Some(DriftCorrection {
+        drift_mil,
+        correction_mil,
+        azimuth_mil: RingTable::from_fn(|ring| {
+            correction_mil
+                .get(ring)
+                .map(|c| mils::normalize(azimuth_mil + c))
+        }),
+    });
+}
+
+/// Solution calculée ou tirée, conservée dans l'historique d'une cible.
+#[derive(Clone, Debug, Serialize)]
+pub struct SolutionRecord {
+    /// Date du calcul (secondes Unix)
+    pub timestamp: u64,
+    /// Mortier ayant servi au calcul
+    pub mortar: String,
+    /// `true` si la solution a été tirée (coup de réglage), `false` si seulement calculée
+    pub fired: bool,
+    /// Déviation observée de l'impact, pour les coups tirés
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deviation: Option<fire::SpotObservation>,
+    /// Solution de tir
+    pub solution: FiringSolution,
+    /// Trace du coup (départ et impact), pour les coups tirés
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shot: Option<replay::ShotTrace>,
+}
+
+/// Calcule la solution de tir sans données de dispersion.
+///
+/// Équivalent à `calculate_solution_with_dispersion` avec une table de dispersion vide.
+///
+/// # Arguments
+///
+/// * `mortar` - Position du mortier
+/// * `target` - Position de la cible
+/// * `ballistics` - Tables balistiques chargées
+pub fn calculate_solution(
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    ballistics: &BallisticTables,
+) -> FiringSolution {
+    calculate_solution_with_dispersion(mortar, target, ballistics, &DispersionTable::new())
+}
+
+/// Calcule la solution de tir complète avec dispersions ajustées.
+///
+/// # Arguments
+///
+/// * `mortar` - Position du mortier avec type de munition
+/// * `target` - Position de la cible avec type tactique
+/// * `ballistics` - Tables balistiques chargées
+/// * `dispersion_table` - Table des dispersions de base
+///
+/// # Retourne
+///
+/// Une [`FiringSolution`] contenant toutes les informations de tir.
+///
+/// # Exemple
+///
+/// ```rust,ignore
+/// let solution = calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+///
+/// println!("Distance: {} m", solution.distance_m);
+/// println!("Azimut: {} deg", solution.azimuth_deg);
+///
+/// if let Some(sel) = &solution.selected_solution {
+///     println!("Élévation 2R: {:?} mils", sel.elevations.get(2));
+///     println!("Dispersion 2R: {:?} m", sel.dispersions.get(2));
+/// }
+/// ```
+pub fn calculate_solution_with_dispersion(
     mortar: &MortarPosition,
     target: &TargetPosition,
-    ballistics: &BTreeMap<(AmmoKind, Ring), BallisticTable>,
+    ballistics: &BallisticTables,
     dispersion_table: &DispersionTable,
 ) -> FiringSolution {
     let mortar_pos = mortar.as_position();
     let target_pos = target.as_position();
 
-    let distance_m = mortar_pos.distance_to(&target_pos);
-    let azimuth_deg = mortar_pos.azimuth_to(&target_pos);
-    let elevation_diff_m = mortar_pos.elevation_difference(&target_pos);
-    let signed_elevation_diff_m = mortar.elevation - target.elevation;
+    let distance_m = mortar_pos.distance_to(&target_pos);
+    let azimuth_deg = mortar_pos.azimuth_to(&target_pos);
+    let elevation_diff_m = mortar_pos.elevation_difference(&target_pos);
+    let signed_elevation_diff_m = mortar.elevation - target.elevation;
+    // Portée lue dans les tables, corrigée du réglage de la pièce puis du
+    // rendement des charges
+    let registration_m = mortar
+        .registration
+        .as_ref()
+        .map_or(0.0, |r| r.range_correction_m);
+    let table_range_m = mortar.table_range(distance_m + registration_m);
+
+    let elevations_for = |kind: AmmoKind| {
+        RingTable::from_fn(|r| {
+            ballistics
+                .get(&(mortar.weapon, kind, r))
+                .and_then(|t| t.elev_at(table_range_m))
+        })
+    };
+    let adjusted = |kind: AmmoKind, r: Ring| {
+        dispersion_table.get(&(kind, r)).map(|base| {
+            base.adjusted(distance_m, mortar.elevation, target.elevation)
+                .scaled(mortar.dispersion_factor(r))
+        })
+    };
+    let dispersions_for =
+        |kind: AmmoKind| RingTable::from_fn(|r| adjusted(kind, r).map(|d| d.radius_m()));
+
+    // Munitions déclarées : seulement celles dont une table est chargée
+    let weapon = mortar.weapon;
+    let mut kinds: Vec<AmmoKind> = AmmoKind::all().to_vec();
+    for &(w, kind, _) in ballistics.keys() {
+        if w == weapon && matches!(kind, AmmoKind::Custom(_)) && !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    let solutions: AmmoRingTables = kinds.iter().map(|&k| (k, elevations_for(k))).collect();
+    let dispersions: AmmoRingTables = kinds.iter().map(|&k| (k, dispersions_for(k))).collect();
+
+    // Selected solution based on target's ammo type
+    let selected_ammo = target.ammo_type;
+    let mut selected_elevations = RingTable::default();
+    let mut selected_range_per_mil = RingTable::default();
+    let mut selected_elev_per_100m = RingTable::default();
+    let mut selected_max_ordinate = RingTable::default();
+    let mut excluded_rings = Vec::new();
+    for r in 0..RING_COUNT as Ring {
+        let table = if mortar.ring_available(r) {
+            ballistics.get(&(weapon, selected_ammo, r))
+        } else {
+            excluded_rings.push(format!("{}R", r));
+            None
+        };
+        selected_elevations.set(r, table.and_then(|t| t.elev_at(table_range_m)));
+        selected_range_per_mil.set(
+            r,
+            table
+                .and_then(|t| t.range_per_mil(table_range_m))
+                .map(|m| mortar.ground_range(m)),
+        );
+        selected_elev_per_100m.set(
+            r,
+            table
+                .and_then(|t| t.elev_per_100m_at(table_range_m))
+                .map(|e| mortar.table_range(e)),
+        );
+        selected_max_ordinate.set(
+            r,
+            table.and_then(|t| {
+                let tof = t.time_of_flight_at(table_range_m).or_else(|| {
+                    t.elev_at(table_range_m)
+                        .map(|e| airspace::vacuum_time_of_flight_s(table_range_m, e))
+                })?;
+                Some(airspace::apex_height_m(tof, -signed_elevation_diff_m))
+            }),
+        );
+    }
+    let selected_dispersions = dispersions_for(selected_ammo);
+    let dispersion_ellipse = selected_dispersions
+        .first()
+        .is_some()
+        .then(|| DispersionEllipse {
+            azimuth_deg,
+            range_pe_m: RingTable::from_fn(|r| adjusted(selected_ammo, r).map(|d| d.range_pe_m)),
+            deflection_pe_m: RingTable::from_fn(|r| {
+                adjusted(selected_ammo, r).map(|d| d.deflection_pe_m)
+            }),
+        });
+
+    // Santé de la solution : seuls les anneaux donnant une élévation comptent
+    let usable: Vec<Ring> = selected_elevations
+        .iter()
+        .filter(|(_, e)| e.is_some())
+        .map(|(r, _)| r)
+        .collect();
+    let mut missing_data = Vec::new();
+    if usable
+        .iter()
+        .any(|r| !dispersion_table.contains_key(&(selected_ammo, *r)))
+    {
+        missing_data.push(MissingData::Dispersion);
+    }
+    if usable.iter().any(|r| {
+        ballistics
+            .get(&(weapon, selected_ammo, *r))
+            .and_then(|t| t.time_of_flight_at(table_range_m))
+            .is_none()
+    }) {
+        missing_data.push(MissingData::TimeOfFlight);
+    }
+
+    // Portée corrigée du seul rendement des charges, pour répartir la correction
+    let charge_range_m = (distance_m + registration_m) / mortar.charge_efficiency.unwrap_or(1.0);
+    let charge_efficiency = mortar
+        .charge_efficiency
+        .map(|factor| ChargeEfficiencyCorrection {
+            factor,
+            table_range_m,
+            range_correction_m: charge_range_m - distance_m - registration_m,
+        });
+    let muzzle_velocity = mortar
+        .mv_correction_pct
+        .map(|pct| MuzzleVelocityCorrection {
+            mv_correction_pct: pct,
+            range_factor: mortar.mv_range_factor(),
+            table_range_m,
+            range_correction_m: table_range_m - charge_range_m,
+        });
+    let mut warnings = Vec::new();
+    if let Err(e) = mortar.check_sector(target) {
+        warnings.push(e.to_string());
+    }
+    if !mortar.dispersion_factors.is_empty() {
+        let factors: Vec<String> = mortar
+            .dispersion_factors
+            .iter()
+            .map(|(r, f)| format!("{}R x{:.2}", r, f))
+            .collect();
+        warnings.push(format!(
+            "Worn tube on mortar '{}': dispersions enlarged ({})",
+            mortar.name,
+            factors.join(", ")
+        ));
+    }
+    if let Some(radius) = target.uncertainty_m {
+        warnings.push(format!(
+            "Target '{}' is located to within {:.0} m only: plan area fire",
+            target.name, radius
+        ));
+    }
+    if let Some(c) = &charge_efficiency {
+        warnings.push(format!(
+            "Degraded charges on mortar '{}' ({:.1}% of table range): elevations aimed at {:.0} m ({:+.0} m)",
+            mortar.name,
+            c.factor * 100.0,
+            c.table_range_m,
+            c.range_correction_m
+        ));
+    }
+    if let Some(c) = &muzzle_velocity {
+        warnings.push(format!(
+            "Muzzle velocity {:+.1}% on mortar '{}' ({:.1}% of table range): elevations aimed at {:.0} m ({:+.0} m)",
+            c.mv_correction_pct,
+            mortar.name,
+            c.range_factor * 100.0,
+            c.table_range_m,
+            c.range_correction_m
+        ));
+    }
+
+    let ring_comparison = compare_rings(
+        mortar,
+        selected_ammo,
+        table_range_m,
+        ballistics,
+        &selected_dispersions,
+    );
+    let selected_solution = Some(SelectedSolution {
+        ammo_type: selected_ammo.as_str().to_string(),
+        elevations: selected_elevations,
+        dispersions: selected_dispersions,
+        dispersion_ellipse,
+        dispersion_factors: RingTable::from_fn(|r| mortar.dispersion_factors.get(&r).copied()),
+        effect_radius_m: None,
+        excluded_rings,
+        default_ring: mortar
+            .default_ring
+            .filter(|r| mortar.ring_available(*r))
+            .map(|r| format!("{}R", r)),
+        range_m_per_mil: selected_range_per_mil,
+        elev_mil_per_100m: selected_elev_per_100m,
+        lateral_m_per_mil: lateral_m_per_mil(distance_m),
+        max_ordinate_m: selected_max_ordinate,
+        ring_comparison,
+    });
+
+    let mut solution = FiringSolution {
+        distance_m,
+        azimuth_deg,
+        elevation_diff_m,
+        signed_elevation_diff_m,
+        weapon,
+        mortar_ammo: target.ammo_type.as_str().to_string(),
+        target_type: target.target_type.as_str().to_string(),
+        recommended_ammo: target.target_type.suggested_ammo().as_str().to_string(),
+        solutions,
+        dispersions,
+        selected_solution,
+        angle_t: None,
+        observation_timing: None,
+        wind_advice: None,
+        wind: None,
+        atmosphere: None,
+        charge_temperature: None,
+        drift: None,
+        charge_efficiency,
+        muzzle_velocity,
+        registration: None,
+        staleness: None,
+        airspace_conflict: None,
+        elevation_warning: None,
+        crest_clearance: None,
+        danger_close: None,
+        degraded: !missing_data.is_empty(),
+        missing_data,
+        warnings,
+    };
+    registration::attach_registration(&mut solution, mortar);
+    solution
+}
+
+// ============================================================================
+// Correction de tir
+// ============================================================================
+
+/// Convention de signes des déviations saisies par l'utilisateur.
+///
+/// Les fonctions de correction de la bibliothèque et les données conservées
+/// (historiques, événements) utilisent toujours la convention standard
+/// Nord (−) / Sud (+), Ouest (−) / Est (+). Une saisie exprimée dans une autre
+/// convention est convertie avec [`SignConvention::to_standard`].
+///
+/// Sa forme textuelle indique les directions positives : `"S+/E+"` pour la
+/// convention standard, `"N+/E+"` pour compter positivement vers le Nord.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::SignConvention;
+///
+/// let convention = SignConvention::parse_str("N+/E+").unwrap();
+/// // Obus tombé 50 m au Nord : saisi +50, soit -50 en convention standard
+/// assert_eq!(convention.to_standard(50.0, 30.0), (-50.0, 30.0));
+/// assert_eq!(SignConvention::default().as_string(), "S+/E+");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SignConvention {
+    /// `true` si une déviation vers le Nord est positive
+    pub north_positive: bool,
+    /// `true` si une déviation vers l'Est est positive
+    pub east_positive: bool,
+}
+
+impl Default for SignConvention {
+    fn default() -> Self {
+        SignConvention {
+            north_positive: false,
+            east_positive: true,
+        }
+    }
+}
+
+impl SignConvention {
+    /// Convertit une déviation saisie dans cette convention vers la
+    /// convention standard.
+    ///
+    /// La conversion est sa propre inverse : elle sert aussi à exprimer une
+    /// déviation standard dans cette convention.
+    pub fn to_standard(&self, vertical_m: f64, horizontal_m: f64) -> (f64, f64) {
+        let v = if self.north_positive {
+            -vertical_m
+        } else {
+            vertical_m
+        };
+        let h = if self.east_positive {
+            horizontal_m
+        } else {
+            -horizontal_m
+        };
+        (v, h)
+    }
+
+    /// Retourne la forme textuelle de la convention (ex: `"S+/E+"`).
+    pub fn as_string(&self) -> String {
+        format!(
+            "{}+/{}+",
+            if self.north_positive { "N" } else { "S" },
+            if self.east_positive { "E" } else { "W" }
+        )
+    }
+
+    /// Retourne un libellé lisible (ex: `"Nord(-)/Sud(+) Ouest(-)/Est(+)"`).
+    pub fn label(&self) -> &'static str {
+        match (self.north_positive, self.east_positive) {
+            (false, true) => "Nord(-)/Sud(+) Ouest(-)/Est(+)",
+            (true, true) => "Sud(-)/Nord(+) Ouest(-)/Est(+)",
+            (false, false) => "Nord(-)/Sud(+) Est(-)/Ouest(+)",
+            (true, false) => "Sud(-)/Nord(+) Est(-)/Ouest(+)",
+        }
+    }
+
+    /// Parse une convention à partir de ses directions positives.
+    ///
+    /// Accepte `"S+/E+"`, `"N+E+"`, `"n+ w+"`... (une direction Nord/Sud et une
+    /// direction Est/Ouest, dans n'importe quel ordre). Retourne `None` si la
+    /// chaîne est invalide.
+    pub fn parse_str(s: &str) -> Option<SignConvention> {
+        let cleaned: String = s
+            .to_uppercase()
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '/' && *c != ',')
+            .collect();
+        let (mut north, mut east) = (None, None);
+        for token in cleaned.split_inclusive('+') {
+            match token {
+                "N+" if north.is_none() => north = Some(true),
+                "S+" if north.is_none() => north = Some(false),
+                "E+" if east.is_none() => east = Some(true),
+                "W+" | "O+" if east.is_none() => east = Some(false),
+                _ => return None,
+            }
+        }
+        Some(SignConvention {
+            north_positive: north?,
+            east_positive: east?,
+        })
+    }
+}
+
+impl From<SignConvention> for String {
+    fn from(c: SignConvention) -> Self {
+        c.as_string()
+    }
+}
+
+impl TryFrom<String> for SignConvention {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        SignConvention::parse_str(&s)
+            .ok_or_else(|| format!("invalid sign convention '{}' (expected e.g. S+/E+)", s))
+    }
+}
+
+/// Trace d'une correction appliquée sur place à une cible.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CorrectionRecord {
+    /// Déviation verticale observée en mètres (Nord négatif / Sud positif)
+    pub vertical_m: f64,
+    /// Déviation horizontale observée en mètres (Ouest négatif / Est positif)
+    pub horizontal_m: f64,
+    /// Coordonnée X avant correction
+    pub from_x: f64,
+    /// Coordonnée Y avant correction
+    pub from_y: f64,
+    /// Coordonnée X après correction
+    pub to_x: f64,
+    /// Coordonnée Y après correction
+    pub to_y: f64,
+}
+
+/// Calcule les coordonnées corrigées à partir d'une déviation observée.
+///
+/// La correction est l'opposé de la déviation.
+fn corrected_coordinates(x: f64, y: f64, vertical_m: f64, horizontal_m: f64) -> (f64, f64) {
+    // If shell landed North of target (negative vertical), we need to move target South (add to Y)
+    // If shell landed East of target (positive horizontal), we need to move target West (subtract from X)
+    (x - horizontal_m, y - vertical_m)
+}
+
+/// Applique une correction à une position de cible basée sur la déviation observée.
+///
+/// Lorsqu'un tir dévie de sa cible, cette fonction calcule la nouvelle position
+/// corrigée de la cible pour compenser la déviation.
+///
+/// # Convention de signes
+///
+/// - `vertical_m` : Nord (négatif) / Sud (positif)
+/// - `horizontal_m` : Ouest (négatif) / Est (positif)
+///
+/// # Arguments
+///
+/// * `target` - Position de cible originale
+/// * `vertical_m` - Déviation verticale observée en mètres
+/// * `horizontal_m` - Déviation horizontale observée en mètres
+///
+/// # Retourne
+///
+/// Une nouvelle [`TargetPosition`] avec les coordonnées corrigées.
+/// Le nom de la cible corrigée est suffixé par `_C`.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::{AmmoKind, TargetPosition, TargetType, apply_correction};
+///
+/// let target = TargetPosition::new("T1".to_string(), 100.0, 500.0, 300.0, TargetType::Infanterie, AmmoKind::He);
+///
+/// // L'obus est tombé 50m au Nord et 30m à l'Est de la cible
+/// let corrected = apply_correction(&target, -50.0, 30.0);
+///
+/// assert_eq!(corrected.name, "T1_C");
+/// assert_eq!(corrected.x, 470.0);  // 500 - 30 (compense vers l'Ouest)
+/// assert_eq!(corrected.y, 350.0);  // 300 - (-50) (compense vers le Sud)
+/// ```
+pub fn apply_correction(
+    target: &TargetPosition,
+    vertical_m: f64,
+    horizontal_m: f64,
+) -> TargetPosition {
+    let (corrected_x, corrected_y) =
+        corrected_coordinates(target.x, target.y, vertical_m, horizontal_m);
+
+    let corrected_name = if target.name.ends_with("_C") {
+        target.name.clone()
+    } else {
+        format!("{}_C", target.name)
+    };
+
+    let mut corrected = TargetPosition::new(
+        corrected_name,
+        target.elevation,
+        corrected_x,
+        corrected_y,
+        target.target_type,
+        target.ammo_type,
+    );
+    corrected.elevation_source = target.elevation_source;
+    corrected
+}
+
+/// Applique une correction directement sur la cible, sans créer de copie `_C`.
+///
+/// Les coordonnées de la cible sont modifiées et le décalage est ajouté à
+/// son historique de corrections. Même convention de signes que [`apply_correction`].
+///
+/// # Retourne
+///
+/// L'enregistrement de correction ajouté à l'historique.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::{AmmoKind, TargetPosition, TargetType, apply_correction_in_place};
+///
+/// let mut target = TargetPosition::new("T1".to_string(), 100.0, 500.0, 300.0, TargetType::Infanterie, AmmoKind::He);
+/// let record = apply_correction_in_place(&mut target, -50.0, 30.0);
+///
+/// assert_eq!(target.name, "T1");
+/// assert_eq!((target.x, target.y), (470.0, 350.0));
+/// assert_eq!((record.from_x, record.from_y), (500.0, 300.0));
+/// assert_eq!(target.corrections.len(), 1);
+/// ```
+pub fn apply_correction_in_place(
+    target: &mut TargetPosition,
+    vertical_m: f64,
+    horizontal_m: f64,
+) -> CorrectionRecord {
+    let (to_x, to_y) = corrected_coordinates(target.x, target.y, vertical_m, horizontal_m);
+
+    let record = CorrectionRecord {
+        vertical_m,
+        horizontal_m,
+        from_x: target.x,
+        from_y: target.y,
+        to_x,
+        to_y,
+    };
+
+    target.x = to_x;
+    target.y = to_y;
+    target.corrections.push(record.clone());
+    record
+}
+
+/// Correction exprimée en données de tir (pièce) plutôt qu'en déplacement de cible.
+///
+/// Correspond à la procédure réglementaire de réglage : l'observateur annonce la
+/// déviation, la pièce corrige sa dérive et son élévation sans déplacer le point visé.
+///
+/// # Convention de signes
+///
+/// - `range_correction_m` : positif = allonger le tir
+/// - `lateral_correction_m` / `deflection_mil` : positif = corriger vers la droite
+/// - `elevation_mil` : positif = monter l'élévation (raccourcit le tir)
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FiringDataCorrection {
+    /// Correction en portée le long de la ligne pièce-but (mètres)
+    pub range_correction_m: f64,
+    /// Correction latérale perpendiculaire à la ligne pièce-but (mètres)
+    pub lateral_correction_m: f64,
+    /// Correction de direction en millièmes
+    pub deflection_mil: f64,
+    /// Correction d'élévation en millièmes, `None` si la portée sort de la table
+    pub elevation_mil: Option<f64>,
+}
+
+/// Calcule la correction de données de tir à partir d'une déviation observée.
+///
+/// La déviation (même convention que [`apply_correction`]) est projetée sur la
+/// ligne pièce-but ; la correction en portée est convertie en millièmes
+/// d'élévation via la pente locale de la table balistique.
+///
+/// # Arguments
+///
+/// * `mortar` - Position du mortier
+/// * `target` - Position de la cible visée
+/// * `table` - Table balistique de la munition/anneau employé
+/// * `vertical_m` - Déviation observée Nord (négatif) / Sud (positif)
+/// * `horizontal_m` - Déviation observée Ouest (négatif) / Est (positif)
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::*;
+///
+/// let table = BallisticTable {
+///     points: vec![
+///         BallisticPoint { range_m: 0.0, elev_mil: 1400.0, time_flight_s: None, delta_elev_per_100m_mil: None },
+///         BallisticPoint { range_m: 1000.0, elev_mil: 900.0, time_flight_s: None, delta_elev_per_100m_mil: None },
+///     ],
+///     ..Default::default()
+/// };
+/// let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+/// let target = TargetPosition::new("T1".into(), 0.0, 0.0, 500.0, TargetType::Infanterie, AmmoKind::He);
+///
+/// // Tir plein Nord, l'obus tombe 50m trop court (au Sud de la cible)
+/// let c = firing_data_correction(&mortar, &target, &table, 50.0, 0.0);
+/// assert!((c.range_correction_m - 50.0).abs() < 1e-9);
+/// assert!((c.elevation_mil.unwrap() + 25.0).abs() < 1e-9); // descendre de 25 mils
+/// ```
+pub fn firing_data_correction(
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    table: &BallisticTable,
+    vertical_m: f64,
+    horizontal_m: f64,
+) -> FiringDataCorrection {
+    let mortar_pos = mortar.as_position();
+    let target_pos = target.as_position();
+    let distance_m = mortar_pos.distance_to(&target_pos);
+    let azimuth = mortar_pos.azimuth_to(&target_pos).to_radians();
+
+    // Deviation in (east, north); vertical is North negative / South positive
+    let east = horizontal_m;
+    let north = -vertical_m;
+
+    let range_deviation = east * azimuth.sin() + north * azimuth.cos();
+    let lateral_deviation = east * azimuth.cos() - north * azimuth.sin();
+
+    let range_correction_m = -range_deviation;
+    let lateral_correction_m = -lateral_deviation;
+
+    let deflection_mil = if distance_m > 0.0 {
+        mils::rad_to_mil(lateral_correction_m.atan2(distance_m))
+    } else {
+        0.0
+    };
+    // Pente en mils par mètre de table : portées converties selon le rendement des charges
+    let elevation_mil = table
+        .slope_at(mortar.table_range(distance_m))
+        .map(|slope| slope * mortar.table_range(range_correction_m));
+
+    FiringDataCorrection {
+        range_correction_m,
+        lateral_correction_m,
+        deflection_mil,
+        elevation_mil,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn ammo_kind_roundtrip() {
+        for &k in AmmoKind::all() {
+            let s = k.as_str();
+            let parsed = AmmoKind::parse_str(s).expect("parse should succeed");
+            assert_eq!(parsed, k);
+            assert_eq!(AmmoKind::parse_str(k.designation()), Some(k));
+            for alias in k.aliases() {
+                assert_eq!(AmmoKind::parse_str(alias), Some(k));
+            }
+        }
+        assert_eq!(AmmoKind::parse_str(" m853a1 "), Some(AmmoKind::Flare));
+        assert_eq!(AmmoKind::parse_str("high-explosive"), Some(AmmoKind::He));
+    }
+
+    #[test]
+    fn target_type_roundtrip_and_suggested_ammo() {
+        for &t in TargetType::all() {
+            let s = t.as_str();
+            let parsed = TargetType::parse_str(s).expect("parse should succeed");
+            assert_eq!(parsed, t);
+        }
+        assert_eq!(TargetType::Infanterie.suggested_ammo(), AmmoKind::He);
+        assert_eq!(TargetType::Vehicule.suggested_ammo(), AmmoKind::He);
+        assert_eq!(TargetType::Soutien.suggested_ammo(), AmmoKind::Smoke);
+    }
+
+    #[test]
+    fn position_examples_hold() {
+        let p1 = Position::new("A".to_string(), 0.0, 0.0, 0.0);
+        let p2 = Position::new("B".to_string(), 0.0, 300.0, 400.0);
+        assert_eq!(p1.distance_to(&p2), 500.0);
+
+        let east = Position::new("E".to_string(), 0.0, 100.0, 0.0);
+        let az = p1.azimuth_to(&east);
+        assert!((az - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn calculate_dispersion_matches_doc() {
+        let d1 = calculate_dispersion(39.0, 105.0, 100.0);
+        assert!((d1 - 48.75).abs() < 0.01);
+
+        let d2 = calculate_dispersion(39.0, 90.0, 100.0);
+        assert!((d2 - 35.1).abs() < 0.01);
+    }
+
+    #[test]
+    fn ballistic_table_interpolation_and_bounds() {
+        let table = BallisticTable {
+            points: vec![
+                BallisticPoint {
+                    range_m: 0.0,
+                    elev_mil: 1000.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+                BallisticPoint {
+                    range_m: 100.0,
+                    elev_mil: 900.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(table.elev_at(0.0), Some(1000.0));
+        assert_eq!(table.elev_at(100.0), Some(900.0));
+        let mid = table.elev_at(50.0).unwrap();
+        assert!((mid - 950.0).abs() < 1e-6);
+        assert_eq!(table.elev_at(-10.0), None);
+        assert_eq!(table.elev_at(150.0), None);
+    }
+
+    #[test]
+    fn apply_correction_example() {
+        let t = TargetPosition::new(
+            "T1".to_string(),
+            100.0,
+            500.0,
+            300.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+
+        let corrected = apply_correction(&t, -50.0, 30.0);
+
+        assert_eq!(corrected.name, "T1_C");
+        assert_eq!(corrected.x, 470.0);
+        assert_eq!(corrected.y, 350.0);
+    }
+
+    #[test]
+    fn sign_convention_parses_and_converts() {
+        let c = SignConvention::parse_str("n+ w+").unwrap();
+        assert_eq!(c.as_string(), "N+/W+");
+        assert_eq!(c.to_standard(20.0, 10.0), (-20.0, -10.0));
+        assert_eq!(
+            SignConvention::parse_str("E+/S+"),
+            Some(SignConvention::default())
+        );
+        assert!(SignConvention::parse_str("N+").is_none());
+        assert!(SignConvention::parse_str("N+S+").is_none());
+        assert!(SignConvention::parse_str("N-/E+").is_none());
+
+        let json = serde_json::to_string(&c).unwrap();
+        assert_eq!(json, "\"N+/W+\"");
+        assert!(serde_json::from_str::<SignConvention>("\"X+/E+\"").is_err());
+    }
+
+    #[test]
+    fn apply_correction_in_place_records_history() {
+        let mut t = TargetPosition::new(
+            "T1".to_string(),
+            100.0,
+            500.0,
+            300.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+
+        apply_correction_in_place(&mut t, -50.0, 30.0);
+        apply_correction_in_place(&mut t, 10.0, -10.0);
+
+        assert_eq!(t.name, "T1");
+        assert_eq!(t.x, 480.0);
+        assert_eq!(t.y, 340.0);
+        assert_eq!(t.corrections.len(), 2);
+        assert_eq!(t.corrections[1].from_x, 470.0);
+        assert_eq!(t.corrections[1].to_y, 340.0);
+    }
+
+    #[test]
+    fn matches_intel_filters_on_reliability_and_age() {
+        let mut t = TargetPosition::new(
+            "T1".to_string(),
+            0.0,
+            0.0,
+            0.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        assert!(t.matches_intel(None, None, 1000));
+        assert!(!t.matches_intel(Some(Reliability::Doubtful), None, 1000));
+
+        t.reliability = Some(Reliability::Probable);
+        t.last_observed = Some(400);
+        assert!(t.matches_intel(Some(Reliability::Possible), None, 1000));
+        assert!(!t.matches_intel(Some(Reliability::Confirmed), None, 1000));
+        assert!(t.matches_intel(None, Some(600), 1000));
+        assert!(!t.matches_intel(None, Some(599), 1000));
+    }
+
+    #[test]
+    fn staleness_depends_on_target_mobility() {
+        let mut infantry = TargetPosition::new(
+            "T1".to_string(),
+            0.0,
+            0.0,
+            0.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        infantry.last_observed = Some(0);
+        let mut support = infantry.clone();
+        support.target_type = TargetType::Soutien;
+
+        // Une demi-vie d'infanterie : 50 %, pas encore périmée
+        let s = infantry.staleness(900).unwrap();
+        assert!((s.score - 0.5).abs() < 1e-9);
+        assert!(!s.stale);
+        assert!(infantry.staleness(1800).unwrap().stale);
+
+        // Une position de soutien n'est jamais signalée, même ancienne
+        let s = support.staleness(1800).unwrap();
+        assert!(s.score < 0.2);
+        assert!(!support.staleness(86_400).unwrap().stale);
+
+        let target = TargetPosition::new(
+            "T2".to_string(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Vehicule,
+            AmmoKind::He,
+        );
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let mut solution = calculate_solution(&mortar, &target, &BTreeMap::new());
+        attach_staleness(&mut solution, &target, 1000);
+        assert!(solution.staleness.is_none());
+
+        let mut target = target;
+        target.last_observed = Some(0);
+        attach_staleness(&mut solution, &target, 1000);
+        assert!(solution.staleness.unwrap().stale);
+        assert!(solution.warnings[0].contains("Stale intel"));
+    }
+
+    #[test]
+    fn firing_data_correction_projects_on_gun_target_line() {
+        let table = BallisticTable {
+            points: vec![
+                BallisticPoint {
+                    range_m: 0.0,
+                    elev_mil: 1400.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+                BallisticPoint {
+                    range_m: 1000.0,
+                    elev_mil: 900.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+            ],
+            ..Default::default()
+        };
+        // Gun-target line due East
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            500.0,
+            0.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+
+        // Round landed 20m beyond the target and 10m to the North (left of the line)
+        let c = firing_data_correction(&mortar, &target, &table, -10.0, 20.0);
+        assert!((c.range_correction_m + 20.0).abs() < 1e-9);
+        assert!((c.lateral_correction_m - 10.0).abs() < 1e-9);
+        assert!(c.deflection_mil > 0.0);
+        assert!((c.elevation_mil.unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn pchip_interpolation_is_smooth_and_monotone() {
+        let point = |range_m, elev_mil| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: None,
+            delta_elev_per_100m_mil: None,
+        };
+        let linear = BallisticTable {
+            points: vec![
+                point(100.0, 1500.0),
+                point(300.0, 1400.0),
+                point(500.0, 1200.0),
+                point(700.0, 900.0),
+            ],
+            ..Default::default()
+        };
+        let pchip = BallisticTable {
+            interpolation: Interpolation::Pchip,
+            ..linear.clone()
+        };
+
+        // Les lignes de la table sont respectées
+        assert_eq!(pchip.elev_at(300.0), Some(1400.0));
+        assert_eq!(pchip.elev_at(700.0), Some(900.0));
+        assert_eq!(pchip.elev_at(800.0), None);
+
+        // Entre deux lignes, la courbe suit la courbure de la table
+        let (l, p) = (
+            linear.elev_at(400.0).unwrap(),
+            pchip.elev_at(400.0).unwrap(),
+        );
+        assert_eq!(l, 1300.0);
+        assert!(p > l && p < 1400.0, "pchip {p}");
+
+        // Pente continue au passage d'une ligne, contrairement au linéaire
+        let before = pchip.slope_at(299.999).unwrap();
+        let after = pchip.slope_at(300.001).unwrap();
+        assert!((before - after).abs() < 1e-3);
+        assert!(linear.slope_at(299.999).unwrap() != linear.slope_at(300.001).unwrap());
+
+        // Monotone : l'élévation décroît partout
+        let elevs: Vec<f64> = (100..=700)
+            .step_by(10)
+            .map(|r| pchip.elev_at(f64::from(r)).unwrap())
+            .collect();
+        assert!(elevs.windows(2).all(|w| w[1] < w[0]));
+
+        // Portée en double : repli sur l'interpolation linéaire
+        let mut duplicate = pchip.clone();
+        duplicate.points.insert(2, point(300.0, 1390.0));
+        assert!(duplicate.elev_at(400.0).is_some());
+    }
+
+    #[test]
+    fn elev_per_100m_reads_the_table_column() {
+        let point = |range_m, elev_mil, delta| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: None,
+            delta_elev_per_100m_mil: delta,
+        };
+        let table = BallisticTable {
+            points: vec![
+                point(100.0, 1500.0, Some(40.0)),
+                point(200.0, 1460.0, Some(50.0)),
+                point(300.0, 1400.0, None),
+            ],
+            ..Default::default()
+        };
+
+        // Colonne interpolée entre deux lignes qui la fournissent
+        assert_eq!(table.elev_per_100m_at(150.0), Some(45.0));
+        // Repli sur la pente de la table sinon
+        assert_eq!(table.elev_per_100m_at(250.0), Some(60.0));
+        assert_eq!(table.elev_per_100m_at(400.0), None);
+    }
+
+    #[test]
+    fn repair_drops_inconsistent_rows() {
+        let point = |range_m, elev_mil| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: None,
+            delta_elev_per_100m_mil: None,
+        };
+        let mut table = BallisticTable {
+            points: vec![
+                point(100.0, 1400.0),
+                point(200.0, 1450.0),
+                point(300.0, 1200.0),
+                point(300.0, 1190.0),
+                point(400.0, 1000.0),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            table.validate().issues,
+            vec![
+                TableIssue::NonMonotonic {
+                    range_m: 200.0,
+                    elev_mil: 1450.0,
+                    previous_elev_mil: 1400.0,
+                },
+                TableIssue::DuplicateRange { range_m: 300.0 },
+            ]
+        );
+
+        let dropped = table.repair();
+        let dropped: Vec<(f64, f64)> = dropped.iter().map(|p| (p.range_m, p.elev_mil)).collect();
+        assert_eq!(dropped, vec![(200.0, 1450.0), (300.0, 1190.0)]);
+        assert!(table.validate().issues.is_empty());
+        assert_eq!(table.points.len(), 3);
+    }
+
+    #[test]
+    fn validate_reports_coverage_and_gaps() {
+        let point = |range_m: f64| BallisticPoint {
+            range_m,
+            elev_mil: 1500.0 - range_m,
+            time_flight_s: None,
+            delta_elev_per_100m_mil: None,
+        };
+        let table = BallisticTable {
+            points: [100.0, 150.0, 200.0, 250.0, 500.0, 550.0]
+                .into_iter()
+                .map(point)
+                .collect(),
+            ..Default::default()
+        };
+
+        let report = table.validate();
+        assert_eq!(report.rows, 6);
+        assert_eq!(report.min_range_m, Some(100.0));
+        assert_eq!(report.max_range_m, Some(550.0));
+        assert_eq!(report.median_step_m, Some(50.0));
+        assert_eq!(
+            report.gaps,
+            vec![RangeGap {
+                from_m: 250.0,
+                to_m: 500.0
+            }]
+        );
+        assert!(report.issues.is_empty());
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn relay_data_compares_azimuths_with_the_previous_position() {
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        assert!(mortar.relay_data(std::slice::from_ref(&target)).is_empty());
+
+        // Déplacement de 1000 m vers l'Est : la cible passe de 0 à 5600 mils
+        mortar.move_to(0.0, 1000.0, 0.0, 100);
+        let relay = mortar.relay_data(&[target]);
+        assert!((relay[0].previous_azimuth_mil - 0.0).abs() < 1e-9);
+        assert!((relay[0].azimuth_mil - 5600.0).abs() < 1e-9);
+        assert!((relay[0].azimuth_shift_mil + 800.0).abs() < 1e-9);
+        assert!((relay[0].distance_m - 1000.0 * 2f64.sqrt()).abs() < 1e-9);
+        assert_eq!(mortar.displacements.len(), 1);
+    }
+
+    #[test]
+    fn data_packs_load_like_the_data_directory() {
+        let data = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let mut files: Vec<(String, Vec<u8>)> = vec![(
+            "data/metrics.json".into(),
+            std::fs::read(data.join("metrics.json")).unwrap(),
+        )];
+        for info in ammo::builtin_ammo() {
+            let dir = &info.directory;
+            for entry in std::fs::read_dir(data.join(dir)).unwrap() {
+                let path = entry.unwrap().path();
+                let name = format!(
+                    "data/{}/{}",
+                    dir,
+                    path.file_name().unwrap().to_string_lossy()
+                );
+                files.push((name, std::fs::read(path).unwrap()));
+            }
+        }
+        let entries: Vec<archive::ZipEntry> = files
+            .iter()
+            .map(|(name, bytes)| archive::ZipEntry::file(name, bytes))
+            .collect();
+        let pack = std::env::temp_dir().join(format!("mortar_pack_{}.zip", std::process::id()));
+        std::fs::write(&pack, archive::write_zip(&entries).unwrap()).unwrap();
+
+        let (tables, report) = load_ballistics_checked(&data, false).unwrap();
+        let packed = load_ballistics_from_archive(&pack).unwrap();
+        let metrics = load_metrics_from(&pack).unwrap();
+        std::fs::remove_file(&pack).unwrap();
+
+        assert_eq!(packed.len(), tables.len());
+        for (key, table) in &tables {
+            assert_eq!(packed[key].points.len(), table.points.len(), "{:?}", key);
+        }
+        assert_eq!(report.failed().count(), 0);
+        assert_eq!(
+            metrics.dispersions,
+            load_metrics_from(&data).unwrap().dispersions
+        );
+    }
+
+    #[test]
+    fn declared_ammo_loads_from_the_manifest_and_gets_solutions() {
+        let base = std::env::temp_dir().join(format!("mortar_ammo_{}", std::process::id()));
+        let repo = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        std::fs::create_dir_all(base.join("M720")).unwrap();
+        std::fs::copy(repo.join("metrics.json"), base.join("metrics.json")).unwrap();
+        std::fs::copy(
+            repo.join("HE/M821_HE_2R.csv"),
+            base.join("M720/M720_HE_2R.csv"),
+        )
+        .unwrap();
+        std::fs::write(
+            base.join("ammo.json"),
+            r#"{ "ammo": [{ "name": "TEST_M720_HE", "designation": "TEST_M720",
+                 "directory": "M720", "file_prefix": "M720_HE", "rings": [2] }] }"#,
+        )
+        .unwrap();
+
+        let (tables, report) = load_ballistics_checked(&base, false).unwrap();
+        let registry = AmmoRegistry::load(&base).unwrap();
+        std::fs::remove_dir_all(&base).unwrap();
+        let m720 = registry.parse_kind("test m720").unwrap();
+        assert!(matches!(m720, AmmoKind::Custom(_)));
+        assert!(tables.contains_key(&(WeaponSystem::M224, m720, 2)));
+        // Tables livrées absentes de ce répertoire : seule M720 est chargée
+        assert_eq!(tables.len(), 1);
+        assert_eq!(report.failed().count(), report.files.len() - 1);
+        assert_eq!(
+            registry.file_name(WeaponSystem::M224, m720, 2),
+            "M720/M720_HE_2R.csv"
+        );
+
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target =
+            TargetPosition::new("T1".into(), 0.0, 0.0, 800.0, TargetType::Infanterie, m720);
+        let solution =
+            calculate_solution_with_dispersion(&mortar, &target, &tables, &BTreeMap::new());
+        assert!(solution.solutions[&m720].get(2).is_some());
+        assert_eq!(
+            solution.solutions[&m720].get(2),
+            tables[&(WeaponSystem::M224, m720, 2)].elev_at(800.0)
+        );
+    }
+
+    #[test]
+    fn exported_tables_reload_identically() {
+        let base = std::env::temp_dir().join(format!("mortar_export_{}", std::process::id()));
+        let repo = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let tables = load_ballistics_from(&repo).unwrap();
+        let registry = AmmoRegistry::default();
+        let written = export_tables(&tables, &registry, &base, None).unwrap();
+        std::fs::copy(repo.join("metrics.json"), base.join("metrics.json")).unwrap();
+        let reloaded = load_ballistics_from(&base);
+        let resampled = export_tables(&tables, &registry, &base.join("dense"), Some(DENSE_STEP_M));
+        let dense = read_csv_points(base.join("dense").join(&written[0]));
+        std::fs::remove_dir_all(&base).unwrap();
+
+        assert_eq!(written.len(), tables.len());
+        assert!(written.contains(&"HE/M821_HE_2R.csv".to_string()));
+        let reloaded = reloaded.unwrap();
+        assert_eq!(reloaded.len(), tables.len());
+        for (key, table) in &tables {
+            assert_eq!(reloaded[key].points, table.points, "{:?}", key);
+        }
+        assert_eq!(resampled.unwrap(), written);
+        // Rééchantillonnée au mètre : la courbe que le calcul interpole
+        let first = tables.values().next().unwrap();
+        let dense = dense.unwrap();
+        assert_eq!(dense.len(), first.curve(DENSE_STEP_M).len());
+        assert!(dense.iter().all(|p| p.delta_elev_per_100m_mil.is_some()));
+    }
+
+    #[test]
+    fn overlays_replace_single_tables_of_the_data_directory() {
+        let data = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let overlay = std::env::temp_dir().join(format!("mortar_overlay_{}", std::process::id()));
+        std::fs::create_dir_all(overlay.join("HE")).unwrap();
+        std::fs::write(
+            overlay.join("HE/M821_HE_2R.csv"),
+            "range_m,elev_mil\n100,1400\n1100,900\n",
+        )
+        .unwrap();
+        // Table présente mais invalide : signalée, la table livrée est conservée
+        std::fs::write(
+            overlay.join("HE/M821_HE_3R.csv"),
+            "range_m,elev_mil\n100,abc\n",
+        )
+        .unwrap();
+
+        let (base, _) = load_ballistics_checked(&data, false).unwrap();
+        let overlays = [overlay.clone()];
+        let (tables, report) = load_ballistics_layered(&data, &overlays, false, false).unwrap();
+        let metrics_layer = data_layer(&data, &overlays, "metrics.json").to_path_buf();
+        std::fs::remove_dir_all(&overlay).unwrap();
+
+        assert_eq!(tables.len(), base.len());
+        let he2 = &tables[&(WeaponSystem::M224, AmmoKind::He, 2)];
+        assert_eq!(he2.points.len(), 2);
+        assert_eq!(
+            tables[&(WeaponSystem::M224, AmmoKind::He, 3)].points,
+            base[&(WeaponSystem::M224, AmmoKind::He, 3)].points
+        );
+        let failed: Vec<_> = report.failed().collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].ring, 3);
+        assert!(report
+            .files
+            .iter()
+            .any(|f| f.loaded && f.ring == 2 && f.path.starts_with(&*overlay.to_string_lossy())));
+        assert_eq!(metrics_layer, data);
+    }
+
+    #[test]
+    fn dispersion_curves_grow_with_range() {
+        let dir = std::env::temp_dir().join(format!("mortar_dispersion_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("dispersion")).unwrap();
+        std::fs::write(
+            dir.join("metrics.json"),
+            r#"{ "dispersion": { "HE": { "1R": 20, "2R": 39 } } }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("dispersion/HE_2R.csv"),
+            "range_m,range_pe_m,deflection_pe_m\n500,20,10\n1500,60,20\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("dispersion/SMOKE_3R.csv"),
+            "range_m,dispersion_m\n800,30\n",
+        )
+        .unwrap();
+        let metrics = load_metrics_from(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let he2 = &metrics.dispersions[&(AmmoKind::He, 2)];
+        assert_eq!(he2.flat, Dispersion::circular(39.0));
+        assert_eq!(
+            he2.at_range(1000.0),
+            Dispersion {
+                range_pe_m: 40.0,
+                deflection_pe_m: 15.0
+            }
+        );
+        // Sans courbe, la valeur fixe ; sans valeur fixe, la courbe seule
+        assert_eq!(
+            metrics.dispersions[&(AmmoKind::He, 1)].at_range(1000.0),
+            Dispersion::circular(20.0)
+        );
+        assert_eq!(
+            metrics.dispersions[&(AmmoKind::Smoke, 3)].flat,
+            Dispersion::circular(30.0)
+        );
+
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1250.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let sol = calculate_solution_with_dispersion(
+            &mortar,
+            &target,
+            &BallisticTables::new(),
+            &metrics.dispersions,
+        );
+        assert_eq!(sol.dispersions[&AmmoKind::He].get(2), Some(50.0));
+        assert_eq!(sol.dispersions[&AmmoKind::He].get(1), Some(20.0));
+    }
+
+    #[test]
+    fn weapon_systems_read_their_own_tables() {
+        let base = std::env::temp_dir().join(format!("mortar_weapons_{}", std::process::id()));
+        let repo = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        std::fs::create_dir_all(base.join("X853")).unwrap();
+        std::fs::create_dir_all(base.join("M252/X853")).unwrap();
+        std::fs::copy(
+            repo.join("HE/M821_HE_2R.csv"),
+            base.join("X853/X853_2R.csv"),
+        )
+        .unwrap();
+        std::fs::copy(
+            repo.join("HE/M821_HE_4R.csv"),
+            base.join("M252/X853/X853_2R.csv"),
+        )
+        .unwrap();
+        std::fs::write(
+            base.join("ammo.json"),
+            r#"{ "ammo": [{ "name": "TEST_X853", "directory": "X853", "file_prefix": "X853",
+                 "rings": [2], "calibers": ["60mm", "81mm"] }] }"#,
+        )
+        .unwrap();
+
+        let (tables, report) = load_ballistics_checked(&base, false).unwrap();
+        let (scanned, _) = scan_ballistics_checked(&base, false).unwrap();
+        let registry = AmmoRegistry::load(&base).unwrap();
+        std::fs::remove_dir_all(&base).unwrap();
+        let x853 = registry.parse_kind("test_x853").unwrap();
+        let m224 = (WeaponSystem::M224, x853, 2);
+        let m252 = (WeaponSystem::M252, x853, 2);
+        assert!(tables.contains_key(&m224) && tables.contains_key(&m252));
+        assert!(scanned.contains_key(&m224) && scanned.contains_key(&m252));
+        // Le M120 ne tire pas cette munition : aucune table attendue
+        assert!(report.files.iter().all(|f| f.weapon != WeaponSystem::M120));
+        assert_eq!(
+            registry.file_name(WeaponSystem::M252, x853, 2),
+            "M252/X853/X853_2R.csv"
+        );
+
+        // Chaque pièce lit les tables de son système
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target =
+            TargetPosition::new("T1".into(), 0.0, 0.0, 800.0, TargetType::Infanterie, x853);
+        let light = calculate_solution_with_dispersion(&mortar, &target, &tables, &BTreeMap::new());
+        mortar.weapon = WeaponSystem::M252;
+        assert!(mortar.check_ammo(x853, &registry).is_ok());
+        // Munition inconnue d'un autre registre : aucune pièce ne la tire
+        assert!(mortar.check_ammo(x853, &AmmoRegistry::default()).is_err());
+        let medium =
+            calculate_solution_with_dispersion(&mortar, &target, &tables, &BTreeMap::new());
+        assert_eq!(medium.weapon, WeaponSystem::M252);
+        assert_eq!(light.solutions[&x853].get(2), tables[&m224].elev_at(800.0));
+        assert_eq!(medium.solutions[&x853].get(2), tables[&m252].elev_at(800.0));
+        assert_ne!(
+            light.solutions[&x853].get(2),
+            medium.solutions[&x853].get(2)
+        );
+    }
+
+    #[test]
+    fn weapon_systems_parse_and_replace_the_saved_caliber() {
+        assert_eq!(WeaponSystem::parse_str("M120"), Some(WeaponSystem::M120));
+        assert_eq!(WeaponSystem::parse_str("81"), Some(WeaponSystem::M252));
+        assert_eq!(WeaponSystem::M252.caliber(), Caliber::Mm81);
+
+        // Sauvegardes antérieures : le calibre donne le système
+        let json = r#"{ "name": "M1", "elevation": 0.0, "x": 0.0, "y": 0.0, "caliber": "120mm" }"#;
+        let mortar: MortarPosition = serde_json::from_str(json).unwrap();
+        assert_eq!(mortar.weapon, WeaponSystem::M120);
+        let saved = serde_json::to_value(&mortar).unwrap();
+        assert_eq!(saved["weapon"], "M120");
+    }
+
+    #[test]
+    fn scanned_tables_accept_any_file_name_with_a_ring() {
+        let files = scan_table_files(
+            &AmmoRegistry::default(),
+            [
+                "HE/M821_HE_0R.csv",
+                "HE/smoothed/M821_HE_1R_smoothed_1m.csv",
+                "HE/M821_HE_2R.csv",
+                "HE/old_2R.CSV",
+                "HE/HE_3r.csv",
+                "HE/M821_HE_4R.txt",
+                "HE/M821_HE_7R.csv",
+                "HEAT/M821_HE_4R.csv",
+                "metrics.json",
+            ],
+        );
+        let he: Vec<(Ring, &str, Option<&str>)> = files
+            .iter()
+            .filter(|f| f.ammo == AmmoKind::He)
+            .map(|f| (f.ring, f.name.as_str(), f.error.as_deref()))
+            .collect();
+        assert_eq!(
+            he,
+            vec![
+                (7, "HE/M821_HE_7R.csv", Some("ring 7R out of range (0-4R)")),
+                (0, "HE/M821_HE_0R.csv", None),
+                (1, "HE/smoothed/M821_HE_1R_smoothed_1m.csv", None),
+                (
+                    2,
+                    "HE",
+                    Some("several 2R tables, keep only one: HE/M821_HE_2R.csv, HE/old_2R.CSV")
+                ),
+                (3, "HE/HE_3r.csv", None),
+                (4, "HE", Some("no 4R table found")),
+            ]
+        );
+
+        // Les fichiers livrés sont trouvés sous leurs noms fixes
+        let data = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let (tables, report) = scan_ballistics_checked(&data, false).unwrap();
+        let fixed: Vec<String> = fixed_table_files(&AmmoRegistry::default())
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        let scanned: Vec<String> = report
+            .files
+            .iter()
+            .map(|f| f.path.strip_prefix(&*data.to_string_lossy()).unwrap()[1..].to_string())
+            .collect();
+        assert_eq!(scanned, fixed);
+        assert_eq!(tables.len(), fixed.len());
+    }
+
+    #[test]
+    fn shipped_tables_are_consistent() {
+        let data = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let (tables, report) = load_ballistics_checked(&data, false).unwrap();
+        assert!(!tables.is_empty());
+        assert!(report.checks.is_empty(), "{:?}", report.checks);
+        assert_eq!(report.failed().count(), 0);
+        assert_eq!(report.files.len(), tables.len());
+        // Chargement parallèle : bilan dans l'ordre des fichiers
+        let order: Vec<(AmmoKind, Ring)> =
+            report.files.iter().map(|f| (f.ammo_type, f.ring)).collect();
+        let expected: Vec<(AmmoKind, Ring)> = ammo::builtin_ammo()
+            .into_iter()
+            .flat_map(|info| info.rings.into_iter().map(move |r| (info.kind, r)))
+            .collect();
+        assert_eq!(order, expected);
+
+        // Répertoire vide : chaque fichier attendu est signalé avec son erreur
+        let (tables, report) = load_ballistics_checked(data.join("absent"), false).unwrap();
+        assert!(tables.is_empty());
+        assert_eq!(report.failed().count(), report.files.len());
+        assert!(report.files.iter().all(|f| f.error.is_some()));
+    }
+
+    #[test]
+    fn dense_tables_match_interpolated_elevations() {
+        let data = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let (tables, _) = load_ballistics_checked(data, false).unwrap();
+        for interpolation in [Interpolation::Linear, Interpolation::Pchip] {
+            for table in tables.values() {
+                let table = BallisticTable {
+                    interpolation,
+                    ..table.clone()
+                };
+                let mut dense = table.clone();
+                dense.densify();
+                assert!(dense.dense.is_some());
+                let (minr, maxr) = table.range_bounds().unwrap();
+                let mut r = minr;
+                while r <= maxr {
+                    let (a, b) = (table.elev_at(r).unwrap(), dense.elev_at(r).unwrap());
+                    assert!((a - b).abs() < 0.05, "{r} m: {a} != {b}");
+                    r += 7.3;
+                }
+                assert_eq!(dense.elev_at(maxr), table.elev_at(maxr));
+                assert_eq!(dense.elev_at(maxr + 1.0), None);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_metrics_validates_schema() {
+        let metrics = parse_metrics(
+            r#"{
+                "version": 1,
+                "dispersion": { "HE": { "0R": 10, "2R": 39 } },
+                "effect_radius": { "HE": { "casualty_m": 35 } },
+                "min_safe_distance": { "HE": 250 },
+                "charge_temperature": {
+                    "default_range_factor_per_c": 0.0008,
+                    "range_factor_per_c": { "HE": { "2R": 0.001 } }
+                },
+                "increments": { "HE": { "0R": 0, "2R": 2 } },
+                "drift": { "HE": { "2R": [[1100, 6], [100, 1]] } }
+            }"#
+            .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            metrics.dispersions[&(AmmoKind::He, 0)].flat.radius_m(),
+            10.0
+        );
+        assert_eq!(metrics.effects[&AmmoKind::He].casualty_m, Some(35.0));
+        assert_eq!(metrics.min_safe_distances[&AmmoKind::He], 250.0);
+        assert_eq!(
+            metrics.dispersion_coefficients,
+            DispersionCoefficients::default()
+        );
+        assert_eq!(metrics.charge_temperature.standard_c, 21.0);
+        assert_eq!(
+            metrics.charge_temperature.factors[&(AmmoKind::He, 2)],
+            0.001
+        );
+        assert_eq!(
+            metrics.charge_temperature.factor(AmmoKind::Smoke, 3),
+            Some(0.0008)
+        );
+        assert_eq!(metrics.increments[&(AmmoKind::He, 2)], 2);
+        assert_eq!(
+            metrics.drift[&(AmmoKind::He, 2)].points,
+            vec![(100.0, 1.0), (1100.0, 6.0)]
+        );
+
+        let invalid = [
+            r#"{ "version": 2, "dispersion": {} }"#,
+            r#"{ "dispersion": { "HEAT": { "0R": 10 } } }"#,
+            r#"{ "dispersion": { "HE": { "X": 10 } } }"#,
+            r#"{ "dispersion": { "HE": { "0R": -1 } } }"#,
+            r#"{ "dispersion": { "HE": { "0R": { "range_pe_m": 10 } } } }"#,
+            r#"{ "dispersion": { "HE": { "0R": { "range_pe_m": 10, "deflection_pe_m": -1 } } } }"#,
+            r#"{ "dispersion": {}, "dispersal": {} }"#,
+            r#"{ "dispersion": {}, "effect_radius": { "HE": { "lethal_m": 5 } } }"#,
+            r#"{ "dispersion": {}, "charge_temperature": { "range_factor_per_c": { "HE": { "2": 0.001 } } } }"#,
+            r#"{ "dispersion": {}, "charge_temperature": { "default_range_factor_per_c": -0.001 } }"#,
+            r#"{ "dispersion": {}, "increments": { "HE": { "2R": -1 } } }"#,
+            r#"{ "dispersion": {}, "drift": { "HE": { "2R": [[100, 1], [100, 2]] } } }"#,
+        ];
+        for json in invalid {
+            assert!(parse_metrics(json.as_bytes()).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn calculate_solution_with_dispersion_populates_struct() {
+        let mut ballistics: BallisticTables = BTreeMap::new();
+        ballistics.insert(
+            (WeaponSystem::M224, AmmoKind::He, 2),
+            BallisticTable {
+                points: vec![
+                    BallisticPoint {
+                        range_m: 0.0,
+                        elev_mil: 1200.0,
+                        time_flight_s: None,
+                        delta_elev_per_100m_mil: None,
+                    },
+                    BallisticPoint {
+                        range_m: 600.0,
+                        elev_mil: 1100.0,
+                        time_flight_s: None,
+                        delta_elev_per_100m_mil: None,
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        let mut dispersions: DispersionTable = BTreeMap::new();
+        dispersions.insert((AmmoKind::He, 2), Dispersion::circular(39.0).into());
 
-    let rings: &[u8] = &[0, 1, 2, 3, 4];
-    let kinds = AmmoKind::all();
+        let mortar = MortarPosition::new("M1".into(), 100.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            50.0,
+            500.0,
+            300.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+
+        let sol = calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
 
-    let mut solutions: BTreeMap<String, BTreeMap<String, Option<f64>>> = BTreeMap::new();
-    let mut dispersions: BTreeMap<String, BTreeMap<String, Option<f64>>> = BTreeMap::new();
+        assert!(sol.distance_m > 0.0);
+        assert!(sol.azimuth_deg >= 0.0 && sol.azimuth_deg <= 360.0);
+        assert_eq!(sol.mortar_ammo, "HE");
+        assert_eq!(sol.target_type, "INFANTERIE");
+        assert_eq!(sol.recommended_ammo, "HE");
+        assert!(sol.solutions.contains_key(&AmmoKind::He));
+        assert!(sol.dispersions.contains_key(&AmmoKind::He));
+        let sel = sol.selected_solution.as_ref().expect("selected_solution");
+        assert_eq!(sel.ammo_type, "HE");
+        assert!(sel.elevations.get(2).is_some());
+        assert!(sel.dispersions.get(2).is_some());
+        assert_eq!(sel.effect_radius_m, None);
+        // 100 mils pour 600 m : 6 m de portée par mil
+        assert!((sel.range_m_per_mil.get(2).unwrap() - 6.0).abs() < 1e-9);
+        assert_eq!(sel.range_m_per_mil.get(1), None);
+        assert!(
+            (sel.lateral_m_per_mil - sol.distance_m * std::f64::consts::TAU / 6400.0).abs() < 1e-9
+        );
 
-    for kind in kinds {
-        let mut ring_solutions: BTreeMap<String, Option<f64>> = BTreeMap::new();
-        let mut ring_dispersions: BTreeMap<String, Option<f64>> = BTreeMap::new();
-        for r in rings {
-            let key = format!("{}R", r);
-            let elev = ballistics
-                .get(&(*kind, *r))
-                .and_then(|t| t.elev_at(distance_m));
-            ring_solutions.insert(key.clone(), elev);
+        // Forme JSON attendue par l'interface web
+        let json = serde_json::to_value(&sol).unwrap();
+        assert!(json["solutions"]["HE"]["2R"].is_f64());
+        assert!(json["solutions"]["SMOKE"]["0R"].is_null());
+        assert!(json["dispersions"]["HE"]["2R"].is_f64());
+        assert!(json["selected_solution"]["elevations"]["2R"].is_f64());
 
-            let disp = dispersion_table
-                .get(&(*kind, *r))
-                .map(|&base| calculate_dispersion(base, mortar.elevation, target.elevation));
-            ring_dispersions.insert(key, disp);
-        }
-        solutions.insert(kind.as_str().to_string(), ring_solutions);
-        dispersions.insert(kind.as_str().to_string(), ring_dispersions);
+        let mut effects = EffectTable::new();
+        effects.insert(
+            AmmoKind::He,
+            EffectRadii {
+                casualty_m: Some(35.0),
+                ..EffectRadii::default()
+            },
+        );
+        let mut sol = sol;
+        attach_effect_radius(&mut sol, &effects);
+        assert_eq!(sol.selected_solution.unwrap().effect_radius_m, Some(35.0));
     }
 
-    // Selected solution based on target's ammo type
-    let selected_ammo = target.ammo_type;
-    let mut selected_elevations: BTreeMap<String, Option<f64>> = BTreeMap::new();
-    let mut selected_dispersions: BTreeMap<String, Option<f64>> = BTreeMap::new();
-    for r in rings {
-        let key = format!("{}R", r);
-        let elev = ballistics
-            .get(&(selected_ammo, *r))
-            .and_then(|t| t.elev_at(distance_m));
-        selected_elevations.insert(key.clone(), elev);
+    #[test]
+    fn builders_validate_positions() {
+        let mortar = MortarPosition::builder()
+            .name("M1")
+            .at(10.0, 20.0)
+            .weapon(WeaponSystem::M252)
+            .exclude_ring(4)
+            .mv_correction_pct(-2.0)
+            .status(MortarStatus::Moving)
+            .build()
+            .unwrap();
+        assert_eq!((mortar.x, mortar.y, mortar.elevation), (10.0, 20.0, 0.0));
+        assert!(!mortar.ring_available(4));
+        assert_eq!(mortar.status, MortarStatus::Moving);
+
+        let base = || MortarPosition::builder().name("M1").at(0.0, 0.0);
+        assert!(base().exclude_ring(2).default_ring(2).build().is_err());
+        assert!(base().mv_correction_pct(-30.0).build().is_err());
+        assert!(base().elevation(f64::NAN).build().is_err());
+        assert!(MortarPosition::builder().at(0.0, 0.0).build().is_err());
 
-        let disp = dispersion_table
-            .get(&(selected_ammo, *r))
-            .map(|&base| calculate_dispersion(base, mortar.elevation, target.elevation));
-        selected_dispersions.insert(key, disp);
+        let target = TargetPosition::builder()
+            .name("T1")
+            .at(0.0, 100.0)
+            .target_type(TargetType::Vehicule)
+            .reliability(Reliability::Probable)
+            .build()
+            .unwrap();
+        assert_eq!(target.ammo_type, AmmoKind::He);
+        assert_eq!(target.reliability, Some(Reliability::Probable));
+        assert!(TargetPosition::builder()
+            .name("T2")
+            .at(f64::INFINITY, 0.0)
+            .build()
+            .is_err());
     }
 
-    let selected_solution = Some(SelectedSolution {
-        ammo_type: selected_ammo.as_str().to_string(),
-        elevations: selected_elevations,
-        dispersions: selected_dispersions,
-    });
+    #[test]
+    fn elliptical_dispersion_is_oriented_along_the_gun_target_line() {
+        let json =
+            r#"{ "dispersion": { "HE": { "2R": { "range_pe_m": 40, "deflection_pe_m": 15 } } } }"#;
+        let metrics = parse_metrics(json.as_bytes()).unwrap();
+        let mut ballistics: BallisticTables = BTreeMap::new();
+        ballistics.insert(
+            (WeaponSystem::M224, AmmoKind::He, 2),
+            BallisticTable {
+                points: vec![
+                    BallisticPoint {
+                        range_m: 0.0,
+                        elev_mil: 1200.0,
+                        time_flight_s: None,
+                        delta_elev_per_100m_mil: None,
+                    },
+                    BallisticPoint {
+                        range_m: 1000.0,
+                        elev_mil: 1000.0,
+                        time_flight_s: None,
+                        delta_elev_per_100m_mil: None,
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        // Cible à l'est, 10 m plus bas : dispersions augmentées de 50 %
+        let mortar = MortarPosition::new("M1".into(), 10.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            500.0,
+            0.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
 
-    FiringSolution {
-        distance_m,
-        azimuth_deg,
-        elevation_diff_m,
-        signed_elevation_diff_m,
-        mortar_ammo: target.ammo_type.as_str().to_string(),
-        target_type: target.target_type.as_str().to_string(),
-        recommended_ammo: target.target_type.suggested_ammo().as_str().to_string(),
-        solutions,
-        dispersions,
-        selected_solution,
-    }
-}
+        let sol =
+            calculate_solution_with_dispersion(&mortar, &target, &ballistics, &metrics.dispersions);
+        let sel = sol.selected_solution.as_ref().unwrap();
+        let ellipse = sel.dispersion_ellipse.as_ref().expect("dispersion ellipse");
+        assert!((ellipse.azimuth_deg - 90.0).abs() < 1e-9);
+        assert!((ellipse.range_pe_m.get(2).unwrap() - 60.0).abs() < 1e-9);
+        assert!((ellipse.deflection_pe_m.get(2).unwrap() - 22.5).abs() < 1e-9);
+        assert_eq!(ellipse.range_pe_m.get(1), None);
+        // Le rayon reste celui du cercle englobant l'ellipse
+        assert!((sel.dispersions.get(2).unwrap() - 60.0).abs() < 1e-9);
 
-// ============================================================================
-// Correction de tir
-// ============================================================================
+        let no_data = calculate_solution(&mortar, &target, &ballistics);
+        assert!(no_data
+            .selected_solution
+            .unwrap()
+            .dispersion_ellipse
+            .is_none());
+    }
 
-/// Applique une correction à une position de cible basée sur la déviation observée.
-///
-/// Lorsqu'un tir dévie de sa cible, cette fonction calcule la nouvelle position
-/// corrigée de la cible pour compenser la déviation.
-///
-/// # Convention de signes
-///
-/// - `vertical_m` : Nord (négatif) / Sud (positif)
-/// - `horizontal_m` : Ouest (négatif) / Est (positif)
-///
-/// # Arguments
-///
-/// * `target` - Position de cible originale
-/// * `vertical_m` - Déviation verticale observée en mètres
-/// * `horizontal_m` - Déviation horizontale observée en mètres
-///
-/// # Retourne
-///
-/// Une nouvelle [`TargetPosition`] avec les coordonnées corrigées.
-/// Le nom de la cible corrigée est suffixé par `_C`.
-///
-/// # Exemple
-///
-/// ```
-/// use mortar::{TargetPosition, TargetType, apply_correction};
-///
-/// let target = TargetPosition::new("T1".to_string(), 100.0, 500.0, 300.0, TargetType::Infanterie);
-///
-/// // L'obus est tombé 50m au Nord et 30m à l'Est de la cible
-/// let corrected = apply_correction(&target, -50.0, 30.0);
-///
-/// assert_eq!(corrected.name, "T1_C");
-/// assert_eq!(corrected.x, 470.0);  // 500 - 30 (compense vers l'Ouest)
-/// assert_eq!(corrected.y, 350.0);  // 300 - (-50) (compense vers le Sud)
-/// ```
-pub fn apply_correction(
-    target: &TargetPosition,
-    vertical_m: f64,
-    horizontal_m: f64,
-) -> TargetPosition {
-    // Correction is the opposite of the deviation
-    // If shell landed North of target (negative vertical), we need to move target South (add to Y)
-    // If shell landed East of target (positive horizontal), we need to move target West (subtract from X)
-    let corrected_x = target.x - horizontal_m;
-    let corrected_y = target.y - vertical_m;
+    #[test]
+    fn dispersion_factor_enlarges_the_table_dispersion() {
+        let mut dispersions: DispersionTable = BTreeMap::new();
+        dispersions.insert((AmmoKind::He, 2), Dispersion::circular(40.0).into());
+        let table = BallisticTable {
+            points: vec![
+                BallisticPoint {
+                    range_m: 0.0,
+                    elev_mil: 1200.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+                BallisticPoint {
+                    range_m: 1000.0,
+                    elev_mil: 1000.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let ballistics = BTreeMap::from([((WeaponSystem::M224, AmmoKind::He, 2), table)]);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let base = || MortarPosition::builder().name("M1").at(0.0, 0.0);
 
-    let corrected_name = if target.name.ends_with("_C") {
-        target.name.clone()
-    } else {
-        format!("{}_C", target.name)
-    };
+        // Tube usé : dispersions des tables x1.5 sur l'anneau 2
+        let worn = base().dispersion_factor(2, 1.5).build().unwrap();
+        let sol = calculate_solution_with_dispersion(&worn, &target, &ballistics, &dispersions);
+        let sel = sol.selected_solution.as_ref().unwrap();
+        assert!((sel.dispersions.get(2).unwrap() - 60.0).abs() < 1e-9);
+        assert_eq!(sel.dispersion_factors.get(2), Some(1.5));
+        assert_eq!(sel.dispersion_factors.get(1), None);
+        assert!(sol.warnings.iter().any(|w| w.contains("2R x1.50")));
 
-    TargetPosition::new(
-        corrected_name,
-        target.elevation,
-        corrected_x,
-        corrected_y,
-        target.target_type,
-        target.ammo_type,
-    )
-}
+        let new = base().build().unwrap();
+        let sol = calculate_solution_with_dispersion(&new, &target, &ballistics, &dispersions);
+        let sel = sol.selected_solution.unwrap();
+        assert!((sel.dispersions.get(2).unwrap() - 40.0).abs() < 1e-9);
+        assert!(sel.dispersion_factors.is_empty());
+        assert!(sol.warnings.is_empty());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::BTreeMap;
+        // Jamais plus précis que les tables
+        assert!(base().dispersion_factor(2, 0.8).build().is_err());
+    }
 
     #[test]
-    fn ammo_kind_roundtrip() {
-        for &k in AmmoKind::all() {
-            let s = k.as_str();
-            let parsed = AmmoKind::parse_str(s).expect("parse should succeed");
-            assert_eq!(parsed, k);
-        }
+    fn drift_is_reported_as_a_separate_azimuth_correction() {
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            600.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let mut sol = calculate_solution_with_dispersion(
+            &mortar,
+            &target,
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+        );
+
+        // Aucune dérive connue : pas de correction
+        attach_drift(&mut sol, &mortar, &DriftTable::new());
+        assert!(sol.drift.is_none());
+
+        let drift = DriftTable::from([(
+            (AmmoKind::He, 2),
+            DriftCurve {
+                points: vec![(100.0, 1.0), (1100.0, 6.0)],
+            },
+        )]);
+        attach_drift(&mut sol, &mortar, &drift);
+        let correction = sol.drift.as_ref().expect("drift");
+        assert_eq!(correction.drift_mil.get(2), Some(3.5));
+        assert_eq!(correction.correction_mil.get(2), Some(-3.5));
+        // Gisement plein Nord corrigé vers la gauche
+        assert_eq!(correction.azimuth_mil.get(2), Some(6396.5));
+        assert_eq!(correction.drift_mil.get(1), None);
+        assert_eq!(sol.azimuth_deg, 0.0);
     }
 
     #[test]
-    fn target_type_roundtrip_and_suggested_ammo() {
-        for &t in TargetType::all() {
-            let s = t.as_str();
-            let parsed = TargetType::parse_str(s).expect("parse should succeed");
-            assert_eq!(parsed, t);
+    fn excluded_rings_are_skipped() {
+        let mut ballistics: BallisticTables = BTreeMap::new();
+        for ring in [1, 2] {
+            ballistics.insert(
+                (WeaponSystem::M224, AmmoKind::He, ring),
+                BallisticTable {
+                    points: vec![
+                        BallisticPoint {
+                            range_m: 0.0,
+                            elev_mil: 1200.0,
+                            time_flight_s: None,
+                            delta_elev_per_100m_mil: None,
+                        },
+                        BallisticPoint {
+                            range_m: 1000.0,
+                            elev_mil: 900.0,
+                            time_flight_s: None,
+                            delta_elev_per_100m_mil: None,
+                        },
+                    ],
+                    ..Default::default()
+                },
+            );
         }
-        assert_eq!(TargetType::Infanterie.suggested_ammo(), AmmoKind::He);
-        assert_eq!(TargetType::Vehicule.suggested_ammo(), AmmoKind::He);
-        assert_eq!(TargetType::Soutien.suggested_ammo(), AmmoKind::Smoke);
-    }
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        mortar.excluded_rings.insert(1);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
 
-    #[test]
-    fn position_examples_hold() {
-        let p1 = Position::new("A".to_string(), 0.0, 0.0, 0.0);
-        let p2 = Position::new("B".to_string(), 0.0, 300.0, 400.0);
-        assert_eq!(p1.distance_to(&p2), 500.0);
+        assert_eq!(
+            available_rings(&ballistics, WeaponSystem::M224, AmmoKind::He, 500.0),
+            vec![1, 2]
+        );
+        assert_eq!(
+            mortar.usable_rings(&ballistics, AmmoKind::He, 500.0),
+            vec![2]
+        );
 
-        let east = Position::new("E".to_string(), 0.0, 100.0, 0.0);
-        let az = p1.azimuth_to(&east);
-        assert!((az - 90.0).abs() < 0.01);
+        let sol = calculate_solution(&mortar, &target, &ballistics);
+        let sel = sol.selected_solution.unwrap();
+        assert_eq!(sel.elevations.get(1), None);
+        assert!(sel.elevations.get(2).is_some());
+        assert_eq!(sel.excluded_rings, vec!["1R".to_string()]);
+        assert!(sol.solutions[&AmmoKind::He].get(1).is_some());
     }
 
     #[test]
-    fn calculate_dispersion_matches_doc() {
-        let d1 = calculate_dispersion(39.0, 105.0, 100.0);
-        assert!((d1 - 48.75).abs() < 0.01);
+    fn degraded_charges_aim_at_a_longer_table_range() {
+        let table = BallisticTable {
+            points: vec![
+                BallisticPoint {
+                    range_m: 0.0,
+                    elev_mil: 1400.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+                BallisticPoint {
+                    range_m: 1000.0,
+                    elev_mil: 900.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let ballistics = BTreeMap::from([((WeaponSystem::M224, AmmoKind::He, 1), table)]);
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            800.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
 
-        let d2 = calculate_dispersion(39.0, 90.0, 100.0);
-        assert!((d2 - 35.1).abs() < 0.01);
+        let nominal = calculate_solution(&mortar, &target, &ballistics);
+        assert!(nominal.charge_efficiency.is_none());
+        assert!(nominal.warnings.is_empty());
+        assert_eq!(
+            nominal.selected_solution.unwrap().elevations.get(1),
+            Some(1000.0)
+        );
+
+        // Charges tirant 20 % court : 800 m au sol demandent 1000 m de table
+        mortar.charge_efficiency = Some(0.8);
+        let degraded = calculate_solution(&mortar, &target, &ballistics);
+        let c = degraded.charge_efficiency.as_ref().unwrap();
+        assert!((c.table_range_m - 1000.0).abs() < 1e-9);
+        assert!((c.range_correction_m - 200.0).abs() < 1e-9);
+        assert_eq!(degraded.warnings.len(), 1);
+        assert!(degraded.warnings[0].contains("80.0%"));
+        let sel = degraded.selected_solution.unwrap();
+        assert_eq!(sel.elevations.get(1), Some(900.0));
+        assert_eq!(degraded.solutions[&AmmoKind::He].get(1), Some(900.0));
+        // 1 mil déplace l'impact de 2 m de table, soit 1.6 m au sol
+        assert!((sel.range_m_per_mil.get(1).unwrap() - 1.6).abs() < 1e-9);
+
+        // Au-delà de la portée corrigée, l'anneau ne couvre plus la cible
+        mortar.charge_efficiency = Some(0.7);
+        assert!(mortar
+            .usable_rings(&ballistics, AmmoKind::He, 800.0)
+            .is_empty());
     }
 
     #[test]
-    fn ballistic_table_interpolation_and_bounds() {
+    fn worn_tube_adds_to_the_charge_efficiency_correction() {
         let table = BallisticTable {
             points: vec![
                 BallisticPoint {
                     range_m: 0.0,
-                    elev_mil: 1000.0,
+                    elev_mil: 1400.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
                 },
                 BallisticPoint {
-                    range_m: 100.0,
+                    range_m: 1000.0,
                     elev_mil: 900.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
                 },
             ],
+            ..Default::default()
+        };
+        let ballistics = BTreeMap::from([((WeaponSystem::M224, AmmoKind::He, 1), table)]);
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = |range_m| {
+            TargetPosition::new(
+                "T1".into(),
+                0.0,
+                0.0,
+                range_m,
+                TargetType::Infanterie,
+                AmmoKind::He,
+            )
         };
 
-        assert_eq!(table.elev_at(0.0), Some(1000.0));
-        assert_eq!(table.elev_at(100.0), Some(900.0));
-        let mid = table.elev_at(50.0).unwrap();
-        assert!((mid - 950.0).abs() < 1e-6);
-        assert_eq!(table.elev_at(-10.0), None);
-        assert_eq!(table.elev_at(150.0), None);
+        // Tube perdant 10 % de vitesse : 81 % de la portée, 810 m au sol pour 1000 m de table
+        mortar.mv_correction_pct = Some(-10.0);
+        let worn = calculate_solution(&mortar, &target(810.0), &ballistics);
+        assert!(worn.charge_efficiency.is_none());
+        let c = worn.muzzle_velocity.as_ref().unwrap();
+        assert!((c.range_factor - 0.81).abs() < 1e-9);
+        assert!((c.table_range_m - 1000.0).abs() < 1e-9);
+        assert!((c.range_correction_m - 190.0).abs() < 1e-9);
+        assert!(worn.warnings[0].contains("-10.0%"));
+        let elevation = worn.selected_solution.unwrap().elevations.get(1).unwrap();
+        assert!((elevation - 900.0).abs() < 1e-9);
+
+        // Avec des charges à 90 % : 729 m au sol, 81 m dus aux charges et 190 m au tube
+        mortar.charge_efficiency = Some(0.9);
+        let both = calculate_solution(&mortar, &target(729.0), &ballistics);
+        assert_eq!(both.warnings.len(), 2);
+        let charges = both.charge_efficiency.as_ref().unwrap();
+        let tube = both.muzzle_velocity.as_ref().unwrap();
+        assert!((charges.range_correction_m - 81.0).abs() < 1e-9);
+        assert!((tube.range_correction_m - 190.0).abs() < 1e-9);
+        assert!((tube.table_range_m - 1000.0).abs() < 1e-9);
+        assert!((mortar.ground_range(1000.0) - 729.0).abs() < 1e-9);
     }
 
     #[test]
-    fn apply_correction_example() {
-        let t = TargetPosition::new(
-            "T1".to_string(),
-            100.0,
+    fn default_ring_is_preferred_when_usable() {
+        let mut ballistics: BallisticTables = BTreeMap::new();
+        for (ring, max_range) in [(1, 600.0), (2, 1000.0)] {
+            ballistics.insert(
+                (WeaponSystem::M224, AmmoKind::He, ring),
+                BallisticTable {
+                    points: vec![
+                        BallisticPoint {
+                            range_m: 0.0,
+                            elev_mil: 1200.0,
+                            time_flight_s: None,
+                            delta_elev_per_100m_mil: None,
+                        },
+                        BallisticPoint {
+                            range_m: max_range,
+                            elev_mil: 900.0,
+                            time_flight_s: None,
+                            delta_elev_per_100m_mil: None,
+                        },
+                    ],
+                    ..Default::default()
+                },
+            );
+        }
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        assert_eq!(
+            mortar.preferred_ring(&ballistics, AmmoKind::He, 500.0),
+            Some(1)
+        );
+
+        mortar.default_ring = Some(2);
+        assert_eq!(
+            mortar.preferred_ring(&ballistics, AmmoKind::He, 500.0),
+            Some(2)
+        );
+        // Hors de portée de l'anneau par défaut : repli sur un anneau utilisable
+        mortar.default_ring = Some(1);
+        assert_eq!(
+            mortar.preferred_ring(&ballistics, AmmoKind::He, 800.0),
+            Some(2)
+        );
+
+        mortar.default_ring = Some(2);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
             500.0,
-            300.0,
             TargetType::Infanterie,
             AmmoKind::He,
         );
+        let sel = calculate_solution(&mortar, &target, &ballistics)
+            .selected_solution
+            .unwrap();
+        assert_eq!(sel.default_ring.as_deref(), Some("2R"));
 
-        let corrected = apply_correction(&t, -50.0, 30.0);
+        mortar.excluded_rings.insert(2);
+        assert_eq!(
+            mortar.preferred_ring(&ballistics, AmmoKind::He, 500.0),
+            Some(1)
+        );
+        let sel = calculate_solution(&mortar, &target, &ballistics)
+            .selected_solution
+            .unwrap();
+        assert_eq!(sel.default_ring, None);
+    }
 
-        assert_eq!(corrected.name, "T1_C");
-        assert_eq!(corrected.x, 470.0);
-        assert_eq!(corrected.y, 350.0);
+    #[test]
+    fn overlapping_rings_are_compared() {
+        let mut ballistics: BallisticTables = BTreeMap::new();
+        for (ring, min_range, max_range, tof) in [(1, 0.0, 1000.0, 20.0), (2, 200.0, 2000.0, 26.0)]
+        {
+            ballistics.insert(
+                (WeaponSystem::M224, AmmoKind::He, ring),
+                BallisticTable {
+                    points: vec![
+                        BallisticPoint {
+                            range_m: min_range,
+                            elev_mil: 1400.0,
+                            time_flight_s: Some(tof),
+                            delta_elev_per_100m_mil: None,
+                        },
+                        BallisticPoint {
+                            range_m: max_range,
+                            elev_mil: 900.0,
+                            time_flight_s: Some(tof - 6.0),
+                            delta_elev_per_100m_mil: None,
+                        },
+                    ],
+                    ..Default::default()
+                },
+            );
+        }
+        let mut dispersions: DispersionTable = BTreeMap::new();
+        dispersions.insert((AmmoKind::He, 1), Dispersion::circular(20.0).into());
+        dispersions.insert((AmmoKind::He, 2), Dispersion::circular(40.0).into());
+
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let solve = |range: f64| {
+            let target = TargetPosition::new(
+                "T1".into(),
+                0.0,
+                0.0,
+                range,
+                TargetType::Infanterie,
+                AmmoKind::He,
+            );
+            calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions)
+                .selected_solution
+                .unwrap()
+                .ring_comparison
+        };
+
+        let cmp = solve(500.0).unwrap();
+        assert_eq!(cmp.options.len(), 2);
+        assert_eq!(cmp.recommended_ring, "1R");
+        assert!((cmp.options[0].time_of_flight_s.unwrap() - 17.0).abs() < 1e-9);
+
+        // En limite de table de l'anneau 1, l'anneau 2 est conseillé
+        let cmp = solve(980.0).unwrap();
+        assert!(cmp.options[0].near_table_limit);
+        assert_eq!(cmp.recommended_ring, "2R");
+
+        // Un seul anneau couvre la distance
+        assert!(solve(1500.0).is_none());
     }
 
     #[test]
-    fn calculate_solution_with_dispersion_populates_struct() {
-        let mut ballistics: BTreeMap<(AmmoKind, Ring), BallisticTable> = BTreeMap::new();
-        ballistics.insert(
-            (AmmoKind::He, 2),
-            BallisticTable {
-                points: vec![
-                    BallisticPoint {
-                        range_m: 0.0,
-                        elev_mil: 1200.0,
-                    },
-                    BallisticPoint {
-                        range_m: 600.0,
-                        elev_mil: 1100.0,
-                    },
-                ],
-            },
+    fn solution_is_degraded_when_data_is_missing() {
+        let table = |tof: Option<f64>| BallisticTable {
+            points: vec![
+                BallisticPoint {
+                    range_m: 0.0,
+                    elev_mil: 1400.0,
+                    time_flight_s: tof,
+                    delta_elev_per_100m_mil: None,
+                },
+                BallisticPoint {
+                    range_m: 1000.0,
+                    elev_mil: 900.0,
+                    time_flight_s: tof,
+                    delta_elev_per_100m_mil: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
         );
-        let mut dispersions: DispersionTable = BTreeMap::new();
-        dispersions.insert((AmmoKind::He, 2), 39.0);
 
-        let mortar = MortarPosition::new("M1".into(), 100.0, 0.0, 0.0);
-        let target = TargetPosition::new("T1".into(), 50.0, 500.0, 300.0, TargetType::Infanterie, AmmoKind::He);
+        let mut ballistics = BTreeMap::new();
+        ballistics.insert((WeaponSystem::M224, AmmoKind::He, 1), table(Some(20.0)));
+        let mut dispersions: DispersionTable = BTreeMap::new();
+        dispersions.insert((AmmoKind::He, 1), Dispersion::circular(20.0).into());
+        let sol = calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+        assert!(!sol.degraded);
+        assert!(sol.missing_data.is_empty());
 
+        ballistics.insert((WeaponSystem::M224, AmmoKind::He, 2), table(None));
         let sol = calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+        assert!(sol.degraded);
+        assert_eq!(
+            sol.missing_data,
+            vec![MissingData::Dispersion, MissingData::TimeOfFlight]
+        );
 
-        assert!(sol.distance_m > 0.0);
-        assert!(sol.azimuth_deg >= 0.0 && sol.azimuth_deg <= 360.0);
-        assert_eq!(sol.mortar_ammo, "HE");
-        assert_eq!(sol.target_type, "INFANTERIE");
-        assert_eq!(sol.recommended_ammo, "HE");
-        assert!(sol.solutions.contains_key("HE"));
-        assert!(sol.dispersions.contains_key("HE"));
-        let sel = sol.selected_solution.as_ref().expect("selected_solution");
-        assert_eq!(sel.ammo_type, "HE");
-        assert!(sel.elevations.contains_key("2R"));
-        assert!(sel.dispersions.contains_key("2R"));
+        // Flèche : durée de trajet de la table, sinon trajectoire dans le vide
+        let sel = sol.selected_solution.unwrap();
+        assert!((sel.max_ordinate_m.get(1).unwrap() - 490.5).abs() < 1e-9);
+        let vacuum = 500.0 * mils_to_degrees(1150.0).to_radians().tan() / 4.0;
+        assert!((sel.max_ordinate_m.get(2).unwrap() - vacuum).abs() < 1e-6);
+        assert_eq!(sel.max_ordinate_m.get(3), None);
     }
 }
 
+pub mod airspace;
+pub mod ammo;
+pub mod angle;
+pub mod archive;
+pub mod bundle;
+pub mod coverage;
+pub mod dry_run;
+pub mod environment;
+pub mod events;
+pub mod fire;
+pub mod markers;
+pub mod met;
+pub mod mils;
+pub mod mission_pack;
+pub mod observation;
 pub mod pchip;
+pub mod prelude;
+pub mod reference;
+pub mod registration;
+pub mod replay;
+pub mod safety;
 pub mod server;
 pub mod server_cli;
+pub mod share;
+pub mod simulation;
+pub mod snapshot;
+pub mod terrain;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+pub mod timeline;
+pub mod trajectory;
 
+pub use angle::Angle;
 // Re-export so server_cli can `use crate::AppState;`
 pub use server::AppState;
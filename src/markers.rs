@@ -0,0 +1,290 @@
+//! Import de marqueurs exportés par les outils de cartographie web.
+//!
+//! Les formats reconnus sont les plus courants :
+//! - GeoJSON (`FeatureCollection` ou `Feature` de type `Point`), le nom et
+//!   l'icône étant lus dans `properties` ;
+//! - une liste de marqueurs, nue ou sous une clé `markers`, chacun avec un nom
+//!   (`name`, `title` ou `label`), une position (`x`/`y` en mètres ou
+//!   `lat`/`lon`) et une icône (`icon`, `symbol`, `marker-symbol`, `type` ou
+//!   `category`).
+//!
+//! Le type de cible est déduit de l'icône par mots-clés
+//! ([`target_type_for_icon`]).
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::replay::GeoReference;
+use crate::TargetType;
+
+/// Clés lues pour le nom d'un marqueur, par ordre de priorité.
+const NAME_KEYS: &[&str] = &["name", "title", "label"];
+
+/// Clés lues pour l'icône d'un marqueur, par ordre de priorité.
+const ICON_KEYS: &[&str] = &["icon", "marker-symbol", "symbol", "type", "category"];
+
+/// Mots-clés d'icône associés à chaque type de cible.
+const ICON_KEYWORDS: &[(TargetType, &[&str])] = &[
+    (
+        TargetType::Vehicule,
+        &[
+            "vehic", "tank", "char", "apc", "ifv", "truck", "camion", "car", "armor", "armour",
+            "blinde", "jeep", "btr", "bmp",
+        ],
+    ),
+    (
+        TargetType::Soutien,
+        &[
+            "mortar", "mortier", "artil", "support", "soutien", "hq", "command", "radio", "supply",
+            "logist", "depot", "fob", "smoke", "fumi",
+        ],
+    ),
+    (
+        TargetType::Infanterie,
+        &[
+            "inf", "soldier", "soldat", "troop", "squad", "team", "person", "man", "mg", "sniper",
+            "trench",
+        ],
+    ),
+];
+
+/// Position d'un marqueur, locale ou géographique.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarkerPosition {
+    /// Coordonnées locales en mètres
+    Local { x: f64, y: f64 },
+    /// Coordonnées géographiques en degrés
+    Geo { lat: f64, lon: f64 },
+}
+
+/// Marqueur lu dans un export de carte.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Marker {
+    /// Nom du marqueur, s'il en a un
+    pub name: Option<String>,
+    /// Position du marqueur
+    pub position: MarkerPosition,
+    /// Icône ou catégorie du marqueur
+    pub icon: Option<String>,
+}
+
+impl Marker {
+    /// Coordonnées locales du marqueur, converties autour de `reference` pour
+    /// un marqueur géographique.
+    ///
+    /// # Retourne
+    ///
+    /// `None` pour un marqueur géographique sans point de référence.
+    pub fn local_m(&self, reference: Option<&GeoReference>) -> Option<(f64, f64)> {
+        match self.position {
+            MarkerPosition::Local { x, y } => Some((x, y)),
+            MarkerPosition::Geo { lat, lon } => reference.map(|r| r.local_m(lat, lon)),
+        }
+    }
+
+    /// Type de cible déduit de l'icône, ou `default` si aucune ne correspond.
+    ///
+    /// Le booléen indique si l'icône a été reconnue.
+    pub fn target_type(&self, default: TargetType) -> (TargetType, bool) {
+        match self.icon.as_deref().and_then(target_type_for_icon) {
+            Some(t) => (t, true),
+            None => (default, false),
+        }
+    }
+}
+
+/// Marqueur importé comme cible.
+#[derive(Clone, Debug, Serialize)]
+pub struct ImportedMarker {
+    /// Nom de la cible créée
+    pub name: String,
+    /// Coordonnées locales en mètres
+    pub x: f64,
+    pub y: f64,
+    /// Type de cible retenu
+    pub target_type: TargetType,
+    /// Icône du marqueur, si présente
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// `true` si le type a été déduit de l'icône, `false` s'il s'agit du type
+    /// par défaut
+    pub icon_matched: bool,
+}
+
+/// Marqueur ignoré à l'import.
+#[derive(Clone, Debug, Serialize)]
+pub struct SkippedMarker {
+    /// Nom du marqueur, s'il en a un
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Raison du rejet
+    pub reason: String,
+}
+
+/// Résultat d'un import de marqueurs.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MarkerImport {
+    /// Cibles créées
+    pub imported: Vec<ImportedMarker>,
+    /// Marqueurs ignorés
+    pub skipped: Vec<SkippedMarker>,
+}
+
+/// Déduit un type de cible d'un nom d'icône, insensible à la casse.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::markers::target_type_for_icon;
+/// use mortar::TargetType;
+/// assert_eq!(target_type_for_icon("Tank"), Some(TargetType::Vehicule));
+/// assert_eq!(target_type_for_icon("mortar-team"), Some(TargetType::Soutien));
+/// assert_eq!(target_type_for_icon("star"), None);
+/// ```
+pub fn target_type_for_icon(icon: &str) -> Option<TargetType> {
+    let icon = icon.to_ascii_lowercase();
+    ICON_KEYWORDS
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|k| icon.contains(k)))
+        .map(|(t, _)| *t)
+}
+
+/// Lit les marqueurs d'un export JSON.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le document n'est dans aucun format reconnu ou si
+/// un marqueur n'a pas de position exploitable.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::markers::{parse_markers, MarkerPosition};
+/// let json = serde_json::json!({
+///     "type": "FeatureCollection",
+///     "features": [{
+///         "type": "Feature",
+///         "geometry": { "type": "Point", "coordinates": [2.35, 48.85] },
+///         "properties": { "name": "Char", "marker-symbol": "tank" }
+///     }]
+/// });
+/// let markers = parse_markers(&json).unwrap();
+/// assert_eq!(markers[0].position, MarkerPosition::Geo { lat: 48.85, lon: 2.35 });
+/// assert_eq!(markers[0].icon.as_deref(), Some("tank"));
+/// ```
+pub fn parse_markers(json: &Value) -> Result<Vec<Marker>, String> {
+    let items = match json {
+        Value::Array(items) => items,
+        Value::Object(o) => match (o.get("type").and_then(Value::as_str), o.get("markers")) {
+            (Some("FeatureCollection"), _) => match o.get("features").and_then(Value::as_array) {
+                Some(features) => features,
+                None => return Err("FeatureCollection without features".to_string()),
+            },
+            (Some("Feature"), _) => return Ok(vec![parse_marker(json, 0)?]),
+            (_, Some(Value::Array(items))) => items,
+            _ => return Err("Expected a GeoJSON document or a list of markers".to_string()),
+        },
+        _ => return Err("Expected a GeoJSON document or a list of markers".to_string()),
+    };
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| parse_marker(item, i))
+        .collect()
+}
+
+/// Lit un marqueur (objet simple ou `Feature` GeoJSON) à l'indice `index`.
+fn parse_marker(item: &Value, index: usize) -> Result<Marker, String> {
+    if item.get("type").and_then(Value::as_str) == Some("Feature") {
+        let properties = item.get("properties").unwrap_or(&Value::Null);
+        let geometry = item.get("geometry").unwrap_or(&Value::Null);
+        if geometry.get("type").and_then(Value::as_str) != Some("Point") {
+            return Err(format!("Feature {} is not a Point", index));
+        }
+        // GeoJSON: [longitude, latitude]
+        let position = match geometry.get("coordinates").and_then(Value::as_array) {
+            Some(c) => match (
+                c.first().and_then(Value::as_f64),
+                c.get(1).and_then(Value::as_f64),
+            ) {
+                (Some(lon), Some(lat)) => MarkerPosition::Geo { lat, lon },
+                _ => return Err(format!("Feature {} has invalid coordinates", index)),
+            },
+            None => return Err(format!("Feature {} has no coordinates", index)),
+        };
+        return Ok(Marker {
+            name: first_str(properties, NAME_KEYS),
+            position,
+            icon: first_str(properties, ICON_KEYS),
+        });
+    }
+
+    let number = |key: &str| item.get(key).and_then(Value::as_f64);
+    let position = match (
+        number("x"),
+        number("y"),
+        number("lat"),
+        number("lon").or_else(|| number("lng")),
+    ) {
+        (Some(x), Some(y), _, _) => MarkerPosition::Local { x, y },
+        (_, _, Some(lat), Some(lon)) => MarkerPosition::Geo { lat, lon },
+        _ => return Err(format!("Marker {} has no x/y or lat/lon", index)),
+    };
+    Ok(Marker {
+        name: first_str(item, NAME_KEYS),
+        position,
+        icon: first_str(item, ICON_KEYS),
+    })
+}
+
+/// Première valeur textuelle non vide parmi `keys`.
+fn first_str(object: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|k| object.get(*k).and_then(Value::as_str))
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn plain_marker_lists_are_parsed() {
+        let markers = parse_markers(&json!({
+            "markers": [
+                { "title": "MG", "x": 500.0, "y": 300.0, "icon": "infantry" },
+                { "label": "Camion", "lat": 48.0, "lng": 2.0 }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(markers[0].name.as_deref(), Some("MG"));
+        assert_eq!(
+            markers[0].position,
+            MarkerPosition::Local { x: 500.0, y: 300.0 }
+        );
+        assert_eq!(
+            markers[1].position,
+            MarkerPosition::Geo {
+                lat: 48.0,
+                lon: 2.0
+            }
+        );
+        assert_eq!(markers[1].icon, None);
+        assert_eq!(
+            markers[0].target_type(TargetType::Soutien),
+            (TargetType::Infanterie, true)
+        );
+        assert_eq!(
+            markers[1].target_type(TargetType::Soutien),
+            (TargetType::Soutien, false)
+        );
+        // Sans point de référence, un marqueur géographique n'a pas de position locale
+        assert_eq!(markers[1].local_m(None), None);
+
+        assert!(parse_markers(&json!([{ "name": "T1" }])).is_err());
+        assert!(parse_markers(&json!({ "name": "T1" })).is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Messages météo OTAN pour calculateur (METCM) et balistiques (METB3).
+//!
+//! Un message est une suite de groupes de chiffres, les espaces et retours à
+//! la ligne étant indifférents :
+//!
+//! - METCM : en-tête `METCM Q LaLaLa LoLoLo YYGGG G hhh PPP` puis une ligne
+//!   par zone `ZZ ddd FFF TTTT PPPP` (direction en dizaines de mils, vitesse
+//!   en nœuds, température virtuelle en dixièmes de kelvin, pression en hPa) ;
+//! - METB3 : même en-tête (pression en pourcentage de la pression standard)
+//!   puis une ligne par zone `ZZ dd FFF TTT DDD` (vent balistique, direction
+//!   en centaines de mils, température et densité balistiques en pourcentage
+//!   du standard, au dixième).
+//!
+//! Les pourcentages sont donnés sur trois chiffres sans la centaine : `975`
+//! vaut 97,5 %, `012` vaut 101,2 %. Le message se termine à la fin du texte
+//! ou au groupe `99999`. Les altitudes des zones sont comptées depuis la
+//! station, assimilée à l'altitude des pièces.
+
+use serde::Serialize;
+
+use crate::environment::{
+    Atmosphere, Wind, WindLayer, WindProfile, STANDARD_AIR_TEMPERATURE_C, STANDARD_PRESSURE_HPA,
+};
+use crate::mils;
+
+/// Sommet des zones météo au-dessus de la station (m), par numéro de zone.
+pub const ZONE_TOPS_M: [f64; 27] = [
+    0.0, 200.0, 500.0, 1000.0, 1500.0, 2000.0, 2500.0, 3000.0, 3500.0, 4000.0, 4500.0, 5000.0,
+    6000.0, 7000.0, 8000.0, 9000.0, 10000.0, 11000.0, 12000.0, 13000.0, 14000.0, 15000.0, 16000.0,
+    17000.0, 18000.0, 19000.0, 20000.0,
+];
+
+/// Mètres par seconde dans un nœud.
+const MPS_PER_KNOT: f64 = 0.514_444;
+
+/// Chiffres de l'en-tête après l'identifiant (`Q LaLaLa LoLoLo YYGGG G hhh PPP`).
+const HEADER_DIGITS: usize = 19;
+
+/// Type de message météo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MetKind {
+    /// Message météo pour calculateur : vent et température par zone
+    Metcm,
+    /// Message météo balistique : vent et température moyens jusqu'au sommet de la zone
+    Metb3,
+}
+
+/// Ligne d'un message météo.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MetZone {
+    /// Numéro de la zone (00 : sol)
+    pub zone: u8,
+    /// Sommet de la zone au-dessus de la station (m)
+    pub top_m: f64,
+    /// Direction d'où vient le vent (degrés)
+    pub direction_deg: f64,
+    /// Vitesse du vent (m/s)
+    pub speed_mps: f64,
+    /// Température (°C)
+    pub temperature_c: f64,
+    /// Pression (hPa), donnée par les messages METCM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure_hpa: Option<f64>,
+}
+
+/// Message météo décodé.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MetMessage {
+    pub kind: MetKind,
+    /// Altitude de la station (m)
+    pub station_height_m: f64,
+    /// Pression au niveau de la station (hPa)
+    pub station_pressure_hpa: f64,
+    /// Lignes du message, par zone croissante
+    pub zones: Vec<MetZone>,
+}
+
+/// Décode un message METCM ou METB3.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::met::{parse_met, MetKind};
+///
+/// let msg = parse_met("METCM1 345 067 12301 0 004 013\n00 480 010 2931 1013\n01 480 010 2925 0990\n").unwrap();
+/// assert_eq!(msg.kind, MetKind::Metcm);
+/// assert_eq!(msg.station_height_m, 40.0);
+/// assert_eq!(msg.zones.len(), 2);
+/// assert!((msg.zones[0].direction_deg - 270.0).abs() < 1e-9);
+/// assert!((msg.zones[1].temperature_c - 19.35).abs() < 1e-9);
+/// ```
+pub fn parse_met(text: &str) -> Result<MetMessage, String> {
+    let compact: String = text.split_whitespace().collect();
+    let upper = compact.to_uppercase();
+    let (kind, body) = if let Some(body) = upper.strip_prefix("METCM") {
+        (MetKind::Metcm, body)
+    } else if let Some(body) = upper.strip_prefix("METB3") {
+        (MetKind::Metb3, body)
+    } else {
+        return Err("expected a METCM or METB3 message".to_string());
+    };
+    if !body.bytes().all(|b| b.is_ascii_digit()) {
+        return Err("MET groups must only contain digits".to_string());
+    }
+    if body.len() < HEADER_DIGITS {
+        return Err("incomplete MET header".to_string());
+    }
+    let (header, mut lines) = body.split_at(HEADER_DIGITS);
+    let number = |digits: &str| digits.parse::<f64>().unwrap_or_default();
+
+    let station_height_m = number(&header[13..16]) * 10.0;
+    let station_pressure_hpa = match kind {
+        MetKind::Metcm => thousands(number(&header[16..19])),
+        MetKind::Metb3 => percent(&header[16..19]) / 100.0 * STANDARD_PRESSURE_HPA,
+    };
+
+    let line_len = match kind {
+        MetKind::Metcm => 16,
+        MetKind::Metb3 => 13,
+    };
+    let mut zones: Vec<MetZone> = Vec::new();
+    while !lines.is_empty() && !lines.starts_with("99999") {
+        if lines.len() < line_len {
+            return Err(format!("incomplete MET line '{}'", lines));
+        }
+        let (line, rest) = lines.split_at(line_len);
+        lines = rest;
+        let zone = line[..2].parse::<u8>().unwrap_or(u8::MAX);
+        let top_m = match ZONE_TOPS_M.get(usize::from(zone)) {
+            Some(top) => *top,
+            None => return Err(format!("invalid MET zone {}", &line[..2])),
+        };
+        if zones.last().is_some_and(|z| z.zone >= zone) {
+            return Err(format!("MET zone {:02} out of order", zone));
+        }
+        let (direction_mil, speed_kt, temperature_c, pressure_hpa) = match kind {
+            MetKind::Metcm => (
+                number(&line[2..5]) * 10.0,
+                number(&line[5..8]),
+                number(&line[8..12]) / 10.0 - 273.15,
+                Some(thousands(number(&line[12..16]))),
+            ),
+            MetKind::Metb3 => (
+                number(&line[2..4]) * 100.0,
+                number(&line[4..7]),
+                percent(&line[7..10]) / 100.0 * (STANDARD_AIR_TEMPERATURE_C + 273.15) - 273.15,
+                None,
+            ),
+        };
+        zones.push(MetZone {
+            zone,
+            top_m,
+            direction_deg: mils::mil_to_deg(mils::normalize(direction_mil)),
+            speed_mps: speed_kt * MPS_PER_KNOT,
+            temperature_c,
+            pressure_hpa,
+        });
+    }
+    if zones.is_empty() {
+        return Err("MET message has no lines".to_string());
+    }
+
+    Ok(MetMessage {
+        kind,
+        station_height_m,
+        station_pressure_hpa,
+        zones,
+    })
+}
+
+/// Pression donnée sans le chiffre des milliers (`013` : 1013 hPa).
+fn thousands(hpa: f64) -> f64 {
+    if hpa < 500.0 {
+        hpa + 1000.0
+    } else {
+        hpa
+    }
+}
+
+/// Pourcentage donné au dixième sans la centaine (`975` : 97,5 %, `012` : 101,2 %).
+fn percent(digits: &str) -> f64 {
+    let tenths = digits.parse::<f64>().unwrap_or_default() / 10.0;
+    if tenths < 50.0 {
+        tenths + 100.0
+    } else {
+        tenths
+    }
+}
+
+impl MetMessage {
+    /// Vent de l'opération décrit par le message.
+    ///
+    /// Le vent au sol est celui de la zone 00 (à défaut, de la première
+    /// ligne). Chaque zone suivante donne une couche à partir du sommet de la
+    /// zone précédente ; les vents balistiques d'un METB3, moyens depuis le
+    /// sol, sont d'abord ramenés au vent propre de chaque zone.
+    pub fn wind(&self) -> WindProfile {
+        let vector = |z: &MetZone| {
+            let from = z.direction_deg.to_radians();
+            (z.speed_mps * from.sin(), z.speed_mps * from.cos())
+        };
+        let surface = &self.zones[0];
+        let mut layers = Vec::new();
+        let mut previous: Option<&MetZone> = None;
+        for zone in self.zones.iter().filter(|z| z.zone > 0) {
+            let mut base_m = ZONE_TOPS_M[usize::from(zone.zone) - 1];
+            let (mut east, mut north) = vector(zone);
+            if self.kind == MetKind::Metb3 {
+                // Moyenne sur [0, sommet] moins la moyenne sur [0, ligne précédente]
+                let (below_east, below_north) = previous.map_or((0.0, 0.0), vector);
+                base_m = previous.map_or(0.0, |p| p.top_m);
+                let thickness = zone.top_m - base_m;
+                east = (east * zone.top_m - below_east * base_m) / thickness;
+                north = (north * zone.top_m - below_north * base_m) / thickness;
+            }
+            layers.push(WindLayer {
+                height_m: base_m,
+                direction_deg: east.atan2(north).to_degrees().rem_euclid(360.0),
+                speed_mps: east.hypot(north),
+            });
+            previous = Some(zone);
+        }
+        WindProfile {
+            surface: Wind {
+                direction_deg: surface.direction_deg,
+                speed_mps: surface.speed_mps,
+            },
+            layers,
+        }
+    }
+
+    /// Conditions de l'air au niveau de la station : température de la
+    /// première ligne et pression de la station.
+    pub fn atmosphere(&self) -> Atmosphere {
+        let surface = &self.zones[0];
+        Atmosphere {
+            air_temperature_c: surface.temperature_c,
+            pressure_hpa: Some(surface.pressure_hpa.unwrap_or(self.station_pressure_hpa)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metb3_ballistic_winds_are_split_into_zone_winds() {
+        // Vent de 16 (1600 mils, Est) : 10 kt au sol et sur 0-200 m, puis
+        // moyenne de 20 kt sur 0-500 m, soit 80/3 kt entre 200 et 500 m
+        let msg = parse_met(
+            "METB3 1 345 067 12301 0 004 012
+             00 16 010 987 012
+             01 16 010 985 010
+             02 16 020 980 005
+             99999",
+        )
+        .unwrap();
+        assert_eq!(msg.kind, MetKind::Metb3);
+        assert!((msg.station_pressure_hpa - 1.012 * 1013.25).abs() < 1e-9);
+        assert!((msg.zones[0].temperature_c - (0.987 * 288.15 - 273.15)).abs() < 1e-9);
+
+        let wind = msg.wind();
+        assert!((wind.surface.direction_deg - 90.0).abs() < 1e-9);
+        assert_eq!(wind.layers.len(), 2);
+        assert_eq!(wind.layers[0].height_m, 0.0);
+        assert_eq!(wind.layers[1].height_m, 200.0);
+        let expected = (20.0 * 500.0 - 10.0 * 200.0) / 300.0 * MPS_PER_KNOT;
+        assert!((wind.layers[1].speed_mps - expected).abs() < 1e-9);
+        assert!((wind.layers[1].direction_deg - 90.0).abs() < 1e-9);
+        // Le vent balistique du profil retrouve celui du message
+        let ballistic = wind.ballistic_wind(500.0);
+        assert!((ballistic.speed_mps - 20.0 * MPS_PER_KNOT).abs() < 1e-9);
+
+        assert!(parse_met("METB3 1 345").is_err());
+        assert!(parse_met("METCM1 345 067 12301 0 004 013 27 480 010 2931 1013").is_err());
+        assert!(parse_met("GRID 1 2 3").is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Conversions entre degrés, millièmes et radians.
+//!
+//! Les calculs internes sont en degrés (gisements) ou en radians
+//! (trigonométrie), les données de tir en millièmes. Ce module regroupe les
+//! conversions pour les deux systèmes en usage — OTAN (6400 millièmes au
+//! tour) et Pacte de Varsovie (6000) — ainsi que le repli des gisements et
+//! l'arrondi des valeurs affichées. Les fonctions libres travaillent en
+//! millièmes OTAN, ceux des tables ; [`MilSystem`] donne les mêmes
+//! conversions dans l'autre système. Pour lire un angle saisi avec son
+//! unité, voir [`crate::Angle`].
+//!
+//! # Exemple
+//!
+//! ```
+//! use mortar::mils::{self, MilSystem};
+//!
+//! assert_eq!(mils::deg_to_mil(90.0), 1600.0);
+//! assert_eq!(MilSystem::WarsawPact.deg_to_mil(90.0), 1500.0);
+//! assert_eq!(mils::normalize(-100.0), 6300.0);
+//! assert_eq!(mils::signed(6300.0), -100.0);
+//! assert_eq!(mils::round_to(1234.56, 10.0), 1230.0);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+
+use crate::angle::{NATO_MILS_PER_CIRCLE, WARSAW_PACT_MILS_PER_CIRCLE};
+
+/// Système de millièmes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MilSystem {
+    /// 6400 millièmes au tour
+    #[default]
+    Nato,
+    /// 6000 millièmes au tour
+    WarsawPact,
+}
+
+impl MilSystem {
+    /// Nombre de millièmes dans un tour.
+    pub fn per_circle(self) -> f64 {
+        match self {
+            MilSystem::Nato => NATO_MILS_PER_CIRCLE,
+            MilSystem::WarsawPact => WARSAW_PACT_MILS_PER_CIRCLE,
+        }
+    }
+
+    /// Retourne la représentation textuelle du système.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MilSystem::Nato => "nato",
+            MilSystem::WarsawPact => "warsaw-pact",
+        }
+    }
+
+    /// Parse un système ("nato", "6400", "warsaw-pact", "6000").
+    pub fn parse_str(s: &str) -> Option<MilSystem> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "nato" | "otan" | "6400" => Some(MilSystem::Nato),
+            "warsaw-pact" | "warsaw" | "pv" | "6000" => Some(MilSystem::WarsawPact),
+            _ => None,
+        }
+    }
+
+    /// Degrés vers millièmes.
+    pub fn deg_to_mil(self, deg: f64) -> f64 {
+        deg * self.per_circle() / 360.0
+    }
+
+    /// Millièmes vers degrés.
+    pub fn mil_to_deg(self, mil: f64) -> f64 {
+        mil * 360.0 / self.per_circle()
+    }
+
+    /// Radians vers millièmes.
+    pub fn rad_to_mil(self, rad: f64) -> f64 {
+        rad * self.per_circle() / TAU
+    }
+
+    /// Millièmes vers radians.
+    pub fn mil_to_rad(self, mil: f64) -> f64 {
+        mil * TAU / self.per_circle()
+    }
+
+    /// Même direction ramenée à `[0, tour[`.
+    pub fn normalize(self, mil: f64) -> f64 {
+        mil.rem_euclid(self.per_circle())
+    }
+
+    /// Écart angulaire ramené à `]-demi-tour, demi-tour]`.
+    pub fn signed(self, mil: f64) -> f64 {
+        let half = self.per_circle() / 2.0;
+        let wrapped = self.normalize(mil);
+        if wrapped > half {
+            wrapped - self.per_circle()
+        } else {
+            wrapped
+        }
+    }
+
+    /// Gisement arrondi à `step` millièmes, ramené à `[0, tour[` : un
+    /// gisement de 6399,6 s'affiche 0 et non 6400.
+    pub fn round_azimuth(self, mil: f64, step: f64) -> f64 {
+        self.normalize(round_to(mil, step))
+    }
+}
+
+impl std::fmt::Display for MilSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Degrés vers millièmes OTAN.
+pub fn deg_to_mil(deg: f64) -> f64 {
+    MilSystem::Nato.deg_to_mil(deg)
+}
+
+/// Millièmes OTAN vers degrés.
+pub fn mil_to_deg(mil: f64) -> f64 {
+    MilSystem::Nato.mil_to_deg(mil)
+}
+
+/// Radians vers millièmes OTAN.
+pub fn rad_to_mil(rad: f64) -> f64 {
+    MilSystem::Nato.rad_to_mil(rad)
+}
+
+/// Millièmes OTAN vers radians.
+pub fn mil_to_rad(mil: f64) -> f64 {
+    MilSystem::Nato.mil_to_rad(mil)
+}
+
+/// Gisement en millièmes OTAN ramené à `[0, 6400[`.
+pub fn normalize(mil: f64) -> f64 {
+    MilSystem::Nato.normalize(mil)
+}
+
+/// Écart en millièmes OTAN ramené à `]-3200, 3200]`.
+pub fn signed(mil: f64) -> f64 {
+    MilSystem::Nato.signed(mil)
+}
+
+/// Arrondit au multiple de `step` le plus proche (`step` positif, ex: 1 ou
+/// 10 millièmes) ; avec un pas nul ou négatif, la valeur est inchangée.
+pub fn round_to(value: f64, step: f64) -> f64 {
+    if step > 0.0 {
+        (value / step).round() * step
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_agree_between_systems() {
+        for system in [MilSystem::Nato, MilSystem::WarsawPact] {
+            let quarter = system.per_circle() / 4.0;
+            assert_eq!(system.deg_to_mil(90.0), quarter);
+            assert_eq!(system.mil_to_deg(quarter), 90.0);
+            assert!((system.rad_to_mil(std::f64::consts::FRAC_PI_2) - quarter).abs() < 1e-9);
+            assert!((system.mil_to_rad(quarter) - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+            assert_eq!(system.normalize(-quarter), 3.0 * quarter);
+            assert_eq!(system.signed(3.0 * quarter), -quarter);
+            // Le demi-tour reste positif
+            assert_eq!(system.signed(-2.0 * quarter), 2.0 * quarter);
+            assert_eq!(
+                MilSystem::parse_str(&system.per_circle().to_string()),
+                Some(system)
+            );
+            assert_eq!(MilSystem::parse_str(system.as_str()), Some(system));
+        }
+        assert_eq!(MilSystem::WarsawPact.round_azimuth(5999.6, 1.0), 0.0);
+        assert_eq!(round_to(1234.4, 1.0), 1234.0);
+        assert_eq!(round_to(1234.4, 0.0), 1234.4);
+        assert_eq!(mil_to_deg(deg_to_mil(12.5)), 12.5);
+        assert_eq!(MilSystem::parse_str("grad"), None);
+        assert_eq!(
+            serde_json::to_string(&MilSystem::WarsawPact).unwrap(),
+            r#""warsaw-pact""#
+        );
+    }
+}
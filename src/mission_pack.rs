@@ -0,0 +1,714 @@
+//! Dossier de mission imprimable.
+//!
+//! Regroupe l'opération courante en un document autonome : liste des cibles,
+//! fiches de tir par mortier (cibles et points de référence), plan de feux et
+//! croquis de situation. Le rendu
+//! HTML ([`render_html`]) embarque sa feuille de style et un croquis SVG ; il
+//! est prévu pour être imprimé ou converti en PDF depuis le navigateur.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::reference::{ReferencePoint, ReferencePoints};
+use crate::{
+    mils, AmmoKind, BallisticTables, Declination, FiringSolution, MortarPosition, Position, Ring,
+    TargetPosition, TargetType,
+};
+
+/// Munition des données de tir vers les points de référence.
+pub const REFERENCE_POINT_AMMO: AmmoKind = AmmoKind::He;
+
+/// Portée couverte par un anneau pour une munition.
+#[derive(Clone, Debug, Serialize)]
+pub struct RingCoverage {
+    /// Munition
+    pub ammo_type: AmmoKind,
+    /// Anneau
+    pub ring: Ring,
+    /// Portée minimale (m)
+    pub min_range_m: f64,
+    /// Portée maximale (m)
+    pub max_range_m: f64,
+}
+
+/// Données de tir d'un mortier vers une cible.
+#[derive(Clone, Debug, Serialize)]
+pub struct FiringData {
+    /// Mortier
+    pub mortar: String,
+    /// Cible
+    pub target: String,
+    /// Munition de la cible
+    pub ammo_type: AmmoKind,
+    /// Gisement grille en mils
+    pub azimuth_mil: f64,
+    /// Gisement magnétique en mils
+    pub magnetic_azimuth_mil: f64,
+    /// Distance horizontale (m)
+    pub distance_m: f64,
+    /// Anneau retenu, `None` si hors de portée
+    pub ring: Option<Ring>,
+    /// Élévation en mils pour l'anneau retenu
+    pub elevation_mil: Option<f64>,
+}
+
+/// Fiche de tir d'un mortier.
+#[derive(Clone, Debug, Serialize)]
+pub struct RangeCard {
+    /// Mortier
+    pub mortar: MortarPosition,
+    /// Portées couvertes par les anneaux utilisables de la pièce
+    pub coverage: Vec<RingCoverage>,
+    /// Données de tir vers chaque cible
+    pub entries: Vec<FiringData>,
+    /// Données de tir vers chaque point de référence, en [`REFERENCE_POINT_AMMO`]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reference_points: Vec<FiringData>,
+}
+
+/// Dossier de mission complet.
+#[derive(Clone, Debug, Serialize)]
+pub struct MissionPack {
+    /// Date de génération (secondes Unix)
+    pub generated_at: u64,
+    /// Déclinaison utilisée pour les gisements magnétiques
+    pub declination: Declination,
+    /// Mortiers
+    pub mortars: Vec<MortarPosition>,
+    /// Cibles
+    pub targets: Vec<TargetPosition>,
+    /// Points de référence, hors plan de feux
+    #[serde(skip_serializing_if = "ReferencePoints::is_empty")]
+    pub reference_points: ReferencePoints,
+    /// Fiches de tir, une par mortier
+    pub range_cards: Vec<RangeCard>,
+    /// Plan de feux : pour chaque cible, le mortier prêt le plus proche
+    /// pouvant l'atteindre (`ring` à `None` si aucun) parmi ceux dont le
+    /// secteur de tir la couvre ; vide sans mortier prêt
+    pub fire_plan: Vec<FiringData>,
+}
+
+/// Solutions complètes des mortiers, par nom de mortier puis de cible ou de
+/// point de référence. Une paire absente n'a pas de solution (munition
+/// refusée par la pièce, par exemple).
+#[derive(Clone, Debug, Default)]
+pub struct PackSolutions {
+    /// Solutions vers les cibles
+    pub targets: BTreeMap<(String, String), FiringSolution>,
+    /// Solutions vers les points de référence, en [`REFERENCE_POINT_AMMO`]
+    pub reference_points: BTreeMap<(String, String), FiringSolution>,
+}
+
+/// Cible fictive placée sur un point de référence, pour en calculer la
+/// solution en [`REFERENCE_POINT_AMMO`].
+pub fn reference_point_target(name: &str, point: &ReferencePoint) -> TargetPosition {
+    TargetPosition::new(
+        name.to_string(),
+        point.elevation,
+        point.x,
+        point.y,
+        TargetType::default(),
+        REFERENCE_POINT_AMMO,
+    )
+}
+
+/// Calcule les données de tir d'un mortier vers une cible avec son anneau
+/// préféré, d'après la solution complète de la pièce sur la cible.
+pub fn firing_data(
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    solution: Option<&FiringSolution>,
+    ballistics: &BallisticTables,
+    declination: Declination,
+) -> FiringData {
+    firing_data_to(
+        mortar,
+        &target.as_position(),
+        target.ammo_type,
+        solution,
+        ballistics,
+        declination,
+    )
+}
+
+/// Données de tir d'un mortier vers une position quelconque, pour une munition.
+///
+/// Élévation et gisement sont ceux de `solution` à l'anneau retenu :
+/// réglage, dérive, vent, air et température des charges compris. Sans
+/// solution, aucun anneau n'est retenu.
+fn firing_data_to(
+    mortar: &MortarPosition,
+    to: &Position,
+    ammo: AmmoKind,
+    solution: Option<&FiringSolution>,
+    ballistics: &BallisticTables,
+    declination: Declination,
+) -> FiringData {
+    let from = mortar.as_position();
+    let distance_m = from.distance_to(to);
+    let ring = solution.and_then(|_| mortar.preferred_ring(ballistics, ammo, distance_m));
+    let azimuth_mil = match (solution, ring) {
+        (Some(s), Some(r)) => s.aim_azimuth_mil(r),
+        _ => mils::deg_to_mil(from.azimuth_to(to)),
+    };
+    FiringData {
+        mortar: mortar.name.clone(),
+        target: to.name.clone(),
+        ammo_type: ammo,
+        azimuth_mil,
+        magnetic_azimuth_mil: declination.magnetic_azimuth_mil(azimuth_mil),
+        distance_m,
+        ring,
+        elevation_mil: ring.and_then(|r| {
+            solution
+                .and_then(|s| s.selected_solution.as_ref())
+                .and_then(|s| s.elevations.get(r))
+        }),
+    }
+}
+
+/// Construit le dossier de mission à partir de l'état courant.
+///
+/// # Arguments
+///
+/// * `mortars` - Mortiers de l'opération
+/// * `targets` - Cibles de l'opération
+/// * `reference_points` - Points de référence, portés sur les fiches de tir
+/// * `solutions` - Solutions complètes des mortiers sur les cibles et les
+///   points de référence
+/// * `ballistics` - Tables balistiques
+/// * `declination` - Déclinaison de la zone, pour les gisements magnétiques
+/// * `override_sectors` - Ignorer les secteurs de tir dans le plan de feux
+/// * `now` - Date de génération (secondes Unix)
+#[allow(clippy::too_many_arguments)]
+pub fn build_mission_pack(
+    mortars: &[MortarPosition],
+    targets: &[TargetPosition],
+    reference_points: &ReferencePoints,
+    solutions: &PackSolutions,
+    ballistics: &BallisticTables,
+    declination: Declination,
+    override_sectors: bool,
+    now: u64,
+) -> MissionPack {
+    let range_cards = mortars
+        .iter()
+        .map(|m| RangeCard {
+            mortar: m.clone(),
+            coverage: ballistics
+                .iter()
+                .filter(|((weapon, _, ring), _)| *weapon == m.weapon && m.ring_available(*ring))
+                .filter_map(|((_, ammo, ring), table)| {
+                    table.range_bounds().map(|(min, max)| RingCoverage {
+                        ammo_type: *ammo,
+                        ring: *ring,
+                        min_range_m: min,
+                        max_range_m: max,
+                    })
+                })
+                .collect(),
+            entries: targets
+                .iter()
+                .map(|t| {
+                    let solution = solutions.targets.get(&(m.name.clone(), t.name.clone()));
+                    firing_data(m, t, solution, ballistics, declination)
+                })
+                .collect(),
+            reference_points: reference_points
+                .0
+                .iter()
+                .map(|(name, p)| {
+                    firing_data_to(
+                        m,
+                        &p.as_position(name),
+                        REFERENCE_POINT_AMMO,
+                        solutions
+                            .reference_points
+                            .get(&(m.name.clone(), name.clone())),
+                        ballistics,
+                        declination,
+                    )
+                })
+                .collect(),
+        })
+        .collect();
+
+    let fire_plan = targets
+        .iter()
+        .filter_map(|t| {
+            let all: Vec<FiringData> = mortars
+                .iter()
+                .filter(|m| m.status.is_ready())
+                .filter(|m| override_sectors || m.in_sector(t.x, t.y))
+                .map(|m| {
+                    let solution = solutions.targets.get(&(m.name.clone(), t.name.clone()));
+                    firing_data(m, t, solution, ballistics, declination)
+                })
+                .collect();
+            let reachable = all
+                .iter()
+                .filter(|d| d.ring.is_some())
+                .min_by(|a, b| a.distance_m.total_cmp(&b.distance_m));
+            let nearest = all
+                .iter()
+                .min_by(|a, b| a.distance_m.total_cmp(&b.distance_m));
+            reachable.or(nearest).cloned()
+        })
+        .collect();
+
+    MissionPack {
+        generated_at: now,
+        declination,
+        mortars: mortars.to_vec(),
+        targets: targets.to_vec(),
+        reference_points: reference_points.clone(),
+        range_cards,
+        fire_plan,
+    }
+}
+
+/// Échappe les caractères spéciaux HTML.
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn fmt_opt(v: Option<f64>) -> String {
+    v.map(|v| format!("{:.1}", v))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn fmt_ring(r: Option<Ring>) -> String {
+    r.map(|r| format!("{}R", r))
+        .unwrap_or_else(|| "hors portee".to_string())
+}
+
+const PACK_STYLE: &str = "body{font-family:sans-serif;font-size:11pt;margin:1.5em}\
+h1{font-size:16pt}h2{font-size:13pt;border-bottom:1px solid #556b2f;margin-top:1.5em}\
+table{border-collapse:collapse;margin:.5em 0}th,td{border:1px solid #999;padding:2px 8px;text-align:right}\
+th:first-child,td:first-child{text-align:left}.card{page-break-inside:avoid}\
+@media print{.card{page-break-before:always}}";
+
+/// Taille du croquis SVG en pixels.
+const SKETCH_SIZE: f64 = 600.0;
+
+/// Dessine le croquis de situation (Nord en haut) en SVG.
+fn render_sketch(pack: &MissionPack, out: &mut String) {
+    let points: Vec<(f64, f64)> = pack
+        .mortars
+        .iter()
+        .map(|m| (m.x, m.y))
+        .chain(pack.targets.iter().map(|t| (t.x, t.y)))
+        .chain(pack.reference_points.0.values().map(|p| (p.x, p.y)))
+        .collect();
+    if points.is_empty() {
+        out.push_str("<p>(aucune position)</p>\n");
+        return;
+    }
+
+    let min_x = points.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let max_x = points.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+    let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    let span = (max_x - min_x).max(max_y - min_y).max(1.0);
+    let margin = 40.0;
+    let scale = (SKETCH_SIZE - 2.0 * margin) / span;
+    let px = |x: f64| margin + (x - min_x) * scale;
+    let py = |y: f64| SKETCH_SIZE - margin - (y - min_y) * scale;
+
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">",
+        SKETCH_SIZE
+    );
+    let _ = writeln!(
+        out,
+        "<rect width=\"{0}\" height=\"{0}\" fill=\"white\" stroke=\"#999\"/>\
+         <text x=\"{1}\" y=\"20\" text-anchor=\"middle\" font-weight=\"bold\">N</text>",
+        SKETCH_SIZE,
+        SKETCH_SIZE / 2.0
+    );
+    for d in pack.fire_plan.iter().filter(|d| d.ring.is_some()) {
+        let m = pack.mortars.iter().find(|m| m.name == d.mortar);
+        let t = pack.targets.iter().find(|t| t.name == d.target);
+        if let (Some(m), Some(t)) = (m, t) {
+            let _ = writeln!(
+                out,
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#556b2f\" stroke-dasharray=\"4 3\"/>",
+                px(m.x),
+                py(m.y),
+                px(t.x),
+                py(t.y)
+            );
+        }
+    }
+    for m in &pack.mortars {
+        let (x, y) = (px(m.x), py(m.y));
+        let _ = writeln!(
+            out,
+            "<polygon points=\"{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" fill=\"#1f4e79\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\">{}</text>",
+            x,
+            y - 7.0,
+            x - 6.0,
+            y + 5.0,
+            x + 6.0,
+            y + 5.0,
+            x + 9.0,
+            y + 4.0,
+            escape_html(&m.name)
+        );
+    }
+    for t in &pack.targets {
+        let (x, y) = (px(t.x), py(t.y));
+        let _ = writeln!(
+            out,
+            "<path d=\"M{:.1} {:.1}l12 12m0 -12l-12 12\" stroke=\"#b22222\" stroke-width=\"2\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\">{}</text>",
+            x - 6.0,
+            y - 6.0,
+            x + 9.0,
+            y + 4.0,
+            escape_html(&t.name)
+        );
+    }
+    for (name, p) in &pack.reference_points.0 {
+        let (x, y) = (px(p.x), py(p.y));
+        let _ = writeln!(
+            out,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"5\" fill=\"none\" stroke=\"#8b6914\" stroke-width=\"2\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\">{}</text>",
+            x,
+            y,
+            x + 9.0,
+            y + 4.0,
+            escape_html(name)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"10\">Echelle : {:.0} m</text>",
+        margin,
+        SKETCH_SIZE - 12.0,
+        span
+    );
+    out.push_str("</svg>\n");
+}
+
+/// Produit le dossier de mission en HTML autonome, prêt à imprimer.
+pub fn render_html(pack: &MissionPack) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"fr\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Dossier de mission</title>\n<style>{}</style>\n</head>\n<body>",
+        PACK_STYLE
+    );
+    let _ = writeln!(
+        out,
+        "<h1>Dossier de mission</h1>\n<p>Genere le {} (Unix) - {} mortier(s), {} cible(s)</p>",
+        pack.generated_at,
+        pack.mortars.len(),
+        pack.targets.len()
+    );
+    let _ = writeln!(
+        out,
+        "<p><strong>{}</strong> - gisements grille (G) et magnetiques (M)</p>",
+        pack.declination.describe()
+    );
+
+    out.push_str("<h2>Cibles</h2>\n<table>\n<tr><th>Cible</th><th>Type</th><th>Munition</th><th>X</th><th>Y</th><th>Alt (m)</th><th>Fiabilite</th></tr>\n");
+    for t in &pack.targets {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}</td><td>{}</td></tr>",
+            escape_html(&t.name),
+            t.target_type,
+            t.ammo_type,
+            t.x,
+            t.y,
+            t.elevation,
+            t.reliability.map(|r| r.as_str()).unwrap_or("-")
+        );
+    }
+    out.push_str("</table>\n");
+
+    if !pack.reference_points.is_empty() {
+        out.push_str("<h2>Points de reference</h2>\n<table>\n<tr><th>Point</th><th>X</th><th>Y</th><th>Alt (m)</th><th>Description</th></tr>\n");
+        for (name, p) in &pack.reference_points.0 {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}</td><td>{}</td></tr>",
+                escape_html(name),
+                p.x,
+                p.y,
+                p.elevation,
+                escape_html(p.description.as_deref().unwrap_or("-"))
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Plan de feux</h2>\n<table>\n<tr><th>Cible</th><th>Mortier</th><th>Munition</th><th>Gisement G (mils)</th><th>Gisement M (mils)</th><th>Distance (m)</th><th>Anneau</th><th>Elevation (mils)</th></tr>\n");
+    for d in &pack.fire_plan {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&d.target),
+            escape_html(&d.mortar),
+            d.ammo_type,
+            d.azimuth_mil,
+            d.magnetic_azimuth_mil,
+            d.distance_m,
+            fmt_ring(d.ring),
+            fmt_opt(d.elevation_mil)
+        );
+    }
+    out.push_str("</table>\n");
+
+    out.push_str("<h2>Croquis</h2>\n");
+    render_sketch(pack, &mut out);
+
+    for card in &pack.range_cards {
+        let m = &card.mortar;
+        let _ = writeln!(
+            out,
+            "<div class=\"card\">\n<h2>Fiche de tir {}</h2>\n<p>X={:.0} Y={:.0} Alt={:.0} m - {}</p>",
+            escape_html(&m.name),
+            m.x,
+            m.y,
+            m.elevation,
+            pack.declination.describe()
+        );
+        out.push_str("<table>\n<tr><th>Munition</th><th>Anneau</th><th>Portee min (m)</th><th>Portee max (m)</th></tr>\n");
+        for c in &card.coverage {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}R</td><td>{:.0}</td><td>{:.0}</td></tr>",
+                c.ammo_type, c.ring, c.min_range_m, c.max_range_m
+            );
+        }
+        out.push_str("</table>\n<table>\n<tr><th>Cible</th><th>Munition</th><th>Gisement G (mils)</th><th>Gisement M (mils)</th><th>Distance (m)</th><th>Anneau</th><th>Elevation (mils)</th></tr>\n");
+        for d in &card.entries {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&d.target),
+                d.ammo_type,
+                d.azimuth_mil,
+                d.magnetic_azimuth_mil,
+                d.distance_m,
+                fmt_ring(d.ring),
+                fmt_opt(d.elevation_mil)
+            );
+        }
+        out.push_str("</table>\n");
+        if !card.reference_points.is_empty() {
+            out.push_str("<table>\n<tr><th>Point de reference</th><th>Munition</th><th>Gisement G (mils)</th><th>Gisement M (mils)</th><th>Distance (m)</th><th>Anneau</th><th>Elevation (mils)</th></tr>\n");
+            for d in &card.reference_points {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&d.target),
+                    d.ammo_type,
+                    d.azimuth_mil,
+                    d.magnetic_azimuth_mil,
+                    d.distance_m,
+                    fmt_ring(d.ring),
+                    fmt_opt(d.elevation_mil)
+                );
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("</div>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calculate_solution, BallisticPoint, BallisticTable, MortarStatus, Sector, WeaponSystem,
+    };
+
+    fn ballistics() -> BallisticTables {
+        let mut b = BallisticTables::new();
+        b.insert(
+            (WeaponSystem::M224, AmmoKind::He, 1),
+            BallisticTable {
+                points: vec![
+                    BallisticPoint {
+                        range_m: 100.0,
+                        elev_mil: 1400.0,
+                        time_flight_s: None,
+                        delta_elev_per_100m_mil: None,
+                    },
+                    BallisticPoint {
+                        range_m: 1000.0,
+                        elev_mil: 900.0,
+                        time_flight_s: None,
+                        delta_elev_per_100m_mil: None,
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        b
+    }
+
+    fn solutions(
+        mortars: &[MortarPosition],
+        targets: &[TargetPosition],
+        trps: &ReferencePoints,
+    ) -> PackSolutions {
+        let b = ballistics();
+        let mut solutions = PackSolutions::default();
+        for m in mortars {
+            for t in targets {
+                solutions.targets.insert(
+                    (m.name.clone(), t.name.clone()),
+                    calculate_solution(m, t, &b),
+                );
+            }
+            for (name, p) in &trps.0 {
+                solutions.reference_points.insert(
+                    (m.name.clone(), name.clone()),
+                    calculate_solution(m, &reference_point_target(name, p), &b),
+                );
+            }
+        }
+        solutions
+    }
+
+    #[test]
+    fn fire_plan_assigns_nearest_mortar_in_range() {
+        let mortars = vec![
+            // Trop près de T1 : sous la portée minimale
+            MortarPosition::new("M1".into(), 0.0, 0.0, 950.0),
+            MortarPosition::new("M2".into(), 0.0, 0.0, 0.0),
+        ];
+        let targets = vec![
+            TargetPosition::new(
+                "T1".into(),
+                0.0,
+                0.0,
+                1000.0,
+                TargetType::Infanterie,
+                AmmoKind::He,
+            ),
+            TargetPosition::new(
+                "T<2>".into(),
+                0.0,
+                5000.0,
+                0.0,
+                TargetType::Vehicule,
+                AmmoKind::He,
+            ),
+        ];
+
+        let trps = ReferencePoints(
+            [(
+                "TRP1".to_string(),
+                ReferencePoint {
+                    x: 0.0,
+                    y: 500.0,
+                    elevation: 0.0,
+                    description: Some("pont".into()),
+                },
+            )]
+            .into(),
+        );
+        let declination = Declination {
+            magnetic_deg: 3.0,
+            grid_convergence_deg: 0.75,
+        };
+        let pack = build_mission_pack(
+            &mortars,
+            &targets,
+            &trps,
+            &solutions(&mortars, &targets, &trps),
+            &ballistics(),
+            declination,
+            false,
+            0,
+        );
+        assert_eq!(pack.range_cards.len(), 2);
+        assert_eq!(pack.range_cards[0].coverage.len(), 1);
+        // Les points de référence figurent sur les fiches, pas au plan de feux
+        assert_eq!(pack.fire_plan.len(), 2);
+        let trp = &pack.range_cards[1].reference_points[0];
+        assert_eq!((trp.target.as_str(), trp.ring), ("TRP1", Some(1)));
+
+        assert_eq!(pack.fire_plan[0].mortar, "M2");
+        assert_eq!(pack.fire_plan[0].ring, Some(1));
+        assert!((pack.fire_plan[0].elevation_mil.unwrap() - 900.0).abs() < 1e-9);
+        assert!(pack.fire_plan[0].azimuth_mil.abs() < 1e-9);
+        assert_eq!(pack.fire_plan[1].ring, None);
+        assert!((pack.fire_plan[1].azimuth_mil - 1600.0).abs() < 1e-9);
+        // Angle G-M de 2,25° Est = 40 mils
+        assert!((pack.fire_plan[0].magnetic_azimuth_mil - 6360.0).abs() < 1e-9);
+        assert!((pack.fire_plan[1].magnetic_azimuth_mil - 1560.0).abs() < 1e-9);
+
+        let html = render_html(&pack);
+        assert!(html.contains("<svg"));
+        assert!(html.contains("Fiche de tir M2"));
+        assert!(html.contains("<td>TRP1</td><td>0</td><td>500</td><td>0</td><td>pont</td>"));
+        assert!(html.contains("Declinaison +3.0 deg, convergence +0.8 deg (G-M +40 mils)"));
+        assert!(html.contains("T&lt;2&gt;"));
+        assert!(!html.contains("T<2>"));
+
+        // Pièce en déplacement : le plan de feux se rabat sur M1
+        let mut mortars = mortars;
+        mortars[1].set_status(MortarStatus::Moving, 10);
+        let pack = build_mission_pack(
+            &mortars,
+            &targets,
+            &trps,
+            &solutions(&mortars, &targets, &trps),
+            &ballistics(),
+            declination,
+            false,
+            0,
+        );
+        assert_eq!(pack.range_cards.len(), 2);
+        assert_eq!(pack.fire_plan[0].mortar, "M1");
+        assert_eq!(pack.fire_plan[0].ring, None);
+
+        // M2 prête mais T1 hors de son secteur (face à l'Est) : sauf dérogation
+        mortars[1].set_status(MortarStatus::Ready, 20);
+        mortars[1].sector = Some(Sector {
+            left_deg: 45.0,
+            right_deg: 135.0,
+            max_range_m: None,
+        });
+        let pack = build_mission_pack(
+            &mortars,
+            &targets,
+            &trps,
+            &solutions(&mortars, &targets, &trps),
+            &ballistics(),
+            declination,
+            false,
+            0,
+        );
+        assert_eq!(pack.fire_plan[0].mortar, "M1");
+        assert_eq!(pack.fire_plan[1].mortar, "M2");
+        let pack = build_mission_pack(
+            &mortars,
+            &targets,
+            &trps,
+            &solutions(&mortars, &targets, &trps),
+            &ballistics(),
+            declination,
+            true,
+            0,
+        );
+        assert_eq!(pack.fire_plan[0].mortar, "M2");
+    }
+}
@@ -0,0 +1,646 @@
+//! Géométrie liée aux observateurs avancés.
+//!
+//! Calcul de l'angle T (observateur-cible-pièce) utilisé pour adapter les
+//! corrections de l'observateur lorsque sa ligne de visée s'écarte de la
+//! ligne pièce-but, localisation d'une cible par intersection de visées,
+//! estimation de la position d'un mortier ennemi (contre-batterie), zone de
+//! départ déduite des cratères et délais d'observation (durée de trajet,
+//! arrivée du son chez l'observateur).
+
+use serde::{Deserialize, Serialize};
+
+use crate::{mils, AmmoKind, BallisticTables, FiringSolution, Position, RingTable};
+
+/// Seuil (en millièmes) au-delà duquel l'angle T est considéré comme grand.
+pub const LARGE_ANGLE_T_MIL: f64 = 500.0;
+
+/// Vitesse du son dans l'air (m/s), à 15 °C.
+pub const SPEED_OF_SOUND_MPS: f64 = 340.0;
+
+/// Erreur typique (en millièmes) d'un relèvement à la boussole.
+pub const BEARING_ERROR_MIL: f64 = 10.0;
+
+/// Position d'un observateur.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Observer {
+    /// Identifiant de l'observateur
+    #[serde(default)]
+    pub name: String,
+    /// Coordonnée X en mètres
+    pub x: f64,
+    /// Coordonnée Y en mètres
+    pub y: f64,
+}
+
+impl Observer {
+    /// Convertit en position générique (altitude nulle).
+    pub fn as_position(&self) -> Position {
+        Position::new(self.name.clone(), 0.0, self.x, self.y)
+    }
+}
+
+/// Angle T entre la ligne observateur-cible et la ligne pièce-cible.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct AngleT {
+    /// Angle en millièmes (0-3200)
+    pub mils: f64,
+    /// Angle en degrés (0-180)
+    pub degrees: f64,
+    /// `true` si l'angle dépasse [`LARGE_ANGLE_T_MIL`]
+    pub large: bool,
+}
+
+/// Calcule l'angle T au sommet de la cible.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::Position;
+/// use mortar::observation::angle_t;
+///
+/// let gun = Position::new("M1".into(), 0.0, 0.0, 0.0);
+/// let target = Position::new("T1".into(), 0.0, 0.0, 1000.0);
+/// let observer = Position::new("OA".into(), 0.0, 1000.0, 1000.0);
+///
+/// let t = angle_t(&gun, &target, &observer);
+/// assert!((t.mils - 1600.0).abs() < 1e-6); // angle droit
+/// assert!(t.large);
+/// ```
+pub fn angle_t(gun: &Position, target: &Position, observer: &Position) -> AngleT {
+    let to_gun = target.azimuth_to(gun);
+    let to_observer = target.azimuth_to(observer);
+    let mut degrees = (to_gun - to_observer).abs() % 360.0;
+    if degrees > 180.0 {
+        degrees = 360.0 - degrees;
+    }
+    let mils = mils::deg_to_mil(degrees);
+    AngleT {
+        mils,
+        degrees,
+        large: mils > LARGE_ANGLE_T_MIL,
+    }
+}
+
+/// Renseigne l'angle T d'une solution et ajoute un avertissement s'il est grand.
+pub fn attach_angle_t(
+    solution: &mut FiringSolution,
+    gun: &Position,
+    target: &Position,
+    observer: &Position,
+) {
+    let t = angle_t(gun, target, observer);
+    if t.large {
+        solution.warnings.push(format!(
+            "Angle T {:.0} mils exceeds {:.0} mils: observer corrections must be converted to the gun-target line",
+            t.mils, LARGE_ANGLE_T_MIL
+        ));
+    }
+    solution.angle_t = Some(t);
+}
+
+/// Délais utiles à l'observateur pour caler sa fenêtre d'observation.
+///
+/// Tous les délais sont comptés depuis le départ du coup (« shot »).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ObservationTiming {
+    /// Distance observateur-pièce (mètres)
+    pub observer_to_gun_m: f64,
+    /// Distance observateur-cible (mètres)
+    pub observer_to_target_m: f64,
+    /// Arrivée du son du départ chez l'observateur (secondes)
+    pub firing_sound_s: f64,
+    /// Durée de trajet par anneau : délai « shot » - « splash » (secondes)
+    pub splash_s: RingTable,
+    /// Arrivée du son de l'impact chez l'observateur, par anneau (secondes)
+    pub impact_sound_s: RingTable,
+}
+
+/// Calcule les délais d'observation des anneaux de la solution sélectionnée.
+///
+/// La durée de trajet vient de la table de chaque anneau ; le son se propage
+/// à [`SPEED_OF_SOUND_MPS`] en ligne droite (distances horizontales).
+pub fn observation_timing(
+    solution: &FiringSolution,
+    gun: &Position,
+    target: &Position,
+    observer: &Position,
+    ballistics: &BallisticTables,
+) -> ObservationTiming {
+    let observer_to_gun_m = observer.distance_to(gun);
+    let observer_to_target_m = observer.distance_to(target);
+    let impact_delay_s = observer_to_target_m / SPEED_OF_SOUND_MPS;
+    let splash_s = match &solution.selected_solution {
+        Some(sel) => match AmmoKind::from_name(&sel.ammo_type) {
+            Some(ammo) => RingTable::from_fn(|r| {
+                sel.elevations.get(r)?;
+                ballistics
+                    .get(&(solution.weapon, ammo, r))?
+                    .time_of_flight_at(solution.distance_m)
+            }),
+            None => RingTable::default(),
+        },
+        None => RingTable::default(),
+    };
+    ObservationTiming {
+        observer_to_gun_m,
+        observer_to_target_m,
+        firing_sound_s: observer_to_gun_m / SPEED_OF_SOUND_MPS,
+        splash_s,
+        impact_sound_s: RingTable::from_fn(|r| splash_s.get(r).map(|t| t + impact_delay_s)),
+    }
+}
+
+/// Renseigne les délais d'observation d'une solution.
+pub fn attach_observation_timing(
+    solution: &mut FiringSolution,
+    gun: &Position,
+    target: &Position,
+    observer: &Position,
+    ballistics: &BallisticTables,
+) {
+    solution.observation_timing = Some(observation_timing(
+        solution, gun, target, observer, ballistics,
+    ));
+}
+
+/// Relèvement d'une cible depuis la position d'un observateur.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObserverBearing {
+    /// Coordonnée X de l'observateur en mètres
+    pub x: f64,
+    /// Coordonnée Y de l'observateur en mètres
+    pub y: f64,
+    /// Azimut observé vers la cible, en degrés depuis le Nord (accepte aussi
+    /// `"1200mil"` en entrée)
+    #[serde(deserialize_with = "crate::deserialize_angle_deg")]
+    pub azimuth_deg: f64,
+}
+
+impl ObserverBearing {
+    /// Vecteur unitaire (Est, Nord) de la visée.
+    fn direction(&self) -> (f64, f64) {
+        let az = self.azimuth_deg.to_radians();
+        (az.sin(), az.cos())
+    }
+}
+
+/// Position obtenue par intersection de deux visées.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Intersection {
+    /// Coordonnée X de la cible en mètres
+    pub x: f64,
+    /// Coordonnée Y de la cible en mètres
+    pub y: f64,
+    /// Angle entre les deux visées au niveau de la cible (0-180°)
+    pub intersection_angle_deg: f64,
+    /// Erreur de position estimée en mètres
+    pub error_estimate_m: f64,
+}
+
+/// Calcule la position d'une cible par intersection de deux visées.
+///
+/// L'erreur estimée suppose une erreur de relèvement de [`BEARING_ERROR_MIL`]
+/// par observateur ; elle croît quand l'angle d'intersection s'éloigne de 90°.
+///
+/// # Retourne
+///
+/// `None` si les visées sont parallèles ou ne se croisent pas devant les
+/// deux observateurs.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::observation::{intersect_bearings, ObserverBearing};
+///
+/// let a = ObserverBearing { x: 0.0, y: 0.0, azimuth_deg: 45.0 };
+/// let b = ObserverBearing { x: 1000.0, y: 0.0, azimuth_deg: 315.0 };
+///
+/// let i = intersect_bearings(&a, &b).unwrap();
+/// assert!((i.x - 500.0).abs() < 1e-6);
+/// assert!((i.y - 500.0).abs() < 1e-6);
+/// assert!((i.intersection_angle_deg - 90.0).abs() < 1e-6);
+/// ```
+pub fn intersect_bearings(a: &ObserverBearing, b: &ObserverBearing) -> Option<Intersection> {
+    let (ax, ay) = a.direction();
+    let (bx, by) = b.direction();
+
+    let cross = ax * by - ay * bx;
+    if cross.abs() < 1e-9 {
+        return None;
+    }
+
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let t = (dx * by - dy * bx) / cross;
+    let s = (dx * ay - dy * ax) / cross;
+    if t <= 0.0 || s <= 0.0 {
+        return None;
+    }
+
+    let x = a.x + t * ax;
+    let y = a.y + t * ay;
+
+    let sin_angle = cross.abs();
+    let intersection_angle_deg = (ax * bx + ay * by).clamp(-1.0, 1.0).acos().to_degrees();
+
+    let sigma = mils::mil_to_rad(BEARING_ERROR_MIL);
+    let error_estimate_m = (t * sigma).hypot(s * sigma) / sin_angle;
+
+    Some(Intersection {
+        x,
+        y,
+        intersection_angle_deg,
+        error_estimate_m,
+    })
+}
+
+/// Analyse de cratère : azimut inverse vers la pièce ennemie depuis un point d'impact.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CraterAnalysis {
+    /// Coordonnée X du cratère en mètres
+    pub x: f64,
+    /// Coordonnée Y du cratère en mètres
+    pub y: f64,
+    /// Azimut inverse (cratère vers pièce ennemie) en degrés (accepte aussi
+    /// `"1200mil"` en entrée)
+    #[serde(deserialize_with = "crate::deserialize_angle_deg")]
+    pub back_azimuth_deg: f64,
+    /// Portée estimée de la pièce ennemie, si elle a pu être déduite
+    #[serde(default)]
+    pub range_m: Option<f64>,
+    /// Portée minimale de la bande estimée (arme, charge, forme du cratère)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_range_m: Option<f64>,
+    /// Portée maximale de la bande estimée
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_range_m: Option<f64>,
+}
+
+impl CraterAnalysis {
+    /// Bande de portée `(mini, maxi)` : la portée estimée seule, ou la bande
+    /// saisie. `None` sans portée, ou si la bande est incohérente.
+    pub fn range_band(&self) -> Option<(f64, f64)> {
+        let (min, max) = match (self.range_m, self.min_range_m, self.max_range_m) {
+            (Some(r), None, None) => (r, r),
+            (None, Some(min), Some(max)) => (min, max),
+            _ => return None,
+        };
+        (min.is_finite() && max.is_finite() && 0.0 <= min && min <= max).then_some((min, max))
+    }
+
+    /// Indique si la portée est absente ou forme une bande cohérente.
+    pub fn is_valid(&self) -> bool {
+        self.back_azimuth_deg.is_finite()
+            && (self.range_band().is_some()
+                || (self.range_m.is_none()
+                    && self.min_range_m.is_none()
+                    && self.max_range_m.is_none()))
+    }
+}
+
+/// Position estimée d'un mortier ennemi.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct BackplotEstimate {
+    /// Coordonnée X estimée en mètres
+    pub x: f64,
+    /// Coordonnée Y estimée en mètres
+    pub y: f64,
+    /// Écart quadratique moyen entre l'estimation et les observations (mètres)
+    pub residual_m: f64,
+    /// Nombre d'observations utilisées
+    pub observations: usize,
+}
+
+/// Zone de départ estimée d'une pièce ennemie.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct OriginArea {
+    /// Coordonnée X du centre en mètres
+    pub x: f64,
+    /// Coordonnée Y du centre en mètres
+    pub y: f64,
+    /// Rayon d'incertitude en mètres
+    pub radius_m: f64,
+    /// Nombre de cratères utilisés
+    pub observations: usize,
+}
+
+/// Estime la zone de départ d'une pièce ennemie à partir de cratères.
+///
+/// Le centre est la position de [`backplot`] (milieu de la bande de portée de
+/// chaque cratère). Le rayon couvre, pour le cratère le mieux renseigné, la
+/// demi-bande de portée combinée à l'erreur de relèvement
+/// ([`BEARING_ERROR_MIL`]) à la distance du centre ; sans aucune portée, il
+/// retient l'erreur de relèvement la plus forte. Il n'est jamais inférieur à
+/// l'écart résiduel entre les cratères.
+///
+/// # Retourne
+///
+/// `None` si les cratères ne suffisent pas à fixer une position.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::observation::{origin_area, CraterAnalysis};
+///
+/// let crater = CraterAnalysis {
+///     x: 0.0,
+///     y: 0.0,
+///     back_azimuth_deg: 90.0,
+///     range_m: None,
+///     min_range_m: Some(1000.0),
+///     max_range_m: Some(2000.0),
+/// };
+/// let area = origin_area(&[crater]).unwrap();
+/// assert!((area.x - 1500.0).abs() < 1e-6 && area.y.abs() < 1e-6);
+/// // Demi-bande de 500 m, élargie par l'erreur de relèvement
+/// assert!(area.radius_m > 500.0 && area.radius_m < 510.0);
+/// ```
+pub fn origin_area(craters: &[CraterAnalysis]) -> Option<OriginArea> {
+    let estimate = backplot(&[], craters)?;
+    let sigma = mils::mil_to_rad(BEARING_ERROR_MIL);
+    let bearing_error = |c: &CraterAnalysis| (estimate.x - c.x).hypot(estimate.y - c.y) * sigma;
+    let ranged = craters
+        .iter()
+        .filter_map(|c| {
+            let (min, max) = c.range_band()?;
+            Some(((max - min) / 2.0).hypot(bearing_error(c)))
+        })
+        .min_by(|a, b| a.total_cmp(b));
+    let spread = ranged.unwrap_or_else(|| craters.iter().map(bearing_error).fold(0.0, f64::max));
+    Some(OriginArea {
+        x: estimate.x,
+        y: estimate.y,
+        radius_m: spread.max(estimate.residual_m),
+        observations: estimate.observations,
+    })
+}
+
+/// Estime la position d'une pièce ennemie à partir de relèvements et de cratères.
+///
+/// Chaque relèvement (signature de départ) et chaque azimut inverse de cratère
+/// sans portée définit une droite ; un cratère avec portée (ou bande de portée,
+/// prise en son milieu) définit un point.
+/// La position retenue minimise la somme des carrés des distances à ces
+/// droites et points (moindres carrés).
+///
+/// # Retourne
+///
+/// `None` si les observations ne suffisent pas à fixer une position
+/// (moins de deux droites non parallèles et aucun point).
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::observation::{backplot, ObserverBearing};
+///
+/// let bearings = [
+///     ObserverBearing { x: 0.0, y: 0.0, azimuth_deg: 45.0 },
+///     ObserverBearing { x: 1000.0, y: 0.0, azimuth_deg: 315.0 },
+///     ObserverBearing { x: 500.0, y: 0.0, azimuth_deg: 0.0 },
+/// ];
+/// let est = backplot(&bearings, &[]).unwrap();
+/// assert!((est.x - 500.0).abs() < 1e-6);
+/// assert!((est.y - 500.0).abs() < 1e-6);
+/// assert_eq!(est.observations, 3);
+/// ```
+pub fn backplot(
+    bearings: &[ObserverBearing],
+    craters: &[CraterAnalysis],
+) -> Option<BackplotEstimate> {
+    // Lines as (point, unit direction); points as (x, y)
+    let mut lines: Vec<((f64, f64), (f64, f64))> = bearings
+        .iter()
+        .map(|b| ((b.x, b.y), b.direction()))
+        .collect();
+    let mut points: Vec<(f64, f64)> = Vec::new();
+
+    for c in craters {
+        let az = c.back_azimuth_deg.to_radians();
+        let dir = (az.sin(), az.cos());
+        match c.range_band() {
+            Some((min, max)) => {
+                let r = (min + max) / 2.0;
+                points.push((c.x + r * dir.0, c.y + r * dir.1))
+            }
+            None => lines.push(((c.x, c.y), dir)),
+        }
+    }
+
+    // Normal equations: A q = b with A = sum(n n^T) + k I, b = sum(n n^T p) + sum(points)
+    let (mut a11, mut a12, mut a22, mut b1, mut b2) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for &((px, py), (dx, dy)) in &lines {
+        let (nx, ny) = (dy, -dx);
+        let np = nx * px + ny * py;
+        a11 += nx * nx;
+        a12 += nx * ny;
+        a22 += ny * ny;
+        b1 += nx * np;
+        b2 += ny * np;
+    }
+    for &(px, py) in &points {
+        a11 += 1.0;
+        a22 += 1.0;
+        b1 += px;
+        b2 += py;
+    }
+
+    let det = a11 * a22 - a12 * a12;
+    if det.abs() < 1e-9 {
+        return None;
+    }
+    let x = (b1 * a22 - b2 * a12) / det;
+    let y = (a11 * b2 - a12 * b1) / det;
+
+    let mut sq_sum = 0.0;
+    for &((px, py), (dx, dy)) in &lines {
+        let d = dy * (x - px) - dx * (y - py);
+        sq_sum += d * d;
+    }
+    for &(px, py) in &points {
+        sq_sum += (x - px).powi(2) + (y - py).powi(2);
+    }
+    let observations = lines.len() + points.len();
+
+    Some(BackplotEstimate {
+        x,
+        y,
+        residual_m: (sq_sum / observations as f64).sqrt(),
+        observations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calculate_solution_with_dispersion, BallisticPoint, BallisticTable, DispersionTable,
+        MortarPosition, TargetPosition, TargetType, WeaponSystem,
+    };
+
+    #[test]
+    fn observation_timing_adds_sound_delays() {
+        let point = |range_m, elev_mil| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: Some(20.0),
+            delta_elev_per_100m_mil: None,
+        };
+        let table = BallisticTable {
+            points: vec![point(0.0, 1400.0), point(2000.0, 800.0)],
+            ..Default::default()
+        };
+        let ballistics = BallisticTables::from([((WeaponSystem::M224, AmmoKind::He, 2), table)]);
+        let gun = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let solution =
+            calculate_solution_with_dispersion(&gun, &target, &ballistics, &DispersionTable::new());
+        let observer = Position::new("OA".into(), 0.0, 680.0, 1000.0);
+
+        let timing = observation_timing(
+            &solution,
+            &gun.as_position(),
+            &target.as_position(),
+            &observer,
+            &ballistics,
+        );
+        assert!((timing.observer_to_target_m - 680.0).abs() < 1e-9);
+        assert!((timing.firing_sound_s - 1207.0 / SPEED_OF_SOUND_MPS).abs() < 0.01);
+        assert_eq!(timing.splash_s.get(2), Some(20.0));
+        assert_eq!(timing.impact_sound_s.get(2), Some(22.0));
+        assert_eq!(timing.splash_s.get(1), None);
+    }
+
+    #[test]
+    fn bearings_accept_mils_or_degrees() {
+        let b: ObserverBearing =
+            serde_json::from_str(r#"{ "x": 0, "y": 0, "azimuth_deg": "800mil" }"#).unwrap();
+        assert!((b.azimuth_deg - 45.0).abs() < 1e-9);
+        let b: ObserverBearing =
+            serde_json::from_str(r#"{ "x": 0, "y": 0, "azimuth_deg": 45 }"#).unwrap();
+        assert!((b.azimuth_deg - 45.0).abs() < 1e-9);
+        assert!(serde_json::from_str::<ObserverBearing>(
+            r#"{ "x": 0, "y": 0, "azimuth_deg": "45grad" }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn angle_t_is_small_when_observer_behind_gun() {
+        let gun = Position::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = Position::new("T1".into(), 0.0, 0.0, 1000.0);
+        let observer = Position::new("OA".into(), 0.0, 50.0, 300.0);
+
+        let t = angle_t(&gun, &target, &observer);
+        assert!(t.mils < LARGE_ANGLE_T_MIL);
+        assert!(!t.large);
+    }
+
+    #[test]
+    fn intersection_rejects_parallel_and_diverging_bearings() {
+        let a = ObserverBearing {
+            x: 0.0,
+            y: 0.0,
+            azimuth_deg: 0.0,
+        };
+        let parallel = ObserverBearing {
+            x: 100.0,
+            y: 0.0,
+            azimuth_deg: 0.0,
+        };
+        let behind = ObserverBearing {
+            x: 1000.0,
+            y: 0.0,
+            azimuth_deg: 135.0,
+        };
+        assert!(intersect_bearings(&a, &parallel).is_none());
+        assert!(intersect_bearings(&a, &behind).is_none());
+    }
+
+    #[test]
+    fn shallow_intersection_has_larger_error() {
+        let a = ObserverBearing {
+            x: 0.0,
+            y: 0.0,
+            azimuth_deg: 45.0,
+        };
+        let wide = ObserverBearing {
+            x: 1000.0,
+            y: 0.0,
+            azimuth_deg: 315.0,
+        };
+        let narrow = ObserverBearing {
+            x: 100.0,
+            y: 0.0,
+            azimuth_deg: 40.0,
+        };
+        let good = intersect_bearings(&a, &wide).unwrap();
+        let poor = intersect_bearings(&a, &narrow).unwrap();
+        assert!(poor.error_estimate_m > good.error_estimate_m);
+    }
+
+    #[test]
+    fn backplot_combines_bearing_and_crater_range() {
+        let bearings = [ObserverBearing {
+            x: 0.0,
+            y: 0.0,
+            azimuth_deg: 90.0,
+        }];
+        // Single line alone is not enough
+        assert!(backplot(&bearings, &[]).is_none());
+
+        let craters = [CraterAnalysis {
+            x: 800.0,
+            y: -300.0,
+            back_azimuth_deg: 0.0,
+            range_m: Some(300.0),
+            min_range_m: None,
+            max_range_m: None,
+        }];
+        let est = backplot(&bearings, &craters).unwrap();
+        assert!((est.x - 800.0).abs() < 1e-6);
+        assert!(est.y.abs() < 1e-6);
+        assert_eq!(est.observations, 2);
+    }
+
+    #[test]
+    fn crater_bands_plot_an_origin_area() {
+        let crater = |x, y, back_azimuth_deg, band: Option<(f64, f64)>| CraterAnalysis {
+            x,
+            y,
+            back_azimuth_deg,
+            range_m: None,
+            min_range_m: band.map(|b| b.0),
+            max_range_m: band.map(|b| b.1),
+        };
+        // Deux azimuts inverses concourants en (1000, 1000), bandes cohérentes
+        let craters = [
+            crater(0.0, 1000.0, 90.0, Some((800.0, 1200.0))),
+            crater(1000.0, 0.0, 0.0, Some((900.0, 1100.0))),
+        ];
+        let area = origin_area(&craters).unwrap();
+        assert!((area.x - 1000.0).abs() < 1e-6 && (area.y - 1000.0).abs() < 1e-6);
+        // Bande la plus étroite : ±100 m, plus ~10 m d'erreur de relèvement
+        assert!(area.radius_m > 100.0 && area.radius_m < 101.0);
+        assert_eq!(area.observations, 2);
+
+        // Sans portée, les azimuts seuls fixent le centre
+        let lines = [
+            crater(0.0, 1000.0, 90.0, None),
+            crater(1000.0, 0.0, 0.0, None),
+        ];
+        let area = origin_area(&lines).unwrap();
+        assert!((area.x - 1000.0).abs() < 1e-6);
+        assert!((area.radius_m - 1000.0 * 10.0 * std::f64::consts::TAU / 6400.0).abs() < 1e-6);
+        assert!(origin_area(&lines[..1]).is_none());
+
+        let inverted = crater(0.0, 0.0, 0.0, Some((500.0, 200.0)));
+        assert!(!inverted.is_valid());
+        assert!(crater(0.0, 0.0, 0.0, None).is_valid());
+    }
+}
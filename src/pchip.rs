@@ -124,3 +124,27 @@ pub fn pchip_eval(x: &[f64], y: &[f64], d: &[f64], xq: f64) -> Result<f64> {
 
     Ok(h00 * y[i] + h10 * h * d[i] + h01 * y[i + 1] + h11 * h * d[i + 1])
 }
+
+/// Évalue la dérivée de l'interpolation PCHIP en un point donné.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si `xq` est hors des bornes de `x`.
+pub fn pchip_derivative(x: &[f64], y: &[f64], d: &[f64], xq: f64) -> Result<f64> {
+    let n = x.len();
+    if xq < x[0] || xq > x[n - 1] {
+        bail!("Query out of bounds");
+    }
+
+    let i = x.partition_point(|v| *v <= xq).clamp(1, n - 1) - 1;
+    let h = x[i + 1] - x[i];
+    let t = (xq - x[i]) / h;
+
+    // Dérivées des fonctions de base d'Hermite (par rapport à t)
+    let dh00 = 6.0 * t * t - 6.0 * t;
+    let dh10 = 3.0 * t * t - 4.0 * t + 1.0;
+    let dh01 = -dh00;
+    let dh11 = 3.0 * t * t - 2.0 * t;
+
+    Ok((dh00 * y[i] + dh01 * y[i + 1]) / h + dh10 * d[i] + dh11 * d[i + 1])
+}
@@ -0,0 +1,29 @@
+//! Imports courants pour intégrer la bibliothèque.
+//!
+//! ```
+//! use mortar::prelude::*;
+//!
+//! let mortar = MortarPosition::builder()
+//!     .name("M1")
+//!     .at(0.0, 0.0)
+//!     .elevation(100.0)
+//!     .build()
+//!     .unwrap();
+//! let target = TargetPosition::builder()
+//!     .name("T1")
+//!     .at(300.0, 400.0)
+//!     .elevation(50.0)
+//!     .ammo(AmmoKind::He)
+//!     .build()
+//!     .unwrap();
+//! let solution = calculate_solution(&mortar, &target, &BallisticTables::new());
+//! assert_eq!(solution.distance_m, 500.0);
+//! ```
+
+pub use crate::{
+    apply_correction, calculate_solution, calculate_solution_with_dispersion, load_ballistics,
+    load_ballistics_from, load_dispersion, load_dispersion_from, load_metrics_from, AmmoKind,
+    Angle, BallisticTable, BallisticTables, Dispersion, DispersionTable, FiringSolution, Metrics,
+    MortarBuilder, MortarPosition, MortarStatus, Ring, TargetBuilder, TargetPosition, TargetType,
+    WeaponSystem,
+};
@@ -0,0 +1,145 @@
+//! Points de référence (TRP, points connus).
+//!
+//! Un point de référence est un point du terrain repéré à l'avance (point de
+//! réglage, repère remarquable) : il n'est pas une cible, n'apparaît ni dans
+//! les listes d'engagement ni dans le plan de feux, mais sert de base à
+//! l'observateur pour désigner une cible par déplacement depuis un point
+//! connu ([`KnownPointShift`]) et figure sur les fiches de tir.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::Position;
+
+/// Point de référence de l'opération.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReferencePoint {
+    /// Coordonnée X (Est-Ouest) en mètres
+    pub x: f64,
+    /// Coordonnée Y (Nord-Sud) en mètres
+    pub y: f64,
+    /// Altitude en mètres
+    #[serde(default)]
+    pub elevation: f64,
+    /// Description libre (ex: "carrefour", "eglise")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ReferencePoint {
+    /// Indique si les coordonnées et l'altitude sont finies.
+    pub fn is_valid(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.elevation.is_finite()
+    }
+
+    /// Position nommée du point.
+    pub fn as_position(&self, name: &str) -> Position {
+        Position::new(name.to_string(), self.elevation, self.x, self.y)
+    }
+}
+
+/// Points de référence de l'opération, par nom.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReferencePoints(pub BTreeMap<String, ReferencePoint>);
+
+impl ReferencePoints {
+    /// Indique si aucun point n'est défini.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Déplacement depuis un point connu, tel que transmis par l'observateur.
+///
+/// Les écarts sont donnés dans le repère de l'observateur : en direction
+/// (droite/gauche) perpendiculairement à la ligne observateur-but, en portée
+/// (allonger/raccourcir) le long de cette ligne, et en hauteur.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::reference::{KnownPointShift, ReferencePoint};
+///
+/// let trp = ReferencePoint { x: 1000.0, y: 1000.0, elevation: 50.0, description: None };
+/// // Observateur regardant vers l'Est : droite 100 = Sud, allonger 200 = Est
+/// let shift = KnownPointShift { direction_deg: 90.0, right_m: 100.0, add_m: 200.0, up_m: -10.0 };
+/// let (x, y, elevation) = shift.apply(&trp);
+/// assert!((x - 1200.0).abs() < 1e-9);
+/// assert!((y - 900.0).abs() < 1e-9);
+/// assert_eq!(elevation, 40.0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct KnownPointShift {
+    /// Direction observateur-but (degrés, ou texte avec unité `"1600mil"`)
+    #[serde(deserialize_with = "crate::deserialize_angle_deg")]
+    pub direction_deg: f64,
+    /// Écart en direction (m), positif vers la droite
+    #[serde(default)]
+    pub right_m: f64,
+    /// Écart en portée (m), positif pour allonger
+    #[serde(default)]
+    pub add_m: f64,
+    /// Écart en hauteur (m), positif vers le haut
+    #[serde(default)]
+    pub up_m: f64,
+}
+
+impl KnownPointShift {
+    /// Indique si la direction et les écarts sont finis.
+    pub fn is_valid(&self) -> bool {
+        [self.direction_deg, self.right_m, self.add_m, self.up_m]
+            .iter()
+            .all(|v| v.is_finite())
+    }
+
+    /// Coordonnées `(x, y, altitude)` du but désigné depuis le point connu.
+    pub fn apply(&self, from: &ReferencePoint) -> (f64, f64, f64) {
+        let (sin, cos) = self.direction_deg.to_radians().sin_cos();
+        (
+            from.x + self.add_m * sin + self.right_m * cos,
+            from.y + self.add_m * cos - self.right_m * sin,
+            from.elevation + self.up_m,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shift_follows_the_observer_target_line() {
+        let trp = ReferencePoint {
+            x: 0.0,
+            y: 0.0,
+            elevation: 100.0,
+            description: Some("carrefour".into()),
+        };
+        // Observateur regardant au Nord : droite = Est, allonger = Nord
+        let north = KnownPointShift {
+            direction_deg: 0.0,
+            right_m: 50.0,
+            add_m: 300.0,
+            up_m: 0.0,
+        };
+        let (x, y, e) = north.apply(&trp);
+        assert!((x - 50.0).abs() < 1e-9 && (y - 300.0).abs() < 1e-9);
+        assert_eq!(e, 100.0);
+
+        // Observateur regardant au Sud-Ouest : gauche 100 = Sud-Est
+        let south_west: KnownPointShift = serde_json::from_value(serde_json::json!({
+            "direction_deg": "4000mil",
+            "right_m": -100.0
+        }))
+        .unwrap();
+        let (x, y, _) = south_west.apply(&trp);
+        let h = 100.0 / 2f64.sqrt();
+        assert!((x - h).abs() < 1e-9 && (y + h).abs() < 1e-9);
+
+        let points: ReferencePoints =
+            serde_json::from_value(serde_json::json!({ "TRP1": { "x": 1.0, "y": 2.0 } })).unwrap();
+        assert_eq!(points.0["TRP1"].elevation, 0.0);
+        assert!(points.0["TRP1"].is_valid());
+    }
+}
@@ -0,0 +1,144 @@
+//! Tir de réglage sur point connu (registration).
+//!
+//! La pièce tire sur un point dont les coordonnées sont connues ; l'impact
+//! observé donne l'écart entre les tables et le tir réel de la pièce
+//! (usure, météo, charges). Cet écart, exprimé en correction de portée et de
+//! gisement, est conservé sur la pièce et appliqué à toutes ses solutions
+//! jusqu'à son effacement ou au déplacement de la pièce.
+
+use serde::{Deserialize, Serialize};
+
+use crate::fire::SpotObservation;
+use crate::{mils, FiringSolution, MortarPosition, Position};
+
+/// Correction de tir d'une pièce tirée d'un réglage sur point connu.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Registration {
+    /// Coordonnée X du point de réglage
+    pub point_x: f64,
+    /// Coordonnée Y du point de réglage
+    pub point_y: f64,
+    /// Coordonnée X de l'impact observé
+    pub impact_x: f64,
+    /// Coordonnée Y de l'impact observé
+    pub impact_y: f64,
+    /// Correction de portée (m, positive lorsque les coups tombent court)
+    pub range_correction_m: f64,
+    /// Correction de gisement (mils, positive vers la droite)
+    pub deflection_mil: f64,
+    /// Date du réglage (secondes Unix)
+    pub registered_at: u64,
+}
+
+/// Calcule la correction d'une pièce à partir de l'impact observé d'un coup
+/// tiré sur un point connu.
+///
+/// La déviation suit la convention des coups de réglage ([`SpotObservation`]).
+/// La correction ramène l'impact sur le point : en portée, la différence des
+/// distances pièce-point et pièce-impact ; en gisement, la différence des
+/// gisements, dans ]-3200, 3200] mils.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::fire::SpotObservation;
+/// use mortar::registration::register;
+/// use mortar::{MortarPosition, Position};
+///
+/// let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+/// let point = Position::new("PR1".into(), 0.0, 0.0, 1000.0);
+/// // Coup tombé 50 m court (au Sud du point, plein Nord de la pièce)
+/// let reg = register(&mortar, &point, SpotObservation { vertical_m: 50.0, horizontal_m: 0.0 }, 0);
+/// assert!((reg.range_correction_m - 50.0).abs() < 1e-9);
+/// assert!(reg.deflection_mil.abs() < 1e-9);
+/// ```
+pub fn register(
+    mortar: &MortarPosition,
+    point: &Position,
+    observation: SpotObservation,
+    registered_at: u64,
+) -> Registration {
+    let origin = mortar.as_position();
+    let impact = Position::new(
+        String::new(),
+        point.elevation,
+        point.x + observation.horizontal_m,
+        point.y - observation.vertical_m,
+    );
+    let deflection_mil = mils::signed(
+        mils::deg_to_mil(origin.azimuth_to(point)) - mils::deg_to_mil(origin.azimuth_to(&impact)),
+    );
+    Registration {
+        point_x: point.x,
+        point_y: point.y,
+        impact_x: impact.x,
+        impact_y: impact.y,
+        range_correction_m: origin.distance_to(point) - origin.distance_to(&impact),
+        deflection_mil,
+        registered_at,
+    }
+}
+
+/// Correction de réglage appliquée à une solution.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RegistrationCorrection {
+    /// Correction de portée ajoutée avant la lecture des tables (m)
+    pub range_correction_m: f64,
+    /// Correction de gisement (mils, positive vers la droite)
+    pub deflection_mil: f64,
+    /// Gisement grille corrigé du réglage (mils)
+    pub azimuth_mil: f64,
+    /// Date du réglage (secondes Unix)
+    pub registered_at: u64,
+}
+
+/// Renseigne [`FiringSolution::registration`] à partir du réglage de la pièce.
+///
+/// Les élévations de la solution sont déjà lues à la portée corrigée ; le
+/// gisement de la solution (`azimuth_deg`) n'est pas modifié, le gisement
+/// corrigé est donné à part. Un avertissement rappelle la correction.
+pub fn attach_registration(solution: &mut FiringSolution, mortar: &MortarPosition) {
+    let reg = match &mortar.registration {
+        Some(reg) => reg,
+        None => return,
+    };
+    let azimuth_mil = mils::normalize(mils::deg_to_mil(solution.azimuth_deg) + reg.deflection_mil);
+    solution.warnings.push(format!(
+        "Registration of mortar '{}' applied: {:+.0} m, {:+.0} mil (azimuth {:.0} mil)",
+        mortar.name, reg.range_correction_m, reg.deflection_mil, azimuth_mil
+    ));
+    solution.registration = Some(RegistrationCorrection {
+        range_correction_m: reg.range_correction_m,
+        deflection_mil: reg.deflection_mil,
+        azimuth_mil,
+        registered_at: reg.registered_at,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registration_corrects_range_and_deflection() {
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let point = Position::new("PR1".into(), 0.0, 0.0, 1000.0);
+
+        // Coup tombé 100 m long et 100 m à l'Est : tirer plus court et à gauche
+        let reg = register(
+            &mortar,
+            &point,
+            SpotObservation {
+                vertical_m: -100.0,
+                horizontal_m: 100.0,
+            },
+            42,
+        );
+        assert_eq!((reg.impact_x, reg.impact_y), (100.0, 1100.0));
+        assert!((reg.range_correction_m - (1000.0 - 100.0 * 122f64.sqrt())).abs() < 1e-9);
+        let expected_mil = -mils::rad_to_mil(100.0f64.atan2(1100.0));
+        assert!((reg.deflection_mil - expected_mil).abs() < 1e-9);
+        assert!(reg.deflection_mil < 0.0);
+        assert_eq!(reg.registered_at, 42);
+    }
+}
@@ -0,0 +1,349 @@
+//! Journal des tirs et export pour relecture spatiale.
+//!
+//! Chaque coup tiré (coup de réglage) est conservé dans l'historique des
+//! solutions avec sa trace ([`ShotTrace`]) : position du mortier, point
+//! d'impact et durée de trajet. [`render_acmi`] produit un fichier ACMI texte
+//! (format de Tacview) pour rejouer une mission d'entraînement : mortiers,
+//! obus en vol et impacts.
+//!
+//! Les coordonnées locales (mètres) sont converties en longitude/latitude
+//! autour d'un point de référence ([`GeoReference`]) et reportées aussi en
+//! coordonnées planes `U`/`V`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::fire::{Expenditure, SpotReport};
+use crate::{
+    AmmoKind, IncrementTable, MortarPosition, Position, Ring, SolutionRecord, TargetPosition,
+};
+
+/// Accélération de la pesanteur (m/s²), pour la flèche des trajectoires.
+pub const GRAVITY_MPS2: f64 = 9.81;
+
+/// Nombre de points tracés par trajectoire d'obus.
+pub const TRAJECTORY_SAMPLES: u32 = 8;
+
+/// Durée d'affichage d'un impact (s).
+pub const IMPACT_DISPLAY_S: f64 = 2.0;
+
+/// Mètres par degré de latitude.
+const METERS_PER_DEG_LAT: f64 = 110_574.0;
+
+/// Mètres par degré de longitude à l'équateur.
+const METERS_PER_DEG_LON: f64 = 111_320.0;
+
+/// Trace d'un coup tiré.
+#[derive(Clone, Debug, Serialize)]
+pub struct ShotTrace {
+    /// Position du mortier au départ du coup
+    pub mortar: Position,
+    /// Point d'impact (nommé d'après la cible, altitude de la cible)
+    pub impact: Position,
+    /// Munition tirée
+    pub ammo_type: String,
+    /// Anneau utilisé
+    pub ring: Ring,
+    /// Durée de trajet en secondes, si la table la fournit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_of_flight_s: Option<f64>,
+}
+
+impl ShotTrace {
+    /// Construit la trace d'un coup de réglage.
+    pub fn from_spot(
+        mortar: &MortarPosition,
+        target: &TargetPosition,
+        report: &SpotReport,
+    ) -> Self {
+        ShotTrace {
+            mortar: mortar.as_position(),
+            impact: Position::new(
+                target.name.clone(),
+                target.elevation,
+                report.impact.x,
+                report.impact.y,
+            ),
+            ammo_type: report.ammo_type.clone(),
+            ring: report.ring,
+            time_of_flight_s: report.time_of_flight_s,
+        }
+    }
+}
+
+/// Coup tiré, daté, extrait de l'historique.
+#[derive(Clone, Debug, Serialize)]
+pub struct ShotEvent {
+    /// Heure de départ (secondes Unix)
+    pub timestamp: u64,
+    /// Cible visée
+    pub target: String,
+    /// Trace du coup
+    pub shot: ShotTrace,
+}
+
+/// Extrait les coups tirés de l'historique des solutions, par ordre chronologique.
+pub fn shot_events(history: &BTreeMap<String, Vec<SolutionRecord>>) -> Vec<ShotEvent> {
+    let mut events: Vec<ShotEvent> = history
+        .iter()
+        .flat_map(|(target, records)| {
+            records.iter().filter_map(move |r| {
+                r.shot.as_ref().map(|shot| ShotEvent {
+                    timestamp: r.timestamp,
+                    target: target.clone(),
+                    shot: shot.clone(),
+                })
+            })
+        })
+        .collect();
+    events.sort_by_key(|e| e.timestamp);
+    events
+}
+
+/// Consommation des coups tirés, par munition puis anneau.
+///
+/// Les incréments sont comptés d'après `increments` ; un coup d'une munition
+/// inconnue est ignoré.
+pub fn expenditure(shots: &[ShotEvent], increments: &IncrementTable) -> Vec<Expenditure> {
+    let mut bombs: BTreeMap<(AmmoKind, Ring), u32> = BTreeMap::new();
+    for event in shots {
+        if let Some(ammo) = AmmoKind::from_name(&event.shot.ammo_type) {
+            *bombs.entry((ammo, event.shot.ring)).or_default() += 1;
+        }
+    }
+    bombs
+        .into_iter()
+        .map(|((ammo, ring), n)| Expenditure::new(ammo, ring, n, increments))
+        .collect()
+}
+
+/// Point de référence géographique de l'origine des coordonnées locales.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeoReference {
+    /// Latitude de l'origine en degrés
+    #[serde(default)]
+    pub latitude_deg: f64,
+    /// Longitude de l'origine en degrés
+    #[serde(default)]
+    pub longitude_deg: f64,
+}
+
+impl GeoReference {
+    /// Convertit des coordonnées locales en décalage `(longitude, latitude)`
+    /// en degrés par rapport à la référence (approximation locale plane).
+    pub fn offset_deg(&self, x: f64, y: f64) -> (f64, f64) {
+        let meters_per_deg_lon = METERS_PER_DEG_LON * self.latitude_deg.to_radians().cos();
+        (x / meters_per_deg_lon, y / METERS_PER_DEG_LAT)
+    }
+
+    /// Convertit une position géographique en coordonnées locales `(x, y)`
+    /// en mètres, inverse de [`GeoReference::offset_deg`].
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::replay::GeoReference;
+    /// let reference = GeoReference { latitude_deg: 48.0, longitude_deg: 2.0 };
+    /// let (x, y) = reference.local_m(48.01, 2.0);
+    /// assert!(x.abs() < 1e-6 && (y - 1105.74).abs() < 1e-6);
+    /// ```
+    pub fn local_m(&self, latitude_deg: f64, longitude_deg: f64) -> (f64, f64) {
+        let meters_per_deg_lon = METERS_PER_DEG_LON * self.latitude_deg.to_radians().cos();
+        (
+            (longitude_deg - self.longitude_deg) * meters_per_deg_lon,
+            (latitude_deg - self.latitude_deg) * METERS_PER_DEG_LAT,
+        )
+    }
+}
+
+/// Formate une date Unix en UTC ISO 8601 (`2024-01-31T12:00:00Z`).
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::replay::format_utc;
+/// assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+/// assert_eq!(format_utc(1_700_000_000), "2023-11-14T22:13:20Z");
+/// ```
+pub fn format_utc(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let secs = timestamp % 86_400;
+
+    // Conversion jours -> date civile (algorithme de H. Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// Produit le journal des tirs au format ACMI texte (Tacview 2.2).
+///
+/// Le temps zéro est le départ du premier coup. Chaque mortier apparaît au
+/// départ de son premier coup ; chaque obus suit une trajectoire parabolique
+/// de flèche `g·T²/8` (durée de trajet `T`) jusqu'à l'impact, affiché pendant
+/// [`IMPACT_DISPLAY_S`] secondes. Sans durée de trajet connue, l'impact est
+/// affiché au départ du coup.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::replay::{render_acmi, GeoReference};
+///
+/// let acmi = render_acmi(&[], GeoReference::default(), "Exercice");
+/// assert!(acmi.starts_with("FileType=text/acmi/tacview\nFileVersion=2.2\n"));
+/// ```
+pub fn render_acmi(shots: &[ShotEvent], reference: GeoReference, title: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "FileType=text/acmi/tacview");
+    let _ = writeln!(out, "FileVersion=2.2");
+    let start = shots.iter().map(|s| s.timestamp).min().unwrap_or(0);
+    let _ = writeln!(out, "0,ReferenceTime={}", format_utc(start));
+    let _ = writeln!(out, "0,ReferenceLongitude={}", reference.longitude_deg);
+    let _ = writeln!(out, "0,ReferenceLatitude={}", reference.latitude_deg);
+    let _ = writeln!(out, "0,Title={}", escape_acmi(title));
+    let _ = writeln!(out, "0,DataSource=mortar");
+
+    let coords = |x: f64, y: f64, alt: f64| {
+        let (lon, lat) = reference.offset_deg(x, y);
+        format!("{:.7}|{:.7}|{:.1}|{:.1}|{:.1}", lon, lat, alt, x, y)
+    };
+
+    // (temps relatif, ligne), triés ensuite par temps
+    let mut lines: Vec<(f64, String)> = Vec::new();
+    let mut mortar_ids: BTreeMap<String, u64> = BTreeMap::new();
+
+    for (i, event) in shots.iter().enumerate() {
+        let shot = &event.shot;
+        let t0 = (event.timestamp - start) as f64;
+
+        let next_id = 0x100 + mortar_ids.len() as u64;
+        let mortar_id = *mortar_ids
+            .entry(shot.mortar.name.clone())
+            .or_insert_with(|| {
+                lines.push((
+                    t0,
+                    format!(
+                        "{:x},T={},Type=Ground+Static,Name={},Color=Blue",
+                        next_id,
+                        coords(shot.mortar.x, shot.mortar.y, shot.mortar.elevation),
+                        escape_acmi(&shot.mortar.name)
+                    ),
+                ));
+                next_id
+            });
+
+        let shell_id = 0x1000 + i as u64;
+        let tof = shot.time_of_flight_s.unwrap_or(0.0).max(0.0);
+        if tof > 0.0 {
+            for k in 0..=TRAJECTORY_SAMPLES {
+                let tau = f64::from(k) / f64::from(TRAJECTORY_SAMPLES);
+                let t = tau * tof;
+                let x = shot.mortar.x + (shot.impact.x - shot.mortar.x) * tau;
+                let y = shot.mortar.y + (shot.impact.y - shot.mortar.y) * tau;
+                let ground =
+                    shot.mortar.elevation + (shot.impact.elevation - shot.mortar.elevation) * tau;
+                let alt = ground + 0.5 * GRAVITY_MPS2 * t * (tof - t);
+                let line = if k == 0 {
+                    format!(
+                        "{:x},T={},Type=Weapon+Shell,Name={} {}R,Parent={:x},Color=Blue",
+                        shell_id,
+                        coords(x, y, alt),
+                        escape_acmi(&shot.ammo_type),
+                        shot.ring,
+                        mortar_id
+                    )
+                } else {
+                    format!("{:x},T={}", shell_id, coords(x, y, alt))
+                };
+                lines.push((t0 + t, line));
+            }
+            lines.push((t0 + tof, format!("-{:x}", shell_id)));
+        }
+
+        let impact_id = 0x10000 + i as u64;
+        lines.push((
+            t0 + tof,
+            format!(
+                "{:x},T={},Type=Misc+Explosion,Name=Impact {},Color=Red",
+                impact_id,
+                coords(shot.impact.x, shot.impact.y, shot.impact.elevation),
+                escape_acmi(&event.target)
+            ),
+        ));
+        lines.push((t0 + tof + IMPACT_DISPLAY_S, format!("-{:x}", impact_id)));
+    }
+
+    lines.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut frame = None;
+    for (t, line) in lines {
+        if frame != Some(t) {
+            let _ = writeln!(out, "#{:.2}", t);
+            frame = Some(t);
+        }
+        let _ = writeln!(out, "{}", line);
+    }
+    out
+}
+
+/// Échappe les virgules d'une valeur ACMI.
+fn escape_acmi(s: &str) -> String {
+    s.replace(',', "\\,").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: u64, tof: Option<f64>) -> ShotEvent {
+        ShotEvent {
+            timestamp,
+            target: "T1".into(),
+            shot: ShotTrace {
+                mortar: Position::new("M1".into(), 100.0, 0.0, 0.0),
+                impact: Position::new("T1".into(), 50.0, 0.0, 800.0),
+                ammo_type: "HE".into(),
+                ring: 2,
+                time_of_flight_s: tof,
+            },
+        }
+    }
+
+    #[test]
+    fn acmi_traces_shells_from_mortar_to_impact() {
+        let shots = vec![event(1_000, Some(20.0)), event(1_030, None)];
+        let acmi = render_acmi(&shots, GeoReference::default(), "Exercice, 1");
+
+        assert!(acmi.contains("0,ReferenceTime=1970-01-01T00:16:40Z\n"));
+        assert!(acmi.contains("0,Title=Exercice\\, 1\n"));
+        // Un seul mortier, déclaré au premier coup
+        assert_eq!(acmi.matches("Type=Ground+Static").count(), 1);
+        assert_eq!(acmi.matches("Type=Weapon+Shell").count(), 1);
+        assert_eq!(acmi.matches("Type=Misc+Explosion").count(), 2);
+        // Flèche de la trajectoire à mi-parcours : g·T²/8 au-dessus du sol
+        let apex = 75.0 + GRAVITY_MPS2 * 400.0 / 8.0;
+        assert!(acmi.contains(&format!(
+            "#10.00\n1000,T=0.0000000|0.0036175|{:.1}|0.0|400.0\n",
+            apex
+        )));
+        assert!(acmi.contains("#20.00\n1000,T="));
+        assert!(acmi.contains("-1000\n"));
+        // Deuxième coup sans durée de trajet : impact au départ
+        assert!(acmi.contains("#30.00\n10001,T="));
+    }
+}
@@ -0,0 +1,611 @@
+//! Tir à proximité des troupes amies.
+//!
+//! Un ami est en danger lorsqu'il se trouve à moins d'un multiple de la
+//! dispersion ajustée du point visé, augmenté du rayon létal de la munition
+//! ([`crate::EffectRadii::casualty_m`]). Plutôt que de seulement le signaler, on
+//! cherche un anneau plus faible (dispersion plus serrée) de la même munition,
+//! puis une munition de remplacement ([`substitute_ammo`]), dont le rayon de
+//! danger laisse l'ami à l'écart. Seule une munition indiquée pour le type de
+//! la cible et acceptée par la pièce peut remplacer la munition prévue.
+//!
+//! Les positions amies de l'opération ([`FriendlyPosition`]) sont contrôlées
+//! à chaque calcul par [`attach_danger_close`].
+
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+use crate::ammo::AmmoRegistry;
+use crate::{
+    AmmoKind, EffectTable, FiringSolution, MortarPosition, Position, Ring, RingTable,
+    TargetPosition, RING_COUNT,
+};
+
+/// Multiple de la dispersion ajustée définissant le rayon de danger.
+pub const DEFAULT_DANGER_CLOSE_MULTIPLE: f64 = 3.0;
+
+/// Multiples de la dispersion acceptés pour le rayon de danger.
+pub const DANGER_CLOSE_MULTIPLE_RANGE: RangeInclusive<f64> = 1.0..=10.0;
+
+/// Position de troupes amies.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FriendlyPosition {
+    pub name: String,
+    /// Coordonnée X (Est-Ouest) en mètres
+    pub x: f64,
+    /// Coordonnée Y (Nord-Sud) en mètres
+    pub y: f64,
+    /// Altitude en mètres
+    #[serde(default)]
+    pub elevation: f64,
+    /// Description libre (ex: "2e section", "poste d'observation")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl FriendlyPosition {
+    /// Crée une position amie sans description.
+    pub fn new(name: String, elevation: f64, x: f64, y: f64) -> Self {
+        FriendlyPosition {
+            name,
+            x,
+            y,
+            elevation,
+            description: None,
+        }
+    }
+
+    /// Indique si les coordonnées et l'altitude sont finies.
+    pub fn is_valid(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.elevation.is_finite()
+    }
+
+    /// Position nommée de l'ami.
+    pub fn as_position(&self) -> Position {
+        Position::new(self.name.clone(), self.elevation, self.x, self.y)
+    }
+}
+
+/// Ami à l'intérieur du rayon de danger d'au moins un anneau.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EndangeredFriendly {
+    pub name: String,
+    /// Distance horizontale au point visé (m)
+    pub distance_m: f64,
+    /// Anneaux dont le rayon de danger atteint l'ami (ex: ["2R", "3R"])
+    pub rings: Vec<String>,
+}
+
+/// Tir rapproché : amis menacés par la munition sélectionnée.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DangerClose {
+    /// Multiple de la dispersion ajustée appliqué
+    pub multiple: f64,
+    /// Rayon létal de la munition sélectionnée, inclus dans le rayon de danger (m)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub casualty_radius_m: Option<f64>,
+    /// Rayon de danger par anneau de la munition sélectionnée (m)
+    pub danger_radius_m: RingTable,
+    /// Amis menacés, du plus proche au plus éloigné
+    pub friendlies: Vec<EndangeredFriendly>,
+    /// Tir laissant tous les amis à l'écart, s'il en existe un
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safer_fire: Option<SaferFire>,
+}
+
+/// Tir de remplacement levant un danger rapproché.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SaferFire {
+    /// Munition à employer
+    pub ammo_type: String,
+    /// Anneau à employer (ex: "1R")
+    pub ring: String,
+    /// Élévation à cet anneau (mils)
+    pub elevation_mil: f64,
+    /// Dispersion ajustée à cet anneau (m)
+    pub dispersion_m: f64,
+    /// Rayon de danger correspondant (m)
+    pub danger_radius_m: f64,
+    /// Distance restant entre l'ami le plus proche et le rayon de danger (m)
+    pub margin_m: f64,
+}
+
+/// Rayon létal d'une munition, s'il est renseigné dans `effects`.
+pub fn casualty_radius_m(effects: &EffectTable, ammo: AmmoKind) -> Option<f64> {
+    effects.get(&ammo).and_then(|e| e.casualty_m)
+}
+
+/// Rayon de danger d'une munition à un anneau : `multiple` fois sa
+/// dispersion ajustée, plus son rayon létal s'il est connu.
+///
+/// # Retourne
+///
+/// `None` si la dispersion de l'anneau est inconnue.
+pub fn danger_radius_m(
+    solution: &FiringSolution,
+    effects: &EffectTable,
+    ammo: AmmoKind,
+    ring: Ring,
+    multiple: f64,
+) -> Option<f64> {
+    let casualty_m = casualty_radius_m(effects, ammo).unwrap_or(0.0);
+    solution
+        .dispersions
+        .get(&ammo)
+        .and_then(|d| d.get(ring))
+        .map(|d| d * multiple + casualty_m)
+}
+
+/// Munitions pouvant remplacer celle de la cible lors d'un tir rapproché :
+/// munitions du registre indiquées pour le type de la cible
+/// ([`crate::ammo::AmmoInfo::suggested_targets`]) et acceptées par la pièce.
+/// Une munition d'exercice, fumigène ou éclairante n'a pas l'effet demandé et
+/// n'est donc pas retenue pour une cible d'infanterie.
+pub fn substitute_ammo(
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    registry: &AmmoRegistry,
+) -> Vec<AmmoKind> {
+    registry
+        .kinds()
+        .filter(|&kind| kind != target.ammo_type)
+        .filter(|&kind| {
+            registry
+                .get(kind)
+                .is_some_and(|info| info.suggested_targets.contains(&target.target_type))
+        })
+        .filter(|&kind| mortar.check_ammo(kind, registry).is_ok())
+        .collect()
+}
+
+/// Cherche un tir laissant l'ami le plus proche hors du rayon de danger.
+///
+/// Les anneaux inférieurs à `ring` de la munition sélectionnée sont essayés
+/// du plus fort au plus faible (le moins d'écart avec le tir prévu), puis les
+/// anneaux des munitions de `substitutes` par dispersion croissante. Seuls
+/// les anneaux donnant une élévation à la distance de tir et non exclus par
+/// la pièce sont retenus.
+///
+/// # Arguments
+///
+/// * `solution` - Solution de tir calculée
+/// * `effects` - Rayons d'effet des munitions (rayon létal)
+/// * `ring` - Anneau prévu pour la munition sélectionnée
+/// * `friendly_distance_m` - Distance de la cible à l'ami le plus proche
+/// * `multiple` - Multiple de la dispersion définissant le rayon de danger
+/// * `substitutes` - Munitions pouvant remplacer la munition sélectionnée
+///   (voir [`substitute_ammo`])
+///
+/// # Retourne
+///
+/// `None` si aucun anneau ni aucune munition de remplacement ne lève le danger.
+pub fn safer_fire(
+    solution: &FiringSolution,
+    effects: &EffectTable,
+    ring: Ring,
+    friendly_distance_m: f64,
+    multiple: f64,
+    substitutes: &[AmmoKind],
+) -> Option<SaferFire> {
+    let selected = solution.selected_solution.as_ref()?;
+    let selected_ammo = AmmoKind::from_name(&selected.ammo_type)?;
+    let option = |ammo: AmmoKind, r: Ring| {
+        let label = format!("{}R", r);
+        if selected.excluded_rings.contains(&label) {
+            return None;
+        }
+        let elevation_mil = solution.solutions.get(&ammo)?.get(r)?;
+        let dispersion_m = solution.dispersions.get(&ammo)?.get(r)?;
+        let danger_radius_m = danger_radius_m(solution, effects, ammo, r, multiple)?;
+        (danger_radius_m < friendly_distance_m).then(|| SaferFire {
+            ammo_type: ammo.as_str().to_string(),
+            ring: label,
+            elevation_mil,
+            dispersion_m,
+            danger_radius_m,
+            margin_m: friendly_distance_m - danger_radius_m,
+        })
+    };
+
+    if let Some(lower) = (0..ring).rev().find_map(|r| option(selected_ammo, r)) {
+        return Some(lower);
+    }
+    substitutes
+        .iter()
+        .filter(|&&ammo| ammo != selected_ammo)
+        .flat_map(|&ammo| (0..RING_COUNT as Ring).filter_map(move |r| option(ammo, r)))
+        .min_by(|a, b| a.dispersion_m.total_cmp(&b.dispersion_m))
+}
+
+/// Contrôle les amis contre le rayon de danger de chaque anneau de la
+/// munition sélectionnée (dispersion et rayon létal, voir
+/// [`danger_radius_m`]) et ajoute un avertissement par ami menacé.
+///
+/// Lorsque l'anneau par défaut de la pièce (ou, sans anneau par défaut, un
+/// anneau quelconque) menace un ami, un tir de remplacement est cherché par
+/// [`safer_fire`] pour l'ami le plus proche, parmi les anneaux de la
+/// munition puis les munitions de `substitutes`. Sans ami menacé ni solution
+/// sélectionnée, la solution est inchangée.
+pub fn attach_danger_close(
+    solution: &mut FiringSolution,
+    target: &TargetPosition,
+    friendlies: &[FriendlyPosition],
+    effects: &EffectTable,
+    multiple: f64,
+    substitutes: &[AmmoKind],
+) {
+    let selected = match &solution.selected_solution {
+        Some(sel) => sel,
+        None => return,
+    };
+    let casualty_radius_m =
+        AmmoKind::from_name(&selected.ammo_type).and_then(|ammo| casualty_radius_m(effects, ammo));
+    let danger_radius_m = RingTable::from_fn(|r| {
+        selected.elevations.get(r)?;
+        selected
+            .dispersions
+            .get(r)
+            .map(|d| d * multiple + casualty_radius_m.unwrap_or(0.0))
+    });
+
+    let mut endangered: Vec<EndangeredFriendly> = friendlies
+        .iter()
+        .filter_map(|f| {
+            let distance_m = (f.x - target.x).hypot(f.y - target.y);
+            let rings: Vec<String> = danger_radius_m
+                .iter()
+                .filter(|(_, radius)| radius.is_some_and(|r| distance_m <= r))
+                .map(|(r, _)| format!("{}R", r))
+                .collect();
+            (!rings.is_empty()).then(|| EndangeredFriendly {
+                name: f.name.clone(),
+                distance_m,
+                rings,
+            })
+        })
+        .collect();
+    if endangered.is_empty() {
+        return;
+    }
+    endangered.sort_by(|a, b| a.distance_m.total_cmp(&b.distance_m));
+
+    let planned = selected.default_ring.clone();
+    let nearest = &endangered[0];
+    let safer = match planned {
+        Some(ring) if !nearest.rings.contains(&ring) => None,
+        Some(ring) => ring.trim_end_matches('R').parse().ok().and_then(|r| {
+            safer_fire(
+                solution,
+                effects,
+                r,
+                nearest.distance_m,
+                multiple,
+                substitutes,
+            )
+        }),
+        None => safer_fire(
+            solution,
+            effects,
+            RING_COUNT as Ring,
+            nearest.distance_m,
+            multiple,
+            substitutes,
+        ),
+    };
+
+    for f in &endangered {
+        solution.warnings.push(format!(
+            "Danger close: friendly '{}' {:.0} m from the target, inside the danger radius of {} ({} x dispersion)",
+            f.name,
+            f.distance_m,
+            f.rings.join(", "),
+            multiple
+        ));
+    }
+    if let Some(s) = &safer {
+        solution.warnings.push(format!(
+            "Danger close: {} {} keeps friendlies clear ({:.0} m margin)",
+            s.ammo_type, s.ring, s.margin_m
+        ));
+    }
+    solution.danger_close = Some(DangerClose {
+        multiple,
+        casualty_radius_m,
+        danger_radius_m,
+        friendlies: endangered,
+        safer_fire: safer,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calculate_solution_with_dispersion, BallisticPoint, BallisticTable, BallisticTables,
+        Dispersion, DispersionTable, MortarPosition, TargetPosition, TargetType, WeaponSystem,
+    };
+
+    fn table() -> BallisticTable {
+        BallisticTable {
+            points: vec![
+                BallisticPoint {
+                    range_m: 0.0,
+                    elev_mil: 1400.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+                BallisticPoint {
+                    range_m: 2000.0,
+                    elev_mil: 800.0,
+                    time_flight_s: None,
+                    delta_elev_per_100m_mil: None,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    /// Registre avec un obus explosif déclaré, indiqué contre l'infanterie.
+    fn registry() -> AmmoRegistry {
+        let json = r#"{
+            "version": 1,
+            "ammo": [{
+                "name": "M768_HE",
+                "designation": "M768",
+                "directory": "M768",
+                "rings": [0, 1],
+                "calibers": ["60mm"],
+                "suggested_targets": ["INF"]
+            }]
+        }"#;
+        AmmoRegistry::parse(json.as_bytes()).unwrap()
+    }
+
+    fn m768(registry: &AmmoRegistry) -> AmmoKind {
+        registry.parse_kind("M768_HE").unwrap()
+    }
+
+    #[test]
+    fn lower_ring_then_substitute_ammo_clear_the_friendly() {
+        let registry = registry();
+        let mut ballistics = BallisticTables::new();
+        let mut dispersions = DispersionTable::new();
+        for (ammo, ring, radius) in [
+            (AmmoKind::He, 1, 20.0),
+            (AmmoKind::He, 2, 40.0),
+            (AmmoKind::He, 3, 55.0),
+            (AmmoKind::Practice, 0, 5.0),
+            (m768(&registry), 0, 10.0),
+        ] {
+            ballistics.insert((WeaponSystem::M224, ammo, ring), table());
+            dispersions.insert((ammo, ring), Dispersion::circular(radius).into());
+        }
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let substitutes = substitute_ammo(&mortar, &target, &registry);
+        assert_eq!(substitutes, [m768(&registry)]);
+        let solution =
+            calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+        assert_eq!(
+            danger_radius_m(
+                &solution,
+                &EffectTable::new(),
+                AmmoKind::He,
+                3,
+                DEFAULT_DANGER_CLOSE_MULTIPLE
+            ),
+            Some(165.0)
+        );
+        let safer = |distance_m| {
+            safer_fire(
+                &solution,
+                &EffectTable::new(),
+                3,
+                distance_m,
+                DEFAULT_DANGER_CLOSE_MULTIPLE,
+                &substitutes,
+            )
+        };
+
+        // Ami à 130 m : 2R (120 m) suffit, c'est le moindre changement
+        let fire = safer(130.0).unwrap();
+        assert_eq!((fire.ammo_type.as_str(), fire.ring.as_str()), ("HE", "2R"));
+        assert!((fire.margin_m - 10.0).abs() < 1e-9);
+
+        // Ami à 50 m : aucun anneau explosif ne convient, l'obus M768 à 0R
+        // (30 m) le remplace ; l'exercice (15 m) n'a pas l'effet demandé
+        let fire = safer(50.0).unwrap();
+        assert_eq!(
+            (fire.ammo_type.as_str(), fire.ring.as_str()),
+            ("M768_HE", "0R")
+        );
+        assert!(safer(25.0).is_none());
+
+        // Sans remplacement accepté, seuls les anneaux inférieurs comptent
+        assert!(safer_fire(
+            &solution,
+            &EffectTable::new(),
+            3,
+            50.0,
+            DEFAULT_DANGER_CLOSE_MULTIPLE,
+            &[]
+        )
+        .is_none());
+
+        // Un anneau exclu par la pièce n'est pas proposé
+        mortar.excluded_rings.insert(2);
+        let solution =
+            calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+        let fire = safer_fire(
+            &solution,
+            &EffectTable::new(),
+            3,
+            130.0,
+            DEFAULT_DANGER_CLOSE_MULTIPLE,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(fire.ring, "1R");
+    }
+
+    #[test]
+    fn substitutes_suit_the_target_and_the_mortar() {
+        let registry = registry();
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let mut target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        assert_eq!(
+            substitute_ammo(&mortar, &target, &registry),
+            [m768(&registry)]
+        );
+
+        // L'obus M768 n'est pas indiqué contre les véhicules
+        target.target_type = TargetType::Vehicule;
+        assert!(!substitute_ammo(&mortar, &target, &registry).contains(&m768(&registry)));
+
+        // Une pièce de 81 mm ne tire pas l'obus de 60 mm
+        target.target_type = TargetType::Infanterie;
+        let mut m252 = mortar.clone();
+        m252.weapon = WeaponSystem::M252;
+        assert!(substitute_ammo(&m252, &target, &registry).is_empty());
+    }
+
+    #[test]
+    fn friendlies_inside_the_danger_radius_are_flagged() {
+        let registry = registry();
+        let mut ballistics = BallisticTables::new();
+        let mut dispersions = DispersionTable::new();
+        for (ammo, ring, radius) in [
+            (AmmoKind::He, 1, 20.0),
+            (AmmoKind::He, 2, 40.0),
+            (AmmoKind::He, 3, 55.0),
+            (AmmoKind::Practice, 0, 5.0),
+            (m768(&registry), 0, 10.0),
+        ] {
+            ballistics.insert((WeaponSystem::M224, ammo, ring), table());
+            dispersions.insert((ammo, ring), Dispersion::circular(radius).into());
+        }
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        mortar.default_ring = Some(3);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let solve = |mortar: &MortarPosition, friendlies: &[FriendlyPosition]| {
+            let mut solution =
+                calculate_solution_with_dispersion(mortar, &target, &ballistics, &dispersions);
+            attach_danger_close(
+                &mut solution,
+                &target,
+                friendlies,
+                &EffectTable::new(),
+                DEFAULT_DANGER_CLOSE_MULTIPLE,
+                &substitute_ammo(mortar, &target, &registry),
+            );
+            solution
+        };
+
+        // Ami à 200 m : hors de tous les rayons (60, 120, 165 m)
+        let far = FriendlyPosition::new("F2".into(), 0.0, 200.0, 1000.0);
+        assert!(solve(&mortar, std::slice::from_ref(&far))
+            .danger_close
+            .is_none());
+
+        // Ami à 100 m : menacé par 2R et 3R, 1R prévu à sa place
+        let near = FriendlyPosition::new("F1".into(), 0.0, 0.0, 1100.0);
+        let solution = solve(&mortar, &[far, near.clone()]);
+        let danger = solution.danger_close.unwrap();
+        assert_eq!(danger.friendlies.len(), 1);
+        assert_eq!(danger.friendlies[0].name, "F1");
+        assert_eq!(danger.friendlies[0].rings, ["2R", "3R"]);
+        assert_eq!(danger.danger_radius_m.get(3), Some(165.0));
+        assert_eq!(danger.safer_fire.unwrap().ring, "1R");
+        assert!(solution.warnings.iter().any(|w| w.contains("'F1'")));
+
+        // L'anneau prévu laisse l'ami à l'écart : signalé, sans remplacement
+        mortar.default_ring = Some(1);
+        let danger = solve(&mortar, &[near]).danger_close.unwrap();
+        assert!(danger.safer_fire.is_none());
+
+        // Ami à 50 m : seul l'obus M768 à 0R convient, pas l'exercice
+        let close = FriendlyPosition::new("F3".into(), 0.0, -50.0, 1000.0);
+        let danger = solve(&mortar, &[close]).danger_close.unwrap();
+        assert_eq!(danger.friendlies[0].rings, ["1R", "2R", "3R"]);
+        let safer = danger.safer_fire.unwrap();
+        assert_eq!(
+            (safer.ammo_type.as_str(), safer.ring.as_str()),
+            ("M768_HE", "0R")
+        );
+    }
+
+    #[test]
+    fn casualty_radius_widens_the_danger_radius() {
+        let mut ballistics = BallisticTables::new();
+        let mut dispersions = DispersionTable::new();
+        for (ring, radius) in [(1, 20.0), (3, 55.0)] {
+            ballistics.insert((WeaponSystem::M224, AmmoKind::He, ring), table());
+            dispersions.insert((AmmoKind::He, ring), Dispersion::circular(radius).into());
+        }
+        let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        mortar.default_ring = Some(3);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let mut effects = EffectTable::new();
+        effects.insert(
+            AmmoKind::He,
+            crate::EffectRadii {
+                casualty_m: Some(35.0),
+                ..Default::default()
+            },
+        );
+        let solution =
+            calculate_solution_with_dispersion(&mortar, &target, &ballistics, &dispersions);
+        assert_eq!(
+            danger_radius_m(&solution, &effects, AmmoKind::He, 3, 3.0),
+            Some(200.0)
+        );
+
+        // Ami à 180 m : hors de la seule dispersion (165 m), mais dans le
+        // rayon létal ajouté ; 1R (60 + 35 m) le laisse à l'écart
+        let friendly = FriendlyPosition::new("F1".into(), 0.0, 180.0, 1000.0);
+        let mut solution = solution;
+        attach_danger_close(
+            &mut solution,
+            &target,
+            std::slice::from_ref(&friendly),
+            &effects,
+            3.0,
+            &[],
+        );
+        let danger = solution.danger_close.unwrap();
+        assert_eq!(danger.casualty_radius_m, Some(35.0));
+        assert_eq!(danger.danger_radius_m.get(3), Some(200.0));
+        assert_eq!(danger.friendlies[0].rings, ["3R"]);
+        let safer = danger.safer_fire.unwrap();
+        assert_eq!(safer.ring, "1R");
+        assert_eq!(safer.danger_radius_m, 95.0);
+        assert_eq!(safer.margin_m, 85.0);
+    }
+}
@@ -1,34 +1,977 @@
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::Json,
-    routing::{delete, get, post},
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Json, Response},
+    routing::{any, delete, get, post, put},
     Router,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
-use tower_http::services::ServeDir;
+use tower_http::compression::CompressionLayer;
+use tower_http::services::{ServeDir, ServeFile};
 
+use crate::airspace::{attach_airspace_check, Airspace, AirspaceRestriction};
+use crate::ammo::AmmoRegistry;
+use crate::coverage::{coverage_grid, CoverageGrid, GridExtent, DEFAULT_CELL_M};
+use crate::dry_run::StateView;
+use crate::environment::{
+    attach_atmosphere, attach_charge_temperature, attach_wind_advice, attach_wind_correction,
+    Atmosphere, ChargeTemperatureTable, Wind, WindProfile, AIR_TEMPERATURE_RANGE_C,
+    CHARGE_TEMPERATURE_RANGE_C, PRESSURE_RANGE_HPA, WIND_SPEED_RANGE_MPS,
+};
+use crate::events::{EventBus, StateEvent};
+use crate::fire::{
+    render_volley_text, simulate_impacts, simulate_scatter, spot_round, volley_plan,
+    HitProbability, ScatterReport, SpotObservation, SpotReport, VolleyPlan,
+    DEFAULT_ROUND_INTERVAL_S,
+};
+use crate::markers::{parse_markers, ImportedMarker, Marker, MarkerImport, SkippedMarker};
+use crate::met::{parse_met, MetMessage};
+use crate::mission_pack::{build_mission_pack, reference_point_target, render_html, PackSolutions};
+use crate::observation::{
+    attach_angle_t, attach_observation_timing, backplot, intersect_bearings, origin_area,
+    BackplotEstimate, CraterAnalysis, Intersection, Observer, ObserverBearing, OriginArea,
+};
+use crate::reference::{KnownPointShift, ReferencePoint, ReferencePoints};
+use crate::registration::{register, Registration};
+use crate::replay::{expenditure, render_acmi, shot_events, GeoReference, ShotTrace};
+use crate::safety::{
+    attach_danger_close, substitute_ammo, FriendlyPosition, DANGER_CLOSE_MULTIPLE_RANGE,
+    DEFAULT_DANGER_CLOSE_MULTIPLE,
+};
+use crate::share::{generate_share_token, ShareFilter, ShareLink, SharedTarget};
+use crate::simulation::{Budget, SessionSeed, SimRng, TrainingOffset};
+use crate::snapshot::{
+    merge_airspace, merge_ammo_policy, merge_correction_presets, merge_friendlies,
+    merge_mission_log, merge_missions, merge_reference_points, merge_target_numbering,
+    read_snapshot, restore_snapshot, transfer_targets, write_snapshot, MergeStrategy,
+    RestoreReport, StateSnapshot, TransferMode, TransferReport, SNAPSHOT_VERSION,
+};
+use crate::terrain::{
+    attach_crest_clearance, attach_elevation_warning, ElevationThresholds, TerrainGeoref,
+    TerrainModel, TerrainProfile, TerrainSummary,
+};
+use crate::timeline::{
+    build_timeline, end_of_mission, plan_mission, EndOfMission, MissionLog, MissionPlans,
+    MissionSchedule, ScheduledMission, Timeline, DEFAULT_TIMELINE_PAST_S,
+    DEFAULT_TIMELINE_WINDOW_S,
+};
+use crate::trajectory::{trajectory_points, Trajectory, DEFAULT_TRAJECTORY_STEP_M};
 use crate::{
-    apply_correction, calculate_solution_with_dispersion, load_ballistics_from,
-    load_dispersion_from, AmmoKind, BallisticTable, DispersionTable, FiringSolution,
-    MortarPosition, Ring, TargetPosition, TargetType,
+    apply_correction, apply_correction_in_place, attach_drift, attach_effect_radius,
+    attach_staleness, ballistic_file_name, calculate_solution_with_dispersion, check_table,
+    data_layer, firing_data_correction, load_ballistics_layered, load_metrics_with, parse_ring,
+    read_ballistic_points, unix_now, AmmoIncompatibility, AmmoKind, AmmoPolicy, BallisticPoint,
+    BallisticTables, Caliber, Clearance, CorrectionPreset, CorrectionPresets, CorrectionRecord,
+    Declination, DispersionTable, DriftTable, EffectRadii, EffectTable, ElevationFallback,
+    ElevationSource, FileLoad, FiringDataCorrection, FiringSolution, IncrementTable, Interpolation,
+    LoadReport, Metrics, MortarPosition, MortarStatus, Position, RelayTarget, Reliability, Ring,
+    Sector, SignConvention, SolutionRecord, Staleness, TableReport, TargetNumbering,
+    TargetPosition, TargetType, WeaponSystem, CHARGE_EFFICIENCY_RANGE, DEFAULT_CURVE_STEP_M,
+    DENSE_STEP_M, DISPERSION_FACTOR_RANGE, MV_CORRECTION_RANGE_PCT, RING_COUNT,
 };
 
-fn default_ammo() -> String {
-    "HE".to_string()
-}
-
 // =====================
 // Application state
 // =====================
 pub struct AppState {
-    pub ballistics: BTreeMap<(AmmoKind, Ring), BallisticTable>,
-    pub dispersions: DispersionTable,
+    /// Ballistic tables, extended at runtime by uploads
+    pub ballistics: RwLock<BallisticTables>,
+    /// Interpolation of uploaded tables
+    pub interpolation: Interpolation,
+    /// Resample uploaded tables every meter
+    pub dense_tables: bool,
+    pub dispersions: RwLock<DispersionTable>,
+    /// Effect radii per ammo
+    pub effects: RwLock<EffectTable>,
+    /// Charge temperature range factors per ammo/ring
+    pub charge_temperature: RwLock<ChargeTemperatureTable>,
+    /// Charge increments consumed per round, per ammo/ring
+    pub increments: RwLock<IncrementTable>,
+    /// Drift curves per ammo/ring
+    pub drift: RwLock<DriftTable>,
+    /// Ammo of the loaded data: built-in plus declared in `ammo.json`
+    pub ammo: RwLock<AmmoRegistry>,
+    /// Data directory the tables were loaded from, reloaded by `reload`
+    pub data_path: Option<PathBuf>,
+    /// Partial data directories layered over `data_path`, in priority order
+    pub data_overlays: Vec<PathBuf>,
+    /// Drop inconsistent rows of reloaded tables
+    pub repair_tables: bool,
+    /// Find reloaded tables by scanning the ammo directories
+    pub scan_tables: bool,
+    /// Outcome of the last load of the ballistic files
+    pub load_report: RwLock<LoadReport>,
     pub mortars: RwLock<Vec<MortarPosition>>,
     pub targets: RwLock<Vec<TargetPosition>>,
+    /// Own troops, checked against the danger radius of solutions
+    pub friendlies: RwLock<Vec<FriendlyPosition>>,
+    /// Computed/fired solutions, keyed by target name
+    pub solution_history: RwLock<BTreeMap<String, Vec<SolutionRecord>>>,
+    /// State change notifications
+    pub events: EventBus,
+    /// Read-only share links, keyed by token
+    pub shares: RwLock<BTreeMap<String, ShareLink>>,
+    /// Corrections previewed and awaiting confirmation, keyed by token
+    pub pending_corrections: RwLock<BTreeMap<String, PendingCorrection>>,
+    /// Spectator mode: mutating endpoints and CLI commands are rejected
+    pub read_only: bool,
+    /// Sign convention of deviations entered in corrections
+    pub sign_convention: RwLock<SignConvention>,
+    /// Elevation of positions entered without one
+    pub elevation_fallback: ElevationFallback,
+    /// Heightmap of the area, read before the elevation fallback
+    pub terrain: RwLock<Option<TerrainModel>>,
+    /// Browser cache lifetime of static assets (0: always revalidate)
+    pub asset_max_age_s: u64,
+    /// Time budget of heavy calculations (simulations); `None`: unlimited
+    pub calc_budget: Option<Duration>,
+    /// Operation default ammo per target type
+    pub ammo_policy: RwLock<AmmoPolicy>,
+    /// Training: systematic error added to simulated impacts
+    pub training_offset: RwLock<Option<TrainingOffset>>,
+    /// Session seed of simulations without their own seed; `None`: clock
+    pub session_seed: RwLock<Option<SessionSeed>>,
+    /// Operation auto-numbering of targets created without a name
+    pub target_numbering: RwLock<TargetNumbering>,
+    /// Operation named corrections, applied by name
+    pub correction_presets: RwLock<CorrectionPresets>,
+    /// Declination stamped on printed products (magnetic azimuths)
+    pub declination: RwLock<Declination>,
+    /// Operation airspace ceilings checked against trajectories
+    pub airspace: RwLock<Airspace>,
+    /// Operation reference points (TRPs), kept apart from targets
+    pub reference_points: RwLock<ReferencePoints>,
+    /// Multiple of the adjusted dispersion within which friendlies are danger close
+    pub danger_close_multiple: RwLock<f64>,
+    /// Elevation differences beyond which solutions are flagged
+    pub elevation_thresholds: RwLock<ElevationThresholds>,
+    /// Operation propellant temperature (°C) applied to solutions; `None`: standard
+    pub charge_temperature_c: RwLock<Option<f64>>,
+    /// Operation wind applied to solutions; `None`: no wind correction
+    pub wind: RwLock<Option<WindProfile>>,
+    /// Operation air conditions applied to solutions; `None`: standard atmosphere
+    pub atmosphere: RwLock<Option<Atmosphere>>,
+    /// Scheduled fire missions, merged into the battery timeline
+    pub missions: RwLock<MissionSchedule>,
+    /// End-of-mission reports of closed missions, with their archived targets
+    pub mission_log: RwLock<MissionLog>,
+    /// Dry run: mutating CLI commands only report what they would change
+    pub dry_run: AtomicBool,
+    /// Rounds are only fired at targets with a clearance
+    pub require_clearance: bool,
+}
+
+/// Request inputs of a firing solution, over the operation's settings.
+#[derive(Clone, Debug, Default)]
+pub struct SolveOptions {
+    /// Observer position, used to compute Angle T and observation timing
+    pub observer: Option<Observer>,
+    /// Wind instead of the operation's wind
+    pub wind: Option<WindProfile>,
+    /// Propellant temperature in °C instead of the operation's
+    pub charge_temperature_c: Option<f64>,
+    /// Terrain profile along the gun-target line, checked for crests
+    pub terrain_profile: Option<TerrainProfile>,
+}
+
+/// Maximum number of solutions kept per target
+pub const MAX_SOLUTION_HISTORY: usize = 100;
+
+/// Lifetime of a correction preview's confirmation token, in seconds
+pub const CORRECTION_PREVIEW_TTL_S: u64 = 300;
+
+/// Maximum size of an uploaded heightmap
+pub const MAX_HEIGHTMAP_BYTES: usize = 64 * 1024 * 1024;
+
+impl AppState {
+    /// Empty operation over the given ballistic tables and metrics.
+    pub fn new(ballistics: BallisticTables, metrics: Metrics, options: &AppOptions) -> Self {
+        let Metrics {
+            dispersions,
+            effects,
+            charge_temperature,
+            increments,
+            drift,
+            ammo,
+            ..
+        } = metrics;
+        AppState {
+            ballistics: RwLock::new(ballistics),
+            interpolation: options.interpolation,
+            dense_tables: options.dense_tables,
+            dispersions: RwLock::new(dispersions),
+            effects: RwLock::new(effects),
+            charge_temperature: RwLock::new(charge_temperature),
+            increments: RwLock::new(increments),
+            drift: RwLock::new(drift),
+            ammo: RwLock::new(ammo),
+            data_path: None,
+            data_overlays: options.data_overlays.clone(),
+            repair_tables: options.repair_tables,
+            scan_tables: options.scan_tables,
+            load_report: RwLock::new(LoadReport::default()),
+            mortars: RwLock::new(Vec::new()),
+            targets: RwLock::new(Vec::new()),
+            friendlies: RwLock::new(Vec::new()),
+            solution_history: RwLock::new(BTreeMap::new()),
+            events: EventBus::new(),
+            shares: RwLock::new(BTreeMap::new()),
+            pending_corrections: RwLock::new(BTreeMap::new()),
+            read_only: options.read_only,
+            sign_convention: RwLock::new(options.sign_convention),
+            elevation_fallback: options.elevation_fallback,
+            terrain: RwLock::new(options.terrain.clone()),
+            asset_max_age_s: options.asset_max_age_s,
+            calc_budget: (options.calc_budget_ms > 0)
+                .then(|| Duration::from_millis(options.calc_budget_ms)),
+            ammo_policy: RwLock::new(AmmoPolicy::default()),
+            target_numbering: RwLock::new(TargetNumbering::default()),
+            correction_presets: RwLock::new(CorrectionPresets::default()),
+            declination: RwLock::new(options.declination),
+            airspace: RwLock::new(Airspace::default()),
+            reference_points: RwLock::new(ReferencePoints::default()),
+            danger_close_multiple: RwLock::new(DEFAULT_DANGER_CLOSE_MULTIPLE),
+            elevation_thresholds: RwLock::new(options.elevation_thresholds),
+            charge_temperature_c: RwLock::new(options.charge_temperature_c),
+            wind: RwLock::new(None),
+            atmosphere: RwLock::new(None),
+            missions: RwLock::new(MissionSchedule::default()),
+            mission_log: RwLock::new(MissionLog::default()),
+            training_offset: RwLock::new(None),
+            session_seed: RwLock::new(options.seed.map(SessionSeed::new)),
+            dry_run: AtomicBool::new(options.dry_run),
+            require_clearance: options.require_clearance,
+        }
+    }
+
+    /// Seed of the session, if simulations are reproducible.
+    pub async fn session_seed(&self) -> Option<u64> {
+        self.session_seed
+            .read()
+            .await
+            .as_ref()
+            .map(SessionSeed::seed)
+    }
+
+    /// Random generator of a simulation: the request seed if given, else the
+    /// next generator of the session seed, else the clock.
+    pub async fn sim_rng(&self, seed: Option<u64>) -> SimRng {
+        if let Some(seed) = seed {
+            return SimRng::new(seed);
+        }
+        match self.session_seed.write().await.as_mut() {
+            Some(session) => session.next_rng(),
+            None => SimRng::from_time(),
+        }
+    }
+
+    /// Firing solution of a mortar on a target, completed by
+    /// [`AppState::complete_solution`]. Every endpoint and CLI command
+    /// solving a target goes through here.
+    pub async fn solve(
+        &self,
+        mortar: &MortarPosition,
+        target: &TargetPosition,
+        ballistics: &BallisticTables,
+        options: &SolveOptions,
+    ) -> Result<FiringSolution, AmmoIncompatibility> {
+        mortar.check_ammo(target.ammo_type, &*self.ammo.read().await)?;
+        let mut solution = calculate_solution_with_dispersion(
+            mortar,
+            target,
+            ballistics,
+            &*self.dispersions.read().await,
+        );
+        self.complete_solution(&mut solution, mortar, target, ballistics, options)
+            .await;
+        Ok(solution)
+    }
+
+    /// Volley of a scheduled mission, laid from the completed solution of its
+    /// mortar on its target with the operation's increments and declination.
+    pub async fn plan_mission(
+        &self,
+        mission: &ScheduledMission,
+        mortars: &[MortarPosition],
+        targets: &[TargetPosition],
+        ballistics: &BallisticTables,
+    ) -> Result<VolleyPlan, String> {
+        let mortar = mortars
+            .iter()
+            .find(|m| m.name == mission.mortar)
+            .ok_or_else(|| format!("Mortar '{}' not found", mission.mortar))?;
+        let target = targets
+            .iter()
+            .find(|t| t.name == mission.target)
+            .ok_or_else(|| format!("Target '{}' not found", mission.target))?;
+        let solution = self
+            .solve(mortar, target, ballistics, &SolveOptions::default())
+            .await
+            .map_err(|e| e.to_string())?;
+        plan_mission(
+            mission,
+            mortar,
+            target,
+            &solution,
+            ballistics,
+            &*self.increments.read().await,
+            *self.declination.read().await,
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    /// Plans of every scheduled mission, for the timeline.
+    pub async fn plan_missions(&self) -> MissionPlans {
+        let schedule = self.missions.read().await.clone();
+        let mortars = self.mortars.read().await;
+        let targets = self.targets.read().await;
+        let ballistics = self.ballistics.read().await;
+        let mut plans = MissionPlans::new();
+        for (name, mission) in &schedule.0 {
+            let plan = self
+                .plan_mission(mission, &mortars, &targets, &ballistics)
+                .await;
+            plans.insert(name.clone(), plan);
+        }
+        plans
+    }
+
+    /// Solutions of every mortar on every target and reference point, for the
+    /// mission pack. Pairs whose ammo the mortar refuses are left out.
+    pub async fn pack_solutions(
+        &self,
+        mortars: &[MortarPosition],
+        targets: &[TargetPosition],
+        reference_points: &ReferencePoints,
+        ballistics: &BallisticTables,
+    ) -> PackSolutions {
+        let options = SolveOptions::default();
+        let mut solutions = PackSolutions::default();
+        for m in mortars {
+            for t in targets {
+                if let Ok(solution) = self.solve(m, t, ballistics, &options).await {
+                    solutions
+                        .targets
+                        .insert((m.name.clone(), t.name.clone()), solution);
+                }
+            }
+            for (name, point) in &reference_points.0 {
+                let t = reference_point_target(name, point);
+                if let Ok(solution) = self.solve(m, &t, ballistics, &options).await {
+                    solutions
+                        .reference_points
+                        .insert((m.name.clone(), name.clone()), solution);
+                }
+            }
+        }
+        solutions
+    }
+
+    /// Apply the operation's checks and corrections to a computed solution:
+    /// ammo policy, effect radius, drift, airspace, elevation difference,
+    /// danger close, crests, staleness, observer, wind, air conditions and
+    /// propellant temperature.
+    pub async fn complete_solution(
+        &self,
+        solution: &mut FiringSolution,
+        mortar: &MortarPosition,
+        target: &TargetPosition,
+        ballistics: &BallisticTables,
+        options: &SolveOptions,
+    ) {
+        self.ammo_policy
+            .read()
+            .await
+            .apply(solution, target.target_type);
+        attach_effect_radius(solution, &*self.effects.read().await);
+        attach_drift(solution, mortar, &*self.drift.read().await);
+        attach_airspace_check(
+            solution,
+            mortar,
+            target,
+            ballistics,
+            &*self.airspace.read().await,
+        );
+        attach_elevation_warning(solution, &*self.elevation_thresholds.read().await);
+        attach_danger_close(
+            solution,
+            target,
+            &self.friendlies.read().await,
+            &*self.effects.read().await,
+            *self.danger_close_multiple.read().await,
+            &substitute_ammo(mortar, target, &*self.ammo.read().await),
+        );
+        if let Some(profile) = &options.terrain_profile {
+            attach_crest_clearance(solution, mortar, ballistics, profile);
+        }
+        attach_staleness(solution, target, unix_now());
+        if let Some(observer) = &options.observer {
+            attach_angle_t(
+                solution,
+                &mortar.as_position(),
+                &target.as_position(),
+                &observer.as_position(),
+            );
+            attach_observation_timing(
+                solution,
+                &mortar.as_position(),
+                &target.as_position(),
+                &observer.as_position(),
+                ballistics,
+            );
+        }
+        let wind = match &options.wind {
+            Some(wind) => Some(wind.clone()),
+            None => self.wind.read().await.clone(),
+        };
+        if let Some(wind) = &wind {
+            attach_wind_advice(solution, target, &wind.surface);
+            attach_wind_correction(solution, mortar, ballistics, wind);
+        }
+        if let Some(atmosphere) = &*self.atmosphere.read().await {
+            attach_atmosphere(solution, mortar, ballistics, atmosphere);
+        }
+        let temperature_c = match options.charge_temperature_c {
+            Some(t) => Some(t),
+            None => *self.charge_temperature_c.read().await,
+        };
+        if let Some(temperature_c) = temperature_c {
+            attach_charge_temperature(
+                solution,
+                mortar,
+                ballistics,
+                &*self.charge_temperature.read().await,
+                temperature_c,
+            );
+        }
+    }
+
+    /// Reload the ballistic tables and metrics from the data directory and
+    /// swap them in at once. On error the current tables are kept.
+    ///
+    /// Tables uploaded at runtime are replaced by the files.
+    pub async fn reload(&self) -> Result<ReloadReport, String> {
+        let data_path = match &self.data_path {
+            Some(path) => path,
+            None => return Err("No data directory to reload from".to_string()),
+        };
+        let (mut ballistics, load_report) = load_ballistics_layered(
+            data_path,
+            &self.data_overlays,
+            self.repair_tables,
+            self.scan_tables,
+        )
+        .map_err(|e| format!("Failed to load ballistics: {e:#}"))?;
+        if ballistics.is_empty() {
+            return Err(format!(
+                "No ballistic table found in {}",
+                data_path.display()
+            ));
+        }
+        for table in ballistics.values_mut() {
+            table.interpolation = self.interpolation;
+            if self.dense_tables {
+                table.densify();
+            }
+        }
+        let registry = AmmoRegistry::load_layered(data_path, &self.data_overlays)
+            .map_err(|e| format!("Failed to load ammo: {e:#}"))?;
+        let metrics = load_metrics_with(
+            data_layer(data_path, &self.data_overlays, "metrics.json"),
+            registry,
+        )
+        .map_err(|e| format!("Failed to load metrics: {e:#}"))?;
+
+        // Same order as the handlers: ballistics first, then the metrics
+        let mut current_ballistics = self.ballistics.write().await;
+        let mut dispersions = self.dispersions.write().await;
+        let mut effects = self.effects.write().await;
+        let mut charge_temperature = self.charge_temperature.write().await;
+        let mut increments = self.increments.write().await;
+        let mut drift = self.drift.write().await;
+        let mut ammo = self.ammo.write().await;
+        let report = ReloadReport {
+            tables: ballistics.len(),
+            inconsistent_tables: load_report
+                .checks
+                .iter()
+                .map(|c| format!("{} {}R", c.ammo_type, c.ring))
+                .collect(),
+            repaired: self.repair_tables && !load_report.checks.is_empty(),
+            dispersions: metrics.dispersions.len(),
+            failed_files: load_report.failed().cloned().collect(),
+        };
+        *current_ballistics = ballistics;
+        *dispersions = metrics.dispersions;
+        *effects = metrics.effects;
+        *charge_temperature = metrics.charge_temperature;
+        *increments = metrics.increments;
+        *drift = metrics.drift;
+        *ammo = metrics.ammo;
+        *self.load_report.write().await = load_report;
+        Ok(report)
+    }
+
+    /// Whether mutating CLI commands run as a dry run.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.load(Ordering::Relaxed)
+    }
+
+    /// Throwaway copy of the state to try a command on, with its own event bus.
+    pub async fn scratch_copy(&self) -> AppState {
+        AppState {
+            ballistics: RwLock::new(self.ballistics.read().await.clone()),
+            interpolation: self.interpolation,
+            dense_tables: self.dense_tables,
+            dispersions: RwLock::new(self.dispersions.read().await.clone()),
+            effects: RwLock::new(self.effects.read().await.clone()),
+            charge_temperature: RwLock::new(self.charge_temperature.read().await.clone()),
+            increments: RwLock::new(self.increments.read().await.clone()),
+            drift: RwLock::new(self.drift.read().await.clone()),
+            ammo: RwLock::new(self.ammo.read().await.clone()),
+            data_path: self.data_path.clone(),
+            data_overlays: self.data_overlays.clone(),
+            repair_tables: self.repair_tables,
+            scan_tables: self.scan_tables,
+            load_report: RwLock::new(self.load_report.read().await.clone()),
+            mortars: RwLock::new(self.mortars.read().await.clone()),
+            targets: RwLock::new(self.targets.read().await.clone()),
+            friendlies: RwLock::new(self.friendlies.read().await.clone()),
+            solution_history: RwLock::new(self.solution_history.read().await.clone()),
+            events: EventBus::new(),
+            shares: RwLock::new(self.shares.read().await.clone()),
+            pending_corrections: RwLock::new(self.pending_corrections.read().await.clone()),
+            read_only: self.read_only,
+            sign_convention: RwLock::new(*self.sign_convention.read().await),
+            elevation_fallback: self.elevation_fallback,
+            terrain: RwLock::new(self.terrain.read().await.clone()),
+            asset_max_age_s: self.asset_max_age_s,
+            calc_budget: self.calc_budget,
+            ammo_policy: RwLock::new(self.ammo_policy.read().await.clone()),
+            training_offset: RwLock::new(*self.training_offset.read().await),
+            session_seed: RwLock::new(self.session_seed.read().await.clone()),
+            target_numbering: RwLock::new(self.target_numbering.read().await.clone()),
+            correction_presets: RwLock::new(self.correction_presets.read().await.clone()),
+            declination: RwLock::new(*self.declination.read().await),
+            airspace: RwLock::new(self.airspace.read().await.clone()),
+            reference_points: RwLock::new(self.reference_points.read().await.clone()),
+            danger_close_multiple: RwLock::new(*self.danger_close_multiple.read().await),
+            elevation_thresholds: RwLock::new(*self.elevation_thresholds.read().await),
+            charge_temperature_c: RwLock::new(*self.charge_temperature_c.read().await),
+            wind: RwLock::new(self.wind.read().await.clone()),
+            atmosphere: RwLock::new(*self.atmosphere.read().await),
+            missions: RwLock::new(self.missions.read().await.clone()),
+            mission_log: RwLock::new(self.mission_log.read().await.clone()),
+            dry_run: AtomicBool::new(false),
+            require_clearance: self.require_clearance,
+        }
+    }
+
+    /// Comparable view of the entities and settings, for dry-run reports.
+    pub async fn view(&self) -> StateView {
+        fn json<T: Serialize>(value: &T) -> serde_json::Value {
+            serde_json::to_value(value).unwrap_or_default()
+        }
+        StateView {
+            mortars: self
+                .mortars
+                .read()
+                .await
+                .iter()
+                .map(|m| (m.name.clone(), json(m)))
+                .collect(),
+            targets: self
+                .targets
+                .read()
+                .await
+                .iter()
+                .map(|t| (t.name.clone(), json(t)))
+                .collect(),
+            settings: BTreeMap::from([
+                ("sign_convention", json(&*self.sign_convention.read().await)),
+                ("ammo_policy", json(&*self.ammo_policy.read().await)),
+                ("training_offset", json(&*self.training_offset.read().await)),
+                ("session_seed", json(&self.session_seed().await)),
+                (
+                    "target_numbering",
+                    json(&*self.target_numbering.read().await),
+                ),
+                (
+                    "correction_presets",
+                    json(&*self.correction_presets.read().await),
+                ),
+                ("declination", json(&*self.declination.read().await)),
+                ("airspace", json(&*self.airspace.read().await)),
+                (
+                    "reference_points",
+                    json(&*self.reference_points.read().await),
+                ),
+                ("friendlies", json(&*self.friendlies.read().await)),
+                (
+                    "danger_close_multiple",
+                    json(&*self.danger_close_multiple.read().await),
+                ),
+                (
+                    "elevation_thresholds",
+                    json(&*self.elevation_thresholds.read().await),
+                ),
+                (
+                    "charge_temperature_c",
+                    json(&*self.charge_temperature_c.read().await),
+                ),
+                (
+                    "terrain",
+                    json(
+                        &self
+                            .terrain
+                            .read()
+                            .await
+                            .as_ref()
+                            .map(TerrainModel::summary),
+                    ),
+                ),
+                ("wind", json(&*self.wind.read().await)),
+                ("atmosphere", json(&*self.atmosphere.read().await)),
+                ("missions", json(&*self.missions.read().await)),
+                ("mission_log", json(&*self.mission_log.read().await)),
+            ]),
+            history: self
+                .solution_history
+                .read()
+                .await
+                .iter()
+                .map(|(target, records)| (target.clone(), records.len()))
+                .collect(),
+            shares: self.shares.read().await.keys().cloned().collect(),
+        }
+    }
+
+    /// Append a solution to a target's history, dropping the oldest beyond the cap.
+    /// Nothing is recorded in read-only mode.
+    pub async fn record_solution(&self, target_name: &str, record: SolutionRecord) {
+        if self.read_only {
+            return;
+        }
+        self.events.emit(StateEvent::SolutionCalculated {
+            mortar: record.mortar.clone(),
+            target: target_name.to_string(),
+            fired: record.fired,
+        });
+        let mut history = self.solution_history.write().await;
+        let entries = history.entry(target_name.to_string()).or_default();
+        entries.push(record);
+        if entries.len() > MAX_SOLUTION_HISTORY {
+            let excess = entries.len() - MAX_SOLUTION_HISTORY;
+            entries.drain(..excess);
+        }
+    }
+
+    /// Elevation for a new position at `(x, y)`: the requested one, otherwise
+    /// the heightmap if it covers the position, otherwise the configured
+    /// fallback over positions whose elevation was entered.
+    pub async fn fill_elevation(
+        &self,
+        elevation: Option<f64>,
+        x: f64,
+        y: f64,
+        mortars: &[MortarPosition],
+        targets: &[TargetPosition],
+    ) -> (f64, ElevationSource) {
+        match elevation {
+            Some(e) => (e, ElevationSource::Provided),
+            None => {
+                let terrain = self.terrain.read().await;
+                if let Some(e) = terrain.as_ref().and_then(|t| t.elevation_at(x, y)) {
+                    return (e, ElevationSource::Terrain);
+                }
+                let known: Vec<Position> = mortars
+                    .iter()
+                    .filter(|m| m.elevation_source.is_provided())
+                    .map(|m| m.as_position())
+                    .chain(
+                        targets
+                            .iter()
+                            .filter(|t| t.elevation_source.is_provided())
+                            .map(|t| t.as_position()),
+                    )
+                    .collect();
+                self.elevation_fallback.resolve(x, y, &known)
+            }
+        }
+    }
+
+    /// Ammo of a new target: the requested one, else the ammo policy entry
+    /// for its type, else HE. An unknown requested ammo falls back to HE.
+    pub async fn default_ammo(&self, requested: Option<&str>, target_type: TargetType) -> AmmoKind {
+        match requested {
+            Some(ammo) => self
+                .ammo
+                .read()
+                .await
+                .parse_kind(ammo)
+                .unwrap_or(AmmoKind::He),
+            None => self
+                .ammo_policy
+                .read()
+                .await
+                .get(target_type)
+                .unwrap_or(AmmoKind::He),
+        }
+    }
+
+    /// Name of a new target: the requested one, else the next free number
+    /// of the operation's target numbering.
+    pub async fn target_name(
+        &self,
+        requested: Option<String>,
+        targets: &[TargetPosition],
+    ) -> String {
+        match requested {
+            Some(name) => name,
+            None => self
+                .target_numbering
+                .write()
+                .await
+                .next_name(|name| targets.iter().any(|t| t.name == name)),
+        }
+    }
+
+    /// Capture the current mortars, targets, missions and operation settings.
+    pub async fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            created_at: unix_now(),
+            mortars: self.mortars.read().await.clone(),
+            targets: self.targets.read().await.clone(),
+            ammo_policy: self.ammo_policy.read().await.clone(),
+            target_numbering: self.target_numbering.read().await.clone(),
+            correction_presets: self.correction_presets.read().await.clone(),
+            airspace: self.airspace.read().await.clone(),
+            reference_points: self.reference_points.read().await.clone(),
+            friendlies: self.friendlies.read().await.clone(),
+            missions: self.missions.read().await.clone(),
+            mission_log: self.mission_log.read().await.clone(),
+            ..StateSnapshot::default()
+        }
+    }
+
+    /// Merge a snapshot into the current state (unchanged when `dry_run`).
+    pub async fn restore(
+        &self,
+        snapshot: &StateSnapshot,
+        strategy: MergeStrategy,
+        dry_run: bool,
+    ) -> RestoreReport {
+        let mut mortars = self.mortars.write().await;
+        let mut targets = self.targets.write().await;
+        let report = restore_snapshot(&mut mortars, &mut targets, snapshot, strategy, dry_run);
+        if !dry_run {
+            merge_ammo_policy(
+                &mut *self.ammo_policy.write().await,
+                &snapshot.ammo_policy,
+                strategy,
+            );
+            merge_target_numbering(
+                &mut *self.target_numbering.write().await,
+                &snapshot.target_numbering,
+                strategy,
+            );
+            merge_correction_presets(
+                &mut *self.correction_presets.write().await,
+                &snapshot.correction_presets,
+                strategy,
+            );
+            merge_airspace(
+                &mut *self.airspace.write().await,
+                &snapshot.airspace,
+                strategy,
+            );
+            merge_reference_points(
+                &mut *self.reference_points.write().await,
+                &snapshot.reference_points,
+                strategy,
+            );
+            merge_friendlies(
+                &mut *self.friendlies.write().await,
+                &snapshot.friendlies,
+                strategy,
+            );
+            merge_missions(
+                &mut *self.missions.write().await,
+                &snapshot.missions,
+                strategy,
+            );
+            merge_mission_log(
+                &mut *self.mission_log.write().await,
+                &snapshot.mission_log,
+                strategy,
+            );
+            self.events.emit(StateEvent::StateRestored {
+                mortars: mortars.len(),
+                targets: targets.len(),
+            });
+        }
+        report
+    }
+
+    /// Close a mission: its end-of-mission report is appended to the mission
+    /// log and its target is removed from the active targets.
+    pub async fn close_mission(
+        &self,
+        name: &str,
+        effect: String,
+        remarks: Option<String>,
+    ) -> Result<EndOfMission, String> {
+        // Targets before missions, as when scheduling or restoring
+        let mut targets = self.targets.write().await;
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .0
+            .get(name)
+            .ok_or_else(|| format!("Mission '{}' not found", name))?;
+        let index = targets
+            .iter()
+            .position(|t| t.name == mission.target)
+            .ok_or_else(|| format!("Target '{}' not found", mission.target))?;
+        // The history is copied out: it is never held while waiting on
+        // another lock
+        let history = self
+            .solution_history
+            .read()
+            .await
+            .get(&mission.target)
+            .cloned()
+            .unwrap_or_default();
+        let report = end_of_mission(
+            name,
+            mission,
+            targets.remove(index),
+            &history,
+            effect,
+            remarks,
+            unix_now(),
+        );
+        missions.0.remove(name);
+        self.events.emit(StateEvent::MissionRemoved {
+            name: name.to_string(),
+        });
+        self.events.emit(StateEvent::TargetRemoved {
+            name: report.target.name.clone(),
+        });
+        self.mission_log.write().await.0.push(report.clone());
+        Ok(report)
+    }
+
+    /// Convention to apply: the per-request override, or the configured one.
+    pub async fn sign_convention(&self, requested: Option<SignConvention>) -> SignConvention {
+        match requested {
+            Some(c) => c,
+            None => *self.sign_convention.read().await,
+        }
+    }
+
+    /// Add the markers of a map export as targets, typed from their icon.
+    /// Markers whose name is already taken are skipped.
+    pub async fn import_markers(
+        &self,
+        markers: &[Marker],
+        reference: Option<&GeoReference>,
+        default_type: TargetType,
+    ) -> MarkerImport {
+        let mortars = self.mortars.read().await;
+        let mut targets = self.targets.write().await;
+        let mut report = MarkerImport::default();
+        for marker in markers {
+            let (x, y) = match marker.local_m(reference) {
+                Some(xy) => xy,
+                None => {
+                    report.skipped.push(SkippedMarker {
+                        name: marker.name.clone(),
+                        reason: "Latitude/longitude without a reference point".to_string(),
+                    });
+                    continue;
+                }
+            };
+            if let Some(name) = marker
+                .name
+                .as_ref()
+                .filter(|n| targets.iter().any(|t| &t.name == *n))
+            {
+                report.skipped.push(SkippedMarker {
+                    name: Some(name.clone()),
+                    reason: format!("Target '{}' already exists", name),
+                });
+                continue;
+            }
+            let (target_type, icon_matched) = marker.target_type(default_type);
+            let ammo_type = self.default_ammo(None, target_type).await;
+            let name = self.target_name(marker.name.clone(), &targets).await;
+            let (elevation, elevation_source) =
+                self.fill_elevation(None, x, y, &mortars, &targets).await;
+            let mut target =
+                TargetPosition::new(name.clone(), elevation, x, y, target_type, ammo_type);
+            target.elevation_source = elevation_source;
+            targets.push(target.clone());
+            self.events.emit(StateEvent::TargetAdded { target });
+            report.imported.push(ImportedMarker {
+                name,
+                x,
+                y,
+                target_type,
+                icon: marker.icon.clone(),
+                icon_matched,
+            });
+        }
+        report
+    }
+
+    /// Copy or move targets between the current state and another operation.
+    pub async fn transfer_targets(
+        &self,
+        operation: &mut StateSnapshot,
+        direction: TransferDirection,
+        names: &[String],
+        mode: TransferMode,
+        strategy: MergeStrategy,
+    ) -> TransferReport {
+        let mut targets = self.targets.write().await;
+        match direction {
+            TransferDirection::Import => {
+                let report =
+                    transfer_targets(&mut operation.targets, &mut targets, names, mode, strategy);
+                for t in targets.iter() {
+                    if report.targets.added.contains(&t.name) {
+                        self.events
+                            .emit(StateEvent::TargetAdded { target: t.clone() });
+                    } else if report.targets.overwritten.contains(&t.name) {
+                        self.events
+                            .emit(StateEvent::TargetUpdated { target: t.clone() });
+                    }
+                }
+                for name in &report.targets.removed {
+                    self.events
+                        .emit(StateEvent::TargetRemoved { name: name.clone() });
+                }
+                report
+            }
+            TransferDirection::Export => {
+                let report =
+                    transfer_targets(&mut targets, &mut operation.targets, names, mode, strategy);
+                for name in &report.removed_from_source {
+                    self.events
+                        .emit(StateEvent::TargetRemoved { name: name.clone() });
+                }
+                report
+            }
+        }
+    }
 }
 
 // =====================
@@ -38,32 +981,76 @@ pub struct AppState {
 pub struct CalculateByNameRequest {
     pub mortar_name: String,
     pub target_name: String,
+    /// Observer position, used to compute Angle T
+    #[serde(default)]
+    pub observer: Option<Observer>,
+    /// Surface wind, used for smoke employment advice and the wind correction
+    /// (omitted: the operation's wind, if set)
+    #[serde(default)]
+    pub wind: Option<Wind>,
+    /// Propellant temperature in °C, for the charge temperature correction
+    /// (omitted: the operation's charge temperature, if set)
+    #[serde(default)]
+    pub charge_temperature_c: Option<f64>,
+    /// Terrain profile along the gun-target line, checked against the
+    /// trajectory of each ring
+    #[serde(default)]
+    pub terrain_profile: Option<TerrainProfile>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AddMortarRequest {
     pub name: String,
-    pub elevation: f64,
+    /// Omitted: filled by the configured elevation fallback
+    #[serde(default)]
+    pub elevation: Option<f64>,
     pub x: f64,
     pub y: f64,
+    /// Omitted: 60mm
+    #[serde(default)]
+    pub caliber: Option<String>,
+    /// Weapon system (M224, M252, M120); takes precedence over `caliber`
+    #[serde(default)]
+    pub weapon: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AddTargetRequest {
-    pub name: String,
-    pub elevation: f64,
+    /// Omitted: next name of the operation's target numbering
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Omitted: filled by the configured elevation fallback
+    #[serde(default)]
+    pub elevation: Option<f64>,
     pub x: f64,
     pub y: f64,
     #[serde(default = "default_target_type")]
     pub target_type: String,
-    #[serde(default = "default_ammo")]
-    pub ammo_type: String,
+    /// Omitted: the operation's ammo policy for the target type, else HE
+    #[serde(default)]
+    pub ammo_type: Option<String>,
+    #[serde(default)]
+    pub reporting_source: Option<String>,
+    #[serde(default)]
+    pub reliability: Option<String>,
 }
 
 fn default_target_type() -> String {
     "INFANTERIE".to_string()
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AddFriendlyRequest {
+    pub name: String,
+    /// Omitted: filled by the configured elevation fallback
+    #[serde(default)]
+    pub elevation: Option<f64>,
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DeletePositionRequest {
     pub name: String,
@@ -75,17 +1062,332 @@ pub struct UpdateTargetTypeRequest {
     pub target_type: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RestoreRequest {
+    pub snapshot: StateSnapshot,
+    #[serde(default)]
+    pub strategy: MergeStrategy,
+    /// Report what would change without modifying the state
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Direction of a target transfer, seen from the current state
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferDirection {
+    /// From the given operation into the current state
+    Import,
+    /// From the current state into the given operation
+    Export,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferTargetsRequest {
+    /// The other operation
+    pub operation: StateSnapshot,
+    pub direction: TransferDirection,
+    /// Target names; empty for all targets
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default)]
+    pub mode: TransferMode,
+    #[serde(default)]
+    pub strategy: MergeStrategy,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportMarkersRequest {
+    /// Map export: GeoJSON or a list of markers
+    pub markers: serde_json::Value,
+    /// Origin of the local coordinates, required for latitude/longitude markers
+    #[serde(default)]
+    pub reference: Option<GeoReference>,
+    /// Target type of markers whose icon is not recognized (default: INFANTERIE)
+    #[serde(default)]
+    pub default_type: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferTargetsResponse {
+    pub report: TransferReport,
+    /// The other operation after the transfer, to be saved by the caller
+    pub operation: StateSnapshot,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateShareRequest {
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Shared target names; empty for all targets
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// Shared target types; empty for all types
+    #[serde(default)]
+    pub target_types: Vec<String>,
+    #[serde(default)]
+    pub min_reliability: Option<String>,
+    #[serde(default)]
+    pub max_age_s: Option<u64>,
+    /// Link lifetime in seconds; omitted for a permanent link
+    #[serde(default)]
+    pub ttl_s: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveMortarRequest {
+    pub name: String,
+    /// Omitted: filled by the configured elevation fallback
+    #[serde(default)]
+    pub elevation: Option<f64>,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveFriendlyRequest {
+    pub name: String,
+    /// Omitted: filled by the configured elevation fallback
+    #[serde(default)]
+    pub elevation: Option<f64>,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisplacementsResponse {
+    pub mortar: MortarPosition,
+    /// Re-lay data towards every target, from the last move
+    pub relay: Vec<RelayTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterMortarRequest {
+    pub name: String,
+    /// Known point fired at
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub elevation: f64,
+    /// Observed deviation of the impact from the point
+    pub observation: SpotObservation,
+    /// Overrides the configured sign convention (e.g. "N+/E+")
+    #[serde(default)]
+    pub sign_convention: Option<SignConvention>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegistrationResponse {
+    pub mortar: String,
+    /// `null` when the mortar has no registration
+    pub registration: Option<Registration>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMortarRingsRequest {
+    pub name: String,
+    /// Rings unavailable on this mortar; empty to re-enable all
+    #[serde(default)]
+    pub excluded_rings: Vec<Ring>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMortarChargeEfficiencyRequest {
+    pub name: String,
+    /// Fraction of the table range actually reached (e.g. 0.97); null for nominal charges
+    #[serde(default)]
+    pub charge_efficiency: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMortarDispersionFactorRequest {
+    pub name: String,
+    /// Ring to update; every ring of the weapon when absent
+    #[serde(default)]
+    pub ring: Option<Ring>,
+    /// Multiplier of the table dispersions (e.g. 1.5); null for table dispersions
+    #[serde(default)]
+    pub factor: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMortarBiasRequest {
+    pub name: String,
+    /// Muzzle velocity deviation of the tube in % (e.g. -2.0 for a worn tube); null for a new tube
+    #[serde(default)]
+    pub mv_correction_pct: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMortarStatusRequest {
+    pub name: String,
+    /// READY, MOVING, OUT_OF_ACTION or RESUPPLY
+    pub status: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMortarSectorRequest {
+    pub name: String,
+    /// Omitted or null: remove the sector
+    #[serde(default)]
+    pub sector: Option<Sector>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMortarDefaultRingRequest {
+    pub name: String,
+    /// Ring used when none is specified; null to clear
+    #[serde(default)]
+    pub default_ring: Option<Ring>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateTargetAmmoRequest {
     pub name: String,
     pub ammo_type: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateTargetIntelRequest {
+    pub name: String,
+    #[serde(default)]
+    pub reporting_source: Option<String>,
+    #[serde(default)]
+    pub reliability: Option<String>,
+    /// Observation time (Unix seconds); defaults to now
+    #[serde(default)]
+    pub last_observed: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TargetClearanceRequest {
+    pub name: String,
+    pub cleared_by: String,
+    /// Clearance time (Unix seconds); defaults to now
+    #[serde(default)]
+    pub cleared_at: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TargetListQuery {
+    /// Only targets at least this reliable
+    #[serde(default)]
+    pub min_reliability: Option<String>,
+    /// Only targets observed within this many seconds
+    #[serde(default)]
+    pub max_age_s: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MissionPackQuery {
+    /// "html" (default) or "json"
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Assign targets regardless of the mortars' sectors of fire
+    #[serde(default)]
+    pub override_sectors: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleMissionRequest {
+    pub mortar_name: String,
+    pub target_name: String,
+    /// Time of the fire command (Unix seconds)
+    #[serde(default)]
+    pub start: Option<u64>,
+    /// Time of the fire command, in seconds from now
+    #[serde(default)]
+    pub start_in_s: Option<u64>,
+    #[serde(default = "default_tubes")]
+    pub tubes: u32,
+    pub rounds_per_tube: u32,
+    #[serde(default = "default_round_interval")]
+    pub round_interval_s: f64,
+    #[serde(default)]
+    pub ring: Option<Ring>,
+    /// Fire outside the mortar's sector of fire
+    #[serde(default)]
+    pub override_sector: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EndOfMissionRequest {
+    /// Effect achieved, as reported by the observer
+    pub effect: String,
+    #[serde(default)]
+    pub remarks: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TimelineQuery {
+    /// Seconds ahead of now (default 600)
+    #[serde(default)]
+    pub window_s: Option<u64>,
+    /// Seconds of past events kept (default 60)
+    #[serde(default)]
+    pub past_s: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ShotLogQuery {
+    /// "json" (default) or "acmi"
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Latitude of the local origin for ACMI export (degrees)
+    #[serde(default)]
+    pub ref_lat: Option<f64>,
+    /// Longitude of the local origin for ACMI export (degrees)
+    #[serde(default)]
+    pub ref_lon: Option<f64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CorrectionRequest {
     pub target_name: String,
-    pub vertical_m: f64,   // North (negative) / South (positive)
-    pub horizontal_m: f64, // West (negative) / East (positive)
+    #[serde(default)]
+    pub vertical_m: Option<f64>, // North (negative) / South (positive) by default
+    #[serde(default)]
+    pub horizontal_m: Option<f64>, // West (negative) / East (positive) by default
+    /// Named correction to apply instead of `vertical_m`/`horizontal_m`
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Move the original target instead of creating a `_C` copy
+    #[serde(default)]
+    pub in_place: bool,
+    /// Overrides the configured sign convention (e.g. "N+/E+")
+    #[serde(default)]
+    pub sign_convention: Option<SignConvention>,
+    /// Mortar whose solution on the corrected target is returned by a preview
+    #[serde(default)]
+    pub mortar_name: Option<String>,
+    /// Token of a previous preview: applies the previewed correction, the
+    /// deviation fields are ignored
+    #[serde(default)]
+    pub confirmation_token: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CorrectionQuery {
+    /// Compute the correction without applying it
+    #[serde(default)]
+    pub preview: bool,
+}
+
+/// Correction computed by a preview, applied when its token is confirmed.
+#[derive(Clone, Debug)]
+pub struct PendingCorrection {
+    pub target_name: String,
+    /// Target position at preview time; confirmation fails if it has moved
+    pub from_x: f64,
+    pub from_y: f64,
+    /// Deviation as entered, in the request convention
+    pub input_m: (f64, f64),
+    /// Deviation in the standard convention
+    pub standard_m: (f64, f64),
+    pub in_place: bool,
+    pub sign_convention: SignConvention,
+    pub preset: Option<String>,
+    pub expires_at: u64,
 }
 
 #[derive(Debug, Serialize)]
@@ -94,6 +1396,22 @@ pub struct CorrectionResponse {
     pub original: String,
     pub corrected: String,
     pub correction_applied: CorrectionApplied,
+    /// Convention the deviation was read in
+    pub sign_convention: SignConvention,
+    /// Named correction that was applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// `true` when nothing was applied: confirm with `confirmation_token`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub preview: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation_token: Option<String>,
+    /// Expiry of the confirmation token (Unix seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Solution of `mortar_name` on the corrected target (previews only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solution: Option<FiringSolution>,
 }
 
 #[derive(Debug, Serialize)]
@@ -104,98 +1422,1057 @@ pub struct CorrectionApplied {
     pub new_y: f64,
 }
 
-#[derive(Debug, Serialize)]
-pub struct HealthResponse {
-    pub status: String,
-    pub version: String,
+#[derive(Debug, Deserialize)]
+pub struct AdjustFireRequest {
+    pub mortar_name: String,
+    pub target_name: String,
+    pub vertical_m: f64,   // North (negative) / South (positive) by default
+    pub horizontal_m: f64, // West (negative) / East (positive) by default
+    /// Ring in use; defaults to the lowest ring covering the range
+    #[serde(default)]
+    pub ring: Option<Ring>,
+    /// Overrides the configured sign convention (e.g. "N+/E+")
+    #[serde(default)]
+    pub sign_convention: Option<SignConvention>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct AmmoTypesResponse {
-    pub ammo_types: Vec<AmmoTypeInfo>,
+pub struct AdjustFireResponse {
+    pub mortar: String,
+    pub target: String,
+    pub ammo_type: String,
+    pub ring: Ring,
+    pub correction: FiringDataCorrection,
+    /// Convention the deviation was read in
+    pub sign_convention: SignConvention,
 }
 
-#[derive(Debug, Serialize)]
-pub struct AmmoTypeInfo {
-    pub name: String,
-    pub rings: Vec<u8>,
+#[derive(Debug, Deserialize)]
+pub struct SpotRoundRequest {
+    pub mortar_name: String,
+    pub target_name: String,
+    #[serde(default)]
+    pub ring: Option<Ring>,
+    /// Observed deviation; when omitted the impact is simulated
+    #[serde(default)]
+    pub observation: Option<SpotObservation>,
+    /// Seed for the simulated impact
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Overrides the configured sign convention (e.g. "N+/E+")
+    #[serde(default)]
+    pub sign_convention: Option<SignConvention>,
 }
 
+/// Spot report, with the deviation expressed in the request's sign convention
 #[derive(Debug, Serialize)]
-pub struct MortarListResponse {
-    pub positions: Vec<MortarPosition>,
+pub struct SpotRoundResponse {
+    #[serde(flatten)]
+    pub report: SpotReport,
+    pub sign_convention: SignConvention,
 }
 
-#[derive(Debug, Serialize)]
-pub struct TargetListResponse {
-    pub positions: Vec<TargetPosition>,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeedSetting {
+    /// `null` when simulations are seeded from the clock
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChargeTemperatureSetting {
+    /// `null` when solutions use the standard temperature of the tables
+    pub temperature_c: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WindSetting {
+    /// `null` when solutions are not corrected for wind
+    pub wind: Option<WindProfile>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct SuccessResponse {
-    pub success: bool,
+pub struct TerrainSetting {
+    /// `null` when no heightmap is loaded
+    pub terrain: Option<TerrainSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetRequest {
+    /// METCM or METB3 message, groups separated by spaces or new lines
     pub message: String,
 }
 
 #[derive(Debug, Serialize)]
-pub struct ErrorResponse {
-    pub error: String,
+pub struct MetResponse {
+    pub message: MetMessage,
+    /// Operation wind now applied to solutions
+    pub wind: WindProfile,
+    /// Operation air conditions now applied to solutions
+    pub atmosphere: Atmosphere,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AtmosphereSetting {
+    /// `null` when solutions assume the standard atmosphere of the tables
+    pub atmosphere: Option<Atmosphere>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct TypesResponse {
-    pub ammo_types: Vec<String>,
-    pub target_types: Vec<String>,
+pub struct TrainingOffsetSetting {
+    /// `null` when training mode is off
+    pub training_offset: Option<TrainingOffset>,
 }
 
-// =====================
-// Router builder
-// =====================
+#[derive(Debug, Deserialize)]
+pub struct AmmoPolicyRequest {
+    /// Target type -> ammo, e.g. `{ "VEHICULE": "SMOKE" }`; replaces the policy
+    #[serde(default)]
+    pub policy: BTreeMap<String, String>,
+}
 
-pub fn build_app_with_state(data_path: &str, web_path: &str) -> (Router, Arc<AppState>) {
-    let ballistics = load_ballistics_from(data_path).unwrap_or_else(|e| {
-        eprintln!("Warning: failed to load ballistics: {e}");
-        BTreeMap::new()
-    });
+#[derive(Debug, Serialize)]
+pub struct AmmoPolicyResponse {
+    /// Ammo set by the operation, per target type
+    pub policy: BTreeMap<String, String>,
+    /// Resulting recommendation for every target type
+    pub recommended: BTreeMap<String, String>,
+}
 
-    let dispersions = load_dispersion_from(data_path).unwrap_or_else(|e| {
-        eprintln!("Warning: failed to load dispersions: {e}");
-        DispersionTable::new()
-    });
+impl From<&AmmoPolicy> for AmmoPolicyResponse {
+    fn from(policy: &AmmoPolicy) -> Self {
+        AmmoPolicyResponse {
+            policy: policy
+                .0
+                .iter()
+                .map(|(t, a)| (t.as_str().to_string(), a.as_str().to_string()))
+                .collect(),
+            recommended: TargetType::all()
+                .iter()
+                .map(|t| {
+                    (
+                        t.as_str().to_string(),
+                        policy.recommended(*t).as_str().to_string(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
 
-    let state = Arc::new(AppState {
-        ballistics,
-        dispersions,
-        mortars: RwLock::new(Vec::new()),
-        targets: RwLock::new(Vec::new()),
-    });
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DangerCloseSetting {
+    /// Multiple of the adjusted dispersion defining the danger radius
+    pub multiple: f64,
+}
 
-    // IMPORTANT: build as Router<Arc<AppState>> (missing state), then provide it and end as Router<()>.
-    let app: Router<Arc<AppState>> = Router::new()
-        // Health & info
-        .route("/api/health", get(health_check))
+#[derive(Debug, Serialize)]
+pub struct DeclinationSetting {
+    #[serde(flatten)]
+    pub declination: Declination,
+    /// Grid-magnetic angle in mils (east positive)
+    pub grid_magnetic_angle_mil: f64,
+}
+
+impl From<Declination> for DeclinationSetting {
+    fn from(declination: Declination) -> Self {
+        DeclinationSetting {
+            declination,
+            grid_magnetic_angle_mil: declination.grid_magnetic_angle_mil(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorrectionPresetRequest {
+    pub vertical_m: f64,
+    pub horizontal_m: f64,
+    /// Convention of the deviation (default: the configured one)
+    #[serde(default)]
+    pub sign_convention: Option<SignConvention>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TargetNumberingRequest {
+    /// Two letters, e.g. "AB"
+    pub prefix: String,
+    /// Next number (1-9999); omitted: the current counter is kept
+    #[serde(default)]
+    pub next: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TargetNumberingResponse {
+    pub prefix: String,
+    pub next: u32,
+    /// Name given to the next target created without one (if still free)
+    pub next_name: String,
+}
+
+impl From<&TargetNumbering> for TargetNumberingResponse {
+    fn from(numbering: &TargetNumbering) -> Self {
+        TargetNumberingResponse {
+            prefix: numbering.prefix.clone(),
+            next: numbering.next,
+            next_name: numbering.format(numbering.next),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignConventionSetting {
+    pub sign_convention: SignConvention,
+    /// Human-readable form, e.g. "Nord(-)/Sud(+) Ouest(-)/Est(+)"
+    #[serde(default, skip_deserializing)]
+    pub label: String,
+}
+
+impl From<SignConvention> for SignConventionSetting {
+    fn from(sign_convention: SignConvention) -> Self {
+        SignConventionSetting {
+            sign_convention,
+            label: sign_convention.label().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScatterRequest {
+    pub mortar_name: String,
+    pub target_name: String,
+    #[serde(default)]
+    pub ring: Option<Ring>,
+    /// Number of impacts to sample
+    #[serde(default = "default_scatter_count")]
+    pub count: usize,
+    /// Seed for reproducible scatter
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Time budget in milliseconds, capped by the server budget
+    #[serde(default)]
+    pub budget_ms: Option<u64>,
+}
+
+fn default_scatter_count() -> usize {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SimulateRequest {
+    pub mortar_name: String,
+    pub target_name: String,
+    /// Radius around the aim point counted as a hit, in meters
+    pub target_radius_m: f64,
+    /// Ring to simulate (default: the ring preferred by the mortar)
+    #[serde(default)]
+    pub ring: Option<Ring>,
+    /// Number of impacts to sample
+    #[serde(default = "default_simulate_count")]
+    pub count: usize,
+    /// Seed for reproducible sampling
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Time budget in milliseconds, capped by the server budget
+    #[serde(default)]
+    pub budget_ms: Option<u64>,
+}
+
+fn default_simulate_count() -> usize {
+    5000
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VolleyRequest {
+    pub mortar_name: String,
+    pub target_name: String,
+    #[serde(default)]
+    pub ring: Option<Ring>,
+    /// Number of tubes firing from the mortar position
+    #[serde(default = "default_tubes")]
+    pub tubes: u32,
+    pub rounds_per_tube: u32,
+    /// Seconds between two rounds of the same tube
+    #[serde(default = "default_round_interval")]
+    pub round_interval_s: f64,
+}
+
+fn default_tubes() -> u32 {
+    1
+}
+
+fn default_round_interval() -> f64 {
+    DEFAULT_ROUND_INTERVAL_S
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CoverageQuery {
+    pub mortar: String,
+    pub ammo: String,
+    /// Omitted: the mortar's default ring
+    #[serde(default)]
+    pub ring: Option<Ring>,
+    /// Grid cell size in meters (default 50)
+    #[serde(default)]
+    pub cell_m: Option<f64>,
+    /// Grid extent; omitted: square around the mortar covering the ring's maximum range
+    #[serde(default)]
+    pub min_x: Option<f64>,
+    #[serde(default)]
+    pub min_y: Option<f64>,
+    #[serde(default)]
+    pub max_x: Option<f64>,
+    #[serde(default)]
+    pub max_y: Option<f64>,
+    /// Include cells outside the mortar's sector of fire
+    #[serde(default)]
+    pub override_sector: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CurveQuery {
+    /// Sampling step in meters (default 10)
+    #[serde(default)]
+    pub step: Option<f64>,
+    /// Omitted: M224
+    #[serde(default)]
+    pub weapon: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrajectoryQuery {
+    pub ammo: String,
+    pub ring: String,
+    pub range_m: f64,
+    /// Target height above the mortar in meters (default 0)
+    #[serde(default)]
+    pub rise_m: f64,
+    /// Sampling step in meters (default 10)
+    #[serde(default)]
+    pub step_m: Option<f64>,
+    /// Omitted: M224
+    #[serde(default)]
+    pub weapon: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CurveResponse {
+    pub weapon: WeaponSystem,
+    pub ammo_type: String,
+    pub ring: String,
+    /// Interpolation the points were sampled with
+    pub interpolation: Interpolation,
+    pub step_m: f64,
+    pub points: Vec<BallisticPoint>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct VolleyQuery {
+    /// "json" (default) or "text"
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IntersectTargetRequest {
+    /// Omitted: next name of the operation's target numbering
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Exactly two observer bearings to the target
+    pub observers: Vec<ObserverBearing>,
+    #[serde(default)]
+    pub elevation: Option<f64>,
+    #[serde(default = "default_target_type")]
+    pub target_type: String,
+    /// Omitted: the operation's ammo policy for the target type, else HE
+    #[serde(default)]
+    pub ammo_type: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntersectTargetResponse {
+    pub success: bool,
+    pub target: TargetPosition,
+    pub intersection: Intersection,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShiftTargetRequest {
+    /// Omitted: next name of the operation's target numbering
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Reference point the observer shifts from
+    pub from: String,
+    pub shift: KnownPointShift,
+    #[serde(default = "default_target_type")]
+    pub target_type: String,
+    /// Omitted: the operation's ammo policy for the target type, else HE
+    #[serde(default)]
+    pub ammo_type: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShiftTargetResponse {
+    pub success: bool,
+    pub target: TargetPosition,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackplotRequest {
+    /// Omitted: next name of the operation's target numbering
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Bearings to observed enemy firing signatures
+    #[serde(default)]
+    pub bearings: Vec<ObserverBearing>,
+    /// Crater analyses (back-azimuth, optional range)
+    #[serde(default)]
+    pub craters: Vec<CraterAnalysis>,
+    #[serde(default)]
+    pub elevation: Option<f64>,
+    #[serde(default = "default_target_type")]
+    pub target_type: String,
+    /// Omitted: the operation's ammo policy for the target type, else HE
+    #[serde(default)]
+    pub ammo_type: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackplotResponse {
+    pub success: bool,
+    pub target: TargetPosition,
+    pub estimate: BackplotEstimate,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CraterTargetRequest {
+    /// Omitted: next name of the operation's target numbering
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Crater analyses (back-azimuth, range or range band to the origin)
+    pub craters: Vec<CraterAnalysis>,
+    #[serde(default)]
+    pub elevation: Option<f64>,
+    #[serde(default = "default_target_type")]
+    pub target_type: String,
+    /// Omitted: the operation's ammo policy for the target type, else HE
+    #[serde(default)]
+    pub ammo_type: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CraterTargetResponse {
+    pub success: bool,
+    pub target: TargetPosition,
+    pub area: OriginArea,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SolutionHistoryResponse {
+    pub target: String,
+    pub solutions: Vec<SolutionRecord>,
+    /// Mean observed deviation over fired rounds
+    pub mean_deviation: Option<SpotObservation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareListResponse {
+    pub shares: Vec<ShareLink>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedViewResponse {
+    pub label: Option<String>,
+    pub expires_at: Option<u64>,
+    pub targets: Vec<SharedTarget>,
+}
+
+/// Outcome of a data directory reload.
+#[derive(Debug, Serialize)]
+pub struct ReloadReport {
+    /// Ballistic tables loaded
+    pub tables: usize,
+    /// Tables with inconsistencies, e.g. "HE 2R"
+    pub inconsistent_tables: Vec<String>,
+    /// Inconsistent rows were dropped (`--repair-tables`)
+    pub repaired: bool,
+    /// Dispersion entries loaded
+    pub dispersions: usize,
+    /// Expected ballistic files that could not be loaded
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed_files: Vec<FileLoad>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    pub status: String,
+    pub version: String,
+    pub read_only: bool,
+    pub ballistic_files: BallisticFilesHealth,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BallisticFilesHealth {
+    /// Expected files loaded at startup or last reload
+    pub loaded: usize,
+    /// Expected files missing or unreadable
+    pub failed: Vec<FileLoad>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AmmoTypesResponse {
+    pub ammo_types: Vec<AmmoTypeInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AmmoTypeInfo {
+    pub name: String,
+    pub designation: String,
+    pub aliases: Vec<String>,
+    pub rings: Vec<u8>,
+    /// Weapon systems with at least one table for this ammo
+    pub weapons: Vec<WeaponSystem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effect: Option<EffectRadii>,
+    /// Target types this ammo is suggested for
+    pub suggested_targets: Vec<TargetType>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadBallisticsRequest {
+    /// Omitted: M224
+    #[serde(default)]
+    pub weapon: Option<String>,
+    pub ammo_type: String,
+    pub ring: Ring,
+    /// Table in the CSV format of the data directory
+    pub csv: String,
+    /// Drop inconsistent rows instead of rejecting the table
+    #[serde(default)]
+    pub repair: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadBallisticsResponse {
+    pub weapon: WeaponSystem,
+    pub ammo_type: AmmoKind,
+    pub ring: Ring,
+    /// Quality report of the table as inserted
+    pub report: TableReport,
+    /// Ranges of the rows dropped by `repair`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dropped_ranges_m: Vec<f64>,
+    /// A table for this ammo and ring was already loaded
+    pub replaced: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MortarListResponse {
+    pub positions: Vec<MortarPosition>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FriendlyListResponse {
+    pub positions: Vec<FriendlyPosition>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TargetListResponse {
+    pub positions: Vec<TargetListEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TargetListEntry {
+    #[serde(flatten)]
+    pub target: TargetPosition,
+    /// Intel staleness, when the observation time is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staleness: Option<Staleness>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuccessResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TypesResponse {
+    pub ammo_types: Vec<String>,
+    pub target_types: Vec<String>,
+}
+
+// =====================
+// Router builder
+// =====================
+
+/// Options used when building the application.
+#[derive(Clone, Debug, Default)]
+pub struct AppOptions {
+    /// Drop inconsistent rows from ballistic tables at load time
+    pub repair_tables: bool,
+    /// Find ballistic tables by scanning the ammo directories instead of fixed file names
+    pub scan_tables: bool,
+    /// Partial data directories layered over the data directory, in priority order
+    pub data_overlays: Vec<PathBuf>,
+    /// Spectator mode: reject every request that would modify the state
+    pub read_only: bool,
+    /// Initial sign convention of correction deviations
+    pub sign_convention: SignConvention,
+    /// Elevation of positions entered without one
+    pub elevation_fallback: ElevationFallback,
+    /// Heightmap of the area, read before the elevation fallback
+    pub terrain: Option<TerrainModel>,
+    /// Initial declination and grid convergence of the area
+    pub declination: Declination,
+    /// Initial elevation difference thresholds of solution warnings
+    pub elevation_thresholds: ElevationThresholds,
+    /// Initial propellant temperature (°C) applied to solutions (`None`: standard)
+    pub charge_temperature_c: Option<f64>,
+    /// Browser cache lifetime of static assets other than HTML (0: always revalidate)
+    pub asset_max_age_s: u64,
+    /// Time budget of heavy calculations in milliseconds (0: unlimited)
+    pub calc_budget_ms: u64,
+    /// Interpolation of elevations between ballistic table rows
+    pub interpolation: Interpolation,
+    /// Resample ballistic tables every meter at load time for direct lookups
+    pub dense_tables: bool,
+    /// Start the CLI in dry-run mode
+    pub dry_run: bool,
+    /// Refuse to fire at targets without a clearance
+    pub require_clearance: bool,
+    /// Session seed of simulations, for reproducible exercises (`None`: clock)
+    pub seed: Option<u64>,
+}
+
+/// POST endpoints that only compute and are still served in read-only mode
+pub const READ_ONLY_POST_ROUTES: &[&str] = &[
+    "/api/calculate",
+    "/api/targets/adjust",
+    "/api/fire/scatter",
+    "/api/fire/volley",
+    "/api/simulate",
+];
+
+/// Reject mutating requests with 403 when the server is read-only.
+async fn reject_mutations(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let method = req.method();
+    let allowed = method == Method::GET
+        || method == Method::HEAD
+        || method == Method::OPTIONS
+        || (method == Method::POST && READ_ONLY_POST_ROUTES.contains(&req.uri().path()));
+    if state.read_only && !allowed {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "Server is in read-only mode".to_string(),
+            }),
+        )
+            .into_response();
+    }
+    next.run(req).await
+}
+
+pub fn build_app_with_state(data_path: &str, web_path: &str) -> (Router, Arc<AppState>) {
+    build_app_with_options(data_path, web_path, &AppOptions::default())
+}
+
+pub fn build_app_with_options(
+    data_path: &str,
+    web_path: &str,
+    options: &AppOptions,
+) -> (Router, Arc<AppState>) {
+    let (ballistics, metrics, report) = load_tables(data_path, options);
+    let mut state = AppState::new(ballistics, metrics, options);
+    state.data_path = Some(PathBuf::from(data_path));
+    state.load_report = RwLock::new(report);
+    let state = Arc::new(state);
+    (build_router(state.clone(), web_path), state)
+}
+
+/// Load the ballistic tables and metrics of `data_path` and its overlays,
+/// reporting issues on stderr.
+pub fn load_tables(
+    data_path: &str,
+    options: &AppOptions,
+) -> (BallisticTables, Metrics, LoadReport) {
+    let (mut ballistics, report) = load_ballistics_layered(
+        data_path,
+        &options.data_overlays,
+        options.repair_tables,
+        options.scan_tables,
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("Warning: failed to load ballistics: {e}");
+        (BTreeMap::new(), LoadReport::default())
+    });
+    for file in report.failed() {
+        eprintln!(
+            "Warning: {} {}R table not loaded ({}): {}",
+            file.ammo_type,
+            file.ring,
+            file.path,
+            file.error.as_deref().unwrap_or("unknown error")
+        );
+    }
+    for table in ballistics.values_mut() {
+        table.interpolation = options.interpolation;
+        if options.dense_tables {
+            table.densify();
+        }
+    }
+    for check in &report.checks {
+        for issue in &check.issues {
+            eprintln!(
+                "Warning: {} {}R table: {}",
+                check.ammo_type, check.ring, issue
+            );
+        }
+        if options.repair_tables {
+            let ranges: Vec<String> = check
+                .dropped
+                .iter()
+                .map(|p| format!("{}", p.range_m))
+                .collect();
+            eprintln!(
+                "Repaired {} {}R table: dropped {} row(s) [{}]",
+                check.ammo_type,
+                check.ring,
+                check.dropped.len(),
+                ranges.join(", ")
+            );
+        }
+    }
+    if !report.checks.is_empty() && !options.repair_tables {
+        eprintln!(
+            "Warning: inconsistent ballistic tables loaded as-is (use --repair-tables to fix)"
+        );
+    }
+
+    let metrics_path = data_layer(
+        std::path::Path::new(data_path),
+        &options.data_overlays,
+        "metrics.json",
+    );
+    let metrics = AmmoRegistry::load_layered(data_path, &options.data_overlays)
+        .and_then(|registry| load_metrics_with(metrics_path, registry))
+        .unwrap_or_else(|e| {
+            eprintln!("Warning: failed to load metrics: {e:#}");
+            Metrics::default()
+        });
+    (ballistics, metrics, report)
+}
+
+/// Routes of the API and web interface over an existing state.
+pub fn build_router(state: Arc<AppState>, web_path: &str) -> Router {
+    // IMPORTANT: build as Router<Arc<AppState>> (missing state), then provide it and end as Router<()>.
+    let app: Router<Arc<AppState>> = Router::new()
+        // Health & info
+        .route("/api/health", get(health_check))
         .route("/api/types", get(get_types))
         .route("/api/ammo-types", get(get_ammo_types))
+        .route("/api/ballistics/upload", post(upload_ballistics))
+        .route("/api/ballistics/:ammo/:ring/curve", get(ballistic_curve))
+        .route("/api/ballistics/:ammo/:ring/csv", get(ballistic_csv))
+        .route("/api/trajectory", get(trajectory))
+        .route("/api/admin/reload", post(reload_data))
         // Calculate
         .route("/api/calculate", post(calculate_by_name))
         // Mortars CRUD
         .route("/api/mortars", get(list_mortars))
         .route("/api/mortars", post(add_mortar))
         .route("/api/mortars", delete(delete_mortar))
+        .route("/api/mortars/rings", post(update_mortar_rings))
+        .route("/api/mortars/move", post(move_mortar))
+        .route(
+            "/api/mortars/:name/displacements",
+            get(mortar_displacements),
+        )
+        .route(
+            "/api/mortars/default-ring",
+            post(update_mortar_default_ring),
+        )
+        .route(
+            "/api/mortars/charge-efficiency",
+            post(update_mortar_charge_efficiency),
+        )
+        .route(
+            "/api/mortars/dispersion-factor",
+            post(update_mortar_dispersion_factor),
+        )
+        .route("/api/mortars/bias", post(update_mortar_bias))
+        .route("/api/mortars/status", post(update_mortar_status))
+        .route("/api/mortars/sector", post(update_mortar_sector))
+        .route("/api/mortars/registration", post(register_mortar))
+        .route("/api/mortars/:name/registration", get(mortar_registration))
+        .route(
+            "/api/mortars/:name/registration",
+            delete(clear_mortar_registration),
+        )
         // Targets CRUD
+        .route("/api/friendlies", get(list_friendlies))
+        .route("/api/friendlies", post(add_friendly))
+        .route("/api/friendlies", delete(delete_friendly))
+        .route("/api/friendlies/move", post(move_friendly))
         .route("/api/targets", get(list_targets))
         .route("/api/targets", post(add_target))
         .route("/api/targets", delete(delete_target))
         .route("/api/targets/type", post(update_target_type))
         .route("/api/targets/ammo", post(update_target_ammo))
+        .route("/api/targets/intel", post(update_target_intel))
+        .route("/api/targets/clearance", post(clear_target))
+        .route("/api/targets/clearance", delete(revoke_target_clearance))
         .route("/api/targets/correct", post(correct_target))
-        // Static files
-        .nest_service("/", ServeDir::new(web_path));
+        .route("/api/targets/adjust", post(adjust_fire))
+        .route("/api/targets/intersect", post(intersect_target))
+        .route("/api/targets/backplot", post(backplot_target))
+        .route("/api/targets/craters", post(crater_target))
+        .route("/api/targets/shift", post(shift_target))
+        .route("/api/targets/:name/solutions", get(target_solutions))
+        // Fire
+        .route("/api/fire/spot", post(fire_spot))
+        .route("/api/fire/scatter", post(fire_scatter))
+        .route("/api/fire/volley", post(fire_volley))
+        .route("/api/simulate", post(simulate_hit_probability))
+        .route("/api/coverage", get(mortar_coverage))
+        // State snapshots
+        .route("/api/state", get(export_state))
+        .route("/api/state/restore", post(restore_state))
+        .route("/api/targets/transfer", post(transfer_operation_targets))
+        .route("/api/targets/import", post(import_markers))
+        // Settings
+        .route("/api/settings/sign-convention", get(get_sign_convention))
+        .route("/api/settings/sign-convention", put(set_sign_convention))
+        .route("/api/settings/ammo-policy", get(get_ammo_policy))
+        .route("/api/settings/ammo-policy", put(set_ammo_policy))
+        .route("/api/settings/training-offset", get(get_training_offset))
+        .route("/api/settings/training-offset", put(set_training_offset))
+        .route(
+            "/api/settings/training-offset",
+            delete(clear_training_offset),
+        )
+        .route("/api/settings/seed", get(get_seed))
+        .route("/api/settings/seed", put(set_seed))
+        .route("/api/settings/seed", delete(clear_seed))
+        .route("/api/settings/danger-close", get(get_danger_close))
+        .route("/api/settings/danger-close", put(set_danger_close))
+        .route("/api/settings/declination", get(get_declination))
+        .route("/api/settings/declination", put(set_declination))
+        .route(
+            "/api/settings/elevation-thresholds",
+            get(get_elevation_thresholds),
+        )
+        .route(
+            "/api/settings/elevation-thresholds",
+            put(set_elevation_thresholds),
+        )
+        .route(
+            "/api/settings/charge-temperature",
+            get(get_charge_temperature),
+        )
+        .route(
+            "/api/settings/charge-temperature",
+            put(set_charge_temperature),
+        )
+        .route(
+            "/api/settings/charge-temperature",
+            delete(clear_charge_temperature),
+        )
+        .route("/api/environment/wind", get(get_wind))
+        .route("/api/environment/wind", put(set_wind))
+        .route("/api/environment/wind", delete(clear_wind))
+        .route("/api/environment/terrain", get(get_terrain))
+        .route(
+            "/api/environment/terrain",
+            put(set_terrain).layer(DefaultBodyLimit::max(MAX_HEIGHTMAP_BYTES)),
+        )
+        .route("/api/environment/terrain", delete(clear_terrain))
+        .route("/api/environment/met", post(load_met))
+        .route("/api/environment/atmosphere", get(get_atmosphere))
+        .route("/api/environment/atmosphere", put(set_atmosphere))
+        .route("/api/environment/atmosphere", delete(clear_atmosphere))
+        .route("/api/settings/target-numbering", get(get_target_numbering))
+        .route("/api/settings/target-numbering", put(set_target_numbering))
+        .route(
+            "/api/settings/correction-presets",
+            get(get_correction_presets),
+        )
+        .route(
+            "/api/settings/correction-presets/:name",
+            put(set_correction_preset),
+        )
+        .route(
+            "/api/settings/correction-presets/:name",
+            delete(delete_correction_preset),
+        )
+        .route("/api/settings/airspace", get(get_airspace))
+        .route(
+            "/api/settings/airspace/:name",
+            put(set_airspace_restriction),
+        )
+        .route(
+            "/api/settings/airspace/:name",
+            delete(delete_airspace_restriction),
+        )
+        // Reference points (TRPs)
+        .route("/api/reference-points", get(list_reference_points))
+        .route("/api/reference-points/:name", put(set_reference_point))
+        .route(
+            "/api/reference-points/:name",
+            delete(delete_reference_point),
+        )
+        // Scheduled missions and battery timeline
+        .route("/api/missions", get(get_missions))
+        .route("/api/missions/:name", put(schedule_mission))
+        .route("/api/missions/:name", delete(delete_mission))
+        .route("/api/missions/:name/eom", post(end_mission))
+        .route("/api/mission-log", get(get_mission_log))
+        .route("/api/timeline", get(timeline))
+        // Printable mission pack
+        .route("/api/mission-pack", get(mission_pack))
+        // Shot log replay export
+        .route("/api/shots", get(shot_log))
+        // Read-only share links
+        .route("/api/shares", get(list_shares))
+        .route("/api/shares", post(create_share))
+        .route("/api/shares/:token", delete(revoke_share))
+        .route("/api/share/:token", get(shared_view))
+        // Unknown API paths are JSON 404s, never the web interface
+        .route("/api/*path", any(api_not_found))
+        // Static files, with index.html for client-side routes
+        .fallback_service(static_assets(web_path, state.asset_max_age_s))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            reject_mutations,
+        ))
+        .layer(middleware::from_fn(api_cache_control))
+        // gzip/brotli per Accept-Encoding (small bodies and event streams are left as is)
+        .layer(CompressionLayer::new());
 
     // Provide the Arc<AppState>, choose new “missing state” = () so we return Router (Router<()>).
-    let app: Router = app.with_state::<()>(state.clone());
+    let app: Router = app.with_state::<()>(state);
+
+    app
+}
+
+/// Static files of `web_path`; unknown paths serve `index.html` (single-page app routing).
+///
+/// The directory is read on every request, so its content can be replaced while running.
+fn static_assets(web_path: &str, max_age_s: u64) -> Router {
+    let index = std::path::Path::new(web_path).join("index.html");
+    Router::new()
+        .fallback_service(ServeDir::new(web_path).fallback(ServeFile::new(index)))
+        .layer(middleware::map_response(
+            move |response: Response| async move { with_cache_control(response, max_age_s) },
+        ))
+}
+
+/// HTML is always revalidated so a new frontend is picked up; other assets
+/// are cached for `max_age_s` seconds.
+fn with_cache_control(mut response: Response, max_age_s: u64) -> Response {
+    if !response.status().is_success() {
+        return response;
+    }
+    let is_html = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/html"));
+    let value = if is_html || max_age_s == 0 {
+        HeaderValue::from_static("no-cache")
+    } else {
+        match HeaderValue::from_str(&format!("public, max-age={}", max_age_s)) {
+            Ok(v) => v,
+            Err(_) => return response,
+        }
+    };
+    response.headers_mut().insert(header::CACHE_CONTROL, value);
+    response
+}
+
+/// API data reflects the live state: clients and proxies must revalidate it,
+/// unless the handler chose its own policy.
+async fn api_cache_control(request: Request, next: Next) -> Response {
+    let is_api = request.uri().path().starts_with("/api/");
+    let mut response = next.run(request).await;
+    if is_api && !response.headers().contains_key(header::CACHE_CONTROL) {
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    }
+    response
+}
+
+async fn api_not_found(Path(path): Path<String>) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse {
+            error: format!("Unknown API endpoint: /api/{}", path),
+        }),
+    )
+}
+
+/// Periodically write the state to `path` (atomic rename) until the task is aborted.
+pub fn spawn_autosave(
+    state: Arc<AppState>,
+    path: PathBuf,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately; skip it so startup does not overwrite the file
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let snapshot = state.snapshot().await;
+            let path = path.clone();
+            let result =
+                tokio::task::spawn_blocking(move || write_snapshot(&path, &snapshot)).await;
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("Warning: autosave failed: {e:#}"),
+                Err(e) => eprintln!("Warning: autosave task failed: {e}"),
+            }
+        }
+    })
+}
 
-    (app, state)
+/// Periodically reload the state from `path`, written by another instance, until the task is aborted.
+///
+/// Used by read-only instances to mirror the tactical picture; the file is
+/// only reloaded when its `created_at` changes.
+pub fn spawn_mirror(
+    state: Arc<AppState>,
+    path: PathBuf,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        let mut last_loaded = None;
+        loop {
+            ticker.tick().await;
+            let path = path.clone();
+            let result = tokio::task::spawn_blocking(move || read_snapshot(&path)).await;
+            match result {
+                Ok(Ok(snapshot)) if last_loaded != Some(snapshot.created_at) => {
+                    state
+                        .restore(&snapshot, MergeStrategy::ReplaceAll, false)
+                        .await;
+                    last_loaded = Some(snapshot.created_at);
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => eprintln!("Warning: mirror reload failed: {e:#}"),
+                Err(e) => eprintln!("Warning: mirror task failed: {e}"),
+            }
+        }
+    })
 }
 
 pub fn build_app(data_path: &str, web_path: &str) -> Router {
@@ -206,17 +2483,26 @@ pub fn build_app(data_path: &str, web_path: &str) -> Router {
 // Handlers
 // =====================
 
-pub async fn health_check() -> Json<HealthResponse> {
+pub async fn health_check(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
+    let report = state.load_report.read().await;
     Json(HealthResponse {
         status: "ok".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
+        read_only: state.read_only,
+        ballistic_files: BallisticFilesHealth {
+            loaded: report.files.iter().filter(|f| f.loaded).count(),
+            failed: report.failed().cloned().collect(),
+        },
     })
 }
 
-pub async fn get_types() -> Json<TypesResponse> {
+pub async fn get_types(State(state): State<Arc<AppState>>) -> Json<TypesResponse> {
     Json(TypesResponse {
-        ammo_types: AmmoKind::all()
-            .iter()
+        ammo_types: state
+            .ammo
+            .read()
+            .await
+            .kinds()
             .map(|a| a.as_str().to_string())
             .collect(),
         target_types: TargetType::all()
@@ -227,17 +2513,39 @@ pub async fn get_types() -> Json<TypesResponse> {
 }
 
 pub async fn get_ammo_types(State(state): State<Arc<AppState>>) -> Json<AmmoTypesResponse> {
+    let ballistics = state.ballistics.read().await;
+    let registry = state.ammo.read().await;
     let mut ammo_types = Vec::new();
 
-    for kind in AmmoKind::all() {
-        let rings: Vec<u8> = (0..=4)
-            .filter(|r| state.ballistics.contains_key(&(*kind, *r)))
+    for info in &registry.ammo {
+        let kind = info.kind;
+        let rings: Vec<u8> = (0..RING_COUNT as Ring)
+            .filter(|r| {
+                WeaponSystem::all()
+                    .iter()
+                    .any(|w| ballistics.contains_key(&(*w, kind, *r)))
+            })
+            .collect();
+        let weapons: Vec<WeaponSystem> = WeaponSystem::all()
+            .iter()
+            .copied()
+            .filter(|w| {
+                ballistics
+                    .range((*w, kind, 0)..=(*w, kind, Ring::MAX))
+                    .next()
+                    .is_some()
+            })
             .collect();
 
         if !rings.is_empty() {
             ammo_types.push(AmmoTypeInfo {
-                name: kind.as_str().to_string(),
+                name: info.name.clone(),
+                designation: info.designation.clone(),
+                aliases: info.aliases.clone(),
                 rings,
+                weapons,
+                effect: state.effects.read().await.get(&kind).copied(),
+                suggested_targets: info.suggested_targets.clone(),
             });
         }
     }
@@ -245,280 +2553,3503 @@ pub async fn get_ammo_types(State(state): State<Arc<AppState>>) -> Json<AmmoType
     Json(AmmoTypesResponse { ammo_types })
 }
 
-pub async fn calculate_by_name(
+/// Reload the data directory without restarting the server.
+pub async fn reload_data(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ReloadReport>, (StatusCode, Json<ErrorResponse>)> {
+    match state.reload().await {
+        Ok(report) => Ok(Json(report)),
+        Err(error) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error }),
+        )),
+    }
+}
+
+/// Add or replace a ballistic table without restarting the server.
+pub async fn upload_ballistics(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UploadBallisticsRequest>,
+) -> Result<Json<UploadBallisticsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let ammo = match state.ammo.read().await.parse_kind(&req.ammo_type) {
+        Some(ammo) => ammo,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Unknown ammo type: {}", req.ammo_type),
+                }),
+            ))
+        }
+    };
+    let weapon = match req.weapon.as_deref() {
+        Some(w) => match WeaponSystem::parse_str(w) {
+            Some(weapon) => weapon,
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Unknown weapon system: {}", w),
+                    }),
+                ))
+            }
+        },
+        None => WeaponSystem::default(),
+    };
+    if usize::from(req.ring) >= weapon.ring_count() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Invalid ring: {} (expected 0 to {})",
+                    req.ring,
+                    weapon.ring_count() - 1
+                ),
+            }),
+        ));
+    }
+    let points = match read_ballistic_points(req.csv.as_bytes()) {
+        Ok(points) => points,
+        Err(e) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Invalid CSV: {}", e),
+                }),
+            ))
+        }
+    };
+
+    let (mut table, issues, dropped) = check_table(points, req.repair);
+    if !issues.is_empty() && !req.repair {
+        let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: format!("Inconsistent table: {}", issues.join("; ")),
+            }),
+        ));
+    }
+    if table.points.len() < 2 {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: format!("Table needs at least 2 rows, got {}", table.points.len()),
+            }),
+        ));
+    }
+
+    table.interpolation = state.interpolation;
+    if state.dense_tables {
+        table.densify();
+    }
+    let report = table.validate();
+    let replaced = state
+        .ballistics
+        .write()
+        .await
+        .insert((weapon, ammo, req.ring), table)
+        .is_some();
+
+    Ok(Json(UploadBallisticsResponse {
+        weapon,
+        ammo_type: ammo,
+        ring: req.ring,
+        report,
+        dropped_ranges_m: dropped.iter().map(|p| p.range_m).collect(),
+        replaced,
+    }))
+}
+
+pub async fn calculate_by_name(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CalculateByNameRequest>,
+) -> Result<Json<FiringSolution>, (StatusCode, Json<ErrorResponse>)> {
+    if req.terrain_profile.as_ref().is_some_and(|p| !p.is_valid()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Invalid terrain profile: ranges must be positive and heights finite"
+                    .to_string(),
+            }),
+        ));
+    }
+    let mortars = state.mortars.read().await;
+    let targets = state.targets.read().await;
+    let ballistics = state.ballistics.read().await;
+
+    let mortar = mortars.iter().find(|m| m.name == req.mortar_name);
+    let target = targets.iter().find(|t| t.name == req.target_name);
+
+    match (mortar, target) {
+        (Some(m), Some(t)) => {
+            let options = SolveOptions {
+                observer: req.observer,
+                wind: req.wind.map(WindProfile::from),
+                charge_temperature_c: req.charge_temperature_c,
+                terrain_profile: req.terrain_profile,
+            };
+            let solution = match state.solve(m, t, &ballistics, &options).await {
+                Ok(solution) => solution,
+                Err(e) => {
+                    return Err((
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        Json(ErrorResponse {
+                            error: e.to_string(),
+                        }),
+                    ))
+                }
+            };
+            let record = SolutionRecord {
+                timestamp: unix_now(),
+                mortar: m.name.clone(),
+                fired: false,
+                deviation: None,
+                solution: solution.clone(),
+                shot: None,
+            };
+            let target_name = t.name.clone();
+            drop(ballistics);
+            drop(targets);
+            drop(mortars);
+            state.record_solution(&target_name, record).await;
+            Ok(Json(solution))
+        }
+        (None, _) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Mortar '{}' not found", req.mortar_name),
+            }),
+        )),
+        (_, None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Target '{}' not found", req.target_name),
+            }),
+        )),
+    }
+}
+
+pub async fn list_mortars(State(state): State<Arc<AppState>>) -> Json<MortarListResponse> {
+    let mortars = state.mortars.read().await;
+    Json(MortarListResponse {
+        positions: mortars.clone(),
+    })
+}
+
+pub async fn add_mortar(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AddMortarRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if req.name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Name cannot be empty".to_string(),
+            }),
+        ));
+    }
+
+    let weapon = match (req.weapon.as_deref(), req.caliber.as_deref()) {
+        (Some(w), _) => match WeaponSystem::parse_str(w) {
+            Some(weapon) => weapon,
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Unknown weapon system: {}", w),
+                    }),
+                ))
+            }
+        },
+        (None, Some(c)) => match Caliber::parse_str(c) {
+            Some(caliber) => WeaponSystem::from_caliber(caliber),
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Invalid caliber: {}", c),
+                    }),
+                ))
+            }
+        },
+        (None, None) => WeaponSystem::default(),
+    };
+
+    let mut mortars = state.mortars.write().await;
+
+    if mortars.iter().any(|m| m.name == req.name) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Mortar '{}' already exists", req.name),
+            }),
+        ));
+    }
+
+    let (elevation, elevation_source) = {
+        let targets = state.targets.read().await;
+        state
+            .fill_elevation(req.elevation, req.x, req.y, &mortars, &targets)
+            .await
+    };
+    let mut mortar = MortarPosition::new(req.name.clone(), elevation, req.x, req.y);
+    mortar.elevation_source = elevation_source;
+    mortar.weapon = weapon;
+    mortars.push(mortar.clone());
+    state.events.emit(StateEvent::MortarAdded { mortar });
+
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Mortar '{}' added", req.name),
+    }))
+}
+
+pub async fn delete_mortar(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DeletePositionRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut mortars = state.mortars.write().await;
+    let initial_len = mortars.len();
+    mortars.retain(|m| m.name != req.name);
+
+    if mortars.len() < initial_len {
+        state.events.emit(StateEvent::MortarRemoved {
+            name: req.name.clone(),
+        });
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!("Mortar '{}' deleted", req.name),
+        }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Mortar '{}' not found", req.name),
+            }),
+        ))
+    }
+}
+
+pub async fn list_friendlies(State(state): State<Arc<AppState>>) -> Json<FriendlyListResponse> {
+    Json(FriendlyListResponse {
+        positions: state.friendlies.read().await.clone(),
+    })
+}
+
+/// Add own troops, checked against the danger radius of every solution.
+pub async fn add_friendly(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AddFriendlyRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if req.name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Name cannot be empty".to_string(),
+            }),
+        ));
+    }
+
+    let mut friendlies = state.friendlies.write().await;
+    if friendlies.iter().any(|f| f.name == req.name) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Friendly '{}' already exists", req.name),
+            }),
+        ));
+    }
+
+    let (elevation, _) = {
+        let mortars = state.mortars.read().await;
+        let targets = state.targets.read().await;
+        state
+            .fill_elevation(req.elevation, req.x, req.y, &mortars, &targets)
+            .await
+    };
+    let mut friendly = FriendlyPosition::new(req.name.clone(), elevation, req.x, req.y);
+    friendly.description = req.description;
+    if !friendly.is_valid() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Coordinates and elevation must be finite".to_string(),
+            }),
+        ));
+    }
+    friendlies.push(friendly.clone());
+    state.events.emit(StateEvent::FriendlyAdded { friendly });
+
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Friendly '{}' added", req.name),
+    }))
+}
+
+pub async fn delete_friendly(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DeletePositionRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut friendlies = state.friendlies.write().await;
+    let initial_len = friendlies.len();
+    friendlies.retain(|f| f.name != req.name);
+
+    if friendlies.len() < initial_len {
+        state.events.emit(StateEvent::FriendlyRemoved {
+            name: req.name.clone(),
+        });
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!("Friendly '{}' deleted", req.name),
+        }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Friendly '{}' not found", req.name),
+            }),
+        ))
+    }
+}
+
+/// Move own troops; later solutions are checked against the new position.
+pub async fn move_friendly(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MoveFriendlyRequest>,
+) -> Result<Json<FriendlyPosition>, (StatusCode, Json<ErrorResponse>)> {
+    let (elevation, _) = {
+        let mortars = state.mortars.read().await;
+        let targets = state.targets.read().await;
+        state
+            .fill_elevation(req.elevation, req.x, req.y, &mortars, &targets)
+            .await
+    };
+    if ![req.x, req.y, elevation].iter().all(|v| v.is_finite()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Coordinates and elevation must be finite".to_string(),
+            }),
+        ));
+    }
+
+    let mut friendlies = state.friendlies.write().await;
+    let friendly = match friendlies.iter_mut().find(|f| f.name == req.name) {
+        Some(f) => f,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Friendly '{}' not found", req.name),
+                }),
+            ))
+        }
+    };
+    friendly.x = req.x;
+    friendly.y = req.y;
+    friendly.elevation = elevation;
+    state.events.emit(StateEvent::FriendlyUpdated {
+        friendly: friendly.clone(),
+    });
+    Ok(Json(friendly.clone()))
+}
+
+/// Move a mortar, keeping its previous position, and return the re-lay data.
+pub async fn move_mortar(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MoveMortarRequest>,
+) -> Result<Json<DisplacementsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut mortars = state.mortars.write().await;
+    let targets = state.targets.read().await;
+    let (elevation, elevation_source) = state
+        .fill_elevation(req.elevation, req.x, req.y, &mortars, &targets)
+        .await;
+
+    let mortar = match mortars.iter_mut().find(|m| m.name == req.name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.name),
+                }),
+            ))
+        }
+    };
+    mortar.move_to(elevation, req.x, req.y, unix_now());
+    mortar.elevation_source = elevation_source;
+    state.events.emit(StateEvent::MortarUpdated {
+        mortar: mortar.clone(),
+    });
+
+    Ok(Json(DisplacementsResponse {
+        relay: mortar.relay_data(&targets),
+        mortar: mortar.clone(),
+    }))
+}
+
+/// Displacement history of a mortar and re-lay data from its last move.
+pub async fn mortar_displacements(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<DisplacementsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mortars = state.mortars.read().await;
+    let targets = state.targets.read().await;
+    match mortars.iter().find(|m| m.name == name) {
+        Some(m) => Ok(Json(DisplacementsResponse {
+            relay: m.relay_data(&targets),
+            mortar: m.clone(),
+        })),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Mortar '{}' not found", name),
+            }),
+        )),
+    }
+}
+
+pub async fn update_mortar_rings(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateMortarRingsRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut mortars = state.mortars.write().await;
+    if let Some(mortar) = mortars.iter_mut().find(|m| m.name == req.name) {
+        mortar.excluded_rings = req.excluded_rings.into_iter().collect();
+        state.events.emit(StateEvent::MortarUpdated {
+            mortar: mortar.clone(),
+        });
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!(
+                "Mortar '{}' excluded rings: {:?}",
+                req.name, mortar.excluded_rings
+            ),
+        }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Mortar '{}' not found", req.name),
+            }),
+        ))
+    }
+}
+
+pub async fn update_mortar_default_ring(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateMortarDefaultRingRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut mortars = state.mortars.write().await;
+    let mortar = match mortars.iter_mut().find(|m| m.name == req.name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.name),
+                }),
+            ))
+        }
+    };
+    if let Some(r) = req.default_ring.filter(|r| !mortar.ring_available(*r)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Ring {}R is excluded on mortar '{}'", r, req.name),
+            }),
+        ));
+    }
+
+    mortar.default_ring = req.default_ring;
+    state.events.emit(StateEvent::MortarUpdated {
+        mortar: mortar.clone(),
+    });
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: match req.default_ring {
+            Some(r) => format!("Mortar '{}' default ring: {}R", req.name, r),
+            None => format!("Mortar '{}' default ring cleared", req.name),
+        },
+    }))
+}
+
+/// Declare degraded (old, wet) charges on a mortar: every solution aims at a
+/// corrected table range.
+pub async fn update_mortar_charge_efficiency(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateMortarChargeEfficiencyRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(f) = req
+        .charge_efficiency
+        .filter(|f| !CHARGE_EFFICIENCY_RANGE.contains(f))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Charge efficiency {} out of range ({}-{})",
+                    f,
+                    CHARGE_EFFICIENCY_RANGE.start(),
+                    CHARGE_EFFICIENCY_RANGE.end()
+                ),
+            }),
+        ));
+    }
+    let mut mortars = state.mortars.write().await;
+    let mortar = match mortars.iter_mut().find(|m| m.name == req.name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.name),
+                }),
+            ))
+        }
+    };
+
+    mortar.charge_efficiency = req.charge_efficiency;
+    state.events.emit(StateEvent::MortarUpdated {
+        mortar: mortar.clone(),
+    });
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: match req.charge_efficiency {
+            Some(f) => format!("Mortar '{}' charge efficiency: {:.1}%", req.name, f * 100.0),
+            None => format!("Mortar '{}' charge efficiency cleared", req.name),
+        },
+    }))
+}
+
+/// Declare a tube known to shoot wider than the tables (worn tube, loose
+/// bipod): dispersions and hit probabilities of its solutions are enlarged.
+pub async fn update_mortar_dispersion_factor(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateMortarDispersionFactorRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(f) = req.factor.filter(|f| !DISPERSION_FACTOR_RANGE.contains(f)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Dispersion factor {} out of range ({}-{})",
+                    f,
+                    DISPERSION_FACTOR_RANGE.start(),
+                    DISPERSION_FACTOR_RANGE.end()
+                ),
+            }),
+        ));
+    }
+    let mut mortars = state.mortars.write().await;
+    let mortar = match mortars.iter_mut().find(|m| m.name == req.name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.name),
+                }),
+            ))
+        }
+    };
+
+    let ring_count = mortar.weapon.ring_count();
+    if let Some(r) = req.ring.filter(|r| usize::from(*r) >= ring_count) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Invalid ring: {} (expected 0 to {})", r, ring_count - 1),
+            }),
+        ));
+    }
+    let rings: Vec<Ring> = match req.ring {
+        Some(r) => vec![r],
+        None => (0..ring_count).map(|r| r as Ring).collect(),
+    };
+    for ring in rings {
+        match req.factor {
+            Some(f) => mortar.dispersion_factors.insert(ring, f),
+            None => mortar.dispersion_factors.remove(&ring),
+        };
+    }
+    state.events.emit(StateEvent::MortarUpdated {
+        mortar: mortar.clone(),
+    });
+    let rings = req.ring.map(|r| format!(" ({}R)", r)).unwrap_or_default();
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: match req.factor {
+            Some(f) => format!(
+                "Mortar '{}' dispersion factor{}: x{:.2}",
+                req.name, rings, f
+            ),
+            None => format!("Mortar '{}' dispersion factor{} cleared", req.name, rings),
+        },
+    }))
+}
+
+/// Declare the muzzle velocity deviation of a worn tube: every solution aims
+/// at a corrected table range.
+pub async fn update_mortar_bias(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateMortarBiasRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(pct) = req
+        .mv_correction_pct
+        .filter(|pct| !MV_CORRECTION_RANGE_PCT.contains(pct))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Muzzle velocity correction {}% out of range ({}-{}%)",
+                    pct,
+                    MV_CORRECTION_RANGE_PCT.start(),
+                    MV_CORRECTION_RANGE_PCT.end()
+                ),
+            }),
+        ));
+    }
+    let mut mortars = state.mortars.write().await;
+    let mortar = match mortars.iter_mut().find(|m| m.name == req.name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.name),
+                }),
+            ))
+        }
+    };
+
+    mortar.mv_correction_pct = req.mv_correction_pct;
+    state.events.emit(StateEvent::MortarUpdated {
+        mortar: mortar.clone(),
+    });
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: match req.mv_correction_pct {
+            Some(pct) => format!("Mortar '{}' muzzle velocity: {:+.1}%", req.name, pct),
+            None => format!("Mortar '{}' muzzle velocity correction cleared", req.name),
+        },
+    }))
+}
+
+/// Change the readiness of a mortar; only READY mortars are tasked.
+pub async fn update_mortar_status(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateMortarStatusRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let status = match MortarStatus::parse_str(&req.status) {
+        Some(status) => status,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Invalid status: {} (expected READY, MOVING, OUT_OF_ACTION or RESUPPLY)",
+                        req.status
+                    ),
+                }),
+            ))
+        }
+    };
+    let mut mortars = state.mortars.write().await;
+    let mortar = match mortars.iter_mut().find(|m| m.name == req.name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.name),
+                }),
+            ))
+        }
+    };
+
+    mortar.set_status(status, unix_now());
+    state.events.emit(StateEvent::MortarUpdated {
+        mortar: mortar.clone(),
+    });
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Mortar '{}' is {}", req.name, status),
+    }))
+}
+
+/// Assign (or remove) a mortar's sector of fire.
+pub async fn update_mortar_sector(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateMortarSectorRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(sector) = req.sector.filter(|s| !s.is_valid()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Invalid sector of fire: {} (limits in [0, 360[ and distinct, positive max range)",
+                    sector
+                ),
+            }),
+        ));
+    }
+    let mut mortars = state.mortars.write().await;
+    let mortar = match mortars.iter_mut().find(|m| m.name == req.name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.name),
+                }),
+            ))
+        }
+    };
+
+    mortar.sector = req.sector;
+    state.events.emit(StateEvent::MortarUpdated {
+        mortar: mortar.clone(),
+    });
+    let message = match req.sector {
+        Some(sector) => format!("Mortar '{}' sector of fire: {}", req.name, sector),
+        None => format!("Mortar '{}' sector of fire removed", req.name),
+    };
+    Ok(Json(SuccessResponse {
+        success: true,
+        message,
+    }))
+}
+
+/// Register a mortar on a known point: the correction derived from the
+/// observed impact is applied to every later solution of the mortar.
+pub async fn register_mortar(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RegisterMortarRequest>,
+) -> Result<Json<RegistrationResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let convention = state.sign_convention(req.sign_convention).await;
+    let (vertical_m, horizontal_m) =
+        convention.to_standard(req.observation.vertical_m, req.observation.horizontal_m);
+    let mut mortars = state.mortars.write().await;
+    let mortar = match mortars.iter_mut().find(|m| m.name == req.name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.name),
+                }),
+            ))
+        }
+    };
+
+    let point = Position::new(String::new(), req.elevation, req.x, req.y);
+    let registration = register(
+        mortar,
+        &point,
+        SpotObservation {
+            vertical_m,
+            horizontal_m,
+        },
+        unix_now(),
+    );
+    mortar.registration = Some(registration.clone());
+    state.events.emit(StateEvent::MortarUpdated {
+        mortar: mortar.clone(),
+    });
+    Ok(Json(RegistrationResponse {
+        mortar: req.name,
+        registration: Some(registration),
+    }))
+}
+
+pub async fn mortar_registration(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<RegistrationResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mortars = state.mortars.read().await;
+    match mortars.iter().find(|m| m.name == name) {
+        Some(m) => Ok(Json(RegistrationResponse {
+            registration: m.registration.clone(),
+            mortar: name,
+        })),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Mortar '{}' not found", name),
+            }),
+        )),
+    }
+}
+
+pub async fn clear_mortar_registration(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut mortars = state.mortars.write().await;
+    let mortar = match mortars.iter_mut().find(|m| m.name == name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", name),
+                }),
+            ))
+        }
+    };
+
+    mortar.registration = None;
+    state.events.emit(StateEvent::MortarUpdated {
+        mortar: mortar.clone(),
+    });
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Mortar '{}' registration cleared", name),
+    }))
+}
+
+pub async fn update_target_ammo(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateTargetAmmoRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let ammo_type = match state.ammo.read().await.parse_kind(&req.ammo_type) {
+        Some(a) => a,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Invalid ammo type: {}", req.ammo_type),
+                }),
+            ))
+        }
+    };
+
+    let mut targets = state.targets.write().await;
+    if let Some(target) = targets.iter_mut().find(|t| t.name == req.name) {
+        target.ammo_type = ammo_type;
+        state.events.emit(StateEvent::TargetUpdated {
+            target: target.clone(),
+        });
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!("Target '{}' ammo set to {}", req.name, ammo_type),
+        }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Target '{}' not found", req.name),
+            }),
+        ))
+    }
+}
+
+pub async fn list_targets(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TargetListQuery>,
+) -> Result<Json<TargetListResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let min_reliability = match query.min_reliability.as_deref().map(Reliability::parse_str) {
+        Some(None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Invalid reliability: {}",
+                        query.min_reliability.unwrap_or_default()
+                    ),
+                }),
+            ))
+        }
+        Some(r) => r,
+        None => None,
+    };
+
+    let now = unix_now();
+    let targets = state.targets.read().await;
+    Ok(Json(TargetListResponse {
+        positions: targets
+            .iter()
+            .filter(|t| t.matches_intel(min_reliability, query.max_age_s, now))
+            .map(|t| TargetListEntry {
+                staleness: t.staleness(now),
+                target: t.clone(),
+            })
+            .collect(),
+    }))
+}
+
+pub async fn update_target_intel(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateTargetIntelRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let reliability = match req.reliability.as_deref().map(Reliability::parse_str) {
+        Some(None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Invalid reliability: {}",
+                        req.reliability.unwrap_or_default()
+                    ),
+                }),
+            ))
+        }
+        Some(r) => r,
+        None => None,
+    };
+
+    let mut targets = state.targets.write().await;
+    if let Some(target) = targets.iter_mut().find(|t| t.name == req.name) {
+        target.last_observed = Some(req.last_observed.unwrap_or_else(unix_now));
+        if req.reporting_source.is_some() {
+            target.reporting_source = req.reporting_source;
+        }
+        if reliability.is_some() {
+            target.reliability = reliability;
+        }
+        state.events.emit(StateEvent::TargetUpdated {
+            target: target.clone(),
+        });
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!("Target '{}' intelligence updated", req.name),
+        }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Target '{}' not found", req.name),
+            }),
+        ))
+    }
+}
+
+pub async fn clear_target(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TargetClearanceRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let cleared_by = req.cleared_by.trim();
+    if cleared_by.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "cleared_by cannot be empty".to_string(),
+            }),
+        ));
+    }
+
+    let mut targets = state.targets.write().await;
+    if let Some(target) = targets.iter_mut().find(|t| t.name == req.name) {
+        target.clearance = Some(Clearance {
+            cleared_by: cleared_by.to_string(),
+            cleared_at: req.cleared_at.unwrap_or_else(unix_now),
+        });
+        state.events.emit(StateEvent::TargetUpdated {
+            target: target.clone(),
+        });
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!("Target '{}' cleared by {}", req.name, cleared_by),
+        }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Target '{}' not found", req.name),
+            }),
+        ))
+    }
+}
+
+pub async fn revoke_target_clearance(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DeletePositionRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut targets = state.targets.write().await;
+    if let Some(target) = targets.iter_mut().find(|t| t.name == req.name) {
+        target.clearance = None;
+        state.events.emit(StateEvent::TargetUpdated {
+            target: target.clone(),
+        });
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!("Target '{}' clearance revoked", req.name),
+        }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Target '{}' not found", req.name),
+            }),
+        ))
+    }
+}
+
+pub async fn add_target(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AddTargetRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if req.name.as_deref().is_some_and(str::is_empty) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Name cannot be empty".to_string(),
+            }),
+        ));
+    }
+    let reliability = match req.reliability.as_deref().map(Reliability::parse_str) {
+        Some(None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Invalid reliability: {}",
+                        req.reliability.unwrap_or_default()
+                    ),
+                }),
+            ))
+        }
+        Some(r) => r,
+        None => None,
+    };
+
+    let target_type = TargetType::parse_str(&req.target_type).unwrap_or(TargetType::Infanterie);
+    let ammo_type = state
+        .default_ammo(req.ammo_type.as_deref(), target_type)
+        .await;
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+
+    let name = state.target_name(req.name, &targets).await;
+    if targets.iter().any(|t| t.name == name) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Target '{}' already exists", name),
+            }),
+        ));
+    }
+
+    let (elevation, elevation_source) = state
+        .fill_elevation(req.elevation, req.x, req.y, &mortars, &targets)
+        .await;
+    let mut target = TargetPosition::new(
+        name.clone(),
+        elevation,
+        req.x,
+        req.y,
+        target_type,
+        ammo_type,
+    );
+    target.elevation_source = elevation_source;
+    target.reliability = reliability;
+    target.reporting_source = req.reporting_source;
+    if target.reliability.is_some() || target.reporting_source.is_some() {
+        target.last_observed = Some(unix_now());
+    }
+    targets.push(target.clone());
+    state.events.emit(StateEvent::TargetAdded { target });
+
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Target '{}' added as {}", name, target_type),
+    }))
+}
+
+pub async fn delete_target(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DeletePositionRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut targets = state.targets.write().await;
+    let initial_len = targets.len();
+    targets.retain(|t| t.name != req.name);
+
+    if targets.len() < initial_len {
+        state.events.emit(StateEvent::TargetRemoved {
+            name: req.name.clone(),
+        });
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!("Target '{}' deleted", req.name),
+        }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Target '{}' not found", req.name),
+            }),
+        ))
+    }
+}
+
+pub async fn update_target_type(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpdateTargetTypeRequest>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let target_type = match TargetType::parse_str(&req.target_type) {
+        Some(t) => t,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Invalid target type: {}", req.target_type),
+                }),
+            ))
+        }
+    };
+
+    let mut targets = state.targets.write().await;
+    if let Some(target) = targets.iter_mut().find(|t| t.name == req.name) {
+        target.target_type = target_type;
+        state.events.emit(StateEvent::TargetUpdated {
+            target: target.clone(),
+        });
+        Ok(Json(SuccessResponse {
+            success: true,
+            message: format!("Target '{}' type set to {}", req.name, target_type),
+        }))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Target '{}' not found", req.name),
+            }),
+        ))
+    }
+}
+
+/// Correct a target from an observed deviation. With `?preview=true` nothing
+/// is applied: the response carries the would-be coordinates and a token that
+/// a follow-up call confirms, so a retried request cannot correct twice.
+pub async fn correct_target(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CorrectionQuery>,
+    Json(req): Json<CorrectionRequest>,
+) -> Result<Json<CorrectionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let now = unix_now();
+    let confirmed = match &req.confirmation_token {
+        Some(token) => {
+            let mut pending = state.pending_corrections.write().await;
+            pending.retain(|_, p| p.expires_at > now);
+            match pending.remove(token) {
+                Some(p) if p.target_name == req.target_name => Some(p),
+                _ => {
+                    return Err((
+                        StatusCode::CONFLICT,
+                        Json(ErrorResponse {
+                            error: format!(
+                                "Confirmation token for target '{}' is unknown, expired or already used",
+                                req.target_name
+                            ),
+                        }),
+                    ))
+                }
+            }
+        }
+        None => None,
+    };
+
+    let mut targets = state.targets.write().await;
+
+    let target = match targets.iter_mut().find(|t| t.name == req.target_name) {
+        Some(t) => t,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Target '{}' not found", req.target_name),
+                }),
+            ))
+        }
+    };
+
+    // Deviation as entered (request convention) and in the standard convention
+    let (convention, (input_v, input_h), (vertical_m, horizontal_m), in_place, preset) =
+        match confirmed {
+            Some(p) => {
+                if (p.from_x, p.from_y) != (target.x, target.y) {
+                    return Err((
+                        StatusCode::CONFLICT,
+                        Json(ErrorResponse {
+                            error: format!(
+                                "Target '{}' has moved since the preview",
+                                req.target_name
+                            ),
+                        }),
+                    ));
+                }
+                (
+                    p.sign_convention,
+                    p.input_m,
+                    p.standard_m,
+                    p.in_place,
+                    p.preset,
+                )
+            }
+            None => {
+                let convention = state.sign_convention(req.sign_convention).await;
+                let (input, standard) = match (&req.preset, req.vertical_m, req.horizontal_m) {
+                    (Some(name), None, None) => {
+                        match state.correction_presets.read().await.get(name) {
+                            Some(p) => (
+                                convention.to_standard(p.vertical_m, p.horizontal_m),
+                                (p.vertical_m, p.horizontal_m),
+                            ),
+                            None => {
+                                return Err((
+                                    StatusCode::NOT_FOUND,
+                                    Json(ErrorResponse {
+                                        error: format!("Correction preset '{}' not found", name),
+                                    }),
+                                ))
+                            }
+                        }
+                    }
+                    (None, Some(v), Some(h)) => ((v, h), convention.to_standard(v, h)),
+                    _ => {
+                        return Err((
+                            StatusCode::BAD_REQUEST,
+                            Json(ErrorResponse {
+                                error: "Provide vertical_m and horizontal_m, or a preset"
+                                    .to_string(),
+                            }),
+                        ))
+                    }
+                };
+                (convention, input, standard, req.in_place, req.preset)
+            }
+        };
+
+    if query.preview && req.confirmation_token.is_none() {
+        let corrected = if in_place {
+            let mut moved = target.clone();
+            apply_correction_in_place(&mut moved, vertical_m, horizontal_m);
+            moved
+        } else {
+            apply_correction(target, vertical_m, horizontal_m)
+        };
+        let pending = PendingCorrection {
+            target_name: req.target_name.clone(),
+            from_x: target.x,
+            from_y: target.y,
+            input_m: (input_v, input_h),
+            standard_m: (vertical_m, horizontal_m),
+            in_place,
+            sign_convention: convention,
+            preset: preset.clone(),
+            expires_at: now + CORRECTION_PREVIEW_TTL_S,
+        };
+        drop(targets);
+
+        let solution = match &req.mortar_name {
+            Some(name) => {
+                let mortars = state.mortars.read().await;
+                let mortar = match mortars.iter().find(|m| &m.name == name) {
+                    Some(m) => m,
+                    None => {
+                        return Err((
+                            StatusCode::NOT_FOUND,
+                            Json(ErrorResponse {
+                                error: format!("Mortar '{}' not found", name),
+                            }),
+                        ))
+                    }
+                };
+                let ballistics = state.ballistics.read().await;
+                match state
+                    .solve(mortar, &corrected, &ballistics, &SolveOptions::default())
+                    .await
+                {
+                    Ok(solution) => Some(solution),
+                    Err(e) => {
+                        return Err((
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                            Json(ErrorResponse {
+                                error: e.to_string(),
+                            }),
+                        ))
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let token = generate_share_token();
+        let expires_at = pending.expires_at;
+        let mut pending_corrections = state.pending_corrections.write().await;
+        pending_corrections.retain(|_, p| p.expires_at > now);
+        pending_corrections.insert(token.clone(), pending);
+        return Ok(Json(CorrectionResponse {
+            success: true,
+            original: req.target_name,
+            corrected: corrected.name,
+            correction_applied: CorrectionApplied {
+                vertical_m: input_v,
+                horizontal_m: input_h,
+                new_x: corrected.x,
+                new_y: corrected.y,
+            },
+            sign_convention: convention,
+            preset,
+            preview: true,
+            confirmation_token: Some(token),
+            expires_at: Some(expires_at),
+            solution,
+        }));
+    }
+
+    if in_place {
+        let record = apply_correction_in_place(target, vertical_m, horizontal_m);
+        state.events.emit(StateEvent::TargetCorrected {
+            original: req.target_name.clone(),
+            corrected: req.target_name.clone(),
+            correction: record.clone(),
+        });
+        return Ok(Json(CorrectionResponse {
+            success: true,
+            original: req.target_name.clone(),
+            corrected: req.target_name,
+            correction_applied: CorrectionApplied {
+                vertical_m: input_v,
+                horizontal_m: input_h,
+                new_x: record.to_x,
+                new_y: record.to_y,
+            },
+            sign_convention: convention,
+            preset,
+            preview: false,
+            confirmation_token: None,
+            expires_at: None,
+            solution: None,
+        }));
+    }
+
+    let target = target.clone();
+    let corrected = apply_correction(&target, vertical_m, horizontal_m);
+    let corrected_name = corrected.name.clone();
+    let new_x = corrected.x;
+    let new_y = corrected.y;
+
+    if let Some(existing) = targets.iter_mut().find(|t| t.name == corrected_name) {
+        existing.x = new_x;
+        existing.y = new_y;
+    } else {
+        targets.push(corrected);
+    }
+    state.events.emit(StateEvent::TargetCorrected {
+        original: req.target_name.clone(),
+        corrected: corrected_name.clone(),
+        correction: CorrectionRecord {
+            vertical_m,
+            horizontal_m,
+            from_x: target.x,
+            from_y: target.y,
+            to_x: new_x,
+            to_y: new_y,
+        },
+    });
+
+    Ok(Json(CorrectionResponse {
+        success: true,
+        original: req.target_name,
+        corrected: corrected_name,
+        correction_applied: CorrectionApplied {
+            vertical_m: input_v,
+            horizontal_m: input_h,
+            new_x,
+            new_y,
+        },
+        sign_convention: convention,
+        preset,
+        preview: false,
+        confirmation_token: None,
+        expires_at: None,
+        solution: None,
+    }))
+}
+
+pub async fn adjust_fire(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AdjustFireRequest>,
+) -> Result<Json<AdjustFireResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mortars = state.mortars.read().await;
+    let targets = state.targets.read().await;
+    let ballistics = state.ballistics.read().await;
+
+    let mortar = match mortars.iter().find(|m| m.name == req.mortar_name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.mortar_name),
+                }),
+            ))
+        }
+    };
+    let target = match targets.iter().find(|t| t.name == req.target_name) {
+        Some(t) => t,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Target '{}' not found", req.target_name),
+                }),
+            ))
+        }
+    };
+
+    if let Err(e) = mortar.check_ammo(target.ammo_type, &*state.ammo.read().await) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        ));
+    }
+
+    let ammo = target.ammo_type;
+    let distance = mortar.as_position().distance_to(&target.as_position());
+    let ring = match req
+        .ring
+        .or_else(|| mortar.preferred_ring(&ballistics, ammo, distance))
+    {
+        Some(r) => r,
+        None => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: format!("No {} ring covers {:.0} m", ammo, distance),
+                }),
+            ))
+        }
+    };
+    let table = match ballistics.get(&(mortar.weapon, ammo, ring)) {
+        Some(t) => t,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("No ballistic table for {} {}R", ammo, ring),
+                }),
+            ))
+        }
+    };
+
+    let convention = state.sign_convention(req.sign_convention).await;
+    let (vertical_m, horizontal_m) = convention.to_standard(req.vertical_m, req.horizontal_m);
+    let correction = firing_data_correction(mortar, target, table, vertical_m, horizontal_m);
+
+    Ok(Json(AdjustFireResponse {
+        mortar: mortar.name.clone(),
+        target: target.name.clone(),
+        ammo_type: ammo.as_str().to_string(),
+        ring,
+        correction,
+        sign_convention: convention,
+    }))
+}
+
+pub async fn fire_spot(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SpotRoundRequest>,
+) -> Result<Json<SpotRoundResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+    let ballistics = state.ballistics.read().await;
+
+    let mortar = match mortars.iter().find(|m| m.name == req.mortar_name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.mortar_name),
+                }),
+            ))
+        }
+    };
+    let target = match targets.iter_mut().find(|t| t.name == req.target_name) {
+        Some(t) => t,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Target '{}' not found", req.target_name),
+                }),
+            ))
+        }
+    };
+
+    if let Err(e) = mortar.check_ammo(target.ammo_type, &*state.ammo.read().await) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        ));
+    }
+    if state.require_clearance && !target.is_cleared() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: format!("Target '{}' is not cleared for fire", target.name),
+            }),
+        ));
+    }
+
+    let convention = state.sign_convention(req.sign_convention).await;
+    let observation = req.observation.map(|o| {
+        let (vertical_m, horizontal_m) = convention.to_standard(o.vertical_m, o.horizontal_m);
+        SpotObservation {
+            vertical_m,
+            horizontal_m,
+        }
+    });
+    let mut rng = state.sim_rng(req.seed).await;
+    let mut report = spot_round(
+        mortar,
+        target,
+        &ballistics,
+        &*state.dispersions.read().await,
+        req.ring,
+        observation,
+        *state.training_offset.read().await,
+        &mut rng,
+    )
+    .map_err(|e| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+    state
+        .complete_solution(
+            &mut report.solution,
+            mortar,
+            target,
+            &ballistics,
+            &SolveOptions::default(),
+        )
+        .await;
+    state.events.emit(StateEvent::TargetCorrected {
+        original: req.target_name.clone(),
+        corrected: req.target_name.clone(),
+        correction: report.correction.clone(),
+    });
+
+    let record = SolutionRecord {
+        timestamp: unix_now(),
+        mortar: req.mortar_name.clone(),
+        fired: true,
+        deviation: Some(report.deviation),
+        solution: report.solution.clone(),
+        shot: Some(ShotTrace::from_spot(mortar, target, &report)),
+    };
+    // Position locks are released before the history is taken
+    drop(ballistics);
+    drop(targets);
+    drop(mortars);
+    state.record_solution(&req.target_name, record).await;
+
+    let (vertical_m, horizontal_m) =
+        convention.to_standard(report.deviation.vertical_m, report.deviation.horizontal_m);
+    report.deviation = SpotObservation {
+        vertical_m,
+        horizontal_m,
+    };
+    Ok(Json(SpotRoundResponse {
+        report,
+        sign_convention: convention,
+    }))
+}
+
+pub async fn get_sign_convention(
+    State(state): State<Arc<AppState>>,
+) -> Json<SignConventionSetting> {
+    Json(SignConventionSetting::from(
+        *state.sign_convention.read().await,
+    ))
+}
+
+/// Change the sign convention used when a request does not override it.
+pub async fn set_sign_convention(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SignConventionSetting>,
+) -> Json<SignConventionSetting> {
+    *state.sign_convention.write().await = req.sign_convention;
+    Json(SignConventionSetting::from(req.sign_convention))
+}
+
+pub async fn get_ammo_policy(State(state): State<Arc<AppState>>) -> Json<AmmoPolicyResponse> {
+    Json(AmmoPolicyResponse::from(&*state.ammo_policy.read().await))
+}
+
+/// Replace the operation's default ammo per target type.
+pub async fn set_ammo_policy(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AmmoPolicyRequest>,
+) -> Result<Json<AmmoPolicyResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut policy = AmmoPolicy::default();
+    for (target_type, ammo) in &req.policy {
+        let target_type = match TargetType::parse_str(target_type) {
+            Some(t) => t,
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Invalid target type: {}", target_type),
+                    }),
+                ))
+            }
+        };
+        let ammo = match state.ammo.read().await.parse_kind(ammo) {
+            Some(a) => a,
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Invalid ammo type: {}", ammo),
+                    }),
+                ))
+            }
+        };
+        policy.0.insert(target_type, ammo);
+    }
+    let response = AmmoPolicyResponse::from(&policy);
+    *state.ammo_policy.write().await = policy;
+    Ok(Json(response))
+}
+
+pub async fn get_training_offset(
+    State(state): State<Arc<AppState>>,
+) -> Json<TrainingOffsetSetting> {
+    Json(TrainingOffsetSetting {
+        training_offset: *state.training_offset.read().await,
+    })
+}
+
+/// Set the systematic error added to simulated impacts (training mode).
+pub async fn set_training_offset(
+    State(state): State<Arc<AppState>>,
+    Json(offset): Json<TrainingOffset>,
+) -> Result<Json<TrainingOffsetSetting>, (StatusCode, Json<ErrorResponse>)> {
+    if !offset.north_m.is_finite() || !offset.east_m.is_finite() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Offset must be finite".to_string(),
+            }),
+        ));
+    }
+    *state.training_offset.write().await = Some(offset);
+    Ok(Json(TrainingOffsetSetting {
+        training_offset: Some(offset),
+    }))
+}
+
+pub async fn clear_training_offset(
+    State(state): State<Arc<AppState>>,
+) -> Json<TrainingOffsetSetting> {
+    *state.training_offset.write().await = None;
+    Json(TrainingOffsetSetting {
+        training_offset: None,
+    })
+}
+
+pub async fn get_seed(State(state): State<Arc<AppState>>) -> Json<SeedSetting> {
+    Json(SeedSetting {
+        seed: state.session_seed().await,
+    })
+}
+
+/// Restart the session seed series: the following simulations without their
+/// own seed replay the same impacts.
+pub async fn set_seed(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SeedSetting>,
+) -> Json<SeedSetting> {
+    *state.session_seed.write().await = req.seed.map(SessionSeed::new);
+    Json(req)
+}
+
+pub async fn clear_seed(State(state): State<Arc<AppState>>) -> Json<SeedSetting> {
+    *state.session_seed.write().await = None;
+    Json(SeedSetting { seed: None })
+}
+
+pub async fn get_declination(State(state): State<Arc<AppState>>) -> Json<DeclinationSetting> {
+    Json(DeclinationSetting::from(*state.declination.read().await))
+}
+
+/// Change the declination used for magnetic azimuths on exported products.
+pub async fn set_declination(
+    State(state): State<Arc<AppState>>,
+    Json(declination): Json<Declination>,
+) -> Result<Json<DeclinationSetting>, (StatusCode, Json<ErrorResponse>)> {
+    let valid = |deg: f64| deg.is_finite() && deg.abs() <= 180.0;
+    if !valid(declination.magnetic_deg) || !valid(declination.grid_convergence_deg) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Declination and convergence must be between -180 and 180 degrees"
+                    .to_string(),
+            }),
+        ));
+    }
+    *state.declination.write().await = declination;
+    Ok(Json(DeclinationSetting::from(declination)))
+}
+
+pub async fn get_danger_close(State(state): State<Arc<AppState>>) -> Json<DangerCloseSetting> {
+    Json(DangerCloseSetting {
+        multiple: *state.danger_close_multiple.read().await,
+    })
+}
+
+/// Change the multiple of the adjusted dispersion within which friendlies
+/// are danger close.
+pub async fn set_danger_close(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DangerCloseSetting>,
+) -> Result<Json<DangerCloseSetting>, (StatusCode, Json<ErrorResponse>)> {
+    if !DANGER_CLOSE_MULTIPLE_RANGE.contains(&req.multiple) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Danger close multiple must be between {} and {}",
+                    DANGER_CLOSE_MULTIPLE_RANGE.start(),
+                    DANGER_CLOSE_MULTIPLE_RANGE.end()
+                ),
+            }),
+        ));
+    }
+    *state.danger_close_multiple.write().await = req.multiple;
+    Ok(Json(req))
+}
+
+pub async fn get_elevation_thresholds(
+    State(state): State<Arc<AppState>>,
+) -> Json<ElevationThresholds> {
+    Json(*state.elevation_thresholds.read().await)
+}
+
+/// Change the elevation differences beyond which solutions are flagged.
+pub async fn set_elevation_thresholds(
+    State(state): State<Arc<AppState>>,
+    Json(thresholds): Json<ElevationThresholds>,
+) -> Result<Json<ElevationThresholds>, (StatusCode, Json<ErrorResponse>)> {
+    if !thresholds.is_valid() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Thresholds must be positive with caution_m <= critical_m".to_string(),
+            }),
+        ));
+    }
+    *state.elevation_thresholds.write().await = thresholds;
+    Ok(Json(thresholds))
+}
+
+pub async fn get_charge_temperature(
+    State(state): State<Arc<AppState>>,
+) -> Json<ChargeTemperatureSetting> {
+    Json(ChargeTemperatureSetting {
+        temperature_c: *state.charge_temperature_c.read().await,
+    })
+}
+
+/// Set the propellant temperature applied to the operation's solutions.
+pub async fn set_charge_temperature(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ChargeTemperatureSetting>,
+) -> Result<Json<ChargeTemperatureSetting>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(t) = req.temperature_c {
+        if !CHARGE_TEMPERATURE_RANGE_C.contains(&t) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Charge temperature must be between {} and {} °C",
+                        CHARGE_TEMPERATURE_RANGE_C.start(),
+                        CHARGE_TEMPERATURE_RANGE_C.end()
+                    ),
+                }),
+            ));
+        }
+    }
+    *state.charge_temperature_c.write().await = req.temperature_c;
+    Ok(Json(req))
+}
+
+pub async fn clear_charge_temperature(
+    State(state): State<Arc<AppState>>,
+) -> Json<ChargeTemperatureSetting> {
+    *state.charge_temperature_c.write().await = None;
+    Json(ChargeTemperatureSetting {
+        temperature_c: None,
+    })
+}
+
+pub async fn get_wind(State(state): State<Arc<AppState>>) -> Json<WindSetting> {
+    Json(WindSetting {
+        wind: state.wind.read().await.clone(),
+    })
+}
+
+/// Set the operation wind applied to solutions.
+pub async fn set_wind(
+    State(state): State<Arc<AppState>>,
+    Json(mut wind): Json<WindProfile>,
+) -> Result<Json<WindSetting>, (StatusCode, Json<ErrorResponse>)> {
+    if !wind.is_valid() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Wind speeds must be between {} and {} m/s and layer heights non-negative",
+                    WIND_SPEED_RANGE_MPS.start(),
+                    WIND_SPEED_RANGE_MPS.end()
+                ),
+            }),
+        ));
+    }
+    wind.layers
+        .sort_by(|a, b| a.height_m.total_cmp(&b.height_m));
+    *state.wind.write().await = Some(wind.clone());
+    Ok(Json(WindSetting { wind: Some(wind) }))
+}
+
+pub async fn clear_wind(State(state): State<Arc<AppState>>) -> Json<WindSetting> {
+    *state.wind.write().await = None;
+    Json(WindSetting { wind: None })
+}
+
+pub async fn get_terrain(State(state): State<Arc<AppState>>) -> Json<TerrainSetting> {
+    Json(TerrainSetting {
+        terrain: state
+            .terrain
+            .read()
+            .await
+            .as_ref()
+            .map(TerrainModel::summary),
+    })
+}
+
+/// Load a heightmap (grayscale PNG body, georeference in the query string):
+/// positions added without elevation then read it from the map.
+pub async fn set_terrain(
+    State(state): State<Arc<AppState>>,
+    Query(georef): Query<TerrainGeoref>,
+    body: Bytes,
+) -> Result<Json<TerrainSetting>, (StatusCode, Json<ErrorResponse>)> {
+    if !georef.is_valid() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Invalid georeference: values must be finite and cell_size_m positive"
+                    .to_string(),
+            }),
+        ));
+    }
+    let terrain = match TerrainModel::from_png(body.as_ref(), georef) {
+        Ok(terrain) => terrain,
+        Err(e) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("{e:#}"),
+                }),
+            ))
+        }
+    };
+    let summary = terrain.summary();
+    *state.terrain.write().await = Some(terrain);
+    Ok(Json(TerrainSetting {
+        terrain: Some(summary),
+    }))
+}
+
+pub async fn clear_terrain(State(state): State<Arc<AppState>>) -> Json<TerrainSetting> {
+    *state.terrain.write().await = None;
+    Json(TerrainSetting { terrain: None })
+}
+
+/// Load the operation wind and air conditions from a MET message.
+pub async fn load_met(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MetRequest>,
+) -> Result<Json<MetResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let message = parse_met(&req.message).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Invalid MET message: {}", e),
+            }),
+        )
+    })?;
+    let (wind, atmosphere) = (message.wind(), message.atmosphere());
+    if !wind.is_valid() || !atmosphere.is_valid() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "MET message has wind or air conditions out of range".to_string(),
+            }),
+        ));
+    }
+    *state.wind.write().await = Some(wind.clone());
+    *state.atmosphere.write().await = Some(atmosphere);
+    Ok(Json(MetResponse {
+        message,
+        wind,
+        atmosphere,
+    }))
+}
+
+pub async fn get_atmosphere(State(state): State<Arc<AppState>>) -> Json<AtmosphereSetting> {
+    Json(AtmosphereSetting {
+        atmosphere: *state.atmosphere.read().await,
+    })
+}
+
+/// Set the operation air conditions applied to solutions.
+pub async fn set_atmosphere(
+    State(state): State<Arc<AppState>>,
+    Json(atmosphere): Json<Atmosphere>,
+) -> Result<Json<AtmosphereSetting>, (StatusCode, Json<ErrorResponse>)> {
+    if !atmosphere.is_valid() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Air temperature must be between {} and {} °C and pressure between {} and {} hPa",
+                    AIR_TEMPERATURE_RANGE_C.start(),
+                    AIR_TEMPERATURE_RANGE_C.end(),
+                    PRESSURE_RANGE_HPA.start(),
+                    PRESSURE_RANGE_HPA.end()
+                ),
+            }),
+        ));
+    }
+    *state.atmosphere.write().await = Some(atmosphere);
+    Ok(Json(AtmosphereSetting {
+        atmosphere: Some(atmosphere),
+    }))
+}
+
+pub async fn clear_atmosphere(State(state): State<Arc<AppState>>) -> Json<AtmosphereSetting> {
+    *state.atmosphere.write().await = None;
+    Json(AtmosphereSetting { atmosphere: None })
+}
+
+pub async fn get_correction_presets(State(state): State<Arc<AppState>>) -> Json<CorrectionPresets> {
+    Json(state.correction_presets.read().await.clone())
+}
+
+/// Save a named correction, entered in the given (or configured) convention.
+pub async fn set_correction_preset(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(req): Json<CorrectionPresetRequest>,
+) -> Result<Json<CorrectionPresets>, (StatusCode, Json<ErrorResponse>)> {
+    if name.trim().is_empty() || name.contains(char::is_whitespace) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Invalid correction preset name: '{}'", name),
+            }),
+        ));
+    }
+    if !req.vertical_m.is_finite() || !req.horizontal_m.is_finite() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Deviation must be a finite number of meters".to_string(),
+            }),
+        ));
+    }
+    let convention = state.sign_convention(req.sign_convention).await;
+    let (vertical_m, horizontal_m) = convention.to_standard(req.vertical_m, req.horizontal_m);
+    let mut presets = state.correction_presets.write().await;
+    presets.0.insert(
+        name,
+        CorrectionPreset {
+            vertical_m,
+            horizontal_m,
+        },
+    );
+    Ok(Json(presets.clone()))
+}
+
+pub async fn delete_correction_preset(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<CorrectionPresets>, (StatusCode, Json<ErrorResponse>)> {
+    let mut presets = state.correction_presets.write().await;
+    if presets.0.remove(&name).is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Correction preset '{}' not found", name),
+            }),
+        ));
+    }
+    Ok(Json(presets.clone()))
+}
+
+pub async fn get_airspace(State(state): State<Arc<AppState>>) -> Json<Airspace> {
+    Json(state.airspace.read().await.clone())
+}
+
+/// Define a named airspace ceiling, over the whole area or a corridor.
+pub async fn set_airspace_restriction(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(restriction): Json<AirspaceRestriction>,
+) -> Result<Json<Airspace>, (StatusCode, Json<ErrorResponse>)> {
+    if name.trim().is_empty() || name.contains(char::is_whitespace) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Invalid airspace restriction name: '{}'", name),
+            }),
+        ));
+    }
+    let corridor_valid = restriction.corridor.is_none_or(|c| {
+        c.from.iter().chain(&c.to).all(|v| v.is_finite())
+            && c.width_m.is_finite()
+            && c.width_m > 0.0
+    });
+    if !restriction.ceiling_m.is_finite() || !corridor_valid {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Ceiling and corridor must be finite, with a positive width".to_string(),
+            }),
+        ));
+    }
+    let mut airspace = state.airspace.write().await;
+    airspace.0.insert(name, restriction);
+    Ok(Json(airspace.clone()))
+}
+
+pub async fn delete_airspace_restriction(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<Airspace>, (StatusCode, Json<ErrorResponse>)> {
+    let mut airspace = state.airspace.write().await;
+    if airspace.0.remove(&name).is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Airspace restriction '{}' not found", name),
+            }),
+        ));
+    }
+    Ok(Json(airspace.clone()))
+}
+
+pub async fn list_reference_points(State(state): State<Arc<AppState>>) -> Json<ReferencePoints> {
+    Json(state.reference_points.read().await.clone())
+}
+
+/// Define (or move) a named reference point.
+pub async fn set_reference_point(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(point): Json<ReferencePoint>,
+) -> Result<Json<ReferencePoints>, (StatusCode, Json<ErrorResponse>)> {
+    if name.trim().is_empty() || name.contains(char::is_whitespace) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Invalid reference point name: '{}'", name),
+            }),
+        ));
+    }
+    if !point.is_valid() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Coordinates and elevation must be finite".to_string(),
+            }),
+        ));
+    }
+    let mut points = state.reference_points.write().await;
+    let event = match points.0.insert(name.clone(), point.clone()) {
+        Some(_) => StateEvent::ReferencePointUpdated { name, point },
+        None => StateEvent::ReferencePointAdded { name, point },
+    };
+    state.events.emit(event);
+    Ok(Json(points.clone()))
+}
+
+pub async fn delete_reference_point(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<ReferencePoints>, (StatusCode, Json<ErrorResponse>)> {
+    let mut points = state.reference_points.write().await;
+    if points.0.remove(&name).is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Reference point '{}' not found", name),
+            }),
+        ));
+    }
+    state
+        .events
+        .emit(StateEvent::ReferencePointRemoved { name });
+    Ok(Json(points.clone()))
+}
+
+pub async fn get_target_numbering(
+    State(state): State<Arc<AppState>>,
+) -> Json<TargetNumberingResponse> {
+    Json(TargetNumberingResponse::from(
+        &*state.target_numbering.read().await,
+    ))
+}
+
+/// Change the prefix and/or counter used to name targets created without a name.
+pub async fn set_target_numbering(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TargetNumberingRequest>,
+) -> Result<Json<TargetNumberingResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut numbering = state.target_numbering.write().await;
+    let next = req.next.unwrap_or(numbering.next);
+    *numbering = match TargetNumbering::new(&req.prefix, next) {
+        Some(n) => n,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Invalid target numbering: {}{:04} (expected two letters and 1-9999)",
+                        req.prefix, next
+                    ),
+                }),
+            ))
+        }
+    };
+    Ok(Json(TargetNumberingResponse::from(&*numbering)))
+}
+
+pub async fn target_solutions(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<SolutionHistoryResponse>, (StatusCode, Json<ErrorResponse>)> {
+    // Clone the entry and release the history before reading the targets:
+    // fire endpoints hold the targets while recording into the history
+    let recorded = state.solution_history.read().await.get(&name).cloned();
+    let solutions = match recorded {
+        Some(s) => s,
+        None => {
+            if !state.targets.read().await.iter().any(|t| t.name == name) {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    Json(ErrorResponse {
+                        error: format!("Target '{}' not found", name),
+                    }),
+                ));
+            }
+            Vec::new()
+        }
+    };
+
+    let deviations: Vec<SpotObservation> = solutions.iter().filter_map(|r| r.deviation).collect();
+    let mean_deviation = (!deviations.is_empty()).then(|| {
+        let n = deviations.len() as f64;
+        SpotObservation {
+            vertical_m: deviations.iter().map(|d| d.vertical_m).sum::<f64>() / n,
+            horizontal_m: deviations.iter().map(|d| d.horizontal_m).sum::<f64>() / n,
+        }
+    });
+
+    Ok(Json(SolutionHistoryResponse {
+        target: name,
+        solutions,
+        mean_deviation,
+    }))
+}
+
+pub async fn intersect_target(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<IntersectTargetRequest>,
+) -> Result<Json<IntersectTargetResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if req.name.as_deref().is_some_and(str::is_empty) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Name cannot be empty".to_string(),
+            }),
+        ));
+    }
+    if req.observers.len() != 2 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Exactly two observer bearings required, got {}",
+                    req.observers.len()
+                ),
+            }),
+        ));
+    }
+
+    let intersection = match intersect_bearings(&req.observers[0], &req.observers[1]) {
+        Some(i) => i,
+        None => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: "Bearings do not intersect in front of both observers".to_string(),
+                }),
+            ))
+        }
+    };
+
+    let target_type = TargetType::parse_str(&req.target_type).unwrap_or(TargetType::Infanterie);
+    let ammo_type = state
+        .default_ammo(req.ammo_type.as_deref(), target_type)
+        .await;
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+
+    let name = state.target_name(req.name, &targets).await;
+    if targets.iter().any(|t| t.name == name) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Target '{}' already exists", name),
+            }),
+        ));
+    }
+
+    let (elevation, elevation_source) = state
+        .fill_elevation(
+            req.elevation,
+            intersection.x,
+            intersection.y,
+            &mortars,
+            &targets,
+        )
+        .await;
+    let mut target = TargetPosition::new(
+        name,
+        elevation,
+        intersection.x,
+        intersection.y,
+        target_type,
+        ammo_type,
+    );
+    target.elevation_source = elevation_source;
+    targets.push(target.clone());
+    state.events.emit(StateEvent::TargetAdded {
+        target: target.clone(),
+    });
+
+    Ok(Json(IntersectTargetResponse {
+        success: true,
+        target,
+        intersection,
+    }))
+}
+
+/// Create a target by shifting from a reference point along the
+/// observer-target line.
+pub async fn shift_target(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ShiftTargetRequest>,
+) -> Result<Json<ShiftTargetResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if req.name.as_deref().is_some_and(str::is_empty) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Name cannot be empty".to_string(),
+            }),
+        ));
+    }
+    if !req.shift.is_valid() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Shift direction and distances must be finite".to_string(),
+            }),
+        ));
+    }
+    let (x, y, elevation) = match state.reference_points.read().await.0.get(&req.from) {
+        Some(point) => req.shift.apply(point),
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Reference point '{}' not found", req.from),
+                }),
+            ))
+        }
+    };
+
+    let target_type = TargetType::parse_str(&req.target_type).unwrap_or(TargetType::Infanterie);
+    let ammo_type = state
+        .default_ammo(req.ammo_type.as_deref(), target_type)
+        .await;
+    let mut targets = state.targets.write().await;
+
+    let name = state.target_name(req.name, &targets).await;
+    if targets.iter().any(|t| t.name == name) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Target '{}' already exists", name),
+            }),
+        ));
+    }
+
+    let target = TargetPosition::new(name, elevation, x, y, target_type, ammo_type);
+    targets.push(target.clone());
+    state.events.emit(StateEvent::TargetAdded {
+        target: target.clone(),
+    });
+
+    Ok(Json(ShiftTargetResponse {
+        success: true,
+        target,
+    }))
+}
+
+pub async fn backplot_target(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BackplotRequest>,
+) -> Result<Json<BackplotResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if req.name.as_deref().is_some_and(str::is_empty) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Name cannot be empty".to_string(),
+            }),
+        ));
+    }
+
+    let estimate = match backplot(&req.bearings, &req.craters) {
+        Some(e) => e,
+        None => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: "Not enough observations to fix the hostile position".to_string(),
+                }),
+            ))
+        }
+    };
+
+    let target_type = TargetType::parse_str(&req.target_type).unwrap_or(TargetType::Infanterie);
+    let ammo_type = state
+        .default_ammo(req.ammo_type.as_deref(), target_type)
+        .await;
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+
+    let name = state.target_name(req.name, &targets).await;
+    if targets.iter().any(|t| t.name == name) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Target '{}' already exists", name),
+            }),
+        ));
+    }
+
+    let (elevation, elevation_source) = state
+        .fill_elevation(req.elevation, estimate.x, estimate.y, &mortars, &targets)
+        .await;
+    let mut target = TargetPosition::new(
+        name,
+        elevation,
+        estimate.x,
+        estimate.y,
+        target_type,
+        ammo_type,
+    );
+    target.elevation_source = elevation_source;
+    targets.push(target.clone());
+    state.events.emit(StateEvent::TargetAdded {
+        target: target.clone(),
+    });
+
+    Ok(Json(BackplotResponse {
+        success: true,
+        target,
+        estimate,
+    }))
+}
+
+/// Plot an enemy firing position area from crater analyses and record it as a
+/// target carrying its uncertainty radius.
+pub async fn crater_target(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CraterTargetRequest>,
+) -> Result<Json<CraterTargetResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if req.name.as_deref().is_some_and(str::is_empty) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Name cannot be empty".to_string(),
+            }),
+        ));
+    }
+    if !req.craters.iter().all(CraterAnalysis::is_valid) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Invalid crater: give range_m or min_range_m <= max_range_m".to_string(),
+            }),
+        ));
+    }
+
+    let area = match origin_area(&req.craters) {
+        Some(a) => a,
+        None => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: "Not enough crater analyses to fix the hostile position".to_string(),
+                }),
+            ))
+        }
+    };
+
+    let target_type = TargetType::parse_str(&req.target_type).unwrap_or(TargetType::Infanterie);
+    let ammo_type = state
+        .default_ammo(req.ammo_type.as_deref(), target_type)
+        .await;
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+
+    let name = state.target_name(req.name, &targets).await;
+    if targets.iter().any(|t| t.name == name) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Target '{}' already exists", name),
+            }),
+        ));
+    }
+
+    let (elevation, elevation_source) = state
+        .fill_elevation(req.elevation, area.x, area.y, &mortars, &targets)
+        .await;
+    let mut target = TargetPosition::new(name, elevation, area.x, area.y, target_type, ammo_type);
+    target.elevation_source = elevation_source;
+    target.uncertainty_m = Some(area.radius_m);
+    targets.push(target.clone());
+    state.events.emit(StateEvent::TargetAdded {
+        target: target.clone(),
+    });
+
+    Ok(Json(CraterTargetResponse {
+        success: true,
+        target,
+        area,
+    }))
+}
+
+pub async fn fire_scatter(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ScatterRequest>,
+) -> Result<Json<ScatterReport>, (StatusCode, Json<ErrorResponse>)> {
+    let mortars = state.mortars.read().await;
+    let targets = state.targets.read().await;
+
+    let mortar = match mortars.iter().find(|m| m.name == req.mortar_name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.mortar_name),
+                }),
+            ))
+        }
+    };
+    let target = match targets.iter().find(|t| t.name == req.target_name) {
+        Some(t) => t,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Target '{}' not found", req.target_name),
+                }),
+            ))
+        }
+    };
+
+    if let Err(e) = mortar.check_ammo(target.ammo_type, &*state.ammo.read().await) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        ));
+    }
+
+    let (mortar, target) = (mortar.clone(), target.clone());
+    drop(targets);
+    drop(mortars);
+
+    let limit = match (state.calc_budget, req.budget_ms.map(Duration::from_millis)) {
+        (Some(server), Some(requested)) => Some(server.min(requested)),
+        (server, requested) => server.or(requested),
+    };
+    let budget = limit.map_or_else(Budget::unlimited, Budget::with_limit);
+    // Stop the simulation if the client goes away before the response
+    let _cancel = budget.cancel_on_drop();
+
+    let mut rng = state.sim_rng(req.seed).await;
+    let offset = *state.training_offset.read().await;
+    let worker = state.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        simulate_scatter(
+            &mortar,
+            &target,
+            &worker.ballistics.blocking_read(),
+            &worker.dispersions.blocking_read(),
+            req.ring,
+            req.count,
+            offset,
+            &mut rng,
+            &budget,
+        )
+    })
+    .await;
+
+    match result {
+        Ok(Ok(report)) => Ok(Json(report)),
+        Ok(Err(e)) => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Simulation failed: {}", e),
+            }),
+        )),
+    }
+}
+
+/// Monte Carlo estimate of the hit probability of a ring on a target.
+pub async fn simulate_hit_probability(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SimulateRequest>,
+) -> Result<Json<HitProbability>, (StatusCode, Json<ErrorResponse>)> {
+    let mortars = state.mortars.read().await;
+    let targets = state.targets.read().await;
+
+    let mortar = match mortars.iter().find(|m| m.name == req.mortar_name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.mortar_name),
+                }),
+            ))
+        }
+    };
+    let target = match targets.iter().find(|t| t.name == req.target_name) {
+        Some(t) => t,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Target '{}' not found", req.target_name),
+                }),
+            ))
+        }
+    };
+
+    if !(req.target_radius_m.is_finite() && req.target_radius_m > 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "target_radius_m must be positive".to_string(),
+            }),
+        ));
+    }
+    let (solution, ring) = {
+        let ballistics = state.ballistics.read().await;
+        let solution = match state
+            .solve(mortar, target, &ballistics, &SolveOptions::default())
+            .await
+        {
+            Ok(solution) => solution,
+            Err(e) => {
+                return Err((
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Json(ErrorResponse {
+                        error: e.to_string(),
+                    }),
+                ))
+            }
+        };
+        let ring = req
+            .ring
+            .or_else(|| mortar.preferred_ring(&ballistics, target.ammo_type, solution.distance_m));
+        (solution, ring)
+    };
+    let ring = match ring {
+        Some(r) if !mortar.ring_available(r) => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: format!("Ring {}R is excluded on mortar '{}'", r, mortar.name),
+                }),
+            ))
+        }
+        Some(r) => r,
+        None => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: format!(
+                        "No {} ring covers {:.0} m",
+                        target.ammo_type, solution.distance_m
+                    ),
+                }),
+            ))
+        }
+    };
+    drop(targets);
+    drop(mortars);
+
+    let limit = match (state.calc_budget, req.budget_ms.map(Duration::from_millis)) {
+        (Some(server), Some(requested)) => Some(server.min(requested)),
+        (server, requested) => server.or(requested),
+    };
+    let budget = limit.map_or_else(Budget::unlimited, Budget::with_limit);
+    // Stop the simulation if the client goes away before the response
+    let _cancel = budget.cancel_on_drop();
+
+    let mut rng = state.sim_rng(req.seed).await;
+    let result = tokio::task::spawn_blocking(move || {
+        simulate_impacts(
+            &solution,
+            ring,
+            req.target_radius_m,
+            req.count,
+            &mut rng,
+            &budget,
+        )
+    })
+    .await;
+
+    match result {
+        Ok(Ok(report)) => Ok(Json(report)),
+        Ok(Err(e)) => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Simulation failed: {}", e),
+            }),
+        )),
+    }
+}
+
+/// Elevation curve of a table resampled with its interpolation, for plotting.
+pub async fn ballistic_curve(
+    State(state): State<Arc<AppState>>,
+    Path((ammo, ring)): Path<(String, String)>,
+    Query(query): Query<CurveQuery>,
+) -> Result<Json<CurveResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }));
+    let ammo = state
+        .ammo
+        .read()
+        .await
+        .parse_kind(&ammo)
+        .ok_or_else(|| bad_request(format!("Unknown ammo type: {}", ammo)))?;
+    let ring = parse_ring(&ring).ok_or_else(|| bad_request(format!("Invalid ring: {}", ring)))?;
+    let weapon = match query.weapon.as_deref() {
+        Some(w) => WeaponSystem::parse_str(w)
+            .ok_or_else(|| bad_request(format!("Unknown weapon system: {}", w)))?,
+        None => WeaponSystem::default(),
+    };
+    let step_m = query.step.unwrap_or(DEFAULT_CURVE_STEP_M);
+    if !(step_m >= DENSE_STEP_M && step_m.is_finite()) {
+        return Err(bad_request(format!(
+            "Step must be at least {} m",
+            DENSE_STEP_M
+        )));
+    }
+
+    let ballistics = state.ballistics.read().await;
+    let table = ballistics.get(&(weapon, ammo, ring)).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!(
+                    "No {} table for {} ring {}R",
+                    weapon.as_str(),
+                    ammo.as_str(),
+                    ring
+                ),
+            }),
+        )
+    })?;
+    Ok(Json(CurveResponse {
+        weapon,
+        ammo_type: ammo.as_str().to_string(),
+        ring: format!("{}R", ring),
+        interpolation: table.interpolation,
+        step_m,
+        points: table.curve(step_m),
+    }))
+}
+
+/// Loaded table as CSV, in the format read from the data directory.
+///
+/// Without `step` the rows are the ones the solver uses (sorted, repaired if
+/// the table was repaired on load); with `step` the table is resampled along
+/// its interpolation, so the file can be archived and diffed against the
+/// source table.
+pub async fn ballistic_csv(
+    State(state): State<Arc<AppState>>,
+    Path((ammo, ring)): Path<(String, String)>,
+    Query(query): Query<CurveQuery>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }));
+    let ammo = state
+        .ammo
+        .read()
+        .await
+        .parse_kind(&ammo)
+        .ok_or_else(|| bad_request(format!("Unknown ammo type: {}", ammo)))?;
+    let ring = parse_ring(&ring).ok_or_else(|| bad_request(format!("Invalid ring: {}", ring)))?;
+    let weapon = match query.weapon.as_deref() {
+        Some(w) => WeaponSystem::parse_str(w)
+            .ok_or_else(|| bad_request(format!("Unknown weapon system: {}", w)))?,
+        None => WeaponSystem::default(),
+    };
+    if let Some(step_m) = query.step {
+        if !(step_m >= DENSE_STEP_M && step_m.is_finite()) {
+            return Err(bad_request(format!(
+                "Step must be at least {} m",
+                DENSE_STEP_M
+            )));
+        }
+    }
+
+    let ballistics = state.ballistics.read().await;
+    let table = ballistics.get(&(weapon, ammo, ring)).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!(
+                    "No {} table for {} ring {}R",
+                    weapon.as_str(),
+                    ammo.as_str(),
+                    ring
+                ),
+            }),
+        )
+    })?;
+    let mut body = Vec::new();
+    table.write_csv(&mut body, query.step).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+    let file_name = ballistic_file_name(weapon, ammo, ring);
+    let file_name = file_name.rsplit('/').next().unwrap_or(&file_name);
+    let disposition = format!("attachment; filename=\"{}\"", file_name);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Flight path of a ring at a range, sampled for plotting and obstacle checks.
+pub async fn trajectory(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TrajectoryQuery>,
+) -> Result<Json<Trajectory>, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }));
+    let ammo = state
+        .ammo
+        .read()
+        .await
+        .parse_kind(&query.ammo)
+        .ok_or_else(|| bad_request(format!("Unknown ammo type: {}", query.ammo)))?;
+    let ring = parse_ring(&query.ring)
+        .ok_or_else(|| bad_request(format!("Invalid ring: {}", query.ring)))?;
+    let weapon = match query.weapon.as_deref() {
+        Some(w) => WeaponSystem::parse_str(w)
+            .ok_or_else(|| bad_request(format!("Unknown weapon system: {}", w)))?,
+        None => WeaponSystem::default(),
+    };
+    let step_m = query.step_m.unwrap_or(DEFAULT_TRAJECTORY_STEP_M);
+    if !(step_m >= DENSE_STEP_M && step_m.is_finite()) {
+        return Err(bad_request(format!(
+            "Step must be at least {} m",
+            DENSE_STEP_M
+        )));
+    }
+
+    let ballistics = state.ballistics.read().await;
+    let table = ballistics.get(&(weapon, ammo, ring)).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!(
+                    "No {} table for {} ring {}R",
+                    weapon.as_str(),
+                    ammo.as_str(),
+                    ring
+                ),
+            }),
+        )
+    })?;
+    match trajectory_points(table, ammo, ring, query.range_m, query.rise_m, step_m) {
+        Ok(trajectory) => Ok(Json(trajectory)),
+        Err(e) => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )),
+    }
+}
+
+/// Elevation field of a mortar over a map grid, for coverage heatmaps.
+pub async fn mortar_coverage(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CoverageQuery>,
+) -> Result<Json<CoverageGrid>, (StatusCode, Json<ErrorResponse>)> {
+    let mortars = state.mortars.read().await;
+    let ballistics = state.ballistics.read().await;
+
+    let mortar = match mortars.iter().find(|m| m.name == query.mortar) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", query.mortar),
+                }),
+            ))
+        }
+    };
+    let ammo = match state.ammo.read().await.parse_kind(&query.ammo) {
+        Some(ammo) => ammo,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Unknown ammo type: {}", query.ammo),
+                }),
+            ))
+        }
+    };
+    if let Err(e) = mortar.check_ammo(ammo, &*state.ammo.read().await) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        ));
+    }
+    let ring = match query.ring.or(mortar.default_ring) {
+        Some(ring) => ring,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "No ring given and mortar '{}' has no default ring",
+                        mortar.name
+                    ),
+                }),
+            ))
+        }
+    };
+    if !mortar.ring_available(ring) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: format!("Ring {}R is excluded on mortar '{}'", ring, mortar.name),
+            }),
+        ));
+    }
+    let table = match ballistics.get(&(mortar.weapon, ammo, ring)) {
+        Some(table) => table,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("No ballistic table for {} {}R", ammo, ring),
+                }),
+            ))
+        }
+    };
+    let extent = match (query.min_x, query.min_y, query.max_x, query.max_y) {
+        (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) => Some(GridExtent {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }),
+        (None, None, None, None) => None,
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "Grid extent needs min_x, min_y, max_x and max_y".to_string(),
+                }),
+            ))
+        }
+    };
+
+    // Without its sector, the grid covers the mortar's full range
+    let unrestricted;
+    let mortar = if query.override_sector {
+        unrestricted = MortarPosition {
+            sector: None,
+            ..mortar.clone()
+        };
+        &unrestricted
+    } else {
+        mortar
+    };
+    match coverage_grid(
+        mortar,
+        table,
+        ammo,
+        ring,
+        extent,
+        query.cell_m.unwrap_or(DEFAULT_CELL_M),
+    ) {
+        Ok(grid) => Ok(Json(grid)),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )),
+    }
+}
+
+/// Volley table for a mission (JSON by default, `?format=text` for print).
+pub async fn fire_volley(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CalculateByNameRequest>,
-) -> Result<Json<FiringSolution>, (StatusCode, Json<ErrorResponse>)> {
+    Query(query): Query<VolleyQuery>,
+    Json(req): Json<VolleyRequest>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let text = match query.format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "text" => true,
+        other => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Invalid format: {} (expected json or text)", other),
+                }),
+            ))
+        }
+    };
+
     let mortars = state.mortars.read().await;
     let targets = state.targets.read().await;
+    let ballistics = state.ballistics.read().await;
 
-    let mortar = mortars.iter().find(|m| m.name == req.mortar_name);
-    let target = targets.iter().find(|t| t.name == req.target_name);
+    let mortar = match mortars.iter().find(|m| m.name == req.mortar_name) {
+        Some(m) => m,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Mortar '{}' not found", req.mortar_name),
+                }),
+            ))
+        }
+    };
+    let target = match targets.iter().find(|t| t.name == req.target_name) {
+        Some(t) => t,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Target '{}' not found", req.target_name),
+                }),
+            ))
+        }
+    };
 
-    match (mortar, target) {
-        (Some(m), Some(t)) => {
-            let solution =
-                calculate_solution_with_dispersion(m, t, &state.ballistics, &state.dispersions);
-            Ok(Json(solution))
+    let solution = match state
+        .solve(mortar, target, &ballistics, &SolveOptions::default())
+        .await
+    {
+        Ok(solution) => solution,
+        Err(e) => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ))
         }
-        (None, _) => Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Mortar '{}' not found", req.mortar_name),
-            }),
-        )),
-        (_, None) => Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Target '{}' not found", req.target_name),
-            }),
-        )),
+    };
+
+    let plan = match volley_plan(
+        &solution,
+        mortar,
+        target,
+        &ballistics,
+        &*state.increments.read().await,
+        req.ring,
+        req.tubes,
+        req.rounds_per_tube,
+        req.round_interval_s,
+        *state.declination.read().await,
+    ) {
+        Ok(plan) => plan,
+        Err(e) => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            ))
+        }
+    };
+    if text {
+        Ok(render_volley_text(&plan).into_response())
+    } else {
+        Ok(Json(plan).into_response())
     }
 }
 
-pub async fn list_mortars(State(state): State<Arc<AppState>>) -> Json<MortarListResponse> {
-    let mortars = state.mortars.read().await;
-    Json(MortarListResponse {
-        positions: mortars.clone(),
-    })
+pub async fn export_state(State(state): State<Arc<AppState>>) -> Json<StateSnapshot> {
+    Json(state.snapshot().await)
 }
 
-pub async fn add_mortar(
+pub async fn restore_state(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<AddMortarRequest>,
-) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
-    if req.name.is_empty() {
+    Json(req): Json<RestoreRequest>,
+) -> Result<Json<RestoreReport>, (StatusCode, Json<ErrorResponse>)> {
+    if req.snapshot.version > SNAPSHOT_VERSION {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: "Name cannot be empty".to_string(),
+                error: format!(
+                    "Unsupported snapshot version {} (supported: {})",
+                    req.snapshot.version, SNAPSHOT_VERSION
+                ),
             }),
         ));
     }
 
-    let mut mortars = state.mortars.write().await;
+    Ok(Json(
+        state
+            .restore(&req.snapshot, req.strategy, req.dry_run)
+            .await,
+    ))
+}
 
-    if mortars.iter().any(|m| m.name == req.name) {
+/// Copy or move targets between the current state and another operation.
+pub async fn transfer_operation_targets(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TransferTargetsRequest>,
+) -> Result<Json<TransferTargetsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if req.operation.version > SNAPSHOT_VERSION {
         return Err((
-            StatusCode::CONFLICT,
+            StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: format!("Mortar '{}' already exists", req.name),
+                error: format!(
+                    "Unsupported snapshot version {} (supported: {})",
+                    req.operation.version, SNAPSHOT_VERSION
+                ),
             }),
         ));
     }
 
-    mortars.push(MortarPosition::new(
-        req.name.clone(),
-        req.elevation,
-        req.x,
-        req.y,
-    ));
-
-    Ok(Json(SuccessResponse {
-        success: true,
-        message: format!("Mortar '{}' added", req.name),
-    }))
+    let mut operation = req.operation;
+    let report = state
+        .transfer_targets(
+            &mut operation,
+            req.direction,
+            &req.targets,
+            req.mode,
+            req.strategy,
+        )
+        .await;
+    operation.created_at = unix_now();
+    Ok(Json(TransferTargetsResponse { report, operation }))
 }
 
-pub async fn delete_mortar(
+/// Add targets from the markers of a web map export.
+pub async fn import_markers(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<DeletePositionRequest>,
-) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let mut mortars = state.mortars.write().await;
-    let initial_len = mortars.len();
-    mortars.retain(|m| m.name != req.name);
+    Json(req): Json<ImportMarkersRequest>,
+) -> Result<Json<MarkerImport>, (StatusCode, Json<ErrorResponse>)> {
+    let default_type = match req.default_type.as_deref() {
+        None => TargetType::Infanterie,
+        Some(t) => match TargetType::parse_str(t) {
+            Some(t) => t,
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Invalid target type: {}", t),
+                    }),
+                ));
+            }
+        },
+    };
+    let markers = match parse_markers(&req.markers) {
+        Ok(markers) => markers,
+        Err(e) => {
+            return Err((StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })));
+        }
+    };
+    Ok(Json(
+        state
+            .import_markers(&markers, req.reference.as_ref(), default_type)
+            .await,
+    ))
+}
 
-    if mortars.len() < initial_len {
-        Ok(Json(SuccessResponse {
-            success: true,
-            message: format!("Mortar '{}' deleted", req.name),
-        }))
-    } else {
-        Err((
-            StatusCode::NOT_FOUND,
+/// Printable pack of the current operation (HTML by default, `?format=json` for data).
+pub async fn mission_pack(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<MissionPackQuery>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let pack = {
+        let mortars = state.mortars.read().await;
+        let targets = state.targets.read().await;
+        let ballistics = state.ballistics.read().await;
+        let reference_points = state.reference_points.read().await;
+        let solutions = state
+            .pack_solutions(&mortars, &targets, &reference_points, &ballistics)
+            .await;
+        build_mission_pack(
+            &mortars,
+            &targets,
+            &reference_points,
+            &solutions,
+            &ballistics,
+            *state.declination.read().await,
+            query.override_sectors,
+            unix_now(),
+        )
+    };
+    match query.format.as_deref().unwrap_or("html") {
+        "html" => Ok(Html(render_html(&pack)).into_response()),
+        "json" => Ok(Json(pack).into_response()),
+        other => Err((
+            StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: format!("Mortar '{}' not found", req.name),
+                error: format!("Invalid format: {} (expected html or json)", other),
             }),
-        ))
+        )),
     }
 }
 
-pub async fn update_target_ammo(
+pub async fn get_missions(State(state): State<Arc<AppState>>) -> Json<MissionSchedule> {
+    Json(state.missions.read().await.clone())
+}
+
+/// Schedule a named fire-for-effect volley at a fixed time.
+pub async fn schedule_mission(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<UpdateTargetAmmoRequest>,
-) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let ammo_type = match AmmoKind::parse_str(&req.ammo_type) {
-        Some(a) => a,
-        None => {
+    Path(name): Path<String>,
+    Json(req): Json<ScheduleMissionRequest>,
+) -> Result<Json<MissionSchedule>, (StatusCode, Json<ErrorResponse>)> {
+    if name.trim().is_empty() || name.contains(char::is_whitespace) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Invalid mission name: '{}'", name),
+            }),
+        ));
+    }
+    let start = match (req.start, req.start_in_s) {
+        (Some(start), None) => start,
+        (None, Some(delay)) => unix_now().saturating_add(delay),
+        _ => {
             return Err((
                 StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
-                    error: format!("Invalid ammo type: {}", req.ammo_type),
+                    error: "Exactly one of start and start_in_s is required".to_string(),
                 }),
             ))
         }
     };
 
-    let mut targets = state.targets.write().await;
-    if let Some(target) = targets.iter_mut().find(|t| t.name == req.name) {
-        target.ammo_type = ammo_type;
-        Ok(Json(SuccessResponse {
-            success: true,
-            message: format!("Target '{}' ammo set to {}", req.name, ammo_type),
-        }))
-    } else {
-        Err((
+    let mortars = state.mortars.read().await;
+    let targets = state.targets.read().await;
+    if !mortars.iter().any(|m| m.name == req.mortar_name) {
+        return Err((
             StatusCode::NOT_FOUND,
             Json(ErrorResponse {
-                error: format!("Target '{}' not found", req.name),
+                error: format!("Mortar '{}' not found", req.mortar_name),
             }),
-        ))
+        ));
+    }
+    if !targets.iter().any(|t| t.name == req.target_name) {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Target '{}' not found", req.target_name),
+            }),
+        ));
+    }
+    let mission = ScheduledMission {
+        mortar: req.mortar_name,
+        target: req.target_name,
+        start,
+        tubes: req.tubes,
+        rounds_per_tube: req.rounds_per_tube,
+        round_interval_s: req.round_interval_s,
+        ring: req.ring,
+        override_sector: req.override_sector,
+    };
+    // Plan once so that an unreachable mission is refused now, not silently dropped
+    if let Err(error) = state
+        .plan_mission(
+            &mission,
+            &mortars,
+            &targets,
+            &*state.ballistics.read().await,
+        )
+        .await
+    {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse { error }),
+        ));
     }
-}
 
-pub async fn list_targets(State(state): State<Arc<AppState>>) -> Json<TargetListResponse> {
-    let targets = state.targets.read().await;
-    Json(TargetListResponse {
-        positions: targets.clone(),
-    })
+    let mut missions = state.missions.write().await;
+    missions.0.insert(name.clone(), mission.clone());
+    state
+        .events
+        .emit(StateEvent::MissionScheduled { name, mission });
+    Ok(Json(missions.clone()))
 }
 
-pub async fn add_target(
+pub async fn delete_mission(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<AddTargetRequest>,
-) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
-    if req.name.is_empty() {
+    Path(name): Path<String>,
+) -> Result<Json<MissionSchedule>, (StatusCode, Json<ErrorResponse>)> {
+    let mut missions = state.missions.write().await;
+    if missions.0.remove(&name).is_none() {
         return Err((
-            StatusCode::BAD_REQUEST,
+            StatusCode::NOT_FOUND,
             Json(ErrorResponse {
-                error: "Name cannot be empty".to_string(),
+                error: format!("Mission '{}' not found", name),
             }),
         ));
     }
+    state.events.emit(StateEvent::MissionRemoved { name });
+    Ok(Json(missions.clone()))
+}
 
-    let target_type = TargetType::parse_str(&req.target_type).unwrap_or(TargetType::Infanterie);
-    let ammo_type = AmmoKind::parse_str(&req.ammo_type).unwrap_or(AmmoKind::He);
-    let mut targets = state.targets.write().await;
-
-    if targets.iter().any(|t| t.name == req.name) {
+/// Close a mission: report its rounds and effect, log it and archive its target.
+pub async fn end_mission(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(req): Json<EndOfMissionRequest>,
+) -> Result<Json<EndOfMission>, (StatusCode, Json<ErrorResponse>)> {
+    if req.effect.trim().is_empty() {
         return Err((
-            StatusCode::CONFLICT,
+            StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: format!("Target '{}' already exists", req.name),
+                error: "Effect achieved is required".to_string(),
             }),
         ));
     }
+    match state
+        .close_mission(&name, req.effect.trim().to_string(), req.remarks)
+        .await
+    {
+        Ok(report) => Ok(Json(report)),
+        Err(error) => Err((StatusCode::NOT_FOUND, Json(ErrorResponse { error }))),
+    }
+}
 
-    targets.push(TargetPosition::new(
-        req.name.clone(),
-        req.elevation,
-        req.x,
-        req.y,
-        target_type,
-        ammo_type,
-    ));
-
-    Ok(Json(SuccessResponse {
-        success: true,
-        message: format!("Target '{}' added as {}", req.name, target_type),
-    }))
+pub async fn get_mission_log(State(state): State<Arc<AppState>>) -> Json<MissionLog> {
+    Json(state.mission_log.read().await.clone())
 }
 
-pub async fn delete_target(
+/// Scheduled missions, fired shots, expected splashes and lifts of the battery,
+/// merged in chronological order.
+pub async fn timeline(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<DeletePositionRequest>,
-) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let mut targets = state.targets.write().await;
-    let initial_len = targets.len();
-    targets.retain(|t| t.name != req.name);
+    Query(query): Query<TimelineQuery>,
+) -> Json<Timeline> {
+    let shots = shot_events(&*state.solution_history.read().await);
+    let plans = state.plan_missions().await;
+    Json(build_timeline(
+        &*state.missions.read().await,
+        &plans,
+        &shots,
+        unix_now(),
+        query.past_s.unwrap_or(DEFAULT_TIMELINE_PAST_S),
+        query.window_s.unwrap_or(DEFAULT_TIMELINE_WINDOW_S),
+    ))
+}
 
-    if targets.len() < initial_len {
-        Ok(Json(SuccessResponse {
-            success: true,
-            message: format!("Target '{}' deleted", req.name),
-        }))
-    } else {
-        Err((
-            StatusCode::NOT_FOUND,
+/// Fired shots in chronological order (JSON by default, `?format=acmi` for Tacview).
+pub async fn shot_log(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ShotLogQuery>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let events = shot_events(&*state.solution_history.read().await);
+    match query.format.as_deref().unwrap_or("json") {
+        "json" => Ok(Json(events).into_response()),
+        "expenditure" => {
+            Ok(Json(expenditure(&events, &*state.increments.read().await)).into_response())
+        }
+        "acmi" => {
+            let reference = GeoReference {
+                latitude_deg: query.ref_lat.unwrap_or(0.0),
+                longitude_deg: query.ref_lon.unwrap_or(0.0),
+            };
+            Ok(render_acmi(&events, reference, "Mortar shot log").into_response())
+        }
+        other => Err((
+            StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: format!("Target '{}' not found", req.name),
+                error: format!(
+                    "Invalid format: {} (expected json, expenditure or acmi)",
+                    other
+                ),
             }),
-        ))
+        )),
     }
 }
 
-pub async fn update_target_type(
+pub async fn create_share(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<UpdateTargetTypeRequest>,
-) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let target_type = match TargetType::parse_str(&req.target_type) {
-        Some(t) => t,
-        None => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("Invalid target type: {}", req.target_type),
-                }),
-            ))
+    Json(req): Json<CreateShareRequest>,
+) -> Result<Json<ShareLink>, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }));
+
+    let mut target_types = Vec::new();
+    for t in &req.target_types {
+        match TargetType::parse_str(t) {
+            Some(tt) => target_types.push(tt),
+            None => return Err(bad_request(format!("Invalid target type: {}", t))),
         }
+    }
+    let min_reliability = match req.min_reliability.as_deref().map(Reliability::parse_str) {
+        Some(None) => {
+            return Err(bad_request(format!(
+                "Invalid reliability: {}",
+                req.min_reliability.unwrap_or_default()
+            )))
+        }
+        Some(r) => r,
+        None => None,
     };
 
-    let mut targets = state.targets.write().await;
-    if let Some(target) = targets.iter_mut().find(|t| t.name == req.name) {
-        target.target_type = target_type;
+    let filter = ShareFilter {
+        targets: req.targets,
+        target_types,
+        min_reliability,
+        max_age_s: req.max_age_s,
+    };
+    let link = ShareLink::new(req.label, filter, req.ttl_s, unix_now());
+    state
+        .shares
+        .write()
+        .await
+        .insert(link.token.clone(), link.clone());
+    Ok(Json(link))
+}
+
+pub async fn list_shares(State(state): State<Arc<AppState>>) -> Json<ShareListResponse> {
+    let now = unix_now();
+    let mut shares = state.shares.write().await;
+    shares.retain(|_, link| !link.is_expired(now));
+    Json(ShareListResponse {
+        shares: shares.values().cloned().collect(),
+    })
+}
+
+pub async fn revoke_share(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<Json<SuccessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if state.shares.write().await.remove(&token).is_some() {
         Ok(Json(SuccessResponse {
             success: true,
-            message: format!("Target '{}' type set to {}", req.name, target_type),
+            message: "Share link revoked".to_string(),
         }))
     } else {
         Err((
             StatusCode::NOT_FOUND,
             Json(ErrorResponse {
-                error: format!("Target '{}' not found", req.name),
+                error: "Share link not found".to_string(),
             }),
         ))
     }
 }
 
-pub async fn correct_target(
+/// Filtered, read-only target list for holders of a share token (no mortar positions).
+pub async fn shared_view(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CorrectionRequest>,
-) -> Result<Json<CorrectionResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let mut targets = state.targets.write().await;
-
-    let target = match targets.iter().find(|t| t.name == req.target_name) {
-        Some(t) => t.clone(),
+    Path(token): Path<String>,
+) -> Result<Json<SharedViewResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let now = unix_now();
+    let link = {
+        let mut shares = state.shares.write().await;
+        match shares.get(&token) {
+            Some(link) if !link.is_expired(now) => Some(link.clone()),
+            Some(_) => {
+                shares.remove(&token);
+                None
+            }
+            None => None,
+        }
+    };
+    let link = match link {
+        Some(link) => link,
         None => {
             return Err((
                 StatusCode::NOT_FOUND,
                 Json(ErrorResponse {
-                    error: format!("Target '{}' not found", req.target_name),
+                    error: "Share link not found or expired".to_string(),
                 }),
             ))
         }
     };
 
-    let corrected = apply_correction(&target, req.vertical_m, req.horizontal_m);
-    let corrected_name = corrected.name.clone();
-    let new_x = corrected.x;
-    let new_y = corrected.y;
-
-    if let Some(existing) = targets.iter_mut().find(|t| t.name == corrected_name) {
-        existing.x = new_x;
-        existing.y = new_y;
-    } else {
-        targets.push(corrected);
-    }
-
-    Ok(Json(CorrectionResponse {
-        success: true,
-        original: req.target_name,
-        corrected: corrected_name,
-        correction_applied: CorrectionApplied {
-            vertical_m: req.vertical_m,
-            horizontal_m: req.horizontal_m,
-            new_x,
-            new_y,
-        },
+    let targets = state.targets.read().await;
+    Ok(Json(SharedViewResponse {
+        label: link.label,
+        expires_at: link.expires_at,
+        targets: link.filter.apply(&targets, now),
     }))
 }
@@ -1,5 +1,45 @@
-use crate::{apply_correction, calculate_solution_with_dispersion, AmmoKind, AppState, TargetType};
+use crate::airspace::{AirspaceRestriction, Corridor};
+use crate::archive::is_data_archive;
+use crate::bundle::build_bundle;
+use crate::dry_run::changes;
+use crate::environment::{
+    density_altitude_m, Atmosphere, Wind, WindLayer, WindProfile, AIR_TEMPERATURE_RANGE_C,
+    CHARGE_TEMPERATURE_RANGE_C, PRESSURE_RANGE_HPA, WIND_SPEED_RANGE_MPS,
+};
+use crate::events::StateEvent;
+use crate::fire::{
+    render_volley_text, spot_round, volley_plan, SpotObservation, DEFAULT_ROUND_INTERVAL_S,
+};
+use crate::markers::parse_markers;
+use crate::met::parse_met;
+use crate::mission_pack::{build_mission_pack, render_html};
+use crate::observation::{
+    backplot, intersect_bearings, origin_area, CraterAnalysis, Observer, ObserverBearing,
+};
+use crate::reference::{KnownPointShift, ReferencePoint};
+use crate::registration::register;
+use crate::replay::{expenditure, format_utc, render_acmi, shot_events, GeoReference, ShotTrace};
+use crate::safety::{FriendlyPosition, DANGER_CLOSE_MULTIPLE_RANGE};
+use crate::server::{SolveOptions, TransferDirection};
+use crate::share::{ShareFilter, ShareLink};
+use crate::simulation::{SessionSeed, TrainingOffset};
+use crate::snapshot::{
+    read_snapshot, transfer_targets, write_snapshot, MergeStrategy, StateSnapshot, TransferMode,
+};
+use crate::terrain::{ElevationThresholds, TerrainGeoref, TerrainModel};
+use crate::timeline::{
+    build_timeline, ScheduledMission, DEFAULT_TIMELINE_PAST_S, DEFAULT_TIMELINE_WINDOW_S,
+};
+use crate::{
+    apply_correction, apply_correction_in_place, data_layer, export_tables, firing_data_correction,
+    parse_angle_deg, parse_ring, read_metrics_file, unix_now, Angle, AppState, BallisticTables,
+    Clearance, CorrectionPreset, CorrectionRecord, Declination, ElevationSource, MortarStatus,
+    Reliability, Ring, Sector, SignConvention, SolutionRecord, TargetNumbering, TargetType,
+    CHARGE_EFFICIENCY_RANGE, DENSE_STEP_M, DISPERSION_FACTOR_RANGE, MV_CORRECTION_RANGE_PCT,
+    RING_COUNT, RING_LABELS, TARGET_NUMBER_MAX,
+};
 use std::io::{self, Write};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 pub async fn handle_cli_command(line: &str, state: &Arc<AppState>) {
@@ -7,42 +47,219 @@ pub async fn handle_cli_command(line: &str, state: &Arc<AppState>) {
     if parts.is_empty() {
         return;
     }
+    if state.read_only && is_mutation(&parts) {
+        println!("Read-only mode: '{}' is disabled", parts[0]);
+        return;
+    }
+    if state.is_dry_run() && is_mutation(&parts) {
+        dry_run_command(&parts, state).await;
+        return;
+    }
+    run_command(&parts, state).await;
+}
+
+/// Run a mutating command on a scratch copy of the state and list what it
+/// would have changed.
+async fn dry_run_command(parts: &[&str], state: &Arc<AppState>) {
+    if matches!(parts[0], "transfer" | "tr") {
+        println!(
+            "Dry run: '{}' writes operation files and cannot be previewed",
+            parts[0]
+        );
+        return;
+    }
+    let scratch = Arc::new(state.scratch_copy().await);
+    let before = scratch.view().await;
+    println!("[dry run] {}", parts.join(" "));
+    run_command(parts, &scratch).await;
+
+    let changes = changes(&before, &scratch.view().await);
+    if changes.is_empty() {
+        println!("Dry run: nothing would change");
+    } else {
+        println!("Dry run: would change (state left untouched):");
+        for change in &changes {
+            println!("  {}", change);
+        }
+    }
+}
 
+async fn run_command(parts: &[&str], state: &Arc<AppState>) {
     match parts[0] {
         "help" | "h" => print_help(),
-        "list" | "ls" => list_all(state).await,
+        "dry_run" | "dry" => dry_run_cli(parts, state),
+        "list" | "ls" => list_all(parts, state).await,
 
-        "add_mortar" | "am" => add_mortar_cli(&parts, state).await,
-        "add_target" | "at" => add_target_cli(&parts, state).await,
+        "add_mortar" | "am" => add_mortar_cli(parts, state).await,
+        "add_target" | "at" => add_target_cli(parts, state).await,
+        "add_friendly" | "af" => add_friendly_cli(parts, state).await,
 
-        "rm_mortar" | "rmm" => rm_mortar_cli(&parts, state).await,
-        "rm_target" | "rmt" => rm_target_cli(&parts, state).await,
+        "rm_mortar" | "rmm" => rm_mortar_cli(parts, state).await,
+        "rm_target" | "rmt" => rm_target_cli(parts, state).await,
+        "rm_friendly" | "rmf" => rm_friendly_cli(parts, state).await,
+        "move_friendly" | "mvf" => move_friendly_cli(parts, state).await,
 
-        "set_ammo" | "sa" => set_ammo_cli(&parts, state).await,
-        "set_type" | "st" => set_type_cli(&parts, state).await,
+        "set_ammo" | "sa" => set_ammo_cli(parts, state).await,
+        "set_rings" | "sr" => set_rings_cli(parts, state).await,
+        "set_ring" | "sdr" => set_ring_cli(parts, state).await,
+        "charge_eff" | "ce" => charge_efficiency_cli(parts, state).await,
+        "disp_factor" | "df" => dispersion_factor_cli(parts, state).await,
+        "set_bias" | "sb" => set_bias_cli(parts, state).await,
+        "set_status" | "ss" => set_status_cli(parts, state).await,
+        "sector" | "sec" => sector_cli(parts, state).await,
+        "register" | "reg" => register_cli(parts, state).await,
+        "registration" | "regs" => registration_cli(parts, state).await,
+        "move" | "mv" => move_cli(parts, state).await,
+        "displacements" | "disp" => displacements_cli(parts, state).await,
+        "set_type" | "st" => set_type_cli(parts, state).await,
+        "set_intel" | "si" => set_intel_cli(parts, state).await,
+        "clearance" | "clr" => clearance_cli(parts, state).await,
 
         "calc" | "c" => {
             if parts.len() < 3 {
-                println!("Usage: calc <mortar_name> <target_name>");
+                println!("Usage: calc <mortar_name> <target_name> [<obs_x> <obs_y>]");
+                println!("  obs_x obs_y: position de l'observateur (calcul de l'angle T)");
             } else {
-                calc_and_print(state, parts[1], parts[2]).await;
+                let observer = match (
+                    parts.get(3).and_then(|v| v.parse::<f64>().ok()),
+                    parts.get(4).and_then(|v| v.parse::<f64>().ok()),
+                ) {
+                    (Some(x), Some(y)) => Some(Observer {
+                        name: "OBS".to_string(),
+                        x,
+                        y,
+                    }),
+                    _ => None,
+                };
+                calc_and_print(state, parts[1], parts[2], observer.as_ref()).await;
             }
         }
 
         "correct" | "cor" => {
-            if parts.len() < 4 {
-                println!("Usage: correct <target_name> <vertical_m> <horizontal_m>");
-                println!("  vertical_m:   Nord (negatif) / Sud (positif)");
-                println!("  horizontal_m: Ouest (negatif) / Est (positif)");
-                println!("  Exemple: correct T1 -50 30  (obus tombe 50m au Nord, 30m a l'Est)");
+            // `correct <target> <preset>` applies a named correction
+            let preset = parts.get(2).filter(|p| p.parse::<f64>().is_err());
+            if parts.len() < 3 || (preset.is_none() && parts.len() < 4) {
+                println!("Usage: correct <target_name> <vertical_m> <horizontal_m> [--in-place]");
+                println!("       correct <target_name> <preset> [--in-place]");
+                println!(
+                    "  vertical_m/horizontal_m: {}",
+                    state.sign_convention.read().await.label()
+                );
+                println!("  --in-place:   deplace la cible au lieu de creer une copie _C");
+                println!(
+                    "  Exemple: correct T1 -50 30  (S+/E+ : obus tombe 50m au Nord, 30m a l'Est)"
+                );
             } else {
                 let target_name = parts[1];
-                let vertical: f64 = parts[2].parse().unwrap_or(0.0);
-                let horizontal: f64 = parts[3].parse().unwrap_or(0.0);
-                correct_target_cli(state, target_name, vertical, horizontal).await;
+                let (vertical, horizontal, options) = match preset {
+                    Some(name) => match state.correction_presets.read().await.get(name) {
+                        Some(p) => {
+                            // Presets are stored in the standard convention
+                            let convention = *state.sign_convention.read().await;
+                            let (v, h) = convention.to_standard(p.vertical_m, p.horizontal_m);
+                            println!("Correction enregistree: {}", name);
+                            (v, h, &parts[3..])
+                        }
+                        None => {
+                            println!("Unknown correction preset: {}", name);
+                            return;
+                        }
+                    },
+                    None => (
+                        parts[2].parse().unwrap_or(0.0),
+                        parts[3].parse().unwrap_or(0.0),
+                        &parts[4..],
+                    ),
+                };
+                let in_place = options.iter().any(|p| *p == "--in-place" || *p == "-i");
+                if in_place {
+                    correct_target_in_place_cli(state, target_name, vertical, horizontal).await;
+                } else {
+                    correct_target_cli(state, target_name, vertical, horizontal).await;
+                }
+            }
+        }
+
+        "adjust" | "adj" => {
+            if parts.len() < 5 {
+                println!("Usage: adjust <mortar> <target> <vertical_m> <horizontal_m> [ring]");
+                println!(
+                    "  Donne la correction pieces (direction/elevation) sans deplacer la cible"
+                );
+                println!(
+                    "  vertical_m/horizontal_m: {}",
+                    state.sign_convention.read().await.label()
+                );
+            } else {
+                let vertical: f64 = parts[3].parse().unwrap_or(0.0);
+                let horizontal: f64 = parts[4].parse().unwrap_or(0.0);
+                let ring: Option<Ring> = parts.get(5).and_then(|r| parse_ring(r));
+                adjust_fire_cli(state, parts[1], parts[2], vertical, horizontal, ring).await;
+            }
+        }
+
+        "spot" => {
+            if parts.len() < 3 {
+                println!("Usage: spot <mortar> <target> [<vertical_m> <horizontal_m>] [ring]");
+                println!("  Sans deviation, l'impact du coup de reglage est simule");
+            } else {
+                let observation = match (
+                    parts.get(3).and_then(|v| v.parse::<f64>().ok()),
+                    parts.get(4).and_then(|h| h.parse::<f64>().ok()),
+                ) {
+                    (Some(vertical_m), Some(horizontal_m)) => Some(SpotObservation {
+                        vertical_m,
+                        horizontal_m,
+                    }),
+                    _ => None,
+                };
+                let ring_arg = if observation.is_some() { 5 } else { 3 };
+                let ring: Option<Ring> = parts.get(ring_arg).and_then(|r| parse_ring(r));
+                spot_cli(state, parts[1], parts[2], observation, ring).await;
             }
         }
 
+        "volley" | "vol" => volley_cli(parts, state).await,
+        "mission" | "ms" => mission_cli(parts, state).await,
+        "eom" => eom_cli(parts, state).await,
+        "timeline" | "tl" => timeline_cli(parts, state).await,
+        "intersect" | "ix" => intersect_cli(parts, state).await,
+        "trp" => trp_cli(parts, state).await,
+        "shift" => shift_cli(parts, state).await,
+        "backplot" | "bp" => backplot_cli(parts, state).await,
+        "crater" => crater_cli(parts, state).await,
+        "history" | "hist" => history_cli(parts, state).await,
+        "save" => save_cli(parts, state).await,
+        "restore" => restore_cli(parts, state).await,
+        "transfer" | "tr" => transfer_cli(parts, state).await,
+        "convention" | "conv" => convention_cli(parts, state).await,
+        "ammo_policy" | "ap" => ammo_policy_cli(parts, state).await,
+        "training" => training_cli(parts, state).await,
+        "seed" => seed_cli(parts, state).await,
+        "numbering" | "num" => numbering_cli(parts, state).await,
+        "declination" | "decl" => declination_cli(parts, state).await,
+        "elev_thresholds" | "et" => elev_thresholds_cli(parts, state).await,
+        "danger_close" | "dc" => danger_close_cli(parts, state).await,
+        "charge_temp" | "ctemp" => charge_temp_cli(parts, state).await,
+        "set_wind" | "wind" => wind_cli(parts, state).await,
+        "atmosphere" | "atm" => atmosphere_cli(parts, state).await,
+        "met" => met_cli(parts, state).await,
+        "terrain" | "dem" => terrain_cli(parts, state).await,
+        "preset" => preset_cli(parts, state).await,
+        "airspace" | "air" => airspace_cli(parts, state).await,
+        "share" => share_cli(parts, state).await,
+        "pack" => pack_cli(parts, state).await,
+        "bundle" => bundle_cli(parts, state).await,
+        "replay" => replay_cli(parts, state).await,
+        "import" => import_cli(parts, state).await,
+        "reload" => reload_cli(state).await,
+        "verify" => {
+            print_table_summary(&*state.ballistics.read().await);
+        }
+        "export_tables" => export_tables_cli(parts, state).await,
+        "expenditure" | "exp" => expenditure_cli(state).await,
+        "unshare" => unshare_cli(parts, state).await,
+
         "clear" => {
             print!("\x1B[2J\x1B[1;1H");
             let _ = io::stdout().flush();
@@ -55,31 +272,159 @@ pub async fn handle_cli_command(line: &str, state: &Arc<AppState>) {
     }
 }
 
+/// Whether a command modifies the state (rejected in read-only mode).
+fn is_mutation(parts: &[&str]) -> bool {
+    match parts[0] {
+        "add_mortar" | "am" | "add_target" | "at" | "rm_mortar" | "rmm" | "rm_target" | "rmt"
+        | "set_ammo" | "sa" | "set_rings" | "sr" | "set_ring" | "sdr" | "charge_eff" | "ce"
+        | "disp_factor" | "df" | "set_bias" | "sb" | "set_status" | "ss" | "register" | "reg"
+        | "set_type" | "st" | "set_intel" | "si" | "correct" | "cor" | "spot" | "intersect"
+        | "ix" | "backplot" | "bp" | "crater" | "shift" | "restore" | "unshare" | "import"
+        | "reload" | "move" | "mv" | "met" | "add_friendly" | "af" | "rm_friendly" | "rmf"
+        | "move_friendly" | "mvf" => true,
+        // With only a target, `clearance` shows its clearance
+        "clearance" | "clr" => parts.len() > 2,
+        // With only a mortar, `registration` shows its registration
+        "registration" | "regs" => parts.len() > 2,
+        // With only a mortar, `sector` shows its sector of fire
+        "sector" | "sec" => parts.len() > 2,
+        // Without arguments, `convention` only shows the active one
+        "convention" | "conv" => parts.len() > 1,
+        // Without arguments, these settings commands only show the setting
+        "ammo_policy" | "ap" | "training" | "seed" | "numbering" | "num" | "declination"
+        | "decl" | "elev_thresholds" | "et" | "danger_close" | "dc" | "charge_temp" | "ctemp"
+        | "set_wind" | "wind" | "atmosphere" | "atm" | "preset" | "airspace" | "air"
+        | "mission" | "ms" | "trp" | "eom" | "terrain" | "dem" => parts.len() > 1,
+        // Without arguments, `share` only lists the links
+        "share" => parts.len() > 1,
+        // Transfers between operation files leave the state untouched
+        "transfer" | "tr" => parts.iter().skip(2).take(2).any(|p| *p == LIVE_OPERATION),
+        _ => false,
+    }
+}
+
 pub fn print_help() {
     println!();
     println!("=== MORTAR CALCULATOR CLI ===");
     println!();
     println!("Commands:");
     println!("  help, h                                    Show this help");
-    println!("  list, ls                                   List all mortars and targets");
-    println!("  add_mortar, am <n> <e> <x> <y>             Add mortar");
-    println!("  add_target, at <n> <e> <x> <y> [type] [ammo]  Add target (type: INF/VEH/SOU, ammo: HE/PRACTICE/SMOKE/FLARE)");
+    println!("  list, ls [min_reliability] [max_age_s]     List all mortars and targets");
+    println!("  add_mortar, am <n> <e> <x> <y> [weapon]    Add mortar (M224/M252/M120 or 60mm/81mm/120mm)");
+    println!("  add_target, at <n|-> <e> <x> <y> [type] [ammo]  Add target (type: INF/VEH/SOU, ammo: HE/PRACTICE/SMOKE/FLARE)");
     println!("  rm_mortar, rmm <name>                      Remove mortar");
     println!("  rm_target, rmt <name>                      Remove target");
+    println!(
+        "  add_friendly, af <n> <e|-> <x> <y> [desc]  Add own troops (checked by danger close)"
+    );
+    println!("  rm_friendly, rmf <name>                    Remove own troops");
+    println!("  move_friendly, mvf <name> <e|-> <x> <y>    Move own troops");
     println!("  set_ammo, sa <target> <ammo>               Set target ammo type");
     println!("  set_type, st <target> <type>               Set target type");
-    println!("  calc, c <mortar> <target>            Calculate firing solution");
-    println!("  correct, cor <target> <V> <H>        Correct target position");
-    println!("                                         V: Nord(-)/Sud(+)  H: Ouest(-)/Est(+)");
+    println!(
+        "  set_rings, sr <mortar> [ring...]           Exclude unavailable rings (none: clear)"
+    );
+    println!("  set_ring, sdr <mortar> [ring]              Set default ring (none: clear)");
+    println!("  charge_eff, ce <mortar> [factor]           Set charge efficiency, e.g. 0.97 (none: clear)");
+    println!("  disp_factor, df <mortar> [factor] [ring..] Enlarge dispersions of a worn tube (none: clear)");
+    println!("  set_bias, sb <mortar> [mv_pct]             Set tube muzzle velocity deviation, e.g. -2 (none: clear)");
+    println!("  set_status, ss <mortar> <READY|MOVING|OOA|RESUPPLY>  Set tube/crew readiness");
+    println!("  sector, sec <mortar> [<left> <right> [max_m] | off]  Sector of fire (angles: 45, 800mil)");
+    println!("  register, reg <mortar> <x> <y> <V> <H>     Register on a known point from the observed impact");
+    println!("  registration, regs <mortar> [-]            Show mortar registration (-: clear)");
+    println!("  move, mv <mortar> <e|-> <x> <y>            Move mortar, show re-lay data");
+    println!("  displacements, disp <mortar>               Mortar position history");
+    println!("  set_intel, si <target> <reliability> [source]  Mark target observed now");
+    println!("  clearance, clr <target> [by|-]             Clear target for fire (-: revoke)");
+    println!("  calc, c <mortar> <target> [ox oy]    Calculate firing solution (ox oy: observer)");
+    println!("  correct, cor <target> <V> <H> [-i]   Correct target position");
+    println!(
+        "                                         V/H signs: see 'convention' (default S+/E+)"
+    );
+    println!("                                         -i, --in-place: move target, no _C copy");
+    println!("  adjust, adj <m> <t> <V> <H> [ring]   Gun corrections (mils) from deviation");
+    println!("  convention, conv [N+|S+ E+|W+]       Show/set deviation sign convention");
+    println!("  ammo_policy, ap [<type> <ammo|->]    Show/set default ammo per target type");
+    println!("  training [<north_m> <east_m>|off]    Show/set simulated-fire systematic error");
+    println!("  seed [<n>|off]                       Show/set session seed of simulated impacts");
+    println!("  numbering, num [<prefix> [next]]     Show/set auto-numbering of unnamed targets");
+    println!("  declination, decl [<deg> [conv_deg]] Show/set declination for magnetic azimuths");
+    println!(
+        "  elev_thresholds, et [<caution_m> <critical_m>]  Show/set elevation difference warnings"
+    );
+    println!("  danger_close, dc [multiple]          Show/set danger radius of friendlies (x dispersion)");
+    println!("  charge_temp, ctemp [<deg_c>|off]     Show/set propellant temperature applied to solutions");
+    println!(
+        "  set_wind, wind [<dir> <mps> [h:dir:mps...]|off]  Show/set wind applied to solutions"
+    );
+    println!("  atmosphere, atm [<air_c> [hpa]|off]  Show/set air conditions applied to solutions");
+    println!(
+        "  met <file>|<METCM/METB3 message>     Load wind and air conditions from a MET message"
+    );
+    println!(
+        "  terrain, dem [<png> <x,y,cell[,scale[,base]]>|off]  Show/load heightmap filling elevations"
+    );
+    println!("  preset [<name> <V> <H>|<name> -]     Show/save/delete named corrections");
+    println!(
+        "  airspace, air [<name> <ceiling_m> [x1 y1 x2 y2 width_m]|<name> -]  Airspace ceilings"
+    );
+    println!("  spot <m> <t> [V H] [ring]            Spotting round (simulated if no V H)");
+    println!(
+        "  volley, vol <m> <t> <tubes> <rounds> [interval_s] [ring]  Fire-for-effect volley table"
+    );
+    println!("  mission, ms [<n> <m> <t> <in_s> <rounds> [tubes]|<n> -]  Scheduled fire missions");
+    println!(
+        "  eom [<mission> <effect...>]          Close a mission and archive its target (none: log)"
+    );
+    println!("  timeline, tl [window_s]              Battery timeline: fire, splashes and lifts");
+    println!("  intersect, ix <n> <x1> <y1> <az1> <x2> <y2> <az2> [e]  Fix target from 2 bearings");
+    println!("  backplot, bp <n> <x> <y> <az> <x> <y> <az> [...]     Locate hostile mortar");
+    println!(
+        "  crater <n> <x> <y> <back_az> <min_m> <max_m> [...]   Hostile firing area from craters"
+    );
+    println!("  trp [<name> <e> <x> <y> [description]|<name> -]  Show/set/delete reference points");
+    println!("  shift <trp> <dir> <right_m> <add_m> [up_m] [name]  Target shifted from a reference point");
+    println!("  history, hist <target>               Show solution history for a target");
+    println!("  save <file>                          Save mortars and targets to a JSON file");
+    println!("  restore <file> [strategy] [--dry-run]  Restore a saved file");
+    println!("  transfer, tr <copy|move> <from> <to> [target...] [--overwrite]  Transfer targets between operations");
+    println!("  share [all|target...]                Create a read-only share link (none: list)");
+    println!("  unshare <token>                      Revoke a share link");
+    println!(
+        "  pack <file>                          Write the printable mission pack (.html/.json)"
+    );
+    println!("  bundle <file.zip> [--with-binary]    Offline bundle: tables, metrics and state");
+    println!("  replay <file.acmi> [lat lon]         Export fired shots for Tacview replay");
+    println!("  import <file.json> [lat lon]         Import map markers (GeoJSON...) as targets");
+    println!("  verify                               Data quality summary of ballistic tables");
+    println!(
+        "  reload                               Reload ballistic tables and metrics from disk"
+    );
+    println!(
+        "  export_tables <dir> [step_m]         Write loaded tables as CSV (resampled if step)"
+    );
+    println!(
+        "  dry_run, dry [on|off]                Preview mutating commands without applying them"
+    );
+    println!("  expenditure, exp                     Bombs and charge increments fired");
+    println!("                                         strategy: replace-all, merge-keep-existing, merge-overwrite");
     println!("  clear                                Clear screen");
     println!();
     println!("Web interface available at: http://localhost:3000");
     println!();
 }
 
-pub async fn list_all(state: &Arc<AppState>) {
+pub async fn list_all(parts: &[&str], state: &Arc<AppState>) {
+    let min_reliability = parts.get(1).and_then(|r| Reliability::parse_str(r));
+    let max_age_s: Option<u64> = parts.get(2).and_then(|a| a.parse().ok());
+    let now = unix_now();
+
     let mortars = state.mortars.read().await;
     let targets = state.targets.read().await;
+    let targets: Vec<&crate::TargetPosition> = targets
+        .iter()
+        .filter(|t| t.matches_intel(min_reliability, max_age_s, now))
+        .collect();
 
     println!();
     println!("--- MORTIERS ({}) ---", mortars.len());
@@ -87,9 +432,107 @@ pub async fn list_all(state: &Arc<AppState>) {
         println!("  (aucun)");
     } else {
         for m in mortars.iter() {
+            let excluded = if m.excluded_rings.is_empty() {
+                String::new()
+            } else {
+                let rings: Vec<String> =
+                    m.excluded_rings.iter().map(|r| format!("{}R", r)).collect();
+                format!(" (exclus: {})", rings.join(" "))
+            };
+            let default_ring = m
+                .default_ring
+                .map(|r| format!(" (defaut: {}R)", r))
+                .unwrap_or_default();
+            let charges = m
+                .charge_efficiency
+                .map(|f| format!(" (charges: {:.1}%)", f * 100.0))
+                .unwrap_or_default();
+            let dispersion = if m.dispersion_factors.is_empty() {
+                String::new()
+            } else {
+                let factors: Vec<String> = m
+                    .dispersion_factors
+                    .iter()
+                    .map(|(r, f)| format!("{}R x{:.2}", r, f))
+                    .collect();
+                format!(" (dispersion: {})", factors.join(" "))
+            };
+            let bias = m
+                .mv_correction_pct
+                .map(|pct| format!(" (V0: {:+.1}%)", pct))
+                .unwrap_or_default();
+            let registration = m
+                .registration
+                .as_ref()
+                .map(|r| {
+                    format!(
+                        " (reglage: {:+.0}m {:+.0}mil)",
+                        r.range_correction_m, r.deflection_mil
+                    )
+                })
+                .unwrap_or_default();
+            let status = if m.status.is_ready() {
+                String::new()
+            } else {
+                format!(" ({})", m.status)
+            };
+            let sector = m
+                .sector
+                .map(|s| format!(" (secteur {})", s))
+                .unwrap_or_default();
             println!(
-                "  {} : X={:.0} Y={:.0} E={:.0}m",
-                m.name, m.x, m.y, m.elevation
+                "  {} : X={:.0} Y={:.0} E={:.0}m [{}]{}{}{}{}{}{}{}{}",
+                m.name,
+                m.x,
+                m.y,
+                m.elevation,
+                m.weapon,
+                excluded,
+                default_ring,
+                charges,
+                dispersion,
+                bias,
+                registration,
+                status,
+                sector
+            );
+        }
+    }
+
+    let points = state.reference_points.read().await;
+    if !points.is_empty() {
+        println!();
+        println!("--- POINTS DE REFERENCE ({}) ---", points.0.len());
+        for (name, p) in &points.0 {
+            println!(
+                "  {} : X={:.0} Y={:.0} E={:.0}m{}",
+                name,
+                p.x,
+                p.y,
+                p.elevation,
+                p.description
+                    .as_deref()
+                    .map(|d| format!(" ({})", d))
+                    .unwrap_or_default()
+            );
+        }
+    }
+
+    let friendlies = state.friendlies.read().await;
+    if !friendlies.is_empty() {
+        println!();
+        println!("--- AMIS ({}) ---", friendlies.len());
+        for f in friendlies.iter() {
+            println!(
+                "  {} : X={:.0} Y={:.0} E={:.0}m{}",
+                f.name,
+                f.x,
+                f.y,
+                f.elevation,
+                f.description
+                    .as_ref()
+                    .map(|d| format!(" ({})", d))
+                    .unwrap_or_default()
             );
         }
     }
@@ -100,9 +543,37 @@ pub async fn list_all(state: &Arc<AppState>) {
         println!("  (aucune)");
     } else {
         for t in targets.iter() {
+            let intel = match (t.reliability, t.observation_age(now)) {
+                (Some(r), Some(age)) => format!(" ({} il y a {}s)", r, age),
+                (Some(r), None) => format!(" ({})", r),
+                (None, Some(age)) => format!(" (vu il y a {}s)", age),
+                (None, None) => String::new(),
+            };
+            let staleness = match t.staleness(now) {
+                Some(s) if s.stale => format!(" (perime {:.0}%)", s.score * 100.0),
+                Some(s) => format!(" (peremption {:.0}%)", s.score * 100.0),
+                None => String::new(),
+            };
+            let cleared = match &t.clearance {
+                Some(c) => format!(" [autorisee: {}]", c.cleared_by),
+                None => String::new(),
+            };
+            let uncertainty = t
+                .uncertainty_m
+                .map(|r| format!(" (+/- {:.0}m)", r))
+                .unwrap_or_default();
             println!(
-                "  {} : X={:.0} Y={:.0} E={:.0}m [{}] [{}]",
-                t.name, t.x, t.y, t.elevation, t.target_type, t.ammo_type
+                "  {} : X={:.0} Y={:.0} E={:.0}m [{}] [{}]{}{}{}{}",
+                t.name,
+                t.x,
+                t.y,
+                t.elevation,
+                t.target_type,
+                t.ammo_type,
+                uncertainty,
+                intel,
+                staleness,
+                cleared
             );
         }
     }
@@ -111,148 +582,2742 @@ pub async fn list_all(state: &Arc<AppState>) {
 
 async fn add_mortar_cli(parts: &[&str], state: &Arc<AppState>) {
     if parts.len() < 5 {
-        println!("Usage: add_mortar <name> <elevation> <x> <y>");
+        println!("Usage: add_mortar <name> <elevation> <x> <y> [weapon]");
+        println!("  elevation: '-' pour l'altitude de repli configuree");
+        println!("  weapon: M224 (defaut), M252, M120, ou le calibre 60mm, 81mm, 120mm");
+        return;
+    }
+
+    let name = parts[1].to_string();
+    let elevation = parse_elevation_arg(parts[2]);
+    let x: f64 = parts[3].parse().unwrap_or(0.0);
+    let y: f64 = parts[4].parse().unwrap_or(0.0);
+    let weapon = match parts.get(5) {
+        Some(w) => match crate::WeaponSystem::parse_str(w) {
+            Some(weapon) => weapon,
+            None => {
+                println!("Unknown weapon system: {}", w);
+                return;
+            }
+        },
+        None => crate::WeaponSystem::default(),
+    };
+
+    let mut mortars = state.mortars.write().await;
+    if mortars.iter().any(|m| m.name == name) {
+        println!("Error: Mortar '{}' already exists", name);
+    } else {
+        let (elevation, source) = {
+            let targets = state.targets.read().await;
+            state
+                .fill_elevation(elevation, x, y, &mortars, &targets)
+                .await
+        };
+        let mut mortar = crate::MortarPosition::new(name.clone(), elevation, x, y);
+        mortar.elevation_source = source;
+        mortar.weapon = weapon;
+        mortars.push(mortar.clone());
+        state.events.emit(StateEvent::MortarAdded { mortar });
+        println!(
+            "Mortar '{}' added{}",
+            name,
+            elevation_note(elevation, source)
+        );
+    }
+}
+
+async fn add_friendly_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 5 {
+        println!("Usage: add_friendly <name> <elevation> <x> <y> [description...]");
+        println!("  elevation: '-' pour l'altitude de repli configuree");
+        println!(
+            "  Les tirs dont le rayon de danger atteint l'ami sont signales (voir 'danger_close')"
+        );
+        return;
+    }
+
+    let name = parts[1].to_string();
+    let elevation = parse_elevation_arg(parts[2]);
+    let x: f64 = parts[3].parse().unwrap_or(0.0);
+    let y: f64 = parts[4].parse().unwrap_or(0.0);
+
+    let mut friendlies = state.friendlies.write().await;
+    if friendlies.iter().any(|f| f.name == name) {
+        println!("Error: Friendly '{}' already exists", name);
+        return;
+    }
+    let (elevation, source) = {
+        let mortars = state.mortars.read().await;
+        let targets = state.targets.read().await;
+        state
+            .fill_elevation(elevation, x, y, &mortars, &targets)
+            .await
+    };
+    let mut friendly = FriendlyPosition::new(name.clone(), elevation, x, y);
+    if parts.len() > 5 {
+        friendly.description = Some(parts[5..].join(" "));
+    }
+    friendlies.push(friendly.clone());
+    state.events.emit(StateEvent::FriendlyAdded { friendly });
+    println!(
+        "Friendly '{}' added{}",
+        name,
+        elevation_note(elevation, source)
+    );
+}
+
+/// Target name argument: `-` takes the next number of the target numbering.
+fn parse_name_arg(arg: &str) -> Option<String> {
+    match arg {
+        "-" => None,
+        name => Some(name.to_string()),
+    }
+}
+
+/// Elevation argument: `-` leaves it to the configured fallback.
+fn parse_elevation_arg(arg: &str) -> Option<f64> {
+    match arg {
+        "-" => None,
+        e => Some(e.parse().unwrap_or(0.0)),
+    }
+}
+
+/// Mention of a fallback elevation after an "added" message.
+fn elevation_note(elevation: f64, source: ElevationSource) -> String {
+    if source.is_provided() {
+        String::new()
+    } else {
+        format!(
+            " (altitude {:.0} m, source: {})",
+            elevation,
+            source.as_str()
+        )
+    }
+}
+
+async fn add_target_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 5 {
+        println!("Usage: add_target <name> <elevation> <x> <y> [target_type] [ammo_type]");
+        println!("  target_type: INFANTERIE/INF, VEHICULE/VEH, SOUTIEN/SOU (default: INFANTERIE)");
+        println!("  ammo_type: HE, PRACTICE, SMOKE, FLARE (default: ammo_policy, else HE)");
+        println!("  name: '-' pour le prochain numero de cible (voir 'numbering')");
+        println!("  elevation: '-' pour l'altitude de repli configuree");
+        return;
+    }
+
+    let elevation = parse_elevation_arg(parts[2]);
+    let x: f64 = parts[3].parse().unwrap_or(0.0);
+    let y: f64 = parts[4].parse().unwrap_or(0.0);
+
+    let ttype = if parts.len() > 5 {
+        TargetType::parse_str(parts[5]).unwrap_or(TargetType::Infanterie)
+    } else {
+        TargetType::Infanterie
+    };
+
+    let ammo = state.default_ammo(parts.get(6).copied(), ttype).await;
+
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+    let name = state.target_name(parse_name_arg(parts[1]), &targets).await;
+    if targets.iter().any(|t| t.name == name) {
+        println!("Error: Target '{}' already exists", name);
+    } else {
+        let (elevation, source) = state
+            .fill_elevation(elevation, x, y, &mortars, &targets)
+            .await;
+        let mut target = crate::TargetPosition::new(name.clone(), elevation, x, y, ttype, ammo);
+        target.elevation_source = source;
+        targets.push(target.clone());
+        state.events.emit(StateEvent::TargetAdded { target });
+        println!(
+            "Target '{}' added as {} [{}]{}",
+            name,
+            ttype,
+            ammo,
+            elevation_note(elevation, source)
+        );
+    }
+}
+
+async fn mission_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        [name, "-"] => {
+            if state.missions.write().await.0.remove(*name).is_none() {
+                println!("Unknown mission: {}", name);
+                return;
+            }
+            state.events.emit(StateEvent::MissionRemoved {
+                name: name.to_string(),
+            });
+        }
+        [name, mortar, target, in_s, rounds, rest @ ..] if rest.len() <= 1 => {
+            let (delay, rounds_per_tube, tubes) = match (
+                in_s.parse::<u64>(),
+                rounds.parse::<u32>(),
+                rest.first().map_or(Ok(1), |t| t.parse::<u32>()),
+            ) {
+                (Ok(d), Ok(r), Ok(t)) => (d, r, t),
+                _ => {
+                    println!("Invalid delay, rounds or tubes");
+                    return;
+                }
+            };
+            let mission = ScheduledMission {
+                mortar: mortar.to_string(),
+                target: target.to_string(),
+                start: unix_now().saturating_add(delay),
+                tubes,
+                rounds_per_tube,
+                round_interval_s: DEFAULT_ROUND_INTERVAL_S,
+                ring: None,
+                override_sector: false,
+            };
+            {
+                let mortars = state.mortars.read().await;
+                let targets = state.targets.read().await;
+                let ballistics = state.ballistics.read().await;
+                let plan = state
+                    .plan_mission(&mission, &mortars, &targets, &ballistics)
+                    .await;
+                match plan {
+                    Ok(plan) => {
+                        for f in plan.danger_close.iter().flat_map(|d| &d.friendlies) {
+                            println!(
+                                "Warning: friendly '{}' {:.0} m from the target, inside the danger radius ({})",
+                                f.name,
+                                f.distance_m,
+                                f.rings.join(", ")
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        println!("Error: {}", e);
+                        return;
+                    }
+                }
+            }
+            state
+                .missions
+                .write()
+                .await
+                .0
+                .insert(name.to_string(), mission.clone());
+            state.events.emit(StateEvent::MissionScheduled {
+                name: name.to_string(),
+                mission,
+            });
+        }
+        _ => {
+            println!("Usage: mission [<name> <mortar> <target> <in_s> <rounds_per_tube> [tubes] | <name> -]");
+            println!("  Exemple: mission FEU1 M1 T1 120 4 2  (feu dans 2 min, 2 tubes x 4 coups)");
+            return;
+        }
+    }
+    let missions = state.missions.read().await;
+    if missions.is_empty() {
+        println!("Aucune mission programmee");
+    }
+    let now = unix_now();
+    for (name, m) in &missions.0 {
+        println!(
+            "  {:<10} {} -> {}, {} tubes x {} coups, feu {}",
+            name,
+            m.mortar,
+            m.target,
+            m.tubes,
+            m.rounds_per_tube,
+            format_offset(m.start as f64 - now as f64)
+        );
+    }
+}
+
+async fn eom_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1..).unwrap_or_default() {
+        [] => {
+            let log = state.mission_log.read().await;
+            if log.is_empty() {
+                println!("Aucune mission close");
+            }
+            for r in &log.0 {
+                println!(
+                    "  {}  {:<10} {} -> {}, {} coups ({} reglage + {} efficacite) : {}",
+                    format_utc(r.closed_at),
+                    r.mission,
+                    r.mortar,
+                    r.target.name,
+                    r.rounds_fired,
+                    r.adjusting_rounds,
+                    r.effect_rounds,
+                    r.effect
+                );
+            }
+        }
+        [name, effect @ ..] if !effect.is_empty() => {
+            match state.close_mission(name, effect.join(" "), None).await {
+                Ok(r) => {
+                    println!();
+                    println!("=== FIN DE MISSION {} ===", r.mission);
+                    println!("  Mortier:        {}", r.mortar);
+                    println!(
+                        "  Cible:          {} ({:.0}, {:.0}) archivee",
+                        r.target.name, r.target.x, r.target.y
+                    );
+                    println!(
+                        "  Coups tires:    {} ({} reglage + {} efficacite)",
+                        r.rounds_fired, r.adjusting_rounds, r.effect_rounds
+                    );
+                    match &r.final_correction {
+                        Some(c) => println!(
+                            "  Correction:     V {:+.0} m, H {:+.0} m ({} au total)",
+                            c.vertical_m,
+                            c.horizontal_m,
+                            r.target.corrections.len()
+                        ),
+                        None => println!("  Correction:     aucune"),
+                    }
+                    println!("  Effet:          {}", r.effect);
+                }
+                Err(e) => println!("Error: {}", e),
+            }
+        }
+        _ => {
+            println!("Usage: eom [<mission> <effect...>]");
+            println!("  Exemple: eom FEU1 objectif neutralise");
+        }
+    }
+}
+
+/// Relative time as `T+mm:ss` / `T-mm:ss`.
+fn format_offset(offset_s: f64) -> String {
+    let sign = if offset_s < 0.0 { '-' } else { '+' };
+    let secs = offset_s.abs().round() as u64;
+    format!("T{}{:02}:{:02}", sign, secs / 60, secs % 60)
+}
+
+async fn timeline_cli(parts: &[&str], state: &Arc<AppState>) {
+    let window_s = match parts.get(1).map(|w| w.parse::<u64>()) {
+        Some(Ok(w)) => w,
+        Some(Err(_)) => {
+            println!("Usage: timeline [window_s]");
+            return;
+        }
+        None => DEFAULT_TIMELINE_WINDOW_S,
+    };
+    let shots = shot_events(&*state.solution_history.read().await);
+    let plans = state.plan_missions().await;
+    let timeline = build_timeline(
+        &*state.missions.read().await,
+        &plans,
+        &shots,
+        unix_now(),
+        DEFAULT_TIMELINE_PAST_S,
+        window_s,
+    );
+    println!();
+    println!(
+        "=== CHRONOLOGIE ({} -> {}) ===",
+        format_utc(timeline.from),
+        format_utc(timeline.until)
+    );
+    if timeline.events.is_empty() {
+        println!("  Aucun evenement");
+    }
+    for e in &timeline.events {
+        let rounds = if e.rounds > 0 {
+            format!("{} x {} {}R", e.rounds, e.ammo_type, e.ring)
+        } else {
+            String::new()
+        };
+        println!(
+            "  {}  {:<6} {:<10} {} -> {}  {}",
+            format_offset(e.offset_s),
+            e.kind,
+            e.mission.as_deref().unwrap_or("-"),
+            e.mortar,
+            e.target,
+            rounds
+        );
+    }
+    for issue in &timeline.issues {
+        println!("  ! {}: {}", issue.mission, issue.error);
+    }
+}
+
+async fn volley_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 5 {
+        println!("Usage: volley <mortar> <target> <tubes> <rounds_per_tube> [interval_s] [ring]");
+        println!(
+            "  Tableau de tir d'une salve (intervalle par defaut: {:.0} s)",
+            DEFAULT_ROUND_INTERVAL_S
+        );
+        return;
+    }
+    let (tubes, rounds_per_tube) = match (parts[3].parse::<u32>(), parts[4].parse::<u32>()) {
+        (Ok(t), Ok(r)) => (t, r),
+        _ => {
+            println!("Invalid tubes or rounds: {} {}", parts[3], parts[4]);
+            return;
+        }
+    };
+    let round_interval_s = match parts.get(5).map(|i| i.parse::<f64>()) {
+        Some(Ok(i)) => i,
+        Some(Err(_)) => {
+            println!("Invalid interval: {}", parts[5]);
+            return;
+        }
+        None => DEFAULT_ROUND_INTERVAL_S,
+    };
+    let ring: Option<Ring> = parts.get(6).and_then(|r| parse_ring(r));
+
+    let mortars = state.mortars.read().await;
+    let targets = state.targets.read().await;
+    let ballistics = state.ballistics.read().await;
+    let m = match mortars.iter().find(|m| m.name == parts[1]) {
+        Some(m) => m,
+        None => {
+            println!("Mortar '{}' not found", parts[1]);
+            return;
+        }
+    };
+    let t = match targets.iter().find(|t| t.name == parts[2]) {
+        Some(t) => t,
+        None => {
+            println!("Target '{}' not found", parts[2]);
+            return;
+        }
+    };
+    let solution = match state
+        .solve(m, t, &ballistics, &SolveOptions::default())
+        .await
+    {
+        Ok(solution) => solution,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+
+    match volley_plan(
+        &solution,
+        m,
+        t,
+        &ballistics,
+        &*state.increments.read().await,
+        ring,
+        tubes,
+        rounds_per_tube,
+        round_interval_s,
+        *state.declination.read().await,
+    ) {
+        Ok(plan) => {
+            println!();
+            print!("{}", render_volley_text(&plan));
+            println!();
+        }
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+async fn trp_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        [name, "-"] => {
+            if state
+                .reference_points
+                .write()
+                .await
+                .0
+                .remove(*name)
+                .is_none()
+            {
+                println!("Unknown reference point: {}", name);
+                return;
+            }
+            state.events.emit(StateEvent::ReferencePointRemoved {
+                name: name.to_string(),
+            });
+        }
+        [name, e, x, y, description @ ..] => {
+            let point = match (e.parse::<f64>(), x.parse::<f64>(), y.parse::<f64>()) {
+                (Ok(elevation), Ok(x), Ok(y)) => ReferencePoint {
+                    x,
+                    y,
+                    elevation,
+                    description: (!description.is_empty()).then(|| description.join(" ")),
+                },
+                _ => {
+                    println!("Error: Invalid coordinates");
+                    return;
+                }
+            };
+            if !point.is_valid() {
+                println!("Error: Invalid coordinates");
+                return;
+            }
+            let name = name.to_string();
+            let previous = state
+                .reference_points
+                .write()
+                .await
+                .0
+                .insert(name.clone(), point.clone());
+            state.events.emit(match previous {
+                Some(_) => StateEvent::ReferencePointUpdated { name, point },
+                None => StateEvent::ReferencePointAdded { name, point },
+            });
+        }
+        _ => {
+            println!("Usage: trp [<name> <elevation> <x> <y> [description] | <name> -]");
+            println!(
+                "  Points de reference (TRP) : hors plan de feux, portes sur les fiches de tir"
+            );
+            return;
+        }
+    }
+    let points = state.reference_points.read().await;
+    if points.is_empty() {
+        println!("Aucun point de reference");
+    }
+    for (name, p) in &points.0 {
+        println!(
+            "  {:<16} X={:.0} Y={:.0} E={:.0}m {}",
+            name,
+            p.x,
+            p.y,
+            p.elevation,
+            p.description.as_deref().unwrap_or("")
+        );
+    }
+}
+
+async fn shift_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 5 {
+        println!("Usage: shift <trp> <direction> <right_m> <add_m> [up_m] [name]");
+        println!("  Cible designee depuis un point de reference (voir 'trp')");
+        println!("  direction: observateur-but, degres ou avec unite (45deg, 800mil)");
+        println!("  right_m: + droite / - gauche ; add_m: + allonger / - raccourcir");
+        return;
+    }
+    let direction_deg = match parts[2].parse::<Angle>() {
+        Ok(a) => a.degrees(),
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let distance = |i: usize| parts.get(i).map_or(Ok(0.0), |v| v.parse::<f64>());
+    let shift = match (distance(3), distance(4), distance(5)) {
+        (Ok(right_m), Ok(add_m), Ok(up_m)) => KnownPointShift {
+            direction_deg,
+            right_m,
+            add_m,
+            up_m,
+        },
+        _ => {
+            println!("Error: Invalid shift");
+            return;
+        }
+    };
+    if !shift.is_valid() {
+        println!("Error: Invalid shift");
+        return;
+    }
+    let (x, y, elevation) = match state.reference_points.read().await.0.get(parts[1]) {
+        Some(point) => shift.apply(point),
+        None => {
+            println!("Unknown reference point: {}", parts[1]);
+            return;
+        }
+    };
+
+    let mut targets = state.targets.write().await;
+    let name = state
+        .target_name(parts.get(6).and_then(|n| parse_name_arg(n)), &targets)
+        .await;
+    if targets.iter().any(|t| t.name == name) {
+        println!("Error: Target '{}' already exists", name);
         return;
     }
+    let ammo = state.default_ammo(None, TargetType::Infanterie).await;
+    let target =
+        crate::TargetPosition::new(name.clone(), elevation, x, y, TargetType::Infanterie, ammo);
+    targets.push(target.clone());
+    state.events.emit(StateEvent::TargetAdded { target });
+
+    println!(
+        "Target '{}' added at X={:.0} Y={:.0} E={:.0}m (depuis {})",
+        name, x, y, elevation, parts[1]
+    );
+}
+
+async fn intersect_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 8 {
+        println!("Usage: intersect <name> <x1> <y1> <az1> <x2> <y2> <az2> [elevation]");
+        println!("  Localise une cible a partir des visees de deux observateurs");
+        println!("  name: '-' pour le prochain numero de cible (voir 'numbering')");
+        println!("  az: degres par defaut, ou avec unite (45deg, 800mil)");
+        return;
+    }
+
+    let num = |i: usize| parts[i].parse::<f64>().unwrap_or(0.0);
+    let (az1, az2) = match (parts[4].parse::<Angle>(), parts[7].parse::<Angle>()) {
+        (Ok(a), Ok(b)) => (a.degrees(), b.degrees()),
+        (Err(e), _) | (_, Err(e)) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let a = ObserverBearing {
+        x: num(2),
+        y: num(3),
+        azimuth_deg: az1,
+    };
+    let b = ObserverBearing {
+        x: num(5),
+        y: num(6),
+        azimuth_deg: az2,
+    };
+    let elevation: Option<f64> = parts.get(8).and_then(|e| e.parse().ok());
+
+    let i = match intersect_bearings(&a, &b) {
+        Some(i) => i,
+        None => {
+            println!("Error: bearings do not intersect in front of both observers");
+            return;
+        }
+    };
+
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+    let name = state.target_name(parse_name_arg(parts[1]), &targets).await;
+    if targets.iter().any(|t| t.name == name) {
+        println!("Error: Target '{}' already exists", name);
+        return;
+    }
+    let (elevation, source) = state
+        .fill_elevation(elevation, i.x, i.y, &mortars, &targets)
+        .await;
+    let ammo = state.default_ammo(None, TargetType::Infanterie).await;
+    let mut target = crate::TargetPosition::new(
+        name.clone(),
+        elevation,
+        i.x,
+        i.y,
+        TargetType::Infanterie,
+        ammo,
+    );
+    target.elevation_source = source;
+    targets.push(target.clone());
+    state.events.emit(StateEvent::TargetAdded { target });
+
+    println!(
+        "Target '{}' added at X={:.0} Y={:.0} (angle {:.0} deg, erreur estimee {:.0} m){}",
+        name,
+        i.x,
+        i.y,
+        i.intersection_angle_deg,
+        i.error_estimate_m,
+        elevation_note(elevation, source)
+    );
+}
+
+async fn backplot_cli(parts: &[&str], state: &Arc<AppState>) {
+    let triplets = &parts[2.min(parts.len())..];
+    if parts.len() < 2 || triplets.len() < 6 || !triplets.len().is_multiple_of(3) {
+        println!("Usage: backplot <name> <x> <y> <az> <x> <y> <az> [<x> <y> <az> ...]");
+        println!("  Estime la position d'un mortier ennemi a partir de 2+ releves");
+        println!("  name: '-' pour le prochain numero de cible (voir 'numbering')");
+        println!("  az: degres par defaut, ou avec unite (45deg, 800mil)");
+        return;
+    }
+
+    let mut bearings = Vec::new();
+    for c in triplets.chunks(3) {
+        let azimuth_deg = match c[2].parse::<Angle>() {
+            Ok(az) => az.degrees(),
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        };
+        bearings.push(ObserverBearing {
+            x: c[0].parse().unwrap_or(0.0),
+            y: c[1].parse().unwrap_or(0.0),
+            azimuth_deg,
+        });
+    }
+
+    let est = match backplot(&bearings, &[]) {
+        Some(e) => e,
+        None => {
+            println!("Error: not enough observations to fix the hostile position");
+            return;
+        }
+    };
+
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+    let name = state.target_name(parse_name_arg(parts[1]), &targets).await;
+    if targets.iter().any(|t| t.name == name) {
+        println!("Error: Target '{}' already exists", name);
+        return;
+    }
+    let (elevation, source) = state
+        .fill_elevation(None, est.x, est.y, &mortars, &targets)
+        .await;
+    let ammo = state.default_ammo(None, TargetType::Infanterie).await;
+    let mut target = crate::TargetPosition::new(
+        name.clone(),
+        elevation,
+        est.x,
+        est.y,
+        TargetType::Infanterie,
+        ammo,
+    );
+    target.elevation_source = source;
+    targets.push(target.clone());
+    state.events.emit(StateEvent::TargetAdded { target });
+
+    println!(
+        "Target '{}' added at X={:.0} Y={:.0} ({} releves, residu {:.0} m){}",
+        name,
+        est.x,
+        est.y,
+        est.observations,
+        est.residual_m,
+        elevation_note(elevation, source)
+    );
+}
+
+async fn crater_cli(parts: &[&str], state: &Arc<AppState>) {
+    let fields = &parts[2.min(parts.len())..];
+    if parts.len() < 2 || fields.is_empty() || !fields.len().is_multiple_of(5) {
+        println!("Usage: crater <name> <x> <y> <back_az> <min_m> <max_m> [<x> <y> <back_az> <min_m> <max_m> ...]");
+        println!("  Zone de depart ennemie a partir de l'azimut inverse et de la bande de portee de chaque cratere");
+        println!("  name: '-' pour le prochain numero de cible (voir 'numbering')");
+        println!("  back_az: degres par defaut, ou avec unite (45deg, 800mil)");
+        return;
+    }
+
+    let mut craters = Vec::new();
+    for c in fields.chunks(5) {
+        let back_azimuth_deg = match c[2].parse::<Angle>() {
+            Ok(az) => az.degrees(),
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        };
+        let crater = CraterAnalysis {
+            x: c[0].parse().unwrap_or(0.0),
+            y: c[1].parse().unwrap_or(0.0),
+            back_azimuth_deg,
+            range_m: None,
+            min_range_m: c[3].parse().ok(),
+            max_range_m: c[4].parse().ok(),
+        };
+        if !crater.is_valid() {
+            println!("Invalid range band: {} - {}", c[3], c[4]);
+            return;
+        }
+        craters.push(crater);
+    }
+
+    let area = match origin_area(&craters) {
+        Some(a) => a,
+        None => {
+            println!("Error: not enough crater analyses to fix the hostile position");
+            return;
+        }
+    };
+
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+    let name = state.target_name(parse_name_arg(parts[1]), &targets).await;
+    if targets.iter().any(|t| t.name == name) {
+        println!("Error: Target '{}' already exists", name);
+        return;
+    }
+    let (elevation, source) = state
+        .fill_elevation(None, area.x, area.y, &mortars, &targets)
+        .await;
+    let ammo = state.default_ammo(None, TargetType::Infanterie).await;
+    let mut target = crate::TargetPosition::new(
+        name.clone(),
+        elevation,
+        area.x,
+        area.y,
+        TargetType::Infanterie,
+        ammo,
+    );
+    target.elevation_source = source;
+    target.uncertainty_m = Some(area.radius_m);
+    targets.push(target.clone());
+    state.events.emit(StateEvent::TargetAdded { target });
+
+    println!(
+        "Target '{}' added at X={:.0} Y={:.0}, zone de {:.0} m ({} crateres){}",
+        name,
+        area.x,
+        area.y,
+        area.radius_m,
+        area.observations,
+        elevation_note(elevation, source)
+    );
+}
+
+async fn save_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: save <file>");
+        return;
+    }
+
+    let snapshot = state.snapshot().await;
+    match write_snapshot(parts[1], &snapshot) {
+        Ok(()) => println!(
+            "Saved {} mortars and {} targets to {}",
+            snapshot.mortars.len(),
+            snapshot.targets.len(),
+            parts[1]
+        ),
+        Err(e) => println!("Error: {:#}", e),
+    }
+}
+
+/// Operation name designating the running state in `transfer`.
+const LIVE_OPERATION: &str = "live";
+
+/// Read an operation file, or an empty operation if it does not exist yet.
+fn read_operation(path: &str) -> anyhow::Result<StateSnapshot> {
+    if std::path::Path::new(path).exists() {
+        read_snapshot(path)
+    } else {
+        Ok(StateSnapshot::default())
+    }
+}
+
+async fn transfer_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 4 {
+        println!("Usage: transfer <copy|move> <from> <to> [target...] [--overwrite]");
+        println!(
+            "  from/to: fichier d'operation (.json) ou '{}' (etat courant)",
+            LIVE_OPERATION
+        );
+        println!("  Exemple: transfer copy entrainement.json live T1 T2");
+        return;
+    }
+
+    let mode = match TransferMode::parse_str(parts[1]) {
+        Some(m) => m,
+        None => {
+            println!("Invalid mode: {} (expected copy or move)", parts[1]);
+            return;
+        }
+    };
+    let (from, to) = (parts[2], parts[3]);
+    if from == to {
+        println!("Source and destination are the same operation");
+        return;
+    }
+    let strategy = if parts[4..].contains(&"--overwrite") {
+        MergeStrategy::MergeOverwrite
+    } else {
+        MergeStrategy::MergeKeepExisting
+    };
+    let names: Vec<String> = parts[4..]
+        .iter()
+        .filter(|p| !p.starts_with('-'))
+        .map(|p| p.to_string())
+        .collect();
+
+    let result = if from == LIVE_OPERATION || to == LIVE_OPERATION {
+        let (file, direction) = if to == LIVE_OPERATION {
+            (from, TransferDirection::Import)
+        } else {
+            (to, TransferDirection::Export)
+        };
+        match read_operation(file) {
+            Ok(mut operation) => {
+                let report = state
+                    .transfer_targets(&mut operation, direction, &names, mode, strategy)
+                    .await;
+                operation.created_at = unix_now();
+                if direction == TransferDirection::Export || mode == TransferMode::Move {
+                    write_snapshot(file, &operation).map(|()| report)
+                } else {
+                    Ok(report)
+                }
+            }
+            Err(e) => Err(e),
+        }
+    } else {
+        match (read_snapshot(from), read_operation(to)) {
+            (Ok(mut source), Ok(mut destination)) => {
+                let report = transfer_targets(
+                    &mut source.targets,
+                    &mut destination.targets,
+                    &names,
+                    mode,
+                    strategy,
+                );
+                let now = unix_now();
+                destination.created_at = now;
+                source.created_at = now;
+                write_snapshot(to, &destination)
+                    .and_then(|()| match mode {
+                        TransferMode::Move => write_snapshot(from, &source),
+                        TransferMode::Copy => Ok(()),
+                    })
+                    .map(|()| report)
+            }
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        }
+    };
+
+    let report = match result {
+        Ok(r) => r,
+        Err(e) => {
+            println!("Error: {:#}", e);
+            return;
+        }
+    };
+
+    println!();
+    println!("--- TRANSFERT {} {} -> {} ---", mode.as_str(), from, to);
+    println!(
+        "  Cibles: +{} ajoutees, {} ecrasees, {} conservees",
+        report.targets.added.len(),
+        report.targets.overwritten.len(),
+        report.targets.kept.len()
+    );
+    if !report.targets.kept.is_empty() {
+        println!(
+            "  Conservees (deja presentes): {}",
+            report.targets.kept.join(", ")
+        );
+    }
+    if !report.removed_from_source.is_empty() {
+        println!(
+            "  Retirees de {}: {}",
+            from,
+            report.removed_from_source.join(", ")
+        );
+    }
+    if !report.missing.is_empty() {
+        println!("  Introuvables: {}", report.missing.join(", "));
+    }
+    println!();
+}
+
+async fn export_tables_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 || parts.len() > 3 {
+        println!("Usage: export_tables <dir> [step_m]");
+        println!("  Exemple: export_tables archive/2024-06-01      (lignes des tables)");
+        println!("           export_tables archive/dense 1         (courbe interpolee au metre)");
+        return;
+    }
+    let step_m = match parts.get(2) {
+        Some(s) => match s.parse::<f64>() {
+            Ok(v) if v >= DENSE_STEP_M && v.is_finite() => Some(v),
+            _ => {
+                println!("Invalid step: {} (at least {} m)", s, DENSE_STEP_M);
+                return;
+            }
+        },
+        None => None,
+    };
+    let dir = std::path::Path::new(parts[1]);
+    match export_tables(
+        &*state.ballistics.read().await,
+        &*state.ammo.read().await,
+        dir,
+        step_m,
+    ) {
+        Ok(written) => println!("{} table(s) written to {}", written.len(), dir.display()),
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+async fn reload_cli(state: &Arc<AppState>) {
+    match state.reload().await {
+        Ok(report) => {
+            println!(
+                "Reloaded {} ballistic table(s), {} dispersion(s)",
+                report.tables, report.dispersions
+            );
+            if !report.inconsistent_tables.is_empty() {
+                println!(
+                    "Warning: inconsistent table(s){}: {}",
+                    if report.repaired { ", repaired" } else { "" },
+                    report.inconsistent_tables.join(", ")
+                );
+            }
+        }
+        Err(e) => println!("Error: {} (current tables kept)", e),
+    }
+}
+
+fn dry_run_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1).copied() {
+        None => {}
+        Some("on") => state.dry_run.store(true, Ordering::Relaxed),
+        Some("off") => state.dry_run.store(false, Ordering::Relaxed),
+        Some(other) => {
+            println!("Usage: dry_run [on|off]");
+            println!("Invalid value: {}", other);
+            return;
+        }
+    }
+    if state.is_dry_run() {
+        println!("Dry run: on (mutating commands only report their changes)");
+    } else {
+        println!("Dry run: off");
+    }
+}
+
+async fn convention_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() > 1 {
+        match SignConvention::parse_str(&parts[1..].join(" ")) {
+            Some(c) => *state.sign_convention.write().await = c,
+            None => {
+                println!("Invalid convention: {}", parts[1..].join(" "));
+                println!("Usage: convention [N+|S+ E+|W+]   (ex: convention N+ E+)");
+                return;
+            }
+        }
+    }
+    let c = *state.sign_convention.read().await;
+    println!("Convention: {} ({})", c.as_string(), c.label());
+}
+
+async fn ammo_policy_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() > 1 {
+        let target_type = match TargetType::parse_str(parts[1]) {
+            Some(t) => t,
+            None => {
+                println!("Invalid target type: {}", parts[1]);
+                println!("Usage: ammo_policy [<type> <ammo|->]   (ex: ammo_policy VEH SMOKE)");
+                return;
+            }
+        };
+        let mut policy = state.ammo_policy.write().await;
+        match parts.get(2).copied() {
+            Some("-") | None => {
+                policy.0.remove(&target_type);
+            }
+            Some(a) => match state.ammo.read().await.parse_kind(a) {
+                Some(ammo) => {
+                    policy.0.insert(target_type, ammo);
+                }
+                None => {
+                    println!("Invalid ammo type: {}", a);
+                    return;
+                }
+            },
+        }
+    }
+    let policy = state.ammo_policy.read().await;
+    for t in TargetType::all() {
+        let origin = if policy.get(*t).is_some() {
+            "operation"
+        } else {
+            "defaut"
+        };
+        println!(
+            "  {:>10} -> {} ({})",
+            t.as_str(),
+            policy.recommended(*t),
+            origin
+        );
+    }
+}
+
+async fn preset_cli(parts: &[&str], state: &Arc<AppState>) {
+    let convention = *state.sign_convention.read().await;
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        [name, "-"] => {
+            if state
+                .correction_presets
+                .write()
+                .await
+                .0
+                .remove(*name)
+                .is_none()
+            {
+                println!("Unknown correction preset: {}", name);
+                return;
+            }
+        }
+        [name, v, h] => match (v.parse::<f64>(), h.parse::<f64>()) {
+            (Ok(v), Ok(h)) if v.is_finite() && h.is_finite() => {
+                let (vertical_m, horizontal_m) = convention.to_standard(v, h);
+                state.correction_presets.write().await.0.insert(
+                    name.to_string(),
+                    CorrectionPreset {
+                        vertical_m,
+                        horizontal_m,
+                    },
+                );
+            }
+            _ => {
+                println!("Error: Invalid deviation");
+                return;
+            }
+        },
+        _ => {
+            println!("Usage: preset [<name> <V> <H> | <name> -]");
+            println!("  Exemple: preset carte_NO40 -28 -28  (puis: correct T1 carte_NO40)");
+            return;
+        }
+    }
+    let presets = state.correction_presets.read().await;
+    if presets.is_empty() {
+        println!("Aucune correction enregistree");
+    }
+    for (name, p) in &presets.0 {
+        let (v, h) = convention.to_standard(p.vertical_m, p.horizontal_m);
+        println!(
+            "  {:<16} V={:+.0}m H={:+.0}m ({})",
+            name,
+            v,
+            h,
+            convention.label()
+        );
+    }
+}
+
+async fn airspace_cli(parts: &[&str], state: &Arc<AppState>) {
+    let values: Vec<f64> = parts
+        .iter()
+        .skip(2)
+        .filter_map(|p| p.parse().ok())
+        .collect();
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        [name, "-"] => {
+            if state.airspace.write().await.0.remove(*name).is_none() {
+                println!("Unknown airspace restriction: {}", name);
+                return;
+            }
+        }
+        [name, rest @ ..]
+            if (rest.len() == 1 || rest.len() == 6)
+                && values.len() == rest.len()
+                && values.iter().all(|v| v.is_finite()) =>
+        {
+            let corridor = (values.len() == 6).then(|| Corridor {
+                from: [values[1], values[2]],
+                to: [values[3], values[4]],
+                width_m: values[5],
+            });
+            if corridor.is_some_and(|c| c.width_m <= 0.0) {
+                println!("Error: Corridor width must be positive");
+                return;
+            }
+            state.airspace.write().await.0.insert(
+                name.to_string(),
+                AirspaceRestriction {
+                    ceiling_m: values[0],
+                    corridor,
+                },
+            );
+        }
+        _ => {
+            println!("Usage: airspace [<name> <ceiling_m> [x1 y1 x2 y2 width_m] | <name> -]");
+            println!(
+                "  Exemple: airspace HELICO 400 0 1000 2000 1000 300  (couloir, plafond 400 m)"
+            );
+            return;
+        }
+    }
+    let airspace = state.airspace.read().await;
+    if airspace.is_empty() {
+        println!("Aucun plafond d'espace aerien");
+    }
+    for (name, r) in &airspace.0 {
+        match &r.corridor {
+            Some(c) => println!(
+                "  {:<12} plafond {:.0} m, couloir ({:.0},{:.0}) -> ({:.0},{:.0}) largeur {:.0} m",
+                name, r.ceiling_m, c.from[0], c.from[1], c.to[0], c.to[1], c.width_m
+            ),
+            None => println!("  {:<12} plafond {:.0} m, toute la zone", name, r.ceiling_m),
+        }
+    }
+}
+
+async fn training_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        ["off"] => *state.training_offset.write().await = None,
+        [north, east] => match (north.parse::<f64>(), east.parse::<f64>()) {
+            (Ok(north_m), Ok(east_m)) if north_m.is_finite() && east_m.is_finite() => {
+                *state.training_offset.write().await = Some(TrainingOffset { north_m, east_m })
+            }
+            _ => {
+                println!("Invalid offset: {} {}", north, east);
+                return;
+            }
+        },
+        _ => {
+            println!("Usage: training [<north_m> <east_m> | off]");
+            println!("  Decale tous les impacts simules (spot sans deviation) d'une erreur fixe");
+            return;
+        }
+    }
+    match *state.training_offset.read().await {
+        Some(o) => println!(
+            "Entrainement: impacts simules decales de {:.0} m Nord, {:.0} m Est",
+            o.north_m, o.east_m
+        ),
+        None => println!("Entrainement: desactive"),
+    }
+}
+
+async fn seed_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        ["off"] => *state.session_seed.write().await = None,
+        [seed] => match seed.parse::<u64>() {
+            Ok(seed) => *state.session_seed.write().await = Some(SessionSeed::new(seed)),
+            Err(_) => {
+                println!("Invalid seed: {}", seed);
+                return;
+            }
+        },
+        _ => {
+            println!("Usage: seed [<n> | off]");
+            println!("  Rend les impacts simules reproductibles ; <n> relance la serie");
+            return;
+        }
+    }
+    match state.session_seed().await {
+        Some(seed) => println!(
+            "Graine de session: {} (impacts simules reproductibles)",
+            seed
+        ),
+        None => println!("Graine de session: aucune (horloge)"),
+    }
+}
+
+async fn numbering_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() > 1 {
+        let mut numbering = state.target_numbering.write().await;
+        let next = match parts.get(2).map(|n| n.parse::<u32>()) {
+            Some(Ok(n)) => n,
+            Some(Err(_)) => {
+                println!("Invalid number: {}", parts[2]);
+                return;
+            }
+            None => numbering.next,
+        };
+        match TargetNumbering::new(parts[1], next) {
+            Some(n) => *numbering = n,
+            None => {
+                println!("Usage: numbering [<prefix> [next]]   (ex: numbering AB 1001)");
+                println!("  prefix: deux lettres, next: 1 a {}", TARGET_NUMBER_MAX);
+                return;
+            }
+        }
+    }
+    let numbering = state.target_numbering.read().await;
+    println!(
+        "Numerotation des cibles: prefixe {}, prochaine cible sans nom: {}",
+        numbering.prefix,
+        numbering.format(numbering.next)
+    );
+}
+
+async fn declination_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() > 1 {
+        let valid = |deg: &f64| deg.is_finite() && deg.abs() <= 180.0;
+        let magnetic = parts[1].parse::<f64>().ok().filter(valid);
+        let convergence = match parts.get(2) {
+            Some(c) => c.parse::<f64>().ok().filter(valid),
+            None => Some(0.0),
+        };
+        match (magnetic, convergence) {
+            (Some(magnetic_deg), Some(grid_convergence_deg)) => {
+                *state.declination.write().await = Declination {
+                    magnetic_deg,
+                    grid_convergence_deg,
+                }
+            }
+            _ => {
+                println!("Usage: declination [<declinaison_deg> [convergence_deg]]");
+                println!("  Est positif, ex: declination 2.5 -0.3");
+                return;
+            }
+        }
+    }
+    println!("{}", state.declination.read().await.describe());
+}
+
+async fn elev_thresholds_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() > 1 {
+        let thresholds = match (
+            parts[1].parse::<f64>(),
+            parts.get(2).map(|c| c.parse::<f64>()),
+        ) {
+            (Ok(caution_m), Some(Ok(critical_m))) => Some(ElevationThresholds {
+                caution_m,
+                critical_m,
+            }),
+            _ => None,
+        };
+        match thresholds.filter(ElevationThresholds::is_valid) {
+            Some(thresholds) => *state.elevation_thresholds.write().await = thresholds,
+            None => {
+                println!("Usage: elev_thresholds [<caution_m> <critical_m>]");
+                println!("  Seuils positifs, attention <= critique, ex: elev_thresholds 100 300");
+                return;
+            }
+        }
+    }
+    println!("{}", state.elevation_thresholds.read().await.describe());
+}
+
+async fn danger_close_cli(parts: &[&str], state: &Arc<AppState>) {
+    if let Some(value) = parts.get(1) {
+        match value.parse::<f64>() {
+            Ok(m) if DANGER_CLOSE_MULTIPLE_RANGE.contains(&m) => {
+                *state.danger_close_multiple.write().await = m
+            }
+            _ => {
+                println!("Usage: danger_close [multiple]");
+                println!(
+                    "  Multiple de la dispersion ajustee, entre {} et {}, ex: danger_close 3",
+                    DANGER_CLOSE_MULTIPLE_RANGE.start(),
+                    DANGER_CLOSE_MULTIPLE_RANGE.end()
+                );
+                return;
+            }
+        }
+    }
+    println!(
+        "Danger close: friendlies within {} x the adjusted dispersion ({} friendly position(s))",
+        *state.danger_close_multiple.read().await,
+        state.friendlies.read().await.len()
+    );
+}
+
+async fn wind_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        ["off"] => *state.wind.write().await = None,
+        [direction, speed, layers @ ..] => {
+            let surface = parse_angle_deg(direction).zip(speed.parse::<f64>().ok());
+            let layers: Option<Vec<WindLayer>> = layers
+                .iter()
+                .map(|layer| match layer.split(':').collect::<Vec<_>>()[..] {
+                    [height, direction, speed] => Some(WindLayer {
+                        height_m: height.parse().ok()?,
+                        direction_deg: parse_angle_deg(direction)?,
+                        speed_mps: speed.parse().ok()?,
+                    }),
+                    _ => None,
+                })
+                .collect();
+            let mut wind = match (surface, layers) {
+                (Some((direction_deg, speed_mps)), Some(layers)) => WindProfile {
+                    surface: Wind {
+                        direction_deg,
+                        speed_mps,
+                    },
+                    layers,
+                },
+                _ => {
+                    println!(
+                        "Usage: set_wind <dir> <speed_mps> [<height_m>:<dir>:<speed_mps> ...]"
+                    );
+                    return;
+                }
+            };
+            if !wind.is_valid() {
+                println!(
+                    "Invalid wind: speeds {}-{} m/s, layer heights non-negative",
+                    WIND_SPEED_RANGE_MPS.start(),
+                    WIND_SPEED_RANGE_MPS.end()
+                );
+                return;
+            }
+            wind.layers
+                .sort_by(|a, b| a.height_m.total_cmp(&b.height_m));
+            *state.wind.write().await = Some(wind);
+        }
+        _ => {
+            println!(
+                "Usage: set_wind [<dir> <speed_mps> [<height_m>:<dir>:<speed_mps> ...] | off]"
+            );
+            println!("  Vent applique aux solutions, ex: set_wind 270 5 300:290:9 800:300:14");
+            return;
+        }
+    }
+    match &*state.wind.read().await {
+        Some(wind) => {
+            println!(
+                "Vent: du {:.0} deg, {:.1} m/s au sol",
+                wind.surface.direction_deg, wind.surface.speed_mps
+            );
+            for layer in &wind.layers {
+                println!(
+                    "  a {:.0} m: du {:.0} deg, {:.1} m/s",
+                    layer.height_m, layer.direction_deg, layer.speed_mps
+                );
+            }
+        }
+        None => println!("Vent: aucun (solutions non corrigees)"),
+    }
+}
+
+async fn terrain_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        ["off"] => *state.terrain.write().await = None,
+        [path, georef] => {
+            let georef = match TerrainGeoref::parse_str(georef) {
+                Some(georef) => georef,
+                None => {
+                    println!(
+                        "Invalid georeference: {} (expected x,y,cell[,scale[,base]])",
+                        georef
+                    );
+                    return;
+                }
+            };
+            let loaded = std::fs::File::open(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|f| TerrainModel::from_png(std::io::BufReader::new(f), georef));
+            match loaded {
+                Ok(terrain) => *state.terrain.write().await = Some(terrain),
+                Err(e) => {
+                    println!("Error loading heightmap {}: {:#}", path, e);
+                    return;
+                }
+            }
+        }
+        _ => {
+            println!("Usage: terrain [<png> <x,y,cell[,scale[,base]]> | off]");
+            println!(
+                "  Carte d'altitude en niveaux de gris, ex: terrain relief.png 1000,5000,10,0.5"
+            );
+            println!("  (pixel nord-ouest en 1000,5000, pixels de 10 m, 0.5 m par niveau de gris)");
+            return;
+        }
+    }
+    match state.terrain.read().await.as_ref().map(TerrainModel::summary) {
+        Some(t) => println!(
+            "Carte d'altitude: {}x{} pixels de {:.1} m, X {:.0}-{:.0} Y {:.0}-{:.0}, altitudes {:.0}-{:.0} m",
+            t.width,
+            t.height,
+            t.georef.cell_size_m,
+            t.bounds[0],
+            t.bounds[2],
+            t.bounds[1],
+            t.bounds[3],
+            t.min_elevation_m,
+            t.max_elevation_m
+        ),
+        None => println!("Carte d'altitude: aucune (altitude de repli)"),
+    }
+}
+
+async fn met_cli(parts: &[&str], state: &Arc<AppState>) {
+    let text = match parts.get(1) {
+        Some(first) if first.to_uppercase().starts_with("MET") => parts[1..].join(" "),
+        Some(path) if parts.len() == 2 => match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                println!("Error reading {}: {}", path, e);
+                return;
+            }
+        },
+        _ => {
+            println!("Usage: met <file> | met <METCM/METB3 message>");
+            println!("  ex: met METCM1 345 067 12301 0 004 013 00 480 010 2931 1013 ...");
+            return;
+        }
+    };
+    let message = match parse_met(&text) {
+        Ok(message) => message,
+        Err(e) => {
+            println!("Invalid MET message: {}", e);
+            return;
+        }
+    };
+    let (wind, atmosphere) = (message.wind(), message.atmosphere());
+    if !wind.is_valid() || !atmosphere.is_valid() {
+        println!("MET message has wind or air conditions out of range");
+        return;
+    }
+    println!(
+        "{:?}: {} lignes, station {:.0} m",
+        message.kind,
+        message.zones.len(),
+        message.station_height_m
+    );
+    *state.wind.write().await = Some(wind);
+    *state.atmosphere.write().await = Some(atmosphere);
+    wind_cli(&["wind"], state).await;
+    atmosphere_cli(&["atmosphere"], state).await;
+}
+
+async fn atmosphere_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        ["off"] => *state.atmosphere.write().await = None,
+        [air, pressure @ ..] if pressure.len() <= 1 => {
+            let atmosphere = match (air.parse::<f64>(), pressure.first().map(|p| p.parse())) {
+                (Ok(air_temperature_c), None) => Atmosphere {
+                    air_temperature_c,
+                    pressure_hpa: None,
+                },
+                (Ok(air_temperature_c), Some(Ok(pressure_hpa))) => Atmosphere {
+                    air_temperature_c,
+                    pressure_hpa: Some(pressure_hpa),
+                },
+                _ => {
+                    println!("Usage: atmosphere <air_temp_c> [<pressure_hpa>]");
+                    return;
+                }
+            };
+            if !atmosphere.is_valid() {
+                println!(
+                    "Invalid air conditions: {}..{} C, {}..{} hPa",
+                    AIR_TEMPERATURE_RANGE_C.start(),
+                    AIR_TEMPERATURE_RANGE_C.end(),
+                    PRESSURE_RANGE_HPA.start(),
+                    PRESSURE_RANGE_HPA.end()
+                );
+                return;
+            }
+            *state.atmosphere.write().await = Some(atmosphere);
+        }
+        _ => {
+            println!("Usage: atmosphere [<air_temp_c> [<pressure_hpa>] | off]");
+            println!("  Conditions de l'air au niveau des pieces, ex: atmosphere 30 950");
+            return;
+        }
+    }
+    match *state.atmosphere.read().await {
+        Some(atmosphere) => match atmosphere.pressure_hpa {
+            Some(pressure_hpa) => println!(
+                "Air: {:.0} C, {:.0} hPa (altitude-densite {:.0} m)",
+                atmosphere.air_temperature_c,
+                pressure_hpa,
+                density_altitude_m(atmosphere.density_ratio(0.0))
+            ),
+            None => println!(
+                "Air: {:.0} C, pression standard a l'altitude de chaque piece",
+                atmosphere.air_temperature_c
+            ),
+        },
+        None => println!("Air: atmosphere standard des tables"),
+    }
+}
+
+async fn charge_temp_cli(parts: &[&str], state: &Arc<AppState>) {
+    match parts.get(1..).unwrap_or_default() {
+        [] => {}
+        ["off"] => *state.charge_temperature_c.write().await = None,
+        [t] => match t.parse::<f64>() {
+            Ok(t) if CHARGE_TEMPERATURE_RANGE_C.contains(&t) => {
+                *state.charge_temperature_c.write().await = Some(t)
+            }
+            _ => {
+                println!("Invalid charge temperature: {}", t);
+                return;
+            }
+        },
+        _ => {
+            println!("Usage: charge_temp [<deg_c> | off]");
+            println!("  Temperature des charges appliquee aux solutions, ex: charge_temp 35");
+            return;
+        }
+    }
+    let standard_c = state.charge_temperature.read().await.standard_c;
+    match *state.charge_temperature_c.read().await {
+        Some(t) => println!(
+            "Temperature des charges: {:.0} C ({:+.0} C / reference {:.0} C)",
+            t,
+            t - standard_c,
+            standard_c
+        ),
+        None => println!(
+            "Temperature des charges: reference des tables ({:.0} C)",
+            standard_c
+        ),
+    }
+}
+
+async fn bundle_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 || !is_data_archive(parts[1]) {
+        println!("Usage: bundle <file.zip> [--with-binary]");
+        println!("  Paquet hors ligne : tables en service, metrics.json et etat de l'operation");
+        println!("  --with-binary: joint l'executable du serveur (bin/)");
+        println!("  A charger sur le poste deconnecte avec: server --bundle <file.zip>");
+        return;
+    }
+
+    let metrics = match &state.data_path {
+        Some(path) => {
+            match read_metrics_file(data_layer(path, &state.data_overlays, "metrics.json")) {
+                Ok(metrics) => metrics,
+                Err(e) => {
+                    println!("Error: {:#}", e);
+                    return;
+                }
+            }
+        }
+        None => {
+            println!("Error: no data directory to read metrics.json from");
+            return;
+        }
+    };
+    let binary = if parts[2..].contains(&"--with-binary") {
+        let exe = std::env::current_exe().and_then(|exe| {
+            let name = exe
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            Ok((name, std::fs::read(&exe)?))
+        });
+        match exe {
+            Ok(exe) => Some(exe),
+            Err(e) => {
+                println!("Error: cannot read the server executable: {}", e);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    let snapshot = state.snapshot().await;
+    let bundle = {
+        let ballistics = state.ballistics.read().await;
+        build_bundle(
+            &ballistics,
+            &*state.ammo.read().await,
+            &metrics,
+            &snapshot,
+            binary
+                .as_ref()
+                .map(|(name, data)| (name.as_str(), data.as_slice())),
+        )
+        .map(|bytes| (bytes, ballistics.len()))
+    };
+    let (bytes, tables) = match bundle {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            println!("Error: {:#}", e);
+            return;
+        }
+    };
+    let tmp = format!("{}.tmp", parts[1]);
+    match std::fs::write(&tmp, &bytes).and_then(|()| std::fs::rename(&tmp, parts[1])) {
+        Ok(()) => println!(
+            "Bundle written to {} ({} tables, {} mortars, {} targets{}, {} KiB)",
+            parts[1],
+            tables,
+            snapshot.mortars.len(),
+            snapshot.targets.len(),
+            if binary.is_some() {
+                ", server binary"
+            } else {
+                ""
+            },
+            bytes.len() / 1024
+        ),
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+async fn pack_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: pack <file>");
+        println!("  Exemple: pack mission.html   (a imprimer ou convertir en PDF)");
+        println!("           pack mission.json   (donnees brutes)");
+        return;
+    }
+
+    let pack = {
+        let mortars = state.mortars.read().await;
+        let targets = state.targets.read().await;
+        let ballistics = state.ballistics.read().await;
+        let reference_points = state.reference_points.read().await;
+        let solutions = state
+            .pack_solutions(&mortars, &targets, &reference_points, &ballistics)
+            .await;
+        build_mission_pack(
+            &mortars,
+            &targets,
+            &reference_points,
+            &solutions,
+            &ballistics,
+            *state.declination.read().await,
+            false,
+            unix_now(),
+        )
+    };
+    let content = if parts[1].ends_with(".json") {
+        match serde_json::to_string_pretty(&pack) {
+            Ok(json) => json,
+            Err(e) => {
+                println!("Error: {}", e);
+                return;
+            }
+        }
+    } else {
+        render_html(&pack)
+    };
+    match std::fs::write(parts[1], content) {
+        Ok(()) => println!(
+            "Mission pack written to {} ({} mortars, {} targets)",
+            parts[1],
+            pack.mortars.len(),
+            pack.targets.len()
+        ),
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+async fn replay_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() != 2 && parts.len() != 4 {
+        println!("Usage: replay <file.acmi> [ref_lat ref_lon]");
+        println!("  Exemple: replay exercice.acmi 48.85 2.35");
+        return;
+    }
+    let reference = if parts.len() == 4 {
+        match (parts[2].parse::<f64>(), parts[3].parse::<f64>()) {
+            (Ok(latitude_deg), Ok(longitude_deg)) => GeoReference {
+                latitude_deg,
+                longitude_deg,
+            },
+            _ => {
+                println!("Error: Invalid reference coordinates");
+                return;
+            }
+        }
+    } else {
+        GeoReference::default()
+    };
+
+    let events = shot_events(&*state.solution_history.read().await);
+    if events.is_empty() {
+        println!("Aucun coup tire a exporter");
+        return;
+    }
+    match std::fs::write(parts[1], render_acmi(&events, reference, "Mortar shot log")) {
+        Ok(()) => println!("Shot log written to {} ({} shots)", parts[1], events.len()),
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+async fn import_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() != 2 && parts.len() != 4 {
+        println!("Usage: import <file.json> [ref_lat ref_lon]");
+        println!("  Marqueurs GeoJSON ou liste {{name, x/y ou lat/lon, icon}}");
+        println!("  ref_lat ref_lon: origine des coordonnees locales (marqueurs lat/lon)");
+        return;
+    }
+    let reference = if parts.len() == 4 {
+        match (parts[2].parse::<f64>(), parts[3].parse::<f64>()) {
+            (Ok(latitude_deg), Ok(longitude_deg)) => Some(GeoReference {
+                latitude_deg,
+                longitude_deg,
+            }),
+            _ => {
+                println!("Error: Invalid reference coordinates");
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    let json = match std::fs::read_to_string(parts[1])
+        .map_err(|e| e.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+    {
+        Ok(json) => json,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    let markers = match parse_markers(&json) {
+        Ok(markers) => markers,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    let report = state
+        .import_markers(&markers, reference.as_ref(), TargetType::Infanterie)
+        .await;
+    for m in &report.imported {
+        let guess = if m.icon_matched { "" } else { " (par defaut)" };
+        println!(
+            "  + {:<12} ({:.0}, {:.0}) {}{}",
+            m.name, m.x, m.y, m.target_type, guess
+        );
+    }
+    for m in &report.skipped {
+        println!("  - {}: {}", m.name.as_deref().unwrap_or("?"), m.reason);
+    }
+    println!(
+        "{} target(s) imported, {} skipped",
+        report.imported.len(),
+        report.skipped.len()
+    );
+}
+
+/// Print a data quality summary per (weapon, ammo, ring) table.
+///
+/// Returns `false` if a table has inconsistent rows.
+pub fn print_table_summary(ballistics: &BallisticTables) -> bool {
+    println!();
+    println!("--- TABLES BALISTIQUES ---");
+    let mut consistent = true;
+    for ((weapon, ammo, ring), table) in ballistics {
+        let report = table.validate();
+        let coverage = match (report.min_range_m, report.max_range_m) {
+            (Some(min), Some(max)) => format!("{:.0}-{:.0} m", min, max),
+            _ => "-".to_string(),
+        };
+        let step = report
+            .median_step_m
+            .map(|s| format!("pas {:.0} m", s))
+            .unwrap_or_default();
+        let status = if report.is_clean() {
+            "OK"
+        } else {
+            "A VERIFIER"
+        };
+        println!(
+            "  {:<5} {:<9} {}R  {:>3} lignes  {:<12} {:<10} {}",
+            weapon.as_str(),
+            ammo.as_str(),
+            ring,
+            report.rows,
+            coverage,
+            step,
+            status
+        );
+        for issue in &report.issues {
+            println!("      ! {}", issue);
+        }
+        for gap in &report.gaps {
+            println!(
+                "      ? ecart {:.0} -> {:.0} m ({:.0} m)",
+                gap.from_m,
+                gap.to_m,
+                gap.to_m - gap.from_m
+            );
+        }
+        consistent &= report.issues.is_empty();
+    }
+    if ballistics.is_empty() {
+        println!("  Aucune table chargee");
+    }
+    consistent
+}
+
+async fn expenditure_cli(state: &Arc<AppState>) {
+    let events = shot_events(&*state.solution_history.read().await);
+    let totals = expenditure(&events, &*state.increments.read().await);
+    println!();
+    println!("--- CONSOMMATION ({} coups) ---", events.len());
+    for e in &totals {
+        println!("  {}", e.describe());
+    }
+    let increments: Option<u32> = totals.iter().map(|e| e.increments).sum();
+    if let Some(total) = increments {
+        println!("  Total increments: {}", total);
+    }
+    println!();
+}
+
+async fn share_cli(parts: &[&str], state: &Arc<AppState>) {
+    let now = unix_now();
+    let mut shares = state.shares.write().await;
+    shares.retain(|_, link| !link.is_expired(now));
+
+    if parts.len() < 2 {
+        println!();
+        println!("--- PARTAGES ({}) ---", shares.len());
+        for link in shares.values() {
+            let targets = if link.filter.targets.is_empty() {
+                "toutes".to_string()
+            } else {
+                link.filter.targets.join(" ")
+            };
+            println!("  {} : cibles {}", link.token, targets);
+        }
+        println!();
+        return;
+    }
+
+    let names: Vec<String> = if parts[1..] == ["all"] {
+        Vec::new()
+    } else {
+        parts[1..].iter().map(|s| s.to_string()).collect()
+    };
+    let targets = state.targets.read().await;
+    if let Some(missing) = names
+        .iter()
+        .find(|name| !targets.iter().any(|t| &t.name == *name))
+    {
+        println!("Target '{}' not found", missing);
+        return;
+    }
+
+    let filter = ShareFilter {
+        targets: names,
+        ..ShareFilter::default()
+    };
+    let link = ShareLink::new(None, filter, None, now);
+    println!("Share link: /api/share/{}", link.token);
+    shares.insert(link.token.clone(), link);
+}
+
+async fn unshare_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: unshare <token>");
+        return;
+    }
+
+    if state.shares.write().await.remove(parts[1]).is_some() {
+        println!("Share link revoked");
+    } else {
+        println!("Share link not found");
+    }
+}
+
+async fn restore_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: restore <file> [strategy] [--dry-run]");
+        println!("  strategy: replace-all, merge-keep-existing (defaut), merge-overwrite");
+        return;
+    }
+
+    let dry_run = parts[2..].iter().any(|p| *p == "--dry-run" || *p == "-n");
+    let strategy = match parts[2..].iter().find(|p| !p.starts_with('-')) {
+        Some(s) => match MergeStrategy::parse_str(s) {
+            Some(strategy) => strategy,
+            None => {
+                println!("Invalid strategy: {}", s);
+                return;
+            }
+        },
+        None => MergeStrategy::default(),
+    };
+
+    let snapshot = match read_snapshot(parts[1]) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("Error: {:#}", e);
+            return;
+        }
+    };
+
+    let report = state.restore(&snapshot, strategy, dry_run).await;
+
+    println!();
+    println!(
+        "--- RESTAURATION {} ({}) ---",
+        parts[1],
+        if dry_run {
+            "dry-run"
+        } else {
+            report.strategy.as_str()
+        }
+    );
+    for (label, counts) in [("Mortiers", &report.mortars), ("Cibles", &report.targets)] {
+        println!(
+            "  {}: +{} ajoutes, {} ecrases, {} conserves, {} supprimes",
+            label,
+            counts.added.len(),
+            counts.overwritten.len(),
+            counts.kept.len(),
+            counts.removed.len()
+        );
+    }
+    if !report.conflicts.is_empty() {
+        println!("  Conflits: {}", report.conflicts.join(", "));
+    }
+    if dry_run {
+        println!("  (aucune modification appliquee)");
+    }
+    println!();
+}
+
+async fn history_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: history <target_name>");
+        return;
+    }
+
+    let name = parts[1];
+    let history = state.solution_history.read().await;
+    let records = match history.get(name) {
+        Some(r) if !r.is_empty() => r,
+        _ => {
+            println!("No solutions recorded for '{}'", name);
+            return;
+        }
+    };
+
+    println!();
+    println!("--- HISTORIQUE {} ({}) ---", name, records.len());
+    for r in records {
+        let elev = r
+            .solution
+            .selected_solution
+            .as_ref()
+            .and_then(|s| s.elevations.first());
+        let deviation = match r.deviation {
+            Some(d) => format!(" dev V={:+.0} H={:+.0}", d.vertical_m, d.horizontal_m),
+            None => String::new(),
+        };
+        println!(
+            "  t={} {} {} D={:.0}m Az={:.1} Elev={}{}",
+            r.timestamp,
+            r.mortar,
+            if r.fired { "TIR " } else { "CALC" },
+            r.solution.distance_m,
+            r.solution.azimuth_deg,
+            elev.map(|e| format!("{:.1}", e))
+                .unwrap_or_else(|| "N/A".to_string()),
+            deviation
+        );
+    }
+    println!();
+}
+
+async fn rm_mortar_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: rm_mortar <name>");
+        return;
+    }
+
+    let name = parts[1];
+    let mut mortars = state.mortars.write().await;
+    let before = mortars.len();
+    mortars.retain(|m| m.name != name);
+
+    if mortars.len() < before {
+        state.events.emit(StateEvent::MortarRemoved {
+            name: name.to_string(),
+        });
+        println!("Mortar '{}' deleted", name);
+    } else {
+        println!("Mortar '{}' not found", name);
+    }
+}
+
+async fn rm_friendly_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: rm_friendly <name>");
+        return;
+    }
+
+    let name = parts[1];
+    let mut friendlies = state.friendlies.write().await;
+    let before = friendlies.len();
+    friendlies.retain(|f| f.name != name);
+
+    if friendlies.len() < before {
+        state.events.emit(StateEvent::FriendlyRemoved {
+            name: name.to_string(),
+        });
+        println!("Friendly '{}' deleted", name);
+    } else {
+        println!("Friendly '{}' not found", name);
+    }
+}
+
+async fn rm_target_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: rm_target <name>");
+        return;
+    }
+
+    let name = parts[1];
+    let mut targets = state.targets.write().await;
+    let before = targets.len();
+    targets.retain(|t| t.name != name);
+
+    if targets.len() < before {
+        state.events.emit(StateEvent::TargetRemoved {
+            name: name.to_string(),
+        });
+        println!("Target '{}' deleted", name);
+    } else {
+        println!("Target '{}' not found", name);
+    }
+}
+
+async fn set_ammo_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 3 {
+        println!("Usage: set_ammo <target_name> <ammo_type>");
+        println!("  ammo_type: HE, PRACTICE, SMOKE, FLARE");
+        return;
+    }
+
+    let name = parts[1];
+    let ammo = match state.ammo.read().await.parse_kind(parts[2]) {
+        Some(a) => a,
+        None => {
+            println!("Invalid ammo type: {}", parts[2]);
+            return;
+        }
+    };
+
+    let mut targets = state.targets.write().await;
+    if let Some(t) = targets.iter_mut().find(|t| t.name == name) {
+        t.ammo_type = ammo;
+        state
+            .events
+            .emit(StateEvent::TargetUpdated { target: t.clone() });
+        println!("Target '{}' ammo set to {}", name, ammo);
+    } else {
+        println!("Target '{}' not found", name);
+    }
+}
+
+async fn set_rings_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: set_rings <mortar_name> [ring...]");
+        println!("  Exemple: set_rings M1 3 4   (plus de charges 3 et 4)");
+        println!("           set_rings M1       (tous les anneaux disponibles)");
+        return;
+    }
+
+    let mut excluded = std::collections::BTreeSet::new();
+    for p in &parts[2..] {
+        match parse_ring(p) {
+            Some(r) => {
+                excluded.insert(r);
+            }
+            None => {
+                println!("Invalid ring: {}", p);
+                return;
+            }
+        }
+    }
+
+    let name = parts[1];
+    let mut mortars = state.mortars.write().await;
+    if let Some(m) = mortars.iter_mut().find(|m| m.name == name) {
+        m.excluded_rings = excluded;
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
+        if m.excluded_rings.is_empty() {
+            println!("Mortar '{}': all rings available", name);
+        } else {
+            let rings: Vec<String> = m.excluded_rings.iter().map(|r| format!("{}R", r)).collect();
+            println!("Mortar '{}': excluded rings {}", name, rings.join(" "));
+        }
+    } else {
+        println!("Mortar '{}' not found", name);
+    }
+}
+
+async fn move_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 5 {
+        println!("Usage: move <mortar_name> <elevation> <x> <y>");
+        println!("  elevation: '-' pour l'altitude de repli configuree");
+        println!("  L'ancienne position est conservee dans l'historique des deplacements");
+        return;
+    }
+
+    let name = parts[1];
+    let elevation = parse_elevation_arg(parts[2]);
+    let x: f64 = parts[3].parse().unwrap_or(0.0);
+    let y: f64 = parts[4].parse().unwrap_or(0.0);
+
+    let mut mortars = state.mortars.write().await;
+    let targets = state.targets.read().await;
+    let (elevation, source) = state
+        .fill_elevation(elevation, x, y, &mortars, &targets)
+        .await;
+    if let Some(m) = mortars.iter_mut().find(|m| m.name == name) {
+        m.move_to(elevation, x, y, unix_now());
+        m.elevation_source = source;
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
+        println!(
+            "Mortar '{}' moved to ({:.0}, {:.0}){}",
+            name,
+            x,
+            y,
+            elevation_note(elevation, source)
+        );
+        print_relay(m, &targets);
+    } else {
+        println!("Mortar '{}' not found", name);
+    }
+}
+
+async fn move_friendly_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 5 {
+        println!("Usage: move_friendly <name> <elevation> <x> <y>");
+        println!("  elevation: '-' pour l'altitude de repli configuree");
+        return;
+    }
+
+    let name = parts[1];
+    let elevation = parse_elevation_arg(parts[2]);
+    let x: f64 = parts[3].parse().unwrap_or(0.0);
+    let y: f64 = parts[4].parse().unwrap_or(0.0);
+
+    let (elevation, source) = {
+        let mortars = state.mortars.read().await;
+        let targets = state.targets.read().await;
+        state
+            .fill_elevation(elevation, x, y, &mortars, &targets)
+            .await
+    };
+    let mut friendlies = state.friendlies.write().await;
+    if let Some(f) = friendlies.iter_mut().find(|f| f.name == name) {
+        f.x = x;
+        f.y = y;
+        f.elevation = elevation;
+        state.events.emit(StateEvent::FriendlyUpdated {
+            friendly: f.clone(),
+        });
+        println!(
+            "Friendly '{}' moved to ({:.0}, {:.0}){}",
+            name,
+            x,
+            y,
+            elevation_note(elevation, source)
+        );
+    } else {
+        println!("Friendly '{}' not found", name);
+    }
+}
+
+async fn displacements_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: displacements <mortar_name>");
+        return;
+    }
+
+    let name = parts[1];
+    let mortars = state.mortars.read().await;
+    let targets = state.targets.read().await;
+    let m = match mortars.iter().find(|m| m.name == name) {
+        Some(m) => m,
+        None => {
+            println!("Mortar '{}' not found", name);
+            return;
+        }
+    };
+    if m.displacements.is_empty() {
+        println!("Mortar '{}' has not moved", name);
+        return;
+    }
+    println!("Displacements of '{}':", name);
+    for d in &m.displacements {
+        println!(
+            "  left ({:.0}, {:.0}) e {:.0} m at {}",
+            d.x, d.y, d.elevation, d.moved_at
+        );
+    }
+    println!("  now  ({:.0}, {:.0}) e {:.0} m", m.x, m.y, m.elevation);
+    print_relay(m, &targets);
+}
+
+/// Re-lay data of a moved mortar towards every target.
+fn print_relay(mortar: &crate::MortarPosition, targets: &[crate::TargetPosition]) {
+    for r in mortar.relay_data(targets) {
+        println!(
+            "  {}: az {:.0} mil ({:+.0}), {:.0} m (was {:.0} m)",
+            r.target, r.azimuth_mil, r.azimuth_shift_mil, r.distance_m, r.previous_distance_m
+        );
+    }
+}
+
+async fn set_ring_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: set_ring <mortar_name> [ring]");
+        println!("  Exemple: set_ring M1 2   (anneau 2R par defaut)");
+        println!("           set_ring M1     (plus d'anneau par defaut)");
+        return;
+    }
+
+    let ring = match parts.get(2) {
+        Some(p) => match parse_ring(p) {
+            Some(r) => Some(r),
+            None => {
+                println!("Invalid ring: {}", p);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let name = parts[1];
+    let mut mortars = state.mortars.write().await;
+    if let Some(m) = mortars.iter_mut().find(|m| m.name == name) {
+        if let Some(r) = ring.filter(|r| !m.ring_available(*r)) {
+            println!("Ring {}R is excluded on mortar '{}'", r, name);
+            return;
+        }
+        m.default_ring = ring;
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
+        match ring {
+            Some(r) => println!("Mortar '{}': default ring {}R", name, r),
+            None => println!("Mortar '{}': no default ring", name),
+        }
+    } else {
+        println!("Mortar '{}' not found", name);
+    }
+}
+
+async fn charge_efficiency_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: charge_eff <mortar_name> [factor]");
+        println!("  Exemple: charge_eff M1 0.97   (charges tirant 3% court)");
+        println!("           charge_eff M1        (charges nominales)");
+        return;
+    }
+
+    let factor = match parts.get(2) {
+        Some(p) => match p.parse::<f64>() {
+            Ok(f) if CHARGE_EFFICIENCY_RANGE.contains(&f) => Some(f),
+            _ => {
+                println!(
+                    "Invalid charge efficiency: {} (expected {}-{})",
+                    p,
+                    CHARGE_EFFICIENCY_RANGE.start(),
+                    CHARGE_EFFICIENCY_RANGE.end()
+                );
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let name = parts[1];
+    let mut mortars = state.mortars.write().await;
+    if let Some(m) = mortars.iter_mut().find(|m| m.name == name) {
+        m.charge_efficiency = factor;
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
+        match factor {
+            Some(f) => println!(
+                "Mortar '{}': charges a {:.1}% de la portee des tables (correction appliquee aux solutions)",
+                name,
+                f * 100.0
+            ),
+            None => println!("Mortar '{}': charges nominales", name),
+        }
+    } else {
+        println!("Mortar '{}' not found", name);
+    }
+}
+
+async fn dispersion_factor_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: disp_factor <mortar_name> [factor] [ring...]");
+        println!("  Exemple: disp_factor M1 1.5       (tube use : dispersions x1.5 sur tous les anneaux)");
+        println!("           disp_factor M1 2 3 4     (bipied fatigue aux fortes charges)");
+        println!("           disp_factor M1           (dispersions des tables)");
+        return;
+    }
+
+    let factor = match parts.get(2) {
+        Some(p) => match p.parse::<f64>() {
+            Ok(f) if DISPERSION_FACTOR_RANGE.contains(&f) => Some(f),
+            _ => {
+                println!(
+                    "Invalid dispersion factor: {} (expected {}-{})",
+                    p,
+                    DISPERSION_FACTOR_RANGE.start(),
+                    DISPERSION_FACTOR_RANGE.end()
+                );
+                return;
+            }
+        },
+        None => None,
+    };
+    let mut rings = Vec::new();
+    for p in parts.iter().skip(3) {
+        match parse_ring(p) {
+            Some(r) => rings.push(r),
+            None => {
+                println!("Invalid ring: {}", p);
+                return;
+            }
+        }
+    }
+
+    let name = parts[1];
+    let mut mortars = state.mortars.write().await;
+    if let Some(m) = mortars.iter_mut().find(|m| m.name == name) {
+        let ring_count = m.weapon.ring_count();
+        if let Some(r) = rings.iter().find(|r| usize::from(**r) >= ring_count) {
+            println!("Invalid ring: {} (expected 0 to {})", r, ring_count - 1);
+            return;
+        }
+        if rings.is_empty() {
+            rings = (0..ring_count).map(|r| r as Ring).collect();
+        }
+        for ring in rings {
+            match factor {
+                Some(f) => m.dispersion_factors.insert(ring, f),
+                None => m.dispersion_factors.remove(&ring),
+            };
+        }
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
+        match factor {
+            Some(f) => println!(
+                "Mortar '{}': dispersions x{:.2} (probabilites de coup au but ajustees)",
+                name, f
+            ),
+            None => println!("Mortar '{}': dispersions des tables", name),
+        }
+    } else {
+        println!("Mortar '{}' not found", name);
+    }
+}
+
+async fn set_bias_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: set_bias <mortar_name> [mv_pct]");
+        println!("  Exemple: set_bias M1 -2   (tube use perdant 2% de vitesse initiale)");
+        println!("           set_bias M1      (tube neuf)");
+        return;
+    }
+
+    let pct = match parts.get(2) {
+        Some(p) => match p.parse::<f64>() {
+            Ok(pct) if MV_CORRECTION_RANGE_PCT.contains(&pct) => Some(pct),
+            _ => {
+                println!(
+                    "Invalid muzzle velocity correction: {} (expected {} to {}%)",
+                    p,
+                    MV_CORRECTION_RANGE_PCT.start(),
+                    MV_CORRECTION_RANGE_PCT.end()
+                );
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let name = parts[1];
+    let mut mortars = state.mortars.write().await;
+    if let Some(m) = mortars.iter_mut().find(|m| m.name == name) {
+        m.mv_correction_pct = pct;
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
+        match pct {
+            Some(pct) => println!(
+                "Mortar '{}': vitesse initiale {:+.1}%, portee a {:.1}% des tables (correction appliquee aux solutions)",
+                name,
+                pct,
+                m.mv_range_factor() * 100.0
+            ),
+            None => println!("Mortar '{}': tube neuf", name),
+        }
+    } else {
+        println!("Mortar '{}' not found", name);
+    }
+}
+
+async fn set_status_cli(parts: &[&str], state: &Arc<AppState>) {
+    let status = match parts.get(2).map(|s| MortarStatus::parse_str(s)) {
+        Some(Some(status)) if parts.len() == 3 => status,
+        _ => {
+            println!("Usage: set_status <mortar_name> <READY|MOVING|OOA|RESUPPLY>");
+            println!("  Seules les pieces READY recoivent des missions (plan de feux, missions programmees)");
+            return;
+        }
+    };
+
+    let name = parts[1];
+    let mut mortars = state.mortars.write().await;
+    if let Some(m) = mortars.iter_mut().find(|m| m.name == name) {
+        m.set_status(status, unix_now());
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
+        println!("Mortar '{}' is {}", name, status);
+    } else {
+        println!("Mortar '{}' not found", name);
+    }
+}
+
+async fn sector_cli(parts: &[&str], state: &Arc<AppState>) {
+    let usage = || {
+        println!("Usage: sector <mortar_name> [<left> <right> [max_range_m] | off]");
+        println!(
+            "  Limites gauche et droite dans le sens horaire, ex: sector M1 5300mil 1100mil 2500"
+        );
+        println!("  Le plan de feux et la couverture ne retiennent que les cibles du secteur");
+    };
+    let sector = match parts.get(2..).unwrap_or_default() {
+        [] => None,
+        ["off"] => Some(None),
+        [left, right, rest @ ..] if rest.len() <= 1 => {
+            let max_range_m = match rest.first().map(|m| m.parse::<f64>()) {
+                None => None,
+                Some(Ok(m)) => Some(m),
+                Some(Err(_)) => {
+                    println!("Invalid max range: {}", rest[0]);
+                    return;
+                }
+            };
+            match (left.parse::<Angle>(), right.parse::<Angle>()) {
+                (Ok(left), Ok(right)) => Some(Some(Sector {
+                    left_deg: left.normalized().degrees(),
+                    right_deg: right.normalized().degrees(),
+                    max_range_m,
+                })),
+                (Err(e), _) | (_, Err(e)) => {
+                    println!("{}", e);
+                    return;
+                }
+            }
+        }
+        _ => {
+            usage();
+            return;
+        }
+    };
+    if parts.len() < 2 {
+        usage();
+        return;
+    }
+    if let Some(Some(s)) = sector {
+        if !s.is_valid() {
+            println!("Invalid sector of fire: {}", s);
+            return;
+        }
+    }
 
-    let name = parts[1].to_string();
-    let elevation: f64 = parts[2].parse().unwrap_or(0.0);
-    let x: f64 = parts[3].parse().unwrap_or(0.0);
-    let y: f64 = parts[4].parse().unwrap_or(0.0);
-
+    let name = parts[1];
     let mut mortars = state.mortars.write().await;
-    if mortars.iter().any(|m| m.name == name) {
-        println!("Error: Mortar '{}' already exists", name);
-    } else {
-        mortars.push(crate::MortarPosition::new(name.clone(), elevation, x, y));
-        println!("Mortar '{}' added", name);
+    let Some(m) = mortars.iter_mut().find(|m| m.name == name) else {
+        println!("Mortar '{}' not found", name);
+        return;
+    };
+    if let Some(sector) = sector {
+        m.sector = sector;
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
+    }
+    match m.sector {
+        Some(s) => println!("Mortar '{}' sector of fire: {}", name, s),
+        None => println!("Mortar '{}' has no sector of fire", name),
     }
 }
 
-async fn add_target_cli(parts: &[&str], state: &Arc<AppState>) {
-    if parts.len() < 5 {
-        println!("Usage: add_target <name> <elevation> <x> <y> [target_type] [ammo_type]");
-        println!("  target_type: INFANTERIE/INF, VEHICULE/VEH, SOUTIEN/SOU (default: INFANTERIE)");
-        println!("  ammo_type: HE, PRACTICE, SMOKE, FLARE (default: HE)");
+async fn register_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 6 {
+        println!("Usage: register <mortar_name> <x> <y> <V> <H>");
+        println!("  x y : point connu tire; V H : deviation observee de l'impact");
+        println!("  La correction est appliquee a toutes les solutions du mortier");
         return;
     }
 
-    let name = parts[1].to_string();
-    let elevation: f64 = parts[2].parse().unwrap_or(0.0);
-    let x: f64 = parts[3].parse().unwrap_or(0.0);
-    let y: f64 = parts[4].parse().unwrap_or(0.0);
-
-    let ttype = if parts.len() > 5 {
-        TargetType::parse_str(parts[5]).unwrap_or(TargetType::Infanterie)
-    } else {
-        TargetType::Infanterie
-    };
-
-    let ammo = if parts.len() > 6 {
-        AmmoKind::parse_str(parts[6]).unwrap_or(AmmoKind::He)
-    } else {
-        AmmoKind::He
+    let coords: Vec<f64> = match parts[2..6].iter().map(|p| p.parse()).collect() {
+        Ok(c) => c,
+        Err(_) => {
+            println!("Invalid coordinates or deviation");
+            return;
+        }
     };
+    let convention = *state.sign_convention.read().await;
+    let (vertical_m, horizontal_m) = convention.to_standard(coords[2], coords[3]);
 
-    let mut targets = state.targets.write().await;
-    if targets.iter().any(|t| t.name == name) {
-        println!("Error: Target '{}' already exists", name);
+    let name = parts[1];
+    let mut mortars = state.mortars.write().await;
+    if let Some(m) = mortars.iter_mut().find(|m| m.name == name) {
+        let point = crate::Position::new(String::new(), 0.0, coords[0], coords[1]);
+        let reg = register(
+            m,
+            &point,
+            SpotObservation {
+                vertical_m,
+                horizontal_m,
+            },
+            unix_now(),
+        );
+        println!(
+            "Mortar '{}' registered: portee {:+.0} m, gisement {:+.0} mil (appliques aux solutions)",
+            name, reg.range_correction_m, reg.deflection_mil
+        );
+        m.registration = Some(reg);
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
     } else {
-        targets.push(crate::TargetPosition::new(
-            name.clone(),
-            elevation,
-            x,
-            y,
-            ttype,
-            ammo,
-        ));
-        println!("Target '{}' added as {} [{}]", name, ttype, ammo);
+        println!("Mortar '{}' not found", name);
     }
 }
 
-async fn rm_mortar_cli(parts: &[&str], state: &Arc<AppState>) {
+async fn registration_cli(parts: &[&str], state: &Arc<AppState>) {
     if parts.len() < 2 {
-        println!("Usage: rm_mortar <name>");
+        println!("Usage: registration <mortar_name> [-]");
         return;
     }
 
     let name = parts[1];
     let mut mortars = state.mortars.write().await;
-    let before = mortars.len();
-    mortars.retain(|m| m.name != name);
-
-    if mortars.len() < before {
-        println!("Mortar '{}' deleted", name);
-    } else {
-        println!("Mortar '{}' not found", name);
+    let m = match mortars.iter_mut().find(|m| m.name == name) {
+        Some(m) => m,
+        None => {
+            println!("Mortar '{}' not found", name);
+            return;
+        }
+    };
+    if parts.get(2) == Some(&"-") {
+        m.registration = None;
+        state
+            .events
+            .emit(StateEvent::MortarUpdated { mortar: m.clone() });
+        println!("Mortar '{}' registration cleared", name);
+        return;
+    }
+    match &m.registration {
+        Some(reg) => println!(
+            "Mortar '{}': point ({:.0}, {:.0}), impact ({:.0}, {:.0}), portee {:+.0} m, gisement {:+.0} mil (at {})",
+            name,
+            reg.point_x,
+            reg.point_y,
+            reg.impact_x,
+            reg.impact_y,
+            reg.range_correction_m,
+            reg.deflection_mil,
+            reg.registered_at
+        ),
+        None => println!("Mortar '{}' has no registration", name),
     }
 }
 
-async fn rm_target_cli(parts: &[&str], state: &Arc<AppState>) {
-    if parts.len() < 2 {
-        println!("Usage: rm_target <name>");
+async fn set_type_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 3 {
+        println!("Usage: set_type <target_name> <target_type>");
+        println!("  target_type: INFANTERIE/INF, VEHICULE/VEH, SOUTIEN/SOU");
         return;
     }
 
     let name = parts[1];
-    let mut targets = state.targets.write().await;
-    let before = targets.len();
-    targets.retain(|t| t.name != name);
+    let ttype = match TargetType::parse_str(parts[2]) {
+        Some(t) => t,
+        None => {
+            println!("Invalid target type: {}", parts[2]);
+            return;
+        }
+    };
 
-    if targets.len() < before {
-        println!("Target '{}' deleted", name);
+    let mut targets = state.targets.write().await;
+    if let Some(t) = targets.iter_mut().find(|t| t.name == name) {
+        t.target_type = ttype;
+        state
+            .events
+            .emit(StateEvent::TargetUpdated { target: t.clone() });
+        println!("Target '{}' type set to {}", name, ttype);
     } else {
         println!("Target '{}' not found", name);
     }
 }
 
-async fn set_ammo_cli(parts: &[&str], state: &Arc<AppState>) {
+async fn set_intel_cli(parts: &[&str], state: &Arc<AppState>) {
     if parts.len() < 3 {
-        println!("Usage: set_ammo <target_name> <ammo_type>");
-        println!("  ammo_type: HE, PRACTICE, SMOKE, FLARE");
+        println!("Usage: set_intel <target_name> <reliability> [source...]");
+        println!("  reliability: CONFIRMED/C, PROBABLE/PR, POSSIBLE/PO, DOUBTFUL/D");
         return;
     }
 
     let name = parts[1];
-    let ammo = match AmmoKind::parse_str(parts[2]) {
-        Some(a) => a,
+    let reliability = match Reliability::parse_str(parts[2]) {
+        Some(r) => r,
         None => {
-            println!("Invalid ammo type: {}", parts[2]);
+            println!("Invalid reliability: {}", parts[2]);
             return;
         }
     };
+    let source = (parts.len() > 3).then(|| parts[3..].join(" "));
 
     let mut targets = state.targets.write().await;
     if let Some(t) = targets.iter_mut().find(|t| t.name == name) {
-        t.ammo_type = ammo;
-        println!("Target '{}' ammo set to {}", name, ammo);
+        t.reliability = Some(reliability);
+        t.last_observed = Some(unix_now());
+        if source.is_some() {
+            t.reporting_source = source;
+        }
+        state
+            .events
+            .emit(StateEvent::TargetUpdated { target: t.clone() });
+        println!("Target '{}' observed now, {}", name, reliability);
     } else {
         println!("Target '{}' not found", name);
     }
 }
 
-async fn set_type_cli(parts: &[&str], state: &Arc<AppState>) {
-    if parts.len() < 3 {
-        println!("Usage: set_type <target_name> <target_type>");
-        println!("  target_type: INFANTERIE/INF, VEHICULE/VEH, SOUTIEN/SOU");
+async fn clearance_cli(parts: &[&str], state: &Arc<AppState>) {
+    if parts.len() < 2 {
+        println!("Usage: clearance <target_name> [cleared_by...|-]");
+        println!("  Autorisation de tir de la cible ('-' : la retirer)");
         return;
     }
 
     let name = parts[1];
-    let ttype = match TargetType::parse_str(parts[2]) {
+    let mut targets = state.targets.write().await;
+    let t = match targets.iter_mut().find(|t| t.name == name) {
         Some(t) => t,
         None => {
-            println!("Invalid target type: {}", parts[2]);
+            println!("Target '{}' not found", name);
             return;
         }
     };
-
-    let mut targets = state.targets.write().await;
-    if let Some(t) = targets.iter_mut().find(|t| t.name == name) {
-        t.target_type = ttype;
-        println!("Target '{}' type set to {}", name, ttype);
-    } else {
-        println!("Target '{}' not found", name);
+    match parts.get(2).copied() {
+        None => match &t.clearance {
+            Some(c) => println!(
+                "Target '{}' cleared by {} {}s ago",
+                name,
+                c.cleared_by,
+                unix_now().saturating_sub(c.cleared_at)
+            ),
+            None => println!("Target '{}' is not cleared", name),
+        },
+        Some("-") => {
+            t.clearance = None;
+            state
+                .events
+                .emit(StateEvent::TargetUpdated { target: t.clone() });
+            println!("Target '{}' clearance revoked", name);
+        }
+        Some(_) => {
+            let cleared_by = parts[2..].join(" ");
+            t.clearance = Some(Clearance {
+                cleared_by: cleared_by.clone(),
+                cleared_at: unix_now(),
+            });
+            state
+                .events
+                .emit(StateEvent::TargetUpdated { target: t.clone() });
+            println!("Target '{}' cleared by {}", name, cleared_by);
+        }
     }
 }
 
@@ -272,6 +3337,9 @@ pub async fn correct_target_cli(
         }
     };
 
+    let convention = *state.sign_convention.read().await;
+    let (input_v, input_h) = (vertical_m, horizontal_m);
+    let (vertical_m, horizontal_m) = convention.to_standard(input_v, input_h);
     let corrected = apply_correction(&target, vertical_m, horizontal_m);
     let corrected_name = corrected.name.clone();
     let new_x = corrected.x;
@@ -285,6 +3353,18 @@ pub async fn correct_target_cli(
         targets.push(corrected);
         println!("Nouvelle cible corrigee: {}", corrected_name);
     }
+    state.events.emit(StateEvent::TargetCorrected {
+        original: target_name.to_string(),
+        corrected: corrected_name.clone(),
+        correction: CorrectionRecord {
+            vertical_m,
+            horizontal_m,
+            from_x: target.x,
+            from_y: target.y,
+            to_x: new_x,
+            to_y: new_y,
+        },
+    });
 
     println!();
     println!(
@@ -292,8 +3372,10 @@ pub async fn correct_target_cli(
         target_name, target.x, target.y
     );
     println!(
-        "  Deviation: V={:+.0}m (N-/S+) H={:+.0}m (O-/E+)",
-        vertical_m, horizontal_m
+        "  Deviation: V={:+.0}m H={:+.0}m ({})",
+        input_v,
+        input_h,
+        convention.label()
     );
     println!(
         "  Corrige:   {} -> X={:.0} Y={:.0}",
@@ -302,20 +3384,318 @@ pub async fn correct_target_cli(
     println!();
 }
 
-pub async fn calc_and_print(state: &Arc<AppState>, mortar_name: &str, target_name: &str) {
+pub async fn correct_target_in_place_cli(
+    state: &Arc<AppState>,
+    target_name: &str,
+    vertical_m: f64,
+    horizontal_m: f64,
+) {
+    let mut targets = state.targets.write().await;
+
+    let target = match targets.iter_mut().find(|t| t.name == target_name) {
+        Some(t) => t,
+        None => {
+            println!("Target '{}' not found", target_name);
+            return;
+        }
+    };
+
+    let convention = *state.sign_convention.read().await;
+    let (v, h) = convention.to_standard(vertical_m, horizontal_m);
+    let record = apply_correction_in_place(target, v, h);
+    state.events.emit(StateEvent::TargetCorrected {
+        original: target_name.to_string(),
+        corrected: target_name.to_string(),
+        correction: record.clone(),
+    });
+
+    println!("Correction sur place: {}", target_name);
+    println!();
+    println!("  Avant:     X={:.0} Y={:.0}", record.from_x, record.from_y);
+    println!(
+        "  Deviation: V={:+.0}m H={:+.0}m ({})",
+        vertical_m,
+        horizontal_m,
+        convention.label()
+    );
+    println!("  Apres:     X={:.0} Y={:.0}", record.to_x, record.to_y);
+    println!("  Corrections cumulees: {}", target.corrections.len());
+    println!();
+}
+
+pub async fn adjust_fire_cli(
+    state: &Arc<AppState>,
+    mortar_name: &str,
+    target_name: &str,
+    vertical_m: f64,
+    horizontal_m: f64,
+    ring: Option<Ring>,
+) {
+    let mortars = state.mortars.read().await;
+    let targets = state.targets.read().await;
+    let ballistics = state.ballistics.read().await;
+
+    let (m, t) = match (
+        mortars.iter().find(|m| m.name == mortar_name),
+        targets.iter().find(|t| t.name == target_name),
+    ) {
+        (Some(m), Some(t)) => (m, t),
+        (None, _) => {
+            println!("Mortar '{}' not found", mortar_name);
+            return;
+        }
+        (_, None) => {
+            println!("Target '{}' not found", target_name);
+            return;
+        }
+    };
+    if let Err(e) = m.check_ammo(t.ammo_type, &*state.ammo.read().await) {
+        println!("Error: {}", e);
+        return;
+    }
+
+    let distance = m.as_position().distance_to(&t.as_position());
+    let ring = match ring.or_else(|| m.preferred_ring(&ballistics, t.ammo_type, distance)) {
+        Some(r) => r,
+        None => {
+            println!("No {} ring covers {:.0} m", t.ammo_type, distance);
+            return;
+        }
+    };
+    let table = match ballistics.get(&(m.weapon, t.ammo_type, ring)) {
+        Some(table) => table,
+        None => {
+            println!("No ballistic table for {} {}R", t.ammo_type, ring);
+            return;
+        }
+    };
+
+    let convention = *state.sign_convention.read().await;
+    let (v, h) = convention.to_standard(vertical_m, horizontal_m);
+    let c = firing_data_correction(m, t, table, v, h);
+
+    println!();
+    println!(
+        "=== CORRECTION PIECE: {} -> {} ({} {}R) ===",
+        m.name, t.name, t.ammo_type, ring
+    );
+    println!();
+    println!(
+        "  Deviation: V={:+.0}m H={:+.0}m ({})",
+        vertical_m,
+        horizontal_m,
+        convention.label()
+    );
+    let side = if c.deflection_mil >= 0.0 {
+        "DROITE"
+    } else {
+        "GAUCHE"
+    };
+    println!(
+        "  Direction: {} {:.0} mils ({:+.0} m lateral)",
+        side,
+        c.deflection_mil.abs(),
+        c.lateral_correction_m
+    );
+    match c.elevation_mil {
+        Some(e) => {
+            let verb = if e >= 0.0 { "MONTER" } else { "DESCENDRE" };
+            println!(
+                "  Elevation: {} {:.0} mils ({:+.0} m en portee)",
+                verb,
+                e.abs(),
+                c.range_correction_m
+            );
+        }
+        None => println!(
+            "  Elevation: N/A ({:+.0} m en portee, hors table)",
+            c.range_correction_m
+        ),
+    }
+    println!();
+}
+
+pub async fn spot_cli(
+    state: &Arc<AppState>,
+    mortar_name: &str,
+    target_name: &str,
+    observation: Option<SpotObservation>,
+    ring: Option<Ring>,
+) {
+    let mortars = state.mortars.read().await;
+    let mut targets = state.targets.write().await;
+    let ballistics = state.ballistics.read().await;
+
+    let m = match mortars.iter().find(|m| m.name == mortar_name) {
+        Some(m) => m,
+        None => {
+            println!("Mortar '{}' not found", mortar_name);
+            return;
+        }
+    };
+    let t = match targets.iter_mut().find(|t| t.name == target_name) {
+        Some(t) => t,
+        None => {
+            println!("Target '{}' not found", target_name);
+            return;
+        }
+    };
+    if let Err(e) = m.check_ammo(t.ammo_type, &*state.ammo.read().await) {
+        println!("Error: {}", e);
+        return;
+    }
+    if state.require_clearance && !t.is_cleared() {
+        println!(
+            "Target '{}' is not cleared for fire (clearance <target> <by>)",
+            t.name
+        );
+        return;
+    }
+    if let Some(staleness) = t.staleness(unix_now()).filter(|s| s.stale) {
+        println!(
+            "Warning: intel on mobile target '{}' is {} min old (staleness {:.0}%), confirm its position",
+            t.name,
+            staleness.age_s / 60,
+            staleness.score * 100.0
+        );
+    }
+
+    let convention = *state.sign_convention.read().await;
+    let observation = observation.map(|o| {
+        let (vertical_m, horizontal_m) = convention.to_standard(o.vertical_m, o.horizontal_m);
+        SpotObservation {
+            vertical_m,
+            horizontal_m,
+        }
+    });
+    let mut report = match spot_round(
+        m,
+        t,
+        &ballistics,
+        &*state.dispersions.read().await,
+        ring,
+        observation,
+        *state.training_offset.read().await,
+        &mut state.sim_rng(None).await,
+    ) {
+        Ok(r) => r,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    state
+        .complete_solution(
+            &mut report.solution,
+            m,
+            t,
+            &ballistics,
+            &SolveOptions::default(),
+        )
+        .await;
+    state.events.emit(StateEvent::TargetCorrected {
+        original: target_name.to_string(),
+        corrected: target_name.to_string(),
+        correction: report.correction.clone(),
+    });
+    let record = SolutionRecord {
+        timestamp: unix_now(),
+        mortar: mortar_name.to_string(),
+        fired: true,
+        deviation: Some(report.deviation),
+        solution: report.solution.clone(),
+        shot: Some(ShotTrace::from_spot(m, t, &report)),
+    };
+    drop(ballistics);
+    drop(targets);
+    drop(mortars);
+    state.record_solution(target_name, record).await;
+
+    println!();
+    println!(
+        "=== COUP DE REGLAGE: {} -> {} ({} {}R) ===",
+        mortar_name, target_name, report.ammo_type, report.ring
+    );
+    println!();
+    let (v, h) = convention.to_standard(report.deviation.vertical_m, report.deviation.horizontal_m);
+    println!(
+        "  Impact{}:    V={:+.0}m H={:+.0}m ({}) -> ecart {:.0} m",
+        if report.simulated { " simule" } else { "" },
+        v,
+        h,
+        convention.label(),
+        report.miss_distance_m
+    );
+    println!(
+        "  Cible corrigee: X={:.0} Y={:.0}",
+        report.correction.to_x, report.correction.to_y
+    );
+    if let Some(sel) = &report.solution.selected_solution {
+        let key = format!("{}R", report.ring);
+        match sel.elevations.get(report.ring) {
+            Some(e) => println!("  Nouvelle elevation {}: {:.1} mil", key, e),
+            None => println!("  Nouvelle elevation {}: N/A", key),
+        }
+    }
+    if let Some(danger) = &report.solution.danger_close {
+        for f in &danger.friendlies {
+            println!(
+                "  ATTENTION: ami '{}' a {:.0} m, dans le rayon de danger ({})",
+                f.name,
+                f.distance_m,
+                f.rings.join(", ")
+            );
+        }
+    }
+    println!();
+    println!("  >>> {} <<<", report.recommendation);
+    println!();
+}
+
+pub async fn calc_and_print(
+    state: &Arc<AppState>,
+    mortar_name: &str,
+    target_name: &str,
+    observer: Option<&Observer>,
+) {
     let mortars = state.mortars.read().await;
     let targets = state.targets.read().await;
+    let ballistics = state.ballistics.read().await;
 
     let mortar = mortars.iter().find(|m| m.name == mortar_name);
     let target = targets.iter().find(|t| t.name == target_name);
 
     match (mortar, target) {
         (Some(m), Some(t)) => {
-            let solution =
-                calculate_solution_with_dispersion(m, t, &state.ballistics, &state.dispersions);
+            let options = SolveOptions {
+                observer: observer.cloned(),
+                ..Default::default()
+            };
+            let solution = match state.solve(m, t, &ballistics, &options).await {
+                Ok(solution) => solution,
+                Err(e) => {
+                    println!("Error: {}", e);
+                    return;
+                }
+            };
+            let record = SolutionRecord {
+                timestamp: unix_now(),
+                mortar: m.name.clone(),
+                fired: false,
+                deviation: None,
+                solution: solution.clone(),
+                shot: None,
+            };
+            drop(ballistics);
+            drop(targets);
+            drop(mortars);
+            state.record_solution(target_name, record).await;
 
             println!();
-            println!("=== SOLUTION DE TIR: {} -> {} ===", m.name, t.name);
+            println!(
+                "=== SOLUTION DE TIR: {} -> {} ===",
+                mortar_name, target_name
+            );
             println!();
             println!("  Distance:       {:.1} m", solution.distance_m);
             println!("  Azimut:         {:.1} deg", solution.azimuth_deg);
@@ -327,6 +3707,22 @@ pub async fn calc_and_print(state: &Arc<AppState>, mortar_name: &str, target_nam
             println!("  Ogive:          {}", solution.mortar_ammo);
             println!("  Type cible:     {}", solution.target_type);
             println!("  Ogive suggeree: {}", solution.recommended_ammo);
+            if let Some(angle_t) = &solution.angle_t {
+                println!("  Angle T:        {:.0} mils", angle_t.mils);
+            }
+            if let Some(timing) = &solution.observation_timing {
+                println!(
+                    "  Son du depart:  {:.1} s apres le tir (observateur a {:.0} m)",
+                    timing.firing_sound_s, timing.observer_to_gun_m
+                );
+                print!("  Impact/son:");
+                for (r, splash) in timing.splash_s.iter() {
+                    if let (Some(splash), Some(sound)) = (splash, timing.impact_sound_s.get(r)) {
+                        print!(" {}R:{:.1}s/{:.1}s", r, splash, sound);
+                    }
+                }
+                println!();
+            }
             println!();
 
             if let Some(sel) = &solution.selected_solution {
@@ -334,8 +3730,14 @@ pub async fn calc_and_print(state: &Arc<AppState>, mortar_name: &str, target_nam
                 print!("  Elev:");
                 for r in 0..=4 {
                     let key = format!("{}R", r);
-                    match sel.elevations.get(&key).and_then(|v| *v) {
-                        Some(e) => print!(" {}:{:.1}", key, e),
+                    let mark = if sel.default_ring.as_ref() == Some(&key) {
+                        "*"
+                    } else {
+                        ""
+                    };
+                    match sel.elevations.get(r) {
+                        Some(e) => print!(" {}{}:{:.1}", key, mark, e),
+                        None if sel.excluded_rings.contains(&key) => print!(" {}:EXCL", key),
                         None => print!(" {}:N/A", key),
                     }
                 }
@@ -343,32 +3745,114 @@ pub async fn calc_and_print(state: &Arc<AppState>, mortar_name: &str, target_nam
                 print!("  Disp:");
                 for r in 0..=4 {
                     let key = format!("{}R", r);
-                    match sel.dispersions.get(&key).and_then(|v| *v) {
+                    match sel.dispersions.get(r) {
                         Some(d) => print!(" {}:{:.1}m", key, d),
                         None => print!(" {}:N/A", key),
                     }
                 }
                 println!();
+                if let Some(ellipse) = &sel.dispersion_ellipse {
+                    print!("  PE:");
+                    for r in 0..=4 {
+                        let key = format!("{}R", r);
+                        match (ellipse.range_pe_m.get(r), ellipse.deflection_pe_m.get(r)) {
+                            (Some(range), Some(deflection)) => {
+                                print!(" {}:{:.0}x{:.0}m", key, range, deflection)
+                            }
+                            _ => print!(" {}:N/A", key),
+                        }
+                    }
+                    println!();
+                }
+                print!("  m/mil:");
+                for r in 0..=4 {
+                    let key = format!("{}R", r);
+                    match sel.range_m_per_mil.get(r) {
+                        Some(m) => print!(" {}:{:.1}m", key, m),
+                        None => print!(" {}:N/A", key),
+                    }
+                }
+                println!();
+                print!("  mil/100m:");
+                for r in 0..=4 {
+                    let key = format!("{}R", r);
+                    match sel.elev_mil_per_100m.get(r) {
+                        Some(d) => print!(" {}:{:.0}", key, d),
+                        None => print!(" {}:N/A", key),
+                    }
+                }
+                println!();
+                print!("  Fleche:");
+                for r in 0..=4 {
+                    let key = format!("{}R", r);
+                    match sel.max_ordinate_m.get(r) {
+                        Some(h) => print!(" {}:{:.0}m", key, h),
+                        None => print!(" {}:N/A", key),
+                    }
+                }
+                println!();
+                println!(
+                    "  Gisement: 1 mil = {:.1} m lateral a {:.0} m",
+                    sel.lateral_m_per_mil, solution.distance_m
+                );
+                if let Some(drift) = &solution.drift {
+                    print!("  Derive:");
+                    for r in 0..RING_COUNT as Ring {
+                        if let (Some(c), Some(az)) =
+                            (drift.correction_mil.get(r), drift.azimuth_mil.get(r))
+                        {
+                            print!(" {}R:{:+.1} -> {:.1}", r, c, az);
+                        }
+                    }
+                    println!(" mil");
+                }
+                if let Some(radius) = sel.effect_radius_m {
+                    println!("  Rayon d'effet: {:.0} m", radius);
+                }
+                if let Some(cmp) = &sel.ring_comparison {
+                    print!("  Anneaux:");
+                    for o in &cmp.options {
+                        let tof = o
+                            .time_of_flight_s
+                            .map_or("N/A".to_string(), |t| format!("{:.1}s", t));
+                        let disp = o
+                            .dispersion_m
+                            .map_or("N/A".to_string(), |d| format!("{:.0}m", d));
+                        let edge = if o.near_table_limit { " (limite)" } else { "" };
+                        print!(
+                            " {}:{:.1}/{}/{}{}",
+                            o.ring, o.elevation_mil, tof, disp, edge
+                        );
+                    }
+                    println!();
+                    println!("  Conseil: {} ({})", cmp.recommended_ring, cmp.reason);
+                }
+            }
+            if solution.degraded {
+                let missing: Vec<&str> = solution.missing_data.iter().map(|m| m.as_str()).collect();
+                println!(
+                    "  Solution degradee: donnees manquantes ({})",
+                    missing.join(", ")
+                );
             }
 
             println!();
             println!("  --- Toutes les elevations (mil) / dispersions (m) ---");
-            let rings = ["0R", "1R", "2R", "3R", "4R"];
             print!("  {:>10} |", "TYPE");
-            for r in &rings {
+            for r in &RING_LABELS {
                 print!(" {:>11} |", r);
             }
             println!();
-            println!("  {}", "-".repeat(10 + 2 + rings.len() * 14));
+            println!("  {}", "-".repeat(10 + 2 + RING_LABELS.len() * 14));
 
-            for ammo in AmmoKind::all() {
+            for ammo in solution.solutions.keys() {
                 print!("  {:>10} |", ammo.as_str());
-                let ammo_sol = solution.solutions.get(ammo.as_str());
-                let ammo_disp = solution.dispersions.get(ammo.as_str());
+                let ammo_sol = solution.solutions.get(ammo);
+                let ammo_disp = solution.dispersions.get(ammo);
 
-                for r in &rings {
-                    let elev = ammo_sol.and_then(|s| s.get(*r).and_then(|v| *v));
-                    let disp = ammo_disp.and_then(|d| d.get(*r).and_then(|v| *v));
+                for r in 0..RING_COUNT as Ring {
+                    let elev = ammo_sol.and_then(|s| s.get(r));
+                    let disp = ammo_disp.and_then(|d| d.get(r));
                     match (elev, disp) {
                         (Some(e), Some(d)) => print!(" {:>5.1}/{:<4.1} |", e, d),
                         (Some(e), None) => print!(" {:>5.1}/---- |", e),
@@ -378,6 +3862,9 @@ pub async fn calc_and_print(state: &Arc<AppState>, mortar_name: &str, target_nam
                 println!();
             }
 
+            for w in &solution.warnings {
+                println!("  ATTENTION: {}", w);
+            }
             println!();
         }
         (None, _) => println!("Mortar '{}' not found", mortar_name),
@@ -0,0 +1,182 @@
+//! Partage de la liste des cibles avec des unités voisines.
+//!
+//! Un lien de partage ([`ShareLink`]) est identifié par un jeton aléatoire et
+//! n'expose qu'une vue filtrée des cibles ([`SharedTarget`]) : aucune position
+//! de mortier ni historique de correction n'est transmis.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Reliability, TargetPosition, TargetType};
+
+/// Critères de sélection des cibles exposées par un lien de partage.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ShareFilter {
+    /// Noms des cibles partagées ; vide pour toutes les cibles
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// Types de cibles partagés ; vide pour tous les types
+    #[serde(default)]
+    pub target_types: Vec<TargetType>,
+    /// Fiabilité minimale du renseignement
+    #[serde(default)]
+    pub min_reliability: Option<Reliability>,
+    /// Âge maximal du renseignement en secondes
+    #[serde(default)]
+    pub max_age_s: Option<u64>,
+}
+
+impl ShareFilter {
+    /// Indique si la cible est exposée par ce filtre à l'instant `now`.
+    pub fn matches(&self, target: &TargetPosition, now: u64) -> bool {
+        (self.targets.is_empty() || self.targets.contains(&target.name))
+            && (self.target_types.is_empty() || self.target_types.contains(&target.target_type))
+            && target.matches_intel(self.min_reliability, self.max_age_s, now)
+    }
+
+    /// Retourne la vue partagée des cibles satisfaisant le filtre.
+    pub fn apply(&self, targets: &[TargetPosition], now: u64) -> Vec<SharedTarget> {
+        targets
+            .iter()
+            .filter(|t| self.matches(t, now))
+            .map(SharedTarget::from)
+            .collect()
+    }
+}
+
+/// Lien de partage en lecture seule.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareLink {
+    /// Jeton d'accès (hexadécimal, 128 bits)
+    pub token: String,
+    /// Libellé libre (ex: unité destinataire)
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Date de création (secondes Unix)
+    pub created_at: u64,
+    /// Date d'expiration (secondes Unix) ; `None` pour un lien permanent
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// Cibles exposées
+    pub filter: ShareFilter,
+}
+
+impl ShareLink {
+    /// Crée un lien avec un nouveau jeton aléatoire.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Libellé du lien
+    /// * `filter` - Cibles exposées
+    /// * `ttl_s` - Durée de validité en secondes ; `None` pour un lien permanent
+    /// * `now` - Heure de création (secondes Unix)
+    pub fn new(label: Option<String>, filter: ShareFilter, ttl_s: Option<u64>, now: u64) -> Self {
+        ShareLink {
+            token: generate_share_token(),
+            label,
+            created_at: now,
+            expires_at: ttl_s.map(|ttl| now.saturating_add(ttl)),
+            filter,
+        }
+    }
+
+    /// Indique si le lien a expiré à l'instant `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|t| now >= t)
+    }
+}
+
+/// Vue d'une cible exposée par un lien de partage.
+#[derive(Clone, Debug, Serialize)]
+pub struct SharedTarget {
+    /// Identifiant de la cible
+    pub name: String,
+    /// Altitude en mètres
+    pub elevation: f64,
+    /// Coordonnée X en mètres
+    pub x: f64,
+    /// Coordonnée Y en mètres
+    pub y: f64,
+    /// Type tactique de la cible
+    pub target_type: TargetType,
+    /// Date de dernière observation (secondes Unix)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_observed: Option<u64>,
+    /// Fiabilité du renseignement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reliability: Option<Reliability>,
+}
+
+impl From<&TargetPosition> for SharedTarget {
+    fn from(t: &TargetPosition) -> Self {
+        SharedTarget {
+            name: t.name.clone(),
+            elevation: t.elevation,
+            x: t.x,
+            y: t.y,
+            target_type: t.target_type,
+            last_observed: t.last_observed,
+            reliability: t.reliability,
+        }
+    }
+}
+
+/// Génère un jeton aléatoire de 128 bits en hexadécimal, tiré du générateur
+/// aléatoire du système d'exploitation. Sert aux liens de partage et aux
+/// jetons de confirmation des corrections.
+///
+/// # Panics
+///
+/// Panique si le générateur aléatoire du système est indisponible.
+pub fn generate_share_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("OS random number generator unavailable");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AmmoKind;
+
+    fn target(name: &str, target_type: TargetType) -> TargetPosition {
+        TargetPosition::new(name.into(), 0.0, 1.0, 2.0, target_type, AmmoKind::He)
+    }
+
+    #[test]
+    fn filter_selects_targets_and_strips_internal_fields() {
+        let mut t1 = target("T1", TargetType::Infanterie);
+        t1.reporting_source = Some("OP Nord".into());
+        let targets = vec![
+            t1,
+            target("T2", TargetType::Vehicule),
+            target("T3", TargetType::Infanterie),
+        ];
+
+        let all = ShareFilter::default().apply(&targets, 0);
+        assert_eq!(all.len(), 3);
+        let json = serde_json::to_value(&all[0]).unwrap();
+        assert!(json.get("reporting_source").is_none());
+        assert!(json.get("corrections").is_none());
+
+        let filter = ShareFilter {
+            targets: vec!["T1".into(), "T2".into()],
+            target_types: vec![TargetType::Infanterie],
+            ..ShareFilter::default()
+        };
+        let shared = filter.apply(&targets, 0);
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].name, "T1");
+    }
+
+    #[test]
+    fn tokens_are_unique_and_links_expire() {
+        let a = ShareLink::new(None, ShareFilter::default(), Some(60), 1000);
+        let b = ShareLink::new(None, ShareFilter::default(), None, 1000);
+        assert_ne!(a.token, b.token);
+        assert_eq!(a.token.len(), 32);
+        assert!(a.token.bytes().all(|c| c.is_ascii_hexdigit()));
+        assert!(!a.is_expired(1059));
+        assert!(a.is_expired(1060));
+        assert!(!b.is_expired(u64::MAX));
+    }
+}
@@ -0,0 +1,290 @@
+//! Simulation de tir : génération pseudo-aléatoire des points d'impact.
+//!
+//! Le générateur (SplitMix64) est volontairement simple et déterministe pour une
+//! graine donnée, afin que les exercices et les tests soient reproductibles.
+//!
+//! Une graine de session ([`SessionSeed`]) rend reproductible tout un
+//! exercice : chaque simulation sans graine explicite reçoit le générateur
+//! suivant de la série, dans l'ordre des tirs.
+//!
+//! En entraînement, une erreur systématique ([`TrainingOffset`]) peut être
+//! ajoutée aux impacts simulés : le stagiaire doit la découvrir et la corriger
+//! par des coups de réglage.
+//!
+//! Les simulations longues respectent un budget de calcul ([`Budget`]) :
+//! elles s'arrêtent à l'échéance ou sur annulation et rendent un résultat
+//! partiel.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Rapport entre l'écart-type d'une loi normale circulaire et son CEP.
+///
+/// Pour une loi normale circulaire d'écart-type `sigma`, le rayon contenant
+/// 50% des impacts vaut `sigma * sqrt(2 ln 2) ≈ 1.1774 * sigma`.
+pub const CEP_PER_SIGMA: f64 = 1.177_410_022_515_474_6;
+
+/// Générateur pseudo-aléatoire SplitMix64.
+#[derive(Clone, Debug)]
+pub struct SimRng {
+    state: u64,
+}
+
+impl SimRng {
+    /// Crée un générateur à partir d'une graine.
+    pub fn new(seed: u64) -> Self {
+        SimRng { state: seed }
+    }
+
+    /// Crée un générateur à partir de l'horloge système.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        SimRng::new(nanos)
+    }
+
+    /// Retourne le prochain entier pseudo-aléatoire.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Retourne un flottant uniforme dans `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Retourne un tirage de loi normale centrée réduite (Box-Muller).
+    pub fn gaussian(&mut self) -> f64 {
+        // 1 - u is in (0, 1], so ln() stays finite
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+    }
+}
+
+/// Graine de session : série de générateurs dérivés d'une graine unique.
+///
+/// Rejouer les mêmes tirs avec la même graine de session redonne les mêmes
+/// impacts, quel que soit le moment où l'exercice est joué.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::simulation::SessionSeed;
+///
+/// let mut a = SessionSeed::new(42);
+/// let mut b = SessionSeed::new(42);
+/// let (first, second) = (a.next_rng().next_u64(), a.next_rng().next_u64());
+/// assert_ne!(first, second);
+/// assert_eq!(b.next_rng().next_u64(), first);
+/// assert_eq!(b.next_rng().next_u64(), second);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SessionSeed {
+    seed: u64,
+    series: SimRng,
+}
+
+impl SessionSeed {
+    /// Démarre une série à partir d'une graine.
+    pub fn new(seed: u64) -> Self {
+        SessionSeed {
+            seed,
+            series: SimRng::new(seed),
+        }
+    }
+
+    /// Graine de départ de la série.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Générateur de la prochaine simulation.
+    pub fn next_rng(&mut self) -> SimRng {
+        SimRng::new(self.series.next_u64())
+    }
+}
+
+/// Erreur systématique appliquée aux impacts simulés (mode entraînement).
+///
+/// Elle représente une erreur de mise en place de la pièce : tous les coups
+/// tombent décalés du même vecteur par rapport au point visé.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TrainingOffset {
+    /// Décalage vers le Nord en mètres (négatif vers le Sud)
+    pub north_m: f64,
+    /// Décalage vers l'Est en mètres (négatif vers l'Ouest)
+    pub east_m: f64,
+}
+
+/// Point d'impact simulé.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct Impact {
+    /// Coordonnée X (Est-Ouest) en mètres
+    pub x: f64,
+    /// Coordonnée Y (Nord-Sud) en mètres
+    pub y: f64,
+}
+
+/// Tire un point d'impact autour du point visé selon une dispersion circulaire.
+///
+/// # Arguments
+///
+/// * `rng` - Générateur pseudo-aléatoire
+/// * `aim_x`, `aim_y` - Point visé en mètres
+/// * `cep_m` - Dispersion (CEP) en mètres
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::simulation::{sample_impact, SimRng};
+///
+/// let mut rng = SimRng::new(42);
+/// let a = sample_impact(&mut rng, 500.0, 300.0, 39.0);
+/// let b = sample_impact(&mut SimRng::new(42), 500.0, 300.0, 39.0);
+/// assert_eq!(a, b); // même graine, même impact
+/// ```
+pub fn sample_impact(rng: &mut SimRng, aim_x: f64, aim_y: f64, cep_m: f64) -> Impact {
+    let sigma = cep_m.max(0.0) / CEP_PER_SIGMA;
+    Impact {
+        x: aim_x + rng.gaussian() * sigma,
+        y: aim_y + rng.gaussian() * sigma,
+    }
+}
+
+/// Tire `count` points d'impact autour du point visé.
+pub fn scatter(rng: &mut SimRng, aim_x: f64, aim_y: f64, cep_m: f64, count: usize) -> Vec<Impact> {
+    scatter_within(rng, aim_x, aim_y, cep_m, count, &Budget::unlimited())
+}
+
+/// Nombre de tirages entre deux vérifications du budget.
+pub const BUDGET_CHECK_INTERVAL: usize = 64;
+
+/// Budget de calcul : échéance optionnelle et annulation coopérative.
+///
+/// Les clones partagent le même drapeau d'annulation : un calcul lancé sur
+/// un autre fil s'arrête dès que le demandeur annule.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::simulation::Budget;
+///
+/// let budget = Budget::unlimited();
+/// let worker = budget.clone();
+/// assert!(!worker.is_exhausted());
+/// budget.cancel();
+/// assert!(worker.is_exhausted());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    deadline: Option<Instant>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Budget {
+    /// Budget sans échéance (annulable).
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Budget expirant après `limit`.
+    pub fn with_limit(limit: Duration) -> Self {
+        Budget {
+            deadline: Instant::now().checked_add(limit),
+            ..Self::default()
+        }
+    }
+
+    /// Annule le calcul en cours.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// `true` si le calcul doit s'arrêter (échéance passée ou annulation).
+    pub fn is_exhausted(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Garde qui annule le budget lorsqu'elle est détruite (demandeur parti).
+    pub fn cancel_on_drop(&self) -> CancelOnDrop {
+        CancelOnDrop(self.clone())
+    }
+}
+
+/// Annule un [`Budget`] à sa destruction.
+#[derive(Debug)]
+pub struct CancelOnDrop(Budget);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Tire jusqu'à `count` points d'impact dans la limite du budget.
+///
+/// Le budget est vérifié tous les [`BUDGET_CHECK_INTERVAL`] tirages : un
+/// budget épuisé rend au moins ce nombre d'impacts (ou `count` s'il est
+/// inférieur).
+pub fn scatter_within(
+    rng: &mut SimRng,
+    aim_x: f64,
+    aim_y: f64,
+    cep_m: f64,
+    count: usize,
+    budget: &Budget,
+) -> Vec<Impact> {
+    let mut impacts = Vec::with_capacity(count);
+    while impacts.len() < count {
+        let chunk = (count - impacts.len()).min(BUDGET_CHECK_INTERVAL);
+        impacts.extend((0..chunk).map(|_| sample_impact(rng, aim_x, aim_y, cep_m)));
+        if budget.is_exhausted() {
+            break;
+        }
+    }
+    impacts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampled_impacts_respect_cep() {
+        let mut rng = SimRng::new(7);
+        let cep = 40.0;
+        let n = 20_000;
+        let inside = scatter(&mut rng, 0.0, 0.0, cep, n)
+            .iter()
+            .filter(|i| (i.x * i.x + i.y * i.y).sqrt() <= cep)
+            .count();
+        let ratio = inside as f64 / n as f64;
+        assert!((ratio - 0.5).abs() < 0.02, "ratio inside CEP = {ratio}");
+    }
+
+    #[test]
+    fn exhausted_budget_returns_partial_scatter() {
+        let budget = Budget::with_limit(Duration::ZERO);
+        let impacts = scatter_within(&mut SimRng::new(7), 0.0, 0.0, 40.0, 1000, &budget);
+        assert_eq!(impacts.len(), BUDGET_CHECK_INTERVAL);
+
+        // Même graine : le résultat partiel est le début du tirage complet
+        let full = scatter(&mut SimRng::new(7), 0.0, 0.0, 40.0, 1000);
+        assert_eq!(full.len(), 1000);
+        assert_eq!(impacts[..], full[..BUDGET_CHECK_INTERVAL]);
+
+        let budget = Budget::unlimited();
+        let guard = budget.cancel_on_drop();
+        assert!(!budget.is_exhausted());
+        drop(guard);
+        assert!(budget.is_exhausted());
+    }
+}
@@ -0,0 +1,675 @@
+//! Sauvegarde et restauration de l'état (mortiers, cibles et missions).
+//!
+//! Un instantané ([`StateSnapshot`]) peut être réimporté sur un serveur en
+//! service selon une stratégie de fusion ([`MergeStrategy`]). Le mode
+//! « dry-run » permet de consulter les conflits avant de modifier l'état.
+//!
+//! Chaque opération (exercice, plan réel...) est sauvegardée dans son propre
+//! instantané ; [`transfer_targets`] copie ou déplace des cibles choisies,
+//! avec leur historique de corrections, d'une opération à une autre.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::airspace::Airspace;
+use crate::reference::ReferencePoints;
+use crate::safety::FriendlyPosition;
+use crate::timeline::{MissionLog, MissionSchedule};
+use crate::{AmmoPolicy, CorrectionPresets, MortarPosition, TargetNumbering, TargetPosition};
+
+/// Version du format d'instantané.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+fn default_snapshot_version() -> u32 {
+    SNAPSHOT_VERSION
+}
+
+/// Instantané sérialisable de l'état.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// Version du format
+    #[serde(default = "default_snapshot_version")]
+    pub version: u32,
+    /// Date de création (secondes Unix)
+    #[serde(default)]
+    pub created_at: u64,
+    /// Mortiers
+    #[serde(default)]
+    pub mortars: Vec<MortarPosition>,
+    /// Cibles
+    #[serde(default)]
+    pub targets: Vec<TargetPosition>,
+    /// Munitions par défaut par type de cible de l'opération
+    #[serde(default, skip_serializing_if = "AmmoPolicy::is_empty")]
+    pub ammo_policy: AmmoPolicy,
+    /// Numérotation automatique des cibles de l'opération
+    #[serde(default, skip_serializing_if = "TargetNumbering::is_default")]
+    pub target_numbering: TargetNumbering,
+    /// Corrections enregistrées de l'opération
+    #[serde(default, skip_serializing_if = "CorrectionPresets::is_empty")]
+    pub correction_presets: CorrectionPresets,
+    /// Plafonds d'espace aérien de l'opération
+    #[serde(default, skip_serializing_if = "Airspace::is_empty")]
+    pub airspace: Airspace,
+    /// Points de référence de l'opération
+    #[serde(default, skip_serializing_if = "ReferencePoints::is_empty")]
+    pub reference_points: ReferencePoints,
+    /// Positions amies de l'opération
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub friendlies: Vec<FriendlyPosition>,
+    /// Missions programmées de l'opération
+    #[serde(default, skip_serializing_if = "MissionSchedule::is_empty")]
+    pub missions: MissionSchedule,
+    /// Journal des missions closes, avec leurs cibles archivées
+    #[serde(default, skip_serializing_if = "MissionLog::is_empty")]
+    pub mission_log: MissionLog,
+}
+
+impl Default for StateSnapshot {
+    fn default() -> Self {
+        StateSnapshot {
+            version: SNAPSHOT_VERSION,
+            created_at: 0,
+            mortars: Vec::new(),
+            targets: Vec::new(),
+            ammo_policy: AmmoPolicy::default(),
+            target_numbering: TargetNumbering::default(),
+            correction_presets: CorrectionPresets::default(),
+            airspace: Airspace::default(),
+            reference_points: ReferencePoints::default(),
+            friendlies: Vec::new(),
+            missions: MissionSchedule::default(),
+            mission_log: MissionLog::default(),
+        }
+    }
+}
+
+/// Stratégie de fusion d'un instantané avec l'état courant.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// Remplace tout l'état par l'instantané
+    ReplaceAll,
+    /// Ajoute les entités absentes, conserve les existantes en cas de conflit
+    #[default]
+    MergeKeepExisting,
+    /// Ajoute les entités absentes, écrase les existantes en cas de conflit
+    MergeOverwrite,
+}
+
+impl MergeStrategy {
+    /// Retourne la représentation textuelle de la stratégie.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MergeStrategy::ReplaceAll => "replace-all",
+            MergeStrategy::MergeKeepExisting => "merge-keep-existing",
+            MergeStrategy::MergeOverwrite => "merge-overwrite",
+        }
+    }
+
+    /// Parse une stratégie ("replace-all", "merge-keep-existing", "merge-overwrite").
+    pub fn parse_str(s: &str) -> Option<MergeStrategy> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "replace-all" | "replace" => Some(MergeStrategy::ReplaceAll),
+            "merge-keep-existing" | "keep" => Some(MergeStrategy::MergeKeepExisting),
+            "merge-overwrite" | "overwrite" => Some(MergeStrategy::MergeOverwrite),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Bilan de fusion pour un type d'entité.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct MergeCounts {
+    /// Entités ajoutées
+    pub added: Vec<String>,
+    /// Entités existantes écrasées par l'instantané
+    pub overwritten: Vec<String>,
+    /// Entités existantes conservées malgré un conflit
+    pub kept: Vec<String>,
+    /// Entités existantes supprimées (replace-all)
+    pub removed: Vec<String>,
+}
+
+/// Bilan d'une restauration.
+#[derive(Clone, Debug, Serialize)]
+pub struct RestoreReport {
+    /// Stratégie appliquée
+    pub strategy: MergeStrategy,
+    /// `true` si l'état n'a pas été modifié
+    pub dry_run: bool,
+    /// Bilan des mortiers
+    pub mortars: MergeCounts,
+    /// Bilan des cibles
+    pub targets: MergeCounts,
+    /// Noms présents à la fois dans l'état et dans l'instantané
+    pub conflicts: Vec<String>,
+}
+
+/// Fusionne `incoming` dans `current` selon la stratégie et retourne le bilan.
+fn merge_named<T: Clone>(
+    current: &mut Vec<T>,
+    incoming: &[T],
+    strategy: MergeStrategy,
+    name: impl Fn(&T) -> &str,
+) -> MergeCounts {
+    let mut counts = MergeCounts::default();
+
+    if strategy == MergeStrategy::ReplaceAll {
+        counts.removed = current
+            .iter()
+            .filter(|c| !incoming.iter().any(|i| name(i) == name(c)))
+            .map(|c| name(c).to_string())
+            .collect();
+        for item in incoming {
+            if current.iter().any(|c| name(c) == name(item)) {
+                counts.overwritten.push(name(item).to_string());
+            } else {
+                counts.added.push(name(item).to_string());
+            }
+        }
+        *current = incoming.to_vec();
+        return counts;
+    }
+
+    for item in incoming {
+        match current.iter_mut().find(|c| name(c) == name(item)) {
+            Some(existing) if strategy == MergeStrategy::MergeOverwrite => {
+                *existing = item.clone();
+                counts.overwritten.push(name(item).to_string());
+            }
+            Some(_) => counts.kept.push(name(item).to_string()),
+            None => {
+                current.push(item.clone());
+                counts.added.push(name(item).to_string());
+            }
+        }
+    }
+    counts
+}
+
+/// Applique un instantané sur des listes de mortiers et de cibles.
+///
+/// Les listes sont modifiées sur place ; pour un « dry-run », appeler la
+/// fonction sur des copies.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::snapshot::{restore_snapshot, MergeStrategy, StateSnapshot};
+/// use mortar::MortarPosition;
+///
+/// let mut mortars = vec![MortarPosition::new("M1".into(), 0.0, 0.0, 0.0)];
+/// let mut targets = Vec::new();
+/// let snapshot = StateSnapshot {
+///     mortars: vec![
+///         MortarPosition::new("M1".into(), 10.0, 5.0, 5.0),
+///         MortarPosition::new("M2".into(), 0.0, 100.0, 0.0),
+///     ],
+///     ..StateSnapshot::default()
+/// };
+///
+/// let report = restore_snapshot(
+///     &mut mortars,
+///     &mut targets,
+///     &snapshot,
+///     MergeStrategy::MergeKeepExisting,
+///     false,
+/// );
+/// assert_eq!(report.conflicts, vec!["mortar:M1"]);
+/// assert_eq!(mortars.len(), 2);
+/// assert_eq!(mortars[0].elevation, 0.0); // M1 conservé
+/// ```
+pub fn restore_snapshot(
+    mortars: &mut Vec<MortarPosition>,
+    targets: &mut Vec<TargetPosition>,
+    snapshot: &StateSnapshot,
+    strategy: MergeStrategy,
+    dry_run: bool,
+) -> RestoreReport {
+    let mut conflicts: Vec<String> = snapshot
+        .mortars
+        .iter()
+        .filter(|i| mortars.iter().any(|m| m.name == i.name))
+        .map(|i| format!("mortar:{}", i.name))
+        .collect();
+    conflicts.extend(
+        snapshot
+            .targets
+            .iter()
+            .filter(|i| targets.iter().any(|t| t.name == i.name))
+            .map(|i| format!("target:{}", i.name)),
+    );
+
+    let (mortar_counts, target_counts) = if dry_run {
+        (
+            merge_named(&mut mortars.clone(), &snapshot.mortars, strategy, |m| {
+                &m.name
+            }),
+            merge_named(&mut targets.clone(), &snapshot.targets, strategy, |t| {
+                &t.name
+            }),
+        )
+    } else {
+        (
+            merge_named(mortars, &snapshot.mortars, strategy, |m| &m.name),
+            merge_named(targets, &snapshot.targets, strategy, |t| &t.name),
+        )
+    };
+
+    RestoreReport {
+        strategy,
+        dry_run,
+        mortars: mortar_counts,
+        targets: target_counts,
+        conflicts,
+    }
+}
+
+/// Fusionne la politique de munitions d'un instantané selon la stratégie.
+pub fn merge_ammo_policy(current: &mut AmmoPolicy, incoming: &AmmoPolicy, strategy: MergeStrategy) {
+    match strategy {
+        MergeStrategy::ReplaceAll => *current = incoming.clone(),
+        MergeStrategy::MergeKeepExisting => {
+            for (target_type, ammo) in &incoming.0 {
+                current.0.entry(*target_type).or_insert(*ammo);
+            }
+        }
+        MergeStrategy::MergeOverwrite => current.0.extend(incoming.0.iter()),
+    }
+}
+
+/// Fusionne les corrections enregistrées d'un instantané selon la stratégie.
+pub fn merge_correction_presets(
+    current: &mut CorrectionPresets,
+    incoming: &CorrectionPresets,
+    strategy: MergeStrategy,
+) {
+    match strategy {
+        MergeStrategy::ReplaceAll => *current = incoming.clone(),
+        MergeStrategy::MergeKeepExisting => {
+            for (name, preset) in &incoming.0 {
+                current.0.entry(name.clone()).or_insert(*preset);
+            }
+        }
+        MergeStrategy::MergeOverwrite => current
+            .0
+            .extend(incoming.0.iter().map(|(n, p)| (n.clone(), *p))),
+    }
+}
+
+/// Fusionne les plafonds d'espace aérien d'un instantané selon la stratégie.
+pub fn merge_airspace(current: &mut Airspace, incoming: &Airspace, strategy: MergeStrategy) {
+    match strategy {
+        MergeStrategy::ReplaceAll => *current = incoming.clone(),
+        MergeStrategy::MergeKeepExisting => {
+            for (name, restriction) in &incoming.0 {
+                current.0.entry(name.clone()).or_insert(*restriction);
+            }
+        }
+        MergeStrategy::MergeOverwrite => current
+            .0
+            .extend(incoming.0.iter().map(|(n, r)| (n.clone(), *r))),
+    }
+}
+
+/// Fusionne les points de référence d'un instantané selon la stratégie.
+pub fn merge_reference_points(
+    current: &mut ReferencePoints,
+    incoming: &ReferencePoints,
+    strategy: MergeStrategy,
+) {
+    match strategy {
+        MergeStrategy::ReplaceAll => *current = incoming.clone(),
+        MergeStrategy::MergeKeepExisting => {
+            for (name, point) in &incoming.0 {
+                current
+                    .0
+                    .entry(name.clone())
+                    .or_insert_with(|| point.clone());
+            }
+        }
+        MergeStrategy::MergeOverwrite => current
+            .0
+            .extend(incoming.0.iter().map(|(n, p)| (n.clone(), p.clone()))),
+    }
+}
+
+/// Fusionne les positions amies d'un instantané selon la stratégie.
+pub fn merge_friendlies(
+    current: &mut Vec<FriendlyPosition>,
+    incoming: &[FriendlyPosition],
+    strategy: MergeStrategy,
+) {
+    merge_named(current, incoming, strategy, |f| &f.name);
+}
+
+/// Fusionne les missions programmées d'un instantané selon la stratégie.
+pub fn merge_missions(
+    current: &mut MissionSchedule,
+    incoming: &MissionSchedule,
+    strategy: MergeStrategy,
+) {
+    match strategy {
+        MergeStrategy::ReplaceAll => *current = incoming.clone(),
+        MergeStrategy::MergeKeepExisting => {
+            for (name, mission) in &incoming.0 {
+                current
+                    .0
+                    .entry(name.clone())
+                    .or_insert_with(|| mission.clone());
+            }
+        }
+        MergeStrategy::MergeOverwrite => current
+            .0
+            .extend(incoming.0.iter().map(|(n, m)| (n.clone(), m.clone()))),
+    }
+}
+
+/// Fusionne le journal des missions d'un instantané selon la stratégie.
+///
+/// Hors remplacement, les comptes rendus absents du journal (même mission
+/// close à la même heure) y sont ajoutés, dans l'ordre chronologique.
+pub fn merge_mission_log(current: &mut MissionLog, incoming: &MissionLog, strategy: MergeStrategy) {
+    if strategy == MergeStrategy::ReplaceAll {
+        *current = incoming.clone();
+        return;
+    }
+    for report in &incoming.0 {
+        let known = current
+            .0
+            .iter()
+            .any(|r| r.mission == report.mission && r.closed_at == report.closed_at);
+        if !known {
+            current.0.push(report.clone());
+        }
+    }
+    current.0.sort_by_key(|r| r.closed_at);
+}
+
+/// Fusionne la numérotation des cibles d'un instantané selon la stratégie.
+///
+/// Avec un même préfixe, le compteur le plus avancé est conservé pour ne pas
+/// réattribuer un numéro déjà utilisé.
+pub fn merge_target_numbering(
+    current: &mut TargetNumbering,
+    incoming: &TargetNumbering,
+    strategy: MergeStrategy,
+) {
+    if current.prefix == incoming.prefix && strategy != MergeStrategy::ReplaceAll {
+        current.next = current.next.max(incoming.next);
+        return;
+    }
+    match strategy {
+        MergeStrategy::ReplaceAll | MergeStrategy::MergeOverwrite => *current = incoming.clone(),
+        MergeStrategy::MergeKeepExisting => {}
+    }
+}
+
+/// Mode de transfert de cibles entre opérations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferMode {
+    /// Les cibles restent dans l'opération source
+    #[default]
+    Copy,
+    /// Les cibles transférées sont retirées de l'opération source
+    Move,
+}
+
+impl TransferMode {
+    /// Retourne la représentation textuelle du mode.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferMode::Copy => "copy",
+            TransferMode::Move => "move",
+        }
+    }
+
+    /// Parse un mode ("copy"/"cp", "move"/"mv").
+    pub fn parse_str(s: &str) -> Option<TransferMode> {
+        match s.to_lowercase().as_str() {
+            "copy" | "cp" => Some(TransferMode::Copy),
+            "move" | "mv" => Some(TransferMode::Move),
+            _ => None,
+        }
+    }
+}
+
+/// Bilan d'un transfert de cibles.
+#[derive(Clone, Debug, Serialize)]
+pub struct TransferReport {
+    /// Mode appliqué
+    pub mode: TransferMode,
+    /// Bilan côté destination
+    pub targets: MergeCounts,
+    /// Cibles retirées de la source (mode `move`)
+    pub removed_from_source: Vec<String>,
+    /// Cibles demandées absentes de la source
+    pub missing: Vec<String>,
+}
+
+/// Copie ou déplace des cibles d'une opération vers une autre.
+///
+/// Les conflits de noms dans la destination sont résolus selon `strategy`,
+/// comme pour une restauration. En mode `move`, seules les cibles
+/// effectivement écrites dans la destination sont retirées de la source :
+/// une cible conservée côté destination reste aussi dans la source.
+///
+/// # Arguments
+///
+/// * `source` - Cibles de l'opération source
+/// * `destination` - Cibles de l'opération destination
+/// * `names` - Cibles à transférer ; vide pour toutes
+/// * `mode` - Copie ou déplacement
+/// * `strategy` - Résolution des conflits de noms
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::snapshot::{transfer_targets, MergeStrategy, TransferMode};
+/// use mortar::{AmmoKind, TargetPosition, TargetType};
+///
+/// let target = |name: &str| {
+///     TargetPosition::new(name.into(), 0.0, 0.0, 0.0, TargetType::Infanterie, AmmoKind::He)
+/// };
+/// let mut training = vec![target("T1"), target("T2")];
+/// let mut live = Vec::new();
+///
+/// let report = transfer_targets(
+///     &mut training,
+///     &mut live,
+///     &["T1".to_string()],
+///     TransferMode::Move,
+///     MergeStrategy::MergeKeepExisting,
+/// );
+/// assert_eq!(report.targets.added, vec!["T1"]);
+/// assert_eq!(live.len(), 1);
+/// assert_eq!(training.len(), 1);
+/// ```
+pub fn transfer_targets(
+    source: &mut Vec<TargetPosition>,
+    destination: &mut Vec<TargetPosition>,
+    names: &[String],
+    mode: TransferMode,
+    strategy: MergeStrategy,
+) -> TransferReport {
+    let selected: Vec<TargetPosition> = source
+        .iter()
+        .filter(|t| names.is_empty() || names.contains(&t.name))
+        .cloned()
+        .collect();
+    let missing = names
+        .iter()
+        .filter(|n| !source.iter().any(|t| &t.name == *n))
+        .cloned()
+        .collect();
+
+    let counts = merge_named(destination, &selected, strategy, |t| &t.name);
+
+    let mut removed_from_source = Vec::new();
+    if mode == TransferMode::Move {
+        source.retain(|t| {
+            let written = counts.added.contains(&t.name) || counts.overwritten.contains(&t.name);
+            if written {
+                removed_from_source.push(t.name.clone());
+            }
+            !written
+        });
+    }
+
+    TransferReport {
+        mode,
+        targets: counts,
+        removed_from_source,
+        missing,
+    }
+}
+
+/// Écrit un instantané en JSON de manière atomique.
+///
+/// Le contenu est d'abord écrit dans `<path>.tmp` puis renommé, afin qu'un
+/// arrêt brutal ne laisse jamais un fichier tronqué.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si l'écriture ou le renommage échoue.
+pub fn write_snapshot<P: AsRef<Path>>(path: P, snapshot: &StateSnapshot) -> Result<()> {
+    let path = path.as_ref();
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+
+    let json = serde_json::to_string_pretty(snapshot)?;
+    std::fs::write(&tmp, json).with_context(|| format!("cannot write {:?}", tmp))?;
+    std::fs::rename(&tmp, path)
+        .with_context(|| format!("cannot rename {:?} to {}", tmp, path.display()))?;
+    Ok(())
+}
+
+/// Lit un instantané JSON.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le fichier est illisible ou invalide.
+pub fn read_snapshot<P: AsRef<Path>>(path: P) -> Result<StateSnapshot> {
+    let path = path.as_ref();
+    let json =
+        std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("invalid snapshot {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AmmoKind, TargetType};
+
+    fn target(name: &str, x: f64) -> TargetPosition {
+        TargetPosition::new(
+            name.into(),
+            0.0,
+            x,
+            0.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        )
+    }
+
+    #[test]
+    fn strategies_resolve_conflicts_and_dry_run_is_side_effect_free() {
+        let snapshot = StateSnapshot {
+            targets: vec![target("T1", 100.0), target("T3", 300.0)],
+            ..StateSnapshot::default()
+        };
+        let initial = vec![target("T1", 1.0), target("T2", 2.0)];
+
+        let mut mortars = Vec::new();
+        let mut targets = initial.clone();
+        let report = restore_snapshot(
+            &mut mortars,
+            &mut targets,
+            &snapshot,
+            MergeStrategy::MergeOverwrite,
+            true,
+        );
+        assert_eq!(report.conflicts, vec!["target:T1"]);
+        assert_eq!(report.targets.overwritten, vec!["T1"]);
+        assert_eq!(report.targets.added, vec!["T3"]);
+        assert_eq!(targets[0].x, 1.0, "dry run must not modify state");
+        assert_eq!(targets.len(), 2);
+
+        restore_snapshot(
+            &mut mortars,
+            &mut targets,
+            &snapshot,
+            MergeStrategy::MergeOverwrite,
+            false,
+        );
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets[0].x, 100.0);
+
+        let mut targets = initial.clone();
+        let report = restore_snapshot(
+            &mut mortars,
+            &mut targets,
+            &snapshot,
+            MergeStrategy::ReplaceAll,
+            false,
+        );
+        assert_eq!(report.targets.removed, vec!["T2"]);
+        assert_eq!(targets.len(), 2);
+        assert!(targets.iter().all(|t| t.name != "T2"));
+    }
+
+    #[test]
+    fn move_keeps_targets_that_were_not_written() {
+        let mut source = vec![target("T1", 1.0), target("T2", 2.0)];
+        source[0].corrections.push(crate::CorrectionRecord {
+            vertical_m: 10.0,
+            horizontal_m: 0.0,
+            from_x: 1.0,
+            from_y: 10.0,
+            to_x: 1.0,
+            to_y: 0.0,
+        });
+        let mut destination = vec![target("T2", 20.0)];
+
+        let report = transfer_targets(
+            &mut source,
+            &mut destination,
+            &["T1".into(), "T2".into(), "T9".into()],
+            TransferMode::Move,
+            MergeStrategy::MergeKeepExisting,
+        );
+        assert_eq!(report.targets.added, vec!["T1"]);
+        assert_eq!(report.targets.kept, vec!["T2"]);
+        assert_eq!(report.removed_from_source, vec!["T1"]);
+        assert_eq!(report.missing, vec!["T9"]);
+        assert_eq!(destination[1].corrections.len(), 1);
+        assert_eq!(source.len(), 1);
+        assert_eq!(destination[0].x, 20.0);
+    }
+
+    #[test]
+    fn snapshot_file_roundtrip() {
+        let path =
+            std::env::temp_dir().join(format!("mortar_snapshot_{}.json", std::process::id()));
+        let snapshot = StateSnapshot {
+            targets: vec![target("T1", 42.0)],
+            ..StateSnapshot::default()
+        };
+
+        write_snapshot(&path, &snapshot).unwrap();
+        let read = read_snapshot(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.version, SNAPSHOT_VERSION);
+        assert_eq!(read.targets[0].x, 42.0);
+    }
+}
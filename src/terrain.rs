@@ -0,0 +1,777 @@
+//! Relief entre la pièce et la cible.
+//!
+//! Les tables de tir supposent la pièce et la cible à la même altitude ; le
+//! modèle simple de la solution (élévation lue à la distance horizontale,
+//! dispersion ajustée linéairement) n'est fiable que pour une différence
+//! d'élévation modérée. Au-delà de seuils configurables, la solution est
+//! signalée et une correction tirée des tables de l'anneau est conseillée.
+//!
+//! La correction conseillée assimile l'angle de chute à l'angle de tir (tir
+//! vertical) : une cible plus haute de `h` mètres est atteinte `h / tan(θ)`
+//! mètres plus tôt, à allonger d'autant ; l'élévation correspondante est lue
+//! dans la colonne `delta_elev_per_100m_mil` de la table.
+//!
+//! Lorsqu'un profil du terrain le long de la ligne pièce-but est fourni
+//! ([`TerrainProfile`]), la trajectoire de chaque anneau (estimée dans le vide
+//! comme pour l'espace aérien, [`crate::airspace`]) est comparée aux crêtes
+//! intermédiaires : un anneau dont la trajectoire passe sous une crête est
+//! signalé dans [`CrestClearance`] avec la distance de la crête.
+//!
+//! Une carte d'altitude ([`TerrainModel`], image PNG en niveaux de gris
+//! géoréférencée par [`TerrainGeoref`]) fournit l'altitude des positions
+//! saisies sans altitude, avant le repli sur la position connue la plus proche
+//! ou l'altitude de référence ([`crate::ElevationFallback`]).
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+use crate::airspace::vacuum_time_of_flight_s;
+use crate::replay::GRAVITY_MPS2;
+use crate::{mils, AmmoKind, BallisticTables, FiringSolution, MortarPosition, RingTable};
+
+/// Seuils d'avertissement sur la différence d'élévation pièce-cible, en
+/// valeur absolue.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::terrain::{ElevationLevel, ElevationThresholds};
+/// let thresholds = ElevationThresholds::default();
+/// assert_eq!(thresholds.level(-50.0), None);
+/// assert_eq!(thresholds.level(-150.0), Some(ElevationLevel::Caution));
+/// assert_eq!(thresholds.level(400.0), Some(ElevationLevel::Critical));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ElevationThresholds {
+    /// Différence (m) au-delà de laquelle la solution est à vérifier
+    pub caution_m: f64,
+    /// Différence (m) au-delà de laquelle le modèle n'est plus fiable
+    pub critical_m: f64,
+}
+
+impl Default for ElevationThresholds {
+    fn default() -> Self {
+        ElevationThresholds {
+            caution_m: 100.0,
+            critical_m: 300.0,
+        }
+    }
+}
+
+impl ElevationThresholds {
+    /// Indique si les seuils sont positifs et ordonnés.
+    pub fn is_valid(&self) -> bool {
+        self.caution_m.is_finite()
+            && self.critical_m.is_finite()
+            && self.caution_m > 0.0
+            && self.caution_m <= self.critical_m
+    }
+
+    /// Retourne le niveau atteint par une différence d'élévation signée.
+    pub fn level(&self, elevation_diff_m: f64) -> Option<ElevationLevel> {
+        let diff = elevation_diff_m.abs();
+        if diff > self.critical_m {
+            Some(ElevationLevel::Critical)
+        } else if diff > self.caution_m {
+            Some(ElevationLevel::Caution)
+        } else {
+            None
+        }
+    }
+
+    /// Seuil franchi pour un niveau.
+    pub fn threshold_m(&self, level: ElevationLevel) -> f64 {
+        match level {
+            ElevationLevel::Caution => self.caution_m,
+            ElevationLevel::Critical => self.critical_m,
+        }
+    }
+
+    /// Description lisible des seuils.
+    pub fn describe(&self) -> String {
+        format!(
+            "Seuils d'elevation : attention > {:.0} m, critique > {:.0} m",
+            self.caution_m, self.critical_m
+        )
+    }
+}
+
+/// Niveau d'avertissement de la différence d'élévation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElevationLevel {
+    /// Solution à vérifier
+    Caution,
+    /// Modèle simple non fiable : appliquer la correction des tables
+    Critical,
+}
+
+impl std::fmt::Display for ElevationLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ElevationLevel::Caution => write!(f, "caution"),
+            ElevationLevel::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// Avertissement de différence d'élévation porté par une solution.
+#[derive(Clone, Debug, Serialize)]
+pub struct ElevationWarning {
+    /// Niveau atteint
+    pub level: ElevationLevel,
+    /// Différence d'élévation signée (pièce - cible, positive si la pièce est
+    /// plus haute)
+    pub elevation_diff_m: f64,
+    /// Seuil franchi
+    pub threshold_m: f64,
+    /// Correction de portée conseillée par anneau (positive : allonger)
+    pub range_correction_m: RingTable,
+    /// Correction d'élévation conseillée par anneau (mils, à ajouter)
+    pub elevation_correction_mil: RingTable,
+}
+
+/// Évalue la différence d'élévation d'une solution.
+///
+/// # Retourne
+///
+/// `None` si la différence ne franchit aucun seuil. Les corrections ne sont
+/// données que pour les anneaux dont la solution sélectionnée fournit une
+/// élévation.
+pub fn elevation_warning(
+    solution: &FiringSolution,
+    thresholds: &ElevationThresholds,
+) -> Option<ElevationWarning> {
+    let diff = solution.signed_elevation_diff_m;
+    let level = thresholds.level(diff)?;
+    let mut range_correction_m = RingTable::default();
+    let mut elevation_correction_mil = RingTable::default();
+    if let Some(sel) = &solution.selected_solution {
+        for (ring, elevation) in sel.elevations.iter() {
+            let angle = match elevation {
+                Some(mil) => mils::mil_to_rad(mil),
+                None => continue,
+            };
+            // Cible plus haute (diff < 0) : impact avant la portée, à allonger
+            let range = -diff / angle.tan();
+            range_correction_m.set(ring, Some(range));
+            elevation_correction_mil.set(
+                ring,
+                sel.elev_mil_per_100m
+                    .get(ring)
+                    .map(|per_100m| -range / 100.0 * per_100m),
+            );
+        }
+    }
+    Some(ElevationWarning {
+        level,
+        elevation_diff_m: diff,
+        threshold_m: thresholds.threshold_m(level),
+        range_correction_m,
+        elevation_correction_mil,
+    })
+}
+
+/// Signale une solution dont la différence d'élévation franchit un seuil.
+///
+/// Renseigne [`FiringSolution::elevation_warning`] et ajoute un
+/// avertissement conseillant la correction des tables.
+pub fn attach_elevation_warning(solution: &mut FiringSolution, thresholds: &ElevationThresholds) {
+    let warning = match elevation_warning(solution, thresholds) {
+        Some(warning) => warning,
+        None => return,
+    };
+    let side = if warning.elevation_diff_m < 0.0 {
+        "above"
+    } else {
+        "below"
+    };
+    let correction = match warning.level {
+        ElevationLevel::Caution => "check the solution against the table height corrections",
+        ElevationLevel::Critical => {
+            "the flat-ground model is unreliable, apply the table height corrections"
+        }
+    };
+    solution.warnings.push(format!(
+        "Target {:.0} m {} the mortar (over {:.0} m): {}",
+        warning.elevation_diff_m.abs(),
+        side,
+        warning.threshold_m,
+        correction
+    ));
+    solution.elevation_warning = Some(warning);
+}
+
+/// Point du profil de terrain le long de la ligne pièce-but.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfilePoint {
+    /// Distance horizontale depuis la pièce (mètres)
+    pub range_m: f64,
+    /// Altitude du terrain (mètres)
+    pub height_m: f64,
+}
+
+/// Profil de terrain le long de la ligne pièce-but, points reliés en ligne
+/// droite.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TerrainProfile(pub Vec<ProfilePoint>);
+
+impl TerrainProfile {
+    /// Indique si le profil ne contient aucun point.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Indique si les distances sont positives et toutes les valeurs finies.
+    pub fn is_valid(&self) -> bool {
+        self.0
+            .iter()
+            .all(|p| p.range_m.is_finite() && p.range_m >= 0.0 && p.height_m.is_finite())
+    }
+}
+
+/// Crête interceptant la trajectoire d'un anneau.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CrestObstruction {
+    /// Anneau (ex: "2R")
+    pub ring: String,
+    /// Distance de la crête depuis la pièce (mètres)
+    pub range_m: f64,
+    /// Altitude de la crête (mètres)
+    pub crest_height_m: f64,
+    /// Altitude de la trajectoire au droit de la crête (mètres)
+    pub trajectory_height_m: f64,
+}
+
+/// Contrôle des crêtes d'une solution.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct CrestClearance {
+    /// Anneaux bloqués, avec la première crête interceptant la trajectoire
+    pub blocked: Vec<CrestObstruction>,
+    /// Plus petite marge au-dessus des crêtes intermédiaires, par anneau
+    /// (mètres, négative si la trajectoire est bloquée)
+    pub min_clearance_m: RingTable,
+}
+
+impl CrestClearance {
+    /// Indique si au moins un anneau est bloqué.
+    pub fn is_blocked(&self) -> bool {
+        !self.blocked.is_empty()
+    }
+}
+
+/// Compare la trajectoire de chaque anneau de la solution sélectionnée au
+/// profil de terrain.
+///
+/// Seuls les points strictement entre la pièce et la cible sont contrôlés.
+/// Entre deux points du profil, le terrain est rectiligne et la trajectoire
+/// concave : la marge la plus faible est toujours atteinte sur un point du
+/// profil. La durée de trajet est celle de la table, ou à défaut estimée à
+/// partir de l'élévation.
+///
+/// # Retourne
+///
+/// `None` sans solution sélectionnée ou sans point intermédiaire.
+pub fn crest_clearance(
+    solution: &FiringSolution,
+    mortar: &MortarPosition,
+    ballistics: &BallisticTables,
+    profile: &TerrainProfile,
+) -> Option<CrestClearance> {
+    let sel = solution.selected_solution.as_ref()?;
+    let ammo = AmmoKind::from_name(&sel.ammo_type)?;
+    let distance = solution.distance_m;
+    let mut crests: Vec<&ProfilePoint> = profile
+        .0
+        .iter()
+        .filter(|p| p.range_m > 0.0 && p.range_m < distance)
+        .collect();
+    if crests.is_empty() {
+        return None;
+    }
+    crests.sort_by(|a, b| a.range_m.total_cmp(&b.range_m));
+    let rise = -solution.signed_elevation_diff_m;
+
+    let mut clearance = CrestClearance::default();
+    for (ring, elevation) in sel.elevations.iter() {
+        let elevation = match elevation {
+            Some(e) => e,
+            None => continue,
+        };
+        let tof = ballistics
+            .get(&(solution.weapon, ammo, ring))
+            .and_then(|t| t.time_of_flight_at(distance))
+            .unwrap_or_else(|| vacuum_time_of_flight_s(distance, elevation));
+        let mut min_clearance = f64::INFINITY;
+        let mut first_block = None;
+        for crest in &crests {
+            let tau = crest.range_m / distance;
+            let t = tau * tof;
+            let height = mortar.elevation + rise * tau + 0.5 * GRAVITY_MPS2 * t * (tof - t);
+            let margin = height - crest.height_m;
+            min_clearance = min_clearance.min(margin);
+            if margin < 0.0 && first_block.is_none() {
+                first_block = Some(CrestObstruction {
+                    ring: format!("{}R", ring),
+                    range_m: crest.range_m,
+                    crest_height_m: crest.height_m,
+                    trajectory_height_m: height,
+                });
+            }
+        }
+        clearance.min_clearance_m.set(ring, Some(min_clearance));
+        clearance.blocked.extend(first_block);
+    }
+    Some(clearance)
+}
+
+/// Contrôle les crêtes du profil et ajoute un avertissement par anneau bloqué.
+///
+/// Renseigne [`FiringSolution::crest_clearance`] ; sans profil, la solution
+/// est inchangée.
+pub fn attach_crest_clearance(
+    solution: &mut FiringSolution,
+    mortar: &MortarPosition,
+    ballistics: &BallisticTables,
+    profile: &TerrainProfile,
+) {
+    let clearance = match crest_clearance(solution, mortar, ballistics, profile) {
+        Some(clearance) => clearance,
+        None => return,
+    };
+    for b in &clearance.blocked {
+        solution.warnings.push(format!(
+            "Crest at {:.0} m blocks the {} trajectory ({:.0} m below the {:.0} m crest)",
+            b.range_m,
+            b.ring,
+            b.crest_height_m - b.trajectory_height_m,
+            b.crest_height_m
+        ));
+    }
+    solution.crest_clearance = Some(clearance);
+}
+
+/// Géoréférencement d'une carte d'altitude.
+///
+/// Le pixel `(colonne, ligne)` est centré en
+/// `(origin_x + colonne · cell_size_m, origin_y - ligne · cell_size_m)` : la
+/// première ligne de l'image est au nord. Un niveau de gris `n` représente
+/// l'altitude `base_m + n · scale_m`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TerrainGeoref {
+    /// Coordonnée X du pixel nord-ouest (mètres)
+    pub origin_x: f64,
+    /// Coordonnée Y du pixel nord-ouest (mètres)
+    pub origin_y: f64,
+    /// Côté d'un pixel (mètres)
+    pub cell_size_m: f64,
+    /// Mètres par niveau de gris
+    #[serde(default = "default_scale_m")]
+    pub scale_m: f64,
+    /// Altitude du niveau 0 (mètres)
+    #[serde(default)]
+    pub base_m: f64,
+}
+
+fn default_scale_m() -> f64 {
+    1.0
+}
+
+impl TerrainGeoref {
+    /// Indique si les valeurs sont finies et le pas strictement positif.
+    pub fn is_valid(&self) -> bool {
+        [
+            self.origin_x,
+            self.origin_y,
+            self.cell_size_m,
+            self.scale_m,
+            self.base_m,
+        ]
+        .iter()
+        .all(|v| v.is_finite())
+            && self.cell_size_m > 0.0
+    }
+
+    /// Parse `x,y,pas[,echelle[,base]]` (échelle 1 et base 0 par défaut).
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::terrain::TerrainGeoref;
+    /// let georef = TerrainGeoref::parse_str("1000,5000,10,0.5,-20").unwrap();
+    /// assert_eq!((georef.origin_x, georef.origin_y, georef.cell_size_m), (1000.0, 5000.0, 10.0));
+    /// assert_eq!((georef.scale_m, georef.base_m), (0.5, -20.0));
+    /// assert_eq!(TerrainGeoref::parse_str("0,0,2").unwrap().scale_m, 1.0);
+    /// assert!(TerrainGeoref::parse_str("0,0,0").is_none());
+    /// ```
+    pub fn parse_str(s: &str) -> Option<Self> {
+        let values: Vec<f64> = s
+            .split(',')
+            .map(|v| v.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        let georef = match values[..] {
+            [origin_x, origin_y, cell_size_m] => TerrainGeoref {
+                origin_x,
+                origin_y,
+                cell_size_m,
+                scale_m: default_scale_m(),
+                base_m: 0.0,
+            },
+            [origin_x, origin_y, cell_size_m, scale_m] => TerrainGeoref {
+                origin_x,
+                origin_y,
+                cell_size_m,
+                scale_m,
+                base_m: 0.0,
+            },
+            [origin_x, origin_y, cell_size_m, scale_m, base_m] => TerrainGeoref {
+                origin_x,
+                origin_y,
+                cell_size_m,
+                scale_m,
+                base_m,
+            },
+            _ => return None,
+        };
+        georef.is_valid().then_some(georef)
+    }
+}
+
+/// Carte d'altitude de la zone d'opération.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerrainModel {
+    georef: TerrainGeoref,
+    width: usize,
+    height: usize,
+    /// Altitudes (mètres), ligne par ligne depuis le nord
+    heights: Vec<f64>,
+}
+
+/// Description d'une carte d'altitude chargée.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TerrainSummary {
+    pub georef: TerrainGeoref,
+    /// Largeur et hauteur de la carte (pixels)
+    pub width: usize,
+    pub height: usize,
+    /// Emprise `[x_min, y_min, x_max, y_max]` des centres de pixels (mètres)
+    pub bounds: [f64; 4],
+    /// Altitudes extrêmes de la carte (mètres)
+    pub min_elevation_m: f64,
+    pub max_elevation_m: f64,
+}
+
+impl TerrainModel {
+    /// Carte de `width` × `height` altitudes, ligne par ligne depuis le nord.
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si le géoréférencement est invalide, si la carte
+    /// est vide ou si le nombre d'altitudes ne correspond pas aux dimensions.
+    pub fn new(
+        georef: TerrainGeoref,
+        width: usize,
+        height: usize,
+        heights: Vec<f64>,
+    ) -> Result<Self> {
+        if !georef.is_valid() {
+            bail!("invalid heightmap georeference");
+        }
+        if width == 0 || height == 0 {
+            bail!("empty heightmap");
+        }
+        if heights.len() != width * height {
+            bail!(
+                "heightmap has {} samples, expected {}x{}",
+                heights.len(),
+                width,
+                height
+            );
+        }
+        Ok(TerrainModel {
+            georef,
+            width,
+            height,
+            heights,
+        })
+    }
+
+    /// Lit une image PNG en niveaux de gris (8 ou 16 bits).
+    ///
+    /// # Erreurs
+    ///
+    /// Retourne une erreur si l'image est illisible ou n'est pas en niveaux
+    /// de gris, ou si le géoréférencement est invalide.
+    pub fn from_png<R: Read>(reader: R, georef: TerrainGeoref) -> Result<Self> {
+        let decoder = png::Decoder::new(reader);
+        let mut reader = decoder.read_info().context("invalid PNG heightmap")?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let frame = reader
+            .next_frame(&mut buf)
+            .context("invalid PNG heightmap")?;
+        let data = &buf[..frame.buffer_size()];
+        let levels: Vec<f64> = match (frame.color_type, frame.bit_depth) {
+            (png::ColorType::Grayscale, png::BitDepth::Eight) => {
+                data.iter().map(|&v| f64::from(v)).collect()
+            }
+            (png::ColorType::Grayscale, png::BitDepth::Sixteen) => data
+                .chunks_exact(2)
+                .map(|v| f64::from(u16::from_be_bytes([v[0], v[1]])))
+                .collect(),
+            (color, depth) => bail!(
+                "heightmap must be an 8 or 16-bit grayscale PNG, got {:?} {:?}",
+                color,
+                depth
+            ),
+        };
+        let heights = levels
+            .iter()
+            .map(|n| georef.base_m + n * georef.scale_m)
+            .collect();
+        Self::new(georef, frame.width as usize, frame.height as usize, heights)
+    }
+
+    /// Géoréférencement de la carte.
+    pub fn georef(&self) -> &TerrainGeoref {
+        &self.georef
+    }
+
+    /// Altitude du pixel `(colonne, ligne)`.
+    fn sample(&self, col: usize, row: usize) -> f64 {
+        self.heights[row * self.width + col]
+    }
+
+    /// Altitude en `(x, y)`, interpolée entre les quatre pixels voisins.
+    ///
+    /// Retourne `None` en dehors de l'emprise de la carte.
+    ///
+    /// # Exemple
+    ///
+    /// ```
+    /// use mortar::terrain::{TerrainGeoref, TerrainModel};
+    /// let georef = TerrainGeoref::parse_str("0,100,100").unwrap();
+    /// // Ligne nord (y = 100) : 10 et 20 m ; ligne sud (y = 0) : 30 et 40 m
+    /// let terrain = TerrainModel::new(georef, 2, 2, vec![10.0, 20.0, 30.0, 40.0]).unwrap();
+    /// assert_eq!(terrain.elevation_at(0.0, 100.0), Some(10.0));
+    /// assert_eq!(terrain.elevation_at(50.0, 50.0), Some(25.0));
+    /// assert_eq!(terrain.elevation_at(150.0, 50.0), None);
+    /// ```
+    pub fn elevation_at(&self, x: f64, y: f64) -> Option<f64> {
+        let g = &self.georef;
+        let col = (x - g.origin_x) / g.cell_size_m;
+        let row = (g.origin_y - y) / g.cell_size_m;
+        let (max_col, max_row) = ((self.width - 1) as f64, (self.height - 1) as f64);
+        if !(0.0..=max_col).contains(&col) || !(0.0..=max_row).contains(&row) {
+            return None;
+        }
+        let (c0, r0) = (col.floor() as usize, row.floor() as usize);
+        let (c1, r1) = ((c0 + 1).min(self.width - 1), (r0 + 1).min(self.height - 1));
+        let (fc, fr) = (col - c0 as f64, row - r0 as f64);
+        let north = self.sample(c0, r0) * (1.0 - fc) + self.sample(c1, r0) * fc;
+        let south = self.sample(c0, r1) * (1.0 - fc) + self.sample(c1, r1) * fc;
+        Some(north * (1.0 - fr) + south * fr)
+    }
+
+    /// Description de la carte : dimensions, emprise et altitudes extrêmes.
+    pub fn summary(&self) -> TerrainSummary {
+        let g = &self.georef;
+        let (min, max) = self
+            .heights
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &h| {
+                (lo.min(h), hi.max(h))
+            });
+        TerrainSummary {
+            georef: *g,
+            width: self.width,
+            height: self.height,
+            bounds: [
+                g.origin_x,
+                g.origin_y - (self.height - 1) as f64 * g.cell_size_m,
+                g.origin_x + (self.width - 1) as f64 * g.cell_size_m,
+                g.origin_y,
+            ],
+            min_elevation_m: min,
+            max_elevation_m: max,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        calculate_solution_with_dispersion, BallisticPoint, BallisticTable, DispersionTable,
+        TargetPosition, TargetType, WeaponSystem,
+    };
+
+    #[test]
+    fn higher_targets_are_flagged_with_a_longer_aim() {
+        let point = |range_m, elev_mil| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: None,
+            delta_elev_per_100m_mil: Some(20.0),
+        };
+        let table = BallisticTable {
+            points: vec![point(0.0, 1600.0), point(2000.0, 800.0)],
+            ..Default::default()
+        };
+        let ballistics = BallisticTables::from([((WeaponSystem::M224, AmmoKind::He, 2), table)]);
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = |elevation| {
+            TargetPosition::new(
+                "T1".into(),
+                elevation,
+                0.0,
+                1000.0,
+                TargetType::Infanterie,
+                AmmoKind::He,
+            )
+        };
+        let thresholds = ElevationThresholds::default();
+
+        let mut flat = calculate_solution_with_dispersion(
+            &mortar,
+            &target(80.0),
+            &ballistics,
+            &DispersionTable::new(),
+        );
+        attach_elevation_warning(&mut flat, &thresholds);
+        assert!(flat.elevation_warning.is_none());
+        assert!(flat.warnings.is_empty());
+
+        let mut steep = calculate_solution_with_dispersion(
+            &mortar,
+            &target(200.0),
+            &ballistics,
+            &DispersionTable::new(),
+        );
+        attach_elevation_warning(&mut steep, &thresholds);
+        let warning = steep.elevation_warning.as_ref().unwrap();
+        assert_eq!(warning.level, ElevationLevel::Caution);
+        assert_eq!(warning.threshold_m, 100.0);
+        // 1200 mils : 67,5°, la cible 200 m plus haute est atteinte ~83 m plus tôt
+        let range = warning.range_correction_m.get(2).unwrap();
+        assert!((range - 200.0 / 67.5f64.to_radians().tan()).abs() < 1e-6);
+        let elevation = warning.elevation_correction_mil.get(2).unwrap();
+        assert!((elevation + range / 100.0 * 20.0).abs() < 1e-9);
+        assert!(steep.warnings[0].contains("200 m above"));
+
+        let mut cliff = calculate_solution_with_dispersion(
+            &mortar,
+            &target(-400.0),
+            &ballistics,
+            &DispersionTable::new(),
+        );
+        attach_elevation_warning(&mut cliff, &thresholds);
+        let warning = cliff.elevation_warning.unwrap();
+        assert_eq!(warning.level, ElevationLevel::Critical);
+        assert!(warning.range_correction_m.get(2).unwrap() < 0.0);
+    }
+
+    #[test]
+    fn crests_under_the_trajectory_block_low_rings() {
+        let table = |time_flight_s| BallisticTable {
+            points: [(0.0, 1400.0), (2000.0, 1000.0)]
+                .map(|(range_m, elev_mil)| BallisticPoint {
+                    range_m,
+                    elev_mil,
+                    time_flight_s: Some(time_flight_s),
+                    delta_elev_per_100m_mil: None,
+                })
+                .to_vec(),
+            ..Default::default()
+        };
+        // Flèches g·T²/8 : ~123 m en 1R, ~490 m en 2R
+        let ballistics = BallisticTables::from([
+            ((WeaponSystem::M224, AmmoKind::He, 1), table(10.0)),
+            ((WeaponSystem::M224, AmmoKind::He, 2), table(20.0)),
+        ]);
+        let mortar = MortarPosition::new("M1".into(), 100.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            100.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let mut solution = calculate_solution_with_dispersion(
+            &mortar,
+            &target,
+            &ballistics,
+            &DispersionTable::new(),
+        );
+        // Crête de 200 m au-dessus de la pièce à 500 m, le but lui-même ignoré
+        let profile: TerrainProfile = serde_json::from_value(serde_json::json!([
+            { "range_m": 200.0, "height_m": 150.0 },
+            { "range_m": 500.0, "height_m": 300.0 },
+            { "range_m": 1000.0, "height_m": 900.0 }
+        ]))
+        .unwrap();
+        assert!(profile.is_valid());
+
+        attach_crest_clearance(&mut solution, &mortar, &ballistics, &profile);
+        let clearance = solution.crest_clearance.as_ref().unwrap();
+        assert!(clearance.is_blocked());
+        assert_eq!(clearance.blocked.len(), 1);
+        let block = &clearance.blocked[0];
+        assert_eq!((block.ring.as_str(), block.range_m), ("1R", 500.0));
+        assert!((block.trajectory_height_m - (100.0 + 122.625)).abs() < 1e-9);
+        assert!((clearance.min_clearance_m.get(1).unwrap() + 77.375).abs() < 1e-9);
+        assert!((clearance.min_clearance_m.get(2).unwrap() - 263.92).abs() < 1e-9);
+        assert!(solution.warnings[0].contains("Crest at 500 m blocks the 1R"));
+
+        let mut open = calculate_solution_with_dispersion(
+            &mortar,
+            &target,
+            &ballistics,
+            &DispersionTable::new(),
+        );
+        attach_crest_clearance(&mut open, &mortar, &ballistics, &TerrainProfile::default());
+        assert!(open.crest_clearance.is_none());
+    }
+
+    #[test]
+    fn grayscale_png_heightmaps_are_georeferenced() {
+        // Image 16 bits 3 x 2, niveaux en décimètres au-dessus de 100 m
+        let mut png_bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png_bytes, 3, 2);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Sixteen);
+            let levels: [u16; 6] = [0, 100, 200, 1000, 1100, 1200];
+            let data: Vec<u8> = levels.iter().flat_map(|v| v.to_be_bytes()).collect();
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&data).unwrap();
+        }
+        let georef = TerrainGeoref::parse_str("500,2000,25,0.1,100").unwrap();
+        let terrain = TerrainModel::from_png(png_bytes.as_slice(), georef).unwrap();
+
+        assert_eq!(terrain.elevation_at(500.0, 2000.0), Some(100.0));
+        assert!((terrain.elevation_at(550.0, 1975.0).unwrap() - 220.0).abs() < 1e-9);
+        assert!((terrain.elevation_at(512.5, 1987.5).unwrap() - 155.0).abs() < 1e-9);
+        assert_eq!(terrain.elevation_at(499.0, 2000.0), None);
+        assert_eq!(terrain.elevation_at(500.0, 1970.0), None);
+
+        let summary = terrain.summary();
+        assert_eq!((summary.width, summary.height), (3, 2));
+        assert_eq!(summary.bounds, [500.0, 1975.0, 550.0, 2000.0]);
+        assert_eq!(summary.min_elevation_m, 100.0);
+        assert!((summary.max_elevation_m - 220.0).abs() < 1e-9);
+
+        // Image en couleur refusée
+        let mut rgb = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut rgb, 1, 1);
+            encoder.set_color(png::ColorType::Rgb);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[1, 2, 3]).unwrap();
+        }
+        assert!(TerrainModel::from_png(rgb.as_slice(), georef).is_err());
+        assert!(TerrainModel::from_png(&b"not a png"[..], georef).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Construction d'états et de serveurs de test (fonctionnalité `test-support`).
+//!
+//! [`AppStateBuilder`] assemble un [`AppState`] à partir de tables balistiques
+//! synthétiques (ou des données d'un répertoire) et de positions initiales,
+//! sans dupliquer la structure interne de l'état. [`TestServer`] sert
+//! l'application sur un port local libre pour les tests d'intégration.
+//!
+//! ```toml
+//! [dev-dependencies]
+//! mortar = { version = "0.1", features = ["test-support"] }
+//! ```
+
+use axum::Router;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::RwLock;
+
+use crate::server::{build_router, load_tables, AppOptions};
+use crate::{
+    AmmoKind, AppState, BallisticPoint, BallisticTable, BallisticTables, Dispersion, LoadReport,
+    Metrics, MortarPosition, Ring, TargetPosition, WeaponSystem,
+};
+
+/// Table balistique linéaire entre deux points `(portée m, élévation mils)`.
+///
+/// La table compte 11 points régulièrement espacés ; la durée de trajet est
+/// constante si elle est fournie.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::test_support::linear_table;
+///
+/// let table = linear_table((100.0, 1400.0), (1100.0, 900.0), Some(20.0));
+/// assert_eq!(table.elev_at(600.0), Some(1150.0));
+/// assert_eq!(table.time_of_flight_at(600.0), Some(20.0));
+/// ```
+pub fn linear_table(
+    min: (f64, f64),
+    max: (f64, f64),
+    time_of_flight_s: Option<f64>,
+) -> BallisticTable {
+    let points = (0..=10)
+        .map(|i| {
+            let f = f64::from(i) / 10.0;
+            BallisticPoint {
+                range_m: min.0 + (max.0 - min.0) * f,
+                elev_mil: min.1 + (max.1 - min.1) * f,
+                time_flight_s: time_of_flight_s,
+                delta_elev_per_100m_mil: None,
+            }
+        })
+        .collect();
+    BallisticTable {
+        points,
+        ..Default::default()
+    }
+}
+
+/// Constructeur d'[`AppState`] pour les tests.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::test_support::{linear_table, AppStateBuilder};
+/// use mortar::{AmmoKind, MortarPosition};
+///
+/// let state = AppStateBuilder::new()
+///     .ballistic_table(AmmoKind::He, 2, linear_table((100.0, 1400.0), (1100.0, 900.0), None))
+///     .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+///     .build();
+/// assert_eq!(state.ballistics.try_read().unwrap().len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct AppStateBuilder {
+    ballistics: BallisticTables,
+    metrics: Metrics,
+    options: AppOptions,
+    mortars: Vec<MortarPosition>,
+    targets: Vec<TargetPosition>,
+    data_path: Option<PathBuf>,
+    load_report: LoadReport,
+}
+
+impl AppStateBuilder {
+    /// État vide, sans table balistique.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Charge les tables et métriques d'un répertoire de données (comme le serveur).
+    pub fn from_data_dir(data_path: &str) -> Self {
+        let (ballistics, metrics, load_report) = load_tables(data_path, &AppOptions::default());
+        AppStateBuilder {
+            ballistics,
+            metrics,
+            data_path: Some(PathBuf::from(data_path)),
+            load_report,
+            ..Self::default()
+        }
+    }
+
+    /// Charge les tables du répertoire `data` du dépôt.
+    pub fn with_repo_data() -> Self {
+        Self::from_data_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/data"))
+    }
+
+    /// Ajoute ou remplace une table balistique du M224.
+    pub fn ballistic_table(self, ammo: AmmoKind, ring: Ring, table: BallisticTable) -> Self {
+        self.weapon_table(WeaponSystem::M224, ammo, ring, table)
+    }
+
+    /// Ajoute ou remplace une table balistique d'un système d'arme.
+    pub fn weapon_table(
+        mut self,
+        weapon: WeaponSystem,
+        ammo: AmmoKind,
+        ring: Ring,
+        table: BallisticTable,
+    ) -> Self {
+        self.ballistics.insert((weapon, ammo, ring), table);
+        self
+    }
+
+    /// Fixe la dispersion (m) d'un anneau.
+    pub fn dispersion(mut self, ammo: AmmoKind, ring: Ring, dispersion_m: f64) -> Self {
+        self.metrics
+            .dispersions
+            .insert((ammo, ring), Dispersion::circular(dispersion_m).into());
+        self
+    }
+
+    /// Remplace les métriques (dispersions, rayons d'effet...).
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Remplace les options de démarrage.
+    pub fn options(mut self, options: AppOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Mode spectateur.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.options.read_only = read_only;
+        self
+    }
+
+    /// Ajoute un mortier à l'état initial.
+    pub fn mortar(mut self, mortar: MortarPosition) -> Self {
+        self.mortars.push(mortar);
+        self
+    }
+
+    /// Ajoute une cible à l'état initial.
+    pub fn target(mut self, target: TargetPosition) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    /// Construit l'état.
+    pub fn build(self) -> Arc<AppState> {
+        let mut state = AppState::new(self.ballistics, self.metrics, &self.options);
+        state.mortars = RwLock::new(self.mortars);
+        state.targets = RwLock::new(self.targets);
+        state.data_path = self.data_path;
+        state.load_report = RwLock::new(self.load_report);
+        Arc::new(state)
+    }
+
+    /// Construit l'état et l'application (interface web servie depuis `web_path`).
+    pub fn build_app(self, web_path: &str) -> (Router, Arc<AppState>) {
+        let state = self.build();
+        (build_router(state.clone(), web_path), state)
+    }
+
+    /// Construit l'état et sert l'application sur un port local libre.
+    pub async fn spawn(self) -> TestServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind failed");
+        let base_url = format!(
+            "http://{}",
+            listener.local_addr().expect("no local address")
+        );
+        let (app, state) = self.build_app(concat!(env!("CARGO_MANIFEST_DIR"), "/src/web"));
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("server failed");
+        });
+        TestServer { base_url, state }
+    }
+}
+
+/// Application servie pour un test.
+pub struct TestServer {
+    /// URL de base, par exemple `http://127.0.0.1:40123`
+    pub base_url: String,
+    /// État partagé avec le serveur
+    pub state: Arc<AppState>,
+}
+
+impl TestServer {
+    /// URL complète d'un chemin de l'API.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
@@ -0,0 +1,584 @@
+//! Chronologie de la batterie.
+//!
+//! Fusionne en une seule liste datée les missions programmées (salves de tir
+//! d'efficacité à heure fixe), les coups déjà tirés de l'historique, les
+//! arrivées attendues des obus et les levées de tir, pour que le PC tir voie
+//! d'un coup d'œil les prochaines minutes.
+//!
+//! Les coups d'une mission sont planifiés comme une salve ([`volley_plan`]) :
+//! les tubes tirent ensemble, un évènement `fire` regroupe donc les coups
+//! partant au même instant. La levée (`lift`) suit la dernière arrivée, ou le
+//! dernier départ si la durée de trajet est inconnue.
+//!
+//! La fin de mission ([`end_of_mission`]) clôt une mission : elle en dresse le
+//! compte rendu (coups tirés, dernière correction, effet obtenu saisi par
+//! l'observateur), versé au journal des missions ([`MissionLog`]) avec la
+//! cible archivée.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::fire::{volley_plan, VolleyPlan, DEFAULT_ROUND_INTERVAL_S};
+use crate::replay::ShotEvent;
+use crate::{
+    BallisticTables, CorrectionRecord, Declination, FiringSolution, IncrementTable, MortarPosition,
+    Ring, SolutionRecord, TargetPosition,
+};
+
+/// Fenêtre par défaut de la chronologie (s à venir).
+pub const DEFAULT_TIMELINE_WINDOW_S: u64 = 600;
+
+/// Durée par défaut (s) des évènements passés conservés en tête de chronologie.
+pub const DEFAULT_TIMELINE_PAST_S: u64 = 60;
+
+fn default_tubes() -> u32 {
+    1
+}
+
+fn default_round_interval() -> f64 {
+    DEFAULT_ROUND_INTERVAL_S
+}
+
+/// Mission de tir programmée : salve d'un mortier sur une cible à heure fixe.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledMission {
+    /// Mortier tirant la mission
+    pub mortar: String,
+    /// Cible
+    pub target: String,
+    /// Heure du commandement « feu » (secondes Unix)
+    pub start: u64,
+    /// Nombre de tubes
+    #[serde(default = "default_tubes")]
+    pub tubes: u32,
+    /// Coups par tube
+    pub rounds_per_tube: u32,
+    /// Intervalle entre deux coups d'un même tube (s)
+    #[serde(default = "default_round_interval")]
+    pub round_interval_s: f64,
+    /// Anneau imposé, sinon choisi comme pour une salve
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ring: Option<Ring>,
+    /// Tir autorisé hors du secteur de tir du mortier
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub override_sector: bool,
+}
+
+/// Missions programmées de l'opération, par nom.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MissionSchedule(pub BTreeMap<String, ScheduledMission>);
+
+impl MissionSchedule {
+    /// Indique si aucune mission n'est programmée.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Nature d'un évènement de la chronologie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    /// Départ des coups
+    Fire,
+    /// Arrivée attendue des obus
+    Splash,
+    /// Levée du tir sur la cible
+    Lift,
+}
+
+impl std::fmt::Display for TimelineEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimelineEventKind::Fire => write!(f, "FEU"),
+            TimelineEventKind::Splash => write!(f, "IMPACT"),
+            TimelineEventKind::Lift => write!(f, "LEVEE"),
+        }
+    }
+}
+
+/// Évènement daté de la chronologie.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TimelineEvent {
+    /// Heure de l'évènement (secondes Unix)
+    pub time: f64,
+    /// Décalage par rapport à l'heure courante (s, négatif dans le passé)
+    pub offset_s: f64,
+    /// Nature de l'évènement
+    pub kind: TimelineEventKind,
+    /// Mission programmée, absente pour un coup déjà tiré hors mission
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mission: Option<String>,
+    /// Mortier
+    pub mortar: String,
+    /// Cible
+    pub target: String,
+    /// Munition
+    pub ammo_type: String,
+    /// Anneau
+    pub ring: Ring,
+    /// Nombre de coups concernés (0 pour une levée)
+    pub rounds: u32,
+}
+
+/// Mission programmée qui ne peut pas être planifiée.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MissionIssue {
+    /// Nom de la mission
+    pub mission: String,
+    /// Raison
+    pub error: String,
+}
+
+/// Chronologie fusionnée de la batterie.
+#[derive(Clone, Debug, Serialize)]
+pub struct Timeline {
+    /// Heure courante (secondes Unix)
+    pub now: u64,
+    /// Début de la fenêtre (secondes Unix)
+    pub from: u64,
+    /// Fin de la fenêtre (secondes Unix)
+    pub until: u64,
+    /// Évènements de la fenêtre, par ordre chronologique
+    pub events: Vec<TimelineEvent>,
+    /// Missions non planifiables (mortier ou cible absents, hors de portée...)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<MissionIssue>,
+}
+
+/// Planifie la salve d'une mission à partir de la solution complète de son
+/// mortier sur sa cible (voir [`volley_plan`]), avec les incréments et la
+/// déclinaison de l'opération.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si le mortier n'est pas prêt, si la cible est hors de
+/// son secteur de tir (sauf dérogation) ou si la salve ne peut pas être
+/// préparée.
+#[allow(clippy::too_many_arguments)]
+pub fn plan_mission(
+    mission: &ScheduledMission,
+    mortar: &MortarPosition,
+    target: &TargetPosition,
+    solution: &FiringSolution,
+    ballistics: &BallisticTables,
+    increments: &IncrementTable,
+    declination: Declination,
+) -> Result<VolleyPlan> {
+    mortar.check_ready()?;
+    if !mission.override_sector {
+        mortar.check_sector(target)?;
+    }
+    volley_plan(
+        solution,
+        mortar,
+        target,
+        ballistics,
+        increments,
+        mission.ring,
+        mission.tubes,
+        mission.rounds_per_tube,
+        mission.round_interval_s,
+        declination,
+    )
+}
+
+/// Salves planifiées des missions programmées, par nom de mission, ou
+/// raison pour laquelle la mission ne peut pas être planifiée.
+pub type MissionPlans = BTreeMap<String, std::result::Result<VolleyPlan, String>>;
+
+/// Construit la chronologie de `now - past_s` à `now + window_s`.
+///
+/// Les missions programmées de `schedule` sont placées d'après leur salve
+/// planifiée dans `plans` ; une mission non planifiable est reportée dans
+/// les problèmes, une mission absente de `plans` est ignorée. Les coups déjà
+/// tirés viennent de `shots`
+/// (voir [`crate::replay::shot_events`]). À instant égal, les départs
+/// précèdent les arrivées puis les levées.
+pub fn build_timeline(
+    schedule: &MissionSchedule,
+    plans: &MissionPlans,
+    shots: &[ShotEvent],
+    now: u64,
+    past_s: u64,
+    window_s: u64,
+) -> Timeline {
+    let mut events = Vec::new();
+    let mut issues = Vec::new();
+    let mut push = |time: f64,
+                    kind: TimelineEventKind,
+                    mission: Option<&str>,
+                    mortar: &str,
+                    target: &str,
+                    ammo_type: &str,
+                    ring: Ring,
+                    rounds: u32| {
+        events.push(TimelineEvent {
+            time,
+            offset_s: time - now as f64,
+            kind,
+            mission: mission.map(str::to_string),
+            mortar: mortar.to_string(),
+            target: target.to_string(),
+            ammo_type: ammo_type.to_string(),
+            ring,
+            rounds,
+        })
+    };
+
+    for (name, mission) in &schedule.0 {
+        let plan = match plans.get(name) {
+            Some(Ok(plan)) => plan,
+            Some(Err(error)) => {
+                issues.push(MissionIssue {
+                    mission: name.clone(),
+                    error: error.clone(),
+                });
+                continue;
+            }
+            None => continue,
+        };
+
+        let start = mission.start as f64;
+        let ring = plan.expenditure.ring;
+        let mut lift = start;
+        // Les tubes tirent ensemble : un évènement par coup de la salve
+        for round in plan.rounds.iter().filter(|r| r.tube == 1) {
+            let fire = start + round.fire_time_s;
+            push(
+                fire,
+                TimelineEventKind::Fire,
+                Some(name),
+                &plan.mortar,
+                &plan.target,
+                &plan.ammo_type,
+                ring,
+                plan.tubes,
+            );
+            lift = lift.max(fire);
+            if let Some(impact) = round.impact_time_s {
+                push(
+                    start + impact,
+                    TimelineEventKind::Splash,
+                    Some(name),
+                    &plan.mortar,
+                    &plan.target,
+                    &plan.ammo_type,
+                    ring,
+                    plan.tubes,
+                );
+                lift = lift.max(start + impact);
+            }
+        }
+        push(
+            lift,
+            TimelineEventKind::Lift,
+            Some(name),
+            &plan.mortar,
+            &plan.target,
+            &plan.ammo_type,
+            ring,
+            0,
+        );
+    }
+
+    for shot in shots {
+        let fire = shot.timestamp as f64;
+        let mortar = shot.shot.mortar.name.as_str();
+        push(
+            fire,
+            TimelineEventKind::Fire,
+            None,
+            mortar,
+            &shot.target,
+            &shot.shot.ammo_type,
+            shot.shot.ring,
+            1,
+        );
+        if let Some(tof) = shot.shot.time_of_flight_s {
+            push(
+                fire + tof,
+                TimelineEventKind::Splash,
+                None,
+                mortar,
+                &shot.target,
+                &shot.shot.ammo_type,
+                shot.shot.ring,
+                1,
+            );
+        }
+    }
+
+    let from = now.saturating_sub(past_s);
+    let until = now.saturating_add(window_s);
+    events.retain(|e| e.time >= from as f64 && e.time <= until as f64);
+    events.sort_by(|a, b| a.time.total_cmp(&b.time).then(a.kind.cmp(&b.kind)));
+    Timeline {
+        now,
+        from,
+        until,
+        events,
+        issues,
+    }
+}
+
+/// Compte rendu de fin de mission.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EndOfMission {
+    /// Nom de la mission
+    pub mission: String,
+    /// Mortier ayant tiré la mission
+    pub mortar: String,
+    /// Heure de clôture (secondes Unix)
+    pub closed_at: u64,
+    /// Coups de réglage tirés sur la cible
+    pub adjusting_rounds: u32,
+    /// Coups du tir d'efficacité (0 si la salve n'a pas encore été déclenchée)
+    pub effect_rounds: u32,
+    /// Total des coups tirés
+    pub rounds_fired: u32,
+    /// Dernière correction appliquée à la cible
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_correction: Option<CorrectionRecord>,
+    /// Effet obtenu, saisi par l'observateur (ex: "neutralise")
+    pub effect: String,
+    /// Remarques libres
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<String>,
+    /// Cible archivée, avec son historique de corrections
+    pub target: TargetPosition,
+}
+
+/// Journal des missions closes, de la plus ancienne à la plus récente.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MissionLog(pub Vec<EndOfMission>);
+
+impl MissionLog {
+    /// Indique si aucune mission n'a été close.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Dresse le compte rendu de fin de la mission `name`.
+///
+/// Les coups de réglage sont les coups tirés de l'historique de la cible
+/// (`history`) ; les coups d'efficacité sont ceux de la salve programmée, comptés
+/// seulement si son heure de feu est passée à `now`.
+pub fn end_of_mission(
+    name: &str,
+    mission: &ScheduledMission,
+    target: TargetPosition,
+    history: &[SolutionRecord],
+    effect: String,
+    remarks: Option<String>,
+    now: u64,
+) -> EndOfMission {
+    let adjusting_rounds = history.iter().filter(|r| r.fired).count() as u32;
+    let effect_rounds = if mission.start <= now {
+        mission.tubes.saturating_mul(mission.rounds_per_tube)
+    } else {
+        0
+    };
+    EndOfMission {
+        mission: name.to_string(),
+        mortar: mission.mortar.clone(),
+        closed_at: now,
+        adjusting_rounds,
+        effect_rounds,
+        rounds_fired: adjusting_rounds + effect_rounds,
+        final_correction: target.corrections.last().cloned(),
+        effect,
+        remarks,
+        target,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::ShotTrace;
+    use crate::{AmmoKind, BallisticPoint, BallisticTable, Position, TargetType, WeaponSystem};
+
+    #[test]
+    fn missions_and_fired_shots_are_merged_in_order() {
+        let point = |range_m, elev_mil| BallisticPoint {
+            range_m,
+            elev_mil,
+            time_flight_s: Some(20.0),
+            delta_elev_per_100m_mil: None,
+        };
+        let table = BallisticTable {
+            points: vec![point(0.0, 1600.0), point(2000.0, 800.0)],
+            ..Default::default()
+        };
+        let ballistics = BallisticTables::from([((WeaponSystem::M224, AmmoKind::He, 2), table)]);
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        let now = 1_000_000;
+        let schedule = MissionSchedule(BTreeMap::from([
+            (
+                "FEU1".to_string(),
+                ScheduledMission {
+                    mortar: "M1".into(),
+                    target: "T1".into(),
+                    start: now + 30,
+                    tubes: 2,
+                    rounds_per_tube: 2,
+                    round_interval_s: 10.0,
+                    ring: None,
+                    override_sector: false,
+                },
+            ),
+            (
+                "FEU2".to_string(),
+                ScheduledMission {
+                    mortar: "M9".into(),
+                    target: "T1".into(),
+                    start: now,
+                    tubes: 1,
+                    rounds_per_tube: 1,
+                    round_interval_s: 5.0,
+                    ring: None,
+                    override_sector: false,
+                },
+            ),
+        ]));
+        let shots = vec![ShotEvent {
+            timestamp: now - 10,
+            target: "T1".into(),
+            shot: ShotTrace {
+                mortar: Position::new("M1".into(), 0.0, 0.0, 0.0),
+                impact: Position::new("T1".into(), 0.0, 0.0, 1000.0),
+                ammo_type: "HE".into(),
+                ring: 2,
+                time_of_flight_s: Some(20.0),
+            },
+        }];
+
+        let feu1 = &schedule.0["FEU1"];
+        let solution = crate::calculate_solution(&mortar, &target, &ballistics);
+        let plan = plan_mission(
+            feu1,
+            &mortar,
+            &target,
+            &solution,
+            &ballistics,
+            &IncrementTable::new(),
+            Declination::default(),
+        );
+        let plans = MissionPlans::from([
+            ("FEU1".to_string(), plan.map_err(|e| e.to_string())),
+            ("FEU2".to_string(), Err("Mortar 'M9' not found".to_string())),
+        ]);
+        let timeline = build_timeline(
+            &schedule,
+            &plans,
+            &shots,
+            now,
+            DEFAULT_TIMELINE_PAST_S,
+            DEFAULT_TIMELINE_WINDOW_S,
+        );
+        let summary: Vec<(f64, TimelineEventKind, u32)> = timeline
+            .events
+            .iter()
+            .map(|e| (e.offset_s, e.kind, e.rounds))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (-10.0, TimelineEventKind::Fire, 1),
+                (10.0, TimelineEventKind::Splash, 1),
+                (30.0, TimelineEventKind::Fire, 2),
+                (40.0, TimelineEventKind::Fire, 2),
+                (50.0, TimelineEventKind::Splash, 2),
+                (60.0, TimelineEventKind::Splash, 2),
+                (60.0, TimelineEventKind::Lift, 0),
+            ]
+        );
+        assert_eq!(timeline.events[2].mission.as_deref(), Some("FEU1"));
+        assert!(timeline.events[0].mission.is_none());
+        assert_eq!(timeline.issues.len(), 1);
+        assert_eq!(timeline.issues[0].mission, "FEU2");
+
+        // Fenêtre de 45 s : la fin de la mission en est exclue
+        let short = build_timeline(&schedule, &plans, &shots, now, 0, 45);
+        assert_eq!(short.events.len(), 3);
+        assert_eq!(short.until, now + 45);
+    }
+
+    #[test]
+    fn end_of_mission_counts_adjusting_and_effect_rounds() {
+        let mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+        let mut target = TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        );
+        target.corrections.push(CorrectionRecord {
+            vertical_m: 20.0,
+            horizontal_m: -10.0,
+            from_x: 0.0,
+            from_y: 1000.0,
+            to_x: 10.0,
+            to_y: 1020.0,
+        });
+        let solution = crate::calculate_solution(&mortar, &target, &BallisticTables::new());
+        let record = |fired| SolutionRecord {
+            timestamp: 0,
+            mortar: "M1".into(),
+            fired,
+            deviation: None,
+            solution: solution.clone(),
+            shot: None,
+        };
+        let history = vec![record(true), record(false), record(true)];
+        let mission = ScheduledMission {
+            mortar: "M1".into(),
+            target: "T1".into(),
+            start: 100,
+            tubes: 2,
+            rounds_per_tube: 3,
+            round_interval_s: 5.0,
+            ring: None,
+            override_sector: false,
+        };
+
+        let report = end_of_mission(
+            "FEU1",
+            &mission,
+            target.clone(),
+            &history,
+            "neutralise".into(),
+            None,
+            200,
+        );
+        assert_eq!(
+            (
+                report.adjusting_rounds,
+                report.effect_rounds,
+                report.rounds_fired
+            ),
+            (2, 6, 8)
+        );
+        assert_eq!(report.final_correction.as_ref().unwrap().to_y, 1020.0);
+        assert_eq!(report.target.name, "T1");
+
+        // Clôturée avant l'heure de feu : la salve n'est pas comptée
+        let early = end_of_mission("FEU1", &mission, target, &[], "annule".into(), None, 50);
+        assert_eq!(early.rounds_fired, 0);
+    }
+}
@@ -0,0 +1,138 @@
+//! Trajectoire échantillonnée d'un obus.
+//!
+//! Comme pour la déconfliction de l'espace aérien ([`crate::airspace`]), la
+//! trajectoire est estimée dans le vide à partir de la durée de trajet de la
+//! table (ou, à défaut, de l'élévation) : une parabole de la pièce au but,
+//! décrite par des points `(distance, hauteur)` que l'interface web trace et
+//! que l'utilisateur compare aux obstacles (crêtes, bâtiments, couvert).
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use crate::airspace::{apex_height_m, vacuum_time_of_flight_s};
+use crate::replay::GRAVITY_MPS2;
+use crate::{AmmoKind, BallisticTable, Ring};
+
+/// Pas d'échantillonnage par défaut, en mètres.
+pub const DEFAULT_TRAJECTORY_STEP_M: f64 = 10.0;
+
+/// Point de la trajectoire.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct TrajectoryPoint {
+    /// Distance horizontale depuis la pièce (mètres)
+    pub x_m: f64,
+    /// Hauteur au-dessus de la pièce (mètres)
+    pub height_m: f64,
+    /// Temps écoulé depuis le départ du coup (s)
+    pub time_s: f64,
+}
+
+/// Trajectoire d'un anneau à une portée donnée.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Trajectory {
+    pub ammo_type: AmmoKind,
+    pub ring: Ring,
+    /// Portée de tir (mètres)
+    pub range_m: f64,
+    /// Dénivelé du but par rapport à la pièce (mètres, positif si plus haut)
+    pub rise_m: f64,
+    /// Élévation lue dans la table (mils)
+    pub elev_mil: f64,
+    /// Durée de trajet (s)
+    pub time_of_flight_s: f64,
+    /// `true` si la durée de trajet est déduite de l'élévation, faute de table
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub estimated_time_of_flight: bool,
+    /// Hauteur maximale au-dessus de la pièce (mètres)
+    pub max_ordinate_m: f64,
+    /// Points de la pièce au but, tous les `step_m` mètres, but inclus
+    pub points: Vec<TrajectoryPoint>,
+}
+
+/// Échantillonne la trajectoire d'un tir à `range_m` mètres avec `table`.
+///
+/// # Erreurs
+///
+/// Retourne une erreur si `step_m` n'est pas un nombre strictement positif,
+/// si la portée ou le dénivelé ne sont pas finis, ou si la portée est hors de
+/// la table.
+///
+/// # Exemple
+///
+/// ```
+/// use mortar::trajectory::trajectory_points;
+/// use mortar::{AmmoKind, BallisticPoint, BallisticTable};
+///
+/// let point = |range_m, elev_mil| BallisticPoint {
+///     range_m,
+///     elev_mil,
+///     time_flight_s: Some(20.0),
+///     delta_elev_per_100m_mil: None,
+/// };
+/// let table = BallisticTable {
+///     points: vec![point(100.0, 1400.0), point(1000.0, 1000.0)],
+///     ..Default::default()
+/// };
+/// let t = trajectory_points(&table, AmmoKind::He, 2, 600.0, 0.0, 250.0).unwrap();
+/// let xs: Vec<f64> = t.points.iter().map(|p| p.x_m).collect();
+/// assert_eq!(xs, vec![0.0, 250.0, 500.0, 600.0]);
+/// // Flèche g·T²/8 à mi-parcours
+/// assert!((t.max_ordinate_m - 490.5).abs() < 1e-9);
+/// assert_eq!(t.points[3].height_m, 0.0);
+/// ```
+pub fn trajectory_points(
+    table: &BallisticTable,
+    ammo_type: AmmoKind,
+    ring: Ring,
+    range_m: f64,
+    rise_m: f64,
+    step_m: f64,
+) -> Result<Trajectory> {
+    if !step_m.is_finite() || step_m <= 0.0 {
+        bail!("invalid step {} m", step_m);
+    }
+    if !range_m.is_finite() || range_m < 0.0 || !rise_m.is_finite() {
+        bail!("invalid range {} m or rise {} m", range_m, rise_m);
+    }
+    let elev_mil = match table.elev_at(range_m) {
+        Some(e) => e,
+        None => bail!(
+            "{:.0} m is out of range of the {} {}R table",
+            range_m,
+            ammo_type,
+            ring
+        ),
+    };
+    let (time_of_flight_s, estimated_time_of_flight) = match table.time_of_flight_at(range_m) {
+        Some(t) => (t, false),
+        None => (vacuum_time_of_flight_s(range_m, elev_mil), true),
+    };
+
+    let height_at = |tau: f64| {
+        let t = tau * time_of_flight_s;
+        TrajectoryPoint {
+            x_m: tau * range_m,
+            height_m: rise_m * tau + 0.5 * GRAVITY_MPS2 * t * (time_of_flight_s - t),
+            time_s: t,
+        }
+    };
+    let steps = (range_m / step_m).ceil() as usize;
+    let points = (0..=steps)
+        .map(|k| {
+            let x = (k as f64 * step_m).min(range_m);
+            height_at(if range_m > 0.0 { x / range_m } else { 1.0 })
+        })
+        .collect();
+
+    Ok(Trajectory {
+        ammo_type,
+        ring,
+        range_m,
+        rise_m,
+        elev_mil,
+        time_of_flight_s,
+        estimated_time_of_flight,
+        max_ordinate_m: apex_height_m(time_of_flight_s, rise_m),
+        points,
+    })
+}
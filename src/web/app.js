@@ -1,6 +1,7 @@
 // State
 let mortars = [];
 let targets = [];
+let friendlies = [];
 let selectedMortar = null;
 let selectedTarget = null;
 
@@ -8,10 +9,14 @@ document.addEventListener('DOMContentLoaded', () => {
     // Load initial data
     loadMortars();
     loadTargets();
+    loadFriendlies();
+    checkReadOnly();
+    loadSignConvention();
 
     // Event listeners
     document.getElementById('add-mortar-btn').addEventListener('click', addMortar);
     document.getElementById('add-target-btn').addEventListener('click', addTarget);
+    document.getElementById('add-friendly-btn').addEventListener('click', addFriendly);
     document.getElementById('calculate-btn').addEventListener('click', calculate);
     document.getElementById('apply-correction-btn').addEventListener('click', applyCorrection);
 
@@ -38,11 +43,39 @@ document.addEventListener('DOMContentLoaded', () => {
             if (e.key === 'Enter') addTarget();
         });
     });
+
+    document.querySelectorAll('#friendly-name, #friendly-elevation, #friendly-x, #friendly-y').forEach(input => {
+        input.addEventListener('keypress', (e) => {
+            if (e.key === 'Enter') addFriendly();
+        });
+    });
 });
 
 // =====================
 // API calls
 // =====================
+async function checkReadOnly() {
+    try {
+        const response = await fetch('/api/health');
+        const data = await response.json();
+        document.body.classList.toggle('read-only', data.read_only === true);
+    } catch (error) {
+        console.error('Failed to load server status:', error);
+    }
+}
+
+async function loadSignConvention() {
+    try {
+        const response = await fetch('/api/settings/sign-convention');
+        const data = await response.json();
+        const [vertical, horizontal] = data.label.split(' ');
+        document.getElementById('hint-vertical').textContent = vertical.replace('/', ' / ');
+        document.getElementById('hint-horizontal').textContent = horizontal.replace('/', ' / ');
+    } catch (error) {
+        console.error('Failed to load sign convention:', error);
+    }
+}
+
 async function loadMortars() {
     try {
         const response = await fetch('/api/mortars');
@@ -67,9 +100,26 @@ async function loadTargets() {
     }
 }
 
+async function loadFriendlies() {
+    try {
+        const response = await fetch('/api/friendlies');
+        const data = await response.json();
+        friendlies = data.positions || [];
+        renderFriendliesList();
+    } catch (error) {
+        console.error('Failed to load friendlies:', error);
+    }
+}
+
+// Empty elevation: left to the server's elevation fallback
+function parseElevation(value) {
+    const elevation = parseFloat(value);
+    return Number.isFinite(elevation) ? elevation : null;
+}
+
 async function addMortar() {
     const name = document.getElementById('mortar-name').value.trim();
-    const elevation = parseFloat(document.getElementById('mortar-elevation').value) || 0;
+    const elevation = parseElevation(document.getElementById('mortar-elevation').value);
     const x = parseFloat(document.getElementById('mortar-x').value) || 0;
     const y = parseFloat(document.getElementById('mortar-y').value) || 0;
 
@@ -123,6 +173,58 @@ async function deleteMortar(name) {
     }
 }
 
+async function addFriendly() {
+    const name = document.getElementById('friendly-name').value.trim();
+    const elevation = parseElevation(document.getElementById('friendly-elevation').value);
+    const x = parseFloat(document.getElementById('friendly-x').value) || 0;
+    const y = parseFloat(document.getElementById('friendly-y').value) || 0;
+
+    if (!name) {
+        showToast("Le nom de la position amie est requis", 'error');
+        return;
+    }
+
+    try {
+        const response = await fetch('/api/friendlies', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ name, elevation, x, y })
+        });
+
+        const data = await response.json();
+
+        if (response.ok) {
+            showToast(`Ami '${name}' ajoute`, 'success');
+            document.getElementById('friendly-name').value = '';
+            document.getElementById('friendly-elevation').value = '0';
+            document.getElementById('friendly-x').value = '0';
+            document.getElementById('friendly-y').value = '0';
+            loadFriendlies();
+        } else {
+            showToast(data.error || 'Erreur', 'error');
+        }
+    } catch (error) {
+        showToast('Erreur de connexion', 'error');
+    }
+}
+
+async function deleteFriendly(name) {
+    try {
+        const response = await fetch('/api/friendlies', {
+            method: 'DELETE',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ name })
+        });
+
+        if (response.ok) {
+            showToast(`Ami '${name}' supprime`, 'success');
+            loadFriendlies();
+        }
+    } catch (error) {
+        showToast('Erreur de connexion', 'error');
+    }
+}
+
 async function updateTargetAmmo(name, ammo_type) {
     try {
         const response = await fetch('/api/targets/ammo', {
@@ -141,18 +243,14 @@ async function updateTargetAmmo(name, ammo_type) {
 }
 
 async function addTarget() {
-    const name = document.getElementById('target-name').value.trim();
-    const elevation = parseFloat(document.getElementById('target-elevation').value) || 0;
+    // Nom vide: numero de cible attribue par le serveur
+    const name = document.getElementById('target-name').value.trim() || null;
+    const elevation = parseElevation(document.getElementById('target-elevation').value);
     const x = parseFloat(document.getElementById('target-x').value) || 0;
     const y = parseFloat(document.getElementById('target-y').value) || 0;
     const target_type = document.getElementById('target-type').value;
     const ammo_type = document.getElementById('target-ammo').value;
 
-    if (!name) {
-        showToast('Le nom de la cible est requis', 'error');
-        return;
-    }
-
     try {
         const response = await fetch('/api/targets', {
             method: 'POST',
@@ -163,7 +261,7 @@ async function addTarget() {
         const data = await response.json();
 
         if (response.ok) {
-            showToast(`Cible '${name}' ajoutee [${target_type}] [${ammo_type}]`, 'success');
+            showToast(`${data.message} [${ammo_type}]`, 'success');
             document.getElementById('target-name').value = '';
             document.getElementById('target-elevation').value = '0';
             document.getElementById('target-x').value = '0';
@@ -354,6 +452,36 @@ function renderMortarsList() {
     }
 }
 
+function renderFriendliesList() {
+    const list = document.getElementById('friendlies-list');
+    list.innerHTML = '';
+
+    if (friendlies.length === 0) {
+        list.innerHTML = '<li class="empty-message">Aucune position amie</li>';
+        return;
+    }
+
+    for (const friendly of friendlies) {
+        const li = document.createElement('li');
+        li.innerHTML = `
+            <div class="position-info" data-name="${friendly.name}">
+                <span class="position-name">${friendly.name}</span>
+                <span class="position-coords">X:${friendly.x} Y:${friendly.y} E:${friendly.elevation}m</span>
+            </div>
+            <div class="item-actions">
+                <button class="btn-delete" data-name="${friendly.name}">X</button>
+            </div>
+        `;
+
+        li.querySelector('.btn-delete').addEventListener('click', (e) => {
+            e.stopPropagation();
+            deleteFriendly(friendly.name);
+        });
+
+        list.appendChild(li);
+    }
+}
+
 function renderTargetsList() {
     const list = document.getElementById('targets-list');
     list.innerHTML = '';
@@ -469,6 +597,23 @@ function displayResults(data, mortarName, targetName) {
     document.getElementById('solution-mortar').textContent = mortarName;
     document.getElementById('solution-target').textContent = targetName;
 
+    // Friendlies inside the danger radius
+    const dangerClose = document.getElementById('danger-close');
+    const endangered = data.danger_close ? data.danger_close.friendlies : [];
+    if (endangered.length > 0) {
+        const lines = endangered.map(f => `${f.name} a ${f.distance_m.toFixed(0)} m (${f.rings.join(' ')})`);
+        const safer = data.danger_close.safer_fire;
+        if (safer) {
+            lines.push(`Conseil : ${safer.ammo_type} ${safer.ring}`);
+        }
+        dangerClose.textContent = `Tir rapproche : ${lines.join(' | ')}`;
+        dangerClose.classList.remove('hidden');
+    } else {
+        dangerClose.classList.add('hidden');
+    }
+
+    renderEffectOverlay(data, targetName);
+
     // Update metrics
     document.getElementById('distance').textContent = data.distance_m.toFixed(1);
     document.getElementById('azimuth').textContent = data.azimuth_deg.toFixed(1);
@@ -489,12 +634,24 @@ function displayResults(data, mortarName, targetName) {
         for (const ring of rings) {
             const elev = data.selected_solution.elevations[ring];
             const disp = data.selected_solution.dispersions ? data.selected_solution.dispersions[ring] : null;
+            const perMil = data.selected_solution.range_m_per_mil ? data.selected_solution.range_m_per_mil[ring] : null;
+            const per100 = data.selected_solution.elev_mil_per_100m ? data.selected_solution.elev_mil_per_100m[ring] : null;
             const card = document.createElement('div');
             card.className = 'elevation-card';
+            if (ring === data.selected_solution.default_ring) {
+                card.classList.add('default-ring');
+            }
+            const cmp = data.selected_solution.ring_comparison;
+            if (cmp && ring === cmp.recommended_ring) {
+                card.classList.add('recommended-ring');
+                card.title = `Anneau conseille : ${cmp.reason}`;
+            }
             card.innerHTML = `
                 <div class="ring">${ring}</div>
                 <div class="value ${elev === null ? 'na' : ''}">${elev !== null ? elev.toFixed(1) : 'N/A'}</div>
                 <div class="dispersion ${disp === null ? 'na' : ''}">±${disp !== null ? disp.toFixed(1) : '--'}m</div>
+                <div class="per-mil" title="Portee pour 1 mil d'elevation">${perMil != null ? perMil.toFixed(1) + 'm/mil' : ''}</div>
+                <div class="per-mil" title="Elevation a retirer pour allonger de 100 m">${per100 != null ? per100.toFixed(0) + ' mil/100m' : ''}</div>
             `;
             cardsContainer.appendChild(card);
         }
@@ -544,6 +701,91 @@ function displayResults(data, mortarName, targetName) {
     resultsSection.classList.remove('hidden');
 }
 
+// Target-centred overlay (North up): dispersion, effect radius and danger
+// radius of the planned ring, with the friendlies around the target.
+function renderEffectOverlay(data, targetName) {
+    const overlay = document.getElementById('effect-overlay');
+    const svg = document.getElementById('effect-overlay-map');
+    const legend = document.getElementById('effect-overlay-legend');
+    const selected = data.selected_solution;
+    const target = targets.find(t => t.name === targetName);
+    if (!selected || !target) {
+        overlay.classList.add('hidden');
+        return;
+    }
+
+    const ring = selected.default_ring
+        || (selected.ring_comparison && selected.ring_comparison.recommended_ring)
+        || ['0R', '1R', '2R', '3R', '4R'].find(r => selected.elevations[r] != null);
+    const dispersion = ring && selected.dispersions ? selected.dispersions[ring] : null;
+    const effect = selected.effect_radius_m;
+    const multiple = data.danger_close ? data.danger_close.multiple : 3;
+    const casualty = data.danger_close ? data.danger_close.casualty_radius_m : null;
+    let danger = data.danger_close && ring ? data.danger_close.danger_radius_m[ring] : null;
+    if (danger == null && dispersion != null) {
+        danger = dispersion * multiple + (casualty || 0);
+    }
+    if (dispersion == null && effect == null) {
+        overlay.classList.add('hidden');
+        return;
+    }
+
+    const size = 240;
+    const centre = size / 2;
+    const nearby = friendlies
+        .map(f => ({ name: f.name, dx: f.x - target.x, dy: f.y - target.y }))
+        .map(f => ({ ...f, distance: Math.hypot(f.dx, f.dy) }));
+    const extent = Math.max(danger || 0, effect || 0, dispersion || 0, 1) * 1.5;
+    const scale = (centre - 10) / extent;
+    const ns = 'http://www.w3.org/2000/svg';
+    const add = (tag, attrs, text) => {
+        const el = document.createElementNS(ns, tag);
+        for (const [key, value] of Object.entries(attrs)) {
+            el.setAttribute(key, value);
+        }
+        if (text) {
+            el.textContent = text;
+        }
+        svg.appendChild(el);
+    };
+
+    svg.innerHTML = '';
+    add('text', { x: centre, y: 14, 'text-anchor': 'middle', 'font-weight': 'bold', 'font-size': 12 }, 'N');
+    if (danger != null) {
+        add('circle', { cx: centre, cy: centre, r: danger * scale, fill: 'none', stroke: '#8b0000', 'stroke-dasharray': '5 3', 'stroke-width': 2 });
+    }
+    if (effect != null) {
+        add('circle', { cx: centre, cy: centre, r: effect * scale, fill: 'rgba(139, 105, 20, 0.25)', stroke: '#8b6914' });
+    }
+    if (dispersion != null) {
+        add('circle', { cx: centre, cy: centre, r: dispersion * scale, fill: 'none', stroke: '#556b2f', 'stroke-width': 2 });
+    }
+    add('path', { d: `M${centre - 6} ${centre - 6}l12 12m0 -12l-12 12`, stroke: '#b22222', 'stroke-width': 2 });
+    for (const f of nearby.filter(f => f.distance <= extent)) {
+        const x = centre + f.dx * scale;
+        const y = centre - f.dy * scale;
+        const inside = danger != null && f.distance <= danger;
+        add('circle', { cx: x, cy: y, r: 4, fill: inside ? '#8b0000' : '#1e3a5f' });
+        add('text', { x: x + 6, y: y + 4, 'font-size': 10 }, f.name);
+    }
+
+    const lines = [`Anneau ${ring || '--'} | echelle ${extent.toFixed(0)} m`];
+    if (dispersion != null) {
+        lines.push(`Dispersion : ${dispersion.toFixed(0)} m`);
+    }
+    lines.push(`Rayon d'effet : ${effect != null ? effect.toFixed(0) + ' m' : 'inconnu'}`);
+    if (danger != null) {
+        lines.push(`Rayon de danger : ${danger.toFixed(0)} m (${multiple} x dispersion${casualty != null ? ' + ' + casualty.toFixed(0) + ' m letal' : ''})`);
+    }
+    legend.innerHTML = '';
+    for (const line of lines) {
+        const div = document.createElement('div');
+        div.textContent = line;
+        legend.appendChild(div);
+    }
+    overlay.classList.remove('hidden');
+}
+
 function showError(message) {
     const errorSection = document.getElementById('error');
     errorSection.textContent = message;
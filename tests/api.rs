@@ -1,37 +1,17 @@
+use mortar::test_support::{linear_table, AppStateBuilder};
+use mortar::{AmmoKind, MortarPosition, TargetPosition, TargetType};
 use reqwest::Client;
 use serde_json::Value;
-use tokio::net::TcpListener;
 
 struct TestApp {
     base_url: String,
     client: Client,
 }
 
-fn repo_paths() -> (String, String) {
-    // Make paths robust in CI and locally
-    let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    let data = root.join("data");
-    let web = root.join("src").join("web");
-    (
-        data.to_string_lossy().to_string(),
-        web.to_string_lossy().to_string(),
-    )
-}
-
 async fn spawn_app() -> TestApp {
-    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind failed");
-    let port = listener.local_addr().unwrap().port();
-    let base_url = format!("http://127.0.0.1:{port}");
-
-    let (data_path, web_path) = repo_paths();
-    let app = mortar::server::build_app(&data_path, &web_path);
-
-    tokio::spawn(async move {
-        axum::serve(listener, app).await.expect("server failed");
-    });
-
+    let server = AppStateBuilder::with_repo_data().spawn().await;
     TestApp {
-        base_url,
+        base_url: server.base_url,
         client: Client::new(),
     }
 }
@@ -52,6 +32,8 @@ async fn health_ok() {
     let body: Value = res.json().await.unwrap();
     assert_eq!(body["status"], "ok");
     assert!(!body["version"].as_str().unwrap_or("").is_empty());
+    assert!(body["ballistic_files"]["loaded"].as_u64().unwrap() > 0);
+    assert_eq!(body["ballistic_files"]["failed"], serde_json::json!([]));
 }
 
 #[derive(serde::Serialize)]
@@ -149,6 +131,12 @@ async fn full_happy_path_returns_firing_solution_json() {
         body.get("selected_solution").is_some(),
         "expected selected_solution key to exist"
     );
+    let per_100m = body["selected_solution"]["elev_mil_per_100m"]
+        .as_object()
+        .unwrap();
+    assert!(per_100m
+        .values()
+        .any(|v| v.as_f64().is_some_and(|d| d > 0.0)));
 }
 
 #[tokio::test]
@@ -204,3 +192,3998 @@ async fn web_assets_are_served() {
         .unwrap();
     assert!(res.status().is_success());
 }
+
+#[tokio::test]
+async fn in_place_correction_moves_original_target() {
+    let app = spawn_app().await;
+
+    let res = app
+        .client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+
+    let res = app
+        .client
+        .post(format!("{}/api/targets/correct", app.base_url))
+        .json(&serde_json::json!({
+            "target_name": "T1",
+            "vertical_m": -50.0,
+            "horizontal_m": 30.0,
+            "in_place": true
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["corrected"], "T1");
+
+    let body: Value = app
+        .client
+        .get(format!("{}/api/targets", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let positions = body["positions"].as_array().unwrap();
+    assert_eq!(positions.len(), 1, "no _C copy should be created");
+    assert_eq!(positions[0]["x"].as_f64(), Some(470.0));
+    assert_eq!(positions[0]["y"].as_f64(), Some(350.0));
+    assert_eq!(positions[0]["corrections"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn correction_preview_is_applied_once_by_its_token() {
+    let app = spawn_app().await;
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    let correct = |preview: bool, body: Value| {
+        app.client
+            .post(format!(
+                "{}/api/targets/correct?preview={}",
+                app.base_url, preview
+            ))
+            .json(&body)
+            .send()
+    };
+    let target_x = || async {
+        let body: Value = app
+            .client
+            .get(format!("{}/api/targets", app.base_url))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body["positions"][0]["x"].as_f64()
+    };
+
+    let res = correct(
+        true,
+        serde_json::json!({
+            "target_name": "T1",
+            "vertical_m": -50.0,
+            "horizontal_m": 30.0,
+            "in_place": true,
+            "mortar_name": "M1"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 200);
+    let preview: Value = res.json().await.unwrap();
+    assert_eq!(preview["preview"], true);
+    assert_eq!(preview["correction_applied"]["new_x"].as_f64(), Some(470.0));
+    let distance = preview["solution"]["distance_m"].as_f64().unwrap();
+    assert!((distance - 470f64.hypot(350.0)).abs() < 1e-6);
+    let token = preview["confirmation_token"].as_str().unwrap().to_string();
+    assert_eq!(target_x().await, Some(500.0), "a preview applies nothing");
+
+    let confirm = serde_json::json!({ "target_name": "T1", "confirmation_token": token });
+    let res = correct(false, confirm.clone()).await.unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert!(body.get("preview").is_none());
+    assert_eq!(target_x().await, Some(470.0));
+
+    // Requête rejouée : pas de seconde correction
+    let res = correct(false, confirm).await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::CONFLICT);
+    assert_eq!(target_x().await, Some(470.0));
+
+    // Cible déplacée entre l'aperçu et la confirmation
+    let preview: Value = correct(
+        true,
+        serde_json::json!({ "target_name": "T1", "vertical_m": 0.0, "horizontal_m": 10.0, "in_place": true }),
+    )
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    correct(
+        false,
+        serde_json::json!({ "target_name": "T1", "vertical_m": 0.0, "horizontal_m": 10.0, "in_place": true }),
+    )
+    .await
+    .unwrap();
+    // Jeton de 128 bits tiré de l'aléa du système, distinct à chaque aperçu
+    let second = preview["confirmation_token"].as_str().unwrap();
+    assert_eq!(second.len(), 32);
+    assert!(second.bytes().all(|c| c.is_ascii_hexdigit()));
+    assert_ne!(second, token);
+    let res = correct(
+        false,
+        serde_json::json!({ "target_name": "T1", "confirmation_token": preview["confirmation_token"] }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::CONFLICT);
+    assert_eq!(target_x().await, Some(460.0));
+}
+
+#[tokio::test]
+async fn solutions_are_recorded_per_target() {
+    let app = spawn_app().await;
+
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+
+    let res = app
+        .client
+        .get(format!("{}/api/targets/T1/solutions", app.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let body: Value = res.json().await.unwrap();
+    assert!(body["solutions"].as_array().unwrap().is_empty());
+
+    app.client
+        .post(format!("{}/api/calculate", app.base_url))
+        .json(&CalcRequest {
+            mortar_name: "M1",
+            target_name: "T1",
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/fire/spot", app.base_url))
+        .json(&serde_json::json!({
+            "mortar_name": "M1",
+            "target_name": "T1",
+            "observation": { "vertical_m": -20.0, "horizontal_m": 10.0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let body: Value = app
+        .client
+        .get(format!("{}/api/targets/T1/solutions", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let solutions = body["solutions"].as_array().unwrap();
+    assert_eq!(solutions.len(), 2);
+    assert_eq!(solutions[0]["fired"], false);
+    assert_eq!(solutions[1]["fired"], true);
+    assert_eq!(body["mean_deviation"]["vertical_m"].as_f64(), Some(-20.0));
+
+    let res = app
+        .client
+        .get(format!("{}/api/targets/NOPE/solutions", app.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn reading_the_history_while_spotting_does_not_deadlock() {
+    let app = spawn_app().await;
+
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+
+    let spots = {
+        let client = app.client.clone();
+        let url = format!("{}/api/fire/spot", app.base_url);
+        tokio::spawn(async move {
+            for _ in 0..50 {
+                let res = client
+                    .post(&url)
+                    .json(&serde_json::json!({
+                        "mortar_name": "M1",
+                        "target_name": "T1",
+                        "observation": { "vertical_m": 0.0, "horizontal_m": 0.0 }
+                    }))
+                    .send()
+                    .await
+                    .unwrap();
+                assert!(res.status().is_success());
+            }
+        })
+    };
+    let reads = {
+        let client = app.client.clone();
+        let url = format!("{}/api/targets/T1/solutions", app.base_url);
+        tokio::spawn(async move {
+            for _ in 0..50 {
+                let res = client.get(&url).send().await.unwrap();
+                assert!(res.status().is_success());
+            }
+        })
+    };
+
+    tokio::time::timeout(std::time::Duration::from_secs(30), async {
+        spots.await.unwrap();
+        reads.await.unwrap();
+    })
+    .await
+    .expect("spot and history requests deadlocked");
+}
+
+#[tokio::test]
+async fn fired_shots_are_exported_for_replay() {
+    let app = spawn_app().await;
+
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/fire/spot", app.base_url))
+        .json(&serde_json::json!({
+            "mortar_name": "M1",
+            "target_name": "T1",
+            "observation": { "vertical_m": -20.0, "horizontal_m": 10.0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    let shots: Value = app
+        .client
+        .get(format!("{}/api/shots", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let shots = shots.as_array().unwrap();
+    assert_eq!(shots.len(), 1);
+    assert_eq!(shots[0]["target"], "T1");
+    assert_eq!(shots[0]["shot"]["mortar"]["name"], "M1");
+    // Impact 20 m au Nord, 10 m a l'Est de la cible
+    assert_eq!(shots[0]["shot"]["impact"]["x"].as_f64(), Some(510.0));
+    assert_eq!(shots[0]["shot"]["impact"]["y"].as_f64(), Some(320.0));
+
+    let res = app
+        .client
+        .get(format!(
+            "{}/api/shots?format=acmi&ref_lat=48.85&ref_lon=2.35",
+            app.base_url
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let acmi = res.text().await.unwrap();
+    assert!(acmi.starts_with("FileType=text/acmi/tacview\n"));
+    assert!(acmi.contains("0,ReferenceLatitude=48.85\n"));
+    assert!(acmi.contains("Type=Misc+Explosion"));
+
+    let spent: Value = app
+        .client
+        .get(format!("{}/api/shots?format=expenditure", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let ring = shots[0]["shot"]["ring"].as_u64().unwrap();
+    assert_eq!(spent[0]["ammo_type"], "HE");
+    assert_eq!(spent[0]["ring"].as_u64(), Some(ring));
+    assert_eq!(spent[0]["bombs"], 1);
+    // Anneau n : n increments par obus dans les donnees livrees
+    assert_eq!(spent[0]["increments"].as_u64(), Some(ring));
+
+    let res = app
+        .client
+        .get(format!("{}/api/shots?format=kml", app.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn state_changes_are_published_on_the_event_bus() {
+    let server = AppStateBuilder::with_repo_data().spawn().await;
+    let mut events = server.state.events.subscribe();
+    let base_url = server.base_url;
+    let client = Client::new();
+
+    client
+        .post(format!("{base_url}/api/mortars"))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    client
+        .delete(format!("{base_url}/api/mortars"))
+        .json(&serde_json::json!({ "name": "M1" }))
+        .send()
+        .await
+        .unwrap();
+
+    let added = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(added["event"], "mortar_added");
+    assert_eq!(added["mortar"]["name"], "M1");
+    let removed = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(removed["event"], "mortar_removed");
+    assert_eq!(removed["name"], "M1");
+}
+
+#[tokio::test]
+async fn mission_changes_are_published_on_the_event_bus() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let mut events = server.state.events.subscribe();
+    let client = Client::new();
+
+    let res = client
+        .put(server.url("/api/missions/FEU1"))
+        .json(&serde_json::json!({
+            "mortar_name": "M1",
+            "target_name": "T1",
+            "start_in_s": 120,
+            "rounds_per_tube": 2
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let res = client
+        .delete(server.url("/api/missions/FEU1"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let scheduled = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(scheduled["event"], "mission_scheduled");
+    assert_eq!(scheduled["name"], "FEU1");
+    assert_eq!(scheduled["mission"]["target"], "T1");
+    let removed = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(removed["event"], "mission_removed");
+    assert_eq!(removed["name"], "FEU1");
+
+    mortar::server_cli::handle_cli_command("mission FEU2 M1 T1 120 2", &server.state).await;
+    mortar::server_cli::handle_cli_command("mission FEU2 -", &server.state).await;
+    let scheduled = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(scheduled["event"], "mission_scheduled");
+    assert_eq!(scheduled["name"], "FEU2");
+    let removed = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(removed["event"], "mission_removed");
+}
+
+#[tokio::test]
+async fn reference_point_changes_are_published_on_the_event_bus() {
+    let server = AppStateBuilder::new().spawn().await;
+    let mut events = server.state.events.subscribe();
+    let client = Client::new();
+
+    for x in [1000.0, 1100.0] {
+        let res = client
+            .put(server.url("/api/reference-points/TRP1"))
+            .json(&serde_json::json!({ "x": x, "y": 1000.0 }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+    }
+    let res = client
+        .delete(server.url("/api/reference-points/TRP1"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let added = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(added["event"], "reference_point_added");
+    assert_eq!(added["name"], "TRP1");
+    assert_eq!(added["point"]["x"], 1000.0);
+    let updated = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(updated["event"], "reference_point_updated");
+    assert_eq!(updated["point"]["x"], 1100.0);
+    let removed = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(removed["event"], "reference_point_removed");
+    assert_eq!(removed["name"], "TRP1");
+
+    mortar::server_cli::handle_cli_command("trp TRP2 50 500 500 pont", &server.state).await;
+    mortar::server_cli::handle_cli_command("trp TRP2 -", &server.state).await;
+    let added = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(added["event"], "reference_point_added");
+    assert_eq!(added["point"]["description"], "pont");
+    let removed = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(removed["event"], "reference_point_removed");
+    assert_eq!(removed["name"], "TRP2");
+}
+
+#[tokio::test]
+async fn missions_and_mission_log_survive_a_snapshot_round_trip() {
+    let spawn = || {
+        AppStateBuilder::new()
+            .ballistic_table(
+                AmmoKind::He,
+                2,
+                linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+            )
+            .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+            .target(TargetPosition::new(
+                "T1".into(),
+                0.0,
+                0.0,
+                500.0,
+                TargetType::Infanterie,
+                AmmoKind::He,
+            ))
+            .target(TargetPosition::new(
+                "T2".into(),
+                0.0,
+                100.0,
+                500.0,
+                TargetType::Infanterie,
+                AmmoKind::He,
+            ))
+            .spawn()
+    };
+    let source = spawn().await;
+    let client = Client::new();
+    for (name, target) in [("FEU1", "T1"), ("FEU2", "T2")] {
+        let res = client
+            .put(source.url(&format!("/api/missions/{name}")))
+            .json(&serde_json::json!({
+                "mortar_name": "M1",
+                "target_name": target,
+                "start_in_s": 120,
+                "rounds_per_tube": 2
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+    }
+    let res = client
+        .post(source.url("/api/missions/FEU2/eom"))
+        .json(&serde_json::json!({ "effect": "neutralise" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let snapshot: Value = client
+        .get(source.url("/api/state"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(snapshot["missions"]["FEU1"]["target"], "T1");
+    assert_eq!(snapshot["mission_log"][0]["mission"], "FEU2");
+
+    let destination = spawn().await;
+    for _ in 0..2 {
+        let res = client
+            .post(destination.url("/api/state/restore"))
+            .json(&serde_json::json!({ "snapshot": snapshot }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+    }
+    let get = |path: &'static str| {
+        let request = client.get(destination.url(path));
+        async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+    let missions = get("/api/missions").await;
+    assert_eq!(missions["FEU1"]["target"], "T1");
+    assert!(missions.get("FEU2").is_none());
+    // Restaure deux fois : le compte rendu n'est pas duplique
+    let log = get("/api/mission-log").await;
+    assert_eq!(log.as_array().unwrap().len(), 1);
+    assert_eq!(log[0]["effect"], "neutralise");
+    assert_eq!(log[0]["target"]["name"], "T2");
+}
+
+#[tokio::test]
+async fn share_link_exposes_only_selected_targets() {
+    let app = spawn_app().await;
+
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    for name in ["T1", "T2"] {
+        app.client
+            .post(format!("{}/api/targets", app.base_url))
+            .json(&NewTarget {
+                name,
+                elevation: 50.0,
+                x: 500.0,
+                y: 300.0,
+                target_type: "INFANTERIE",
+                ammo_type: "HE",
+            })
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let link: Value = app
+        .client
+        .post(format!("{}/api/shares", app.base_url))
+        .json(&serde_json::json!({ "label": "2e Cie", "targets": ["T2"] }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = link["token"].as_str().unwrap();
+
+    let res = app
+        .client
+        .get(format!("{}/api/share/{}", app.base_url, token))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["label"], "2e Cie");
+    let targets = body["targets"].as_array().unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0]["name"], "T2");
+    assert!(body.get("mortars").is_none());
+
+    let res = app
+        .client
+        .delete(format!("{}/api/shares/{}", app.base_url, token))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let res = app
+        .client
+        .get(format!("{}/api/share/{}", app.base_url, token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn targets_are_moved_from_another_operation() {
+    let app = spawn_app().await;
+
+    let training = serde_json::json!({
+        "targets": [
+            { "name": "T1", "elevation": 50.0, "x": 500.0, "y": 300.0,
+              "target_type": "Infanterie", "ammo_type": "He",
+              "corrections": [{ "vertical_m": 10.0, "horizontal_m": 0.0,
+                                "from_x": 500.0, "from_y": 310.0, "to_x": 500.0, "to_y": 300.0 }] },
+            { "name": "T2", "elevation": 50.0, "x": 600.0, "y": 300.0,
+              "target_type": "Infanterie", "ammo_type": "He" }
+        ]
+    });
+    let res = app
+        .client
+        .post(format!("{}/api/targets/transfer", app.base_url))
+        .json(&serde_json::json!({
+            "operation": training,
+            "direction": "import",
+            "targets": ["T1"],
+            "mode": "move"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["report"]["targets"]["added"][0], "T1");
+    let remaining = body["operation"]["targets"].as_array().unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0]["name"], "T2");
+
+    let targets: Value = app
+        .client
+        .get(format!("{}/api/targets", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let targets = targets["positions"].as_array().unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0]["corrections"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn read_only_server_rejects_mutations() {
+    let server = AppStateBuilder::with_repo_data()
+        .read_only(true)
+        .spawn()
+        .await;
+    let base_url = server.base_url;
+    let client = Client::new();
+
+    let health: Value = client
+        .get(format!("{base_url}/api/health"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(health["read_only"], true);
+
+    let res = client
+        .post(format!("{base_url}/api/mortars"))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::FORBIDDEN);
+    let res = client
+        .delete(format!("{base_url}/api/targets"))
+        .json(&serde_json::json!({ "name": "T1" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::FORBIDDEN);
+
+    // Reads and pure computations are still served
+    let res = client
+        .get(format!("{base_url}/api/mortars"))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let res = client
+        .post(format!("{base_url}/api/calculate"))
+        .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn correction_sign_convention_can_be_overridden() {
+    let app = spawn_app().await;
+
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+
+    // North counted positive: +50 is a round that landed 50 m North
+    let body: Value = app
+        .client
+        .post(format!("{}/api/targets/correct", app.base_url))
+        .json(&serde_json::json!({
+            "target_name": "T1",
+            "vertical_m": 50.0,
+            "horizontal_m": 30.0,
+            "in_place": true,
+            "sign_convention": "N+/E+"
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["sign_convention"], "N+/E+");
+    assert_eq!(body["correction_applied"]["new_x"].as_f64(), Some(470.0));
+    assert_eq!(body["correction_applied"]["new_y"].as_f64(), Some(350.0));
+
+    let res = app
+        .client
+        .put(format!("{}/api/settings/sign-convention", app.base_url))
+        .json(&serde_json::json!({ "sign_convention": "sideways" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_client_error());
+    let body: Value = app
+        .client
+        .put(format!("{}/api/settings/sign-convention", app.base_url))
+        .json(&serde_json::json!({ "sign_convention": "N+/E+" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["sign_convention"], "N+/E+");
+}
+
+#[tokio::test]
+async fn omitted_elevation_records_its_fallback_source() {
+    let app = spawn_app().await;
+
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&serde_json::json!({ "name": "T1", "x": 500.0, "y": 300.0 }))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T2",
+            elevation: 50.0,
+            x: 0.0,
+            y: 0.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+
+    let body: Value = app
+        .client
+        .get(format!("{}/api/targets", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let positions = body["positions"].as_array().unwrap();
+    assert_eq!(positions[0]["elevation"].as_f64(), Some(0.0));
+    assert_eq!(positions[0]["elevation_source"], "datum");
+    assert!(positions[1].get("elevation_source").is_none());
+}
+
+#[tokio::test]
+async fn heightmap_fills_omitted_elevations() {
+    let app = spawn_app().await;
+    // Carte 8 bits 2 x 2 : 100 et 120 m au nord (y = 1000), 140 et 160 m au sud (y = 0)
+    let mut heightmap = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut heightmap, 2, 2);
+        encoder.set_color(png::ColorType::Grayscale);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[10, 12, 14, 16]).unwrap();
+    }
+    let terrain_url = format!("{}/api/environment/terrain", app.base_url);
+
+    let res = app
+        .client
+        .put(format!(
+            "{terrain_url}?origin_x=0&origin_y=1000&cell_size_m=1000&scale_m=10"
+        ))
+        .body(heightmap)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["terrain"]["width"], 2);
+    assert_eq!(body["terrain"]["max_elevation_m"], 160.0);
+
+    for (name, x, y) in [("T1", 500.0, 500.0), ("T2", 5000.0, 0.0)] {
+        app.client
+            .post(format!("{}/api/targets", app.base_url))
+            .json(&serde_json::json!({ "name": name, "x": x, "y": y }))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap();
+    }
+    let body: Value = app
+        .client
+        .get(format!("{}/api/targets", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let positions = body["positions"].as_array().unwrap();
+    assert_eq!(positions[0]["elevation"].as_f64(), Some(130.0));
+    assert_eq!(positions[0]["elevation_source"], "terrain");
+    // Hors de la carte : altitude de repli
+    assert_eq!(positions[1]["elevation_source"], "datum");
+
+    let res = app
+        .client
+        .put(format!(
+            "{terrain_url}?origin_x=0&origin_y=0&cell_size_m=10"
+        ))
+        .body("not a png")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let res = app
+        .client
+        .put(format!("{terrain_url}?origin_x=0&origin_y=0&cell_size_m=0"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+
+    app.client.delete(&terrain_url).send().await.unwrap();
+    let body: Value = app
+        .client
+        .get(&terrain_url)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["terrain"].is_null());
+}
+
+#[tokio::test]
+async fn ammo_policy_overrides_recommendations_and_defaults() {
+    let app = spawn_app().await;
+
+    let body: Value = app
+        .client
+        .put(format!("{}/api/settings/ammo-policy", app.base_url))
+        .json(&serde_json::json!({ "policy": { "VEH": "SMOKE" } }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["recommended"]["VEHICULE"], "SMOKE");
+    assert_eq!(body["recommended"]["INFANTERIE"], "HE");
+
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&serde_json::json!({
+            "name": "V1", "elevation": 50.0, "x": 500.0, "y": 300.0, "target_type": "VEHICULE"
+        }))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+
+    let solution: Value = app
+        .client
+        .post(format!("{}/api/calculate", app.base_url))
+        .json(&CalcRequest {
+            mortar_name: "M1",
+            target_name: "V1",
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(solution["recommended_ammo"], "SMOKE");
+    assert_eq!(solution["mortar_ammo"], "SMOKE");
+
+    let res = app
+        .client
+        .put(format!("{}/api/settings/ammo-policy", app.base_url))
+        .json(&serde_json::json!({ "policy": { "TANK": "HE" } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn target_reliability_is_validated_on_creation() {
+    let app = spawn_app().await;
+    let add = |body: Value| {
+        app.client
+            .post(format!("{}/api/targets", app.base_url))
+            .json(&body)
+            .send()
+    };
+
+    let res = add(serde_json::json!({
+        "name": "T9",
+        "elevation": 50.0,
+        "x": 500.0,
+        "y": 300.0,
+        "reliability": "SURE"
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"], "Invalid reliability: SURE");
+
+    add(serde_json::json!({
+        "name": "T9",
+        "elevation": 50.0,
+        "x": 500.0,
+        "y": 300.0,
+        "reliability": "pr"
+    }))
+    .await
+    .unwrap()
+    .error_for_status()
+    .unwrap();
+    let list: Value = app
+        .client
+        .get(format!("{}/api/targets", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let target = list["positions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["name"] == "T9")
+        .unwrap();
+    assert_eq!(target["reliability"], "Probable");
+}
+
+#[tokio::test]
+async fn unnamed_targets_get_sequential_target_numbers() {
+    let app = spawn_app().await;
+
+    let add = |body: Value| {
+        app.client
+            .post(format!("{}/api/targets", app.base_url))
+            .json(&body)
+            .send()
+    };
+    let added: Value = add(serde_json::json!({ "elevation": 50.0, "x": 500.0, "y": 300.0 }))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(added["message"], "Target 'AB1001' added as INFANTERIE");
+    // Un nom deja pris est saute
+    add(serde_json::json!({ "name": "AB1002", "elevation": 50.0, "x": 0.0, "y": 0.0 }))
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+    add(serde_json::json!({ "elevation": 50.0, "x": 600.0, "y": 300.0 }))
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+
+    let body: Value = app
+        .client
+        .put(format!("{}/api/settings/target-numbering", app.base_url))
+        .json(&serde_json::json!({ "prefix": "xy", "next": 9999 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["next_name"], "XY9999");
+    for x in [700.0, 800.0] {
+        add(serde_json::json!({ "elevation": 50.0, "x": x, "y": 300.0 }))
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap();
+    }
+
+    let state: Value = app
+        .client
+        .get(format!("{}/api/state", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let names: Vec<&str> = state["targets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["AB1001", "AB1002", "AB1003", "XY9999", "XY0001"]);
+    assert_eq!(state["target_numbering"]["next"], 2);
+
+    let res = app
+        .client
+        .put(format!("{}/api/settings/target-numbering", app.base_url))
+        .json(&serde_json::json!({ "prefix": "A1" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn synthetic_tables_and_positions_drive_the_api() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), Some(20.0)),
+        )
+        .dispersion(AmmoKind::He, 2, 25.0)
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            600.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+
+    let solution: Value = Client::new()
+        .post(server.url("/api/calculate"))
+        .json(&CalcRequest {
+            mortar_name: "M1",
+            target_name: "T1",
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(solution["distance_m"].as_f64(), Some(600.0));
+    assert_eq!(
+        solution["selected_solution"]["elevations"]["2R"].as_f64(),
+        Some(1150.0)
+    );
+    assert_eq!(
+        solution["selected_solution"]["dispersions"]["2R"].as_f64(),
+        Some(25.0)
+    );
+    assert_eq!(server.state.solution_history.read().await["T1"].len(), 1);
+}
+
+#[tokio::test]
+async fn client_side_routes_serve_the_web_interface() {
+    let server = AppStateBuilder::new()
+        .options(mortar::server::AppOptions {
+            asset_max_age_s: 60,
+            ..Default::default()
+        })
+        .spawn()
+        .await;
+    let client = Client::new();
+
+    let res = client
+        .get(server.url("/targets/T1/history"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(res.headers()["cache-control"], "no-cache");
+    assert!(res.text().await.unwrap().contains("<html"));
+
+    let res = client.get(server.url("/app.js")).send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(res.headers()["cache-control"], "public, max-age=60");
+
+    let res = client.get(server.url("/api/nope")).send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["error"], "Unknown API endpoint: /api/nope");
+}
+
+#[tokio::test]
+async fn effect_radius_sizes_danger_close_volleys_and_the_overlay() {
+    let table = || linear_table((100.0, 1400.0), (1100.0, 900.0), Some(20.0));
+    let server = AppStateBuilder::new()
+        .ballistic_table(AmmoKind::He, 1, table())
+        .ballistic_table(AmmoKind::He, 2, table())
+        .dispersion(AmmoKind::He, 1, 20.0)
+        .dispersion(AmmoKind::He, 2, 40.0)
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    server
+        .state
+        .friendlies
+        .write()
+        .await
+        .push(mortar::safety::FriendlyPosition::new(
+            "SECTION2".into(),
+            0.0,
+            0.0,
+            1140.0,
+        ));
+    server.state.effects.write().await.insert(
+        AmmoKind::He,
+        mortar::EffectRadii {
+            casualty_m: Some(35.0),
+            ..Default::default()
+        },
+    );
+    let client = Client::new();
+
+    // 140 m du but : hors de 3 x 40 m, mais dans 120 + 35 m letal
+    let solution: Value = client
+        .post(server.url("/api/calculate"))
+        .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(solution["selected_solution"]["effect_radius_m"], 35.0);
+    let danger = &solution["danger_close"];
+    assert_eq!(danger["casualty_radius_m"], 35.0);
+    assert_eq!(danger["danger_radius_m"]["2R"], 155.0);
+    assert_eq!(danger["friendlies"][0]["rings"], serde_json::json!(["2R"]));
+    assert_eq!(danger["safer_fire"]["ring"], "1R");
+    assert_eq!(danger["safer_fire"]["danger_radius_m"], 95.0);
+
+    // Dispersion 2R 40 m, rayon letal 35 m : 2 coups couvrent la zone
+    let volley: Value = client
+        .post(server.url("/api/fire/volley"))
+        .json(&serde_json::json!({
+            "mortar_name": "M1",
+            "target_name": "T1",
+            "ring": 2,
+            "rounds_per_tube": 1
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(volley["effect_radius_m"], 35.0);
+    assert_eq!(volley["rounds_required"], 2);
+
+    let html = client
+        .get(server.url("/"))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(html.contains("id=\"effect-overlay-map\""));
+    let js = client
+        .get(server.url("/app.js"))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(js.contains("function renderEffectOverlay"));
+    assert!(js.contains("selected.effect_radius_m"));
+    assert!(js.contains("data.danger_close.casualty_radius_m"));
+}
+
+#[tokio::test]
+async fn responses_are_compressed_and_api_data_is_revalidated() {
+    let server = AppStateBuilder::with_repo_data().spawn().await;
+    let client = Client::new();
+
+    let res = client
+        .get(server.url("/api/mission-pack?format=json"))
+        .header("accept-encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+    assert_eq!(res.headers()["cache-control"], "no-cache");
+
+    let res = client
+        .get(server.url("/app.js"))
+        .header("accept-encoding", "br")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["content-encoding"], "br");
+
+    // Sans Accept-Encoding, la reponse n'est pas compressee
+    let res = client.get(server.url("/api/types")).send().await.unwrap();
+    assert!(res.headers().get("content-encoding").is_none());
+    assert_eq!(res.headers()["cache-control"], "no-cache");
+}
+
+#[tokio::test]
+async fn scatter_beyond_its_time_budget_returns_a_partial_result() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .options(mortar::server::AppOptions {
+            calc_budget_ms: 60_000,
+            ..Default::default()
+        })
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            600.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+
+    let scatter = |budget_ms: Option<u64>| {
+        client
+            .post(server.url("/api/fire/scatter"))
+            .json(&serde_json::json!({
+                "mortar_name": "M1",
+                "target_name": "T1",
+                "count": 2000,
+                "seed": 1,
+                "budget_ms": budget_ms,
+            }))
+            .send()
+    };
+
+    let full: Value = scatter(None).await.unwrap().json().await.unwrap();
+    assert_eq!(full["impacts"].as_array().unwrap().len(), 2000);
+    assert_eq!(full["truncated"], false);
+
+    // Budget nul : le tirage s'arrete a la premiere verification
+    let partial: Value = scatter(Some(0)).await.unwrap().json().await.unwrap();
+    assert_eq!(partial["truncated"], true);
+    assert_eq!(partial["requested"], 2000);
+    let impacts = partial["impacts"].as_array().unwrap();
+    assert_eq!(impacts.len(), mortar::simulation::BUDGET_CHECK_INTERVAL);
+    assert_eq!(
+        impacts[..],
+        full["impacts"].as_array().unwrap()[..impacts.len()]
+    );
+}
+
+#[tokio::test]
+async fn simulation_estimates_hit_probability_of_a_ring() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .dispersion(AmmoKind::He, 2, 30.0)
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            600.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+    let simulate = |radius: f64, ring: Option<u8>| {
+        client
+            .post(server.url("/api/simulate"))
+            .json(&serde_json::json!({
+                "mortar_name": "M1",
+                "target_name": "T1",
+                "target_radius_m": radius,
+                "ring": ring,
+                "seed": 3,
+            }))
+            .send()
+    };
+
+    let res = simulate(30.0, None).await.unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["ring"], 2);
+    assert_eq!(body["samples"], 5000);
+    let p = body["hit_probability"].as_f64().unwrap();
+    assert!((p - 0.5).abs() < 0.03, "p = {p}");
+    assert!(body["radii"]["p90_m"].as_f64().unwrap() > 30.0);
+    assert!(body["rounds_for_confidence"].as_u64().unwrap() >= 3);
+    assert_eq!(body["impacts"].as_array().unwrap().len(), 2000);
+
+    assert_eq!(simulate(-1.0, None).await.unwrap().status(), 400);
+    assert_eq!(simulate(30.0, Some(3)).await.unwrap().status(), 422);
+}
+
+#[tokio::test]
+async fn session_seed_replays_simulated_impacts() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .options(mortar::server::AppOptions {
+            seed: Some(7),
+            ..Default::default()
+        })
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            600.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+
+    let scatter = |seed: Option<u64>| {
+        client
+            .post(server.url("/api/fire/scatter"))
+            .json(&serde_json::json!({
+                "mortar_name": "M1",
+                "target_name": "T1",
+                "count": 20,
+                "seed": seed,
+            }))
+            .send()
+    };
+    let impacts = |body: Value| body["impacts"].clone();
+
+    let setting: Value = client
+        .get(server.url("/api/settings/seed"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(setting["seed"], 7);
+
+    // Chaque tir de la session recoit le generateur suivant de la serie
+    let first = impacts(scatter(None).await.unwrap().json().await.unwrap());
+    let second = impacts(scatter(None).await.unwrap().json().await.unwrap());
+    assert_ne!(first, second);
+
+    // Une graine explicite ne consomme pas la serie
+    let explicit = impacts(scatter(Some(1)).await.unwrap().json().await.unwrap());
+    assert_eq!(
+        explicit,
+        impacts(scatter(Some(1)).await.unwrap().json().await.unwrap())
+    );
+
+    // Relancer la meme graine rejoue l'exercice
+    let reset = client
+        .put(server.url("/api/settings/seed"))
+        .json(&serde_json::json!({ "seed": 7 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(reset.status(), 200);
+    assert_eq!(
+        impacts(scatter(None).await.unwrap().json().await.unwrap()),
+        first
+    );
+    assert_eq!(
+        impacts(scatter(None).await.unwrap().json().await.unwrap()),
+        second
+    );
+
+    let cleared: Value = client
+        .delete(server.url("/api/settings/seed"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(cleared["seed"], Value::Null);
+}
+
+#[tokio::test]
+async fn large_elevation_differences_are_flagged_per_thresholds() {
+    let app = spawn_app().await;
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M1", "elevation": 0.0, "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 250.0,
+            x: 0.0,
+            y: 800.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    let calculate = || async {
+        let body: Value = app
+            .client
+            .post(format!("{}/api/calculate", app.base_url))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body
+    };
+
+    let solution = calculate().await;
+    let warning = &solution["elevation_warning"];
+    assert_eq!(warning["level"], "caution");
+    assert_eq!(warning["threshold_m"].as_f64(), Some(100.0));
+    // Target above the mortar: aim longer on every ring with a solution
+    let corrections = warning["range_correction_m"].as_object().unwrap();
+    assert!(!corrections.is_empty());
+    assert!(corrections
+        .values()
+        .filter_map(Value::as_f64)
+        .all(|r| r > 0.0));
+    assert!(solution["warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|w| w.as_str().unwrap().contains("250 m above")));
+
+    let res = app
+        .client
+        .put(format!(
+            "{}/api/settings/elevation-thresholds",
+            app.base_url
+        ))
+        .json(&serde_json::json!({ "caution_m": 500.0, "critical_m": 100.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let res = app
+        .client
+        .put(format!(
+            "{}/api/settings/elevation-thresholds",
+            app.base_url
+        ))
+        .json(&serde_json::json!({ "caution_m": 300.0, "critical_m": 500.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    assert!(calculate().await.get("elevation_warning").is_none());
+}
+
+#[tokio::test]
+async fn session_charge_temperature_corrects_the_selected_elevations() {
+    let app = spawn_app().await;
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M1", "elevation": 0.0, "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 0.0,
+            x: 0.0,
+            y: 800.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    let calculate = || async {
+        let body: Value = app
+            .client
+            .post(format!("{}/api/calculate", app.base_url))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body
+    };
+    let url = format!("{}/api/settings/charge-temperature", app.base_url);
+
+    let standard = calculate().await;
+    assert!(standard.get("charge_temperature").is_none());
+
+    let res = app
+        .client
+        .put(&url)
+        .json(&serde_json::json!({ "temperature_c": 90.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let res = app
+        .client
+        .put(&url)
+        .json(&serde_json::json!({ "temperature_c": 41.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    // Hot charges carry further: aim shorter, i.e. at a higher elevation
+    let hot = calculate().await;
+    let correction = &hot["charge_temperature"];
+    assert_eq!(correction["temperature_c"].as_f64(), Some(41.0));
+    let elevations = hot["selected_solution"]["elevations"].as_object().unwrap();
+    let mut compared = 0;
+    for (ring, elevation) in elevations {
+        let (hot, standard) = match (
+            elevation.as_f64(),
+            standard["selected_solution"]["elevations"][ring].as_f64(),
+        ) {
+            (Some(hot), Some(standard)) => (hot, standard),
+            _ => continue,
+        };
+        assert_eq!(correction["elevations"][ring].as_f64(), Some(hot));
+        assert!(hot > standard, "{ring}: {hot} <= {standard}");
+        compared += 1;
+    }
+    assert!(compared > 0);
+
+    let res = app.client.delete(&url).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = app
+        .client
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["temperature_c"].is_null());
+    assert!(calculate().await.get("charge_temperature").is_none());
+}
+
+#[tokio::test]
+async fn operation_wind_corrects_range_and_azimuth() {
+    let app = spawn_app().await;
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M1", "elevation": 0.0, "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 0.0,
+            x: 0.0,
+            y: 800.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    let calculate = || async {
+        let body: Value = app
+            .client
+            .post(format!("{}/api/calculate", app.base_url))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body
+    };
+    let url = format!("{}/api/environment/wind", app.base_url);
+
+    let calm = calculate().await;
+    assert!(calm.get("wind").is_none());
+
+    let res = app
+        .client
+        .put(&url)
+        .json(&serde_json::json!({ "direction_deg": 0.0, "speed_mps": 90.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    // Headwind at the surface, crosswind from the West aloft
+    let res = app
+        .client
+        .put(&url)
+        .json(&serde_json::json!({
+            "direction_deg": 0.0,
+            "speed_mps": 4.0,
+            "layers": [{ "height_m": 200.0, "direction_deg": "4800mil", "speed_mps": 8.0 }]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    // Headwind: aim further, i.e. at a lower elevation, and left of the crosswind
+    let windy = calculate().await;
+    let correction = &windy["wind"];
+    assert_eq!(
+        correction["wind"]["layers"][0]["direction_deg"].as_f64(),
+        Some(270.0)
+    );
+    let elevations = windy["selected_solution"]["elevations"]
+        .as_object()
+        .unwrap();
+    let mut compared = 0;
+    for (ring, elevation) in elevations {
+        let (windy, calm) = match (
+            elevation.as_f64(),
+            calm["selected_solution"]["elevations"][ring].as_f64(),
+        ) {
+            (Some(windy), Some(calm)) => (windy, calm),
+            _ => continue,
+        };
+        assert_eq!(correction["elevations"][ring].as_f64(), Some(windy));
+        assert!(windy < calm, "{ring}: {windy} >= {calm}");
+        assert!(correction["deflection_mil"][ring].as_f64().unwrap() < 0.0);
+        compared += 1;
+    }
+    assert!(compared > 0);
+
+    let res = app.client.delete(&url).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = app
+        .client
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["wind"].is_null());
+    assert!(calculate().await.get("wind").is_none());
+}
+
+#[tokio::test]
+async fn met_message_sets_wind_and_air_conditions() {
+    let app = spawn_app().await;
+    let url = format!("{}/api/environment/met", app.base_url);
+
+    let res = app
+        .client
+        .post(&url)
+        .json(&serde_json::json!({
+            "message": "METCM1 345 067 12301 0 004 013\n\
+                        00 480 010 2931 1013\n\
+                        01 480 012 2925 1000\n\
+                        02 500 020 2910 0975\n\
+                        99999"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["message"]["kind"], "METCM");
+    assert_eq!(body["message"]["zones"].as_array().unwrap().len(), 3);
+
+    let wind: Value = app
+        .client
+        .get(format!("{}/api/environment/wind", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let wind = &wind["wind"];
+    assert!((wind["direction_deg"].as_f64().unwrap() - 270.0).abs() < 1e-9);
+    let layers = wind["layers"].as_array().unwrap();
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[1]["height_m"].as_f64(), Some(200.0));
+    assert!((layers[1]["direction_deg"].as_f64().unwrap() - 281.25).abs() < 1e-9);
+
+    let air: Value = app
+        .client
+        .get(format!("{}/api/environment/atmosphere", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(air["atmosphere"]["pressure_hpa"].as_f64(), Some(1013.0));
+    assert!((air["atmosphere"]["air_temperature_c"].as_f64().unwrap() - 19.95).abs() < 1e-9);
+
+    let res = app
+        .client
+        .post(&url)
+        .json(&serde_json::json!({ "message": "METCM1 345 067" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn ballistic_curve_resamples_a_table() {
+    let app = spawn_app().await;
+    let curve = |query: &str| {
+        let url = format!("{}/api/ballistics/HE/2R/curve{}", app.base_url, query);
+        let client = app.client.clone();
+        async move { client.get(url).send().await.unwrap() }
+    };
+
+    let res = curve("?step=50").await;
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["ring"], "2R");
+    assert_eq!(body["step_m"].as_f64(), Some(50.0));
+    let points = body["points"].as_array().unwrap();
+    // Table rows 200 m to 1600 m, both ends included
+    assert_eq!(points.len(), 29);
+    assert_eq!(points[0]["range_m"].as_f64(), Some(200.0));
+    assert_eq!(points[0]["elev_mil"].as_f64(), Some(1538.0));
+    assert_eq!(points[0]["time_flight_s"].as_f64(), Some(26.6));
+    assert_eq!(points[28]["range_m"].as_f64(), Some(1600.0));
+    assert_eq!(points[28]["elev_mil"].as_f64(), Some(912.0));
+    let elevations: Vec<f64> = points
+        .iter()
+        .map(|p| p["elev_mil"].as_f64().unwrap())
+        .collect();
+    assert!(elevations.windows(2).all(|w| w[1] < w[0]));
+
+    let body: Value = curve("").await.json().await.unwrap();
+    assert_eq!(body["points"].as_array().unwrap().len(), 141);
+
+    assert_eq!(curve("?step=0").await.status(), 400);
+    assert_eq!(curve("?weapon=M120").await.status(), 404);
+    let res = app
+        .client
+        .get(format!("{}/api/ballistics/HE/9R/curve", app.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn ballistic_csv_round_trips_the_loaded_table() {
+    let app = spawn_app().await;
+    let csv = |query: &str| {
+        let url = format!("{}/api/ballistics/HE/2R/csv{}", app.base_url, query);
+        let client = app.client.clone();
+        async move { client.get(url).send().await.unwrap() }
+    };
+
+    let res = csv("").await;
+    assert_eq!(res.status(), 200);
+    assert!(res.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/csv"));
+    assert!(res.headers()["content-disposition"]
+        .to_str()
+        .unwrap()
+        .contains("M821_HE_2R.csv"));
+    let exported = mortar::read_ballistic_points(res.text().await.unwrap().as_bytes()).unwrap();
+    let source = std::fs::File::open(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data/HE/M821_HE_2R.csv"),
+    )
+    .unwrap();
+    let mut source = mortar::read_ballistic_points(source).unwrap();
+    source.sort_by(|a, b| a.range_m.total_cmp(&b.range_m));
+    assert_eq!(exported, source);
+
+    // Resampled export: the same rows as the curve, with the slope filled in
+    let text = csv("?step=50").await.text().await.unwrap();
+    let resampled = mortar::read_ballistic_points(text.as_bytes()).unwrap();
+    assert_eq!(resampled.len(), 29);
+    assert_eq!(resampled[0].elev_mil, 1538.0);
+    assert!(resampled
+        .iter()
+        .all(|p| p.delta_elev_per_100m_mil.is_some()));
+
+    assert_eq!(csv("?step=0").await.status(), 400);
+    assert_eq!(csv("?weapon=M120").await.status(), 404);
+}
+
+#[tokio::test]
+async fn trajectory_samples_the_flight_path() {
+    let app = spawn_app().await;
+    let trajectory = |query: &str| {
+        let url = format!("{}/api/trajectory?{}", app.base_url, query);
+        let client = app.client.clone();
+        async move { client.get(url).send().await.unwrap() }
+    };
+
+    let res = trajectory("ammo=HE&ring=2R&range_m=1000&step_m=100").await;
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    let points = body["points"].as_array().unwrap();
+    assert_eq!(points.len(), 11);
+    assert_eq!(points[0]["height_m"].as_f64(), Some(0.0));
+    assert_eq!(points[10]["x_m"].as_f64(), Some(1000.0));
+    let tof = body["time_of_flight_s"].as_f64().unwrap();
+    assert_eq!(points[10]["time_s"].as_f64(), Some(tof));
+    let apex = points
+        .iter()
+        .map(|p| p["height_m"].as_f64().unwrap())
+        .fold(0.0, f64::max);
+    assert!((apex - body["max_ordinate_m"].as_f64().unwrap()).abs() < 1e-6);
+
+    // A target above the mortar ends the arc at its height
+    let body: Value = trajectory("ammo=HE&ring=2&range_m=1000&rise_m=50")
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["points"].as_array().unwrap().len(), 101);
+    assert_eq!(body["points"][100]["height_m"].as_f64(), Some(50.0));
+
+    assert_eq!(
+        trajectory("ammo=HE&ring=2&range_m=1000&step_m=0")
+            .await
+            .status(),
+        400
+    );
+    assert_eq!(
+        trajectory("ammo=XX&ring=2&range_m=1000").await.status(),
+        400
+    );
+    assert_eq!(
+        trajectory("ammo=HE&ring=9&range_m=1000").await.status(),
+        404
+    );
+    assert_eq!(
+        trajectory("ammo=HE&ring=2&range_m=5000").await.status(),
+        422
+    );
+}
+
+#[tokio::test]
+async fn crater_analysis_plots_a_hostile_area_target() {
+    let app = spawn_app().await;
+    let craters = |body: Value| {
+        app.client
+            .post(format!("{}/api/targets/craters", app.base_url))
+            .json(&body)
+            .send()
+    };
+
+    let res = craters(serde_json::json!({
+        "name": "HOSTILE1",
+        "craters": [
+            { "x": 0.0, "y": 1000.0, "back_azimuth_deg": 90.0, "min_range_m": 800.0, "max_range_m": 1200.0 },
+            { "x": 1000.0, "y": 0.0, "back_azimuth_deg": "0mil", "min_range_m": 900.0, "max_range_m": 1100.0 }
+        ],
+        "elevation": 0.0
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert!((body["area"]["x"].as_f64().unwrap() - 1000.0).abs() < 1e-6);
+    assert!((body["area"]["y"].as_f64().unwrap() - 1000.0).abs() < 1e-6);
+    let radius = body["area"]["radius_m"].as_f64().unwrap();
+    assert!(radius > 100.0 && radius < 101.0);
+    assert_eq!(body["target"]["uncertainty_m"].as_f64(), Some(radius));
+
+    // Solutions on the area target warn that it needs area fire
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M1", "elevation": 0.0, "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    let solution: Value = app
+        .client
+        .post(format!("{}/api/calculate", app.base_url))
+        .json(&CalcRequest {
+            mortar_name: "M1",
+            target_name: "HOSTILE1",
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(solution["warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|w| w.as_str().unwrap().contains("plan area fire")));
+
+    let res = craters(serde_json::json!({
+        "craters": [{ "x": 0.0, "y": 0.0, "back_azimuth_deg": 0.0, "min_range_m": 900.0, "max_range_m": 100.0 }]
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 400);
+    let res = craters(serde_json::json!({
+        "craters": [{ "x": 0.0, "y": 0.0, "back_azimuth_deg": 0.0 }]
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 422);
+    let res = craters(serde_json::json!({
+        "name": "HOSTILE1",
+        "craters": [{ "x": 0.0, "y": 0.0, "back_azimuth_deg": 0.0, "range_m": 500.0 }]
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 409);
+}
+
+#[tokio::test]
+async fn displacing_mortar_is_not_tasked() {
+    let app = spawn_app().await;
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M1", "elevation": 0.0, "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 0.0,
+            x: 0.0,
+            y: 800.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    let set_status = |status: &str| {
+        app.client
+            .post(format!("{}/api/mortars/status", app.base_url))
+            .json(&serde_json::json!({ "name": "M1", "status": status }))
+            .send()
+    };
+    let schedule = || {
+        app.client
+            .put(format!("{}/api/missions/FEU1", app.base_url))
+            .json(&serde_json::json!({
+                "mortar_name": "M1", "target_name": "T1", "start_in_s": 60, "rounds_per_tube": 3
+            }))
+            .send()
+    };
+
+    assert_eq!(set_status("PARKED").await.unwrap().status(), 400);
+    assert_eq!(set_status("MOVING").await.unwrap().status(), 200);
+    let mortars: Value = app
+        .client
+        .get(format!("{}/api/mortars", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(mortars["positions"][0]["status"], "MOVING");
+    assert!(mortars["positions"][0]["status_since"].is_u64());
+
+    let res = schedule().await.unwrap();
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("not ready"));
+
+    assert_eq!(set_status("ready").await.unwrap().status(), 200);
+    assert_eq!(schedule().await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn missions_outside_the_sector_of_fire_need_an_override() {
+    let app = spawn_app().await;
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M1", "elevation": 0.0, "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 0.0,
+            x: 800.0,
+            y: 0.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    let res = app
+        .client
+        .post(format!("{}/api/mortars/sector", app.base_url))
+        .json(&serde_json::json!({
+            "name": "M1", "sector": { "left_deg": 300.0, "right_deg": 60.0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let res = app
+        .client
+        .post(format!("{}/api/mortars/sector", app.base_url))
+        .json(&serde_json::json!({
+            "name": "M1", "sector": { "left_deg": 10.0, "right_deg": 10.0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+
+    let schedule = |override_sector: bool| {
+        app.client
+            .put(format!("{}/api/missions/FEU1", app.base_url))
+            .json(&serde_json::json!({
+                "mortar_name": "M1", "target_name": "T1", "start_in_s": 60,
+                "rounds_per_tube": 3, "override_sector": override_sector
+            }))
+            .send()
+    };
+    let res = schedule(false).await.unwrap();
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("sector of fire"));
+    assert_eq!(schedule(true).await.unwrap().status(), 200);
+
+    let solution: Value = app
+        .client
+        .post(format!("{}/api/calculate", app.base_url))
+        .json(&CalcRequest {
+            mortar_name: "M1",
+            target_name: "T1",
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(solution["warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|w| w.as_str().unwrap().contains("sector of fire")));
+}
+
+#[tokio::test]
+async fn timeline_merges_scheduled_missions_and_fired_shots() {
+    let app = spawn_app().await;
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M1", "elevation": 0.0, "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 0.0,
+            x: 0.0,
+            y: 800.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/fire/spot", app.base_url))
+        .json(&serde_json::json!({
+            "mortar_name": "M1",
+            "target_name": "T1",
+            "observation": { "vertical_m": 0.0, "horizontal_m": 0.0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+    let schedule = |body: Value| {
+        app.client
+            .put(format!("{}/api/missions/FEU1", app.base_url))
+            .json(&body)
+            .send()
+    };
+
+    let res = schedule(serde_json::json!({
+        "mortar_name": "M1", "target_name": "T1", "rounds_per_tube": 3
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 400);
+    let res = schedule(serde_json::json!({
+        "mortar_name": "M1", "target_name": "T9", "start_in_s": 60, "rounds_per_tube": 3
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 404);
+    let res = schedule(serde_json::json!({
+        "mortar_name": "M1", "target_name": "T1", "start_in_s": 60,
+        "tubes": 2, "rounds_per_tube": 3
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 200);
+    let missions: Value = res.json().await.unwrap();
+    assert_eq!(missions["FEU1"]["tubes"], 2);
+
+    let timeline: Value = app
+        .client
+        .get(format!("{}/api/timeline", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let events = timeline["events"].as_array().unwrap();
+    let offsets: Vec<f64> = events
+        .iter()
+        .map(|e| e["offset_s"].as_f64().unwrap())
+        .collect();
+    assert!(offsets.windows(2).all(|w| w[0] <= w[1]));
+    // The spotting round just fired comes first, outside any mission
+    assert_eq!(events[0]["kind"], "fire");
+    assert!(events[0].get("mission").is_none());
+    let mission: Vec<&Value> = events.iter().filter(|e| e["mission"] == "FEU1").collect();
+    let fires = mission.iter().filter(|e| e["kind"] == "fire").count();
+    assert_eq!(fires, 3);
+    assert!(mission
+        .iter()
+        .all(|e| e["offset_s"].as_f64().unwrap() >= 59.0));
+    assert_eq!(mission.last().unwrap()["kind"], "lift");
+
+    let res = app
+        .client
+        .delete(format!("{}/api/missions/FEU1", app.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let timeline: Value = app
+        .client
+        .get(format!("{}/api/timeline?window_s=600", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(timeline["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|e| e.get("mission").is_none()));
+}
+
+#[tokio::test]
+async fn end_of_mission_logs_the_report_and_archives_the_target() {
+    let app = spawn_app().await;
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M1", "elevation": 0.0, "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 0.0,
+            x: 0.0,
+            y: 800.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/fire/spot", app.base_url))
+        .json(&serde_json::json!({
+            "mortar_name": "M1",
+            "target_name": "T1",
+            "observation": { "vertical_m": -20.0, "horizontal_m": 10.0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+    let res = app
+        .client
+        .put(format!("{}/api/missions/FEU1", app.base_url))
+        .json(&serde_json::json!({
+            "mortar_name": "M1", "target_name": "T1", "start": 0,
+            "tubes": 2, "rounds_per_tube": 3
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let eom = |mission: &str, body: Value| {
+        app.client
+            .post(format!("{}/api/missions/{}/eom", app.base_url, mission))
+            .json(&body)
+            .send()
+    };
+
+    let res = eom("FEU1", serde_json::json!({ "effect": " " }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let res = eom("FEU9", serde_json::json!({ "effect": "neutralise" }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let res = eom(
+        "FEU1",
+        serde_json::json!({ "effect": "neutralise", "remarks": "vehicule en feu" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(res.status(), 200);
+    let report: Value = res.json().await.unwrap();
+    assert_eq!(report["mission"], "FEU1");
+    assert_eq!(report["adjusting_rounds"], 1);
+    assert_eq!(report["effect_rounds"], 6);
+    assert_eq!(report["rounds_fired"], 7);
+    assert_eq!(report["target"]["name"], "T1");
+    assert_eq!(report["effect"], "neutralise");
+
+    // The mission is closed and its target archived
+    let missions: Value = app
+        .client
+        .get(format!("{}/api/missions", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(missions.get("FEU1").is_none());
+    let targets: Value = app
+        .client
+        .get(format!("{}/api/targets", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(!targets.to_string().contains("\"T1\""));
+    let log: Value = app
+        .client
+        .get(format!("{}/api/mission-log", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(log.as_array().unwrap().len(), 1);
+    assert_eq!(log[0]["remarks"], "vehicule en feu");
+    let res = eom("FEU1", serde_json::json!({ "effect": "neutralise" }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn correction_presets_are_applied_by_name() {
+    let app = spawn_app().await;
+
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+
+    let body: Value = app
+        .client
+        .put(format!(
+            "{}/api/settings/correction-presets/carte",
+            app.base_url
+        ))
+        .json(&serde_json::json!({ "vertical_m": -50.0, "horizontal_m": 30.0 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["carte"]["vertical_m"].as_f64(), Some(-50.0));
+
+    let body: Value = app
+        .client
+        .post(format!("{}/api/targets/correct", app.base_url))
+        .json(&serde_json::json!({
+            "target_name": "T1",
+            "preset": "carte",
+            "in_place": true
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["preset"], "carte");
+    assert_eq!(body["correction_applied"]["new_x"].as_f64(), Some(470.0));
+    assert_eq!(body["correction_applied"]["new_y"].as_f64(), Some(350.0));
+
+    let res = app
+        .client
+        .post(format!("{}/api/targets/correct", app.base_url))
+        .json(&serde_json::json!({ "target_name": "T1", "preset": "inconnu" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+
+    let res = app
+        .client
+        .post(format!("{}/api/targets/correct", app.base_url))
+        .json(&serde_json::json!({ "target_name": "T1", "vertical_m": -50.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let res = app
+        .client
+        .delete(format!(
+            "{}/api/settings/correction-presets/carte",
+            app.base_url
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+}
+
+#[tokio::test]
+async fn map_markers_are_imported_as_targets() {
+    let app = spawn_app().await;
+
+    let body: Value = app
+        .client
+        .post(format!("{}/api/targets/import", app.base_url))
+        .json(&serde_json::json!({
+            "reference": { "latitude_deg": 48.0, "longitude_deg": 2.0 },
+            "markers": {
+                "type": "FeatureCollection",
+                "features": [
+                    {
+                        "type": "Feature",
+                        "geometry": { "type": "Point", "coordinates": [2.0, 48.01] },
+                        "properties": { "name": "Char", "marker-symbol": "tank" }
+                    },
+                    {
+                        "type": "Feature",
+                        "geometry": { "type": "Point", "coordinates": [2.0, 48.0] },
+                        "properties": { "icon": "star" }
+                    }
+                ]
+            }
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let imported = body["imported"].as_array().unwrap();
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0]["name"], "Char");
+    assert_eq!(imported[0]["target_type"], "Vehicule");
+    assert_eq!(imported[0]["icon_matched"], true);
+    assert!((imported[0]["y"].as_f64().unwrap() - 1105.74).abs() < 0.01);
+    assert_eq!(imported[1]["target_type"], "Infanterie");
+    assert_eq!(imported[1]["icon_matched"], false);
+
+    // Names already taken and lat/lon without reference are reported
+    let body: Value = app
+        .client
+        .post(format!("{}/api/targets/import", app.base_url))
+        .json(&serde_json::json!({
+            "markers": [
+                { "name": "Char", "x": 10.0, "y": 20.0 },
+                { "name": "MG", "lat": 48.0, "lon": 2.0 }
+            ]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["imported"].as_array().unwrap().len(), 0);
+    assert_eq!(body["skipped"].as_array().unwrap().len(), 2);
+
+    let res = app
+        .client
+        .post(format!("{}/api/targets/import", app.base_url))
+        .json(&serde_json::json!({ "markers": { "foo": 1 } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn trajectories_above_an_airspace_ceiling_are_flagged() {
+    let app = spawn_app().await;
+
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+
+    let calculate = || async {
+        app.client
+            .post(format!("{}/api/calculate", app.base_url))
+            .json(&CalcRequest {
+                mortar_name: "M1",
+                target_name: "T1",
+            })
+            .send()
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap()
+    };
+    assert!(calculate().await.get("airspace_conflict").is_none());
+
+    let res = app
+        .client
+        .put(format!("{}/api/settings/airspace/HELICO", app.base_url))
+        .json(&serde_json::json!({ "ceiling_m": 150.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+
+    let body = calculate().await;
+    let violations = body["airspace_conflict"]["violations"].as_array().unwrap();
+    assert!(!violations.is_empty());
+    assert_eq!(violations[0]["restriction"], "HELICO");
+    assert!(violations[0]["altitude_m"].as_f64().unwrap() > 150.0);
+    assert!(body["warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|w| w.as_str().unwrap().starts_with("Airspace conflict")));
+
+    let res = app
+        .client
+        .put(format!("{}/api/settings/airspace/BAD", app.base_url))
+        .json(&serde_json::json!({
+            "ceiling_m": 150.0,
+            "corridor": { "from": [0.0, 0.0], "to": [100.0, 0.0], "width_m": 0.0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn observer_gets_splash_and_sound_timings() {
+    let app = spawn_app().await;
+
+    app.client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&NewMortar {
+            name: "M1",
+            elevation: 100.0,
+            x: 0.0,
+            y: 0.0,
+        })
+        .send()
+        .await
+        .unwrap();
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+
+    let body: Value = app
+        .client
+        .post(format!("{}/api/calculate", app.base_url))
+        .json(&serde_json::json!({
+            "mortar_name": "M1",
+            "target_name": "T1",
+            "observer": { "x": 500.0, "y": 0.0 }
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let timing = &body["observation_timing"];
+    assert_eq!(timing["observer_to_target_m"].as_f64(), Some(300.0));
+    let (splash, sound) = body["selected_solution"]["elevations"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .filter(|(_, e)| e.is_f64())
+        .find_map(|(ring, _)| {
+            Some((
+                timing["splash_s"][ring].as_f64()?,
+                timing["impact_sound_s"][ring].as_f64()?,
+            ))
+        })
+        .unwrap();
+    assert!((sound - splash - 300.0 / 340.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn ammo_of_another_caliber_is_rejected() {
+    let app = spawn_app().await;
+
+    let res = app
+        .client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M81", "elevation": 100.0, "x": 0.0, "y": 0.0, "caliber": "81mm" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    app.client
+        .post(format!("{}/api/targets", app.base_url))
+        .json(&NewTarget {
+            name: "T1",
+            elevation: 50.0,
+            x: 500.0,
+            y: 300.0,
+            target_type: "INFANTERIE",
+            ammo_type: "HE",
+        })
+        .send()
+        .await
+        .unwrap();
+
+    let res = app
+        .client
+        .post(format!("{}/api/calculate", app.base_url))
+        .json(&serde_json::json!({ "mortar_name": "M81", "target_name": "T1" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 422);
+    let body: Value = res.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("cannot be fired"));
+
+    let res = app
+        .client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M2", "x": 0.0, "y": 0.0, "caliber": "155mm" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn uploaded_ballistic_tables_are_used_without_restart() {
+    let app = spawn_app().await;
+    let upload = |csv: &str| {
+        app.client
+            .post(format!("{}/api/ballistics/upload", app.base_url))
+            .json(&serde_json::json!({ "ammo_type": "FLARE", "ring": 0, "csv": csv }))
+            .send()
+    };
+
+    // Elevation rising at 200 m: rejected
+    let res = upload("range_m,elev_mil\n100,1400\n200,1450\n300,1200\n")
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 422);
+
+    let res =
+        upload("range_m,elev_mil,time_flight_s\n100,1400,13.0\n200,1300,12.8\n300,1200,12.5\n")
+            .await
+            .unwrap();
+    assert!(res.status().is_success());
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["report"]["rows"].as_u64(), Some(3));
+    assert_eq!(body["replaced"].as_bool(), Some(false));
+
+    let body: Value = app
+        .client
+        .get(format!("{}/api/ammo-types", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let flare = body["ammo_types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["name"] == "FLARE")
+        .unwrap();
+    assert_eq!(flare["rings"][0].as_u64(), Some(0));
+}
+
+#[tokio::test]
+async fn ballistic_tables_are_uploaded_per_weapon_system() {
+    let app = spawn_app().await;
+    let upload = |weapon: &str| {
+        app.client
+            .post(format!("{}/api/ballistics/upload", app.base_url))
+            .json(&serde_json::json!({
+                "weapon": weapon,
+                "ammo_type": "HE",
+                "ring": 1,
+                "csv": "range_m,elev_mil\n100,1400\n900,1000\n"
+            }))
+            .send()
+    };
+
+    let res = upload("M29").await.unwrap();
+    assert_eq!(res.status(), 400);
+    let res = upload("120mm").await.unwrap();
+    assert!(res.status().is_success());
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["weapon"], "M120");
+    // The M224 table of the same ammo and ring is left in place
+    assert_eq!(body["replaced"].as_bool(), Some(false));
+
+    let body: Value = app
+        .client
+        .get(format!("{}/api/ammo-types", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let he = body["ammo_types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["name"] == "HE")
+        .unwrap();
+    assert_eq!(he["weapons"], serde_json::json!(["M224", "M120"]));
+
+    let res = app
+        .client
+        .post(format!("{}/api/mortars", app.base_url))
+        .json(&serde_json::json!({ "name": "M1", "x": 0.0, "y": 0.0, "weapon": "M120" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let body: Value = app
+        .client
+        .get(format!("{}/api/mortars", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["positions"][0]["weapon"], "M120");
+}
+
+#[tokio::test]
+async fn dry_run_cli_commands_leave_the_state_untouched() {
+    use mortar::server_cli::handle_cli_command;
+
+    let state = AppStateBuilder::new().build();
+    handle_cli_command("dry_run on", &state).await;
+    handle_cli_command("add_mortar M1 100 0 0", &state).await;
+    handle_cli_command("convention S-/E-", &state).await;
+    assert!(state.mortars.read().await.is_empty());
+    assert_eq!(*state.sign_convention.read().await, Default::default());
+
+    handle_cli_command("dry_run off", &state).await;
+    handle_cli_command("add_mortar M1 100 0 0", &state).await;
+    assert_eq!(state.mortars.read().await.len(), 1);
+}
+
+#[tokio::test]
+async fn scheduled_missions_are_planned_with_the_operation_settings() {
+    use mortar::fire::SpotObservation;
+    use mortar::registration::register;
+    use mortar::timeline::ScheduledMission;
+    use mortar::{Declination, Position};
+
+    let mut mortar = MortarPosition::new("M1".into(), 0.0, 0.0, 0.0);
+    mortar.registration = Some(register(
+        &mortar,
+        &Position::new(String::new(), 0.0, 0.0, 600.0),
+        SpotObservation {
+            vertical_m: 100.0,
+            horizontal_m: 0.0,
+        },
+        0,
+    ));
+    let state = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .mortar(mortar)
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .build();
+    *state.declination.write().await = Declination {
+        magnetic_deg: -4.5,
+        grid_convergence_deg: 0.0,
+    };
+    state.increments.write().await.insert((AmmoKind::He, 2), 2);
+    let mission = ScheduledMission {
+        mortar: "M1".into(),
+        target: "T1".into(),
+        start: 0,
+        tubes: 2,
+        rounds_per_tube: 3,
+        round_interval_s: 5.0,
+        ring: None,
+        override_sector: false,
+    };
+
+    let plan = {
+        let mortars = state.mortars.read().await;
+        let targets = state.targets.read().await;
+        let ballistics = state.ballistics.read().await;
+        state
+            .plan_mission(&mission, &mortars, &targets, &ballistics)
+            .await
+            .unwrap()
+    };
+    // 500 m + 100 m de reglage : 600 m de table, soit 1150 mils
+    assert_eq!(plan.rounds[0].elevation_mil, 1150.0);
+    // Declinaison 4,5 deg Ouest : gisement magnetique augmente de 80 mils
+    assert!((plan.magnetic_azimuth_mil - 80.0).abs() < 1e-9);
+    assert_eq!(plan.expenditure.increments, Some(12));
+}
+
+#[tokio::test]
+async fn reload_restores_the_tables_of_the_data_directory() {
+    let app = spawn_app().await;
+    let flare_rings = || async {
+        let body: Value = app
+            .client
+            .get(format!("{}/api/ammo-types", app.base_url))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body["ammo_types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|a| a["name"] == "FLARE")
+            .unwrap()["rings"]
+            .clone()
+    };
+    let files = flare_rings().await;
+
+    app.client
+        .post(format!("{}/api/ballistics/upload", app.base_url))
+        .json(&serde_json::json!({
+            "ammo_type": "FLARE",
+            "ring": 0,
+            "csv": "range_m,elev_mil\n100,1400\n200,1300\n"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_ne!(flare_rings().await, files);
+
+    let res = app
+        .client
+        .post(format!("{}/api/admin/reload", app.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let body: Value = res.json().await.unwrap();
+    assert!(body["tables"].as_u64().unwrap() > 0);
+    assert_eq!(flare_rings().await, files);
+
+    // Without a data directory, the current tables are kept
+    let state = AppStateBuilder::new().build();
+    assert!(state.reload().await.is_err());
+}
+
+#[tokio::test]
+async fn fire_requires_a_clearance_when_enforced() {
+    let server = AppStateBuilder::with_repo_data()
+        .options(mortar::server::AppOptions {
+            require_clearance: true,
+            ..Default::default()
+        })
+        .mortar(MortarPosition::new("M1".into(), 100.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            50.0,
+            500.0,
+            300.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+    let spot = || {
+        client
+            .post(format!("{}/api/fire/spot", server.base_url))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+    };
+
+    assert_eq!(spot().await.unwrap().status(), 403);
+
+    let res = client
+        .post(format!("{}/api/targets/clearance", server.base_url))
+        .json(&serde_json::json!({ "name": "T1", "cleared_by": "CDT Alpha" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    assert!(spot().await.unwrap().status().is_success());
+    let clearance = server.state.targets.read().await[0]
+        .clearance
+        .clone()
+        .unwrap();
+    assert_eq!(clearance.cleared_by, "CDT Alpha");
+
+    client
+        .delete(format!("{}/api/targets/clearance", server.base_url))
+        .json(&serde_json::json!({ "name": "T1" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(spot().await.unwrap().status(), 403);
+}
+
+#[tokio::test]
+async fn moved_mortars_keep_their_history_and_get_re_lay_data() {
+    let server = AppStateBuilder::with_repo_data()
+        .mortar(MortarPosition::new("M1".into(), 100.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            100.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+
+    let res = client
+        .post(format!("{}/api/mortars/move", server.base_url))
+        .json(&serde_json::json!({ "name": "M1", "elevation": 100.0, "x": 1000.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let body: serde_json::Value = res.json().await.unwrap();
+    let shift = body["relay"][0]["azimuth_shift_mil"].as_f64().unwrap();
+    assert!((shift + 800.0).abs() < 1e-6);
+
+    let res = client
+        .get(format!("{}/api/mortars/M1/displacements", server.base_url))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["mortar"]["x"], 1000.0);
+    assert_eq!(body["mortar"]["displacements"][0]["x"], 0.0);
+
+    let res = client
+        .post(format!("{}/api/mortars/move", server.base_url))
+        .json(&serde_json::json!({ "name": "M9", "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn coverage_grid_samples_the_real_tables() {
+    let server = AppStateBuilder::with_repo_data()
+        .mortar(MortarPosition::new("M1".into(), 100.0, 0.0, 0.0))
+        .spawn()
+        .await;
+    let client = Client::new();
+
+    let res = client
+        .get(format!(
+            "{}/api/coverage?mortar=M1&ammo=HE&ring=2&cell_m=100",
+            server.base_url
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let grid: serde_json::Value = res.json().await.unwrap();
+    let (cols, rows) = (
+        grid["cols"].as_u64().unwrap(),
+        grid["rows"].as_u64().unwrap(),
+    );
+    let matrix = grid["elevations_mil"].as_array().unwrap();
+    assert_eq!(matrix.len() as u64, rows);
+    assert_eq!(matrix[0].as_array().unwrap().len() as u64, cols);
+    assert!(grid["reachable_cells"].as_u64().unwrap() > 0);
+    // Grid corner: beyond the maximum range
+    assert!(matrix[0][0].is_null());
+
+    let res = client
+        .get(format!(
+            "{}/api/coverage?mortar=M1&ammo=HE&ring=2&cell_m=1",
+            server.base_url
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn offline_bundle_restores_tables_and_state_on_another_server() {
+    use mortar::bundle::read_bundle_state;
+    use mortar::server::build_app_with_options;
+    use mortar::server_cli::handle_cli_command;
+    use mortar::snapshot::MergeStrategy;
+
+    let source = AppStateBuilder::with_repo_data().build();
+    handle_cli_command("add_mortar M1 100 0 0", &source).await;
+    handle_cli_command("add_target T1 50 500 300", &source).await;
+    let path = std::env::temp_dir().join(format!("mortar_offline_{}.zip", std::process::id()));
+    handle_cli_command(&format!("bundle {}", path.display()), &source).await;
+
+    let (_, state) = build_app_with_options(
+        &path.to_string_lossy(),
+        "src/web",
+        &mortar::server::AppOptions::default(),
+    );
+    let snapshot = read_bundle_state(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    state
+        .restore(&snapshot, MergeStrategy::ReplaceAll, false)
+        .await;
+
+    assert_eq!(
+        state.ballistics.read().await.len(),
+        source.ballistics.read().await.len()
+    );
+    assert_eq!(state.load_report.read().await.failed().count(), 0);
+    assert!(!state.dispersions.read().await.is_empty());
+    assert_eq!(state.mortars.read().await[0].name, "M1");
+    assert_eq!(state.targets.read().await[0].name, "T1");
+}
+
+#[tokio::test]
+async fn degraded_charges_are_corrected_and_reported() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            475.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+    let set = |factor: Value| {
+        client
+            .post(server.url("/api/mortars/charge-efficiency"))
+            .json(&serde_json::json!({ "name": "M1", "charge_efficiency": factor }))
+            .send()
+    };
+    let calculate = || async {
+        let body: Value = client
+            .post(server.url("/api/calculate"))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body
+    };
+
+    assert_eq!(set(Value::from(0.95)).await.unwrap().status(), 200);
+    let solution = calculate().await;
+    // 475 m au sol a 95 % : 500 m de table, soit 1200 mils
+    assert_eq!(solution["charge_efficiency"]["factor"], 0.95);
+    assert_eq!(solution["charge_efficiency"]["table_range_m"], 500.0);
+    assert_eq!(solution["selected_solution"]["elevations"]["2R"], 1200.0);
+    assert!(solution["warnings"][0]
+        .as_str()
+        .unwrap()
+        .contains("Degraded charges"));
+
+    let res = set(Value::from(0.2)).await.unwrap();
+    assert_eq!(res.status(), 400);
+    let res = client
+        .post(server.url("/api/mortars/charge-efficiency"))
+        .json(&serde_json::json!({ "name": "M9", "charge_efficiency": 0.9 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    assert_eq!(set(Value::Null).await.unwrap().status(), 200);
+    let solution = calculate().await;
+    assert!(solution.get("charge_efficiency").is_none());
+    assert_eq!(solution["warnings"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn dispersion_factors_enlarge_reported_dispersions() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .dispersion(AmmoKind::He, 2, 25.0)
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+    let set = |body: Value| {
+        client
+            .post(server.url("/api/mortars/dispersion-factor"))
+            .json(&body)
+            .send()
+    };
+    let calculate = || async {
+        let body: Value = client
+            .post(server.url("/api/calculate"))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body
+    };
+
+    let res = set(serde_json::json!({ "name": "M1", "ring": 2, "factor": 2.0 }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let solution = calculate().await;
+    assert_eq!(solution["selected_solution"]["dispersions"]["2R"], 50.0);
+    assert_eq!(
+        solution["selected_solution"]["dispersion_factors"]["2R"],
+        2.0
+    );
+    assert!(solution["warnings"][0]
+        .as_str()
+        .unwrap()
+        .contains("Worn tube"));
+
+    let res = set(serde_json::json!({ "name": "M1", "factor": 0.5 }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let res = set(serde_json::json!({ "name": "M1", "ring": 9, "factor": 1.5 }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let res = set(serde_json::json!({ "name": "M9", "factor": 1.5 }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    // Sans anneau : tous les anneaux reviennent aux dispersions des tables
+    let res = set(serde_json::json!({ "name": "M1", "factor": null }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let solution = calculate().await;
+    assert_eq!(solution["selected_solution"]["dispersions"]["2R"], 25.0);
+    assert!(solution["selected_solution"]
+        .get("dispersion_factors")
+        .is_none());
+}
+
+#[tokio::test]
+async fn terrain_profile_flags_blocked_rings() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            1,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), Some(10.0)),
+        )
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), Some(20.0)),
+        )
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+    let calculate = |profile: Value| {
+        client
+            .post(server.url("/api/calculate"))
+            .json(&serde_json::json!({
+                "mortar_name": "M1",
+                "target_name": "T1",
+                "terrain_profile": profile
+            }))
+            .send()
+    };
+
+    // Flèche ~123 m en 1R, ~490 m en 2R : seule la 1R touche la crête
+    let res = calculate(serde_json::json!([{ "range_m": 500.0, "height_m": 200.0 }]))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let solution: Value = res.json().await.unwrap();
+    let clearance = &solution["crest_clearance"];
+    assert_eq!(clearance["blocked"].as_array().unwrap().len(), 1);
+    assert_eq!(clearance["blocked"][0]["ring"], "1R");
+    assert_eq!(clearance["blocked"][0]["range_m"], 500.0);
+    assert!(clearance["min_clearance_m"]["2R"].as_f64().unwrap() > 0.0);
+    assert!(solution["warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|w| w.as_str().unwrap().contains("blocks the 1R")));
+
+    let res = calculate(serde_json::json!([{ "range_m": -5.0, "height_m": 0.0 }]))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+
+    let res = calculate(Value::Null).await.unwrap();
+    let solution: Value = res.json().await.unwrap();
+    assert!(solution.get("crest_clearance").is_none());
+}
+
+#[tokio::test]
+async fn worn_tube_bias_is_applied_before_table_lookup() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            405.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+    let set = |name: &str, pct: Value| {
+        client
+            .post(server.url("/api/mortars/bias"))
+            .json(&serde_json::json!({ "name": name, "mv_correction_pct": pct }))
+            .send()
+    };
+    let calculate = || async {
+        let body: Value = client
+            .post(server.url("/api/calculate"))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body
+    };
+
+    assert_eq!(set("M1", Value::from(-10.0)).await.unwrap().status(), 200);
+    let solution = calculate().await;
+    // 405 m au sol avec 81 % de la portee : 500 m de table, soit 1200 mils
+    let mv = &solution["muzzle_velocity"];
+    assert_eq!(mv["mv_correction_pct"], -10.0);
+    assert!((mv["table_range_m"].as_f64().unwrap() - 500.0).abs() < 1e-6);
+    assert!((mv["range_correction_m"].as_f64().unwrap() - 95.0).abs() < 1e-6);
+    let elevation = solution["selected_solution"]["elevations"]["2R"]
+        .as_f64()
+        .unwrap();
+    assert!((elevation - 1200.0).abs() < 1e-6);
+    assert!(solution["warnings"][0]
+        .as_str()
+        .unwrap()
+        .contains("Muzzle velocity"));
+    let mortars: Value = client
+        .get(server.url("/api/mortars"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(mortars["positions"][0]["mv_correction_pct"], -10.0);
+
+    assert_eq!(set("M1", Value::from(-50.0)).await.unwrap().status(), 400);
+    assert_eq!(set("M9", Value::from(-2.0)).await.unwrap().status(), 404);
+
+    assert_eq!(set("M1", Value::Null).await.unwrap().status(), 200);
+    let solution = calculate().await;
+    assert!(solution.get("muzzle_velocity").is_none());
+    assert_eq!(solution["warnings"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn registration_corrects_later_solutions_until_cleared() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+    let calculate = || async {
+        let body: Value = client
+            .post(server.url("/api/calculate"))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body
+    };
+
+    // Coup tire sur un point connu a 600 m, tombe 100 m court (S+)
+    let res = client
+        .post(server.url("/api/mortars/registration"))
+        .json(&serde_json::json!({
+            "name": "M1",
+            "x": 0.0,
+            "y": 600.0,
+            "observation": { "vertical_m": 100.0, "horizontal_m": 0.0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["registration"]["range_correction_m"], 100.0);
+
+    // 500 m + 100 m de reglage : 600 m de table, soit 1150 mils
+    let solution = calculate().await;
+    assert_eq!(solution["registration"]["range_correction_m"], 100.0);
+    assert_eq!(solution["selected_solution"]["elevations"]["2R"], 1150.0);
+    assert!(solution["warnings"][0]
+        .as_str()
+        .unwrap()
+        .contains("Registration"));
+
+    let body: Value = client
+        .get(server.url("/api/mortars/M1/registration"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["registration"]["point_y"], 600.0);
+    let res = client
+        .get(server.url("/api/mortars/M9/registration"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let res = client
+        .delete(server.url("/api/mortars/M1/registration"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let solution = calculate().await;
+    assert!(solution.get("registration").is_none());
+    assert_eq!(solution["selected_solution"]["elevations"]["2R"], 1200.0);
+}
+
+#[tokio::test]
+async fn volley_and_mission_pack_lay_a_registered_mortar_like_calculate() {
+    let server = AppStateBuilder::new()
+        .ballistic_table(
+            AmmoKind::He,
+            2,
+            linear_table((100.0, 1400.0), (1100.0, 900.0), None),
+        )
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            500.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+
+    // Tombe 100 m court et 30 m a gauche d'un point connu a 600 m
+    let res = client
+        .post(server.url("/api/mortars/registration"))
+        .json(&serde_json::json!({
+            "name": "M1",
+            "x": 0.0,
+            "y": 600.0,
+            "observation": { "vertical_m": 100.0, "horizontal_m": -30.0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let solution: Value = client
+        .post(server.url("/api/calculate"))
+        .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let plan: Value = client
+        .post(server.url("/api/fire/volley"))
+        .json(&serde_json::json!({
+            "mortar_name": "M1",
+            "target_name": "T1",
+            "tubes": 2,
+            "rounds_per_tube": 1
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let round = &plan["rounds"][0];
+    assert_eq!(round["ring"], 2);
+    assert_eq!(
+        round["elevation_mil"],
+        solution["selected_solution"]["elevations"]["2R"]
+    );
+    // 1200 mils sans reglage
+    assert!(round["elevation_mil"].as_f64().unwrap() < 1160.0);
+    let azimuth = solution["registration"]["azimuth_mil"].as_f64().unwrap();
+    assert!((round["deflection_mil"].as_f64().unwrap() - azimuth).abs() < 1e-9);
+    assert!(azimuth > 0.0 && azimuth < 100.0);
+
+    // Fiche de tir et plan de feux imprimes portent la meme solution
+    let pack: Value = client
+        .get(server.url("/api/mission-pack?format=json"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    for data in [&pack["fire_plan"][0], &pack["range_cards"][0]["entries"][0]] {
+        assert_eq!(data["elevation_mil"], round["elevation_mil"]);
+        assert_eq!(data["azimuth_mil"], round["deflection_mil"]);
+    }
+}
+
+#[tokio::test]
+async fn reference_points_designate_targets_without_being_targets() {
+    let app = spawn_app().await;
+    let res = app
+        .client
+        .put(format!("{}/api/reference-points/TRP1", app.base_url))
+        .json(&serde_json::json!({ "x": 1000.0, "y": 1000.0, "elevation": 50.0, "description": "pont" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let points: Value = res.json().await.unwrap();
+    assert_eq!(points["TRP1"]["description"], "pont");
+    let res = app
+        .client
+        .put(format!("{}/api/reference-points/bad%20name", app.base_url))
+        .json(&serde_json::json!({ "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+
+    // Observateur face a l'Est : droite 100 m = Sud, allonger 200 m = Est
+    let shift = |from: &str| {
+        app.client
+            .post(format!("{}/api/targets/shift", app.base_url))
+            .json(&serde_json::json!({
+                "name": "T1",
+                "from": from,
+                "shift": { "direction_deg": "1600mil", "right_m": 100.0, "add_m": 200.0, "up_m": -10.0 }
+            }))
+            .send()
+    };
+    assert_eq!(shift("TRP9").await.unwrap().status(), 404);
+    let res = shift("TRP1").await.unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert!((body["target"]["x"].as_f64().unwrap() - 1200.0).abs() < 1e-6);
+    assert!((body["target"]["y"].as_f64().unwrap() - 900.0).abs() < 1e-6);
+    assert_eq!(body["target"]["elevation"], 40.0);
+    assert_eq!(shift("TRP1").await.unwrap().status(), 409);
+
+    // Le point de reference n'est pas une cible
+    let targets: Value = app
+        .client
+        .get(format!("{}/api/targets", app.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(targets["positions"].as_array().unwrap().len(), 1);
+
+    let res = app
+        .client
+        .delete(format!("{}/api/reference-points/TRP1", app.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let points: Value = res.json().await.unwrap();
+    assert!(points.as_object().unwrap().is_empty());
+    let res = app
+        .client
+        .delete(format!("{}/api/reference-points/TRP1", app.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn friendlies_inside_the_danger_radius_are_flagged() {
+    let table = || linear_table((100.0, 1400.0), (1100.0, 900.0), Some(20.0));
+    let server = AppStateBuilder::new()
+        .ballistic_table(AmmoKind::He, 1, table())
+        .ballistic_table(AmmoKind::He, 2, table())
+        .dispersion(AmmoKind::He, 1, 20.0)
+        .dispersion(AmmoKind::He, 2, 40.0)
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+    let calculate = || async {
+        client
+            .post(server.url("/api/calculate"))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap()
+    };
+
+    let res = client
+        .post(server.url("/api/friendlies"))
+        .json(&serde_json::json!({ "name": "SECTION2", "x": 0.0, "y": 1100.0, "elevation": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let res = client
+        .post(server.url("/api/friendlies"))
+        .json(&serde_json::json!({ "name": "SECTION2", "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 409);
+    let list: Value = client
+        .get(server.url("/api/friendlies"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list["positions"][0]["name"], "SECTION2");
+
+    // 100 m du but : dans le rayon de 2R (120 m), pas de 1R (60 m)
+    let solution = calculate().await;
+    let danger = &solution["danger_close"];
+    assert_eq!(danger["multiple"], 3.0);
+    assert_eq!(danger["friendlies"][0]["name"], "SECTION2");
+    assert_eq!(danger["friendlies"][0]["rings"], serde_json::json!(["2R"]));
+    assert_eq!(danger["safer_fire"]["ammo_type"], "HE");
+    assert_eq!(danger["safer_fire"]["ring"], "1R");
+    assert!(solution["warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|w| w.as_str().unwrap().starts_with("Danger close")));
+
+    let res = client
+        .put(server.url("/api/settings/danger-close"))
+        .json(&serde_json::json!({ "multiple": 2.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    assert!(calculate().await.get("danger_close").is_none());
+    let res = client
+        .put(server.url("/api/settings/danger-close"))
+        .json(&serde_json::json!({ "multiple": 0.5 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+
+    let state: Value = client
+        .get(server.url("/api/state"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(state["friendlies"][0]["name"], "SECTION2");
+
+    let res = client
+        .delete(server.url("/api/friendlies"))
+        .json(&serde_json::json!({ "name": "SECTION2" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let res = client
+        .delete(server.url("/api/friendlies"))
+        .json(&serde_json::json!({ "name": "SECTION2" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn every_firing_path_flags_friendlies_inside_the_danger_radius() {
+    let table = || linear_table((100.0, 1400.0), (1100.0, 900.0), Some(20.0));
+    let server = AppStateBuilder::new()
+        .ballistic_table(AmmoKind::He, 1, table())
+        .ballistic_table(AmmoKind::He, 2, table())
+        .dispersion(AmmoKind::He, 1, 20.0)
+        .dispersion(AmmoKind::He, 2, 40.0)
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let client = Client::new();
+    let post = |path: &str, body: Value| {
+        let request = client.post(server.url(path)).json(&body);
+        async move {
+            let res = request.send().await.unwrap();
+            assert_eq!(res.status(), 200);
+            res.json::<Value>().await.unwrap()
+        }
+    };
+    post(
+        "/api/friendlies",
+        serde_json::json!({ "name": "SECTION2", "x": 0.0, "y": 1100.0 }),
+    )
+    .await;
+    let flagged = |danger: &Value| danger["friendlies"][0]["name"] == "SECTION2";
+
+    let simulated = post(
+        "/api/simulate",
+        serde_json::json!({ "mortar_name": "M1", "target_name": "T1",
+                            "target_radius_m": 50.0, "count": 100, "seed": 1 }),
+    )
+    .await;
+    assert!(flagged(&simulated["danger_close"]));
+    let volley = post(
+        "/api/fire/volley",
+        serde_json::json!({ "mortar_name": "M1", "target_name": "T1", "rounds_per_tube": 2 }),
+    )
+    .await;
+    assert!(flagged(&volley["danger_close"]));
+    let preview = post(
+        "/api/targets/correct?preview=true",
+        serde_json::json!({ "target_name": "T1", "mortar_name": "M1",
+                            "vertical_m": 10.0, "horizontal_m": 0.0 }),
+    )
+    .await;
+    assert!(flagged(&preview["solution"]["danger_close"]));
+    let spot = post(
+        "/api/fire/spot",
+        serde_json::json!({ "mortar_name": "M1", "target_name": "T1",
+                            "observation": { "vertical_m": 0.0, "horizontal_m": 0.0 } }),
+    )
+    .await;
+    assert!(flagged(&spot["solution"]["danger_close"]));
+}
+
+#[tokio::test]
+async fn moving_a_friendly_is_checked_by_later_solutions() {
+    let table = || linear_table((100.0, 1400.0), (1100.0, 900.0), Some(20.0));
+    let server = AppStateBuilder::new()
+        .ballistic_table(AmmoKind::He, 1, table())
+        .dispersion(AmmoKind::He, 1, 20.0)
+        .mortar(MortarPosition::new("M1".into(), 0.0, 0.0, 0.0))
+        .target(TargetPosition::new(
+            "T1".into(),
+            0.0,
+            0.0,
+            1000.0,
+            TargetType::Infanterie,
+            AmmoKind::He,
+        ))
+        .spawn()
+        .await;
+    let mut events = server.state.events.subscribe();
+    let client = Client::new();
+    let calculate = || async {
+        client
+            .post(server.url("/api/calculate"))
+            .json(&serde_json::json!({ "mortar_name": "M1", "target_name": "T1" }))
+            .send()
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap()
+    };
+
+    client
+        .post(server.url("/api/friendlies"))
+        .json(&serde_json::json!({ "name": "SECTION2", "x": 0.0, "y": 1500.0, "elevation": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert!(calculate().await.get("danger_close").is_none());
+
+    // 40 m du but : dans le rayon de 1R (60 m)
+    let res = client
+        .post(server.url("/api/friendlies/move"))
+        .json(&serde_json::json!({ "name": "SECTION2", "x": 0.0, "y": 1040.0, "elevation": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let moved: Value = res.json().await.unwrap();
+    assert_eq!(moved["y"], 1040.0);
+    assert_eq!(
+        calculate().await["danger_close"]["friendlies"][0]["name"],
+        "SECTION2"
+    );
+
+    let res = client
+        .post(server.url("/api/friendlies/move"))
+        .json(&serde_json::json!({ "name": "SECTION9", "x": 0.0, "y": 0.0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let added = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    assert_eq!(added["event"], "friendly_added");
+    let mut updated = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    while updated["event"] == "solution_calculated" {
+        updated = serde_json::to_value(events.recv().await.unwrap()).unwrap();
+    }
+    assert_eq!(updated["event"], "friendly_updated");
+    assert_eq!(updated["friendly"]["y"], 1040.0);
+}